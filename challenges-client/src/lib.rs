@@ -0,0 +1,304 @@
+//! Typed async client for the challenges microservice.
+//!
+//! Requests and responses use the types of the [`schemas`] crate, so the
+//! client always matches the api of the service built from the same
+//! revision.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), challenges_client::ClientError> {
+//! use challenges_client::{ChallengesClient, SubtaskFilter};
+//!
+//! let client = ChallengesClient::new("http://127.0.0.1:8005/v1/".parse().unwrap())
+//!     .with_token("<access token>");
+//! let subtasks = client.list_subtasks(&SubtaskFilter::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use entity::sea_orm_active_enums::ChallengesSubtaskType;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use schemas::challenges::{
+    coding_challenges::{CreateSubmissionRequest, Submission, SubmissionContent},
+    exams::ExamAnswer,
+    multiple_choice::{SolveMCQFeedback, SolveMCQRequest},
+    question::{SolveQuestionFeedback, SolveQuestionRequest},
+    subtasks::Subtask,
+};
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ChallengesClient {
+    client: Client,
+    base_url: Url,
+    token: Option<String>,
+}
+
+/// The filters of [`ChallengesClient::list_subtasks`].
+#[derive(Debug, Clone, Default)]
+pub struct SubtaskFilter {
+    pub task_id: Option<Uuid>,
+    pub subtask_type: Option<ChallengesSubtaskType>,
+    /// Whether to search for subtasks the user has attempted to solve.
+    pub attempted: Option<bool>,
+    pub solved: Option<bool>,
+    pub rated: Option<bool>,
+    pub enabled: Option<bool>,
+    pub retired: Option<bool>,
+    pub creator: Option<Uuid>,
+    /// Whether to search for subtasks under a permissive license.
+    pub permissive: Option<bool>,
+}
+
+/// The result of an attempt to solve a subtask.
+#[derive(Debug, Clone)]
+pub enum SolveResult<T> {
+    /// The answer has been evaluated.
+    Feedback(T),
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    ExamAnswer(ExamAnswer),
+}
+
+impl ChallengesClient {
+    /// Create a client for the service at `base_url` (including the version
+    /// prefix, e.g. `https://api.example.com/challenges/v1/`).
+    pub fn new(base_url: Url) -> Self {
+        Self::with_client(Client::new(), base_url)
+    }
+
+    /// Create a client that uses an existing [`reqwest::Client`].
+    pub fn with_client(client: Client, base_url: Url) -> Self {
+        Self {
+            client,
+            base_url,
+            token: None,
+        }
+    }
+
+    /// Authenticate all requests with the given access token.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// List subtasks.
+    pub async fn list_subtasks(&self, filter: &SubtaskFilter) -> ClientResult<Vec<Subtask>> {
+        let query = [
+            ("task_id", filter.task_id.map(|x| x.to_string())),
+            (
+                "subtask_type",
+                filter
+                    .subtask_type
+                    .and_then(|x| x.to_json()?.as_str().map(Into::into)),
+            ),
+            ("attempted", filter.attempted.map(|x| x.to_string())),
+            ("solved", filter.solved.map(|x| x.to_string())),
+            ("rated", filter.rated.map(|x| x.to_string())),
+            ("enabled", filter.enabled.map(|x| x.to_string())),
+            ("retired", filter.retired.map(|x| x.to_string())),
+            ("creator", filter.creator.map(|x| x.to_string())),
+            ("permissive", filter.permissive.map(|x| x.to_string())),
+        ];
+        let query = query
+            .into_iter()
+            .filter_map(|(k, v)| Some((k, v?)))
+            .collect::<Vec<_>>();
+        let response = self
+            .request(Method::GET, "subtasks")
+            .query(&query)
+            .send()
+            .await?;
+        parse(expect(response, StatusCode::OK).await?).await
+    }
+
+    /// Attempt to solve a question.
+    pub async fn solve_question(
+        &self,
+        task_id: Uuid,
+        subtask_id: Uuid,
+        data: &SolveQuestionRequest,
+    ) -> ClientResult<SolveResult<SolveQuestionFeedback>> {
+        self.solve(
+            &format!("tasks/{task_id}/questions/{subtask_id}/attempts"),
+            data,
+        )
+        .await
+    }
+
+    /// Attempt to solve a multiple choice question.
+    pub async fn solve_multiple_choice(
+        &self,
+        task_id: Uuid,
+        subtask_id: Uuid,
+        data: &SolveMCQRequest,
+    ) -> ClientResult<SolveResult<SolveMCQFeedback>> {
+        self.solve(
+            &format!("tasks/{task_id}/multiple_choice/{subtask_id}/attempts"),
+            data,
+        )
+        .await
+    }
+
+    /// Create a submission for a coding challenge. The submission is judged
+    /// asynchronously, so the returned submission usually has no result yet.
+    pub async fn create_submission(
+        &self,
+        task_id: Uuid,
+        subtask_id: Uuid,
+        data: &CreateSubmissionRequest,
+    ) -> ClientResult<Submission> {
+        let response = self
+            .request(
+                Method::POST,
+                &format!("tasks/{task_id}/coding_challenges/{subtask_id}/submissions"),
+            )
+            .json(&data.to_json())
+            .send()
+            .await?;
+        parse(expect(response, StatusCode::ACCEPTED).await?).await
+    }
+
+    /// List the submissions of the user for a coding challenge.
+    pub async fn list_submissions(
+        &self,
+        task_id: Uuid,
+        subtask_id: Uuid,
+    ) -> ClientResult<Vec<Submission>> {
+        let response = self
+            .request(
+                Method::GET,
+                &format!("tasks/{task_id}/coding_challenges/{subtask_id}/submissions"),
+            )
+            .send()
+            .await?;
+        parse(expect(response, StatusCode::OK).await?).await
+    }
+
+    /// Return a submission including its code.
+    pub async fn get_submission(
+        &self,
+        task_id: Uuid,
+        subtask_id: Uuid,
+        submission_id: Uuid,
+    ) -> ClientResult<SubmissionContent> {
+        let response = self
+            .request(
+                Method::GET,
+                &format!(
+                    "tasks/{task_id}/coding_challenges/{subtask_id}/submissions/{submission_id}"
+                ),
+            )
+            .send()
+            .await?;
+        parse(expect(response, StatusCode::OK).await?).await
+    }
+
+    async fn solve<T: ParseFromJSON>(
+        &self,
+        path: &str,
+        data: &impl ToJSON,
+    ) -> ClientResult<SolveResult<T>> {
+        let response = self
+            .request(Method::POST, path)
+            .json(&data.to_json())
+            .send()
+            .await?;
+        if response.status() == StatusCode::ACCEPTED {
+            return Ok(SolveResult::ExamAnswer(parse(response).await?));
+        }
+        Ok(SolveResult::Feedback(
+            parse(expect(response, StatusCode::CREATED).await?).await?,
+        ))
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, endpoint_url(&self.base_url, path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("reqwest error: {0}")]
+    ReqwestError(#[from] reqwest::Error),
+    #[error("api error ({status}): {error}")]
+    ApiError {
+        status: StatusCode,
+        /// The error code returned by the service (e.g. `subtask_not_found`).
+        error: String,
+        /// Additional information about the error, if any.
+        details: Option<Value>,
+    },
+    #[error("unexpected response status code: {0}")]
+    UnexpectedStatusCode(StatusCode),
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// Return the response if it has the expected status code or the error
+/// returned by the service otherwise.
+async fn expect(response: Response, status: StatusCode) -> ClientResult<Response> {
+    if response.status() == status {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.json::<Value>().await.ok();
+    match body
+        .as_ref()
+        .and_then(|x| Some((x.get("error")?.as_str()?, x.get("details"))))
+    {
+        Some((error, details)) => Err(ClientError::ApiError {
+            status,
+            error: error.into(),
+            details: details.cloned(),
+        }),
+        None => Err(ClientError::UnexpectedStatusCode(status)),
+    }
+}
+
+async fn parse<T: ParseFromJSON>(response: Response) -> ClientResult<T> {
+    let value = response.json::<Value>().await?;
+    T::parse_from_json(Some(value)).map_err(|err| ClientError::InvalidResponse(err.into_message()))
+}
+
+/// Join a relative path to the base url, keeping the path of the base url even
+/// if it does not end with a slash.
+fn endpoint_url(base_url: &Url, path: &str) -> Url {
+    let mut url = base_url.clone();
+    url.set_path(&format!(
+        "{}/{}",
+        base_url.path().trim_end_matches('/'),
+        path.trim_start_matches('/')
+    ));
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_urls() {
+        for base_url in ["http://localhost/v1", "http://localhost/v1/"] {
+            assert_eq!(
+                endpoint_url(&base_url.parse().unwrap(), "subtasks").as_str(),
+                "http://localhost/v1/subtasks"
+            );
+        }
+        assert_eq!(
+            endpoint_url(&"http://localhost".parse().unwrap(), "/subtasks").as_str(),
+            "http://localhost/subtasks"
+        );
+    }
+}
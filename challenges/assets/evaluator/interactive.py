@@ -0,0 +1,87 @@
+from lib import *
+
+
+@dataclass
+class Input:
+    n: int
+
+    @classmethod
+    def from_seed(cls, seed) -> Self:
+        """
+        Generate a challenge input from a seed. This seed is usually a string or an integer, but
+        could be any value. This function should be deterministic, i.e. if called several times with
+        the same seed, it should always generate the same challenge input.
+        """
+
+        random.seed(seed)
+        n = random.randint(1, 1000)
+        return cls(n)
+
+    def serialize(self) -> str:
+        """
+        Convert an Input object into the first message that is fed into the user's solution program
+        via stdin, before any rounds of the interaction take place.
+        """
+
+        return "1000\n"
+
+    def data(self):
+        """
+        Return additional information that is fed into the `interact` function. This information is
+        only available to this evaluator and not shared with the user.
+        """
+
+        return self.n
+
+
+def interact(data, responses: list[str], log) -> str | bool:
+    """
+    Determine the next step of the interaction. `responses` contains the lines the user's solution
+    program has printed so far, one line per previous round. Return a string to send another message
+    to the program (it is appended to its stdin and the program's next line of output is added to
+    `responses`), or return a bool to end the interaction with the `OK` (True) or `WRONG_ANSWER`
+    (False) verdict. Raising an error here will result in the `INVALID_OUTPUT_FORMAT` verdict.
+
+    This function is called again for every round and must therefore only depend on `data` and
+    `responses`. The user's program is run again for every round with all messages so far on stdin,
+    so it must print exactly one line in response to each message and behave deterministically.
+    """
+
+    # the program has to guess the number in at most 10 tries
+    if len(responses) > 10:
+        log("too many guesses")
+        return False
+
+    if not responses:
+        return ""  # let the program make its first guess after reading the upper bound
+
+    guess = responses[-1].strip()
+    if not guess.isnumeric():
+        log("guess is not a positive integer")
+        raise ValueError
+
+    guess = int(guess)
+    if guess < data:
+        return "higher\n"
+    if guess > data:
+        return "lower\n"
+    return True
+
+
+# Provide example inputs that are shown to the user. See `template.py` for details.
+example(lambda: Input(500))
+example(lambda: Input.from_seed(0))
+
+
+def prepare(environment: str, code: str, data, log) -> str:
+    """
+    This function is called after a challenge input has been generated and before the user's solution
+    program is run. See `template.py` for details.
+    """
+
+    return code
+
+
+# call the library's main function which implements the interactive evaluator api using the
+# provided Input class and interact function
+main_interactive(Input, interact, prepare)
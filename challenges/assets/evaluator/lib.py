@@ -1,4 +1,5 @@
 import json
+import os
 import random
 import sys
 from dataclasses import dataclass
@@ -12,42 +13,81 @@ def example(f):
     return f
 
 
+def _prepare(prepare):
+    with open(0) as f:
+        obj = json.load(f)
+    env = obj["environment"]
+    code = obj["code"]
+    data = obj["data"]
+    if prepare is None:
+        print(json.dumps({"code": code, "reason": ""}))
+        sys.exit()
+    logs = []
+    try:
+        out = prepare(env, code, data, logs.append)
+    except:
+        print(json.dumps({"code": None, "reason": "\n".join(logs)}))
+    else:
+        print(json.dumps({"code": out, "reason": "\n".join(logs)}))
+
+
+def _generate(Input):
+    seed = sys.argv[2]
+    if seed[:4] == "_ex_":
+        inp = _examples[int(seed[4:])]()
+    else:
+        inp = Input.from_seed(seed)
+    out = {"input": inp.serialize(), "data": inp.data()}
+    if os.environ.get("PROTOCOL_VERSION") == "2" and hasattr(inp, "test"):
+        out["test"] = inp.test()
+    print(json.dumps(out))
+
+
 def main(Input, Output, prepare=None):
-    if sys.argv[1] == "examples":
+    if sys.argv[1] == "protocol":
+        print(json.dumps(1))
+    elif sys.argv[1] == "examples":
         print(json.dumps([f"_ex_{x}" for x in range(len(_examples))]))
     elif sys.argv[1] == "generate":
-        seed = sys.argv[2]
-        if seed[:4] == "_ex_":
-            inp = _examples[int(seed[4:])]()
-        else:
-            inp = Input.from_seed(seed)
-        print(json.dumps({"input": inp.serialize(), "data": inp.data()}))
+        _generate(Input)
     elif sys.argv[1] == "prepare":
+        _prepare(prepare)
+    elif sys.argv[1] == "check":
         with open(0) as f:
             obj = json.load(f)
-        env = obj["environment"]
-        code = obj["code"]
+        out = obj["output"]
         data = obj["data"]
-        if prepare is None:
-            print(json.dumps({"code": code, "reason": ""}))
-            sys.exit()
         logs = []
         try:
-            out = prepare(env, code, data, logs.append)
+            out = Output.deserialize(out, logs.append)
         except:
-            print(json.dumps({"code": None, "reason": "\n".join(logs)}))
+            print(json.dumps({"verdict": "INVALID_OUTPUT_FORMAT", "reason": "\n".join(logs)}))
         else:
-            print(json.dumps({"code": out, "reason": "\n".join(logs)}))
-    elif sys.argv[1] == "check":
+            ok = out.check(data, logs.append)
+            print(json.dumps({"verdict": "OK" if ok else "WRONG_ANSWER", "reason": "\n".join(logs)}))
+
+
+def main_interactive(Input, interact, prepare=None):
+    if sys.argv[1] == "protocol":
+        print(json.dumps(2))
+    elif sys.argv[1] == "examples":
+        print(json.dumps([f"_ex_{x}" for x in range(len(_examples))]))
+    elif sys.argv[1] == "generate":
+        _generate(Input)
+    elif sys.argv[1] == "prepare":
+        _prepare(prepare)
+    elif sys.argv[1] == "interact":
         with open(0) as f:
             obj = json.load(f)
-        out = obj["output"]
         data = obj["data"]
+        responses = obj["responses"]
         logs = []
         try:
-            out = Output.deserialize(out, logs.append)
+            out = interact(data, responses, logs.append)
         except:
             print(json.dumps({"verdict": "INVALID_OUTPUT_FORMAT", "reason": "\n".join(logs)}))
         else:
-            ok = out.check(data, logs.append)
-            print(json.dumps({"verdict": "OK" if ok else "WRONG_ANSWER", "reason": "\n".join(logs)}))
+            if isinstance(out, str):
+                print(json.dumps({"message": out}))
+            else:
+                print(json.dumps({"verdict": "OK" if out else "WRONG_ANSWER", "reason": "\n".join(logs)}))
@@ -39,6 +39,20 @@ class Input:
         # in this case we just calculate and return the expected answer
         return self.n * (self.n + 1) // 2
 
+    # def test(self):
+    #     """
+    #     Only used by evaluators implementing protocol version 2. Return the metadata of this test:
+    #     an optional `name`, a `weight` relative to the other tests and the `comparison` used to
+    #     check the output of the user's solution program (defaults to the comparison configured
+    #     for the challenge). With the `"evaluator"` comparison the output is checked by
+    #     `Output.check` and with `"checker"` by the checker program of the challenge. Otherwise it
+    #     is compared with the `expected` output (defaults to the output of the sample solution)
+    #     using one of `"exact"`, `"tokens"`, `"trimmed_lines"`, `"unordered_lines"` or `"float"`
+    #     (numbers may differ by at most `epsilon`).
+    #     """
+    #
+    #     return {"name": f"n = {self.n}", "comparison": "exact", "expected": str(self.data())}
+
 
 @dataclass
 class Output:
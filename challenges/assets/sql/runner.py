@@ -0,0 +1,33 @@
+import json
+import sqlite3
+import sys
+
+
+def main():
+    data = json.load(sys.stdin)
+    db = sqlite3.connect(":memory:")
+    try:
+        db.executescript(data["schema"])
+        db.executescript(data["seed"])
+    except sqlite3.Error as e:
+        print(json.dumps({"setup_error": str(e)}))
+        return
+
+    try:
+        cursor = db.execute(data["query"])
+        columns = [column[0] for column in cursor.description or []]
+        rows = cursor.fetchmany(data["max_rows"] + 1)
+    except sqlite3.Error as e:
+        print(json.dumps({"error": str(e)}))
+        return
+
+    if len(rows) > data["max_rows"]:
+        print(json.dumps({"error": f"the query returned more than {data['max_rows']} rows"}))
+        return
+
+    rows = [[None if value is None else str(value) for value in row] for row in rows]
+    print(json.dumps({"ok": {"columns": columns, "rows": rows}}))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,40 @@
+#![forbid(unsafe_code)]
+#![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
+
+//! Judges the submissions in the judge queue of the challenges microservice.
+
+use std::{sync::Arc, time::Duration};
+
+use challenges::{
+    services::{judge_limiter::JudgeLimiter, judge_worker::JudgeWorker},
+    setup, Setup,
+};
+use fnct::format::JsonFormatter;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let Setup {
+        config: shared_config,
+        state,
+        redis,
+        sandkasten,
+        sentry_guard: _sentry_guard,
+    } = setup().await?;
+    let config = shared_config.load();
+
+    let coding_challenges = &config.challenges.coding_challenges;
+    let worker = Arc::new(JudgeWorker {
+        judge_cache: state.cache.with_formatter(JsonFormatter),
+        judge_limiter: JudgeLimiter::new(
+            redis,
+            coding_challenges.max_concurrency,
+            Duration::from_secs(coding_challenges.judge_lease),
+        ),
+        state,
+        config: shared_config.clone(),
+        sandkasten,
+    });
+    worker.run(coding_challenges.max_concurrency).await;
+
+    Ok(())
+}
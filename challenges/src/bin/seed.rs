@@ -0,0 +1,45 @@
+#![forbid(unsafe_code)]
+#![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
+
+//! Populates the database of the challenges microservice with demo data for
+//! local development. Pass `--force` to seed a database that already contains
+//! tasks.
+
+use std::time::Duration;
+
+use anyhow::bail;
+use challenges::services::seed::{is_seeded, seed, SEED_ADMIN, SEED_BANNED_USER, SEED_USER};
+use lib::config::load_database_config;
+use sea_orm::{ConnectOptions, Database, TransactionTrait};
+use tracing::info;
+use tracing_subscriber::{prelude::*, EnvFilter};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let config = load_database_config()?;
+    let force = std::env::args().skip(1).any(|arg| arg == "--force");
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .init();
+
+    info!("Connecting to database");
+    let mut db_options = ConnectOptions::new(config.url.to_string());
+    db_options.connect_timeout(Duration::from_secs(config.connect_timeout));
+    let db = Database::connect(db_options).await?;
+
+    let txn = db.begin().await?;
+    if is_seeded(&txn).await? && !force {
+        bail!("The database already contains tasks. Use --force to seed it anyway.");
+    }
+    let summary = seed(&txn).await?;
+    txn.commit().await?;
+
+    info!(
+        "Created {} tasks, {} subtasks, {} attempts and {} bans",
+        summary.tasks, summary.subtasks, summary.attempts, summary.bans
+    );
+    info!("Admin: {SEED_ADMIN}, user: {SEED_USER}, banned user: {SEED_BANNED_USER}");
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use entity::{challenges_organization_members, challenges_subtasks};
+use lib::{
+    auth::{AdminAuth, Organization, User},
+    config::SharedConfig,
+    slow_log::slow_log_counters,
+    SharedState,
+};
+use migration::Migrator;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use schemas::challenges::admin::{
+    Metrics, MigrationStatus, SandboxUsageStats, SubtaskAccessExplanation,
+};
+use sea_orm::EntityTrait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{access::explain_subtask_access, sandbox_usage::get_sandbox_usage_stats};
+
+pub struct Admin {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Admin")]
+impl Admin {
+    /// Return the effective configuration of the microservice.
+    ///
+    /// Secrets (e.g. the jwt secret and passwords in urls) are redacted.
+    #[oai(path = "/admin/config", method = "get")]
+    async fn get_config(&self, _auth: AdminAuth) -> GetConfig::Response<AdminAuth> {
+        GetConfig::ok(self.config.load().redacted())
+    }
+
+    /// Return metrics of this instance of the microservice.
+    #[oai(path = "/admin/metrics", method = "get")]
+    async fn get_metrics(&self, _auth: AdminAuth) -> GetMetrics::Response<AdminAuth> {
+        let counters = slow_log_counters();
+        GetMetrics::ok(Metrics {
+            slow_requests: counters.requests,
+            slow_queries: counters.queries,
+        })
+    }
+
+    /// Return the database migrations and whether they have been applied.
+    ///
+    /// Migrations are listed in the order they are applied, followed by
+    /// migrations that have been applied by a newer version of the
+    /// microservice.
+    #[oai(path = "/admin/migrations", method = "get")]
+    async fn list_migrations(
+        &self,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListMigrations::Response<AdminAuth> {
+        ListMigrations::ok(
+            Migrator::state(&***db)
+                .await?
+                .into_iter()
+                .map(|migration| MigrationStatus {
+                    name: migration.name,
+                    applied: migration.applied_at.is_some(),
+                    applied_at: migration
+                        .applied_at
+                        .and_then(|x| DateTime::from_timestamp(x, 0)),
+                    unknown: !migration.known,
+                })
+                .collect(),
+        )
+    }
+
+    /// Load the configuration again from file and environment.
+    ///
+    /// Only values that are read while handling requests (e.g. timeouts,
+    /// limits and rewards) are affected. Changes to connections (e.g. database
+    /// or redis urls) require a restart. The configuration can also be
+    /// reloaded by sending `SIGHUP` to the process.
+    #[oai(path = "/admin/config/reload", method = "post")]
+    async fn reload_config(&self, _auth: AdminAuth) -> ReloadConfig::Response<AdminAuth> {
+        match self.config.reload() {
+            Ok(config) => ReloadConfig::ok(config.redacted()),
+            Err(err) => ReloadConfig::invalid_config(err.to_string()),
+        }
+    }
+
+    /// Return the cpu time coding challenges have used in the sandbox in a
+    /// month, per challenge and per creator.
+    #[oai(path = "/admin/sandbox_usage", method = "get")]
+    async fn get_sandbox_usage(
+        &self,
+        /// Any day of the month. Defaults to the current month.
+        month: Query<Option<NaiveDate>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetSandboxUsage::Response<AdminAuth> {
+        let month = month.0.unwrap_or_else(|| Utc::now().date_naive());
+        GetSandboxUsage::ok(get_sandbox_usage_stats(&db, month).await?)
+    }
+
+    /// Explain whether a user can access a subtask.
+    ///
+    /// Shows all factors that influence the decision (e.g. whether the
+    /// subtask is enabled, whether access has been granted to the user and
+    /// whether the task belongs to the organization the user is acting in),
+    /// so it can be checked why a user cannot see a subtask without acting as
+    /// this user. `organization_id` is the organization the user selects via
+    /// the `X-Organization-Id` header.
+    #[oai(
+        path = "/admin/users/:user_id/subtasks/:subtask_id/access",
+        method = "get"
+    )]
+    async fn explain_access(
+        &self,
+        user_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        organization_id: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ExplainAccess::Response<AdminAuth> {
+        let Some(user) = self.state.services.auth.get_user_by_id(user_id.0).await? else {
+            return ExplainAccess::user_not_found();
+        };
+        let organization = match organization_id.0 {
+            Some(id) => {
+                let role = challenges_organization_members::Entity::find_by_id((id, user.id))
+                    .one(&***db)
+                    .await?
+                    .map(|member| member.role);
+                if role.is_none() && !user.admin {
+                    return ExplainAccess::not_a_member();
+                }
+                Some(Organization { id, role })
+            }
+            None => None,
+        };
+        let user = User {
+            id: user.id,
+            email_verified: true,
+            admin: user.admin,
+            organization,
+        };
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(subtask_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ExplainAccess::subtask_not_found();
+        };
+        ExplainAccess::ok(explain_subtask_access(&db, &user, &subtask).await?)
+    }
+}
+
+response!(GetConfig = {
+    Ok(200) => Value,
+});
+
+response!(GetMetrics = {
+    Ok(200) => Metrics,
+});
+
+response!(ListMigrations = {
+    Ok(200) => Vec<MigrationStatus>,
+});
+
+response!(ReloadConfig = {
+    Ok(200) => Value,
+    /// The configuration could not be loaded. The previous configuration is still active. `details` contains the error message.
+    InvalidConfig(500, error) => String,
+});
+
+response!(GetSandboxUsage = {
+    Ok(200) => SandboxUsageStats,
+});
+
+response!(ExplainAccess = {
+    Ok(200) => SubtaskAccessExplanation,
+    /// The user does not exist.
+    UserNotFound(404, error),
+    /// The subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not a member of the organization.
+    NotAMember(403, error),
+});
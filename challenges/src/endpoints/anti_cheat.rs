@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::{
+    challenges_cheat_flags,
+    sea_orm_active_enums::{ChallengesCheatFlagKind, ChallengesCheatFlagStatus},
+};
+use lib::{auth::AdminAuth, config::SharedConfig, SharedState};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::anti_cheat::{CheatFlag, CheatFlagVerdict, ReviewCheatFlagRequest};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    anti_cheat::has_reward_hold,
+    held_rewards::{flagged_rewards, release_held_rewards, void_held_rewards},
+};
+
+pub struct AntiCheat {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::AntiCheat")]
+impl AntiCheat {
+    /// List suspicious patterns found in the attempts of users.
+    #[oai(path = "/anti_cheat/flags", method = "get")]
+    async fn list_flags(
+        &self,
+        /// Filter by review status.
+        status: Query<Option<ChallengesCheatFlagStatus>>,
+        /// Filter by kind of pattern.
+        kind: Query<Option<ChallengesCheatFlagKind>>,
+        /// Filter by user.
+        user_id: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListFlags::Response<AdminAuth> {
+        let mut query = challenges_cheat_flags::Entity::find();
+        if let Some(status) = status.0 {
+            query = query.filter(challenges_cheat_flags::Column::Status.eq(status));
+        }
+        if let Some(kind) = kind.0 {
+            query = query.filter(challenges_cheat_flags::Column::Kind.eq(kind));
+        }
+        if let Some(user_id) = user_id.0 {
+            query = query.filter(challenges_cheat_flags::Column::UserId.eq(user_id));
+        }
+        ListFlags::ok(
+            query
+                .order_by_desc(challenges_cheat_flags::Column::Timestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Get a flag by id.
+    #[oai(path = "/anti_cheat/flags/:flag_id", method = "get")]
+    async fn get_flag(
+        &self,
+        flag_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetFlag::Response<AdminAuth> {
+        match challenges_cheat_flags::Entity::find_by_id(flag_id.0)
+            .one(&***db)
+            .await?
+        {
+            Some(flag) => GetFlag::ok(flag.into()),
+            None => GetFlag::flag_not_found(),
+        }
+    }
+
+    /// Review a flag.
+    ///
+    /// If the flag has held the rewards of the user, confirming it voids all
+    /// held rewards. After dismissing it, the held rewards are sent unless
+    /// other flags of the user still have to be reviewed.
+    #[oai(path = "/anti_cheat/flags/:flag_id/review", method = "post")]
+    async fn review_flag(
+        &self,
+        flag_id: Path<Uuid>,
+        data: Json<ReviewCheatFlagRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> ReviewFlag::Response<AdminAuth> {
+        let Some(flag) = challenges_cheat_flags::Entity::find_by_id(flag_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ReviewFlag::flag_not_found();
+        };
+        if flag.status != ChallengesCheatFlagStatus::Open {
+            return ReviewFlag::already_reviewed();
+        }
+
+        let flag = challenges_cheat_flags::ActiveModel {
+            id: Unchanged(flag.id),
+            status: Set(data.0.verdict.into()),
+            reviewer: Set(Some(auth.0.id)),
+            review_timestamp: Set(Some(Utc::now().naive_utc())),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+
+        if flag.hold_rewards {
+            let held = flagged_rewards(flag.user_id);
+            match data.0.verdict {
+                CheatFlagVerdict::Confirmed => {
+                    void_held_rewards(&db, held, auth.0.id).await?;
+                }
+                CheatFlagVerdict::Dismissed => {
+                    if !has_reward_hold(&db, flag.user_id).await? {
+                        release_held_rewards(
+                            &self.state.services,
+                            &db,
+                            &self.config.load().challenges.rewards,
+                            held,
+                            auth.0.id,
+                        )
+                        .await?;
+                    }
+                }
+            }
+        }
+
+        ReviewFlag::ok(flag.into())
+    }
+}
+
+response!(ListFlags = {
+    Ok(200) => Vec<CheatFlag>,
+});
+
+response!(GetFlag = {
+    Ok(200) => CheatFlag,
+    /// Flag does not exist.
+    FlagNotFound(404, error),
+});
+
+response!(ReviewFlag = {
+    Ok(200) => CheatFlag,
+    /// Flag does not exist.
+    FlagNotFound(404, error),
+    /// The flag has already been reviewed.
+    AlreadyReviewed(409, error),
+});
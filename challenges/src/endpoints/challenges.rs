@@ -31,8 +31,10 @@ use sea_orm::{
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::subtasks::{
-    get_user_subtasks, stat_subtasks, stat_subtasks_prepare, QuerySubtasksFilter,
+use crate::services::{
+    organizations::{can_see_task, task_scope},
+    subtasks::{get_user_subtasks, stat_subtasks, stat_subtasks_prepare, QuerySubtasksFilter},
+    time_tracking::get_user_sessions,
 };
 
 pub struct Challenges {
@@ -106,10 +108,11 @@ impl Challenges {
         };
 
         let user_subtasks = get_user_subtasks(&db, auth.0.id).await?;
+        let sessions = get_user_sessions(&db, auth.0.id).await?;
         let subtasks = stat_subtasks_prepare(&db, &auth.0, Some(task_ids), &filter).await?;
 
         filter.ty = None;
-        GetCategoryStats::ok(stat_subtasks(&subtasks, &user_subtasks, filter))
+        GetCategoryStats::ok(stat_subtasks(&subtasks, &user_subtasks, &sessions, filter))
     }
 
     /// Create a new challenge category.
@@ -185,11 +188,12 @@ impl Challenges {
         /// Filter by challenge title
         title: Query<Option<String>>,
         db: Data<&DbTxn>,
-        _auth: VerifiedUserAuth,
+        auth: VerifiedUserAuth,
     ) -> ListChallenges::Response<VerifiedUserAuth> {
         let mut query = challenges_challenges::Entity::find()
             .find_also_related(challenges_tasks::Entity)
             .filter(challenges_challenges::Column::CategoryId.eq(category_id.0))
+            .filter(task_scope(&auth.0))
             .order_by_asc(challenges_challenges::Column::Title);
         if let Some(title) = title.0 {
             query = query.filter(challenges_challenges::Column::Title.contains(title));
@@ -214,11 +218,13 @@ impl Challenges {
         category_id: Path<Uuid>,
         challenge_id: Path<Uuid>,
         db: Data<&DbTxn>,
-        _auth: VerifiedUserAuth,
+        auth: VerifiedUserAuth,
     ) -> GetChallenge::Response<VerifiedUserAuth> {
         match get_challenge(&db, category_id.0, challenge_id.0).await? {
-            Some((challenge, task)) => GetChallenge::ok(Challenge::from(challenge, task)),
-            None => GetChallenge::challenge_not_found(),
+            Some((challenge, task)) if can_see_task(&auth.0, &task) => {
+                GetChallenge::ok(Challenge::from(challenge, task))
+            }
+            _ => GetChallenge::challenge_not_found(),
         }
     }
 
@@ -245,6 +251,7 @@ impl Challenges {
             id: Set(Uuid::new_v4()),
             creator: Set(auth.0.id),
             creation_timestamp: Set(Utc::now().naive_utc()),
+            organization_id: Set(auth.0.organization_id()),
         }
         .insert(&***db)
         .await?;
@@ -304,6 +311,7 @@ impl Challenges {
                     id: Unchanged(task.id),
                     creator: Unchanged(task.creator),
                     creation_timestamp: Unchanged(task.creation_timestamp),
+                    organization_id: Unchanged(task.organization_id),
                 }
                 .update(&***db)
                 .await?;
@@ -0,0 +1,622 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_cloze_attempts,
+    challenges_clozes::{self, ClozeBlank, ClozeBlanks},
+    challenges_user_subtasks,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+    events::{publish_event, Event},
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::{
+    cloze::{
+        Cloze, ClozeSummary, ClozeWithSolution, CreateClozeRequest, SolveClozeFeedback,
+        SolveClozeRequest, UpdateClozeRequest, BLANK,
+    },
+    exams::ExamAnswer,
+};
+use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use uuid::Uuid;
+
+use super::{
+    question::{check_answers, normalize_answer},
+    Tags,
+};
+use crate::services::{
+    exams::{get_active_exam, record_exam_answer},
+    notifications::notify_subtask_solved,
+    revisions::{
+        get_current_revision, query_subtask_as_of, record_revision, QuerySubtaskAsOfError,
+    },
+    similarity::{find_duplicates, fingerprint, set_fingerprint},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
+};
+
+pub struct Clozes {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Clozes")]
+impl Clozes {
+    /// List all clozes in a task.
+    #[oai(path = "/tasks/:task_id/clozes", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_clozes(
+        &self,
+        task_id: Path<Uuid>,
+        /// Whether to search for subtasks the user has attempted to solve.
+        attempted: Query<Option<bool>>,
+        /// Whether to search for solved subtasks.
+        solved: Query<Option<bool>>,
+        /// Whether to search for rated subtasks.
+        rated: Query<Option<bool>>,
+        /// Whether to search for enabled subtasks.
+        enabled: Query<Option<bool>>,
+        /// Whether to search for retired subtasks.
+        retired: Query<Option<bool>>,
+        /// Filter by creator.
+        creator: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListClozes::Response<VerifiedUserAuth> {
+        ListClozes::ok(
+            query_subtasks::<challenges_clozes::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                QuerySubtasksFilter {
+                    attempted: attempted.0,
+                    solved: solved.0,
+                    rated: rated.0,
+                    enabled: enabled.0,
+                    retired: retired.0,
+                    creator: creator.0,
+                    ty: None,
+                    permissive: None,
+                },
+                ClozeSummary::from,
+            )
+            .await?,
+        )
+    }
+
+    /// Get a cloze by id.
+    #[oai(path = "/tasks/:task_id/clozes/:subtask_id", method = "get")]
+    async fn get_cloze(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the cloze as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCloze::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetCloze::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_clozes::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                Cloze::from,
+            )
+            .await?
+            {
+                Ok(x) => GetCloze::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetCloze::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetCloze::revision_not_found(),
+            };
+        }
+
+        match query_subtask::<challenges_clozes::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            Cloze::from,
+            include_note.0.unwrap_or(false),
+        )
+        .await?
+        {
+            Some(cloze) => GetCloze::ok(cloze),
+            None => GetCloze::subtask_not_found(),
+        }
+    }
+
+    /// Get a cloze and its solution by id.
+    #[oai(path = "/tasks/:task_id/clozes/:subtask_id/solution", method = "get")]
+    async fn get_cloze_with_solution(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the cloze as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetClozeWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetClozeWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_clozes::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                ClozeWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetClozeWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetClozeWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetClozeWithSolution::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask_admin::<challenges_clozes::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            ClozeWithSolution::from,
+        )
+        .await?
+        {
+            Ok(cloze) => GetClozeWithSolution::ok(cloze),
+            Err(QuerySubtaskAdminError::NotFound) => GetClozeWithSolution::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => GetClozeWithSolution::forbidden(),
+        }
+    }
+
+    /// Create a new cloze.
+    #[oai(path = "/tasks/:task_id/clozes", method = "post")]
+    async fn create_cloze(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<CreateClozeRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateCloze::Response<VerifiedUserAuth> {
+        let subtask = match create_subtask(
+            &db,
+            &self.state.services,
+            &self.config.load(),
+            &auth.0,
+            task_id.0,
+            data.0.subtask,
+            ChallengesSubtaskType::Cloze,
+        )
+        .await?
+        {
+            Ok(subtask) => subtask,
+            Err(CreateSubtaskError::TaskNotFound) => return CreateCloze::task_not_found(),
+            Err(CreateSubtaskError::Forbidden) => return CreateCloze::forbidden(),
+            Err(CreateSubtaskError::Banned(until)) => return CreateCloze::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateCloze::quota_exceeded(reset)
+            }
+            Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                return CreateCloze::xp_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                return CreateCloze::coin_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateCloze::attribution_required()
+            }
+        };
+
+        let blanks = ClozeBlanks(data.0.blanks.into_iter().map(Into::into).collect());
+        match check_cloze(&data.0.text, &blanks.0) {
+            Ok(()) => {}
+            Err(InvalidClozeError::WrongBlankCount(x)) => return CreateCloze::wrong_blank_count(x),
+            Err(InvalidClozeError::InvalidChar(x)) => return CreateCloze::invalid_char(x),
+        }
+
+        let fingerprint = cloze_fingerprint(&data.0.text, &blanks.0);
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates =
+                find_duplicates(&db, task_id.0, ChallengesSubtaskType::Cloze, &fingerprint).await?;
+            if !duplicates.is_empty() {
+                return CreateCloze::duplicate(duplicates);
+            }
+        }
+
+        let cloze = challenges_clozes::ActiveModel {
+            subtask_id: Set(subtask.id),
+            text: Set(data.0.text),
+            blanks: Set(blanks),
+        }
+        .insert(&***db)
+        .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &cloze).await?;
+        CreateCloze::ok(ClozeWithSolution::from(cloze, subtask))
+    }
+
+    /// Update a cloze.
+    #[oai(path = "/tasks/:task_id/clozes/:subtask_id", method = "patch")]
+    async fn update_cloze(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<UpdateClozeRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> UpdateCloze::Response<AdminAuth> {
+        let (cloze, subtask) = match update_subtask::<challenges_clozes::Entity>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            data.0.subtask,
+        )
+        .await?
+        {
+            Ok(x) => x,
+            Err(UpdateSubtaskError::SubtaskNotFound) => return UpdateCloze::subtask_not_found(),
+            Err(UpdateSubtaskError::TaskNotFound) => return UpdateCloze::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateCloze::attribution_required()
+            }
+        };
+
+        let blanks = data
+            .0
+            .blanks
+            .map(|x| ClozeBlanks(x.into_iter().map(Into::into).collect()));
+        match check_cloze(
+            data.0.text.get_new(&cloze.text),
+            &blanks.get_new(&cloze.blanks).0,
+        ) {
+            Ok(()) => {}
+            Err(InvalidClozeError::WrongBlankCount(x)) => return UpdateCloze::wrong_blank_count(x),
+            Err(InvalidClozeError::InvalidChar(x)) => return UpdateCloze::invalid_char(x),
+        }
+
+        let cloze = challenges_clozes::ActiveModel {
+            subtask_id: Unchanged(cloze.subtask_id),
+            text: data.0.text.update(cloze.text),
+            blanks: blanks.update(cloze.blanks),
+        }
+        .update(&***db)
+        .await?;
+        set_fingerprint(
+            &db,
+            cloze.subtask_id,
+            cloze_fingerprint(&cloze.text, &cloze.blanks.0),
+        )
+        .await?;
+        record_revision(&db, cloze.subtask_id, &cloze).await?;
+
+        UpdateCloze::ok(ClozeWithSolution::from(cloze, subtask))
+    }
+
+    /// Attempt to solve a cloze.
+    #[oai(path = "/tasks/:task_id/clozes/:subtask_id/attempts", method = "post")]
+    async fn solve_cloze(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SolveClozeRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SolveCloze::Response<VerifiedUserAuth> {
+        let Some((cloze, subtask)) =
+            get_subtask::<challenges_clozes::Entity>(&db, task_id.0, subtask_id.0).await?
+        else {
+            return SolveCloze::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SolveCloze::subtask_not_found();
+        }
+
+        if data.0.answers.len() != cloze.blanks.0.len() {
+            return SolveCloze::wrong_length();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.clozes.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return SolveCloze::too_many_requests(time_left as u64);
+            }
+        }
+
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveCloze::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
+            return SolveCloze::not_enough_hearts();
+        }
+
+        let wrong = cloze
+            .blanks
+            .0
+            .iter()
+            .zip(&data.0.answers)
+            .enumerate()
+            .filter(|(_, (blank, answer))| !check_blank(blank, answer))
+            .map(|(i, _)| i as u8)
+            .collect::<Vec<_>>();
+        let solved = wrong.is_empty();
+
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveCloze::accepted(exam_answer.into());
+        }
+
+        if let Some(solve_kind) = solve_kind {
+            let now = Utc::now().naive_utc();
+            if solved {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(now)),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+
+                if auth.0.id != subtask.creator {
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
+                }
+            } else {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+
+            challenges_cloze_attempts::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                cloze_id: Set(cloze.subtask_id),
+                user_id: Set(auth.0.id),
+                timestamp: Set(now),
+                solved: Set(solved),
+                revision_id: Set(get_current_revision(&db, cloze.subtask_id).await?),
+            }
+            .insert(&***db)
+            .await?;
+        }
+
+        SolveCloze::ok(SolveClozeFeedback { solved, wrong })
+    }
+}
+
+response!(ListClozes = {
+    Ok(200) => Vec<ClozeSummary>,
+});
+
+response!(GetCloze = {
+    Ok(200) => Cloze,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
+});
+
+response!(GetClozeWithSolution = {
+    Ok(200) => ClozeWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to view the solution to this cloze.
+    Forbidden(403, error),
+});
+
+response!(CreateCloze = {
+    Ok(201) => ClozeWithSolution,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create clozes in this task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The number of placeholders in the text does not match the number of blanks. `details` contains the number of placeholders.
+    WrongBlankCount(400, error) => usize,
+    /// One of `ascii_letters`, `digits` or `punctuation` is set to `false`, but one of the `answers` of a blank contains such a character. `details` contains the index of the blank.
+    InvalidChar(400, error) => u8,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
+});
+
+response!(UpdateCloze = {
+    Ok(200) => ClozeWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The number of placeholders in the text does not match the number of blanks. `details` contains the number of placeholders.
+    WrongBlankCount(400, error) => usize,
+    /// One of `ascii_letters`, `digits` or `punctuation` is set to `false`, but one of the `answers` of a blank contains such a character. `details` contains the index of the blank.
+    InvalidChar(400, error) => u8,
+});
+
+response!(SolveCloze = {
+    Ok(201) => SolveClozeFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+    /// The number of answers does not match the number of blanks.
+    WrongLength(400, error),
+});
+
+fn check_cloze(text: &str, blanks: &[ClozeBlank]) -> Result<(), InvalidClozeError> {
+    let placeholders = text.matches(BLANK).count();
+    if placeholders != blanks.len() {
+        return Err(InvalidClozeError::WrongBlankCount(placeholders));
+    }
+    if let Some(i) = blanks.iter().position(|blank| {
+        !check_answers(
+            &blank.answers,
+            blank.ascii_letters,
+            blank.digits,
+            blank.punctuation,
+        )
+    }) {
+        return Err(InvalidClozeError::InvalidChar(i as _));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum InvalidClozeError {
+    WrongBlankCount(usize),
+    InvalidChar(u8),
+}
+
+fn check_blank(blank: &ClozeBlank, answer: &str) -> bool {
+    let answer = normalize_answer(answer, blank.case_sensitive);
+    blank
+        .answers
+        .iter()
+        .any(|ans| normalize_answer(ans, blank.case_sensitive) == answer)
+}
+
+fn cloze_fingerprint(text: &str, blanks: &[ClozeBlank]) -> String {
+    fingerprint(
+        text,
+        blanks
+            .iter()
+            .flat_map(|blank| blank.answers.iter().map(String::as_str)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank(answers: &[&str], case_sensitive: bool) -> ClozeBlank {
+        ClozeBlank {
+            answers: answers.iter().map(|&x| x.into()).collect(),
+            case_sensitive,
+            ascii_letters: true,
+            digits: false,
+            punctuation: false,
+        }
+    }
+
+    #[test]
+    fn test_check_cloze() {
+        let blanks = [blank(&["fn"], true), blank(&["main"], true)];
+        assert_eq!(check_cloze("{{}} {{}}() {}", &blanks), Ok(()));
+        assert_eq!(
+            check_cloze("{{}}() {}", &blanks),
+            Err(InvalidClozeError::WrongBlankCount(1))
+        );
+        assert_eq!(
+            check_cloze("{{}} {{}}", &[blank(&["fn"], true), blank(&["42"], true)]),
+            Err(InvalidClozeError::InvalidChar(1))
+        );
+    }
+
+    #[test]
+    fn test_check_blank() {
+        assert!(check_blank(
+            &blank(&["Hello World"], false),
+            " hello   WORLD "
+        ));
+        assert!(!check_blank(&blank(&["Hello World"], true), "hello world"));
+        assert!(check_blank(&blank(&["a", "b"], true), "b"));
+        assert!(!check_blank(&blank(&["a", "b"], true), ""));
+    }
+}
@@ -0,0 +1,580 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_code_reading_attempts, challenges_code_readings, challenges_user_subtasks,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+    events::{publish_event, Event},
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use sandkasten_client::schemas::programs::RunResult;
+use schemas::challenges::{
+    code_readings::{
+        CodeReading, CodeReadingSummary, CodeReadingWithSolution, CreateCodeReadingRequest,
+        SolveCodeReadingFeedback, SolveCodeReadingRequest, UpdateCodeReadingRequest,
+    },
+    coding_challenges::CheckResult,
+    exams::ExamAnswer,
+};
+use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use uuid::Uuid;
+
+use super::{question::normalize_answer, Tags};
+use crate::services::{
+    exams::{get_active_exam, record_exam_answer},
+    judge::{run_program, Error as JudgeError},
+    notifications::notify_subtask_solved,
+    revisions::{
+        get_current_revision, query_subtask_as_of, record_revision, QuerySubtaskAsOfError,
+    },
+    sandbox::SharedSandbox,
+    similarity::{find_duplicates, fingerprint, set_fingerprint},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
+};
+
+pub struct CodeReadings {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
+}
+
+#[OpenApi(tag = "Tags::CodeReadings")]
+impl CodeReadings {
+    /// List all code reading questions in a task.
+    #[oai(path = "/tasks/:task_id/code_readings", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_code_readings(
+        &self,
+        task_id: Path<Uuid>,
+        /// Whether to search for subtasks the user has attempted to solve.
+        attempted: Query<Option<bool>>,
+        /// Whether to search for solved subtasks.
+        solved: Query<Option<bool>>,
+        /// Whether to search for rated subtasks.
+        rated: Query<Option<bool>>,
+        /// Whether to search for enabled subtasks.
+        enabled: Query<Option<bool>>,
+        /// Whether to search for retired subtasks.
+        retired: Query<Option<bool>>,
+        /// Filter by creator.
+        creator: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListCodeReadings::Response<VerifiedUserAuth> {
+        ListCodeReadings::ok(
+            query_subtasks::<challenges_code_readings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                QuerySubtasksFilter {
+                    attempted: attempted.0,
+                    solved: solved.0,
+                    rated: rated.0,
+                    enabled: enabled.0,
+                    retired: retired.0,
+                    creator: creator.0,
+                    ty: None,
+                    permissive: None,
+                },
+                CodeReadingSummary::from,
+            )
+            .await?,
+        )
+    }
+
+    /// Get a code reading question by id.
+    #[oai(path = "/tasks/:task_id/code_readings/:subtask_id", method = "get")]
+    async fn get_code_reading(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the question as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCodeReading::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetCodeReading::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_code_readings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                CodeReading::from,
+            )
+            .await?
+            {
+                Ok(x) => GetCodeReading::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetCodeReading::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetCodeReading::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask::<challenges_code_readings::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            CodeReading::from,
+            include_note.0.unwrap_or(false),
+        )
+        .await?
+        {
+            Some(code_reading) => GetCodeReading::ok(code_reading),
+            None => GetCodeReading::subtask_not_found(),
+        }
+    }
+
+    /// Get a code reading question and its solution by id.
+    #[oai(
+        path = "/tasks/:task_id/code_readings/:subtask_id/solution",
+        method = "get"
+    )]
+    async fn get_code_reading_with_solution(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the question as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCodeReadingWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetCodeReadingWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_code_readings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                CodeReadingWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetCodeReadingWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetCodeReadingWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetCodeReadingWithSolution::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask_admin::<challenges_code_readings::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            CodeReadingWithSolution::from,
+        )
+        .await?
+        {
+            Ok(code_reading) => GetCodeReadingWithSolution::ok(code_reading),
+            Err(QuerySubtaskAdminError::NotFound) => {
+                GetCodeReadingWithSolution::subtask_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => GetCodeReadingWithSolution::forbidden(),
+        }
+    }
+
+    /// Create a new code reading question.
+    ///
+    /// The program is run once to determine the expected output.
+    #[oai(path = "/tasks/:task_id/code_readings", method = "post")]
+    async fn create_code_reading(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<CreateCodeReadingRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateCodeReading::Response<VerifiedUserAuth> {
+        let subtask = match create_subtask(
+            &db,
+            &self.state.services,
+            &self.config.load(),
+            &auth.0,
+            task_id.0,
+            data.0.subtask,
+            ChallengesSubtaskType::CodeReading,
+        )
+        .await?
+        {
+            Ok(subtask) => subtask,
+            Err(CreateSubtaskError::TaskNotFound) => return CreateCodeReading::task_not_found(),
+            Err(CreateSubtaskError::Forbidden) => return CreateCodeReading::forbidden(),
+            Err(CreateSubtaskError::Banned(until)) => return CreateCodeReading::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateCodeReading::quota_exceeded(reset)
+            }
+            Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                return CreateCodeReading::xp_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                return CreateCodeReading::coin_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateCodeReading::attribution_required()
+            }
+        };
+
+        let output = match self.run(&data.0.environment, data.0.code.clone()).await {
+            Err(JudgeError::EnvironmentNotFound) => {
+                return CreateCodeReading::environment_not_found()
+            }
+            x => match x? {
+                Ok(output) => output,
+                Err(result) => return CreateCodeReading::program_failed(result),
+            },
+        };
+
+        let fingerprint = fingerprint(&data.0.code, [data.0.environment.as_str()]);
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::CodeReading,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateCodeReading::duplicate(duplicates);
+            }
+        }
+
+        let code_reading = challenges_code_readings::ActiveModel {
+            subtask_id: Set(subtask.id),
+            environment: Set(data.0.environment),
+            code: Set(data.0.code),
+            output: Set(output),
+        }
+        .insert(&***db)
+        .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &code_reading).await?;
+        CreateCodeReading::ok(CodeReadingWithSolution::from(code_reading, subtask))
+    }
+
+    /// Update a code reading question.
+    ///
+    /// The program is run again to make sure the expected output is still
+    /// up to date.
+    #[oai(path = "/tasks/:task_id/code_readings/:subtask_id", method = "patch")]
+    async fn update_code_reading(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<UpdateCodeReadingRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> UpdateCodeReading::Response<AdminAuth> {
+        let (code_reading, subtask) = match update_subtask::<challenges_code_readings::Entity>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            data.0.subtask,
+        )
+        .await?
+        {
+            Ok(x) => x,
+            Err(UpdateSubtaskError::SubtaskNotFound) => {
+                return UpdateCodeReading::subtask_not_found()
+            }
+            Err(UpdateSubtaskError::TaskNotFound) => return UpdateCodeReading::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateCodeReading::attribution_required()
+            }
+        };
+
+        let output = match self
+            .run(
+                data.0.environment.get_new(&code_reading.environment),
+                data.0.code.get_new(&code_reading.code).clone(),
+            )
+            .await
+        {
+            Err(JudgeError::EnvironmentNotFound) => {
+                return UpdateCodeReading::environment_not_found()
+            }
+            x => match x? {
+                Ok(output) => output,
+                Err(result) => return UpdateCodeReading::program_failed(result),
+            },
+        };
+
+        let code_reading = challenges_code_readings::ActiveModel {
+            subtask_id: Unchanged(code_reading.subtask_id),
+            environment: data.0.environment.update(code_reading.environment),
+            code: data.0.code.update(code_reading.code),
+            output: Set(output),
+        }
+        .update(&***db)
+        .await?;
+        set_fingerprint(
+            &db,
+            code_reading.subtask_id,
+            fingerprint(&code_reading.code, [code_reading.environment.as_str()]),
+        )
+        .await?;
+        record_revision(&db, code_reading.subtask_id, &code_reading).await?;
+
+        UpdateCodeReading::ok(CodeReadingWithSolution::from(code_reading, subtask))
+    }
+
+    /// Attempt to solve a code reading question.
+    #[oai(
+        path = "/tasks/:task_id/code_readings/:subtask_id/attempts",
+        method = "post"
+    )]
+    async fn solve_code_reading(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SolveCodeReadingRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SolveCodeReading::Response<VerifiedUserAuth> {
+        let Some((code_reading, subtask)) =
+            get_subtask::<challenges_code_readings::Entity>(&db, task_id.0, subtask_id.0).await?
+        else {
+            return SolveCodeReading::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SolveCodeReading::subtask_not_found();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.code_readings.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return SolveCodeReading::too_many_requests(time_left as u64);
+            }
+        }
+
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveCodeReading::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
+            return SolveCodeReading::not_enough_hearts();
+        }
+
+        let solved =
+            normalize_answer(&data.0.answer, true) == normalize_answer(&code_reading.output, true);
+
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveCodeReading::accepted(exam_answer.into());
+        }
+
+        if let Some(solve_kind) = solve_kind {
+            let now = Utc::now().naive_utc();
+            if solved {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(now)),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+
+                if auth.0.id != subtask.creator {
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
+                }
+            } else {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+
+            challenges_code_reading_attempts::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                code_reading_id: Set(code_reading.subtask_id),
+                user_id: Set(auth.0.id),
+                timestamp: Set(now),
+                solved: Set(solved),
+                revision_id: Set(get_current_revision(&db, code_reading.subtask_id).await?),
+            }
+            .insert(&***db)
+            .await?;
+        }
+
+        SolveCodeReading::ok(SolveCodeReadingFeedback { solved })
+    }
+}
+
+impl CodeReadings {
+    /// Run the program of a code reading question and return its output.
+    async fn run(
+        &self,
+        environment: &str,
+        code: String,
+    ) -> Result<Result<String, CheckResult<RunResult>>, JudgeError> {
+        let config = &self.config.load().challenges.code_readings;
+        Ok(run_program(
+            &*self.sandkasten,
+            environment,
+            code,
+            Vec::new(),
+            Vec::new(),
+            None,
+            Some(config.time_limit),
+            Some(config.memory_limit),
+        )
+        .await?
+        .map(|output| output.run.stdout))
+    }
+}
+
+response!(ListCodeReadings = {
+    Ok(200) => Vec<CodeReadingSummary>,
+});
+
+response!(GetCodeReading = {
+    Ok(200) => CodeReading,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
+});
+
+response!(GetCodeReadingWithSolution = {
+    Ok(200) => CodeReadingWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to view the solution to this question.
+    Forbidden(403, error),
+});
+
+response!(CreateCodeReading = {
+    Ok(201) => CodeReadingWithSolution,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create code reading questions in this task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The environment does not exist.
+    EnvironmentNotFound(404, error),
+    /// The program did not terminate successfully or did not print anything.
+    ProgramFailed(400, error) => CheckResult<RunResult>,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
+});
+
+response!(UpdateCodeReading = {
+    Ok(200) => CodeReadingWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The environment does not exist.
+    EnvironmentNotFound(404, error),
+    /// The program did not terminate successfully or did not print anything.
+    ProgramFailed(400, error) => CheckResult<RunResult>,
+});
+
+response!(SolveCodeReading = {
+    Ok(201) => SolveCodeReadingFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+});
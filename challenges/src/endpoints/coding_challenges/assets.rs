@@ -2,7 +2,7 @@ use poem_openapi::{payload::PlainText, OpenApi};
 
 use crate::{
     endpoints::Tags,
-    services::judge::{EVALUATOR_LIBRARY, EVALUATOR_TEMPLATE},
+    services::judge::{EVALUATOR_LIBRARY, EVALUATOR_TEMPLATE, INTERACTIVE_EVALUATOR_TEMPLATE},
 };
 
 pub struct Api;
@@ -15,6 +15,12 @@ impl Api {
         PlainText(EVALUATOR_TEMPLATE)
     }
 
+    /// Return the evaluator template for interactive challenges.
+    #[oai(path = "/coding_challenges/evaluator/interactive.py", method = "get")]
+    async fn get_interactive_evaluator_template(&self) -> PlainText<&'static str> {
+        PlainText(INTERACTIVE_EVALUATOR_TEMPLATE)
+    }
+
     /// Return the evaluator library.
     #[oai(path = "/coding_challenges/evaluator/lib.py", method = "get")]
     async fn get_evaluator_lib(&self) -> PlainText<&'static str> {
@@ -0,0 +1,169 @@
+use entity::{challenges_build_configs, challenges_coding_challenges};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::coding_challenges::{BuildConfig, SetBuildConfigRequest};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::{
+        build_configs::find_disallowed_flag,
+        subtasks::{can_access_subtask, get_subtask},
+    },
+};
+
+pub struct Api {
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// List the build flags of a coding challenge for each environment.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/build_configs",
+        method = "get"
+    )]
+    async fn list_build_configs(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListBuildConfigs::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListBuildConfigs::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return ListBuildConfigs::subtask_not_found();
+        }
+
+        ListBuildConfigs::ok(
+            cc.find_related(challenges_build_configs::Entity)
+                .order_by_asc(challenges_build_configs::Column::Environment)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Set the build flags of a coding challenge for an environment.
+    ///
+    /// The flags are passed to the build of every solution in this environment
+    /// in the `BUILD_FLAGS` environment variable.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/build_configs/:environment",
+        method = "put"
+    )]
+    async fn set_build_config(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        environment: Path<String>,
+        data: Json<SetBuildConfigRequest>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> SetBuildConfig::Response<AdminAuth> {
+        let Some((cc, _)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return SetBuildConfig::subtask_not_found();
+        };
+
+        if let Some(flag) = find_disallowed_flag(
+            &self.config.load().challenges.coding_challenges.build_flags,
+            &environment.0,
+            &data.0.flags,
+        ) {
+            return SetBuildConfig::flag_not_allowed(flag.into());
+        }
+
+        challenges_build_configs::Entity::insert(challenges_build_configs::ActiveModel {
+            subtask_id: Set(cc.subtask_id),
+            environment: Set(environment.0.clone()),
+            flags: Set(data.0.flags.clone()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                challenges_build_configs::Column::SubtaskId,
+                challenges_build_configs::Column::Environment,
+            ])
+            .update_column(challenges_build_configs::Column::Flags)
+            .to_owned(),
+        )
+        .exec_without_returning(&***db)
+        .await?;
+
+        SetBuildConfig::ok(BuildConfig {
+            environment: environment.0,
+            flags: data.0.flags,
+        })
+    }
+
+    /// Remove the build flags of a coding challenge for an environment.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/build_configs/:environment",
+        method = "delete"
+    )]
+    async fn delete_build_config(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        environment: Path<String>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DeleteBuildConfig::Response<AdminAuth> {
+        let Some((cc, _)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return DeleteBuildConfig::subtask_not_found();
+        };
+
+        let result = challenges_build_configs::Entity::delete_many()
+            .filter(challenges_build_configs::Column::SubtaskId.eq(cc.subtask_id))
+            .filter(challenges_build_configs::Column::Environment.eq(environment.0))
+            .exec(&***db)
+            .await?;
+        if result.rows_affected == 0 {
+            return DeleteBuildConfig::build_config_not_found();
+        }
+
+        DeleteBuildConfig::ok()
+    }
+}
+
+response!(ListBuildConfigs = {
+    Ok(200) => Vec<BuildConfig>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(SetBuildConfig = {
+    Ok(200) => BuildConfig,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The flag is not allowed for this environment.
+    FlagNotAllowed(400, error) => String,
+});
+
+response!(DeleteBuildConfig = {
+    Ok(200),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The coding challenge has no build flags for this environment.
+    BuildConfigNotFound(404, error),
+});
@@ -1,45 +1,60 @@
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use entity::{challenges_coding_challenges, sea_orm_active_enums::ChallengesSubtaskType};
+use entity::{
+    challenges_coding_challenges, challenges_personal_bests,
+    sea_orm_active_enums::{ChallengesComparison, ChallengesSubtaskType},
+};
 use fnct::format::JsonFormatter;
 use lib::{
-    auth::{AdminAuth, VerifiedUserAuth},
-    config::Config,
+    auth::{AdminAuth, User, VerifiedUserAuth},
+    config::SharedConfig,
     Cache, SharedState,
 };
 use poem::web::Data;
-use poem_ext::{db::DbTxn, response};
+use poem_ext::{
+    db::DbTxn,
+    patch_value::PatchValue,
+    response,
+    responses::{internal_server_error, ErrorResponse},
+};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     OpenApi,
 };
-use sandkasten_client::SandkastenClient;
 use schemas::challenges::coding_challenges::{
-    CodingChallenge, CodingChallengeSummary, CreateCodingChallengeRequest, Example,
-    SubmissionContent, UpdateCodingChallengeRequest,
+    CheckReport, CodingChallenge, CodingChallengeExamples, CodingChallengeSummary,
+    CreateCodingChallengeRequest, Example, SubmissionContent, UpdateCodingChallengeRequest,
 };
-use sea_orm::{ActiveModelTrait, Set, Unchanged};
-use tracing::error;
+use sea_orm::{ActiveModelTrait, DatabaseTransaction, EntityTrait, Set, Unchanged};
 use uuid::Uuid;
 
-use super::{_CheckError, check_challenge, CheckChallenge};
+use super::{_CheckError, check_challenge, check_challenge_report, CheckChallenge};
 use crate::{
     endpoints::Tags,
     services::{
-        judge::{self, get_executor_config, Judge},
+        build_configs::{get_build_flags, BuildFlags},
+        evaluators::record_evaluator_version,
+        example_limiter::ExampleLimiter,
+        examples::{get_examples, CachedExamples, GetExamplesError},
+        judge::{checker_program, get_executor_config, Judge, Program},
+        revisions::{query_subtask_as_of, record_revision, QuerySubtaskAsOfError},
+        sandbox::{MeteredSandbox, SharedSandbox},
+        sandbox_usage::record_sandbox_usage,
         subtasks::{
-            create_subtask, query_subtask, query_subtask_admin, query_subtasks, update_subtask,
-            CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter, UpdateSubtaskError,
+            create_subtask, get_subtask, get_user_subtask, query_subtask, query_subtask_admin,
+            query_subtasks, update_subtask, CreateSubtaskError, QuerySubtaskAdminError,
+            QuerySubtasksFilter, UpdateSubtaskError, UserSubtaskExt,
         },
     },
 };
 
 pub struct Api {
-    pub sandkasten: SandkastenClient,
+    pub sandkasten: SharedSandbox,
     pub judge_cache: Cache<JsonFormatter>,
-    pub config: Arc<Config>,
+    pub example_limiter: ExampleLimiter,
+    pub config: SharedConfig,
     pub state: Arc<SharedState>,
 }
 
@@ -79,6 +94,7 @@ impl Api {
                     retired: retired.0,
                     creator: creator.0,
                     ty: None,
+                    permissive: None,
                 },
                 CodingChallengeSummary::from,
             )
@@ -92,24 +108,64 @@ impl Api {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the coding challenge as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetCodingChallenge::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetCodingChallenge::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_coding_challenges::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                CodingChallenge::from,
+            )
+            .await?
+            {
+                Ok(x) => GetCodingChallenge::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetCodingChallenge::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetCodingChallenge::revision_not_found()
+                }
+            };
+        }
+
         match query_subtask::<challenges_coding_challenges::Entity, _>(
             &db,
             &auth.0,
             task_id.0,
             subtask_id.0,
             CodingChallenge::from,
+            include_note.0.unwrap_or(false),
         )
         .await?
         {
-            Some(x) => GetCodingChallenge::ok(x),
+            Some(mut x) => {
+                x.personal_best =
+                    challenges_personal_bests::Entity::find_by_id((auth.0.id, subtask_id.0))
+                        .one(&***db)
+                        .await?
+                        .map(Into::into);
+                GetCodingChallenge::ok(x)
+            }
             None => GetCodingChallenge::subtask_not_found(),
         }
     }
 
     /// Get the examples of a coding challenge by id.
+    ///
+    /// The examples are cached until the evaluator or the solution of the
+    /// challenge is updated. Generating examples that are not cached yet is
+    /// rate limited per user and per challenge.
     #[oai(
         path = "/tasks/:task_id/coding_challenges/:subtask_id/examples",
         method = "get"
@@ -121,56 +177,23 @@ impl Api {
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetExamples::Response<VerifiedUserAuth> {
-        let cc = match query_subtask::<challenges_coding_challenges::Entity, _>(
-            &db,
-            &auth.0,
-            task_id.0,
-            subtask_id.0,
-            |cc, _| cc,
-        )
-        .await?
+        match self
+            .load_examples(&db, &auth.0, task_id.0, subtask_id.0)
+            .await?
         {
-            Some(cc) => cc,
-            None => return GetExamples::subtask_not_found(),
-        };
-
-        let judge = self.get_judge(&cc.evaluator);
-
-        let examples = match judge.examples().await {
-            Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
-                error!(
-                    "evaluator for {} failed to execute: {:?}",
-                    subtask_id.0, err
-                );
-                return GetExamples::evaluator_failed();
+            None => GetExamples::subtask_not_found(),
+            Some(Ok(examples)) => GetExamples::ok(examples.examples),
+            Some(Err(GetExamplesError::TooManyRequests(wait))) => {
+                GetExamples::too_many_requests(wait)
             }
-            x => x?,
-        };
-        let mut out = Vec::with_capacity(examples.len());
-        for seed in &examples {
-            let example = judge
-                .get_example_checked(
-                    seed,
-                    &cc.solution_environment,
-                    &cc.solution_code,
-                    Some(cc.time_limit as _),
-                    Some(cc.memory_limit as _),
-                )
-                .await?;
-            let example = match example {
-                Ok(example) => example,
-                Err(err) => {
-                    error!(
-                        "example generation for {} failed on example {}: {:?}",
-                        subtask_id.0, seed, err
-                    );
-                    return GetExamples::example_generation_failed();
-                }
-            };
-            out.push(example);
+            Some(Err(GetExamplesError::EvaluatorFailed)) => GetExamples::evaluator_failed(),
+            Some(Err(GetExamplesError::ExampleGenerationFailed)) => {
+                GetExamples::example_generation_failed()
+            }
+            Some(Err(GetExamplesError::Judge(err))) => Err(internal_server_error(err)),
+            Some(Err(GetExamplesError::Redis(err))) => Err(internal_server_error(err)),
+            Some(Err(GetExamplesError::Database(err))) => Err(internal_server_error(err)),
         }
-
-        GetExamples::ok(out)
     }
 
     /// Get the evaluator of a coding challenge by id.
@@ -182,9 +205,31 @@ impl Api {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the evaluator as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetEvaluator::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetEvaluator::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_coding_challenges::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                |cc, _| cc.evaluator,
+            )
+            .await?
+            {
+                Ok(x) => GetEvaluator::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetEvaluator::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetEvaluator::revision_not_found(),
+            };
+        }
+
         match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
             &db,
             &auth.0,
@@ -201,6 +246,9 @@ impl Api {
     }
 
     /// Get the solution of a coding challenge by id.
+    ///
+    /// Users who have solved the challenge can request the solution if
+    /// `solution_public_after_solve` is set.
     #[oai(
         path = "/tasks/:task_id/coding_challenges/:subtask_id/solution",
         method = "get"
@@ -209,9 +257,35 @@ impl Api {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the solution as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_coding_challenges::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                |cc, _| SubmissionContent {
+                    environment: cc.solution_environment,
+                    code: cc.solution_code,
+                    files: Vec::new(),
+                },
+            )
+            .await?
+            {
+                Ok(x) => GetSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetSolution::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetSolution::revision_not_found(),
+            };
+        }
+
         match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
             &db,
             &auth.0,
@@ -224,9 +298,107 @@ impl Api {
             Ok(cc) => GetSolution::ok(SubmissionContent {
                 environment: cc.solution_environment,
                 code: cc.solution_code,
+                files: Vec::new(),
             }),
             Err(QuerySubtaskAdminError::NotFound) => GetSolution::subtask_not_found(),
-            Err(QuerySubtaskAdminError::NoAccess) => GetSolution::forbidden(),
+            Err(QuerySubtaskAdminError::NoAccess) => {
+                // users who have solved the challenge may study the solution
+                // if the creator has published it
+                let Some((cc, subtask)) = get_subtask::<challenges_coding_challenges::Entity>(
+                    &db,
+                    task_id.0,
+                    subtask_id.0,
+                )
+                .await?
+                else {
+                    return GetSolution::subtask_not_found();
+                };
+                if !cc.solution_public_after_solve
+                    || !get_user_subtask(&db, auth.0.id, subtask.id)
+                        .await?
+                        .is_solved()
+                {
+                    return GetSolution::forbidden();
+                }
+                GetSolution::ok(SubmissionContent {
+                    environment: cc.solution_environment,
+                    code: cc.solution_code,
+                    files: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// Run the solution of a coding challenge against all of its tests and
+    /// report the result of each test.
+    ///
+    /// In contrast to creating or updating a challenge, all tests are run even
+    /// if some of them fail. Only admins and the creator of the challenge can
+    /// request a check report.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/check_report",
+        method = "get"
+    )]
+    async fn get_check_report(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCheckReport::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => return GetCheckReport::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => return GetCheckReport::forbidden(),
+        };
+
+        let build_flags = get_build_flags(&***db, cc.subtask_id).await?;
+        let sandbox = MeteredSandbox::new(&*self.sandkasten);
+        let report = check_challenge_report(CheckChallenge {
+            judge: Judge {
+                sandkasten: &sandbox,
+                ..self.get_judge(
+                    &cc.evaluator,
+                    cc.protocol_version,
+                    cc.comparison,
+                    cc.epsilon,
+                    Program {
+                        environment: &cc.solution_environment,
+                        code: &cc.solution_code,
+                    },
+                    checker_program(&cc),
+                    &build_flags,
+                )
+            },
+            challenge_id: cc.subtask_id,
+            solution_environment: &cc.solution_environment,
+            solution_code: &cc.solution_code,
+            solution_files: &[],
+            time_limit: cc.time_limit as _,
+            memory_limit: cc.memory_limit as _,
+            static_tests: cc.static_tests as _,
+            random_tests: cc.random_tests as _,
+        })
+        .await;
+        record_sandbox_usage(
+            &self.state.db,
+            cc.subtask_id,
+            &sandbox,
+            Utc::now().naive_utc(),
+        )
+        .await?;
+
+        match report? {
+            Ok(report) => GetCheckReport::ok(report),
+            Err(err) => Ok(_CheckError::Response::from(err).into()),
         }
     }
 
@@ -242,7 +414,7 @@ impl Api {
         let subtask = match create_subtask(
             &db,
             &self.state.services,
-            &self.config,
+            &self.config.load(),
             &auth.0,
             task_id.0,
             data.0.subtask,
@@ -256,28 +428,55 @@ impl Api {
             }
             Err(CreateSubtaskError::Forbidden) => return CreateCodingChallenge::forbidden(),
             Err(CreateSubtaskError::Banned(until)) => return CreateCodingChallenge::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateCodingChallenge::quota_exceeded(reset)
+            }
             Err(CreateSubtaskError::XpLimitExceeded(x)) => {
                 return CreateCodingChallenge::xp_limit_exceeded(x)
             }
             Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
                 return CreateCodingChallenge::coin_limit_exceeded(x)
             }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateCodingChallenge::attribution_required()
+            }
         };
 
-        let config = get_executor_config(&self.judge_cache, &self.sandkasten).await?;
+        let config = get_executor_config(&self.judge_cache, &*self.sandkasten).await?;
         if data.0.time_limit > config.time_limit {
             return CreateCodingChallenge::time_limit_exceeded(config.time_limit);
         }
         if data.0.memory_limit > config.memory_limit {
             return CreateCodingChallenge::memory_limit_exceeded(config.memory_limit);
         }
+        if data.0.output_only && data.0.static_tests == 0 {
+            return CreateCodingChallenge::no_static_tests();
+        }
+        if data.0.comparison == ChallengesComparison::Checker && data.0.checker.is_none() {
+            return CreateCodingChallenge::no_checker();
+        }
 
         let cc_id = Uuid::new_v4();
         if let Err(result) = check_challenge(CheckChallenge {
-            judge: self.get_judge(&data.0.evaluator),
+            judge: self.get_judge(
+                &data.0.evaluator,
+                data.0.protocol_version,
+                data.0.comparison,
+                data.0.epsilon,
+                Program {
+                    environment: &data.0.solution_environment,
+                    code: &data.0.solution_code,
+                },
+                data.0.checker.as_ref().map(|checker| Program {
+                    environment: &checker.environment,
+                    code: &checker.code,
+                }),
+                &BuildFlags::new(),
+            ),
             challenge_id: cc_id,
             solution_environment: &data.0.solution_environment,
             solution_code: &data.0.solution_code,
+            solution_files: &[],
             time_limit: data.0.time_limit,
             memory_limit: data.0.memory_limit,
             static_tests: data.0.static_tests,
@@ -298,9 +497,21 @@ impl Api {
             description: Set(data.0.description),
             solution_environment: Set(data.0.solution_environment),
             solution_code: Set(data.0.solution_code),
+            output_only: Set(data.0.output_only),
+            evaluator_version: Set(1),
+            feedback_level: Set(data.0.feedback_level),
+            protocol_version: Set(data.0.protocol_version),
+            comparison: Set(data.0.comparison),
+            epsilon: Set(data.0.epsilon),
+            checker_environment: Set(data.0.checker.as_ref().map(|x| x.environment.clone())),
+            checker_code: Set(data.0.checker.map(|x| x.code)),
+            golf: Set(data.0.golf),
+            solution_public_after_solve: Set(data.0.solution_public_after_solve),
         }
         .insert(&***db)
         .await?;
+        record_revision(&db, subtask.id, &cc).await?;
+        record_evaluator_version(&db, subtask.id, 1, &cc.evaluator, auth.0.id).await?;
         CreateCodingChallenge::ok(CodingChallenge::from(cc, subtask))
     }
 
@@ -333,24 +544,60 @@ impl Api {
             Err(UpdateSubtaskError::TaskNotFound) => {
                 return UpdateCodingChallenge::task_not_found()
             }
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateCodingChallenge::attribution_required()
+            }
         };
 
-        let config = get_executor_config(&self.judge_cache, &self.sandkasten).await?;
+        let config = get_executor_config(&self.judge_cache, &*self.sandkasten).await?;
         if *data.0.time_limit.get_new(&(cc.time_limit as _)) > config.time_limit {
             return UpdateCodingChallenge::time_limit_exceeded(config.time_limit);
         }
         if *data.0.memory_limit.get_new(&(cc.memory_limit as _)) > config.memory_limit {
             return UpdateCodingChallenge::memory_limit_exceeded(config.memory_limit);
         }
+        if *data.0.output_only.get_new(&cc.output_only)
+            && *data.0.static_tests.get_new(&(cc.static_tests as _)) == 0
+        {
+            return UpdateCodingChallenge::no_static_tests();
+        }
+        let checker = match &data.0.checker {
+            PatchValue::Set(checker) => checker.as_ref().map(|checker| Program {
+                environment: &checker.environment,
+                code: &checker.code,
+            }),
+            PatchValue::Unchanged => checker_program(&cc),
+        };
+        if *data.0.comparison.get_new(&cc.comparison) == ChallengesComparison::Checker
+            && checker.is_none()
+        {
+            return UpdateCodingChallenge::no_checker();
+        }
 
+        let build_flags = get_build_flags(&***db, cc.subtask_id).await?;
         if let Err(result) = check_challenge(CheckChallenge {
-            judge: self.get_judge(data.0.evaluator.get_new(&cc.evaluator)),
+            judge: self.get_judge(
+                data.0.evaluator.get_new(&cc.evaluator),
+                *data.0.protocol_version.get_new(&cc.protocol_version),
+                *data.0.comparison.get_new(&cc.comparison),
+                *data.0.epsilon.get_new(&cc.epsilon),
+                Program {
+                    environment: data
+                        .0
+                        .solution_environment
+                        .get_new(&cc.solution_environment),
+                    code: data.0.solution_code.get_new(&cc.solution_code),
+                },
+                checker,
+                &build_flags,
+            ),
             challenge_id: cc.subtask_id,
             solution_environment: data
                 .0
                 .solution_environment
                 .get_new(&cc.solution_environment),
             solution_code: data.0.solution_code.get_new(&cc.solution_code),
+            solution_files: &[],
             time_limit: *data.0.time_limit.get_new(&(cc.time_limit as _)),
             memory_limit: *data.0.memory_limit.get_new(&(cc.memory_limit as _)),
             static_tests: *data.0.static_tests.get_new(&(cc.static_tests as _)),
@@ -361,6 +608,7 @@ impl Api {
             return Ok(_CheckError::Response::from(result).into());
         }
 
+        let evaluator_changed = *data.0.evaluator.get_new(&cc.evaluator) != cc.evaluator;
         let cc = challenges_coding_challenges::ActiveModel {
             subtask_id: Unchanged(cc.subtask_id),
             time_limit: data.0.time_limit.map(|x| x as _).update(cc.time_limit),
@@ -371,9 +619,46 @@ impl Api {
             description: data.0.description.update(cc.description),
             solution_environment: data.0.solution_environment.update(cc.solution_environment),
             solution_code: data.0.solution_code.update(cc.solution_code),
+            output_only: data.0.output_only.update(cc.output_only),
+            evaluator_version: if evaluator_changed {
+                Set(cc.evaluator_version + 1)
+            } else {
+                Unchanged(cc.evaluator_version)
+            },
+            feedback_level: data.0.feedback_level.update(cc.feedback_level),
+            protocol_version: data.0.protocol_version.update(cc.protocol_version),
+            comparison: data.0.comparison.update(cc.comparison),
+            epsilon: data.0.epsilon.update(cc.epsilon),
+            checker_environment: data
+                .0
+                .checker
+                .clone()
+                .map(|x| x.map(|x| x.environment))
+                .update(cc.checker_environment),
+            checker_code: data
+                .0
+                .checker
+                .map(|x| x.map(|x| x.code))
+                .update(cc.checker_code),
+            golf: data.0.golf.update(cc.golf),
+            solution_public_after_solve: data
+                .0
+                .solution_public_after_solve
+                .update(cc.solution_public_after_solve),
         }
         .update(&***db)
         .await?;
+        record_revision(&db, cc.subtask_id, &cc).await?;
+        if evaluator_changed {
+            record_evaluator_version(
+                &db,
+                cc.subtask_id,
+                cc.evaluator_version,
+                &cc.evaluator,
+                auth.0.id,
+            )
+            .await?;
+        }
 
         UpdateCodingChallenge::ok(CodingChallenge::from(cc, subtask))
     }
@@ -387,12 +672,18 @@ response!(GetCodingChallenge = {
     Ok(200) => CodingChallenge,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
 });
 
 response!(GetExamples = {
     Ok(200) => Vec<Example>,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// The examples have been generated too often. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
     /// The evaluator failed to execute.
     EvaluatorFailed(400, error),
     /// Failed to generate an example.
@@ -403,6 +694,8 @@ response!(GetEvaluator = {
     Ok(200) => String,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
     /// The user is not allowed to request the evaluator of this coding challenge.
     Forbidden(403, error),
 });
@@ -411,10 +704,21 @@ response!(GetSolution = {
     Ok(200) => SubmissionContent,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
     /// The user is not allowed to request the solution of this coding challenge.
     Forbidden(403, error),
 });
 
+response!(GetCheckReport = {
+    Ok(200) => CheckReport,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not allowed to request a check report for this coding challenge.
+    Forbidden(403, error),
+    .._CheckError::Response,
+});
+
 response!(CreateCodingChallenge = {
     Ok(201) => CodingChallenge,
     /// Task does not exist.
@@ -423,14 +727,22 @@ response!(CreateCodingChallenge = {
     Forbidden(403, error),
     /// The user is currently banned from creating subtasks.
     Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
     /// The max xp limit has been exceeded.
     XpLimitExceeded(403, error) => u64,
     /// The max coin limit has been exceeded.
     CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// Time limit exceeded
     TimeLimitExceeded(403, error) => u64,
     /// Memory limit exceeded
     MemoryLimitExceeded(403, error) => u64,
+    /// Output-only challenges need at least one static test.
+    NoStaticTests(400, error),
+    /// The `CHECKER` comparison requires a checker program.
+    NoChecker(400, error),
     .._CheckError::Response,
 });
 
@@ -440,19 +752,161 @@ response!(UpdateCodingChallenge = {
     SubtaskNotFound(404, error),
     /// Task does not exist.
     TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// Time limit exceeded
     TimeLimitExceeded(403, error) => u64,
     /// Memory limit exceeded
     MemoryLimitExceeded(403, error) => u64,
+    /// Output-only challenges need at least one static test.
+    NoStaticTests(400, error),
+    /// The `CHECKER` comparison requires a checker program.
+    NoChecker(400, error),
     .._CheckError::Response,
 });
 
 impl Api {
-    fn get_judge<'a>(&'a self, evaluator: &'a str) -> Judge<'a> {
+    #[allow(clippy::too_many_arguments)]
+    fn get_judge<'a>(
+        &'a self,
+        evaluator: &'a str,
+        protocol_version: i32,
+        comparison: ChallengesComparison,
+        epsilon: Option<f64>,
+        reference: Program<'a>,
+        checker: Option<Program<'a>>,
+        build_flags: &'a BuildFlags,
+    ) -> Judge<'a> {
         Judge {
-            sandkasten: &self.sandkasten,
+            sandkasten: &*self.sandkasten,
             evaluator,
+            protocol_version,
+            comparison: comparison.into(),
+            epsilon,
+            reference,
+            checker,
+            build_flags,
             cache: &self.judge_cache,
         }
     }
+
+    /// Return the examples of a coding challenge or `None` if the challenge
+    /// does not exist or the user cannot access it.
+    async fn load_examples(
+        &self,
+        db: &DatabaseTransaction,
+        user: &User,
+        task_id: Uuid,
+        subtask_id: Uuid,
+    ) -> Result<Option<Result<CachedExamples, GetExamplesError>>, ErrorResponse> {
+        let Some((cc, subtask)) = query_subtask::<challenges_coding_challenges::Entity, _>(
+            db,
+            user,
+            task_id,
+            subtask_id,
+            |cc, subtask| (cc, subtask),
+            false,
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let build_flags = get_build_flags(db, cc.subtask_id).await?;
+        let sandbox = MeteredSandbox::new(&*self.sandkasten);
+        let judge = Judge {
+            sandkasten: &sandbox,
+            ..self.get_judge(
+                &cc.evaluator,
+                cc.protocol_version,
+                cc.comparison,
+                cc.epsilon,
+                Program {
+                    environment: &cc.solution_environment,
+                    code: &cc.solution_code,
+                },
+                checker_program(&cc),
+                &build_flags,
+            )
+        };
+        let examples = get_examples(
+            db,
+            &judge,
+            &self.example_limiter,
+            &self.config.load().challenges.coding_challenges,
+            user,
+            subtask.creator == user.id,
+            &cc,
+        )
+        .await;
+        // recorded outside of the transaction, so the usage of failed generations is kept
+        record_sandbox_usage(
+            &self.state.db,
+            cc.subtask_id,
+            &sandbox,
+            Utc::now().naive_utc(),
+        )
+        .await?;
+        Ok(Some(examples?))
+    }
+}
+
+/// Endpoints with breaking changes to their `/v1` counterparts.
+pub struct ApiV2 {
+    pub api: Api,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl ApiV2 {
+    /// Get the examples of a coding challenge by id together with the time
+    /// they have been generated at.
+    ///
+    /// The examples are cached until the evaluator or the solution of the
+    /// challenge is updated. Generating examples that are not cached yet is
+    /// rate limited per user and per challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/examples",
+        method = "get"
+    )]
+    async fn get_examples(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetExamplesV2::Response<VerifiedUserAuth> {
+        match self
+            .api
+            .load_examples(&db, &auth.0, task_id.0, subtask_id.0)
+            .await?
+        {
+            None => GetExamplesV2::subtask_not_found(),
+            Some(Ok(examples)) => GetExamplesV2::ok(CodingChallengeExamples {
+                examples: examples.examples,
+                generated_at: examples.generated_at,
+            }),
+            Some(Err(GetExamplesError::TooManyRequests(wait))) => {
+                GetExamplesV2::too_many_requests(wait)
+            }
+            Some(Err(GetExamplesError::EvaluatorFailed)) => GetExamplesV2::evaluator_failed(),
+            Some(Err(GetExamplesError::ExampleGenerationFailed)) => {
+                GetExamplesV2::example_generation_failed()
+            }
+            Some(Err(GetExamplesError::Judge(err))) => Err(internal_server_error(err)),
+            Some(Err(GetExamplesError::Redis(err))) => Err(internal_server_error(err)),
+            Some(Err(GetExamplesError::Database(err))) => Err(internal_server_error(err)),
+        }
+    }
 }
+
+response!(GetExamplesV2 = {
+    Ok(200) => CodingChallengeExamples,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The examples have been generated too often. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The evaluator failed to execute.
+    EvaluatorFailed(400, error),
+    /// Failed to generate an example.
+    ExampleGenerationFailed(400, error),
+});
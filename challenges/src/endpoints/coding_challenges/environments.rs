@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use entity::challenges_disabled_environments;
+use fnct::{format::JsonFormatter, key};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    Cache,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use sandkasten_client::schemas::environments::Environment;
+use schemas::challenges::coding_challenges::{
+    CodingEnvironment, DisableEnvironmentRequest, DisabledEnvironment,
+};
+use sea_orm::{sea_query::OnConflict, EntityTrait, Set};
+
+use crate::{endpoints::Tags, services::sandbox::SharedSandbox};
+
+pub struct Api {
+    pub sandkasten: SharedSandbox,
+    pub judge_cache: Cache<JsonFormatter>,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// List all environments solutions can be submitted in.
+    #[oai(path = "/coding_challenges/environments", method = "get")]
+    async fn list_environments(
+        &self,
+        db: Data<&DbTxn>,
+        _auth: VerifiedUserAuth,
+    ) -> ListEnvironments::Response<VerifiedUserAuth> {
+        let mut disabled = challenges_disabled_environments::Entity::find()
+            .all(&***db)
+            .await?
+            .into_iter()
+            .map(|x| (x.environment, x.reason))
+            .collect::<HashMap<_, _>>();
+        let mut environments = get_environments(&self.sandkasten, &self.judge_cache)
+            .await?
+            .into_iter()
+            .map(|(id, env)| {
+                let disabled_reason = disabled.remove(&id);
+                CodingEnvironment {
+                    id,
+                    name: env.name,
+                    version: env.version,
+                    disabled: disabled_reason.is_some(),
+                    disabled_reason: disabled_reason.flatten(),
+                }
+            })
+            .collect::<Vec<_>>();
+        environments.sort_by(|a, b| a.id.cmp(&b.id));
+        ListEnvironments::ok(environments)
+    }
+
+    /// Disable an environment.
+    ///
+    /// New submissions in this environment are rejected until it is enabled
+    /// again. Submissions that are already in the judge queue are still
+    /// judged.
+    #[oai(
+        path = "/coding_challenges/environments/:environment/disabled",
+        method = "put"
+    )]
+    async fn disable_environment(
+        &self,
+        environment: Path<String>,
+        data: Json<DisableEnvironmentRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> DisableEnvironment::Response<AdminAuth> {
+        if !get_environments(&self.sandkasten, &self.judge_cache)
+            .await?
+            .contains_key(&environment.0)
+        {
+            return DisableEnvironment::environment_not_found();
+        }
+
+        let disabled = challenges_disabled_environments::Entity::insert(
+            challenges_disabled_environments::ActiveModel {
+                environment: Set(environment.0),
+                reason: Set(data.0.reason),
+                creator: Set(auth.0.id),
+                timestamp: Set(Utc::now().naive_utc()),
+            },
+        )
+        .on_conflict(
+            OnConflict::column(challenges_disabled_environments::Column::Environment)
+                .update_columns([
+                    challenges_disabled_environments::Column::Reason,
+                    challenges_disabled_environments::Column::Creator,
+                    challenges_disabled_environments::Column::Timestamp,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(&***db)
+        .await?;
+        DisableEnvironment::ok(disabled.into())
+    }
+
+    /// Enable a disabled environment again.
+    #[oai(
+        path = "/coding_challenges/environments/:environment/disabled",
+        method = "delete"
+    )]
+    async fn enable_environment(
+        &self,
+        environment: Path<String>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> EnableEnvironment::Response<AdminAuth> {
+        let result = challenges_disabled_environments::Entity::delete_by_id(environment.0)
+            .exec(&***db)
+            .await?;
+        if result.rows_affected == 0 {
+            return EnableEnvironment::environment_not_disabled();
+        }
+
+        EnableEnvironment::ok()
+    }
+}
+
+response!(ListEnvironments = {
+    Ok(200) => Vec<CodingEnvironment>,
+});
+
+response!(DisableEnvironment = {
+    Ok(200) => DisabledEnvironment,
+    /// The environment does not exist.
+    EnvironmentNotFound(404, error),
+});
+
+response!(EnableEnvironment = {
+    Ok(200),
+    /// The environment is not disabled.
+    EnvironmentNotDisabled(404, error),
+});
+
+/// Load the environments from sandkasten.
+pub(super) async fn get_environments(
+    sandkasten: &SharedSandbox,
+    cache: &Cache<JsonFormatter>,
+) -> Result<HashMap<String, Environment>, ErrorResponse> {
+    Ok(cache
+        .cached_result(key!(), &[], None, || async {
+            sandkasten.list_environments().await
+        })
+        .await??)
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_coding_challenges, challenges_evaluator_versions,
+};
+use lib::auth::AdminAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use schemas::challenges::coding_challenges::{DiffLine, EvaluatorVersion};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::{evaluators::diff_lines, subtasks::get_subtask},
+};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// List all versions of the evaluator of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluator/versions",
+        method = "get"
+    )]
+    async fn list_evaluator_versions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListEvaluatorVersions::Response<AdminAuth> {
+        let Some((cc, _)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListEvaluatorVersions::subtask_not_found();
+        };
+
+        let submissions = count_submissions(&db, cc.subtask_id).await?;
+        ListEvaluatorVersions::ok(
+            cc.find_related(challenges_evaluator_versions::Entity)
+                .order_by_asc(challenges_evaluator_versions::Column::Version)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(|x| EvaluatorVersion {
+                    version: x.version,
+                    creator: x.creator,
+                    timestamp: x.timestamp.and_utc(),
+                    current: x.version == cc.evaluator_version,
+                    submissions: submissions.get(&x.version).copied().unwrap_or(0),
+                })
+                .collect(),
+        )
+    }
+
+    /// Get a specific version of the evaluator of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluator/versions/:version",
+        method = "get"
+    )]
+    async fn get_evaluator_version(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        version: Path<i32>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetEvaluatorVersion::Response<AdminAuth> {
+        if get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+            .await?
+            .is_none()
+        {
+            return GetEvaluatorVersion::subtask_not_found();
+        }
+
+        match get_version(&db, subtask_id.0, version.0).await? {
+            Some(x) => GetEvaluatorVersion::ok(x.evaluator),
+            None => GetEvaluatorVersion::version_not_found(),
+        }
+    }
+
+    /// Compare two versions of the evaluator of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluator/diff",
+        method = "get"
+    )]
+    async fn diff_evaluator_versions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// The older version.
+        from: Query<i32>,
+        /// The newer version. Defaults to the current version.
+        to: Query<Option<i32>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DiffEvaluatorVersions::Response<AdminAuth> {
+        let Some((cc, _)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return DiffEvaluatorVersions::subtask_not_found();
+        };
+
+        let Some(from) = get_version(&db, cc.subtask_id, from.0).await? else {
+            return DiffEvaluatorVersions::version_not_found();
+        };
+        let to = match to.0 {
+            Some(to) => match get_version(&db, cc.subtask_id, to).await? {
+                Some(x) => x.evaluator,
+                None => return DiffEvaluatorVersions::version_not_found(),
+            },
+            None => cc.evaluator,
+        };
+
+        DiffEvaluatorVersions::ok(diff_lines(&from.evaluator, &to))
+    }
+}
+
+response!(ListEvaluatorVersions = {
+    Ok(200) => Vec<EvaluatorVersion>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(GetEvaluatorVersion = {
+    Ok(200) => String,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Evaluator version does not exist.
+    VersionNotFound(404, error),
+});
+
+response!(DiffEvaluatorVersions = {
+    Ok(200) => Vec<DiffLine>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Evaluator version does not exist.
+    VersionNotFound(404, error),
+});
+
+async fn get_version(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+    version: i32,
+) -> Result<Option<challenges_evaluator_versions::Model>, DbErr> {
+    challenges_evaluator_versions::Entity::find_by_id((subtask_id, version))
+        .one(db)
+        .await
+}
+
+/// Return the number of submissions that have been judged against each version
+/// of the evaluator of a coding challenge.
+async fn count_submissions(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+) -> Result<HashMap<i32, u64>, DbErr> {
+    Ok(challenges_coding_challenge_result::Entity::find()
+        .select_only()
+        .column(challenges_coding_challenge_result::Column::EvaluatorVersion)
+        .column_as(
+            Expr::col(challenges_coding_challenge_result::Column::SubmissionId).count(),
+            "submissions",
+        )
+        .inner_join(challenges_coding_challenge_submissions::Entity)
+        .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(subtask_id))
+        .filter(challenges_coding_challenge_result::Column::EvaluatorVersion.is_not_null())
+        .group_by(challenges_coding_challenge_result::Column::EvaluatorVersion)
+        .into_tuple::<(i32, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(version, count)| (version, count as _))
+        .collect())
+}
@@ -1,15 +1,12 @@
 use std::sync::Arc;
 
-use entity::challenges_coding_challenges;
+use entity::{challenges_coding_challenges, sea_orm_active_enums::ChallengesComparison};
 use fnct::{format::JsonFormatter, key};
-use lib::{auth::VerifiedUserAuth, config::Config, Cache, SharedState};
+use lib::{auth::VerifiedUserAuth, config::SharedConfig, Cache, SharedState};
 use poem::web::Data;
 use poem_ext::{db::DbTxn, response};
 use poem_openapi::{param::Path, payload::Json, OpenApi};
-use sandkasten_client::{
-    schemas::{environments::ListEnvironmentsResponse, programs::RunResult},
-    SandkastenClient,
-};
+use sandkasten_client::schemas::{environments::ListEnvironmentsResponse, programs::RunResult};
 use schemas::challenges::coding_challenges::{CheckResult, ExecutorConfig, SubmissionContent};
 use tracing::error;
 use uuid::Uuid;
@@ -17,15 +14,20 @@ use uuid::Uuid;
 use crate::{
     endpoints::Tags,
     services::{
-        judge::{self, get_executor_config, Judge},
-        subtasks::{check_hearts, get_subtask},
+        build_configs::{get_build_flags, BuildFlags},
+        judge::{
+            self, checker_program, get_executor_config, has_duplicate_files, solution_size, Judge,
+            Program,
+        },
+        sandbox::SharedSandbox,
+        subtasks::{can_access_subtask, check_hearts, get_subtask},
     },
 };
 
 pub struct Api {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
-    pub sandkasten: SandkastenClient,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
     pub judge_cache: Cache<JsonFormatter>,
 }
 
@@ -51,18 +53,43 @@ impl Api {
         else {
             return TestExample::example_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return TestExample::example_not_found();
         }
 
-        if !check_hearts(&self.state.services, &self.config, &auth.0, &subtask).await? {
+        if !check_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
             return TestExample::not_enough_hearts();
         }
+        if has_duplicate_files(&data.0.files) {
+            return TestExample::duplicate_file_name();
+        }
+        if solution_size(&data.0.code, &data.0.files)
+            > self
+                .config
+                .load()
+                .challenges
+                .coding_challenges
+                .max_submission_size
+        {
+            return TestExample::submission_too_large();
+        }
 
-        let judge = self.get_judge(&cc.evaluator);
+        let build_flags = get_build_flags(&***db, cc.subtask_id).await?;
+        let judge = self.get_judge(
+            &cc.evaluator,
+            cc.protocol_version,
+            cc.comparison,
+            cc.epsilon,
+            Program {
+                environment: &cc.solution_environment,
+                code: &cc.solution_code,
+            },
+            checker_program(&cc),
+            &build_flags,
+        );
 
         let examples = match judge.examples().await {
-            Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
+            Err(err) if err.is_evaluator_error() => {
                 error!(
                     "evaluator for {} failed to execute while listing examples: {:?}",
                     subtask_id.0, err
@@ -76,7 +103,7 @@ impl Api {
         }
 
         let inp = match judge.generate(&example_id.0).await {
-            Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
+            Err(err) if err.is_evaluator_error() => {
                 error!(
                     "evaluator for {} failed to execute while generating example input for {}: \
                      {:?}",
@@ -93,12 +120,13 @@ impl Api {
                 &inp,
                 &data.0.environment,
                 &data.0.code,
+                &data.0.files,
                 Some(cc.time_limit as _),
                 Some(cc.memory_limit as _),
             )
             .await
         {
-            Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
+            Err(err) if err.is_evaluator_error() => {
                 error!(
                     "evaluator for {} failed to execute while testing submission for example {}: \
                      {:?}",
@@ -136,7 +164,7 @@ impl Api {
     /// Return the config of the code execution engine.
     #[oai(path = "/executor/config", method = "get")]
     async fn get_config(&self, _auth: VerifiedUserAuth) -> GetConfig::Response<VerifiedUserAuth> {
-        GetConfig::ok(get_executor_config(&self.judge_cache, &self.sandkasten).await?)
+        GetConfig::ok(get_executor_config(&self.judge_cache, &*self.sandkasten).await?)
     }
 }
 
@@ -150,6 +178,10 @@ response!(TestExample = {
     NotEnoughHearts(403, error),
     /// The evaluator failed to execute.
     EvaluatorFailed(400, error),
+    /// Multiple files have the same name.
+    DuplicateFileName(400, error),
+    /// The files of the submission exceed the maximum size.
+    SubmissionTooLarge(400, error),
 });
 
 response!(ListEnvironments = {
@@ -163,10 +195,26 @@ response!(GetConfig = {
 });
 
 impl Api {
-    fn get_judge<'a>(&'a self, evaluator: &'a str) -> Judge<'a> {
+    #[allow(clippy::too_many_arguments)]
+    fn get_judge<'a>(
+        &'a self,
+        evaluator: &'a str,
+        protocol_version: i32,
+        comparison: ChallengesComparison,
+        epsilon: Option<f64>,
+        reference: Program<'a>,
+        checker: Option<Program<'a>>,
+        build_flags: &'a BuildFlags,
+    ) -> Judge<'a> {
         Judge {
-            sandkasten: &self.sandkasten,
+            sandkasten: &*self.sandkasten,
             evaluator,
+            protocol_version,
+            comparison: comparison.into(),
+            epsilon,
+            reference,
+            checker,
+            build_flags,
             cache: &self.judge_cache,
         }
     }
@@ -1,77 +1,195 @@
 use std::sync::Arc;
 
+use entity::sea_orm_active_enums::ChallengesVerdict;
 use fnct::format::JsonFormatter;
-use lib::{config::Config, Cache, SharedState};
+use lib::{config::SharedConfig, Cache, SharedState};
 use poem_ext::response;
 use poem_openapi::{Object, OpenApi};
-use sandkasten_client::{
-    schemas::programs::{BuildRunResult, RunResult},
-    SandkastenClient,
+use sandkasten_client::schemas::programs::{BuildRunResult, File, ResourceUsage, RunResult};
+use schemas::challenges::coding_challenges::{
+    CheckReport, CheckReportTest, CheckResult, CheckTestKind, EvaluatorProtocolError,
 };
-use schemas::challenges::coding_challenges::CheckResult;
-use tokio::sync::Semaphore;
 use uuid::Uuid;
 
-use crate::services::judge::{Error as JudgeError, Judge};
+use crate::services::{
+    example_limiter::ExampleLimiter,
+    judge::{CheckedExample, Error as JudgeError, Judge},
+    judge_limiter::JudgeLimiter,
+    sandbox::SharedSandbox,
+};
 
 mod assets;
+mod build_configs;
 mod challenges;
+mod environments;
+mod evaluators;
 mod judge;
+mod outputs;
+mod solutions;
 pub mod submissions;
 
 pub struct CodingChallenges {
     pub state: Arc<SharedState>,
-    pub sandkasten: SandkastenClient,
+    pub sandkasten: SharedSandbox,
     pub judge_cache: Cache<JsonFormatter>,
-    pub judge_lock: Arc<Semaphore>,
-    pub config: Arc<Config>,
+    pub judge_limiter: JudgeLimiter,
+    pub example_limiter: ExampleLimiter,
+    pub config: SharedConfig,
 }
 
 impl CodingChallenges {
     pub async fn setup_api(self) -> anyhow::Result<impl OpenApi> {
         Ok((
             assets::Api,
+            build_configs::Api {
+                config: self.config.clone(),
+            },
             challenges::Api {
                 sandkasten: self.sandkasten.clone(),
                 judge_cache: self.judge_cache.clone(),
-                config: Arc::clone(&self.config),
+                example_limiter: self.example_limiter,
+                config: self.config.clone(),
                 state: Arc::clone(&self.state),
             },
+            environments::Api {
+                sandkasten: self.sandkasten.clone(),
+                judge_cache: self.judge_cache.clone(),
+            },
+            evaluators::Api,
             judge::Api {
                 state: Arc::clone(&self.state),
-                config: Arc::clone(&self.config),
+                config: self.config.clone(),
+                sandkasten: self.sandkasten.clone(),
+                judge_cache: self.judge_cache.clone(),
+            },
+            outputs::Api {
+                state: Arc::clone(&self.state),
+                config: self.config.clone(),
                 sandkasten: self.sandkasten.clone(),
                 judge_cache: self.judge_cache.clone(),
             },
+            solutions::Api,
             submissions::Api {
                 config: self.config,
                 state: self.state,
                 sandkasten: self.sandkasten,
                 judge_cache: self.judge_cache,
-                reward_lock: Default::default(),
-                queue_positions: Arc::new(
-                    QueuePositions::new(self.judge_lock.available_permits()).into(),
-                ),
-                judge_lock: self.judge_lock,
-            }
-            .setup_api()
-            .await?,
+                judge_limiter: self.judge_limiter,
+            },
         ))
     }
 }
 
-async fn check_challenge(
+/// Endpoints of coding challenges with breaking changes to their `/v1`
+/// counterparts.
+pub struct CodingChallengesV2 {
+    pub state: Arc<SharedState>,
+    pub sandkasten: SharedSandbox,
+    pub judge_cache: Cache<JsonFormatter>,
+    pub example_limiter: ExampleLimiter,
+    pub config: SharedConfig,
+}
+
+impl CodingChallengesV2 {
+    pub fn setup_api(self) -> impl OpenApi {
+        challenges::ApiV2 {
+            api: challenges::Api {
+                sandkasten: self.sandkasten,
+                judge_cache: self.judge_cache,
+                example_limiter: self.example_limiter,
+                config: self.config,
+                state: self.state,
+            },
+        }
+    }
+}
+
+/// Run the solution against all tests of the challenge. Returns the resources
+/// the solution used in each test.
+pub(crate) async fn check_challenge(
+    check: CheckChallenge<'_>,
+) -> Result<Result<Vec<ResourceUsage>, CheckError>, JudgeError> {
+    let tests = match run_tests(check, true).await? {
+        Ok(tests) => tests,
+        Err(err) => return Ok(Err(err)),
+    };
+    let mut resource_usage = Vec::with_capacity(tests.len());
+    for test in tests {
+        match test.result {
+            Ok(checked) => resource_usage.push(checked.resource_usage),
+            Err(result) => {
+                return Ok(Err(CheckError::TestcaseFailed(CheckTestcaseError {
+                    seed: test.seed,
+                    result,
+                })));
+            }
+        }
+    }
+
+    Ok(Ok(resource_usage))
+}
+
+/// Run the solution against all tests of the challenge and report the result
+/// of each test, including the failed ones.
+async fn check_challenge_report(
+    check: CheckChallenge<'_>,
+) -> Result<Result<CheckReport, CheckError>, JudgeError> {
+    let tests = match run_tests(check, false).await? {
+        Ok(tests) => tests,
+        Err(err) => return Ok(Err(err)),
+    };
+    let tests = tests
+        .into_iter()
+        .map(|test| {
+            let (verdict, reason, usage) = match test.result {
+                Ok(checked) => (ChallengesVerdict::Ok, None, Some(checked.resource_usage)),
+                Err(result) => (
+                    result.verdict,
+                    result.reason,
+                    result.run.map(|run| run.resource_usage),
+                ),
+            };
+            CheckReportTest {
+                seed: test.seed,
+                kind: test.kind,
+                passed: verdict == ChallengesVerdict::Ok,
+                verdict,
+                reason,
+                time: usage.as_ref().map(|x| x.time),
+                memory: usage.map(|x| x.memory),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Ok(CheckReport {
+        passed: tests.iter().all(|test| test.passed),
+        tests,
+    }))
+}
+
+/// The result of running the solution against a single test.
+struct TestRun {
+    seed: String,
+    kind: CheckTestKind,
+    result: Result<CheckedExample, CheckResult<RunResult>>,
+}
+
+/// Run the solution against the tests of the challenge. If `stop_on_failure`
+/// is set, no more tests are run after the first failed test.
+async fn run_tests(
     CheckChallenge {
         judge,
         challenge_id,
         solution_environment,
         solution_code,
+        solution_files,
         time_limit,
         memory_limit,
         static_tests,
         random_tests,
     }: CheckChallenge<'_>,
-) -> Result<Result<(), CheckError>, JudgeError> {
+    stop_on_failure: bool,
+) -> Result<Result<Vec<TestRun>, CheckError>, JudgeError> {
     let examples = match judge.examples().await {
         Err(JudgeError::EvaluatorFailed(err)) => {
             return Ok(Err(CheckError::EvaluatorFailed(err)));
@@ -79,22 +197,30 @@ async fn check_challenge(
         Err(JudgeError::InvalidOutput(err)) => {
             return Ok(Err(CheckError::InvalidOutput(err)));
         }
+        Err(JudgeError::ProtocolViolation(err)) => {
+            return Ok(Err(CheckError::ProtocolViolation(*err)));
+        }
         x => x?,
     };
     if examples.is_empty() {
         return Ok(Err(CheckError::NoExamples));
     }
 
-    for seed in examples
+    let mut tests = Vec::new();
+    for (kind, seed) in examples
         .into_iter()
-        .chain((0..static_tests).map(|x| format!("_static_{x}_{challenge_id}")))
-        .chain((0..random_tests).map(|_| Uuid::new_v4().to_string()))
+        .map(|x| (CheckTestKind::Example, x))
+        .chain(
+            (0..static_tests).map(|x| (CheckTestKind::Static, static_test_seed(challenge_id, x))),
+        )
+        .chain((0..random_tests).map(|_| (CheckTestKind::Random, Uuid::new_v4().to_string())))
     {
         let result = match judge
             .get_example_checked(
                 &seed,
                 solution_environment,
                 solution_code,
+                solution_files,
                 Some(time_limit),
                 Some(memory_limit),
             )
@@ -109,17 +235,25 @@ async fn check_challenge(
             Err(JudgeError::InvalidOutput(err)) => {
                 return Ok(Err(CheckError::InvalidOutput(err)));
             }
+            Err(JudgeError::ProtocolViolation(err)) => {
+                return Ok(Err(CheckError::ProtocolViolation(*err)));
+            }
             x => x?,
         };
-        if let Err(result) = result {
-            return Ok(Err(CheckError::TestcaseFailed(CheckTestcaseError {
-                seed: seed.clone(),
-                result,
-            })));
+        let failed = result.is_err();
+        tests.push(TestRun { seed, kind, result });
+        if failed && stop_on_failure {
+            break;
         }
     }
 
-    Ok(Ok(()))
+    Ok(Ok(tests))
+}
+
+/// Return the seed of a static test. The inputs of static tests are published
+/// for output-only challenges.
+fn static_test_seed(challenge_id: Uuid, i: u8) -> String {
+    format!("_static_{i}_{challenge_id}")
 }
 
 mod _check_error {
@@ -133,23 +267,24 @@ mod _check_error {
         EvaluatorFailed(400, error) => BuildRunResult,
         /// The evaluator failed to produce valid output.
         InvalidOutput(400, error) => BuildRunResult,
+        /// The output of the evaluator does not conform to its protocol.
+        ProtocolViolation(400, error) => EvaluatorProtocolError,
         /// The sample solution failed on a specific test case.
         TestcaseFailed(400, error) => CheckTestcaseError,
     });
 }
 use _check_error::CheckError::raw as _CheckError;
 
-use self::submissions::QueuePositions;
-
-struct CheckChallenge<'a> {
-    judge: Judge<'a>,
-    challenge_id: Uuid,
-    solution_environment: &'a str,
-    solution_code: &'a str,
-    time_limit: u64,
-    memory_limit: u64,
-    static_tests: u8,
-    random_tests: u8,
+pub(crate) struct CheckChallenge<'a> {
+    pub(crate) judge: Judge<'a>,
+    pub(crate) challenge_id: Uuid,
+    pub(crate) solution_environment: &'a str,
+    pub(crate) solution_code: &'a str,
+    pub(crate) solution_files: &'a [File],
+    pub(crate) time_limit: u64,
+    pub(crate) memory_limit: u64,
+    pub(crate) static_tests: u8,
+    pub(crate) random_tests: u8,
 }
 
 impl From<CheckError> for _CheckError::Response {
@@ -159,13 +294,14 @@ impl From<CheckError> for _CheckError::Response {
             CheckError::EnvironmentNotFound => _CheckError::environment_not_found(),
             CheckError::EvaluatorFailed(x) => _CheckError::evaluator_failed(x),
             CheckError::InvalidOutput(x) => _CheckError::invalid_output(x),
+            CheckError::ProtocolViolation(x) => _CheckError::protocol_violation(x),
             CheckError::TestcaseFailed(x) => _CheckError::testcase_failed(x),
         }
     }
 }
 
 #[derive(Debug)]
-enum CheckError {
+pub(crate) enum CheckError {
     /// The list of examples provided by the evaluator is empty.
     NoExamples,
     /// The solution environment does not exist.
@@ -174,6 +310,8 @@ enum CheckError {
     EvaluatorFailed(BuildRunResult),
     /// The evaluator failed to produce valid output.
     InvalidOutput(BuildRunResult),
+    /// The output of the evaluator does not conform to its protocol.
+    ProtocolViolation(EvaluatorProtocolError),
     /// The sample solution failed on a specific test case.
     TestcaseFailed(CheckTestcaseError),
 }
@@ -0,0 +1,414 @@
+use std::{collections::HashSet, sync::Arc};
+
+use chrono::Utc;
+use entity::{
+    challenges_coding_challenges,
+    challenges_output_submissions::{self, OutputResult, OutputResults},
+    challenges_user_subtasks,
+    sea_orm_active_enums::{ChallengesComparison, ChallengesVerdict},
+};
+use fnct::format::JsonFormatter;
+use lib::{
+    auth::VerifiedUserAuth,
+    config::SharedConfig,
+    events::{publish_event, Event},
+    Cache, SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::coding_challenges::{
+    OutputSubmission, PublishedInput, SubmitOutputsFeedback, SubmitOutputsRequest,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, ModelTrait, QueryFilter, QueryOrder, Set};
+use tracing::error;
+use uuid::Uuid;
+
+use super::static_test_seed;
+use crate::{
+    endpoints::Tags,
+    services::{
+        build_configs::{get_build_flags, BuildFlags},
+        exams::get_active_exam,
+        judge::{checker_program, Judge, Program},
+        notifications::notify_subtask_solved,
+        revisions::get_current_revision,
+        sandbox::SharedSandbox,
+        subtasks::{
+            can_access_subtask, deduct_hearts, get_subtask, get_user_subtask, query_subtask,
+            send_task_rewards, update_user_subtask, SolveKind, UserSubtaskExt,
+        },
+        task_config::get_timeout,
+    },
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
+    pub judge_cache: Cache<JsonFormatter>,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// Get the published inputs of an output-only coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/inputs",
+        method = "get"
+    )]
+    async fn get_inputs(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetInputs::Response<VerifiedUserAuth> {
+        let Some(cc) = query_subtask::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+            false,
+        )
+        .await?
+        else {
+            return GetInputs::subtask_not_found();
+        };
+        if !cc.output_only {
+            return GetInputs::not_output_only();
+        }
+
+        let build_flags = get_build_flags(&***db, cc.subtask_id).await?;
+        let judge = self.get_judge(
+            &cc.evaluator,
+            cc.protocol_version,
+            cc.comparison,
+            cc.epsilon,
+            Program {
+                environment: &cc.solution_environment,
+                code: &cc.solution_code,
+            },
+            checker_program(&cc),
+            &build_flags,
+        );
+        let mut out = Vec::with_capacity(cc.static_tests as _);
+        for i in 0..cc.static_tests as u8 {
+            let id = static_test_seed(cc.subtask_id, i);
+            let input = match judge.generate(&id).await {
+                Err(err) if err.is_evaluator_error() => {
+                    error!(
+                        "evaluator for {} failed to execute while generating input {}: {:?}",
+                        subtask_id.0, id, err
+                    );
+                    return GetInputs::evaluator_failed();
+                }
+                x => x?,
+            };
+            out.push(PublishedInput {
+                id,
+                input: input.input,
+            });
+        }
+
+        GetInputs::ok(out)
+    }
+
+    /// List all output submissions of the user for an output-only coding
+    /// challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/outputs",
+        method = "get"
+    )]
+    async fn list_output_submissions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListOutputSubmissions::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListOutputSubmissions::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return ListOutputSubmissions::subtask_not_found();
+        }
+
+        ListOutputSubmissions::ok(
+            cc.find_related(challenges_output_submissions::Entity)
+                .filter(challenges_output_submissions::Column::Creator.eq(auth.0.id))
+                .order_by_desc(challenges_output_submissions::Column::CreationTimestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Upload the outputs for the published inputs of an output-only coding
+    /// challenge.
+    ///
+    /// Every output is checked by the evaluator. Inputs are credited
+    /// individually, and the challenge is solved as soon as an accepted output
+    /// has been uploaded for every input, possibly across several submissions.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/outputs",
+        method = "post"
+    )]
+    async fn submit_outputs(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SubmitOutputsRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SubmitOutputs::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return SubmitOutputs::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SubmitOutputs::subtask_not_found();
+        }
+        if !cc.output_only {
+            return SubmitOutputs::not_output_only();
+        }
+        if get_active_exam(&db, subtask.task_id).await?.is_some() {
+            return SubmitOutputs::exam_running();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.coding_challenges.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return SubmitOutputs::too_many_requests(time_left as u64);
+            }
+        }
+
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SubmitOutputs::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
+            return SubmitOutputs::not_enough_hearts();
+        }
+
+        let build_flags = get_build_flags(&***db, cc.subtask_id).await?;
+        let judge = self.get_judge(
+            &cc.evaluator,
+            cc.protocol_version,
+            cc.comparison,
+            cc.epsilon,
+            Program {
+                environment: &cc.solution_environment,
+                code: &cc.solution_code,
+            },
+            checker_program(&cc),
+            &build_flags,
+        );
+        let mut results = Vec::with_capacity(cc.static_tests as _);
+        for i in 0..cc.static_tests as u8 {
+            let input_id = static_test_seed(cc.subtask_id, i);
+            let Some(output) = data.0.outputs.iter().find(|x| x.input_id == input_id) else {
+                results.push(OutputResult {
+                    input_id,
+                    verdict: ChallengesVerdict::NoOutput,
+                    reason: None,
+                });
+                continue;
+            };
+            let result = match judge.check_output(&input_id, &output.output).await {
+                Err(err) if err.is_evaluator_error() => {
+                    error!(
+                        "evaluator for {} failed to execute while checking output for {}: {:?}",
+                        subtask_id.0, input_id, err
+                    );
+                    return SubmitOutputs::evaluator_failed();
+                }
+                x => x?,
+            };
+            results.push(OutputResult {
+                input_id,
+                verdict: result.verdict,
+                reason: result.reason,
+            });
+        }
+
+        // after a re-solve period only submissions since the last solve count
+        let solved_at = user_subtask.solved_at();
+        let mut credited = accepted_inputs(&results);
+        for previous in cc
+            .find_related(challenges_output_submissions::Entity)
+            .filter(challenges_output_submissions::Column::Creator.eq(auth.0.id))
+            .all(&***db)
+            .await?
+            .into_iter()
+            .filter(|x| solved_at.map_or(true, |s| x.creation_timestamp.and_utc() > s))
+        {
+            credited.extend(accepted_inputs(&previous.results.0));
+        }
+        credited.retain(|x| results.iter().any(|r| &r.input_id == x));
+        let solved = credited.len() == results.len();
+
+        let now = Utc::now().naive_utc();
+        if let Some(solve_kind) = solve_kind {
+            if solved {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(now)),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+
+                if auth.0.id != subtask.creator {
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
+                }
+            } else {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+        }
+
+        let submission = challenges_output_submissions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            subtask_id: Set(cc.subtask_id),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(now),
+            passed: Set(accepted_inputs(&results).len() as _),
+            total: Set(results.len() as _),
+            results: Set(OutputResults(results)),
+            revision_id: Set(get_current_revision(&db, cc.subtask_id).await?),
+        }
+        .insert(&***db)
+        .await?;
+
+        SubmitOutputs::ok(SubmitOutputsFeedback {
+            submission: submission.into(),
+            credited: credited.len() as _,
+            solved,
+        })
+    }
+}
+
+response!(GetInputs = {
+    Ok(200) => Vec<PublishedInput>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The challenge is not an output-only challenge.
+    NotOutputOnly(400, error),
+    /// The evaluator failed to execute.
+    EvaluatorFailed(400, error),
+});
+
+response!(ListOutputSubmissions = {
+    Ok(200) => Vec<OutputSubmission>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(SubmitOutputs = {
+    Ok(201) => SubmitOutputsFeedback,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The challenge is not an output-only challenge.
+    NotOutputOnly(400, error),
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+    /// The evaluator failed to execute.
+    EvaluatorFailed(400, error),
+    /// Coding challenges cannot be submitted during an exam.
+    ExamRunning(403, error),
+});
+
+impl Api {
+    #[allow(clippy::too_many_arguments)]
+    fn get_judge<'a>(
+        &'a self,
+        evaluator: &'a str,
+        protocol_version: i32,
+        comparison: ChallengesComparison,
+        epsilon: Option<f64>,
+        reference: Program<'a>,
+        checker: Option<Program<'a>>,
+        build_flags: &'a BuildFlags,
+    ) -> Judge<'a> {
+        Judge {
+            sandkasten: &*self.sandkasten,
+            evaluator,
+            protocol_version,
+            comparison: comparison.into(),
+            epsilon,
+            reference,
+            checker,
+            build_flags,
+            cache: &self.judge_cache,
+        }
+    }
+}
+
+/// Return the ids of all inputs with an accepted output.
+fn accepted_inputs(results: &[OutputResult]) -> HashSet<String> {
+    results
+        .iter()
+        .filter(|x| x.verdict == ChallengesVerdict::Ok)
+        .map(|x| x.input_id.clone())
+        .collect()
+}
@@ -0,0 +1,102 @@
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_coding_challenges, sea_orm_active_enums::ChallengesVerdict,
+};
+use lib::auth::VerifiedUserAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use schemas::challenges::coding_challenges::{PublicSolution, PublicSolutions, SolutionsSort};
+use sea_orm::{
+    ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::subtasks::{can_access_subtask, get_subtask, get_user_subtask, UserSubtaskExt},
+};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// List the accepted solutions of all users for a coding challenge.
+    ///
+    /// Only available after the user has solved the challenge. Solutions are
+    /// anonymized unless their creator has opted in to attribution.
+    #[allow(clippy::too_many_arguments)]
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/solutions",
+        method = "get"
+    )]
+    async fn list_solutions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Filter by environment.
+        environment: Query<Option<String>>,
+        /// Sort by runtime or memory usage (defaults to `runtime`).
+        sort: Query<Option<SolutionsSort>>,
+        #[oai(validator(maximum(value = "100")))] limit: Query<u64>,
+        offset: Query<u64>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListSolutions::Response<VerifiedUserAuth> {
+        let Some((_, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListSolutions::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return ListSolutions::subtask_not_found();
+        }
+        if !auth.0.admin
+            && auth.0.id != subtask.creator
+            && !get_user_subtask(&db, auth.0.id, subtask.id)
+                .await?
+                .is_solved()
+        {
+            return ListSolutions::not_solved();
+        }
+
+        let order = match sort.0.unwrap_or(SolutionsSort::Runtime) {
+            SolutionsSort::Runtime => challenges_coding_challenge_result::Column::RunTime,
+            SolutionsSort::Memory => challenges_coding_challenge_result::Column::RunMemory,
+        };
+        let query = challenges_coding_challenge_submissions::Entity::find()
+            .find_also_related(challenges_coding_challenge_result::Entity)
+            .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(subtask.id))
+            .filter(challenges_coding_challenge_result::Column::Verdict.eq(ChallengesVerdict::Ok))
+            .apply_if(environment.0, |query, environment| {
+                query.filter(
+                    challenges_coding_challenge_submissions::Column::Environment.eq(environment),
+                )
+            });
+        let total = query.clone().count(&***db).await?;
+        let solutions = query
+            .order_by_asc(order)
+            .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+            .offset(offset.0)
+            .limit(limit.0)
+            .all(&***db)
+            .await?
+            .into_iter()
+            .filter_map(|(submission, result)| Some(PublicSolution::from((submission, result?))))
+            .collect();
+
+        ListSolutions::ok(PublicSolutions { solutions, total })
+    }
+}
+
+response!(ListSolutions = {
+    Ok(200) => PublicSolutions,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user has not solved this challenge yet.
+    NotSolved(403, error),
+});
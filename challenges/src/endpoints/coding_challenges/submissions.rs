@@ -1,65 +1,80 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
-use anyhow::{bail, Context};
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use entity::{
     challenges_coding_challenge_result, challenges_coding_challenge_submissions,
-    challenges_coding_challenges, challenges_subtasks, challenges_user_subtasks,
-    sea_orm_active_enums::ChallengesVerdict,
+    challenges_coding_challenges, challenges_disabled_environments, challenges_judge_queue,
+    challenges_submission_artifacts, challenges_submission_files,
+    sea_orm_active_enums::{ChallengesFeedbackLevel, ChallengesVerdict},
 };
-use fnct::{format::JsonFormatter, key};
-use key_rwlock::KeyRwLock;
+use fnct::format::JsonFormatter;
 use lib::{
     auth::{AdminAuth, VerifiedUserAuth},
-    config::Config,
+    config::SharedConfig,
     Cache, SharedState,
 };
 use poem::web::Data;
-use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
-use poem_openapi::{param::Path, payload::Json, OpenApi};
-use sandkasten_client::{schemas::environments::Environment, SandkastenClient};
-use schemas::challenges::coding_challenges::{QueueStatus, Submission, SubmissionContent};
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use sandkasten_client::schemas::programs::File;
+use schemas::challenges::coding_challenges::{
+    CreateSubmissionRequest, PendingSubmission, PendingSubmissions, QueueStatus, Rejudge,
+    Submission, SubmissionArtifacts, SubmissionContent, SubmissionStatus,
+};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
-    ModelTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+    sea_query::LockType, ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait,
+    JoinType, ModelTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
 };
-use thiserror::Error;
-use tokio::sync::{RwLock, Semaphore};
-use tracing::{debug, error, trace};
 use uuid::Uuid;
 
-use super::{check_challenge, CheckChallenge, CheckError, CheckTestcaseError};
+use super::environments::get_environments;
 use crate::{
     endpoints::Tags,
     services::{
-        judge::{self, Judge},
+        exams::get_active_exam,
+        judge::{feedback_level, has_duplicate_files, solution_size},
+        judge_limiter::JudgeLimiter,
+        judge_queue::{enqueue_submissions, get_queue_positions},
+        revisions::get_current_revision,
+        sandbox::SharedSandbox,
         subtasks::{
-            deduct_hearts, get_subtask, get_user_subtask, send_task_rewards, update_user_subtask,
-            SendTaskRewardsError, UserSubtaskExt,
+            can_access_subtask, deduct_hearts, get_subtask, get_user_subtask, UserSubtaskExt,
         },
+        task_config::get_timeout,
     },
 };
 
 pub struct Api {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
-    pub sandkasten: SandkastenClient,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
     pub judge_cache: Cache<JsonFormatter>,
-    pub judge_lock: Arc<Semaphore>,
-    pub reward_lock: Arc<KeyRwLock<(Uuid, Uuid)>>,
-    pub queue_positions: Arc<RwLock<QueuePositions>>,
+    pub judge_limiter: JudgeLimiter,
 }
 
 #[OpenApi(tag = "Tags::CodingChallenges")]
 impl Api {
     /// Return the current judge queue status.
     #[oai(path = "/coding_challenges/queue", method = "get")]
-    async fn get_queue_status(&self, _auth: AdminAuth) -> GetQueueStatus::Response<AdminAuth> {
-        let qp = self.queue_positions.read().await;
+    async fn get_queue_status(
+        &self,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetQueueStatus::Response<AdminAuth> {
+        let qp = get_queue_positions(&***db, self.judge_limiter.capacity()).await?;
+        let active = self.judge_limiter.active().await?;
+        let wait_times = self.judge_limiter.wait_times().await?;
         GetQueueStatus::ok(QueueStatus {
             workers: qp.workers(),
-            active: qp.active(),
+            active,
             waiting: qp.waiting(),
+            acquired_tokens: wait_times.acquired,
+            average_wait_time: wait_times.average().as_millis() as u64,
+            max_wait_time: wait_times.max.as_millis() as u64,
         })
     }
 
@@ -81,11 +96,12 @@ impl Api {
         else {
             return ListSubmissions::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return ListSubmissions::subtask_not_found();
         }
 
-        let queue_positions = self.queue_positions.read().await;
+        let feedback_level = feedback_level(&auth.0, subtask.creator, &cc);
+        let queue_positions = get_queue_positions(&***db, self.judge_limiter.capacity()).await?;
         ListSubmissions::ok(
             cc.find_related(challenges_coding_challenge_submissions::Entity)
                 .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
@@ -96,7 +112,7 @@ impl Api {
                 .into_iter()
                 .map(|(submission, result)| {
                     let position = queue_positions.position(submission.id);
-                    Submission::from(&submission, result.map(Into::into), position)
+                    Submission::from(&submission, result, position, feedback_level)
                 })
                 .collect(),
         )
@@ -121,7 +137,7 @@ impl Api {
         else {
             return GetSubmission::submission_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return GetSubmission::submission_not_found();
         }
 
@@ -137,13 +153,80 @@ impl Api {
             return GetSubmission::submission_not_found();
         };
 
+        let files = submission
+            .find_related(challenges_submission_files::Entity)
+            .order_by_asc(challenges_submission_files::Column::Name)
+            .all(&***db)
+            .await?
+            .into_iter()
+            .map(|file| File {
+                name: file.name,
+                content: file.content,
+            })
+            .collect();
         GetSubmission::ok(SubmissionContent {
             environment: submission.environment,
             code: submission.code,
+            files,
         })
     }
 
+    /// Get the output of a failed submission on the first failed test case.
+    ///
+    /// stdout and stderr are truncated to a maximum size.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions/:submission_id/artifacts",
+        method = "get"
+    )]
+    async fn get_submission_artifacts(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        submission_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetSubmissionArtifacts::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return GetSubmissionArtifacts::submission_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return GetSubmissionArtifacts::submission_not_found();
+        }
+
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .filter(
+                    challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id),
+                )
+                .one(&***db)
+                .await?
+        else {
+            return GetSubmissionArtifacts::submission_not_found();
+        };
+        if submission.creator != auth.0.id && !auth.0.admin {
+            return GetSubmissionArtifacts::submission_not_found();
+        }
+        if feedback_level(&auth.0, subtask.creator, &cc) != ChallengesFeedbackLevel::FullDiff {
+            return GetSubmissionArtifacts::forbidden();
+        }
+
+        match submission
+            .find_related(challenges_submission_artifacts::Entity)
+            .one(&***db)
+            .await?
+        {
+            Some(artifacts) => GetSubmissionArtifacts::ok(artifacts.into()),
+            None => GetSubmissionArtifacts::artifacts_not_found(),
+        }
+    }
+
     /// Create a submission for a coding challenge.
+    ///
+    /// The submission is added to the judge queue and judged by a judge worker
+    /// in the background.
     #[oai(
         path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions",
         method = "post"
@@ -152,7 +235,7 @@ impl Api {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
-        data: Json<SubmissionContent>,
+        data: Json<CreateSubmissionRequest>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> CreateSubmission::Response<VerifiedUserAuth> {
@@ -162,61 +245,327 @@ impl Api {
         else {
             return CreateSubmission::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return CreateSubmission::subtask_not_found();
         }
+        if cc.output_only {
+            return CreateSubmission::output_only();
+        }
+        if get_active_exam(&db, subtask.task_id).await?.is_some() {
+            return CreateSubmission::exam_running();
+        }
 
-        if !self
-            .get_environments()
+        if !get_environments(&self.sandkasten, &self.judge_cache)
             .await?
             .contains_key(&data.0.environment)
         {
             return CreateSubmission::environment_not_found();
         }
+        if let Some(disabled) =
+            challenges_disabled_environments::Entity::find_by_id(data.0.environment.clone())
+                .one(&***db)
+                .await?
+        {
+            return CreateSubmission::environment_disabled(disabled.reason);
+        }
+        if has_duplicate_files(&data.0.files) {
+            return CreateSubmission::duplicate_file_name();
+        }
+        let code_length = solution_size(&data.0.code, &data.0.files);
+        if code_length
+            > self
+                .config
+                .load()
+                .challenges
+                .coding_challenges
+                .max_submission_size
+        {
+            return CreateSubmission::submission_too_large();
+        }
+        if let Some(id) = find_duplicate_submission(
+            &db,
+            auth.0.id,
+            subtask.id,
+            &data.0,
+            self.config
+                .load()
+                .challenges
+                .coding_challenges
+                .duplicate_window,
+        )
+        .await?
+        {
+            return CreateSubmission::duplicate_submission(id);
+        }
+
+        let max_pending = self
+            .config
+            .load()
+            .challenges
+            .coding_challenges
+            .max_pending_submissions;
+        let pending = get_pending_submissions(&db, auth.0.id, max_pending).await?;
+        if pending.len() >= max_pending {
+            let queue_positions =
+                get_queue_positions(&***db, self.judge_limiter.capacity()).await?;
+            return CreateSubmission::too_many_pending_submissions(PendingSubmissions {
+                limit: max_pending,
+                submissions: pending
+                    .into_iter()
+                    .map(|(id, subtask_id)| PendingSubmission {
+                        id,
+                        subtask_id,
+                        queue_position: queue_positions.position(id),
+                    })
+                    .collect(),
+            });
+        }
 
         let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
 
         if let Some(last_attempt) = user_subtask.last_attempt() {
-            let time_left = self.config.challenges.coding_challenges.timeout as i64
-                - (Utc::now() - last_attempt).num_seconds();
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.coding_challenges.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
             if time_left > 0 {
                 return CreateSubmission::too_many_requests(time_left as u64);
             }
         }
 
-        if !deduct_hearts(&self.state.services, &self.config, &auth.0, &subtask).await? {
+        if user_subtask.attempts_exhausted(&subtask) {
+            return CreateSubmission::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
             return CreateSubmission::not_enough_hearts();
         }
 
-        let submission = Arc::new(
-            challenges_coding_challenge_submissions::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                subtask_id: Set(cc.subtask_id),
-                creator: Set(auth.0.id),
-                creation_timestamp: Set(Utc::now().naive_utc()),
-                environment: Set(data.0.environment),
-                code: Set(data.0.code),
-            }
-            .insert(&***db)
-            .await?,
-        );
-
-        let position = start_judge_submission_task(StartJudgeSubmissionTask {
-            submission: Arc::clone(&submission),
-            subtask,
-            judge_lock: Arc::clone(&self.judge_lock),
-            db: self.state.db.clone(),
-            sandkasten: self.sandkasten.clone(),
-            cache: self.judge_cache.clone(),
-            reward_lock: Arc::clone(&self.reward_lock),
-            state: Arc::clone(&self.state),
-            challenge: Arc::new(cc),
-            user_subtask,
-            queue_positions: Arc::clone(&self.queue_positions),
+        let submission = challenges_coding_challenge_submissions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            subtask_id: Set(cc.subtask_id),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            environment: Set(data.0.environment),
+            code_length: Set(code_length as _),
+            rewarded: Set(false),
+            code: Set(data.0.code),
+            attributed: Set(data.0.attributed),
+            revision_id: Set(get_current_revision(&db, cc.subtask_id).await?),
+        }
+        .insert(&***db)
+        .await?;
+        if !data.0.files.is_empty() {
+            challenges_submission_files::Entity::insert_many(data.0.files.into_iter().map(
+                |file| challenges_submission_files::ActiveModel {
+                    submission_id: Set(submission.id),
+                    name: Set(file.name),
+                    content: Set(file.content),
+                },
+            ))
+            .exec_without_returning(&***db)
+            .await?;
+        }
+
+        enqueue_submissions(&***db, [submission.id], false).await?;
+
+        let feedback_level = feedback_level(&auth.0, subtask.creator, &cc);
+        let position = get_queue_positions(&***db, self.judge_limiter.capacity())
+            .await?
+            .position(submission.id);
+        CreateSubmission::accepted(Submission::from(
+            &submission,
+            None,
+            position,
+            feedback_level,
+        ))
+    }
+
+    /// Judge all submissions of a coding challenge again that have been judged
+    /// against an older version of the evaluator.
+    ///
+    /// The previous results are replaced as soon as the new ones are
+    /// available. Users who pass with the current evaluator receive the
+    /// rewards if they have not solved the challenge before. Solved challenges
+    /// are never marked as unsolved again.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluator/rejudge",
+        method = "post"
+    )]
+    async fn rejudge_submissions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> RejudgeSubmissions::Response<AdminAuth> {
+        let Some((cc, _)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return RejudgeSubmissions::subtask_not_found();
+        };
+
+        let submissions = cc
+            .find_related(challenges_coding_challenge_submissions::Entity)
+            .inner_join(challenges_coding_challenge_result::Entity)
+            .filter(
+                challenges_coding_challenge_result::Column::EvaluatorVersion
+                    .lt(cc.evaluator_version)
+                    .or(challenges_coding_challenge_result::Column::EvaluatorVersion.is_null()),
+            )
+            .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+            .all(&***db)
+            .await?;
+        let submission_ids = submissions.iter().map(|x| x.id).collect::<Vec<_>>();
+        challenges_coding_challenge_result::Entity::delete_many()
+            .filter(
+                challenges_coding_challenge_result::Column::SubmissionId
+                    .is_in(submission_ids.clone()),
+            )
+            .exec(&***db)
+            .await?;
+        challenges_submission_artifacts::Entity::delete_many()
+            .filter(
+                challenges_submission_artifacts::Column::SubmissionId.is_in(submission_ids.clone()),
+            )
+            .exec(&***db)
+            .await?;
+        let count = submission_ids.len();
+        enqueue_submissions(&***db, submission_ids, true).await?;
+
+        RejudgeSubmissions::ok(Rejudge {
+            submissions: count as _,
         })
-        .await;
+    }
 
-        CreateSubmission::ok(Submission::from(&submission, None, Some(position)))
+    /// List the submissions of all coding challenges with the given status,
+    /// oldest first.
+    ///
+    /// `older_than` only includes submissions that have been created at least
+    /// this many seconds ago.
+    #[oai(path = "/admin/submissions", method = "get")]
+    async fn list_admin_submissions(
+        &self,
+        status: Query<SubmissionStatus>,
+        older_than: Query<Option<u64>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListAdminSubmissions::Response<AdminAuth> {
+        let mut query = challenges_coding_challenge_submissions::Entity::find()
+            .find_also_related(challenges_coding_challenge_result::Entity)
+            .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp);
+        query = match status.0 {
+            SubmissionStatus::Pending => query.join(
+                JoinType::InnerJoin,
+                challenges_coding_challenge_submissions::Relation::ChallengesJudgeQueue.def(),
+            ),
+            SubmissionStatus::Cancelled => query.filter(
+                challenges_coding_challenge_result::Column::Verdict
+                    .eq(ChallengesVerdict::Cancelled),
+            ),
+            SubmissionStatus::Failed => query.filter(
+                challenges_coding_challenge_result::Column::Verdict
+                    .eq(ChallengesVerdict::InternalError),
+            ),
+        };
+        if let Some(older_than) = older_than.0 {
+            let before =
+                Utc::now().naive_utc() - Duration::seconds(older_than.min(u32::MAX as _) as _);
+            query = query.filter(
+                challenges_coding_challenge_submissions::Column::CreationTimestamp.lte(before),
+            );
+        }
+
+        let queue_positions = get_queue_positions(&***db, self.judge_limiter.capacity()).await?;
+        ListAdminSubmissions::ok(
+            query
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(|(submission, result)| {
+                    let position = queue_positions.position(submission.id);
+                    Submission::from(
+                        &submission,
+                        result,
+                        position,
+                        ChallengesFeedbackLevel::FullDiff,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Cancel a pending submission.
+    ///
+    /// The submission is removed from the judge queue and marked as
+    /// cancelled, which also frees the pending submission slot of its
+    /// creator. If the submission is currently being judged, the judge worker
+    /// discards its result.
+    #[oai(path = "/admin/submissions/:submission_id/cancel", method = "post")]
+    async fn cancel_submission(
+        &self,
+        submission_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> CancelSubmission::Response<AdminAuth> {
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .one(&***db)
+                .await?
+        else {
+            return CancelSubmission::submission_not_found();
+        };
+
+        let Some(entry) = challenges_judge_queue::Entity::find_by_id(submission.id)
+            .lock(LockType::Update)
+            .one(&***db)
+            .await?
+        else {
+            return CancelSubmission::submission_not_pending();
+        };
+        entry.delete(&***db).await?;
+
+        challenges_coding_challenge_result::Entity::delete_by_id(submission.id)
+            .exec(&***db)
+            .await?;
+        challenges_submission_artifacts::Entity::delete_by_id(submission.id)
+            .exec(&***db)
+            .await?;
+        let result = challenges_coding_challenge_result::ActiveModel {
+            submission_id: Set(submission.id),
+            verdict: Set(ChallengesVerdict::Cancelled),
+            reason: Set(Some(
+                "The submission has been cancelled by an admin.".into(),
+            )),
+            build_status: Set(None),
+            build_stderr: Set(None),
+            build_time: Set(None),
+            build_memory: Set(None),
+            run_status: Set(None),
+            run_stderr: Set(None),
+            run_time: Set(None),
+            run_memory: Set(None),
+            transcript: Set(None),
+            evaluator_version: Set(None),
+            seed: Set(None),
+            score: Set(None),
+            resources: Set(None),
+            personal_best_time: Set(false),
+            personal_best_memory: Set(false),
+        }
+        .insert(&***db)
+        .await?;
+
+        CancelSubmission::ok(Submission::from(
+            &submission,
+            Some(result),
+            None,
+            ChallengesFeedbackLevel::FullDiff,
+        ))
     }
 }
 
@@ -236,449 +585,130 @@ response!(GetSubmission = {
     SubmissionNotFound(404, error),
 });
 
+response!(GetSubmissionArtifacts = {
+    Ok(200) => SubmissionArtifacts,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+    /// The submission has not been judged yet or did not fail on a test case.
+    ArtifactsNotFound(404, error),
+    /// The feedback level of the challenge does not reveal the output of the solution.
+    Forbidden(403, error),
+});
+
 response!(CreateSubmission = {
-    Ok(201) => Submission,
+    /// The submission has been added to the judge queue.
+    Accepted(202) => Submission,
     /// Try again later. `details` contains the number of seconds to wait.
     TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
     /// The solution environment does not exist.
     EnvironmentNotFound(404, error),
+    /// The solution environment has been disabled by an admin. `details` contains the reason, if any.
+    EnvironmentDisabled(403, error) => Option<String>,
     /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
     NotEnoughHearts(403, error),
+    /// The challenge does not accept code. Upload the outputs for the published inputs instead.
+    OutputOnly(400, error),
+    /// Coding challenges cannot be submitted during an exam.
+    ExamRunning(403, error),
+    /// Multiple files have the same name.
+    DuplicateFileName(400, error),
+    /// The files of the submission exceed the maximum size.
+    SubmissionTooLarge(400, error),
+    /// The submission is identical to the previous submission of the user,
+    /// which has been created only a moment ago. `details` contains the id of
+    /// the previous submission.
+    DuplicateSubmission(409, error) => Uuid,
+    /// The user already has the maximum number of submissions waiting to be
+    /// judged. `details` contains the pending submissions.
+    TooManyPendingSubmissions(429, error) => PendingSubmissions,
 });
 
-struct StartJudgeSubmissionTask {
-    submission: Arc<challenges_coding_challenge_submissions::Model>,
-    subtask: challenges_subtasks::Model,
-    judge_lock: Arc<Semaphore>,
-    db: DatabaseConnection,
-    sandkasten: SandkastenClient,
-    cache: Cache<JsonFormatter>,
-    reward_lock: Arc<KeyRwLock<(Uuid, Uuid)>>,
-    state: Arc<SharedState>,
-    challenge: Arc<challenges_coding_challenges::Model>,
-    user_subtask: Option<challenges_user_subtasks::Model>,
-    queue_positions: Arc<RwLock<QueuePositions>>,
-}
-
-async fn start_judge_submission_task(
-    StartJudgeSubmissionTask {
-        submission,
-        judge_lock,
-        db,
-        sandkasten,
-        cache,
-        reward_lock,
-        state,
-        challenge: cc,
-        queue_positions,
-        subtask,
-        user_subtask,
-    }: StartJudgeSubmissionTask,
-) -> usize {
-    let position = queue_positions.write().await.push(submission.id);
-    trace!(
-        "submission {} enqueued at position {}",
-        submission.id,
-        position
-    );
-    tokio::spawn({
-        async move {
-            let submission_id = submission.id;
-            let pop = || async {
-                if !queue_positions.write().await.pop(submission_id) {
-                    error!("judge task for {submission_id} failed to pop queue position");
-                }
-            };
-            let Ok(_guard) = judge_lock.acquire().await else {
-                error!("judge task for {submission_id} failed to acquire lock",);
-                // don't pop here since we didn't get the semaphore permit
-                return;
-            };
-            let db = match db.begin().await {
-                Ok(x) => x,
-                Err(err) => {
-                    error!("judge task for {submission_id} failed to start db transaction: {err}",);
-                    pop().await;
-                    return;
-                }
-            };
-            let judge = Judge {
-                sandkasten: &sandkasten,
-                evaluator: &cc.evaluator,
-                cache: &cache,
-            };
-            if let Err(err) = judge_submission(JudgeSubmission {
-                db: &db,
-                subtask: &subtask,
-                challenge: &cc,
-                submission,
-                judge,
-                reward_lock,
-                state,
-                user_subtask,
-            })
-            .await
-            {
-                error!("judge task for {submission_id} failed: {err}");
-                db.rollback().await.ok();
-            } else if let Err(err) = db.commit().await {
-                error!("judge task for {submission_id} failed to commit db transaction: {err}");
-            }
-            pop().await;
-        }
-    });
-
-    position
-}
-
-struct JudgeSubmission<'a, 'b> {
-    db: &'a DatabaseTransaction,
-    subtask: &'a challenges_subtasks::Model,
-    challenge: &'a challenges_coding_challenges::Model,
-    submission: Arc<challenges_coding_challenge_submissions::Model>,
-    judge: Judge<'b>,
-    reward_lock: Arc<KeyRwLock<(Uuid, Uuid)>>,
-    state: Arc<SharedState>,
-    user_subtask: Option<challenges_user_subtasks::Model>,
-}
-
-async fn judge_submission(
-    JudgeSubmission {
-        db,
-        subtask,
-        challenge,
-        submission,
-        judge,
-        reward_lock,
-        state,
-        user_subtask,
-    }: JudgeSubmission<'_, '_>,
-) -> Result<(), JudgeSubmissionError> {
-    debug!("judging submission {}", submission.id);
-    let result = check_challenge(CheckChallenge {
-        judge,
-        challenge_id: challenge.subtask_id,
-        solution_environment: &submission.environment,
-        solution_code: &submission.code,
-        time_limit: challenge.time_limit as _,
-        memory_limit: challenge.memory_limit as _,
-        static_tests: challenge.static_tests as _,
-        random_tests: challenge.random_tests as _,
-    })
-    .await?;
-    trace!("judge result for {}: {result:?}", submission.id);
-    match result {
-        Ok(()) => {
-            let _guard = reward_lock
-                .write((submission.subtask_id, submission.creator))
-                .await;
-
-            let solved_previously = user_subtask.is_solved();
-            if !solved_previously {
-                update_user_subtask(
-                    db,
-                    user_subtask.as_ref(),
-                    challenges_user_subtasks::ActiveModel {
-                        user_id: Set(submission.creator),
-                        subtask_id: Set(subtask.id),
-                        solved_timestamp: Set(Some(submission.creation_timestamp)),
-                        last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
-                        attempts: Set(user_subtask.attempts() as i32 + 1),
-                        ..Default::default()
-                    },
-                )
-                .await?;
-
-                if submission.creator != subtask.creator {
-                    send_task_rewards(&state.services, db, submission.creator, subtask).await?;
-                }
-            }
-            challenges_coding_challenge_result::ActiveModel {
-                submission_id: Set(submission.id),
-                verdict: Set(ChallengesVerdict::Ok),
-                reason: Set(None),
-                build_status: Set(None),
-                build_stderr: Set(None),
-                build_time: Set(None),
-                build_memory: Set(None),
-                run_status: Set(None),
-                run_stderr: Set(None),
-                run_time: Set(None),
-                run_memory: Set(None),
-            }
-            .insert(db)
-            .await?;
-        }
-        Err(CheckError::TestcaseFailed(CheckTestcaseError { result, .. })) => {
-            let (build_status, build_stderr, build_time, build_memory) = match result.compile {
-                Some(x) => (
-                    Some(x.status),
-                    Some(x.stderr),
-                    Some(x.resource_usage.time as _),
-                    Some(x.resource_usage.memory as _),
-                ),
-                None => (None, None, None, None),
-            };
-            let (run_status, run_stderr, run_time, run_memory) = match result.run {
-                Some(x) => (
-                    Some(x.status),
-                    Some(x.stderr),
-                    Some(x.resource_usage.time as _),
-                    Some(x.resource_usage.memory as _),
-                ),
-                None => (None, None, None, None),
-            };
-            update_user_subtask(
-                db,
-                user_subtask.as_ref(),
-                challenges_user_subtasks::ActiveModel {
-                    user_id: Set(submission.creator),
-                    subtask_id: Set(subtask.id),
-                    last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
-                    attempts: Set(user_subtask.attempts() as i32 + 1),
-                    ..Default::default()
-                },
-            )
-            .await?;
-            challenges_coding_challenge_result::ActiveModel {
-                submission_id: Set(submission.id),
-                verdict: Set(result.verdict),
-                reason: Set(result.reason),
-                build_status: Set(build_status),
-                build_stderr: Set(build_stderr),
-                build_time: Set(build_time),
-                build_memory: Set(build_memory),
-                run_status: Set(run_status),
-                run_stderr: Set(run_stderr),
-                run_time: Set(run_time),
-                run_memory: Set(run_memory),
-            }
-            .insert(db)
-            .await?;
-        }
-        Err(err) => return Err(JudgeSubmissionError::Check(Box::new(err))),
-    }
-
-    Ok(())
-}
-
-#[derive(Debug, Error)]
-enum JudgeSubmissionError {
-    #[error("failed to judge submission: {0}")]
-    Judge(#[from] judge::Error),
-    #[error("database error: {0}")]
-    Db(#[from] DbErr),
-    #[error("check error: {0:?}")]
-    Check(Box<CheckError>),
-    #[error("could not send task rewards: {0}")]
-    TaskRewards(#[from] SendTaskRewardsError),
-}
-
-impl Api {
-    async fn get_environments(&self) -> Result<HashMap<String, Environment>, ErrorResponse> {
-        Ok(self
-            .judge_cache
-            .cached_result(key!(), &[], None, || async {
-                self.sandkasten.list_environments().await
-            })
-            .await??)
-    }
-
-    pub async fn setup_api(self) -> anyhow::Result<Self> {
-        self.resume_judge()
-            .await
-            .context("failed to resume judge")?;
-        Ok(self)
-    }
-
-    pub async fn resume_judge(&self) -> anyhow::Result<()> {
-        debug!("resuming judge");
-        let db = &self.state.db;
-
-        let subtasks = challenges_subtasks::Entity::find()
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|x| (x.id, x))
-            .collect::<HashMap<_, _>>();
-        let coding_challenges = challenges_coding_challenges::Entity::find()
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|x| (x.subtask_id, Arc::new(x)))
-            .collect::<HashMap<_, _>>();
-        let user_subtasks = challenges_user_subtasks::Entity::find()
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|x| ((x.user_id, x.subtask_id), x))
-            .collect::<HashMap<_, _>>();
-        let submissions = challenges_coding_challenge_submissions::Entity::find()
-            .left_join(challenges_coding_challenge_result::Entity)
-            .filter(challenges_coding_challenge_result::Column::SubmissionId.is_null())
-            .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
-            .all(db)
-            .await?;
-
-        debug!("found {} submission(s) to judge", submissions.len());
-        for submission in submissions {
-            let Some(subtask) = subtasks.get(&submission.subtask_id) else {
-                bail!(
-                    "failed to find subtask {} for submission {}",
-                    submission.subtask_id,
-                    submission.id
-                );
-            };
-            let Some(challenge) = coding_challenges.get(&submission.subtask_id) else {
-                bail!(
-                    "failed to find coding challenge {} for submission {}",
-                    submission.subtask_id,
-                    submission.id
-                );
-            };
-            let user_subtask = user_subtasks.get(&(submission.creator, submission.subtask_id));
-            start_judge_submission_task(StartJudgeSubmissionTask {
-                submission: Arc::new(submission),
-                subtask: subtask.clone(),
-                judge_lock: Arc::clone(&self.judge_lock),
-                db: db.clone(),
-                sandkasten: self.sandkasten.clone(),
-                cache: self.judge_cache.clone(),
-                reward_lock: Arc::clone(&self.reward_lock),
-                state: Arc::clone(&self.state),
-                challenge: Arc::clone(challenge),
-                user_subtask: user_subtask.cloned(),
-                queue_positions: Arc::clone(&self.queue_positions),
-            })
-            .await;
-        }
-
-        Ok(())
-    }
-}
-
-pub struct QueuePositions {
-    workers: usize,
-    counter: usize,
-    done: usize,
-    ids: HashMap<Uuid, usize>,
-}
-
-impl QueuePositions {
-    pub fn new(workers: usize) -> Self {
-        Self {
-            workers,
-            counter: 0,
-            done: 0,
-            ids: HashMap::new(),
-        }
-    }
-
-    pub fn workers(&self) -> usize {
-        self.workers
-    }
-
-    pub fn active(&self) -> usize {
-        self.workers.min(self.counter - self.done)
-    }
-
-    pub fn waiting(&self) -> usize {
-        self.id_position(self.counter)
-    }
-
-    pub fn push(&mut self, key: Uuid) -> usize {
-        let id = *self.ids.entry(key).or_insert_with(|| {
-            self.counter += 1;
-            self.counter
-        });
-        self.id_position(id)
-    }
+response!(RejudgeSubmissions = {
+    Ok(200) => Rejudge,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
 
-    pub fn pop(&mut self, key: Uuid) -> bool {
-        if !self
-            .ids
-            .get(&key)
-            .is_some_and(|&x| self.id_position(x) == 0)
-        {
-            return false;
-        }
+response!(ListAdminSubmissions = {
+    Ok(200) => Vec<Submission>,
+});
 
-        self.ids.remove(&key);
-        self.done += 1;
-        true
-    }
+response!(CancelSubmission = {
+    Ok(200) => Submission,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+    /// The submission is not in the judge queue.
+    SubmissionNotPending(409, error),
+});
 
-    pub fn position(&self, key: Uuid) -> Option<usize> {
-        let id = *self.ids.get(&key)?;
-        Some(self.id_position(id))
+/// Return the id of the latest submission of a user if it has been created
+/// within the last `window` seconds and is identical to the new one.
+async fn find_duplicate_submission(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+    data: &CreateSubmissionRequest,
+    window: u64,
+) -> Result<Option<Uuid>, DbErr> {
+    let since = Utc::now() - Duration::seconds(window as _);
+    let Some(previous) = challenges_coding_challenge_submissions::Entity::find()
+        .filter(challenges_coding_challenge_submissions::Column::Creator.eq(user_id))
+        .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(subtask_id))
+        .filter(
+            challenges_coding_challenge_submissions::Column::CreationTimestamp
+                .gte(since.naive_utc()),
+        )
+        .order_by_desc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    if previous.environment != data.environment || previous.code != data.code {
+        return Ok(None);
     }
 
-    fn id_position(&self, id: usize) -> usize {
-        id.saturating_sub(self.workers + self.done)
-    }
+    let previous_files = previous
+        .find_related(challenges_submission_files::Entity)
+        .all(db)
+        .await?;
+    let mut previous_files = previous_files
+        .iter()
+        .map(|file| (file.name.as_str(), file.content.as_str()))
+        .collect::<Vec<_>>();
+    let mut files = data
+        .files
+        .iter()
+        .map(|file| (file.name.as_str(), file.content.as_str()))
+        .collect::<Vec<_>>();
+    previous_files.sort_unstable();
+    files.sort_unstable();
+    Ok((previous_files == files).then_some(previous.id))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn queue_positions() {
-        let mut qp = QueuePositions::new(3);
-        assert_eq!(qp.workers(), 3);
-        let key = Uuid::from_u128;
-        assert_eq!((qp.active(), qp.waiting()), (0, 0));
-        qp.push(key(0));
-        assert_eq!((qp.active(), qp.waiting()), (1, 0));
-        qp.push(key(1));
-        assert_eq!((qp.active(), qp.waiting()), (2, 0));
-        qp.push(key(2));
-        assert_eq!((qp.active(), qp.waiting()), (3, 0));
-        qp.push(key(3));
-        assert_eq!((qp.active(), qp.waiting()), (3, 1));
-        qp.push(key(4));
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        qp.push(key(5));
-        assert_eq!((qp.active(), qp.waiting()), (3, 3));
-        assert_eq!(qp.position(key(0)), Some(0));
-        assert_eq!(qp.position(key(1)), Some(0));
-        assert_eq!(qp.position(key(2)), Some(0));
-        assert_eq!(qp.position(key(3)), Some(1));
-        assert_eq!(qp.position(key(4)), Some(2));
-        assert_eq!(qp.position(key(5)), Some(3));
-
-        // cannot pop pending keys
-        assert!(!qp.pop(key(3)));
-        assert!(!qp.pop(key(4)));
-        assert!(!qp.pop(key(5)));
-        assert_eq!((qp.active(), qp.waiting()), (3, 3));
-
-        assert!(qp.pop(key(1)));
-        assert_eq!(qp.position(key(0)), Some(0));
-        assert_eq!(qp.position(key(1)), None);
-        assert_eq!(qp.position(key(2)), Some(0));
-        assert_eq!(qp.position(key(3)), Some(0));
-        assert_eq!(qp.position(key(4)), Some(1));
-        assert_eq!(qp.position(key(5)), Some(2));
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        assert!(!qp.pop(key(1))); // already popped
-
-        assert!(qp.pop(key(2)));
-        assert_eq!(qp.position(key(0)), Some(0));
-        assert_eq!(qp.position(key(1)), None);
-        assert_eq!(qp.position(key(2)), None);
-        assert_eq!(qp.position(key(3)), Some(0));
-        assert_eq!(qp.position(key(4)), Some(0));
-        assert_eq!(qp.position(key(5)), Some(1));
-        assert_eq!((qp.active(), qp.waiting()), (3, 1));
-
-        assert_eq!(qp.push(key(6)), 2);
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        assert_eq!(qp.push(key(6)), 2); // push is idempotent
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        assert_eq!(qp.push(key(7)), 3);
-        assert_eq!((qp.active(), qp.waiting()), (3, 3));
-    }
+/// Return the id and subtask id of up to `limit` submissions of the user that
+/// have not been judged yet, oldest first.
+async fn get_pending_submissions(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    limit: usize,
+) -> Result<Vec<(Uuid, Uuid)>, DbErr> {
+    challenges_coding_challenge_submissions::Entity::find()
+        .left_join(challenges_coding_challenge_result::Entity)
+        .filter(challenges_coding_challenge_submissions::Column::Creator.eq(user_id))
+        .filter(challenges_coding_challenge_result::Column::SubmissionId.is_null())
+        .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+        .select_only()
+        .columns([
+            challenges_coding_challenge_submissions::Column::Id,
+            challenges_coding_challenge_submissions::Column::SubtaskId,
+        ])
+        .limit(limit as u64)
+        .into_tuple()
+        .all(db)
+        .await
 }
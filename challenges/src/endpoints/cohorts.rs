@@ -0,0 +1,470 @@
+use chrono::Utc;
+use entity::{challenges_cohort_assignments, challenges_cohort_members, challenges_cohorts};
+use lib::auth::VerifiedUserAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::cohorts::{
+    Cohort, CohortAssignment, CohortMember, CohortProgress, CreateCohortRequest, JoinCohortRequest,
+    SetCohortAssignmentRequest,
+};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, Condition, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    cohorts::{
+        can_manage_cohort, can_view_cohort, generate_invite_code, get_cohort_progress,
+        normalize_invite_code,
+    },
+    organizations::{can_see_task, task_id_scope},
+    tasks::get_task,
+};
+
+pub struct Cohorts;
+
+#[OpenApi(tag = "Tags::Cohorts")]
+impl Cohorts {
+    /// List all cohorts the user has created or is a member of.
+    #[oai(path = "/cohorts", method = "get")]
+    async fn list_cohorts(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListCohorts::Response<VerifiedUserAuth> {
+        let member_of = challenges_cohort_members::Entity::find()
+            .filter(challenges_cohort_members::Column::UserId.eq(auth.0.id))
+            .all(&***db)
+            .await?;
+        ListCohorts::ok(
+            challenges_cohorts::Entity::find()
+                .filter(
+                    Condition::any()
+                        .add(challenges_cohorts::Column::Creator.eq(auth.0.id))
+                        .add(
+                            challenges_cohorts::Column::Id
+                                .is_in(member_of.iter().map(|x| x.cohort_id)),
+                        ),
+                )
+                .order_by_asc(challenges_cohorts::Column::CreationTimestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(|cohort| {
+                    let show_invite_code = can_manage_cohort(&auth.0, &cohort);
+                    Cohort::from(cohort, show_invite_code)
+                })
+                .collect(),
+        )
+    }
+
+    /// Get a cohort by id.
+    #[oai(path = "/cohorts/:cohort_id", method = "get")]
+    async fn get_cohort(
+        &self,
+        cohort_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCohort::Response<VerifiedUserAuth> {
+        match challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        {
+            Some(cohort) if can_view_cohort(&db, &auth.0, &cohort).await? => {
+                let show_invite_code = can_manage_cohort(&auth.0, &cohort);
+                GetCohort::ok(Cohort::from(cohort, show_invite_code))
+            }
+            _ => GetCohort::cohort_not_found(),
+        }
+    }
+
+    /// Create a new cohort.
+    #[oai(path = "/cohorts", method = "post")]
+    async fn create_cohort(
+        &self,
+        data: Json<CreateCohortRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateCohort::Response<VerifiedUserAuth> {
+        let cohort = challenges_cohorts::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            title: Set(data.0.title),
+            invite_code: Set(generate_invite_code()),
+        }
+        .insert(&***db)
+        .await?;
+        CreateCohort::created(Cohort::from(cohort, true))
+    }
+
+    /// Delete a cohort.
+    #[oai(path = "/cohorts/:cohort_id", method = "delete")]
+    async fn delete_cohort(
+        &self,
+        cohort_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DeleteCohort::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return DeleteCohort::cohort_not_found();
+        };
+        if !can_manage_cohort(&auth.0, &cohort) {
+            return DeleteCohort::forbidden();
+        }
+        cohort.delete(&***db).await?;
+        DeleteCohort::ok()
+    }
+
+    /// Replace the invite code of a cohort. The previous code becomes invalid.
+    #[oai(path = "/cohorts/:cohort_id/invite_code", method = "post")]
+    async fn reset_invite_code(
+        &self,
+        cohort_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ResetInviteCode::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ResetInviteCode::cohort_not_found();
+        };
+        if !can_manage_cohort(&auth.0, &cohort) {
+            return ResetInviteCode::forbidden();
+        }
+        let cohort = challenges_cohorts::ActiveModel {
+            id: Unchanged(cohort.id),
+            invite_code: Set(generate_invite_code()),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+        ResetInviteCode::ok(Cohort::from(cohort, true))
+    }
+
+    /// Join a cohort using its invite code.
+    ///
+    /// Joining a cohort again does not change the membership.
+    #[oai(path = "/cohorts/join", method = "post")]
+    async fn join_cohort(
+        &self,
+        data: Json<JoinCohortRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> JoinCohort::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find()
+            .filter(
+                challenges_cohorts::Column::InviteCode
+                    .eq(normalize_invite_code(&data.0.invite_code)),
+            )
+            .one(&***db)
+            .await?
+        else {
+            return JoinCohort::invalid_invite_code();
+        };
+        challenges_cohort_members::Entity::insert(challenges_cohort_members::ActiveModel {
+            cohort_id: Set(cohort.id),
+            user_id: Set(auth.0.id),
+            join_timestamp: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                challenges_cohort_members::Column::CohortId,
+                challenges_cohort_members::Column::UserId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&***db)
+        .await?;
+        let show_invite_code = can_manage_cohort(&auth.0, &cohort);
+        JoinCohort::ok(Cohort::from(cohort, show_invite_code))
+    }
+
+    /// List all members of a cohort.
+    #[oai(path = "/cohorts/:cohort_id/members", method = "get")]
+    async fn list_cohort_members(
+        &self,
+        cohort_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListCohortMembers::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ListCohortMembers::cohort_not_found();
+        };
+        if !can_manage_cohort(&auth.0, &cohort) {
+            return ListCohortMembers::forbidden();
+        }
+        ListCohortMembers::ok(
+            cohort
+                .find_related(challenges_cohort_members::Entity)
+                .order_by_asc(challenges_cohort_members::Column::JoinTimestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Remove a member from a cohort.
+    ///
+    /// Members can remove themselves to leave the cohort.
+    #[oai(path = "/cohorts/:cohort_id/members/:user_id", method = "delete")]
+    async fn remove_cohort_member(
+        &self,
+        cohort_id: Path<Uuid>,
+        user_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> RemoveCohortMember::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return RemoveCohortMember::cohort_not_found();
+        };
+        if !(user_id.0 == auth.0.id || can_manage_cohort(&auth.0, &cohort)) {
+            return RemoveCohortMember::forbidden();
+        }
+        let Some(member) = challenges_cohort_members::Entity::find_by_id((cohort.id, user_id.0))
+            .one(&***db)
+            .await?
+        else {
+            return RemoveCohortMember::member_not_found();
+        };
+        member.delete(&***db).await?;
+        RemoveCohortMember::ok()
+    }
+
+    /// List all tasks assigned to a cohort.
+    #[oai(path = "/cohorts/:cohort_id/assignments", method = "get")]
+    async fn list_cohort_assignments(
+        &self,
+        cohort_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListCohortAssignments::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ListCohortAssignments::cohort_not_found();
+        };
+        if !can_view_cohort(&db, &auth.0, &cohort).await? {
+            return ListCohortAssignments::cohort_not_found();
+        }
+        ListCohortAssignments::ok(
+            cohort
+                .find_related(challenges_cohort_assignments::Entity)
+                .filter(task_id_scope(
+                    challenges_cohort_assignments::Column::TaskId,
+                    &auth.0,
+                ))
+                .order_by_asc(challenges_cohort_assignments::Column::CreationTimestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Assign a task to a cohort or change the due date of an assignment.
+    #[oai(path = "/cohorts/:cohort_id/assignments/:task_id", method = "put")]
+    async fn set_cohort_assignment(
+        &self,
+        cohort_id: Path<Uuid>,
+        task_id: Path<Uuid>,
+        data: Json<SetCohortAssignmentRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SetCohortAssignment::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return SetCohortAssignment::cohort_not_found();
+        };
+        if !can_manage_cohort(&auth.0, &cohort) {
+            return SetCohortAssignment::forbidden();
+        }
+        if !get_task(&db, task_id.0)
+            .await?
+            .is_some_and(|task| can_see_task(&auth.0, &task))
+        {
+            return SetCohortAssignment::task_not_found();
+        }
+
+        let assignment = challenges_cohort_assignments::Entity::insert(
+            challenges_cohort_assignments::ActiveModel {
+                cohort_id: Set(cohort.id),
+                task_id: Set(task_id.0),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                due: Set(data.0.due.map(|x| x.naive_utc())),
+            },
+        )
+        .on_conflict(
+            OnConflict::columns([
+                challenges_cohort_assignments::Column::CohortId,
+                challenges_cohort_assignments::Column::TaskId,
+            ])
+            .update_column(challenges_cohort_assignments::Column::Due)
+            .to_owned(),
+        )
+        .exec_with_returning(&***db)
+        .await?;
+        SetCohortAssignment::ok(assignment.into())
+    }
+
+    /// Remove a task from the assignments of a cohort.
+    #[oai(path = "/cohorts/:cohort_id/assignments/:task_id", method = "delete")]
+    async fn delete_cohort_assignment(
+        &self,
+        cohort_id: Path<Uuid>,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DeleteCohortAssignment::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return DeleteCohortAssignment::cohort_not_found();
+        };
+        if !can_manage_cohort(&auth.0, &cohort) {
+            return DeleteCohortAssignment::forbidden();
+        }
+        let Some(assignment) =
+            challenges_cohort_assignments::Entity::find_by_id((cohort.id, task_id.0))
+                .one(&***db)
+                .await?
+        else {
+            return DeleteCohortAssignment::assignment_not_found();
+        };
+        assignment.delete(&***db).await?;
+        DeleteCohortAssignment::ok()
+    }
+
+    /// Return the progress of all members of a cohort on the subtasks of all
+    /// assigned tasks.
+    ///
+    /// The result is a matrix with one row per member and one column per
+    /// enabled subtask.
+    #[oai(path = "/cohorts/:cohort_id/progress", method = "get")]
+    async fn get_cohort_progress(
+        &self,
+        cohort_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCohortProgress::Response<VerifiedUserAuth> {
+        let Some(cohort) = challenges_cohorts::Entity::find_by_id(cohort_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return GetCohortProgress::cohort_not_found();
+        };
+        if !can_manage_cohort(&auth.0, &cohort) {
+            return GetCohortProgress::forbidden();
+        }
+        GetCohortProgress::ok(get_cohort_progress(&db, &auth.0, &cohort).await?)
+    }
+}
+
+response!(ListCohorts = {
+    Ok(200) => Vec<Cohort>,
+});
+
+response!(GetCohort = {
+    Ok(200) => Cohort,
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+});
+
+response!(CreateCohort = {
+    Created(201) => Cohort,
+});
+
+response!(DeleteCohort = {
+    Ok(200),
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to delete this cohort.
+    Forbidden(403, error),
+});
+
+response!(ResetInviteCode = {
+    Ok(200) => Cohort,
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to manage this cohort.
+    Forbidden(403, error),
+});
+
+response!(JoinCohort = {
+    Ok(200) => Cohort,
+    /// There is no cohort with this invite code.
+    InvalidInviteCode(404, error),
+});
+
+response!(ListCohortMembers = {
+    Ok(200) => Vec<CohortMember>,
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to manage this cohort.
+    Forbidden(403, error),
+});
+
+response!(RemoveCohortMember = {
+    Ok(200),
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to manage this cohort.
+    Forbidden(403, error),
+    /// The user is not a member of this cohort.
+    MemberNotFound(404, error),
+});
+
+response!(ListCohortAssignments = {
+    Ok(200) => Vec<CohortAssignment>,
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+});
+
+response!(SetCohortAssignment = {
+    Ok(200) => CohortAssignment,
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to manage this cohort.
+    Forbidden(403, error),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+});
+
+response!(DeleteCohortAssignment = {
+    Ok(200),
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to manage this cohort.
+    Forbidden(403, error),
+    /// The task has not been assigned to this cohort.
+    AssignmentNotFound(404, error),
+});
+
+response!(GetCohortProgress = {
+    Ok(200) => CohortProgress,
+    /// Cohort does not exist.
+    CohortNotFound(404, error),
+    /// The user is not allowed to manage this cohort.
+    Forbidden(403, error),
+});
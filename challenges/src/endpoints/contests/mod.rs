@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::challenges_contests;
+use lib::auth::{AdminAuth, User, VerifiedUserAuth};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::contests::{Contest, CreateContestRequest, Scoreboard};
+use sea_orm::{
+    ActiveModelTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    contests::{get_scoreboard, ScoreboardHub},
+    organizations::{can_see_task, task_id_scope},
+    tasks::get_task,
+};
+
+pub mod ws;
+
+pub struct Contests {
+    pub scoreboard: Arc<ScoreboardHub>,
+}
+
+#[OpenApi(tag = "Tags::Contests")]
+impl Contests {
+    /// List all contests of the tasks the user can see.
+    #[oai(path = "/contests", method = "get")]
+    async fn list_contests(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListContests::Response<VerifiedUserAuth> {
+        ListContests::ok(
+            challenges_contests::Entity::find()
+                .filter(task_id_scope(challenges_contests::Column::TaskId, &auth.0))
+                .order_by_desc(challenges_contests::Column::Start)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Get a contest by id.
+    #[oai(path = "/contests/:contest_id", method = "get")]
+    async fn get_contest(
+        &self,
+        contest_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetContest::Response<VerifiedUserAuth> {
+        match find_contest(&db, &auth.0, contest_id.0).await? {
+            Some(contest) => GetContest::ok(contest.into()),
+            None => GetContest::contest_not_found(),
+        }
+    }
+
+    /// Return the current scoreboard of a contest.
+    ///
+    /// To receive live updates, connect to the websocket at
+    /// `/contests/{contest_id}/scoreboard/ws` and pass the `resume_token` of
+    /// this response as query parameter.
+    #[oai(path = "/contests/:contest_id/scoreboard", method = "get")]
+    async fn get_scoreboard(
+        &self,
+        contest_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetScoreboard::Response<VerifiedUserAuth> {
+        let Some(contest) = find_contest(&db, &auth.0, contest_id.0).await? else {
+            return GetScoreboard::contest_not_found();
+        };
+        let resume_token = self.scoreboard.resume_token();
+        GetScoreboard::ok(Scoreboard {
+            entries: get_scoreboard(&***db, &contest).await?,
+            resume_token,
+        })
+    }
+
+    /// Create a new contest.
+    #[oai(path = "/contests", method = "post")]
+    async fn create_contest(
+        &self,
+        data: Json<CreateContestRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> CreateContest::Response<AdminAuth> {
+        if data.0.end <= data.0.start {
+            return CreateContest::negative_duration();
+        }
+        if !get_task(&db, data.0.task_id)
+            .await?
+            .is_some_and(|task| can_see_task(&auth.0, &task))
+        {
+            return CreateContest::task_not_found();
+        }
+
+        CreateContest::created(
+            challenges_contests::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                task_id: Set(data.0.task_id),
+                creator: Set(auth.0.id),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                title: Set(data.0.title),
+                start: Set(data.0.start.naive_utc()),
+                end: Set(data.0.end.naive_utc()),
+                reminder_sent: Set(false),
+                hold_rewards: Set(data.0.hold_rewards),
+            }
+            .insert(&***db)
+            .await?
+            .into(),
+        )
+    }
+
+    /// Delete a contest.
+    #[oai(path = "/contests/:contest_id", method = "delete")]
+    async fn delete_contest(
+        &self,
+        contest_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DeleteContest::Response<AdminAuth> {
+        let Some(contest) = challenges_contests::Entity::find_by_id(contest_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return DeleteContest::contest_not_found();
+        };
+        contest.delete(&***db).await?;
+        DeleteContest::ok()
+    }
+}
+
+/// Return a contest of a task the user can see.
+async fn find_contest(
+    db: &DatabaseTransaction,
+    user: &User,
+    contest_id: Uuid,
+) -> Result<Option<challenges_contests::Model>, DbErr> {
+    challenges_contests::Entity::find_by_id(contest_id)
+        .filter(task_id_scope(challenges_contests::Column::TaskId, user))
+        .one(db)
+        .await
+}
+
+response!(ListContests = {
+    Ok(200) => Vec<Contest>,
+});
+
+response!(GetContest = {
+    Ok(200) => Contest,
+    /// Contest does not exist.
+    ContestNotFound(404, error),
+});
+
+response!(GetScoreboard = {
+    Ok(200) => Scoreboard,
+    /// Contest does not exist.
+    ContestNotFound(404, error),
+});
+
+response!(CreateContest = {
+    Created(201) => Contest,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// `end` cannot be before `start`
+    NegativeDuration(400, error),
+});
+
+response!(DeleteContest = {
+    Ok(200),
+    /// Contest does not exist.
+    ContestNotFound(404, error),
+});
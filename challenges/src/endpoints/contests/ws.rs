@@ -0,0 +1,141 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use entity::{challenges_contests, challenges_tasks};
+use futures::{SinkExt, StreamExt};
+use lib::{auth::authenticate, SharedState};
+use poem::{
+    error::{InternalServerError, NotFoundError},
+    handler,
+    http::StatusCode,
+    web::{
+        websocket::{Message, WebSocket, WebSocketStream},
+        Data, Path, Query,
+    },
+    IntoResponse, Request,
+};
+use schemas::challenges::contests::{Scoreboard, ScoreboardMessage};
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::services::{contests::ScoreboardHub, organizations::can_see_task};
+
+/// Interval in which pings are sent to keep the connection alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize)]
+pub struct ScoreboardParams {
+    resume_token: Option<String>,
+}
+
+/// Websocket which pushes scoreboard updates of a contest.
+///
+/// Requires the same authentication as the other contest endpoints. Contests
+/// of tasks that are not visible to the user are reported as not found. On
+/// connect, the complete scoreboard is sent as `snapshot` message. If a valid
+/// `resume_token` is passed, only the entries that changed since the token was
+/// issued are sent as `update` message instead.
+#[handler]
+pub async fn scoreboard_ws(
+    req: &Request,
+    Path(contest_id): Path<Uuid>,
+    Query(params): Query<ScoreboardParams>,
+    ws: WebSocket,
+    state: Data<&Arc<SharedState>>,
+    hub: Data<&Arc<ScoreboardHub>>,
+) -> poem::Result<impl IntoResponse> {
+    let user = authenticate(req)
+        .await
+        .ok_or_else(|| poem::Error::from_status(StatusCode::UNAUTHORIZED))?;
+    if !(user.email_verified || user.admin) {
+        return Err(poem::Error::from_status(StatusCode::FORBIDDEN));
+    }
+    let contest = challenges_contests::Entity::find_by_id(contest_id)
+        .one(&state.db)
+        .await
+        .map_err(InternalServerError)?
+        .ok_or(NotFoundError)?;
+    let task = challenges_tasks::Entity::find_by_id(contest.task_id)
+        .one(&state.db)
+        .await
+        .map_err(InternalServerError)?;
+    if !task.is_some_and(|task| can_see_task(&user, &task)) {
+        return Err(NotFoundError.into());
+    }
+    let hub = Arc::clone(&hub);
+    Ok(ws.on_upgrade(move |socket| async move {
+        if let Err(err) = handle_socket(socket, hub, contest, params.resume_token).await {
+            debug!("scoreboard websocket for contest {contest_id} closed: {err}");
+        }
+    }))
+}
+
+async fn handle_socket(
+    socket: WebSocketStream,
+    hub: Arc<ScoreboardHub>,
+    contest: challenges_contests::Model,
+    resume_token: Option<String>,
+) -> anyhow::Result<()> {
+    let (mut sink, mut stream) = socket.split();
+    let mut updates = hub.subscribe();
+
+    let entries = hub.scoreboard(&contest).await?;
+    let replay = resume_token.as_deref().and_then(|token| hub.replay(token));
+    let message = match replay {
+        Some(events) => {
+            let users = events
+                .into_iter()
+                .filter(|event| event.task_id == contest.task_id)
+                .map(|event| event.user_id)
+                .collect::<HashSet<_>>();
+            ScoreboardMessage::Update {
+                entries: entries
+                    .into_iter()
+                    .filter(|entry| users.contains(&entry.user_id))
+                    .collect(),
+                resume_token: hub.resume_token(),
+            }
+        }
+        None => ScoreboardMessage::Snapshot(Scoreboard {
+            entries,
+            resume_token: hub.resume_token(),
+        }),
+    };
+    sink.send(encode(&message)?).await?;
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                sink.send(Message::Ping(Vec::new())).await?;
+            }
+            update = updates.recv() => {
+                let message = match update {
+                    Ok(update) if update.contest_id == contest.id => {
+                        Message::Text(update.message.to_string())
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(_)) => encode(&ScoreboardMessage::Snapshot(Scoreboard {
+                        entries: hub.scoreboard(&contest).await?,
+                        resume_token: hub.resume_token(),
+                    }))?,
+                    Err(RecvError::Closed) => break,
+                };
+                sink.send(message).await?;
+            }
+            message = stream.next() => match message {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Err(err)) => return Err(err.into()),
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn encode(message: &ScoreboardMessage) -> serde_json::Result<Message> {
+    serde_json::to_string(message).map(Message::Text)
+}
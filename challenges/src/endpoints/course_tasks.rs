@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use chrono::Utc;
 use entity::{challenges_course_tasks, challenges_tasks};
-use lib::{auth::VerifiedUserAuth, config::Config, services::Services, SharedState};
+use lib::{auth::VerifiedUserAuth, config::SharedConfig, services::Services, SharedState};
 use poem::web::Data;
 use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
 use poem_openapi::{
@@ -11,58 +11,22 @@ use poem_openapi::{
     OpenApi,
 };
 use schemas::challenges::course_tasks::{CourseTask, CreateCourseTaskRequest};
-use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseTransaction, EntityTrait, QueryFilter, Set,
-};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter, Set};
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::subtasks::can_create_for_course;
+use crate::services::{
+    organizations::{can_create_in_organization, can_see_task, task_scope},
+    subtasks::can_create_for_course,
+};
 
 pub struct CourseTasks {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::CourseTasks")]
 impl CourseTasks {
-    /// List all tasks in a skill.
-    #[oai(path = "/skills/:skill_id/tasks", method = "get")]
-    async fn list_tasks_in_skill(
-        &self,
-        skill_id: Path<String>,
-        db: Data<&DbTxn>,
-        _auth: VerifiedUserAuth,
-    ) -> ListTasksInSkill::Response<VerifiedUserAuth> {
-        let skill = match self
-            .state
-            .services
-            .skills
-            .get_skills()
-            .await?
-            .remove(&skill_id.0)
-        {
-            Some(skill) => skill,
-            None => return ListTasksInSkill::not_found(),
-        };
-
-        let condition = skill.courses.into_iter().fold(Condition::any(), |acc, e| {
-            acc.add(challenges_course_tasks::Column::CourseId.eq(e))
-        });
-
-        let query = challenges_course_tasks::Entity::find()
-            .find_also_related(challenges_tasks::Entity)
-            .filter(condition);
-        ListTasksInSkill::ok(
-            query
-                .all(&***db)
-                .await?
-                .into_iter()
-                .filter_map(|(challenge, task)| Some(CourseTask::from(challenge, task?)))
-                .collect(),
-        )
-    }
-
     /// List all tasks in a course.
     #[oai(path = "/courses/:course_id/tasks", method = "get")]
     async fn list_course_tasks(
@@ -73,11 +37,12 @@ impl CourseTasks {
         /// Filter by lecture id
         lecture_id: Query<Option<String>>,
         db: Data<&DbTxn>,
-        _auth: VerifiedUserAuth,
+        auth: VerifiedUserAuth,
     ) -> ListCourseTasks::Response<VerifiedUserAuth> {
         let mut query = challenges_course_tasks::Entity::find()
             .find_also_related(challenges_tasks::Entity)
-            .filter(challenges_course_tasks::Column::CourseId.eq(course_id.0));
+            .filter(challenges_course_tasks::Column::CourseId.eq(course_id.0))
+            .filter(task_scope(&auth.0));
         if let Some(section_id) = section_id.0 {
             query = query.filter(challenges_course_tasks::Column::SectionId.eq(section_id));
         }
@@ -101,11 +66,13 @@ impl CourseTasks {
         course_id: Path<String>,
         task_id: Path<Uuid>,
         db: Data<&DbTxn>,
-        _auth: VerifiedUserAuth,
+        auth: VerifiedUserAuth,
     ) -> GetCourseTask::Response<VerifiedUserAuth> {
         match get_course_task(&db, course_id.0, task_id.0).await? {
-            Some((course, task)) => GetCourseTask::ok(CourseTask::from(course, task)),
-            None => GetCourseTask::course_task_not_found(),
+            Some((course, task)) if can_see_task(&auth.0, &task) => {
+                GetCourseTask::ok(CourseTask::from(course, task))
+            }
+            _ => GetCourseTask::course_task_not_found(),
         }
     }
 
@@ -136,8 +103,19 @@ impl CourseTasks {
             Err(CourseNotFoundError::Lecture) => return CreateCourseTask::lecture_not_found(),
         }
 
-        if !can_create_for_course(&self.state.services, &self.config, &course_id.0, &auth.0).await?
-        {
+        let allowed = match auth.0.organization {
+            Some(_) => can_create_in_organization(&auth.0),
+            None => {
+                can_create_for_course(
+                    &self.state.services,
+                    &self.config.load(),
+                    &course_id.0,
+                    &auth.0,
+                )
+                .await?
+            }
+        };
+        if !allowed {
             return CreateCourseTask::forbidden();
         }
 
@@ -149,6 +127,12 @@ impl CourseTasks {
         if let Some((course_task, Some(task))) = challenges_course_tasks::Entity::find()
             .find_also_related(challenges_tasks::Entity)
             .filter(challenges_course_tasks::Column::CourseId.eq(&course_id.0))
+            .filter(match auth.0.organization_id() {
+                Some(organization_id) => {
+                    challenges_tasks::Column::OrganizationId.eq(organization_id)
+                }
+                None => challenges_tasks::Column::OrganizationId.is_null(),
+            })
             .filter(eq(
                 challenges_course_tasks::Column::SectionId,
                 data.0.section_id.as_deref(),
@@ -167,6 +151,7 @@ impl CourseTasks {
             id: Set(Uuid::new_v4()),
             creator: Set(auth.0.id),
             creation_timestamp: Set(Utc::now().naive_utc()),
+            organization_id: Set(auth.0.organization_id()),
         }
         .insert(&***db)
         .await?;
@@ -184,12 +169,6 @@ impl CourseTasks {
     }
 }
 
-response!(ListTasksInSkill = {
-    Ok(200) => Vec<CourseTask>,
-    /// Skill does not exist.
-    NotFound(404, error),
-});
-
 response!(ListCourseTasks = {
     Ok(200) => Vec<CourseTask>,
 });
@@ -233,7 +212,7 @@ async fn get_course_task(
     )
 }
 
-async fn check_course(
+pub(super) async fn check_course(
     services: &Services,
     course_id: &str,
     section_id: Option<&str>,
@@ -262,7 +241,7 @@ async fn check_course(
 }
 
 #[derive(Debug)]
-enum CourseNotFoundError {
+pub(super) enum CourseNotFoundError {
     Course,
     Section,
     Lecture,
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::challenges_exams;
+use lib::{
+    auth::{User, VerifiedUserAuth},
+    config::SharedConfig,
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::exams::{CreateExamRequest, Exam, ExamResult};
+use sea_orm::{
+    ActiveModelTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    exams::{compute_exam_results, get_exam_answers, release_exam_results},
+    organizations::{can_see_task, task_id_scope},
+    tasks::get_task,
+};
+
+pub struct Exams {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Exams")]
+impl Exams {
+    /// List all exams of the tasks the user can see.
+    #[oai(path = "/exams", method = "get")]
+    async fn list_exams(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListExams::Response<VerifiedUserAuth> {
+        ListExams::ok(
+            challenges_exams::Entity::find()
+                .filter(task_id_scope(challenges_exams::Column::TaskId, &auth.0))
+                .order_by_desc(challenges_exams::Column::Start)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Get an exam by id.
+    #[oai(path = "/exams/:exam_id", method = "get")]
+    async fn get_exam(
+        &self,
+        exam_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetExam::Response<VerifiedUserAuth> {
+        match find_exam(&db, &auth.0, exam_id.0).await? {
+            Some(exam) => GetExam::ok(exam.into()),
+            None => GetExam::exam_not_found(),
+        }
+    }
+
+    /// Create a new exam.
+    ///
+    /// While the exam is running, attempts to solve subtasks of the task are
+    /// recorded as exam answers and users do not get any feedback. Coding
+    /// challenges cannot be submitted during an exam. Only the creator of the
+    /// task and admins can create exams.
+    #[oai(path = "/exams", method = "post")]
+    async fn create_exam(
+        &self,
+        data: Json<CreateExamRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateExam::Response<VerifiedUserAuth> {
+        if data.0.end <= data.0.start {
+            return CreateExam::negative_duration();
+        }
+        let Some(task) = get_task(&db, data.0.task_id)
+            .await?
+            .filter(|task| can_see_task(&auth.0, task))
+        else {
+            return CreateExam::task_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == task.creator) {
+            return CreateExam::forbidden();
+        }
+
+        CreateExam::created(
+            challenges_exams::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                task_id: Set(task.id),
+                creator: Set(auth.0.id),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                title: Set(data.0.title),
+                start: Set(data.0.start.naive_utc()),
+                end: Set(data.0.end.naive_utc()),
+                release_timestamp: Set(None),
+            }
+            .insert(&***db)
+            .await?
+            .into(),
+        )
+    }
+
+    /// Delete an exam and all of its answers.
+    #[oai(path = "/exams/:exam_id", method = "delete")]
+    async fn delete_exam(
+        &self,
+        exam_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DeleteExam::Response<VerifiedUserAuth> {
+        let Some(exam) = find_exam(&db, &auth.0, exam_id.0).await? else {
+            return DeleteExam::exam_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == exam.creator) {
+            return DeleteExam::forbidden();
+        }
+        exam.delete(&***db).await?;
+        DeleteExam::ok()
+    }
+
+    /// Return the results of all users who have taken an exam.
+    ///
+    /// Only the creator of the exam and admins can see the results of all
+    /// users, also before they have been released.
+    #[oai(path = "/exams/:exam_id/results", method = "get")]
+    async fn get_exam_results(
+        &self,
+        exam_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetExamResults::Response<VerifiedUserAuth> {
+        let Some(exam) = find_exam(&db, &auth.0, exam_id.0).await? else {
+            return GetExamResults::exam_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == exam.creator) {
+            return GetExamResults::forbidden();
+        }
+        let answers = get_exam_answers(&db, exam.id, None).await?;
+        GetExamResults::ok(compute_exam_results(&answers))
+    }
+
+    /// Return the result of the authenticated user in an exam.
+    ///
+    /// The result is only available after it has been released.
+    #[oai(path = "/exams/:exam_id/results/me", method = "get")]
+    async fn get_own_exam_result(
+        &self,
+        exam_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetOwnExamResult::Response<VerifiedUserAuth> {
+        let Some(exam) = find_exam(&db, &auth.0, exam_id.0).await? else {
+            return GetOwnExamResult::exam_not_found();
+        };
+        if exam.release_timestamp.is_none() {
+            return GetOwnExamResult::results_not_released();
+        }
+        let answers = get_exam_answers(&db, exam.id, Some(auth.0.id)).await?;
+        GetOwnExamResult::ok(compute_exam_results(&answers).pop().unwrap_or(ExamResult {
+            user_id: auth.0.id,
+            answered: 0,
+            correct: 0,
+            answers: Vec::new(),
+        }))
+    }
+
+    /// Release the results of an exam.
+    ///
+    /// All subtasks which have been answered correctly are marked as solved
+    /// and the rewards are sent. Results can only be released once the exam
+    /// is over.
+    #[oai(path = "/exams/:exam_id/release", method = "post")]
+    async fn release_exam_results(
+        &self,
+        exam_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ReleaseExamResults::Response<VerifiedUserAuth> {
+        let Some(exam) = find_exam(&db, &auth.0, exam_id.0).await? else {
+            return ReleaseExamResults::exam_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == exam.creator) {
+            return ReleaseExamResults::forbidden();
+        }
+        let now = Utc::now();
+        if exam.end > now.naive_utc() {
+            return ReleaseExamResults::exam_not_over();
+        }
+        if exam.release_timestamp.is_some() {
+            return ReleaseExamResults::already_released();
+        }
+
+        let config = self.config.load();
+        let exam = release_exam_results(
+            &db,
+            &self.state.services,
+            &config.challenges.re_solve,
+            &config.challenges.due_dates,
+            &config.challenges.rewards,
+            &exam,
+            now,
+        )
+        .await?;
+        ReleaseExamResults::ok(exam.into())
+    }
+}
+
+/// Return an exam of a task the user can see.
+async fn find_exam(
+    db: &DatabaseTransaction,
+    user: &User,
+    exam_id: Uuid,
+) -> Result<Option<challenges_exams::Model>, DbErr> {
+    challenges_exams::Entity::find_by_id(exam_id)
+        .filter(task_id_scope(challenges_exams::Column::TaskId, user))
+        .one(db)
+        .await
+}
+
+response!(ListExams = {
+    Ok(200) => Vec<Exam>,
+});
+
+response!(GetExam = {
+    Ok(200) => Exam,
+    /// Exam does not exist.
+    ExamNotFound(404, error),
+});
+
+response!(CreateExam = {
+    Created(201) => Exam,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create exams for this task.
+    Forbidden(403, error),
+    /// `end` cannot be before `start`
+    NegativeDuration(400, error),
+});
+
+response!(DeleteExam = {
+    Ok(200),
+    /// Exam does not exist.
+    ExamNotFound(404, error),
+    /// The user is not allowed to delete this exam.
+    Forbidden(403, error),
+});
+
+response!(GetExamResults = {
+    Ok(200) => Vec<ExamResult>,
+    /// Exam does not exist.
+    ExamNotFound(404, error),
+    /// The user is not allowed to view the results of this exam.
+    Forbidden(403, error),
+});
+
+response!(GetOwnExamResult = {
+    Ok(200) => ExamResult,
+    /// Exam does not exist.
+    ExamNotFound(404, error),
+    /// The results of the exam have not been released yet.
+    ResultsNotReleased(403, error),
+});
+
+response!(ReleaseExamResults = {
+    Ok(200) => Exam,
+    /// Exam does not exist.
+    ExamNotFound(404, error),
+    /// The user is not allowed to release the results of this exam.
+    Forbidden(403, error),
+    /// The exam is still running or has not started yet.
+    ExamNotOver(409, error),
+    /// The results have already been released.
+    AlreadyReleased(409, error),
+});
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use entity::{challenges_held_rewards, sea_orm_active_enums::ChallengesHeldRewardStatus};
+use lib::{auth::AdminAuth, config::SharedConfig, SharedState};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Query, payload::Json, OpenApi};
+use schemas::challenges::held_rewards::{
+    HeldReward, ResolveHeldRewardsRequest, ResolveHeldRewardsResult,
+};
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::held_rewards::{release_held_rewards, void_held_rewards};
+
+pub struct HeldRewards {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::HeldRewards")]
+impl HeldRewards {
+    /// List rewards which have been held instead of being sent.
+    #[oai(path = "/held_rewards", method = "get")]
+    async fn list_held_rewards(
+        &self,
+        /// Filter by status.
+        status: Query<Option<ChallengesHeldRewardStatus>>,
+        /// Filter by user.
+        user_id: Query<Option<Uuid>>,
+        /// Filter by contest.
+        contest_id: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListHeldRewards::Response<AdminAuth> {
+        let mut query = challenges_held_rewards::Entity::find();
+        if let Some(status) = status.0 {
+            query = query.filter(challenges_held_rewards::Column::Status.eq(status));
+        }
+        if let Some(user_id) = user_id.0 {
+            query = query.filter(challenges_held_rewards::Column::UserId.eq(user_id));
+        }
+        if let Some(contest_id) = contest_id.0 {
+            query = query.filter(challenges_held_rewards::Column::ContestId.eq(contest_id));
+        }
+        ListHeldRewards::ok(
+            query
+                .order_by_desc(challenges_held_rewards::Column::Timestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Send all held rewards matching the given filters to the users.
+    #[oai(path = "/held_rewards/release", method = "post")]
+    async fn release_held_rewards(
+        &self,
+        data: Json<ResolveHeldRewardsRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> ReleaseHeldRewards::Response<AdminAuth> {
+        let Some(condition) = resolve_condition(data.0) else {
+            return ReleaseHeldRewards::no_filter();
+        };
+        ReleaseHeldRewards::ok(ResolveHeldRewardsResult {
+            rewards: release_held_rewards(
+                &self.state.services,
+                &db,
+                &self.config.load().challenges.rewards,
+                condition,
+                auth.0.id,
+            )
+            .await?,
+        })
+    }
+
+    /// Discard all held rewards matching the given filters.
+    #[oai(path = "/held_rewards/void", method = "post")]
+    async fn void_held_rewards(
+        &self,
+        data: Json<ResolveHeldRewardsRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> VoidHeldRewards::Response<AdminAuth> {
+        let Some(condition) = resolve_condition(data.0) else {
+            return VoidHeldRewards::no_filter();
+        };
+        VoidHeldRewards::ok(ResolveHeldRewardsResult {
+            rewards: void_held_rewards(&db, condition, auth.0.id).await?,
+        })
+    }
+}
+
+response!(ListHeldRewards = {
+    Ok(200) => Vec<HeldReward>,
+});
+
+response!(ReleaseHeldRewards = {
+    Ok(200) => ResolveHeldRewardsResult,
+    /// At least one filter has to be specified.
+    NoFilter(400, error),
+});
+
+response!(VoidHeldRewards = {
+    Ok(200) => ResolveHeldRewardsResult,
+    /// At least one filter has to be specified.
+    NoFilter(400, error),
+});
+
+fn resolve_condition(data: ResolveHeldRewardsRequest) -> Option<Condition> {
+    if data.ids.is_none() && data.user_id.is_none() && data.contest_id.is_none() {
+        return None;
+    }
+    Some(
+        Condition::all()
+            .add_option(
+                data.ids
+                    .map(|ids| challenges_held_rewards::Column::Id.is_in(ids)),
+            )
+            .add_option(
+                data.user_id
+                    .map(|user_id| challenges_held_rewards::Column::UserId.eq(user_id)),
+            )
+            .add_option(
+                data.contest_id
+                    .map(|contest_id| challenges_held_rewards::Column::ContestId.eq(contest_id)),
+            ),
+    )
+}
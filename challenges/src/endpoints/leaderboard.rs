@@ -1,5 +1,6 @@
 use std::{sync::Arc, time::Duration};
 
+use entity::challenges_coding_challenges;
 use fnct::{format::JsonFormatter, key};
 use lib::{auth::VerifiedUserAuth, Cache, SharedState};
 use poem::web::Data;
@@ -8,14 +9,20 @@ use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use schemas::challenges::leaderboard::{Leaderboard, Rank};
+use schemas::challenges::leaderboard::{
+    CodingChallengeLeaderboard, Leaderboard, LeaderboardMetric, Rank,
+};
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::leaderboard::{
-    global::{get_global_leaderboard, get_global_leaderboard_user},
-    language::{get_language_leaderboard, get_language_leaderboard_user},
-    task::{get_task_leaderboard, get_task_leaderboard_user},
+use crate::services::{
+    leaderboard::{
+        coding_challenge::get_coding_challenge_leaderboard,
+        global::{get_global_leaderboard, get_global_leaderboard_user},
+        language::{get_language_leaderboard, get_language_leaderboard_user},
+        task::{get_task_leaderboard, get_task_leaderboard_user},
+    },
+    subtasks::{can_access_subtask, get_subtask},
 };
 
 pub struct LeaderboardEndpoints {
@@ -133,6 +140,60 @@ impl LeaderboardEndpoints {
             .await??;
         GetLanguageLeaderboardUser::ok(rank)
     }
+
+    /// Rank the best accepted submission of each user of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/leaderboard",
+        method = "get"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn get_coding_challenge_leaderboard(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// The metric to rank the submissions by.
+        metric: Query<LeaderboardMetric>,
+        /// Only include submissions in this environment.
+        environment: Query<Option<String>>,
+        #[oai(validator(maximum(value = "100")))] limit: Query<u64>,
+        offset: Query<u64>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetCodingChallengeLeaderboard::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return GetCodingChallengeLeaderboard::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return GetCodingChallengeLeaderboard::subtask_not_found();
+        }
+        if metric.0 == LeaderboardMetric::Size && !cc.golf {
+            return GetCodingChallengeLeaderboard::not_golf();
+        }
+
+        let leaderboard = self
+            .cache
+            .cached_result(
+                key!(subtask.id, metric.0, &environment.0, limit.0, offset.0),
+                &[],
+                Some(Duration::from_secs(10)),
+                || {
+                    get_coding_challenge_leaderboard(
+                        &db,
+                        &self.state.services,
+                        subtask.id,
+                        metric.0,
+                        environment.0.as_deref(),
+                        limit.0,
+                        offset.0,
+                    )
+                },
+            )
+            .await??;
+        GetCodingChallengeLeaderboard::ok(leaderboard)
+    }
 }
 
 response!(GetLeaderboard = {
@@ -158,3 +219,11 @@ response!(GetLanguageLeaderboard = {
 response!(GetLanguageLeaderboardUser = {
     Ok(200) => Rank,
 });
+
+response!(GetCodingChallengeLeaderboard = {
+    Ok(200) => CodingChallengeLeaderboard,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The `size` metric is only available for golf challenges.
+    NotGolf(400, error),
+});
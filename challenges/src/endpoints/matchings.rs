@@ -7,7 +7,8 @@ use entity::{
 };
 use lib::{
     auth::{AdminAuth, VerifiedUserAuth},
-    config::Config,
+    config::SharedConfig,
+    events::{publish_event, Event},
     SharedState,
 };
 use poem::web::Data;
@@ -17,24 +18,38 @@ use poem_openapi::{
     payload::Json,
     OpenApi,
 };
-use schemas::challenges::matchings::{
-    CreateMatchingRequest, Matching, MatchingSummary, MatchingWithSolution, SolveMatchingFeedback,
-    SolveMatchingRequest, UpdateMatchingRequest,
+use schemas::challenges::{
+    exams::ExamAnswer,
+    matchings::{
+        CreateMatchingRequest, Matching, MatchingSummary, MatchingWithSolution,
+        SolveMatchingFeedback, SolveMatchingRequest, UpdateMatchingRequest,
+    },
 };
 use sea_orm::{ActiveModelTrait, Set, Unchanged};
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::subtasks::{
-    create_subtask, deduct_hearts, get_subtask, get_user_subtask, query_subtask,
-    query_subtask_admin, query_subtasks, send_task_rewards, update_subtask, update_user_subtask,
-    CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter, UpdateSubtaskError,
-    UserSubtaskExt,
+use crate::services::{
+    exams::{get_active_exam, record_exam_answer},
+    notifications::notify_subtask_solved,
+    revisions::{
+        get_current_revision, query_subtask_as_of, record_revision, QuerySubtaskAsOfError,
+    },
+    similarity::{find_duplicates, matching_fingerprint, set_fingerprint},
+    solve_tokens::{check_solve_token, issue_solve_token, SolveTokenError},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
+    wrong_answers::{matching_mistakes, record_mistakes},
 };
 
 pub struct Matchings {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::Matchings")]
@@ -73,6 +88,7 @@ impl Matchings {
                     retired: retired.0,
                     creator: creator.0,
                     ty: None,
+                    permissive: None,
                 },
                 MatchingSummary::from,
             )
@@ -86,19 +102,48 @@ impl Matchings {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the matching as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetMatching::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetMatching::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_matchings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                Matching::from,
+            )
+            .await?
+            {
+                Ok(x) => GetMatching::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetMatching::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetMatching::revision_not_found(),
+            };
+        }
+
         match query_subtask::<challenges_matchings::Entity, _>(
             &db,
             &auth.0,
             task_id.0,
             subtask_id.0,
             Matching::from,
+            include_note.0.unwrap_or(false),
         )
         .await?
         {
-            Some(matching) => GetMatching::ok(matching),
+            Some(mut matching) => {
+                matching.subtask.solve_token =
+                    issue_solve_token(&self.state.jwt_secret, auth.0.id, &matching.subtask)?;
+                GetMatching::ok(matching)
+            }
             None => GetMatching::subtask_not_found(),
         }
     }
@@ -112,9 +157,35 @@ impl Matchings {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the matching as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetMatchingWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetMatchingWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_matchings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                MatchingWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetMatchingWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetMatchingWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetMatchingWithSolution::revision_not_found()
+                }
+            };
+        }
+
         match query_subtask_admin::<challenges_matchings::Entity, _>(
             &db,
             &auth.0,
@@ -136,13 +207,16 @@ impl Matchings {
         &self,
         task_id: Path<Uuid>,
         data: Json<CreateMatchingRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> CreateMatching::Response<VerifiedUserAuth> {
         let subtask = match create_subtask(
             &db,
             &self.state.services,
-            &self.config,
+            &self.config.load(),
             &auth.0,
             task_id.0,
             data.0.subtask,
@@ -154,12 +228,18 @@ impl Matchings {
             Err(CreateSubtaskError::TaskNotFound) => return CreateMatching::task_not_found(),
             Err(CreateSubtaskError::Forbidden) => return CreateMatching::forbidden(),
             Err(CreateSubtaskError::Banned(until)) => return CreateMatching::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateMatching::quota_exceeded(reset)
+            }
             Err(CreateSubtaskError::XpLimitExceeded(x)) => {
                 return CreateMatching::xp_limit_exceeded(x)
             }
             Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
                 return CreateMatching::coin_limit_exceeded(x)
             }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateMatching::attribution_required()
+            }
         };
 
         match check_matching(&data.0.left, &data.0.right, &data.0.solution) {
@@ -176,6 +256,24 @@ impl Matchings {
             }
         }
 
+        let fingerprint = matching_fingerprint(
+            &data.0.left,
+            &data.0.right,
+            &data.0.solution.iter().map(|&x| x as _).collect::<Vec<_>>(),
+        );
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::Matching,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateMatching::duplicate(duplicates);
+            }
+        }
+
         let matching = challenges_matchings::ActiveModel {
             subtask_id: Set(subtask.id),
             left: Set(data.0.left),
@@ -184,6 +282,8 @@ impl Matchings {
         }
         .insert(&***db)
         .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &matching).await?;
         CreateMatching::ok(MatchingWithSolution::from(matching, subtask))
     }
 
@@ -209,6 +309,9 @@ impl Matchings {
             Ok(x) => x,
             Err(UpdateSubtaskError::SubtaskNotFound) => return UpdateMatching::subtask_not_found(),
             Err(UpdateSubtaskError::TaskNotFound) => return UpdateMatching::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateMatching::attribution_required()
+            }
         };
 
         match check_matching(
@@ -243,6 +346,13 @@ impl Matchings {
         }
         .update(&***db)
         .await?;
+        set_fingerprint(
+            &db,
+            matching.subtask_id,
+            matching_fingerprint(&matching.left, &matching.right, &matching.solution),
+        )
+        .await?;
+        record_revision(&db, matching.subtask_id, &matching).await?;
 
         UpdateMatching::ok(MatchingWithSolution::from(matching, subtask))
     }
@@ -252,7 +362,7 @@ impl Matchings {
         path = "/tasks/:task_id/matchings/:subtask_id/attempts",
         method = "post"
     )]
-    async fn solve_matching(
+    pub(super) async fn solve_matching(
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
@@ -265,7 +375,7 @@ impl Matchings {
         else {
             return SolveMatching::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return SolveMatching::subtask_not_found();
         }
 
@@ -275,16 +385,37 @@ impl Matchings {
 
         let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
 
-        let solved_previously = user_subtask.is_solved();
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
         if let Some(last_attempt) = user_subtask.last_attempt() {
-            let time_left = self.config.challenges.matchings.timeout as i64
-                - (Utc::now() - last_attempt).num_seconds();
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.matchings.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
             if time_left > 0 {
                 return SolveMatching::too_many_requests(time_left as u64);
             }
         }
 
-        if !deduct_hearts(&self.state.services, &self.config, &auth.0, &subtask).await? {
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveMatching::attempts_exhausted();
+        }
+
+        match check_solve_token(
+            &self.state.jwt_secret,
+            data.0.token.as_deref(),
+            auth.0.id,
+            &subtask,
+        ) {
+            Ok(()) => {}
+            Err(SolveTokenError::Invalid) => return SolveMatching::invalid_solve_token(),
+            Err(SolveTokenError::Expired) => return SolveMatching::time_limit_exceeded(),
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
             return SolveMatching::not_enough_hearts();
         }
 
@@ -297,7 +428,17 @@ impl Matchings {
             .count();
         let solved = correct == matching.solution.len();
 
-        if !solved_previously {
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveMatching::accepted(exam_answer.into());
+        }
+
+        if !solved && auth.0.id != subtask.creator {
+            let mistakes = matching_mistakes(&data.0.answer, &matching.solution);
+            record_mistakes(&db, subtask.id, mistakes).await?;
+        }
+
+        if let Some(solve_kind) = solve_kind {
             let now = Utc::now().naive_utc();
             if solved {
                 update_user_subtask(
@@ -313,9 +454,31 @@ impl Matchings {
                     },
                 )
                 .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
 
                 if auth.0.id != subtask.creator {
-                    send_task_rewards(&self.state.services, &db, auth.0.id, &subtask).await?;
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
                 }
             } else {
                 update_user_subtask(
@@ -338,6 +501,7 @@ impl Matchings {
                 user_id: Set(auth.0.id),
                 timestamp: Set(now),
                 solved: Set(solved),
+                revision_id: Set(get_current_revision(&db, matching.subtask_id).await?),
             }
             .insert(&***db)
             .await?;
@@ -355,12 +519,18 @@ response!(GetMatching = {
     Ok(200) => Matching,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
 });
 
 response!(GetMatchingWithSolution = {
     Ok(200) => MatchingWithSolution,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
     /// The user is not allowed to view the solution to this matching.
     Forbidden(403, error),
 });
@@ -373,10 +543,14 @@ response!(CreateMatching = {
     Forbidden(403, error),
     /// The user is currently banned from creating subtasks.
     Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
     /// The max xp limit has been exceeded.
     XpLimitExceeded(403, error) => u64,
     /// The max coin limit has been exceeded.
     CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// The left list does not contain the same number of entries as the right list.
     LeftRightDifferentLength(400, error),
     /// The solution list does not contain the same number of entries as the left and right lists.
@@ -385,6 +559,8 @@ response!(CreateMatching = {
     InvalidIndex(400, error) => u8,
     /// One or more entries in the right list have no match in the left list.
     RightEntriesNotMatched(400, error) => HashSet<u8>,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
 });
 
 response!(UpdateMatching = {
@@ -393,6 +569,8 @@ response!(UpdateMatching = {
     SubtaskNotFound(404, error),
     /// Task does not exist.
     TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// The left list does not contain the same number of entries as the right list.
     LeftRightDifferentLength(400, error),
     /// The solution list does not contain the same number of entries as the left and right lists.
@@ -405,8 +583,17 @@ response!(UpdateMatching = {
 
 response!(SolveMatching = {
     Ok(201) => SolveMatchingFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
     /// Try again later. `details` contains the number of seconds to wait.
     TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// The subtask has a time limit, but no valid solve token has been submitted.
+    InvalidSolveToken(400, error),
+    /// The time limit of the subtask has been exceeded.
+    TimeLimitExceeded(403, error),
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
     /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
@@ -1,28 +1,71 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use fnct::format::JsonFormatter;
-use lib::{config::Config, SharedState};
+use lib::{config::SharedConfig, redis::RedisConnection, SharedState};
 use poem_openapi::OpenApi;
-use sandkasten_client::SandkastenClient;
-use tokio::sync::Semaphore;
 
 use self::{
-    challenges::Challenges, coding_challenges::CodingChallenges, course_tasks::CourseTasks,
-    leaderboard::LeaderboardEndpoints, matchings::Matchings, multiple_choice::MultipleChoice,
-    question::Questions, subtasks::Subtasks,
+    admin::Admin,
+    anti_cheat::AntiCheat,
+    challenges::Challenges,
+    cloze::Clozes,
+    code_readings::CodeReadings,
+    coding_challenges::{CodingChallenges, CodingChallengesV2},
+    cohorts::Cohorts,
+    contests::Contests,
+    course_tasks::CourseTasks,
+    exams::Exams,
+    held_rewards::HeldRewards,
+    leaderboard::LeaderboardEndpoints,
+    matchings::Matchings,
+    multiple_choice::MultipleChoice,
+    orderings::Orderings,
+    organizations::Organizations,
+    paths::Paths,
+    public::Public,
+    question::Questions,
+    reviews::Reviews,
+    sql_challenges::SqlChallenges,
+    subtasks::Subtasks,
+    tasks::Tasks,
+    true_false::TrueFalseStatements,
+    users::Users,
+};
+use crate::services::{
+    contests::ScoreboardHub, example_limiter::ExampleLimiter, feed::FeedCache,
+    judge_limiter::JudgeLimiter, sandbox::SharedSandbox,
 };
 
+mod admin;
+mod anti_cheat;
 mod challenges;
+mod cloze;
+mod code_readings;
 pub mod coding_challenges;
+mod cohorts;
+pub mod contests;
 mod course_tasks;
+mod exams;
+mod held_rewards;
 mod leaderboard;
 mod matchings;
 mod multiple_choice;
+mod orderings;
+mod organizations;
+mod paths;
+pub mod public;
 mod question;
+mod reviews;
+mod sql_challenges;
 mod subtasks;
+mod tasks;
+mod true_false;
+mod users;
 
 #[derive(poem_openapi::Tags)]
 pub enum Tags {
+    /// Management of all tasks
+    Tasks,
     /// Global challenges (tasks)
     Challenges,
     /// Tasks that exist within a course (tasks)
@@ -35,16 +78,68 @@ pub enum Tags {
     Questions,
     /// One to one matchings (subtasks)
     Matchings,
+    /// True/false statements (subtasks)
+    TrueFalse,
+    /// Questions asking to put items in the correct order (subtasks)
+    Orderings,
+    /// Texts with blanks that have to be filled in (subtasks)
+    Clozes,
+    /// Questions asking for the output of a program (subtasks)
+    CodeReadings,
+    /// Database queries that have to produce a given result (subtasks)
+    SqlChallenges,
     /// Coding challenges (subtasks)
     CodingChallenges,
     /// Leaderboard
     Leaderboard,
+    /// Contests with live scoreboards
+    Contests,
+    /// Exams with deferred feedback
+    Exams,
+    /// Cohorts of users with assigned tasks
+    Cohorts,
+    /// Organizations with their own tasks and members
+    Organizations,
+    /// Personal data of users
+    Users,
+    /// Spaced repetition of solved subtasks
+    Reviews,
+    /// Learning paths consisting of tasks and subtasks
+    Paths,
+    /// Review of suspicious answer patterns
+    AntiCheat,
+    /// Rewards held until they are released or voided by an admin
+    HeldRewards,
+    /// Read-only endpoints that do not require authentication
+    Public,
+    /// Administration of the microservice
+    Admin,
+}
+
+/// Endpoints with breaking changes to their `/v1` counterparts.
+pub async fn setup_api_v2(
+    state: Arc<SharedState>,
+    config: SharedConfig,
+    sandkasten: SharedSandbox,
+) -> anyhow::Result<impl OpenApi> {
+    Ok(CodingChallengesV2 {
+        judge_cache: state.cache.with_formatter(JsonFormatter),
+        example_limiter: ExampleLimiter::new(
+            RedisConnection::new(config.load().redis.challenges.as_str()).await?,
+        ),
+        state,
+        sandkasten,
+        config,
+    }
+    .setup_api())
 }
 
 pub async fn setup_api(
     state: Arc<SharedState>,
-    config: Arc<Config>,
-    sandkasten: SandkastenClient,
+    config: SharedConfig,
+    sandkasten: SharedSandbox,
+    scoreboard: Arc<ScoreboardHub>,
+    feed: Arc<FeedCache>,
 ) -> anyhow::Result<impl OpenApi> {
     Ok((
         Challenges {
@@ -52,39 +147,106 @@ pub async fn setup_api(
         },
         CourseTasks {
             state: Arc::clone(&state),
-            config: Arc::clone(&config),
+            config: config.clone(),
+        },
+        Tasks {
+            state: Arc::clone(&state),
+            config: config.clone(),
         },
         Subtasks {
             state: Arc::clone(&state),
-            config: Arc::clone(&config),
+            config: config.clone(),
+            sandkasten: sandkasten.clone(),
         }
         .get_api(),
-        MultipleChoice {
+        (
+            MultipleChoice {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            Questions {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            Matchings {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            TrueFalseStatements {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            Orderings {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            Clozes {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            CodeReadings {
+                state: Arc::clone(&state),
+                config: config.clone(),
+                sandkasten: sandkasten.clone(),
+            },
+            SqlChallenges {
+                state: Arc::clone(&state),
+                config: config.clone(),
+                sandkasten: sandkasten.clone(),
+            },
+        ),
+        Reviews {
             state: Arc::clone(&state),
-            config: Arc::clone(&config),
+            config: config.clone(),
         },
-        Questions {
+        Paths {
             state: Arc::clone(&state),
-            config: Arc::clone(&config),
-        },
-        Matchings {
-            state: Arc::clone(&state),
-            config: Arc::clone(&config),
+            config: config.clone(),
         },
         CodingChallenges {
             judge_cache: state.cache.with_formatter(JsonFormatter),
             state: Arc::clone(&state),
             sandkasten,
-            judge_lock: Arc::new(Semaphore::new(
-                config.challenges.coding_challenges.max_concurrency,
-            )),
-            config,
+            judge_limiter: JudgeLimiter::new(
+                RedisConnection::new(config.load().redis.challenges.as_str()).await?,
+                config.load().challenges.coding_challenges.max_concurrency,
+                Duration::from_secs(config.load().challenges.coding_challenges.judge_lease),
+            ),
+            example_limiter: ExampleLimiter::new(
+                RedisConnection::new(config.load().redis.challenges.as_str()).await?,
+            ),
+            config: config.clone(),
         }
         .setup_api()
         .await?,
         LeaderboardEndpoints {
             cache: state.cache.with_formatter(Default::default()),
-            state,
+            state: Arc::clone(&state),
+        },
+        Contests { scoreboard },
+        Exams {
+            state: Arc::clone(&state),
+            config: config.clone(),
+        },
+        Cohorts,
+        Organizations,
+        Users {
+            cache: state.cache.with_formatter(JsonFormatter),
         },
+        (
+            AntiCheat {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            HeldRewards {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+            Admin {
+                state: Arc::clone(&state),
+                config: config.clone(),
+            },
+        ),
+        Public { config, feed },
     ))
 }
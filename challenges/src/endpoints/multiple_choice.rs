@@ -2,12 +2,13 @@ use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use entity::{
-    challenges_multiple_choice_quizes, challenges_user_subtasks,
-    sea_orm_active_enums::ChallengesSubtaskType,
+    challenges_attempt_seeds, challenges_multiple_choice_quizes, challenges_subtasks,
+    challenges_user_subtasks, sea_orm_active_enums::ChallengesSubtaskType,
 };
 use lib::{
-    auth::{AdminAuth, VerifiedUserAuth},
-    config::Config,
+    auth::{AdminAuth, User, VerifiedUserAuth},
+    config::{challenges::ContentFilter, SharedConfig},
+    events::{publish_event, Event},
     SharedState,
 };
 use poem::web::Data;
@@ -17,25 +18,45 @@ use poem_openapi::{
     payload::Json,
     OpenApi,
 };
-use schemas::challenges::multiple_choice::{
-    check_answers, split_answers, Answer, CreateMultipleChoiceQuestionRequest,
-    MultipleChoiceQuestion, MultipleChoiceQuestionSummary, SolveMCQFeedback, SolveMCQRequest,
-    UpdateMultipleChoiceQuestionRequest,
+use schemas::challenges::{
+    exams::ExamAnswer,
+    multiple_choice::{
+        check_answers, split_answers, Answer, AttemptSeed, CreateMultipleChoiceQuestionRequest,
+        ImportMCQsRequest, ImportMCQsResponse, MCQDiagnostics, MCQDraft, MCQDraftAnswer,
+        MCQImportFormat, MCQIssue, MCQIssueKind, MultipleChoiceQuestion,
+        MultipleChoiceQuestionSummary, RejectedMCQ, SolveMCQFeedback, SolveMCQRequest,
+        UpdateMultipleChoiceQuestionRequest, ValidateMCQsRequest,
+    },
+    subtasks::ContentViolation,
 };
-use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use sea_orm::{ActiveModelTrait, ColumnTrait, ModelTrait, QueryFilter, QueryOrder, Set, Unchanged};
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::subtasks::{
-    create_subtask, deduct_hearts, get_subtask, get_user_subtask, query_subtask,
-    query_subtask_admin, query_subtasks, send_task_rewards, update_subtask, update_user_subtask,
-    CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter, UpdateSubtaskError,
-    UserSubtaskExt,
+use crate::services::{
+    content_filter::ContentChecker,
+    exams::{get_active_exam, record_exam_answer},
+    notifications::notify_subtask_solved,
+    question_import::{gift, moodle_xml, ImportItem},
+    reviews::schedule_review,
+    revisions::{query_subtask_as_of, record_revision, QuerySubtaskAsOfError},
+    shuffle::{attempt_seed, permutation, record_seed, shuffle, unshuffle},
+    similarity::{find_duplicates, fingerprint, set_fingerprint, similarity, DUPLICATE_THRESHOLD},
+    solve_tokens::{check_solve_token, issue_solve_token, SolveTokenError},
+    subtasks::{
+        can_access_subtask, can_create, create_subtask, deduct_hearts, get_subtask,
+        get_user_subtask, query_subtask, query_subtask_admin, query_subtasks, send_task_rewards,
+        update_subtask, update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError,
+        QuerySubtasksFilter, SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::{get_task_limits, get_timeout, TaskLimits},
+    tasks::{get_task_with_specific, Task},
+    wrong_answers::{mcq_mistakes, record_mistakes},
 };
 
 pub struct MultipleChoice {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::MultipleChoice")]
@@ -74,6 +95,7 @@ impl MultipleChoice {
                     retired: retired.0,
                     creator: creator.0,
                     ty: None,
+                    permissive: None,
                 },
                 MultipleChoiceQuestionSummary::from,
             )
@@ -87,19 +109,57 @@ impl MultipleChoice {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the question as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetMCQ::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetMCQ::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_multiple_choice_quizes::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                MultipleChoiceQuestion::<String>::from,
+            )
+            .await?
+            {
+                Ok(x) => GetMCQ::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetMCQ::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetMCQ::revision_not_found(),
+            };
+        }
+
         match query_subtask::<challenges_multiple_choice_quizes::Entity, _>(
             &db,
             &auth.0,
             task_id.0,
             subtask_id.0,
             MultipleChoiceQuestion::<String>::from,
+            include_note.0.unwrap_or(false),
         )
         .await?
         {
-            Some(mcq) => GetMCQ::ok(mcq),
+            Some(mut mcq) => {
+                mcq.subtask.solve_token =
+                    issue_solve_token(&self.state.jwt_secret, auth.0.id, &mcq.subtask)?;
+                if mcq.shuffle_answers {
+                    let attempt = get_user_subtask(&db, auth.0.id, mcq.subtask.id)
+                        .await?
+                        .attempts();
+                    let seed = attempt_seed(auth.0.id, mcq.subtask.id, attempt);
+                    record_seed(&db, auth.0.id, mcq.subtask.id, attempt, seed, None).await?;
+                    let permutation = permutation(seed, mcq.answers.len());
+                    mcq.answers = shuffle(mcq.answers, &permutation);
+                }
+                GetMCQ::ok(mcq)
+            }
             None => GetMCQ::subtask_not_found(),
         }
     }
@@ -113,9 +173,35 @@ impl MultipleChoice {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the question as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetMCQWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetMCQWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_multiple_choice_quizes::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                MultipleChoiceQuestion::<Answer>::from,
+            )
+            .await?
+            {
+                Ok(x) => GetMCQWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetMCQWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetMCQWithSolution::revision_not_found()
+                }
+            };
+        }
+
         match query_subtask_admin::<challenges_multiple_choice_quizes::Entity, _>(
             &db,
             &auth.0,
@@ -131,19 +217,64 @@ impl MultipleChoice {
         }
     }
 
+    /// List the seeds from which the order of the answers has been derived
+    /// for a user.
+    ///
+    /// Combine `order` with the question returned by the get endpoint with
+    /// `as_of` set to `timestamp` to reconstruct what the user has seen in an
+    /// attempt.
+    #[oai(
+        path = "/tasks/:task_id/multiple_choice/:subtask_id/seeds/:user_id",
+        method = "get"
+    )]
+    async fn list_attempt_seeds(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        user_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListAttemptSeeds::Response<AdminAuth> {
+        let Some((mcq, subtask)) =
+            get_subtask::<challenges_multiple_choice_quizes::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListAttemptSeeds::subtask_not_found();
+        };
+        ListAttemptSeeds::ok(
+            subtask
+                .find_related(challenges_attempt_seeds::Entity)
+                .filter(challenges_attempt_seeds::Column::UserId.eq(user_id.0))
+                .order_by_asc(challenges_attempt_seeds::Column::Attempt)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(|x| AttemptSeed {
+                    attempt: x.attempt as _,
+                    seed: x.seed,
+                    timestamp: x.timestamp.and_utc(),
+                    order: permutation(x.seed, mcq.answers.len()),
+                })
+                .collect(),
+        )
+    }
+
     /// Create a new multiple choice question.
     #[oai(path = "/tasks/:task_id/multiple_choice", method = "post")]
     async fn create_question(
         &self,
         task_id: Path<Uuid>,
         data: Json<CreateMultipleChoiceQuestionRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> CreateMCQ::Response<VerifiedUserAuth> {
         let subtask = match create_subtask(
             &db,
             &self.state.services,
-            &self.config,
+            &self.config.load(),
             &auth.0,
             task_id.0,
             data.0.subtask,
@@ -155,10 +286,16 @@ impl MultipleChoice {
             Err(CreateSubtaskError::TaskNotFound) => return CreateMCQ::task_not_found(),
             Err(CreateSubtaskError::Forbidden) => return CreateMCQ::forbidden(),
             Err(CreateSubtaskError::Banned(until)) => return CreateMCQ::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateMCQ::quota_exceeded(reset)
+            }
             Err(CreateSubtaskError::XpLimitExceeded(x)) => return CreateMCQ::xp_limit_exceeded(x),
             Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
                 return CreateMCQ::coin_limit_exceeded(x)
             }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateMCQ::attribution_required()
+            }
         };
 
         let correct_cnt = data.0.answers.iter().filter(|x| x.correct).count();
@@ -169,19 +306,306 @@ impl MultipleChoice {
             return CreateMCQ::invalid_multiple_choice();
         }
 
-        let (answers, correct) = split_answers(data.0.answers);
+        let config = self.config.load();
+        let mut checker = ContentChecker::new(&config.challenges.content_filter, &auth.0);
+        let question = checker.check("question", data.0.question);
+        let answers = data
+            .0
+            .answers
+            .into_iter()
+            .enumerate()
+            .map(|(i, answer)| Answer {
+                answer: checker.check(&format!("answers.{i}"), answer.answer),
+                ..answer
+            })
+            .collect::<Vec<_>>();
+        if let Err(violations) = checker.finish() {
+            return CreateMCQ::content_violation(violations);
+        }
+
+        let fingerprint = fingerprint(&question, answers.iter().map(|x| x.answer.as_str()));
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::MultipleChoiceQuestion,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateMCQ::duplicate(duplicates);
+            }
+        }
+
+        let (answers, correct) = split_answers(answers);
         let mcq = challenges_multiple_choice_quizes::ActiveModel {
             subtask_id: Set(subtask.id),
-            question: Set(data.0.question),
+            question: Set(question),
             answers: Set(answers),
             correct_answers: Set(correct),
             single_choice: Set(data.0.single_choice),
+            shuffle_answers: Set(data.0.shuffle_answers),
         }
         .insert(&***db)
         .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &mcq).await?;
         CreateMCQ::ok(MultipleChoiceQuestion::<Answer>::from(mcq, subtask))
     }
 
+    /// Validate a batch of draft multiple choice questions without creating
+    /// them.
+    ///
+    /// Each draft is checked like in the create endpoint (answer counts,
+    /// length limits, xp and coin limits and duplicates of existing subtasks
+    /// in this task). Additionally, drafts that are highly similar to a
+    /// previous draft in the same request are reported.
+    #[oai(path = "/tasks/:task_id/multiple_choice/validate", method = "post")]
+    async fn validate_questions(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<ValidateMCQsRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ValidateMCQs::Response<VerifiedUserAuth> {
+        let Some((task, specific)) = get_task_with_specific(&db, task_id.0).await? else {
+            return ValidateMCQs::task_not_found();
+        };
+        let config = self.config.load();
+        if !can_create(&self.state.services, &config, &task, &specific, &auth.0).await? {
+            return ValidateMCQs::forbidden();
+        }
+        let limits = if matches!(specific, Task::CourseTask(_)) && !auth.0.admin {
+            Some(get_task_limits(&db, &config.challenges.quizzes, task.id).await?)
+        } else {
+            None
+        };
+
+        let mut fingerprints: Vec<String> = Vec::with_capacity(data.0.questions.len());
+        let mut diagnostics = Vec::with_capacity(data.0.questions.len());
+        for (index, mut draft) in data.0.questions.into_iter().enumerate() {
+            let mut issues = check_draft(&draft, limits.as_ref());
+            issues.extend(filter_draft(
+                &config.challenges.content_filter,
+                &auth.0,
+                &mut draft,
+            ));
+
+            let fingerprint = fingerprint(
+                &draft.question,
+                draft.answers.iter().map(|x| x.answer.as_str()),
+            );
+            let duplicates = find_duplicates(
+                &db,
+                task.id,
+                ChallengesSubtaskType::MultipleChoiceQuestion,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                issues.push(MCQIssue {
+                    subtasks: duplicates,
+                    ..MCQIssue::new(MCQIssueKind::Duplicate)
+                });
+            }
+            if let Some(other) = fingerprints
+                .iter()
+                .position(|other| similarity(&fingerprint, other) >= DUPLICATE_THRESHOLD)
+            {
+                issues.push(MCQIssue {
+                    draft: Some(other),
+                    ..MCQIssue::new(MCQIssueKind::DuplicateDraft)
+                });
+            }
+            fingerprints.push(fingerprint);
+
+            diagnostics.push(MCQDiagnostics {
+                index,
+                valid: issues.is_empty(),
+                issues,
+            });
+        }
+
+        ValidateMCQs::ok(diagnostics)
+    }
+
+    /// Import multiple choice questions from a question bank exported from
+    /// Moodle.
+    ///
+    /// Multiple choice and true/false questions are imported as disabled
+    /// subtasks, which have to be enabled before they are visible to normal
+    /// users. All questions get the xp and coins specified in the request.
+    /// Questions of other types and questions that do not pass the checks of
+    /// the validate endpoint are not imported and reported in `rejected`.
+    #[oai(path = "/tasks/:task_id/multiple_choice/import", method = "post")]
+    async fn import_questions(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<ImportMCQsRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ImportMCQs::Response<VerifiedUserAuth> {
+        let Some((task, specific)) = get_task_with_specific(&db, task_id.0).await? else {
+            return ImportMCQs::task_not_found();
+        };
+        let config = self.config.load();
+        if !can_create(&self.state.services, &config, &task, &specific, &auth.0).await? {
+            return ImportMCQs::forbidden();
+        }
+        let limits = if matches!(specific, Task::CourseTask(_)) && !auth.0.admin {
+            Some(get_task_limits(&db, &config.challenges.quizzes, task.id).await?)
+        } else {
+            None
+        };
+
+        let items = match data.0.format {
+            MCQImportFormat::Gift => gift::parse(&data.0.content),
+            MCQImportFormat::MoodleXml => moodle_xml::parse(&data.0.content),
+        };
+        let items = match items {
+            Ok(items) => items,
+            Err(err) => return ImportMCQs::invalid_format(err.to_string()),
+        };
+
+        let mut fingerprints: Vec<(usize, String)> = Vec::new();
+        let mut created = Vec::new();
+        let mut rejected = Vec::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let question = match item {
+                ImportItem::Question(question) => question,
+                ImportItem::Unsupported { name, ty } => {
+                    rejected.push(RejectedMCQ {
+                        index,
+                        name,
+                        unsupported_type: Some(ty),
+                        issues: Vec::new(),
+                    });
+                    continue;
+                }
+            };
+            let mut draft = MCQDraft {
+                xp: data.0.subtask.xp,
+                coins: data.0.subtask.coins,
+                question: question.question,
+                answers: question
+                    .answers
+                    .into_iter()
+                    .map(|x| MCQDraftAnswer {
+                        answer: x.answer,
+                        correct: x.correct,
+                    })
+                    .collect(),
+                single_choice: question.single_choice,
+            };
+            let mut issues = check_draft(&draft, limits.as_ref());
+            issues.extend(filter_draft(
+                &config.challenges.content_filter,
+                &auth.0,
+                &mut draft,
+            ));
+
+            let fingerprint = fingerprint(
+                &draft.question,
+                draft.answers.iter().map(|x| x.answer.as_str()),
+            );
+            let duplicates = find_duplicates(
+                &db,
+                task.id,
+                ChallengesSubtaskType::MultipleChoiceQuestion,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                issues.push(MCQIssue {
+                    subtasks: duplicates,
+                    ..MCQIssue::new(MCQIssueKind::Duplicate)
+                });
+            }
+            if let Some((other, _)) = fingerprints
+                .iter()
+                .find(|(_, other)| similarity(&fingerprint, other) >= DUPLICATE_THRESHOLD)
+            {
+                issues.push(MCQIssue {
+                    draft: Some(*other),
+                    ..MCQIssue::new(MCQIssueKind::DuplicateDraft)
+                });
+            }
+            if !issues.is_empty() {
+                rejected.push(RejectedMCQ {
+                    index,
+                    name: question.name,
+                    unsupported_type: None,
+                    issues,
+                });
+                continue;
+            }
+
+            let mut subtask = match create_subtask(
+                &db,
+                &self.state.services,
+                &config,
+                &auth.0,
+                task.id,
+                data.0.subtask.clone(),
+                ChallengesSubtaskType::MultipleChoiceQuestion,
+            )
+            .await?
+            {
+                Ok(subtask) => subtask,
+                Err(CreateSubtaskError::TaskNotFound) => return ImportMCQs::task_not_found(),
+                Err(CreateSubtaskError::Forbidden) => return ImportMCQs::forbidden(),
+                Err(CreateSubtaskError::Banned(until)) => return ImportMCQs::banned(until),
+                Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                    return ImportMCQs::quota_exceeded(reset)
+                }
+                Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                    return ImportMCQs::xp_limit_exceeded(x)
+                }
+                Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                    return ImportMCQs::coin_limit_exceeded(x)
+                }
+                Err(CreateSubtaskError::AttributionRequired) => {
+                    return ImportMCQs::attribution_required()
+                }
+            };
+            challenges_subtasks::ActiveModel {
+                id: Unchanged(subtask.id),
+                enabled: Set(false),
+                ..Default::default()
+            }
+            .update(&***db)
+            .await?;
+            subtask.enabled = false;
+
+            let (answers, correct) = split_answers(
+                draft
+                    .answers
+                    .into_iter()
+                    .map(|x| Answer {
+                        answer: x.answer,
+                        correct: x.correct,
+                    })
+                    .collect(),
+            );
+            let mcq = challenges_multiple_choice_quizes::ActiveModel {
+                subtask_id: Set(subtask.id),
+                question: Set(draft.question),
+                answers: Set(answers),
+                correct_answers: Set(correct),
+                single_choice: Set(draft.single_choice),
+                shuffle_answers: Set(false),
+            }
+            .insert(&***db)
+            .await?;
+            set_fingerprint(&db, subtask.id, fingerprint.clone()).await?;
+            record_revision(&db, subtask.id, &mcq).await?;
+            fingerprints.push((index, fingerprint));
+            created.push(MultipleChoiceQuestion::<Answer>::from(mcq, subtask));
+        }
+
+        ImportMCQs::ok(ImportMCQsResponse { created, rejected })
+    }
+
     /// Update a multiple choice question.
     #[oai(path = "/tasks/:task_id/multiple_choice/:subtask_id", method = "patch")]
     async fn update_question(
@@ -204,6 +628,9 @@ impl MultipleChoice {
             Ok(x) => x,
             Err(UpdateSubtaskError::SubtaskNotFound) => return UpdateMCQ::subtask_not_found(),
             Err(UpdateSubtaskError::TaskNotFound) => return UpdateMCQ::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateMCQ::attribution_required()
+            }
         };
 
         let (answers, correct, cnt) = if let PatchValue::Set(answers) = data.0.answers {
@@ -228,9 +655,17 @@ impl MultipleChoice {
             answers,
             correct_answers: correct,
             single_choice: data.0.single_choice.update(mcq.single_choice),
+            shuffle_answers: data.0.shuffle_answers.update(mcq.shuffle_answers),
         }
         .update(&***db)
         .await?;
+        set_fingerprint(
+            &db,
+            mcq.subtask_id,
+            fingerprint(&mcq.question, mcq.answers.iter().map(String::as_str)),
+        )
+        .await?;
+        record_revision(&db, mcq.subtask_id, &mcq).await?;
 
         UpdateMCQ::ok(MultipleChoiceQuestion::<Answer>::from(mcq, subtask))
     }
@@ -240,7 +675,7 @@ impl MultipleChoice {
         path = "/tasks/:task_id/multiple_choice/:subtask_id/attempts",
         method = "post"
     )]
-    async fn solve_question(
+    pub(super) async fn solve_question(
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
@@ -254,7 +689,7 @@ impl MultipleChoice {
         else {
             return SolveMCQ::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return SolveMCQ::subtask_not_found();
         }
 
@@ -264,23 +699,68 @@ impl MultipleChoice {
 
         let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
 
-        let solved_previously = user_subtask.is_solved();
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
         if let Some(last_attempt) = user_subtask.last_attempt() {
-            let time_left = self.config.challenges.multiple_choice_questions.timeout as i64
-                - (Utc::now() - last_attempt).num_seconds();
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config
+                    .load()
+                    .challenges
+                    .multiple_choice_questions
+                    .timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
             if time_left > 0 {
                 return SolveMCQ::too_many_requests(time_left as u64);
             }
         }
 
-        if !deduct_hearts(&self.state.services, &self.config, &auth.0, &subtask).await? {
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveMCQ::attempts_exhausted();
+        }
+
+        match check_solve_token(
+            &self.state.jwt_secret,
+            data.0.token.as_deref(),
+            auth.0.id,
+            &subtask,
+        ) {
+            Ok(()) => {}
+            Err(SolveTokenError::Invalid) => return SolveMCQ::invalid_solve_token(),
+            Err(SolveTokenError::Expired) => return SolveMCQ::time_limit_exceeded(),
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
             return SolveMCQ::not_enough_hearts();
         }
 
-        let correct_cnt = check_answers(&data.0.answers, mcq.correct_answers);
+        let answers = match mcq.shuffle_answers {
+            true => unshuffle(
+                &data.0.answers,
+                &permutation(
+                    attempt_seed(auth.0.id, subtask.id, user_subtask.attempts()),
+                    mcq.answers.len(),
+                ),
+            ),
+            false => data.0.answers,
+        };
+        let correct_cnt = check_answers(&answers, mcq.correct_answers);
         let solved = correct_cnt == mcq.answers.len();
 
-        if !solved_previously {
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveMCQ::accepted(exam_answer.into());
+        }
+
+        if !solved && auth.0.id != subtask.creator {
+            let mistakes = mcq_mistakes(&answers, mcq.correct_answers);
+            record_mistakes(&db, subtask.id, mistakes).await?;
+        }
+
+        if let Some(solve_kind) = solve_kind {
             let now = Utc::now().naive_utc();
             if solved {
                 update_user_subtask(
@@ -296,9 +776,32 @@ impl MultipleChoice {
                     },
                 )
                 .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+                schedule_review(&db, auth.0.id, subtask.id, now).await?;
 
                 if auth.0.id != subtask.creator {
-                    send_task_rewards(&self.state.services, &db, auth.0.id, &subtask).await?;
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
                 }
             } else {
                 update_user_subtask(
@@ -331,16 +834,28 @@ response!(GetMCQ = {
     Ok(200) => MultipleChoiceQuestion<String>,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
 });
 
 response!(GetMCQWithSolution = {
     Ok(200) => MultipleChoiceQuestion<Answer>,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
     /// The user is not allowed to view the solution to this question.
     Forbidden(403, error),
 });
 
+response!(ListAttemptSeeds = {
+    Ok(200) => Vec<AttemptSeed>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
 response!(CreateMCQ = {
     Ok(201) => MultipleChoiceQuestion<Answer>,
     /// Task does not exist.
@@ -349,14 +864,50 @@ response!(CreateMCQ = {
     Forbidden(403, error),
     /// The user is currently banned from creating subtasks.
     Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
     /// The max xp limit has been exceeded.
     XpLimitExceeded(403, error) => u64,
     /// The max coin limit has been exceeded.
     CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// `single_choice` is set to `true`, but there is not exactly one correct answer.
     InvalidSingleChoice(400, error),
     /// There is no correct answer.
     InvalidMultipleChoice(400, error),
+    /// The question or one of the answers contains a forbidden word or personal data.
+    ContentViolation(400, error) => Vec<ContentViolation>,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
+});
+
+response!(ValidateMCQs = {
+    Ok(200) => Vec<MCQDiagnostics>,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create questions in this task.
+    Forbidden(403, error),
+});
+
+response!(ImportMCQs = {
+    Ok(201) => ImportMCQsResponse,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create questions in this task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The question bank could not be parsed. `details` contains the error message.
+    InvalidFormat(400, error) => String,
 });
 
 response!(UpdateMCQ = {
@@ -365,6 +916,8 @@ response!(UpdateMCQ = {
     SubtaskNotFound(404, error),
     /// Task does not exist.
     TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// `single_choice` is set to `true`, but there is not exactly one correct answer.
     InvalidSingleChoice(400, error),
     /// There is no correct answer.
@@ -373,12 +926,160 @@ response!(UpdateMCQ = {
 
 response!(SolveMCQ = {
     Ok(201) => SolveMCQFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
     /// Wrong number of answers.
     WrongLength(400, error),
     /// Try again later. `details` contains the number of seconds to wait.
     TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// The subtask has a time limit, but no valid solve token has been submitted.
+    InvalidSolveToken(400, error),
+    /// The time limit of the subtask has been exceeded.
+    TimeLimitExceeded(403, error),
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
     /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
     NotEnoughHearts(403, error),
 });
+
+/// Apply the content filter to the question and the answers of a draft. If
+/// the filter is configured to mask violations, the texts of the draft are
+/// replaced with the masked texts and no issues are reported.
+fn filter_draft(config: &ContentFilter, user: &User, draft: &mut MCQDraft) -> Vec<MCQIssue> {
+    let mut issues = Vec::new();
+    let mut checker = ContentChecker::new(config, user);
+    draft.question = checker.check("question", std::mem::take(&mut draft.question));
+    if checker.finish().is_err() {
+        issues.push(MCQIssue::new(MCQIssueKind::ContentViolation));
+    }
+    for (i, answer) in draft.answers.iter_mut().enumerate() {
+        let mut checker = ContentChecker::new(config, user);
+        answer.answer = checker.check("answer", std::mem::take(&mut answer.answer));
+        if checker.finish().is_err() {
+            issues.push(MCQIssue {
+                answer: Some(i),
+                ..MCQIssue::new(MCQIssueKind::ContentViolation)
+            });
+        }
+    }
+    issues
+}
+
+/// Check a draft against the constraints that are enforced when creating a
+/// multiple choice question. The length limits must match the validators of
+/// `CreateMultipleChoiceQuestionRequest`. If `limits` is `None`, xp and coins
+/// are not limited.
+fn check_draft(draft: &MCQDraft, limits: Option<&TaskLimits>) -> Vec<MCQIssue> {
+    let mut issues = Vec::new();
+    if draft.question.chars().count() > 4096 {
+        issues.push(MCQIssue::new(MCQIssueKind::QuestionTooLong));
+    }
+    if draft.answers.is_empty() {
+        issues.push(MCQIssue::new(MCQIssueKind::TooFewAnswers));
+    }
+    if draft.answers.len() > 32 {
+        issues.push(MCQIssue::new(MCQIssueKind::TooManyAnswers));
+    }
+    for (i, answer) in draft.answers.iter().enumerate() {
+        if answer.answer.chars().count() > 256 {
+            issues.push(MCQIssue {
+                answer: Some(i),
+                ..MCQIssue::new(MCQIssueKind::AnswerTooLong)
+            });
+        }
+    }
+
+    let correct_cnt = draft.answers.iter().filter(|x| x.correct).count();
+    if draft.single_choice && correct_cnt != 1 {
+        issues.push(MCQIssue::new(MCQIssueKind::InvalidSingleChoice));
+    } else if correct_cnt == 0 && !draft.answers.is_empty() {
+        issues.push(MCQIssue::new(MCQIssueKind::InvalidMultipleChoice));
+    }
+
+    if let Some(limits) = limits {
+        if draft.xp.unwrap_or(limits.max_xp) > limits.max_xp {
+            issues.push(MCQIssue::new(MCQIssueKind::XpLimitExceeded));
+        }
+        if draft.coins.unwrap_or(limits.max_coins) > limits.max_coins {
+            issues.push(MCQIssue::new(MCQIssueKind::CoinLimitExceeded));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use schemas::challenges::multiple_choice::MCQDraftAnswer;
+
+    use super::*;
+
+    fn draft(answers: &[(&str, bool)], single_choice: bool) -> MCQDraft {
+        MCQDraft {
+            xp: None,
+            coins: None,
+            question: "What is 1 + 1?".into(),
+            answers: answers
+                .iter()
+                .map(|&(answer, correct)| MCQDraftAnswer {
+                    answer: answer.into(),
+                    correct,
+                })
+                .collect(),
+            single_choice,
+        }
+    }
+
+    fn kinds(issues: Vec<MCQIssue>) -> Vec<MCQIssueKind> {
+        issues.into_iter().map(|x| x.kind).collect()
+    }
+
+    #[test]
+    fn test_check_draft() {
+        assert_eq!(
+            check_draft(&draft(&[("2", true), ("3", false)], true), None),
+            []
+        );
+        assert_eq!(
+            kinds(check_draft(&draft(&[("2", true), ("3", true)], true), None)),
+            [MCQIssueKind::InvalidSingleChoice]
+        );
+        assert_eq!(
+            kinds(check_draft(&draft(&[("2", false)], false), None)),
+            [MCQIssueKind::InvalidMultipleChoice]
+        );
+        assert_eq!(
+            kinds(check_draft(&draft(&[], false), None)),
+            [MCQIssueKind::TooFewAnswers]
+        );
+
+        let long = "x".repeat(257);
+        assert_eq!(
+            check_draft(&draft(&[("2", true), (&long, false)], false), None),
+            [MCQIssue {
+                answer: Some(1),
+                ..MCQIssue::new(MCQIssueKind::AnswerTooLong)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_draft_limits() {
+        let limits = TaskLimits {
+            max_xp: 10,
+            max_coins: 5,
+        };
+        let mut mcq = draft(&[("2", true)], true);
+        assert_eq!(check_draft(&mcq, Some(&limits)), []);
+        mcq.xp = Some(11);
+        mcq.coins = Some(5);
+        assert_eq!(
+            kinds(check_draft(&mcq, Some(&limits))),
+            [MCQIssueKind::XpLimitExceeded]
+        );
+        assert_eq!(check_draft(&mcq, None), []);
+    }
+}
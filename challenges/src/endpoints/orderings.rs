@@ -0,0 +1,614 @@
+use std::{collections::HashSet, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_ordering_attempts, challenges_orderings, challenges_user_subtasks,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+    events::{publish_event, Event},
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::{
+    exams::ExamAnswer,
+    orderings::{
+        CreateOrderingRequest, Ordering, OrderingSummary, OrderingWithSolution,
+        SolveOrderingFeedback, SolveOrderingRequest, UpdateOrderingRequest,
+    },
+};
+use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    exams::{get_active_exam, record_exam_answer},
+    notifications::notify_subtask_solved,
+    revisions::{
+        get_current_revision, query_subtask_as_of, record_revision, QuerySubtaskAsOfError,
+    },
+    similarity::{find_duplicates, ordering_fingerprint, set_fingerprint},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
+};
+
+pub struct Orderings {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Orderings")]
+impl Orderings {
+    /// List all orderings in a task.
+    #[oai(path = "/tasks/:task_id/orderings", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_orderings(
+        &self,
+        task_id: Path<Uuid>,
+        /// Whether to search for subtasks the user has attempted to solve.
+        attempted: Query<Option<bool>>,
+        /// Whether to search for solved subtasks.
+        solved: Query<Option<bool>>,
+        /// Whether to search for rated subtasks.
+        rated: Query<Option<bool>>,
+        /// Whether to search for enabled subtasks.
+        enabled: Query<Option<bool>>,
+        /// Whether to search for retired subtasks.
+        retired: Query<Option<bool>>,
+        /// Filter by creator.
+        creator: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListOrderings::Response<VerifiedUserAuth> {
+        ListOrderings::ok(
+            query_subtasks::<challenges_orderings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                QuerySubtasksFilter {
+                    attempted: attempted.0,
+                    solved: solved.0,
+                    rated: rated.0,
+                    enabled: enabled.0,
+                    retired: retired.0,
+                    creator: creator.0,
+                    ty: None,
+                    permissive: None,
+                },
+                OrderingSummary::from,
+            )
+            .await?,
+        )
+    }
+
+    /// Get an ordering by id.
+    #[oai(path = "/tasks/:task_id/orderings/:subtask_id", method = "get")]
+    async fn get_ordering(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the ordering as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetOrdering::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetOrdering::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_orderings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                Ordering::from,
+            )
+            .await?
+            {
+                Ok(x) => GetOrdering::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetOrdering::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetOrdering::revision_not_found(),
+            };
+        }
+
+        match query_subtask::<challenges_orderings::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            Ordering::from,
+            include_note.0.unwrap_or(false),
+        )
+        .await?
+        {
+            Some(ordering) => GetOrdering::ok(ordering),
+            None => GetOrdering::subtask_not_found(),
+        }
+    }
+
+    /// Get an ordering and its solution by id.
+    #[oai(
+        path = "/tasks/:task_id/orderings/:subtask_id/solution",
+        method = "get"
+    )]
+    async fn get_ordering_with_solution(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the ordering as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetOrderingWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetOrderingWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_orderings::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                OrderingWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetOrderingWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetOrderingWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetOrderingWithSolution::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask_admin::<challenges_orderings::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            OrderingWithSolution::from,
+        )
+        .await?
+        {
+            Ok(ordering) => GetOrderingWithSolution::ok(ordering),
+            Err(QuerySubtaskAdminError::NotFound) => GetOrderingWithSolution::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => GetOrderingWithSolution::forbidden(),
+        }
+    }
+
+    /// Create a new ordering.
+    #[oai(path = "/tasks/:task_id/orderings", method = "post")]
+    async fn create_ordering(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<CreateOrderingRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateOrdering::Response<VerifiedUserAuth> {
+        let subtask = match create_subtask(
+            &db,
+            &self.state.services,
+            &self.config.load(),
+            &auth.0,
+            task_id.0,
+            data.0.subtask,
+            ChallengesSubtaskType::Ordering,
+        )
+        .await?
+        {
+            Ok(subtask) => subtask,
+            Err(CreateSubtaskError::TaskNotFound) => return CreateOrdering::task_not_found(),
+            Err(CreateSubtaskError::Forbidden) => return CreateOrdering::forbidden(),
+            Err(CreateSubtaskError::Banned(until)) => return CreateOrdering::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateOrdering::quota_exceeded(reset)
+            }
+            Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                return CreateOrdering::xp_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                return CreateOrdering::coin_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateOrdering::attribution_required()
+            }
+        };
+
+        match check_ordering(&data.0.items, &data.0.solution) {
+            Ok(()) => {}
+            Err(InvalidOrderingError::SolutionDifferentLength) => {
+                return CreateOrdering::solution_different_length()
+            }
+            Err(InvalidOrderingError::InvalidIndex(x)) => return CreateOrdering::invalid_index(x),
+            Err(InvalidOrderingError::ItemsNotOrdered(x)) => {
+                return CreateOrdering::items_not_ordered(x)
+            }
+        }
+
+        let fingerprint = ordering_fingerprint(
+            &data.0.question,
+            &data.0.items,
+            &data.0.solution.iter().map(|&x| x as _).collect::<Vec<_>>(),
+        );
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::Ordering,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateOrdering::duplicate(duplicates);
+            }
+        }
+
+        let ordering = challenges_orderings::ActiveModel {
+            subtask_id: Set(subtask.id),
+            question: Set(data.0.question),
+            items: Set(data.0.items),
+            solution: Set(data.0.solution.into_iter().map(|x| x as _).collect()),
+        }
+        .insert(&***db)
+        .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &ordering).await?;
+        CreateOrdering::ok(OrderingWithSolution::from(ordering, subtask))
+    }
+
+    /// Update an ordering.
+    #[oai(path = "/tasks/:task_id/orderings/:subtask_id", method = "patch")]
+    async fn update_ordering(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<UpdateOrderingRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> UpdateOrdering::Response<AdminAuth> {
+        let (ordering, subtask) = match update_subtask::<challenges_orderings::Entity>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            data.0.subtask,
+        )
+        .await?
+        {
+            Ok(x) => x,
+            Err(UpdateSubtaskError::SubtaskNotFound) => return UpdateOrdering::subtask_not_found(),
+            Err(UpdateSubtaskError::TaskNotFound) => return UpdateOrdering::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateOrdering::attribution_required()
+            }
+        };
+
+        match check_ordering(
+            data.0.items.get_new(&ordering.items),
+            data.0
+                .solution
+                .get_new(&ordering.solution.iter().map(|&x| x as _).collect()),
+        ) {
+            Ok(()) => {}
+            Err(InvalidOrderingError::SolutionDifferentLength) => {
+                return UpdateOrdering::solution_different_length()
+            }
+            Err(InvalidOrderingError::InvalidIndex(x)) => return UpdateOrdering::invalid_index(x),
+            Err(InvalidOrderingError::ItemsNotOrdered(x)) => {
+                return UpdateOrdering::items_not_ordered(x)
+            }
+        }
+
+        let ordering = challenges_orderings::ActiveModel {
+            subtask_id: Unchanged(ordering.subtask_id),
+            question: data.0.question.update(ordering.question),
+            items: data.0.items.update(ordering.items),
+            solution: data
+                .0
+                .solution
+                .map(|x| x.into_iter().map(|x| x as _).collect())
+                .update(ordering.solution),
+        }
+        .update(&***db)
+        .await?;
+        set_fingerprint(
+            &db,
+            ordering.subtask_id,
+            ordering_fingerprint(&ordering.question, &ordering.items, &ordering.solution),
+        )
+        .await?;
+        record_revision(&db, ordering.subtask_id, &ordering).await?;
+
+        UpdateOrdering::ok(OrderingWithSolution::from(ordering, subtask))
+    }
+
+    /// Attempt to solve an ordering.
+    #[oai(
+        path = "/tasks/:task_id/orderings/:subtask_id/attempts",
+        method = "post"
+    )]
+    async fn solve_ordering(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SolveOrderingRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SolveOrdering::Response<VerifiedUserAuth> {
+        let Some((ordering, subtask)) =
+            get_subtask::<challenges_orderings::Entity>(&db, task_id.0, subtask_id.0).await?
+        else {
+            return SolveOrdering::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SolveOrdering::subtask_not_found();
+        }
+
+        if data.0.answer.len() != ordering.solution.len() {
+            return SolveOrdering::solution_different_length();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.orderings.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return SolveOrdering::too_many_requests(time_left as u64);
+            }
+        }
+
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveOrdering::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
+            return SolveOrdering::not_enough_hearts();
+        }
+
+        let correct = data
+            .0
+            .answer
+            .iter()
+            .zip(ordering.solution.iter())
+            .filter(|(&x, &y)| x == y as u8)
+            .count();
+        let solved = correct == ordering.solution.len();
+
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveOrdering::accepted(exam_answer.into());
+        }
+
+        if let Some(solve_kind) = solve_kind {
+            let now = Utc::now().naive_utc();
+            if solved {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(now)),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+
+                if auth.0.id != subtask.creator {
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
+                }
+            } else {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+
+            challenges_ordering_attempts::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                ordering_id: Set(ordering.subtask_id),
+                user_id: Set(auth.0.id),
+                timestamp: Set(now),
+                solved: Set(solved),
+                revision_id: Set(get_current_revision(&db, ordering.subtask_id).await?),
+            }
+            .insert(&***db)
+            .await?;
+        }
+
+        SolveOrdering::ok(SolveOrderingFeedback { solved, correct })
+    }
+}
+
+response!(ListOrderings = {
+    Ok(200) => Vec<OrderingSummary>,
+});
+
+response!(GetOrdering = {
+    Ok(200) => Ordering,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
+});
+
+response!(GetOrderingWithSolution = {
+    Ok(200) => OrderingWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to view the solution to this ordering.
+    Forbidden(403, error),
+});
+
+response!(CreateOrdering = {
+    Ok(201) => OrderingWithSolution,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create orderings in this task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The solution list does not contain the same number of entries as the items list.
+    SolutionDifferentLength(400, error),
+    /// The solution list contains an invalid index.
+    InvalidIndex(400, error) => u8,
+    /// One or more items do not appear in the solution list.
+    ItemsNotOrdered(400, error) => HashSet<u8>,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
+});
+
+response!(UpdateOrdering = {
+    Ok(200) => OrderingWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The solution list does not contain the same number of entries as the items list.
+    SolutionDifferentLength(400, error),
+    /// The solution list contains an invalid index.
+    InvalidIndex(400, error) => u8,
+    /// One or more items do not appear in the solution list.
+    ItemsNotOrdered(400, error) => HashSet<u8>,
+});
+
+response!(SolveOrdering = {
+    Ok(201) => SolveOrderingFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+    /// The answer list does not contain the same number of entries as the items list.
+    SolutionDifferentLength(400, error),
+});
+
+fn check_ordering(items: &[String], solution: &[u8]) -> Result<(), InvalidOrderingError> {
+    let n = items.len();
+    if solution.len() != n {
+        return Err(InvalidOrderingError::SolutionDifferentLength);
+    }
+    if let Some(&x) = solution.iter().find(|&&x| x >= n as _) {
+        return Err(InvalidOrderingError::InvalidIndex(x));
+    }
+    let mut not_ordered: HashSet<u8> = (0..n as _).collect();
+    for &x in solution {
+        not_ordered.remove(&x);
+    }
+    if !not_ordered.is_empty() {
+        return Err(InvalidOrderingError::ItemsNotOrdered(not_ordered));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum InvalidOrderingError {
+    SolutionDifferentLength,
+    InvalidIndex(u8),
+    ItemsNotOrdered(HashSet<u8>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_ordering() {
+        let items = ["B".into(), "C".into(), "A".into()];
+        assert_eq!(check_ordering(&items, &[2, 0, 1]), Ok(()));
+        assert_eq!(
+            check_ordering(&items, &[2, 0]),
+            Err(InvalidOrderingError::SolutionDifferentLength)
+        );
+        assert_eq!(
+            check_ordering(&items, &[2, 0, 3]),
+            Err(InvalidOrderingError::InvalidIndex(3))
+        );
+        assert_eq!(
+            check_ordering(&items, &[2, 0, 2]),
+            Err(InvalidOrderingError::ItemsNotOrdered([1].into()))
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::Utc;
+use entity::{challenges_organization_members, challenges_organizations};
+use lib::auth::{AdminAuth, VerifiedUserAuth};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::organizations::{
+    CreateOrganizationRequest, Organization, OrganizationMember, SetOrganizationMemberRequest,
+};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::organizations::can_manage_organization;
+
+pub struct Organizations;
+
+#[OpenApi(tag = "Tags::Organizations")]
+impl Organizations {
+    /// List all organizations the user is a member of.
+    ///
+    /// Admins can see all organizations.
+    #[oai(path = "/organizations", method = "get")]
+    async fn list_organizations(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListOrganizations::Response<VerifiedUserAuth> {
+        let mut query = challenges_organizations::Entity::find()
+            .order_by_asc(challenges_organizations::Column::CreationTimestamp);
+        if !auth.0.admin {
+            let member_of = challenges_organization_members::Entity::find()
+                .filter(challenges_organization_members::Column::UserId.eq(auth.0.id))
+                .all(&***db)
+                .await?;
+            query = query.filter(
+                challenges_organizations::Column::Id
+                    .is_in(member_of.into_iter().map(|x| x.organization_id)),
+            );
+        }
+        ListOrganizations::ok(
+            query
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Create a new organization.
+    #[oai(path = "/organizations", method = "post")]
+    async fn create_organization(
+        &self,
+        data: Json<CreateOrganizationRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> CreateOrganization::Response<AdminAuth> {
+        let organization = challenges_organizations::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            name: Set(data.0.name),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+        }
+        .insert(&***db)
+        .await?;
+        CreateOrganization::created(organization.into())
+    }
+
+    /// Delete an organization.
+    ///
+    /// This will also delete all tasks of this organization!
+    #[oai(path = "/organizations/:organization_id", method = "delete")]
+    async fn delete_organization(
+        &self,
+        organization_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DeleteOrganization::Response<AdminAuth> {
+        match challenges_organizations::Entity::find_by_id(organization_id.0)
+            .one(&***db)
+            .await?
+        {
+            Some(organization) => {
+                organization.delete(&***db).await?;
+                DeleteOrganization::ok()
+            }
+            None => DeleteOrganization::organization_not_found(),
+        }
+    }
+
+    /// List all members of an organization.
+    #[oai(path = "/organizations/:organization_id/members", method = "get")]
+    async fn list_organization_members(
+        &self,
+        organization_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListOrganizationMembers::Response<VerifiedUserAuth> {
+        let Some(organization) = challenges_organizations::Entity::find_by_id(organization_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ListOrganizationMembers::organization_not_found();
+        };
+        if !can_manage_organization(&db, &auth.0, organization.id).await? {
+            return ListOrganizationMembers::forbidden();
+        }
+        ListOrganizationMembers::ok(
+            organization
+                .find_related(challenges_organization_members::Entity)
+                .order_by_asc(challenges_organization_members::Column::JoinTimestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Add a member to an organization or change the role of a member.
+    #[oai(
+        path = "/organizations/:organization_id/members/:user_id",
+        method = "put"
+    )]
+    async fn set_organization_member(
+        &self,
+        organization_id: Path<Uuid>,
+        user_id: Path<Uuid>,
+        data: Json<SetOrganizationMemberRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SetOrganizationMember::Response<VerifiedUserAuth> {
+        let Some(organization) = challenges_organizations::Entity::find_by_id(organization_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return SetOrganizationMember::organization_not_found();
+        };
+        if !can_manage_organization(&db, &auth.0, organization.id).await? {
+            return SetOrganizationMember::forbidden();
+        }
+        let member = challenges_organization_members::Entity::insert(
+            challenges_organization_members::ActiveModel {
+                organization_id: Set(organization.id),
+                user_id: Set(user_id.0),
+                role: Set(data.0.role),
+                join_timestamp: Set(Utc::now().naive_utc()),
+            },
+        )
+        .on_conflict(
+            OnConflict::columns([
+                challenges_organization_members::Column::OrganizationId,
+                challenges_organization_members::Column::UserId,
+            ])
+            .update_column(challenges_organization_members::Column::Role)
+            .to_owned(),
+        )
+        .exec_with_returning(&***db)
+        .await?;
+        SetOrganizationMember::ok(member.into())
+    }
+
+    /// Remove a member from an organization.
+    ///
+    /// Members can remove themselves to leave the organization.
+    #[oai(
+        path = "/organizations/:organization_id/members/:user_id",
+        method = "delete"
+    )]
+    async fn remove_organization_member(
+        &self,
+        organization_id: Path<Uuid>,
+        user_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> RemoveOrganizationMember::Response<VerifiedUserAuth> {
+        let Some(organization) = challenges_organizations::Entity::find_by_id(organization_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return RemoveOrganizationMember::organization_not_found();
+        };
+        if !(user_id.0 == auth.0.id
+            || can_manage_organization(&db, &auth.0, organization.id).await?)
+        {
+            return RemoveOrganizationMember::forbidden();
+        }
+        let Some(member) =
+            challenges_organization_members::Entity::find_by_id((organization.id, user_id.0))
+                .one(&***db)
+                .await?
+        else {
+            return RemoveOrganizationMember::member_not_found();
+        };
+        member.delete(&***db).await?;
+        RemoveOrganizationMember::ok()
+    }
+}
+
+response!(ListOrganizations = {
+    Ok(200) => Vec<Organization>,
+});
+
+response!(CreateOrganization = {
+    Created(201) => Organization,
+});
+
+response!(DeleteOrganization = {
+    Ok(200),
+    /// Organization does not exist.
+    OrganizationNotFound(404, error),
+});
+
+response!(ListOrganizationMembers = {
+    Ok(200) => Vec<OrganizationMember>,
+    /// Organization does not exist.
+    OrganizationNotFound(404, error),
+    /// The user is not allowed to manage the members of this organization.
+    Forbidden(403, error),
+});
+
+response!(SetOrganizationMember = {
+    Ok(200) => OrganizationMember,
+    /// Organization does not exist.
+    OrganizationNotFound(404, error),
+    /// The user is not allowed to manage the members of this organization.
+    Forbidden(403, error),
+});
+
+response!(RemoveOrganizationMember = {
+    Ok(200),
+    /// Organization does not exist.
+    OrganizationNotFound(404, error),
+    /// The user is not allowed to remove this member.
+    Forbidden(403, error),
+    /// The user is not a member of this organization.
+    MemberNotFound(404, error),
+});
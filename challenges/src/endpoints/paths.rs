@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::{challenges_path_items, challenges_paths, challenges_subtasks, challenges_user_paths};
+use lib::{
+    auth::{AdminAuth, User, VerifiedUserAuth},
+    config::SharedConfig,
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::paths::{self, CreatePathRequest, PathItem, PathProgress};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    paths::{can_see_path, get_path, get_path_progress, send_path_rewards},
+    tasks::get_task,
+};
+
+pub struct Paths {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Paths")]
+impl Paths {
+    /// List all learning paths.
+    ///
+    /// Disabled learning paths are only included for admins. Learning paths
+    /// that reference tasks of other organizations are omitted.
+    #[oai(path = "/paths", method = "get")]
+    async fn list_paths(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListPaths::Response<VerifiedUserAuth> {
+        let mut query = challenges_paths::Entity::find()
+            .find_with_related(challenges_path_items::Entity)
+            .order_by_asc(challenges_paths::Column::CreationTimestamp)
+            .order_by_asc(challenges_path_items::Column::Position);
+        if !auth.0.admin {
+            query = query.filter(challenges_paths::Column::Enabled.eq(true));
+        }
+        let mut out = Vec::new();
+        for (path, items) in query.all(&***db).await? {
+            if can_see_path(&db, &auth.0, &items).await? {
+                out.push(paths::Path::from(path, items));
+            }
+        }
+        ListPaths::ok(out)
+    }
+
+    /// Get a learning path by id.
+    #[oai(path = "/paths/:path_id", method = "get")]
+    async fn get_path(
+        &self,
+        path_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetPath::Response<VerifiedUserAuth> {
+        match get_visible_path(&db, &auth.0, path_id.0).await? {
+            Some((path, items)) => GetPath::ok(paths::Path::from(path, items)),
+            None => GetPath::path_not_found(),
+        }
+    }
+
+    /// Create a new learning path.
+    #[oai(path = "/paths", method = "post")]
+    async fn create_path(
+        &self,
+        data: Json<CreatePathRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> CreatePath::Response<AdminAuth> {
+        match check_items(&db, &data.0.items).await? {
+            Some(ItemError::Invalid(position)) => return CreatePath::invalid_item(position),
+            Some(ItemError::TaskNotFound(id)) => return CreatePath::task_not_found(id),
+            Some(ItemError::SubtaskNotFound(id)) => return CreatePath::subtask_not_found(id),
+            None => {}
+        }
+
+        let path = challenges_paths::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            title: Set(data.0.title),
+            description: Set(data.0.description),
+            xp: Set(data.0.xp as _),
+            coins: Set(data.0.coins as _),
+            enabled: Set(data.0.enabled),
+        }
+        .insert(&***db)
+        .await?;
+        let items = insert_items(&db, path.id, data.0.items).await?;
+
+        CreatePath::created(paths::Path::from(path, items))
+    }
+
+    /// Replace a learning path.
+    ///
+    /// The progress of users is computed from their solved subtasks, so it is
+    /// not affected by changes of the items. Completion rewards that have
+    /// already been claimed are not claimed again.
+    #[oai(path = "/paths/:path_id", method = "put")]
+    async fn update_path(
+        &self,
+        path_id: Path<Uuid>,
+        data: Json<CreatePathRequest>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> UpdatePath::Response<AdminAuth> {
+        let Some(path) = challenges_paths::Entity::find_by_id(path_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return UpdatePath::path_not_found();
+        };
+        match check_items(&db, &data.0.items).await? {
+            Some(ItemError::Invalid(position)) => return UpdatePath::invalid_item(position),
+            Some(ItemError::TaskNotFound(id)) => return UpdatePath::task_not_found(id),
+            Some(ItemError::SubtaskNotFound(id)) => return UpdatePath::subtask_not_found(id),
+            None => {}
+        }
+
+        let path = challenges_paths::ActiveModel {
+            id: Unchanged(path.id),
+            creator: Unchanged(path.creator),
+            creation_timestamp: Unchanged(path.creation_timestamp),
+            title: Set(data.0.title),
+            description: Set(data.0.description),
+            xp: Set(data.0.xp as _),
+            coins: Set(data.0.coins as _),
+            enabled: Set(data.0.enabled),
+        }
+        .update(&***db)
+        .await?;
+        challenges_path_items::Entity::delete_many()
+            .filter(challenges_path_items::Column::PathId.eq(path.id))
+            .exec(&***db)
+            .await?;
+        let items = insert_items(&db, path.id, data.0.items).await?;
+
+        UpdatePath::ok(paths::Path::from(path, items))
+    }
+
+    /// Delete a learning path.
+    #[oai(path = "/paths/:path_id", method = "delete")]
+    async fn delete_path(
+        &self,
+        path_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DeletePath::Response<AdminAuth> {
+        let Some(path) = challenges_paths::Entity::find_by_id(path_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return DeletePath::path_not_found();
+        };
+        path.delete(&***db).await?;
+        DeletePath::ok()
+    }
+
+    /// Return the progress of the authenticated user in a learning path.
+    #[oai(path = "/paths/:path_id/progress", method = "get")]
+    async fn get_progress(
+        &self,
+        path_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetProgress::Response<VerifiedUserAuth> {
+        match get_visible_path(&db, &auth.0, path_id.0).await? {
+            Some((path, items)) => {
+                GetProgress::ok(get_path_progress(&db, auth.0.id, path.id, &items).await?)
+            }
+            None => GetProgress::path_not_found(),
+        }
+    }
+
+    /// Claim the completion rewards of a learning path.
+    ///
+    /// The rewards can only be claimed once, after all items of the learning
+    /// path have been completed.
+    #[oai(path = "/paths/:path_id/claim", method = "post")]
+    async fn claim_rewards(
+        &self,
+        path_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ClaimRewards::Response<VerifiedUserAuth> {
+        let Some((path, items)) = get_visible_path(&db, &auth.0, path_id.0).await? else {
+            return ClaimRewards::path_not_found();
+        };
+
+        let mut progress = get_path_progress(&db, auth.0.id, path.id, &items).await?;
+        if progress.claimed.is_some() {
+            return ClaimRewards::already_claimed();
+        }
+        if !progress.completed {
+            return ClaimRewards::not_completed();
+        }
+
+        let now = Utc::now();
+        challenges_user_paths::ActiveModel {
+            user_id: Set(auth.0.id),
+            path_id: Set(path.id),
+            completed_timestamp: Set(now.naive_utc()),
+        }
+        .insert(&***db)
+        .await?;
+        send_path_rewards(
+            &self.state.services,
+            &db,
+            &self.config.load().challenges.rewards,
+            auth.0.id,
+            &path,
+            &items,
+        )
+        .await?;
+
+        progress.claimed = Some(now);
+        ClaimRewards::ok(progress)
+    }
+}
+
+response!(ListPaths = {
+    Ok(200) => Vec<paths::Path>,
+});
+
+response!(GetPath = {
+    Ok(200) => paths::Path,
+    /// Learning path does not exist.
+    PathNotFound(404, error),
+});
+
+response!(CreatePath = {
+    Created(201) => paths::Path,
+    /// An item does not reference exactly one task or subtask. `details` contains the position of the item.
+    InvalidItem(400, error) => u32,
+    /// Task does not exist.
+    TaskNotFound(404, error) => Uuid,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error) => Uuid,
+});
+
+response!(UpdatePath = {
+    Ok(200) => paths::Path,
+    /// Learning path does not exist.
+    PathNotFound(404, error),
+    /// An item does not reference exactly one task or subtask. `details` contains the position of the item.
+    InvalidItem(400, error) => u32,
+    /// Task does not exist.
+    TaskNotFound(404, error) => Uuid,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error) => Uuid,
+});
+
+response!(DeletePath = {
+    Ok(200),
+    /// Learning path does not exist.
+    PathNotFound(404, error),
+});
+
+response!(GetProgress = {
+    Ok(200) => PathProgress,
+    /// Learning path does not exist.
+    PathNotFound(404, error),
+});
+
+response!(ClaimRewards = {
+    Ok(200) => PathProgress,
+    /// Learning path does not exist.
+    PathNotFound(404, error),
+    /// The user has not completed all items of the learning path.
+    NotCompleted(403, error),
+    /// The user has already claimed the rewards of this learning path.
+    AlreadyClaimed(409, error),
+});
+
+/// Return a learning path if it is enabled (or the user is an admin) and the
+/// user can see all referenced tasks.
+async fn get_visible_path(
+    db: &DatabaseTransaction,
+    user: &User,
+    path_id: Uuid,
+) -> Result<Option<(challenges_paths::Model, Vec<challenges_path_items::Model>)>, DbErr> {
+    let Some((path, items)) = get_path(db, path_id)
+        .await?
+        .filter(|(path, _)| path.enabled || user.admin)
+    else {
+        return Ok(None);
+    };
+    Ok(can_see_path(db, user, &items)
+        .await?
+        .then_some((path, items)))
+}
+
+enum ItemError {
+    Invalid(u32),
+    TaskNotFound(Uuid),
+    SubtaskNotFound(Uuid),
+}
+
+async fn check_items(
+    db: &DatabaseTransaction,
+    items: &[PathItem],
+) -> Result<Option<ItemError>, DbErr> {
+    for (position, item) in items.iter().enumerate() {
+        match (item.task_id, item.subtask_id) {
+            (Some(task_id), None) => {
+                if get_task(db, task_id).await?.is_none() {
+                    return Ok(Some(ItemError::TaskNotFound(task_id)));
+                }
+            }
+            (None, Some(subtask_id)) => {
+                if challenges_subtasks::Entity::find_by_id(subtask_id)
+                    .one(db)
+                    .await?
+                    .is_none()
+                {
+                    return Ok(Some(ItemError::SubtaskNotFound(subtask_id)));
+                }
+            }
+            _ => return Ok(Some(ItemError::Invalid(position as _))),
+        }
+    }
+    Ok(None)
+}
+
+async fn insert_items(
+    db: &DatabaseTransaction,
+    path_id: Uuid,
+    items: Vec<PathItem>,
+) -> Result<Vec<challenges_path_items::Model>, DbErr> {
+    let mut out = Vec::with_capacity(items.len());
+    for (position, item) in items.into_iter().enumerate() {
+        out.push(
+            challenges_path_items::ActiveModel {
+                path_id: Set(path_id),
+                position: Set(position as _),
+                task_id: Set(item.task_id),
+                subtask_id: Set(item.subtask_id),
+                min_solved: Set(item.min_solved.map(|x| x as _)),
+            }
+            .insert(db)
+            .await?,
+        );
+    }
+    Ok(out)
+}
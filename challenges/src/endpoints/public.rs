@@ -0,0 +1,278 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::NaiveDateTime;
+use entity::{
+    challenges_challenges, challenges_clozes, challenges_code_readings,
+    challenges_coding_challenges, challenges_matchings, challenges_multiple_choice_quizes,
+    challenges_orderings, challenges_questions, challenges_sql_challenges, challenges_subtasks,
+    challenges_tasks, challenges_true_false_statements,
+};
+use lib::{config::SharedConfig, SharedState};
+use poem::{
+    error::{InternalServerError, NotFoundError},
+    handler,
+    http::header,
+    web::Data,
+    IntoResponse,
+};
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, OpenApi};
+use schemas::challenges::{
+    cloze::Cloze,
+    code_readings::CodeReading,
+    coding_challenges::CodingChallenge,
+    feed::Feed,
+    matchings::Matching,
+    multiple_choice::MultipleChoiceQuestion,
+    orderings::Ordering,
+    question::Question,
+    sql_challenges::SqlChallenge,
+    subtasks::{Subtask, SubtaskContent},
+    tasks::Task,
+    true_false::TrueFalse,
+};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Related,
+};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    exams::get_active_exam,
+    feed::{generate_feed, public_subtasks, render_sitemap, FeedCache},
+    question_variants::{generate_parameters, instantiate},
+};
+
+pub struct Public {
+    pub config: SharedConfig,
+    pub feed: Arc<FeedCache>,
+}
+
+#[OpenApi(tag = "Tags::Public")]
+impl Public {
+    /// List all published challenges.
+    ///
+    /// This endpoint does not require authentication, but is only available if
+    /// the public api is enabled.
+    #[oai(path = "/public/tasks", method = "get")]
+    async fn list_public_tasks(&self, db: Data<&DbTxn>) -> ListPublicTasks::Response {
+        if !self.config.load().challenges.public_api.enabled {
+            return ListPublicTasks::not_found();
+        }
+
+        let challenges = challenges_challenges::Entity::find()
+            .find_also_related(challenges_tasks::Entity)
+            .filter(challenges_tasks::Column::OrganizationId.is_null())
+            .order_by_asc(challenges_challenges::Column::Title)
+            .all(&***db)
+            .await?;
+        let mut subtasks = challenges_subtasks::Entity::find()
+            .select_only()
+            .column(challenges_subtasks::Column::TaskId)
+            .column_as(Expr::col(challenges_subtasks::Column::Id).count(), "count")
+            .filter(public_subtasks())
+            .group_by(challenges_subtasks::Column::TaskId)
+            .into_tuple::<(Uuid, i64)>()
+            .all(&***db)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        ListPublicTasks::ok(
+            challenges
+                .into_iter()
+                .filter_map(|(challenge, task)| {
+                    let task = task?;
+                    let subtasks = subtasks.remove(&task.id)?;
+                    Some(Task::from(task, Some(challenge), None, subtasks as _))
+                })
+                .collect(),
+        )
+    }
+
+    /// List the published subtasks of a challenge without their solutions.
+    ///
+    /// This endpoint does not require authentication, but is only available if
+    /// the public api is enabled.
+    #[oai(path = "/public/tasks/:task_id/subtasks", method = "get")]
+    async fn list_public_subtasks(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+    ) -> ListPublicSubtasks::Response {
+        if !self.config.load().challenges.public_api.enabled {
+            return ListPublicSubtasks::not_found();
+        }
+
+        match challenges_challenges::Entity::find_by_id(task_id.0)
+            .find_also_related(challenges_tasks::Entity)
+            .one(&***db)
+            .await?
+        {
+            Some((_, Some(task))) if task.organization_id.is_none() => {}
+            _ => return ListPublicSubtasks::task_not_found(),
+        }
+        if get_active_exam(&db, task_id.0).await?.is_some() {
+            return ListPublicSubtasks::task_not_found();
+        }
+
+        let mut subtasks = Vec::new();
+        subtasks.extend(
+            load::<challenges_coding_challenges::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::CodingChallenge(CodingChallenge::from(x, subtask))
+            })
+            .await?,
+        );
+        subtasks.extend(
+            load::<challenges_matchings::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::Matching(Matching::from(x, subtask))
+            })
+            .await?,
+        );
+        subtasks.extend(
+            load::<challenges_multiple_choice_quizes::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::MultipleChoice(MultipleChoiceQuestion::<String>::from(x, subtask))
+            })
+            .await?,
+        );
+        for (timestamp, (question, subtask)) in
+            load::<challenges_questions::Entity, _>(&db, task_id.0, |x, subtask| (x, subtask))
+                .await?
+        {
+            // parameterized questions are shown with a fixed variant
+            let values = generate_parameters(0, &question.parameters.0);
+            let question = instantiate(question, &values)?;
+            subtasks.push((
+                timestamp,
+                SubtaskContent::Question(Question::from(question, subtask)),
+            ));
+        }
+        subtasks.extend(
+            load::<challenges_true_false_statements::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::TrueFalse(TrueFalse::from(x, subtask))
+            })
+            .await?,
+        );
+        subtasks.extend(
+            load::<challenges_orderings::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::Ordering(Ordering::from(x, subtask))
+            })
+            .await?,
+        );
+        subtasks.extend(
+            load::<challenges_clozes::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::Cloze(Cloze::from(x, subtask))
+            })
+            .await?,
+        );
+        subtasks.extend(
+            load::<challenges_code_readings::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::CodeReading(CodeReading::from(x, subtask))
+            })
+            .await?,
+        );
+        subtasks.extend(
+            load::<challenges_sql_challenges::Entity, _>(&db, task_id.0, |x, subtask| {
+                SubtaskContent::SqlChallenge(SqlChallenge::from(x, subtask))
+            })
+            .await?,
+        );
+
+        subtasks.sort_by_key(|(timestamp, _)| *timestamp);
+        ListPublicSubtasks::ok(subtasks.into_iter().map(|(_, x)| x).collect())
+    }
+
+    /// Return the most recently added public challenges and subtasks.
+    ///
+    /// The feed is updated periodically, so new content may not appear
+    /// immediately. This endpoint does not require authentication, but is only
+    /// available if the public api is enabled.
+    #[oai(path = "/feed.json", method = "get")]
+    async fn get_feed(&self, db: Data<&DbTxn>) -> GetFeed::Response {
+        let config = self.config.load();
+        if !config.challenges.public_api.enabled {
+            return GetFeed::not_found();
+        }
+        let feed = match self.feed.get() {
+            Some(feed) => feed,
+            None => self
+                .feed
+                .set(generate_feed(&***db, config.challenges.public_api.feed_size).await?),
+        };
+        GetFeed::ok(Feed::clone(&feed))
+    }
+}
+
+/// Sitemap of the public challenges in the feed.
+#[handler]
+pub async fn sitemap(
+    state: Data<&Arc<SharedState>>,
+    config: Data<&SharedConfig>,
+    feed: Data<&Arc<FeedCache>>,
+) -> poem::Result<impl IntoResponse> {
+    let config = config.load();
+    if !config.challenges.public_api.enabled {
+        return Err(NotFoundError.into());
+    }
+    let cached = match feed.get() {
+        Some(cached) => cached,
+        None => feed.set(
+            generate_feed(&state.db, config.challenges.public_api.feed_size)
+                .await
+                .map_err(InternalServerError)?,
+        ),
+    };
+    Ok(
+        render_sitemap(&cached, &config.challenges.public_api.task_url)
+            .with_content_type("application/xml")
+            .with_header(header::CACHE_CONTROL, "public, max-age=3600"),
+    )
+}
+
+response!(ListPublicTasks = {
+    Ok(200) => Vec<Task>,
+    /// The public api is disabled.
+    NotFound(404, error),
+});
+
+response!(ListPublicSubtasks = {
+    Ok(200) => Vec<SubtaskContent>,
+    /// The public api is disabled.
+    NotFound(404, error),
+    /// Task does not exist or is not public.
+    TaskNotFound(404, error),
+});
+
+response!(GetFeed = {
+    Ok(200) => Feed,
+    /// The public api is disabled.
+    NotFound(404, error),
+});
+
+/// Load the public subtasks of a specific type in a task together with their
+/// creation timestamps.
+async fn load<E, T>(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+    map: impl Fn(E::Model, Subtask) -> T,
+) -> Result<Vec<(NaiveDateTime, T)>, DbErr>
+where
+    E: EntityTrait + Related<challenges_subtasks::Entity>,
+{
+    Ok(E::find()
+        .find_also_related(challenges_subtasks::Entity)
+        .filter(challenges_subtasks::Column::TaskId.eq(task_id))
+        .filter(public_subtasks())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(specific, subtask)| {
+            let subtask = subtask?;
+            Some((
+                subtask.creation_timestamp,
+                map(specific, Subtask::from(subtask, false, false)),
+            ))
+        })
+        .collect())
+}
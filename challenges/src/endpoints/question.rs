@@ -2,38 +2,56 @@ use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use entity::{
-    challenges_questions, challenges_user_subtasks, sea_orm_active_enums::ChallengesSubtaskType,
+    challenges_questions::{self, QuestionParameters},
+    challenges_user_subtasks,
+    sea_orm_active_enums::{ChallengesQuestionAnswerType, ChallengesSubtaskType},
 };
 use lib::{
     auth::{AdminAuth, VerifiedUserAuth},
-    config::Config,
+    config::SharedConfig,
+    events::{publish_event, Event},
     SharedState,
 };
 use poem::web::Data;
-use poem_ext::{db::DbTxn, response};
+use poem_ext::{db::DbTxn, patch_value::PatchValue, response};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     OpenApi,
 };
-use schemas::challenges::question::{
-    CreateQuestionRequest, Question, QuestionSummary, QuestionWithSolution, SolveQuestionFeedback,
-    SolveQuestionRequest, UpdateQuestionRequest,
+use schemas::challenges::{
+    exams::ExamAnswer,
+    question::{
+        CreateQuestionRequest, Question, QuestionSummary, QuestionWithSolution,
+        SolveQuestionFeedback, SolveQuestionRequest, UpdateQuestionRequest,
+    },
+    subtasks::ContentViolation,
 };
 use sea_orm::{ActiveModelTrait, Set, Unchanged};
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::subtasks::{
-    create_subtask, deduct_hearts, get_subtask, get_user_subtask, query_subtask,
-    query_subtask_admin, query_subtasks, send_task_rewards, update_subtask, update_user_subtask,
-    CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter, UpdateSubtaskError,
-    UserSubtaskExt,
+use crate::services::{
+    content_filter::ContentChecker,
+    exams::{get_active_exam, record_exam_answer},
+    notifications::notify_subtask_solved,
+    numeric::{parse_number, NumericAnswer},
+    question_variants::{get_variant, validate_template},
+    reviews::schedule_review,
+    revisions::{query_subtask_as_of, record_revision, QuerySubtaskAsOfError},
+    similarity::{find_duplicates, fingerprint, set_fingerprint},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
 };
 
 pub struct Questions {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::Questions")]
@@ -72,6 +90,7 @@ impl Questions {
                     retired: retired.0,
                     creator: creator.0,
                     ty: None,
+                    permissive: None,
                 },
                 QuestionSummary::from,
             )
@@ -85,19 +104,50 @@ impl Questions {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the question as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetQuestion::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetQuestion::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_questions::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                Question::from,
+            )
+            .await?
+            {
+                Ok(x) => GetQuestion::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetQuestion::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetQuestion::revision_not_found(),
+            };
+        }
+
         match query_subtask::<challenges_questions::Entity, _>(
             &db,
             &auth.0,
             task_id.0,
             subtask_id.0,
-            Question::from,
+            |question, subtask| (question, subtask),
+            include_note.0.unwrap_or(false),
         )
         .await?
         {
-            Some(mcq) => GetQuestion::ok(mcq),
+            Some((question, subtask)) => {
+                let attempt = get_user_subtask(&db, auth.0.id, subtask.id)
+                    .await?
+                    .attempts();
+                let question = get_variant(&db, auth.0.id, attempt, question).await??;
+                GetQuestion::ok(Question::from(question, subtask))
+            }
             None => GetQuestion::subtask_not_found(),
         }
     }
@@ -111,9 +161,35 @@ impl Questions {
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
+        /// Return the question as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetQuestionWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetQuestionWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_questions::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                QuestionWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetQuestionWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetQuestionWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetQuestionWithSolution::revision_not_found()
+                }
+            };
+        }
+
         match query_subtask_admin::<challenges_questions::Entity, _>(
             &db,
             &auth.0,
@@ -135,13 +211,16 @@ impl Questions {
         &self,
         task_id: Path<Uuid>,
         data: Json<CreateQuestionRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> CreateQuestion::Response<VerifiedUserAuth> {
         let subtask = match create_subtask(
             &db,
             &self.state.services,
-            &self.config,
+            &self.config.load(),
             &auth.0,
             task_id.0,
             data.0.subtask,
@@ -153,35 +232,89 @@ impl Questions {
             Err(CreateSubtaskError::TaskNotFound) => return CreateQuestion::task_not_found(),
             Err(CreateSubtaskError::Forbidden) => return CreateQuestion::forbidden(),
             Err(CreateSubtaskError::Banned(until)) => return CreateQuestion::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateQuestion::quota_exceeded(reset)
+            }
             Err(CreateSubtaskError::XpLimitExceeded(x)) => {
                 return CreateQuestion::xp_limit_exceeded(x)
             }
             Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
                 return CreateQuestion::coin_limit_exceeded(x)
             }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateQuestion::attribution_required()
+            }
+        };
+
+        let parameters = data
+            .0
+            .parameters
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        let sample_answers = match validate_template(&parameters, &data.0.question, &data.0.answers)
+        {
+            Ok(answers) => answers,
+            Err(err) => return CreateQuestion::invalid_template(err),
         };
 
         if !check_answers(
-            &data.0.answers,
+            &sample_answers,
             data.0.ascii_letters,
             data.0.digits,
             data.0.punctuation,
         ) {
             return CreateQuestion::invalid_char();
         }
+        let answer_type = data
+            .0
+            .answer_type
+            .unwrap_or(ChallengesQuestionAnswerType::Text);
+        if !check_numeric_answers(answer_type, &sample_answers) {
+            return CreateQuestion::invalid_number();
+        }
+
+        let config = self.config.load();
+        let mut checker = ContentChecker::new(&config.challenges.content_filter, &auth.0);
+        let question = checker.check("question", data.0.question);
+        let answers = checker.check_all("answers", data.0.answers);
+        if let Err(violations) = checker.finish() {
+            return CreateQuestion::content_violation(violations);
+        }
+
+        let fingerprint = fingerprint(&question, answers.iter().map(String::as_str));
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::Question,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateQuestion::duplicate(duplicates);
+            }
+        }
 
         let question = challenges_questions::ActiveModel {
             subtask_id: Set(subtask.id),
-            question: Set(data.0.question),
-            answers: Set(data.0.answers),
+            question: Set(question),
+            answers: Set(answers),
             case_sensitive: Set(data.0.case_sensitive),
             ascii_letters: Set(data.0.ascii_letters),
             digits: Set(data.0.digits),
             punctuation: Set(data.0.punctuation),
             blocks: Set(data.0.blocks),
+            parameters: Set(QuestionParameters(parameters)),
+            answer_type: Set(answer_type),
+            unit: Set(data.0.unit),
+            absolute_tolerance: Set(data.0.absolute_tolerance),
+            relative_tolerance: Set(data.0.relative_tolerance),
         }
         .insert(&***db)
         .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &question).await?;
         CreateQuestion::ok(QuestionWithSolution::from(question, subtask))
     }
 
@@ -207,16 +340,40 @@ impl Questions {
             Ok(x) => x,
             Err(UpdateSubtaskError::SubtaskNotFound) => return UpdateQuestion::subtask_not_found(),
             Err(UpdateSubtaskError::TaskNotFound) => return UpdateQuestion::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateQuestion::attribution_required()
+            }
         };
 
-        if !check_answers(
+        let parameters = match data.0.parameters {
+            PatchValue::Set(parameters) => {
+                QuestionParameters(parameters.into_iter().map(Into::into).collect())
+            }
+            PatchValue::Unchanged => question.parameters.clone(),
+        };
+        let sample_answers = match validate_template(
+            &parameters.0,
+            data.0.question.get_new(&question.question),
             data.0.answers.get_new(&question.answers),
+        ) {
+            Ok(answers) => answers,
+            Err(err) => return UpdateQuestion::invalid_template(err),
+        };
+
+        if !check_answers(
+            &sample_answers,
             *data.0.ascii_letters.get_new(&question.ascii_letters),
             *data.0.digits.get_new(&question.digits),
             *data.0.punctuation.get_new(&question.punctuation),
         ) {
             return UpdateQuestion::invalid_char();
         }
+        if !check_numeric_answers(
+            *data.0.answer_type.get_new(&question.answer_type),
+            &sample_answers,
+        ) {
+            return UpdateQuestion::invalid_number();
+        }
 
         let question = challenges_questions::ActiveModel {
             subtask_id: Unchanged(question.subtask_id),
@@ -227,9 +384,30 @@ impl Questions {
             digits: data.0.digits.update(question.digits),
             punctuation: data.0.punctuation.update(question.punctuation),
             blocks: data.0.blocks.update(question.blocks),
+            parameters: Set(parameters),
+            answer_type: data.0.answer_type.update(question.answer_type),
+            unit: data.0.unit.update(question.unit),
+            absolute_tolerance: data
+                .0
+                .absolute_tolerance
+                .update(question.absolute_tolerance),
+            relative_tolerance: data
+                .0
+                .relative_tolerance
+                .update(question.relative_tolerance),
         }
         .update(&***db)
         .await?;
+        set_fingerprint(
+            &db,
+            question.subtask_id,
+            fingerprint(
+                &question.question,
+                question.answers.iter().map(String::as_str),
+            ),
+        )
+        .await?;
+        record_revision(&db, question.subtask_id, &question).await?;
 
         UpdateQuestion::ok(QuestionWithSolution::from(question, subtask))
     }
@@ -239,7 +417,7 @@ impl Questions {
         path = "/tasks/:task_id/questions/:subtask_id/attempts",
         method = "post"
     )]
-    async fn solve_question(
+    pub(super) async fn solve_question(
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
@@ -252,32 +430,44 @@ impl Questions {
         else {
             return SolveQuestion::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return SolveQuestion::subtask_not_found();
         }
 
         let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
 
-        let solved_previously = user_subtask.is_solved();
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
         if let Some(last_attempt) = user_subtask.last_attempt() {
-            let time_left = self.config.challenges.questions.timeout as i64
-                - (Utc::now() - last_attempt).num_seconds();
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.questions.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
             if time_left > 0 {
                 return SolveQuestion::too_many_requests(time_left as u64);
             }
         }
 
-        if !deduct_hearts(&self.state.services, &self.config, &auth.0, &subtask).await? {
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveQuestion::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
             return SolveQuestion::not_enough_hearts();
         }
 
-        let answer = normalize_answer(&data.0.answer, question.case_sensitive);
-        let solved = question
-            .answers
-            .iter()
-            .any(|ans| normalize_answer(ans, question.case_sensitive) == answer);
+        let question = get_variant(&db, auth.0.id, user_subtask.attempts(), question).await??;
+        let solved = check_answer(&question, &data.0.answer);
+
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveQuestion::accepted(exam_answer.into());
+        }
 
-        if !solved_previously {
+        if let Some(solve_kind) = solve_kind {
             let now = Utc::now().naive_utc();
             if solved {
                 update_user_subtask(
@@ -293,9 +483,32 @@ impl Questions {
                     },
                 )
                 .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+                schedule_review(&db, auth.0.id, subtask.id, now).await?;
 
                 if auth.0.id != subtask.creator {
-                    send_task_rewards(&self.state.services, &db, auth.0.id, &subtask).await?;
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
                 }
             } else {
                 update_user_subtask(
@@ -325,12 +538,18 @@ response!(GetQuestion = {
     Ok(200) => Question,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
 });
 
 response!(GetQuestionWithSolution = {
     Ok(200) => QuestionWithSolution,
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
     /// The user is not allowed to view the solution to this question.
     Forbidden(403, error),
 });
@@ -343,12 +562,24 @@ response!(CreateQuestion = {
     Forbidden(403, error),
     /// The user is currently banned from creating subtasks.
     Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
     /// The max xp limit has been exceeded.
     XpLimitExceeded(403, error) => u64,
     /// The max coin limit has been exceeded.
     CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// One of `ascii_letters`, `digits` or `punctuation` is set to `false`, but one of the `answers` contains such a character.
     InvalidChar(400, error),
+    /// The parameters are invalid or a placeholder cannot be evaluated. `details` contains the reason.
+    InvalidTemplate(400, error) => String,
+    /// `answer_type` is set to `NUMERIC`, but one of the `answers` is not a number.
+    InvalidNumber(400, error),
+    /// The question or one of the answers contains a forbidden word or personal data.
+    ContentViolation(400, error) => Vec<ContentViolation>,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
 });
 
 response!(UpdateQuestion = {
@@ -357,21 +588,37 @@ response!(UpdateQuestion = {
     SubtaskNotFound(404, error),
     /// Task does not exist.
     TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
     /// One of `ascii_letters`, `digits` or `punctuation` is set to `false`, but one of the `answers` contains such a character.
     InvalidChar(400, error),
+    /// The parameters are invalid or a placeholder cannot be evaluated. `details` contains the reason.
+    InvalidTemplate(400, error) => String,
+    /// `answer_type` is set to `NUMERIC`, but one of the `answers` is not a number.
+    InvalidNumber(400, error),
 });
 
 response!(SolveQuestion = {
     Ok(201) => SolveQuestionFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
     /// Try again later. `details` contains the number of seconds to wait.
     TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
     /// Subtask does not exist.
     SubtaskNotFound(404, error),
     /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
     NotEnoughHearts(403, error),
 });
 
-fn check_answers(answers: &[String], ascii_letters: bool, digits: bool, punctuation: bool) -> bool {
+pub(super) fn check_answers(
+    answers: &[String],
+    ascii_letters: bool,
+    digits: bool,
+    punctuation: bool,
+) -> bool {
     answers.iter().all(|answer| {
         answer.chars().all(|c| {
             (ascii_letters || !c.is_ascii_alphabetic())
@@ -381,7 +628,35 @@ fn check_answers(answers: &[String], ascii_letters: bool, digits: bool, punctuat
     })
 }
 
-fn normalize_answer(answer: &str, case_sensitive: bool) -> String {
+/// Check whether the answer of a user matches one of the answers of the
+/// question.
+pub(super) fn check_answer(question: &challenges_questions::Model, answer: &str) -> bool {
+    match question.answer_type {
+        ChallengesQuestionAnswerType::Text => {
+            let answer = normalize_answer(answer, question.case_sensitive);
+            question
+                .answers
+                .iter()
+                .any(|ans| normalize_answer(ans, question.case_sensitive) == answer)
+        }
+        ChallengesQuestionAnswerType::Numeric => NumericAnswer {
+            unit: question.unit.as_deref(),
+            absolute_tolerance: question.absolute_tolerance,
+            relative_tolerance: question.relative_tolerance,
+        }
+        .check(answer, &question.answers),
+    }
+}
+
+/// Check that all answers of a numeric question are numbers without a unit.
+fn check_numeric_answers(answer_type: ChallengesQuestionAnswerType, answers: &[String]) -> bool {
+    answer_type != ChallengesQuestionAnswerType::Numeric
+        || answers
+            .iter()
+            .all(|answer| matches!(parse_number(answer), Some((_, None))))
+}
+
+pub(super) fn normalize_answer(answer: &str, case_sensitive: bool) -> String {
     let answer = answer.trim();
     let mut out = String::with_capacity(answer.len());
     let mut whitespace = false;
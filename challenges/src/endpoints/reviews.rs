@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use entity::{
+    challenges_multiple_choice_quizes, challenges_questions, challenges_reviews,
+    challenges_subtasks,
+};
+use lib::{auth::VerifiedUserAuth, config::SharedConfig, SharedState};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::{
+    multiple_choice::check_answers,
+    reviews::{Review, ReviewAnswer, ReviewFeedback},
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set, Unchanged};
+use uuid::Uuid;
+
+use super::{question::check_answer, Tags};
+use crate::services::{
+    question_variants::get_variant,
+    reviews::{get_due_reviews, next_schedule, quality, send_review_rewards, Schedule},
+    shuffle::{attempt_seed, permutation, unshuffle},
+    subtasks::{get_subtask, get_user_subtask, UserSubtaskExt},
+};
+
+pub struct Reviews {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Reviews")]
+impl Reviews {
+    /// List all reviews of the authenticated user that are due.
+    ///
+    /// Solved multiple choice questions and questions are added to the review
+    /// queue automatically and rescheduled after each review.
+    #[oai(path = "/reviews/due", method = "get")]
+    async fn list_due_reviews(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListDueReviews::Response<VerifiedUserAuth> {
+        ListDueReviews::ok(
+            get_due_reviews(&db, auth.0.id, Utc::now().naive_utc())
+                .await?
+                .into_iter()
+                .map(|(review, subtask)| Review::from(review, subtask.task_id, subtask.ty))
+                .collect(),
+        )
+    }
+
+    /// Answer a due review.
+    ///
+    /// `type` must match the type of the subtask (`multiple_choice` for
+    /// multiple choice questions or `question` for questions).
+    #[oai(path = "/reviews/:review_id/answer", method = "post")]
+    async fn answer_review(
+        &self,
+        review_id: Path<Uuid>,
+        data: Json<ReviewAnswer>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> AnswerReview::Response<VerifiedUserAuth> {
+        let Some((review, Some(subtask))) = challenges_reviews::Entity::find_by_id(review_id.0)
+            .find_also_related(challenges_subtasks::Entity)
+            .filter(challenges_reviews::Column::UserId.eq(auth.0.id))
+            .one(&***db)
+            .await?
+        else {
+            return AnswerReview::review_not_found();
+        };
+        if !subtask.enabled {
+            return AnswerReview::review_not_found();
+        }
+
+        let now = Utc::now();
+        if review.due > now.naive_utc() {
+            return AnswerReview::not_due(review.due.and_utc());
+        }
+
+        let (correct, total) = match data.0 {
+            ReviewAnswer::MultipleChoice(answer) => {
+                let Some((mcq, _)) = get_subtask::<challenges_multiple_choice_quizes::Entity>(
+                    &db,
+                    subtask.task_id,
+                    subtask.id,
+                )
+                .await?
+                else {
+                    return AnswerReview::wrong_answer_type();
+                };
+                if answer.answers.len() != mcq.answers.len() {
+                    return AnswerReview::wrong_length();
+                }
+                let answers = match mcq.shuffle_answers {
+                    true => {
+                        let attempt = get_user_subtask(&db, auth.0.id, subtask.id)
+                            .await?
+                            .attempts();
+                        unshuffle(
+                            &answer.answers,
+                            &permutation(
+                                attempt_seed(auth.0.id, subtask.id, attempt),
+                                mcq.answers.len(),
+                            ),
+                        )
+                    }
+                    false => answer.answers,
+                };
+                (
+                    check_answers(&answers, mcq.correct_answers),
+                    mcq.answers.len(),
+                )
+            }
+            ReviewAnswer::Question(answer) => {
+                let Some((question, _)) =
+                    get_subtask::<challenges_questions::Entity>(&db, subtask.task_id, subtask.id)
+                        .await?
+                else {
+                    return AnswerReview::wrong_answer_type();
+                };
+                let attempt = get_user_subtask(&db, auth.0.id, subtask.id)
+                    .await?
+                    .attempts();
+                let question = get_variant(&db, auth.0.id, attempt, question).await??;
+                (check_answer(&question, &answer.answer) as usize, 1)
+            }
+        };
+        let schedule = next_schedule(Schedule::from(&review), quality(correct, total));
+        let correct = correct == total;
+        let next_due = now + Duration::days(schedule.interval as _);
+        challenges_reviews::ActiveModel {
+            id: Unchanged(review.id),
+            ease_factor: Set(schedule.ease_factor),
+            interval: Set(schedule.interval),
+            repetitions: Set(schedule.repetitions),
+            due: Set(next_due.naive_utc()),
+            last_review: Set(Some(now.naive_utc())),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+
+        let xp = if correct {
+            self.config.load().challenges.reviews.xp
+        } else {
+            0
+        };
+        send_review_rewards(
+            &self.state.services,
+            &db,
+            &self.config.load().challenges.rewards,
+            auth.0.id,
+            &subtask,
+            xp,
+        )
+        .await?;
+
+        AnswerReview::ok(ReviewFeedback {
+            correct,
+            next_due,
+            interval: schedule.interval,
+            xp,
+        })
+    }
+}
+
+response!(ListDueReviews = {
+    Ok(200) => Vec<Review>,
+});
+
+response!(AnswerReview = {
+    Ok(201) => ReviewFeedback,
+    /// Review does not exist.
+    ReviewNotFound(404, error),
+    /// The review is not due yet. `details` contains the time the review is due.
+    NotDue(403, error) => DateTime<Utc>,
+    /// The type of the answer does not match the type of the subtask.
+    WrongAnswerType(400, error),
+    /// The number of answers does not match the number of possible answers.
+    WrongLength(400, error),
+});
@@ -0,0 +1,595 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_sql_attempts, challenges_sql_challenges, challenges_user_subtasks,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+    events::{publish_event, Event},
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response, responses::internal_server_error};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::{
+    exams::ExamAnswer,
+    sql_challenges::{
+        CreateSqlChallengeRequest, SolveSqlChallengeFeedback, SolveSqlChallengeRequest,
+        SqlChallenge, SqlChallengeSummary, SqlChallengeWithSolution, UpdateSqlChallengeRequest,
+    },
+};
+use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    exams::{get_active_exam, record_exam_answer},
+    judge::Error as JudgeError,
+    notifications::notify_subtask_solved,
+    revisions::{
+        get_current_revision, query_subtask_as_of, record_revision, QuerySubtaskAsOfError,
+    },
+    sandbox::SharedSandbox,
+    similarity::{find_duplicates, fingerprint, set_fingerprint},
+    sql::{compare_results, run_query, QueryResult},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
+};
+
+pub struct SqlChallenges {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
+}
+
+#[OpenApi(tag = "Tags::SqlChallenges")]
+impl SqlChallenges {
+    /// List all sql challenges in a task.
+    #[oai(path = "/tasks/:task_id/sql_challenges", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_sql_challenges(
+        &self,
+        task_id: Path<Uuid>,
+        /// Whether to search for subtasks the user has attempted to solve.
+        attempted: Query<Option<bool>>,
+        /// Whether to search for solved subtasks.
+        solved: Query<Option<bool>>,
+        /// Whether to search for rated subtasks.
+        rated: Query<Option<bool>>,
+        /// Whether to search for enabled subtasks.
+        enabled: Query<Option<bool>>,
+        /// Whether to search for retired subtasks.
+        retired: Query<Option<bool>>,
+        /// Filter by creator.
+        creator: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListSqlChallenges::Response<VerifiedUserAuth> {
+        ListSqlChallenges::ok(
+            query_subtasks::<challenges_sql_challenges::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                QuerySubtasksFilter {
+                    attempted: attempted.0,
+                    solved: solved.0,
+                    rated: rated.0,
+                    enabled: enabled.0,
+                    retired: retired.0,
+                    creator: creator.0,
+                    ty: None,
+                    permissive: None,
+                },
+                SqlChallengeSummary::from,
+            )
+            .await?,
+        )
+    }
+
+    /// Get a sql challenge by id.
+    #[oai(path = "/tasks/:task_id/sql_challenges/:subtask_id", method = "get")]
+    async fn get_sql_challenge(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the challenge as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetSqlChallenge::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetSqlChallenge::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_sql_challenges::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                SqlChallenge::from,
+            )
+            .await?
+            {
+                Ok(x) => GetSqlChallenge::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetSqlChallenge::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetSqlChallenge::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask::<challenges_sql_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            SqlChallenge::from,
+            include_note.0.unwrap_or(false),
+        )
+        .await?
+        {
+            Some(sql_challenge) => GetSqlChallenge::ok(sql_challenge),
+            None => GetSqlChallenge::subtask_not_found(),
+        }
+    }
+
+    /// Get a sql challenge and its solution by id.
+    #[oai(
+        path = "/tasks/:task_id/sql_challenges/:subtask_id/solution",
+        method = "get"
+    )]
+    async fn get_sql_challenge_with_solution(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the challenge as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetSqlChallengeWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetSqlChallengeWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_sql_challenges::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                SqlChallengeWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetSqlChallengeWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetSqlChallengeWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetSqlChallengeWithSolution::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask_admin::<challenges_sql_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            SqlChallengeWithSolution::from,
+        )
+        .await?
+        {
+            Ok(sql_challenge) => GetSqlChallengeWithSolution::ok(sql_challenge),
+            Err(QuerySubtaskAdminError::NotFound) => {
+                GetSqlChallengeWithSolution::subtask_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => GetSqlChallengeWithSolution::forbidden(),
+        }
+    }
+
+    /// Create a new sql challenge.
+    ///
+    /// The reference query is executed once to determine the expected result.
+    #[oai(path = "/tasks/:task_id/sql_challenges", method = "post")]
+    async fn create_sql_challenge(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<CreateSqlChallengeRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateSqlChallenge::Response<VerifiedUserAuth> {
+        let subtask = match create_subtask(
+            &db,
+            &self.state.services,
+            &self.config.load(),
+            &auth.0,
+            task_id.0,
+            data.0.subtask,
+            ChallengesSubtaskType::SqlChallenge,
+        )
+        .await?
+        {
+            Ok(subtask) => subtask,
+            Err(CreateSubtaskError::TaskNotFound) => return CreateSqlChallenge::task_not_found(),
+            Err(CreateSubtaskError::Forbidden) => return CreateSqlChallenge::forbidden(),
+            Err(CreateSubtaskError::Banned(until)) => return CreateSqlChallenge::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateSqlChallenge::quota_exceeded(reset)
+            }
+            Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                return CreateSqlChallenge::xp_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                return CreateSqlChallenge::coin_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateSqlChallenge::attribution_required()
+            }
+        };
+
+        let expected = match self
+            .run(&data.0.schema, &data.0.seed, &data.0.solution)
+            .await?
+        {
+            QueryResult::Ok(result) => result,
+            QueryResult::SetupError(err) => return CreateSqlChallenge::invalid_schema(err),
+            QueryResult::Error(err) => return CreateSqlChallenge::invalid_query(err),
+        };
+
+        let fingerprint = fingerprint(&data.0.description, [data.0.solution.as_str()]);
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::SqlChallenge,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateSqlChallenge::duplicate(duplicates);
+            }
+        }
+
+        let sql_challenge = challenges_sql_challenges::ActiveModel {
+            subtask_id: Set(subtask.id),
+            description: Set(data.0.description),
+            schema: Set(data.0.schema),
+            seed: Set(data.0.seed),
+            solution: Set(data.0.solution),
+            ordered: Set(data.0.ordered),
+            expected: Set(expected),
+        }
+        .insert(&***db)
+        .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &sql_challenge).await?;
+        CreateSqlChallenge::ok(SqlChallengeWithSolution::from(sql_challenge, subtask))
+    }
+
+    /// Update a sql challenge.
+    ///
+    /// The reference query is executed again to make sure the expected result
+    /// is still up to date.
+    #[oai(path = "/tasks/:task_id/sql_challenges/:subtask_id", method = "patch")]
+    async fn update_sql_challenge(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<UpdateSqlChallengeRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> UpdateSqlChallenge::Response<AdminAuth> {
+        let (sql_challenge, subtask) = match update_subtask::<challenges_sql_challenges::Entity>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            data.0.subtask,
+        )
+        .await?
+        {
+            Ok(x) => x,
+            Err(UpdateSubtaskError::SubtaskNotFound) => {
+                return UpdateSqlChallenge::subtask_not_found()
+            }
+            Err(UpdateSubtaskError::TaskNotFound) => return UpdateSqlChallenge::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateSqlChallenge::attribution_required()
+            }
+        };
+
+        let expected = match self
+            .run(
+                data.0.schema.get_new(&sql_challenge.schema),
+                data.0.seed.get_new(&sql_challenge.seed),
+                data.0.solution.get_new(&sql_challenge.solution),
+            )
+            .await?
+        {
+            QueryResult::Ok(result) => result,
+            QueryResult::SetupError(err) => return UpdateSqlChallenge::invalid_schema(err),
+            QueryResult::Error(err) => return UpdateSqlChallenge::invalid_query(err),
+        };
+
+        let sql_challenge = challenges_sql_challenges::ActiveModel {
+            subtask_id: Unchanged(sql_challenge.subtask_id),
+            description: data.0.description.update(sql_challenge.description),
+            schema: data.0.schema.update(sql_challenge.schema),
+            seed: data.0.seed.update(sql_challenge.seed),
+            solution: data.0.solution.update(sql_challenge.solution),
+            ordered: data.0.ordered.update(sql_challenge.ordered),
+            expected: Set(expected),
+        }
+        .update(&***db)
+        .await?;
+        set_fingerprint(
+            &db,
+            sql_challenge.subtask_id,
+            fingerprint(
+                &sql_challenge.description,
+                [sql_challenge.solution.as_str()],
+            ),
+        )
+        .await?;
+        record_revision(&db, sql_challenge.subtask_id, &sql_challenge).await?;
+
+        UpdateSqlChallenge::ok(SqlChallengeWithSolution::from(sql_challenge, subtask))
+    }
+
+    /// Attempt to solve a sql challenge.
+    #[oai(
+        path = "/tasks/:task_id/sql_challenges/:subtask_id/attempts",
+        method = "post"
+    )]
+    async fn solve_sql_challenge(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SolveSqlChallengeRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SolveSqlChallenge::Response<VerifiedUserAuth> {
+        let Some((sql_challenge, subtask)) =
+            get_subtask::<challenges_sql_challenges::Entity>(&db, task_id.0, subtask_id.0).await?
+        else {
+            return SolveSqlChallenge::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SolveSqlChallenge::subtask_not_found();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.sql_challenges.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return SolveSqlChallenge::too_many_requests(time_left as u64);
+            }
+        }
+
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveSqlChallenge::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
+            return SolveSqlChallenge::not_enough_hearts();
+        }
+
+        let (error, diff) = match self
+            .run(&sql_challenge.schema, &sql_challenge.seed, &data.0.query)
+            .await?
+        {
+            QueryResult::Ok(result) => (
+                None,
+                compare_results(&sql_challenge.expected, &result, sql_challenge.ordered),
+            ),
+            QueryResult::Error(err) => (Some(err), None),
+            QueryResult::SetupError(err) => {
+                return Err(internal_server_error(format!(
+                    "failed to set up database of sql challenge {}: {err}",
+                    sql_challenge.subtask_id
+                )))
+            }
+        };
+        let solved = error.is_none() && diff.is_none();
+
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveSqlChallenge::accepted(exam_answer.into());
+        }
+
+        if let Some(solve_kind) = solve_kind {
+            let now = Utc::now().naive_utc();
+            if solved {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(now)),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+
+                if auth.0.id != subtask.creator {
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
+                }
+            } else {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+
+            challenges_sql_attempts::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                sql_challenge_id: Set(sql_challenge.subtask_id),
+                user_id: Set(auth.0.id),
+                timestamp: Set(now),
+                solved: Set(solved),
+                revision_id: Set(get_current_revision(&db, sql_challenge.subtask_id).await?),
+            }
+            .insert(&***db)
+            .await?;
+        }
+
+        SolveSqlChallenge::ok(SolveSqlChallengeFeedback {
+            solved,
+            error,
+            diff,
+        })
+    }
+}
+
+impl SqlChallenges {
+    /// Execute a query in a new database which is initialized with the schema
+    /// and seed data of a sql challenge.
+    async fn run(&self, schema: &str, seed: &str, query: &str) -> Result<QueryResult, JudgeError> {
+        run_query(
+            &*self.sandkasten,
+            schema,
+            seed,
+            query,
+            self.config.load().challenges.sql_challenges.max_rows,
+        )
+        .await
+    }
+}
+
+response!(ListSqlChallenges = {
+    Ok(200) => Vec<SqlChallengeSummary>,
+});
+
+response!(GetSqlChallenge = {
+    Ok(200) => SqlChallenge,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
+});
+
+response!(GetSqlChallengeWithSolution = {
+    Ok(200) => SqlChallengeWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to view the solution to this challenge.
+    Forbidden(403, error),
+});
+
+response!(CreateSqlChallenge = {
+    Ok(201) => SqlChallengeWithSolution,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create sql challenges in this task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The schema or the seed data could not be loaded. `details` contains the error message of the database.
+    InvalidSchema(400, error) => String,
+    /// The reference query could not be executed. `details` contains the error message of the database.
+    InvalidQuery(400, error) => String,
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
+});
+
+response!(UpdateSqlChallenge = {
+    Ok(200) => SqlChallengeWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// The schema or the seed data could not be loaded. `details` contains the error message of the database.
+    InvalidSchema(400, error) => String,
+    /// The reference query could not be executed. `details` contains the error message of the database.
+    InvalidQuery(400, error) => String,
+});
+
+response!(SolveSqlChallenge = {
+    Ok(201) => SolveSqlChallengeFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+});
@@ -0,0 +1,71 @@
+use entity::challenges_wrong_answers;
+use lib::auth::VerifiedUserAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, OpenApi};
+use schemas::challenges::subtasks::{WrongAnswerCount, WrongAnswerStats};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{endpoints::Tags, services::organizations::can_see_task};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Return how often users have made each mistake in a subtask.
+    ///
+    /// Mistakes are only recorded for failed attempts to solve multiple choice
+    /// questions and matchings outside of exams. Only the creator of the
+    /// subtask and admins can see these statistics.
+    #[oai(
+        path = "/tasks/:task_id/subtasks/:subtask_id/analytics",
+        method = "get"
+    )]
+    pub async fn get_wrong_answer_stats(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetWrongAnswerStats::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0)
+            .await?
+            .filter(|(_, task)| can_see_task(&auth.0, task))
+        else {
+            return GetWrongAnswerStats::subtask_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == subtask.creator) {
+            return GetWrongAnswerStats::forbidden();
+        }
+
+        let mistakes = challenges_wrong_answers::Entity::find()
+            .filter(challenges_wrong_answers::Column::SubtaskId.eq(subtask.id))
+            .order_by_desc(challenges_wrong_answers::Column::Count)
+            .order_by_asc(challenges_wrong_answers::Column::Item)
+            .order_by_asc(challenges_wrong_answers::Column::Choice)
+            .all(&***db)
+            .await?
+            .into_iter()
+            .map(|x| WrongAnswerCount {
+                item: x.item as _,
+                choice: x.choice as _,
+                count: x.count as _,
+            })
+            .collect();
+
+        GetWrongAnswerStats::ok(WrongAnswerStats {
+            subtask_type: subtask.ty,
+            mistakes,
+        })
+    }
+}
+
+response!(GetWrongAnswerStats = {
+    Ok(200) => WrongAnswerStats,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not allowed to view the statistics of this subtask.
+    Forbidden(403, error),
+});
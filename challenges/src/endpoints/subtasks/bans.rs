@@ -15,7 +15,7 @@ use sea_orm::{
 };
 use uuid::Uuid;
 
-use crate::endpoints::Tags;
+use crate::{endpoints::Tags, services::subtasks::publish_ban_event};
 
 pub struct Api;
 
@@ -86,20 +86,20 @@ impl Api {
             return CreateBan::negative_duration();
         }
 
-        CreateBan::created(
-            challenges_ban::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                user_id: Set(data.0.user_id),
-                creator: Set(auth.0.id),
-                start: Set(start.naive_utc()),
-                end: Set(data.0.end.map(|ts| ts.naive_utc())),
-                action: Set(data.0.action),
-                reason: Set(data.0.reason),
-            }
-            .insert(&***db)
-            .await?
-            .into(),
-        )
+        let ban = challenges_ban::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(data.0.user_id),
+            creator: Set(auth.0.id),
+            start: Set(start.naive_utc()),
+            end: Set(data.0.end.map(|ts| ts.naive_utc())),
+            action: Set(data.0.action),
+            reason: Set(data.0.reason),
+        }
+        .insert(&***db)
+        .await?;
+        publish_ban_event(&db, &ban).await?;
+
+        CreateBan::created(ban.into())
     }
 
     /// Update a ban.
@@ -0,0 +1,130 @@
+use std::{collections::HashSet, sync::Arc};
+
+use lib::{
+    auth::VerifiedUserAuth,
+    config::SharedConfig,
+    errors::{add_error_code, Language},
+    SharedState,
+};
+use poem::{web::Data, IntoResponse};
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::subtasks::{
+    BatchAnswer, BatchAttemptResult, BatchSolveRequest, BatchSolveResponse,
+};
+use sea_orm::TransactionTrait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::endpoints::{
+    matchings::Matchings, multiple_choice::MultipleChoice, question::Questions, Tags,
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Submit answers for multiple subtasks of a task at once.
+    ///
+    /// Supports multiple choice questions, matchings and questions. Every
+    /// attempt is validated and rewarded exactly like an attempt submitted via
+    /// the solve endpoint of the respective subtask type (including cooldowns
+    /// and attempt limits). For each attempt the response contains the status
+    /// code and body this endpoint would have returned.
+    #[oai(path = "/tasks/:task_id/subtasks/attempts/batch", method = "post")]
+    pub async fn solve_batch(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<BatchSolveRequest>,
+        db: Data<&DbTxn>,
+        language: Language,
+        auth: VerifiedUserAuth,
+    ) -> SolveBatch::Response<VerifiedUserAuth> {
+        let mut seen = HashSet::new();
+        if let Some(attempt) = data.0.attempts.iter().find(|x| !seen.insert(x.subtask_id)) {
+            return SolveBatch::duplicate_subtask(attempt.subtask_id);
+        }
+
+        let mut results = Vec::with_capacity(data.0.attempts.len());
+        for attempt in data.0.attempts {
+            let subtask_id = Path(attempt.subtask_id);
+            let auth = VerifiedUserAuth(auth.0.clone());
+            // each attempt runs in its own savepoint, so that the changes of a
+            // failed attempt are rolled back like those of a failed request
+            let savepoint = Arc::new(db.begin().await?);
+            let response = match attempt.answer {
+                BatchAnswer::MultipleChoice(answer) => MultipleChoice {
+                    state: Arc::clone(&self.state),
+                    config: self.config.clone(),
+                }
+                .solve_question(
+                    Path(task_id.0),
+                    subtask_id,
+                    Json(answer),
+                    Data(&savepoint),
+                    auth,
+                )
+                .await?
+                .into_response(),
+                BatchAnswer::Matching(answer) => Matchings {
+                    state: Arc::clone(&self.state),
+                    config: self.config.clone(),
+                }
+                .solve_matching(
+                    Path(task_id.0),
+                    subtask_id,
+                    Json(answer),
+                    Data(&savepoint),
+                    auth,
+                )
+                .await?
+                .into_response(),
+                BatchAnswer::Question(answer) => Questions {
+                    state: Arc::clone(&self.state),
+                    config: self.config.clone(),
+                }
+                .solve_question(
+                    Path(task_id.0),
+                    subtask_id,
+                    Json(answer),
+                    Data(&savepoint),
+                    auth,
+                )
+                .await?
+                .into_response(),
+            };
+
+            let status = response.status();
+            let savepoint =
+                Arc::into_inner(savepoint).expect("savepoint is still used by the attempt");
+            match status.is_success() {
+                true => savepoint.commit().await?,
+                false => savepoint.rollback().await?,
+            }
+            let body = response.into_body().into_bytes().await?;
+            let mut body = match body.is_empty() {
+                true => Value::Null,
+                false => serde_json::from_slice(&body)?,
+            };
+            if !status.is_success() {
+                add_error_code(&mut body, language);
+            }
+            results.push(BatchAttemptResult {
+                subtask_id: attempt.subtask_id,
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        SolveBatch::ok(BatchSolveResponse { results })
+    }
+}
+
+response!(SolveBatch = {
+    Ok(200) => BatchSolveResponse,
+    /// The request contains multiple attempts for the same subtask.
+    DuplicateSubtask(400, error) => Uuid,
+});
@@ -0,0 +1,141 @@
+use chrono::Utc;
+use entity::{challenges_subtasks, challenges_user_bookmarks};
+use lib::auth::VerifiedUserAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, OpenApi};
+use schemas::challenges::subtasks::{Bookmark, BookmarkedSubtask, Subtask};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{
+    endpoints::Tags,
+    services::subtasks::{can_access_subtask, get_user_subtasks, UserSubtaskExt},
+};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Return all subtasks the authenticated user has bookmarked, most recently
+    /// bookmarked first.
+    ///
+    /// Subtasks the user can no longer access are omitted.
+    #[oai(path = "/subtasks/bookmarks", method = "get")]
+    pub async fn list_bookmarks(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListBookmarks::Response<VerifiedUserAuth> {
+        let bookmarks = challenges_user_bookmarks::Entity::find()
+            .find_also_related(challenges_subtasks::Entity)
+            .filter(challenges_user_bookmarks::Column::UserId.eq(auth.0.id))
+            .order_by_desc(challenges_user_bookmarks::Column::Timestamp)
+            .all(&***db)
+            .await?;
+        let user_subtasks = get_user_subtasks(&db, auth.0.id).await?;
+
+        let mut out = Vec::with_capacity(bookmarks.len());
+        for (bookmark, subtask) in bookmarks {
+            let Some(subtask) = subtask else {
+                continue;
+            };
+            if !can_access_subtask(&db, &auth.0, &subtask).await? {
+                continue;
+            }
+            let user_subtask = user_subtasks.get(&subtask.id);
+            out.push(BookmarkedSubtask {
+                subtask: Subtask::from(subtask, user_subtask.is_solved(), user_subtask.is_rated()),
+                bookmark_timestamp: bookmark.timestamp.and_utc(),
+            });
+        }
+        ListBookmarks::ok(out)
+    }
+
+    /// Bookmark a subtask.
+    ///
+    /// Bookmarking a subtask again does not change the bookmark.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/bookmark", method = "put")]
+    pub async fn create_bookmark(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateBookmark::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return CreateBookmark::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return CreateBookmark::subtask_not_found();
+        }
+
+        let bookmark = match challenges_user_bookmarks::Entity::find_by_id((auth.0.id, subtask.id))
+            .one(&***db)
+            .await?
+        {
+            Some(bookmark) => bookmark,
+            None => {
+                challenges_user_bookmarks::ActiveModel {
+                    user_id: Set(auth.0.id),
+                    subtask_id: Set(subtask.id),
+                    timestamp: Set(Utc::now().naive_utc()),
+                }
+                .insert(&***db)
+                .await?
+            }
+        };
+
+        CreateBookmark::ok(Bookmark {
+            task_id: subtask.task_id,
+            subtask_id: subtask.id,
+            timestamp: bookmark.timestamp.and_utc(),
+        })
+    }
+
+    /// Remove a bookmark.
+    #[oai(
+        path = "/tasks/:task_id/subtasks/:subtask_id/bookmark",
+        method = "delete"
+    )]
+    pub async fn delete_bookmark(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DeleteBookmark::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return DeleteBookmark::subtask_not_found();
+        };
+        let Some(bookmark) = challenges_user_bookmarks::Entity::find_by_id((auth.0.id, subtask.id))
+            .one(&***db)
+            .await?
+        else {
+            return DeleteBookmark::bookmark_not_found();
+        };
+        bookmark.delete(&***db).await?;
+        DeleteBookmark::ok()
+    }
+}
+
+response!(ListBookmarks = {
+    Ok(200) => Vec<BookmarkedSubtask>,
+});
+
+response!(CreateBookmark = {
+    Ok(200) => Bookmark,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(DeleteBookmark = {
+    Ok(200),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The subtask has not been bookmarked.
+    BookmarkNotFound(404, error),
+});
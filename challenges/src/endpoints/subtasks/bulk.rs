@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+
+use entity::{
+    challenges_subtask_prerequisites, challenges_subtasks,
+    sea_orm_active_enums::ChallengesReviewStatus,
+};
+use lib::auth::AdminAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::subtasks::{BulkUpdateSubtasksRequest, BulkUpdateSubtasksSummary};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::{
+        notifications::notify_subtask_disabled, organizations::can_see_task, tasks::get_task,
+    },
+};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Update multiple subtasks of a task at once.
+    ///
+    /// All operations are applied to all selected subtasks in a single
+    /// transaction. Prerequisites between moved and remaining subtasks are
+    /// removed.
+    #[oai(path = "/tasks/:task_id/subtasks/bulk", method = "post")]
+    pub async fn bulk_update_subtasks(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<BulkUpdateSubtasksRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> BulkUpdateSubtasks::Response<AdminAuth> {
+        if !get_task(&db, task_id.0)
+            .await?
+            .is_some_and(|task| can_see_task(&auth.0, &task))
+        {
+            return BulkUpdateSubtasks::task_not_found();
+        }
+        if let Some(target) = data.0.move_to {
+            if !get_task(&db, target)
+                .await?
+                .is_some_and(|task| can_see_task(&auth.0, &task))
+            {
+                return BulkUpdateSubtasks::target_task_not_found();
+            }
+        }
+
+        let mut query = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::TaskId.eq(task_id.0))
+            .order_by_asc(challenges_subtasks::Column::CreationTimestamp);
+        if let Some(ids) = &data.0.subtask_ids {
+            query = query.filter(challenges_subtasks::Column::Id.is_in(ids.iter().copied()));
+        }
+        let subtasks = query.all(&***db).await?;
+        if let Some(ids) = &data.0.subtask_ids {
+            let found = subtasks.iter().map(|x| x.id).collect::<HashSet<_>>();
+            let missing = ids
+                .iter()
+                .filter(|id| !found.contains(id))
+                .copied()
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return BulkUpdateSubtasks::subtask_not_found(missing);
+            }
+        }
+
+        let mut summary = BulkUpdateSubtasksSummary {
+            matched: subtasks.len() as _,
+            enabled_changed: 0,
+            moved: 0,
+            rewards_changed: 0,
+        };
+        let mut moved = Vec::new();
+        for subtask in subtasks {
+            let mut model = challenges_subtasks::ActiveModel {
+                id: Unchanged(subtask.id),
+                ..Default::default()
+            };
+            if let Some(enabled) = data.0.enabled.filter(|&x| x != subtask.enabled) {
+                model.enabled = Set(enabled);
+                if enabled {
+                    model.review_status = Set(ChallengesReviewStatus::Approved);
+                    model.rejection_reason = Set(None);
+                }
+                summary.enabled_changed += 1;
+                if !enabled {
+                    notify_subtask_disabled(&***db, &subtask, Some(auth.0.id)).await?;
+                }
+            }
+            if let Some(target) = data.0.move_to.filter(|&x| x != subtask.task_id) {
+                model.task_id = Set(target);
+                moved.push(subtask.id);
+                summary.moved += 1;
+            }
+            let xp = data
+                .0
+                .xp_percent
+                .map_or(subtask.xp, |p| adjust_by_percent(subtask.xp, p));
+            let coins = data
+                .0
+                .coins_percent
+                .map_or(subtask.coins, |p| adjust_by_percent(subtask.coins, p));
+            if xp != subtask.xp || coins != subtask.coins {
+                model.xp = Set(xp);
+                model.coins = Set(coins);
+                summary.rewards_changed += 1;
+            }
+            if model.is_changed() {
+                model.update(&***db).await?;
+            }
+        }
+
+        if !moved.is_empty() {
+            challenges_subtask_prerequisites::Entity::delete_many()
+                .filter(
+                    Condition::any()
+                        .add(
+                            Condition::all()
+                                .add(
+                                    challenges_subtask_prerequisites::Column::SubtaskId
+                                        .is_in(moved.iter().copied()),
+                                )
+                                .add(
+                                    challenges_subtask_prerequisites::Column::PrerequisiteId
+                                        .is_not_in(moved.iter().copied()),
+                                ),
+                        )
+                        .add(
+                            Condition::all()
+                                .add(
+                                    challenges_subtask_prerequisites::Column::SubtaskId
+                                        .is_not_in(moved.iter().copied()),
+                                )
+                                .add(
+                                    challenges_subtask_prerequisites::Column::PrerequisiteId
+                                        .is_in(moved.iter().copied()),
+                                ),
+                        ),
+                )
+                .exec(&***db)
+                .await?;
+        }
+
+        BulkUpdateSubtasks::ok(summary)
+    }
+}
+
+response!(BulkUpdateSubtasks = {
+    Ok(200) => BulkUpdateSubtasksSummary,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The task to move the subtasks to does not exist.
+    TargetTaskNotFound(404, error),
+    /// Some of the subtasks do not exist in this task. `details` contains the ids of the missing subtasks.
+    SubtaskNotFound(404, error) => Vec<Uuid>,
+});
+
+/// Adjust a reward by the given percentage, rounding to the nearest integer.
+fn adjust_by_percent(value: i64, percent: i32) -> i64 {
+    let adjusted = value as f64 * (100.0 + percent as f64) / 100.0;
+    adjusted.round().max(0.0) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust_by_percent() {
+        assert_eq!(adjust_by_percent(100, 10), 110);
+        assert_eq!(adjust_by_percent(100, -50), 50);
+        assert_eq!(adjust_by_percent(5, -100), 0);
+        assert_eq!(adjust_by_percent(3, 50), 5);
+        assert_eq!(adjust_by_percent(7, 0), 7);
+    }
+}
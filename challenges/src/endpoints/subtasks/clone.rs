@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_clozes, challenges_code_readings, challenges_coding_challenges,
+    challenges_matchings, challenges_multiple_choice_quizes, challenges_orderings,
+    challenges_questions, challenges_sql_challenges, challenges_subtasks,
+    challenges_true_false_statements, sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{auth::VerifiedUserAuth, config::SharedConfig, SharedState};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use schemas::challenges::subtasks::{CreateSubtaskRequest, Subtask};
+use sea_orm::{
+    ActiveModelTrait, DatabaseTransaction, DbErr, EntityTrait, IntoActiveModel, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{
+    endpoints::Tags,
+    services::{
+        evaluators::record_evaluator_version,
+        licenses::attribution_of_copy,
+        organizations::can_see_task,
+        revisions::record_revision,
+        subtasks::{create_subtask, CreateSubtaskError},
+    },
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Create a copy of a subtask in another task.
+    ///
+    /// The copy includes the complete content of the subtask (e.g. the
+    /// evaluator and the solution of coding challenges). It is owned by the
+    /// authenticated user and disabled until it is enabled explicitly. The
+    /// copy keeps the license of the subtask and credits the original
+    /// authors in its attribution.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/clone", method = "post")]
+    pub async fn clone_subtask(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// The task to copy the subtask to.
+        target_task_id: Query<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CloneSubtask::Response<VerifiedUserAuth> {
+        let Some((source, _)) = get_subtask(&db, task_id.0, subtask_id.0)
+            .await?
+            .filter(|(_, task)| can_see_task(&auth.0, task))
+        else {
+            return CloneSubtask::subtask_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == source.creator) {
+            return CloneSubtask::forbidden();
+        }
+
+        let mut subtask = match create_subtask(
+            &db,
+            &self.state.services,
+            &self.config.load(),
+            &auth.0,
+            target_task_id.0,
+            CreateSubtaskRequest {
+                xp: Some(source.xp as _),
+                coins: Some(source.coins as _),
+                license: Some(source.license),
+                attribution: Some(attribution_of_copy(&source)),
+                max_attempts: source.max_attempts.map(|x| x as _),
+                reveal_solution: source.reveal_solution,
+                time_limit: source.time_limit.map(|x| x as _),
+                unlock_price: source.unlock_price.map(|x| x as _),
+                locked: source.locked,
+            },
+            source.ty,
+        )
+        .await?
+        {
+            Ok(subtask) => subtask,
+            Err(CreateSubtaskError::TaskNotFound) => return CloneSubtask::task_not_found(),
+            Err(CreateSubtaskError::Forbidden) => return CloneSubtask::forbidden(),
+            Err(CreateSubtaskError::Banned(until)) => return CloneSubtask::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CloneSubtask::quota_exceeded(reset)
+            }
+            Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                return CloneSubtask::xp_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                return CloneSubtask::coin_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CloneSubtask::attribution_required()
+            }
+        };
+
+        challenges_subtasks::ActiveModel {
+            id: Unchanged(subtask.id),
+            enabled: Set(false),
+            fingerprint: Set(source.fingerprint.clone()),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+        subtask.enabled = false;
+
+        copy_content(&db, &source, subtask.id, subtask.creator).await?;
+
+        CloneSubtask::ok(subtask)
+    }
+}
+
+response!(CloneSubtask = {
+    Ok(201) => Subtask,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Target task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to copy this subtask or to create subtasks in the target task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+});
+
+/// Copy the type specific content of a subtask to another subtask.
+async fn copy_content(
+    db: &DatabaseTransaction,
+    source: &challenges_subtasks::Model,
+    subtask_id: Uuid,
+    creator: Uuid,
+) -> Result<(), DbErr> {
+    match source.ty {
+        ChallengesSubtaskType::CodingChallenge => {
+            if let Some(x) = challenges_coding_challenges::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                x.evaluator_version = Set(1);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+                record_evaluator_version(db, subtask_id, 1, &x.evaluator, creator).await?;
+            }
+        }
+        ChallengesSubtaskType::Matching => {
+            if let Some(x) = challenges_matchings::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::MultipleChoiceQuestion => {
+            if let Some(x) = challenges_multiple_choice_quizes::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::Question => {
+            if let Some(x) = challenges_questions::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::TrueFalse => {
+            if let Some(x) = challenges_true_false_statements::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::Ordering => {
+            if let Some(x) = challenges_orderings::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::Cloze => {
+            if let Some(x) = challenges_clozes::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::CodeReading => {
+            if let Some(x) = challenges_code_readings::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+        ChallengesSubtaskType::SqlChallenge => {
+            if let Some(x) = challenges_sql_challenges::Entity::find_by_id(source.id)
+                .one(db)
+                .await?
+            {
+                let mut x = x.into_active_model().reset_all();
+                x.subtask_id = Set(subtask_id);
+                let x = x.insert(db).await?;
+                record_revision(db, subtask_id, &x).await?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,33 +1,149 @@
-use std::sync::Arc;
+use entity::challenges_task_config;
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::subtasks::{SubtasksUserConfig, TaskConfig};
+use sea_orm::{sea_query::OnConflict, EntityTrait, ModelTrait, Set};
+use uuid::Uuid;
 
-use lib::{auth::VerifiedUserAuth, config::Config};
-use poem_ext::response;
-use poem_openapi::OpenApi;
-use schemas::challenges::subtasks::SubtasksUserConfig;
-
-use crate::endpoints::Tags;
+use crate::{
+    endpoints::Tags,
+    services::{
+        task_config::{get_task_config, TaskLimits},
+        tasks::get_task,
+    },
+};
 
 pub struct Api {
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::Subtasks")]
 impl Api {
     /// Return the configuration values that are relevant for normal users
     /// creating subtasks.
+    ///
+    /// If `task_id` is set, task specific overrides are taken into account.
     #[oai(path = "/subtasks/user_config", method = "get")]
     pub async fn get_user_config(
         &self,
+        task_id: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
         _auth: VerifiedUserAuth,
     ) -> GetUserConfig::Response<VerifiedUserAuth> {
+        let overrides = match task_id.0 {
+            Some(task_id) => get_task_config(&db, task_id).await?,
+            None => None,
+        };
+        let config = &self.config.load().challenges.quizzes;
+        let limits = TaskLimits::resolve(config, overrides.as_ref());
         GetUserConfig::ok(SubtasksUserConfig {
-            min_level: self.config.challenges.quizzes.min_level,
-            max_xp: self.config.challenges.quizzes.max_xp,
-            max_coins: self.config.challenges.quizzes.max_coins,
+            min_level: config.min_level,
+            max_xp: limits.max_xp,
+            max_coins: limits.max_coins,
         })
     }
+
+    /// Return the configuration overrides of a task.
+    #[oai(path = "/tasks/:task_id/config", method = "get")]
+    pub async fn get_task_config(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetTaskConfig::Response<AdminAuth> {
+        if get_task(&db, task_id.0).await?.is_none() {
+            return GetTaskConfig::task_not_found();
+        }
+        GetTaskConfig::ok(match get_task_config(&db, task_id.0).await? {
+            Some(config) => config.into(),
+            None => TaskConfig {
+                max_xp: None,
+                max_coins: None,
+                timeout_incr: None,
+                pool_size: None,
+                completion_xp: None,
+                completion_coins: None,
+            },
+        })
+    }
+
+    /// Replace the configuration overrides of a task.
+    ///
+    /// Values that are omitted fall back to the global configuration.
+    #[oai(path = "/tasks/:task_id/config", method = "put")]
+    pub async fn set_task_config(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<TaskConfig>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> SetTaskConfig::Response<AdminAuth> {
+        if get_task(&db, task_id.0).await?.is_none() {
+            return SetTaskConfig::task_not_found();
+        }
+
+        let config = data.0;
+        if config.max_xp.is_none()
+            && config.max_coins.is_none()
+            && config.timeout_incr.is_none()
+            && config.pool_size.is_none()
+            && config.completion_xp.is_none()
+            && config.completion_coins.is_none()
+        {
+            if let Some(config) = get_task_config(&db, task_id.0).await? {
+                config.delete(&***db).await?;
+            }
+        } else {
+            challenges_task_config::Entity::insert(challenges_task_config::ActiveModel {
+                task_id: Set(task_id.0),
+                max_xp: Set(config.max_xp.map(|x| x as _)),
+                max_coins: Set(config.max_coins.map(|x| x as _)),
+                timeout_incr: Set(config.timeout_incr.map(|x| x as _)),
+                pool_size: Set(config.pool_size.map(|x| x as _)),
+                completion_xp: Set(config.completion_xp.map(|x| x as _)),
+                completion_coins: Set(config.completion_coins.map(|x| x as _)),
+            })
+            .on_conflict(
+                OnConflict::column(challenges_task_config::Column::TaskId)
+                    .update_columns([
+                        challenges_task_config::Column::MaxXp,
+                        challenges_task_config::Column::MaxCoins,
+                        challenges_task_config::Column::TimeoutIncr,
+                        challenges_task_config::Column::PoolSize,
+                        challenges_task_config::Column::CompletionXp,
+                        challenges_task_config::Column::CompletionCoins,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&***db)
+            .await?;
+        }
+
+        SetTaskConfig::ok(config)
+    }
 }
 
 response!(GetUserConfig = {
     Ok(200) => SubtasksUserConfig,
 });
+
+response!(GetTaskConfig = {
+    Ok(200) => TaskConfig,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+});
+
+response!(SetTaskConfig = {
+    Ok(200) => TaskConfig,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+});
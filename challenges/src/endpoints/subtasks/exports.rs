@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use entity::{challenges_subtasks, challenges_tasks};
+use fnct::format::JsonFormatter;
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    Cache, SharedState,
+};
+use poem::{web::Data, Body};
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Attachment,
+    ApiResponse, OpenApi,
+};
+use schemas::challenges::subtasks::ExportFormat;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::{
+        exports::{export_csv, export_xlsx},
+        markdown::export_markdown,
+        organizations::can_see_task,
+        sandbox::SharedSandbox,
+        tasks::{get_task_with_specific, Task},
+    },
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub sandkasten: SharedSandbox,
+    pub judge_cache: Cache<JsonFormatter>,
+}
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Export all attempts and submissions of all subtasks of a task.
+    ///
+    /// csv exports are streamed while the attempts are loaded from the
+    /// database.
+    #[oai(path = "/tasks/:task_id/attempts/export", method = "get")]
+    pub async fn export_attempts(
+        &self,
+        task_id: Path<Uuid>,
+        /// The file format of the export (defaults to `csv`).
+        format: Query<Option<ExportFormat>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ExportAttempts::Response<AdminAuth> {
+        if challenges_tasks::Entity::find_by_id(task_id.0)
+            .one(&***db)
+            .await?
+            .is_none()
+        {
+            return ExportAttempts::task_not_found();
+        }
+        let subtasks = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::TaskId.eq(task_id.0))
+            .order_by_asc(challenges_subtasks::Column::CreationTimestamp)
+            .all(&***db)
+            .await?;
+
+        let db = self.state.db.clone();
+        let file = match format.0.unwrap_or(ExportFormat::Csv) {
+            ExportFormat::Csv => Attachment::new(export_csv(db, subtasks))
+                .filename(format!("attempts_{}.csv", task_id.0)),
+            ExportFormat::Xlsx => Attachment::new(Body::from(export_xlsx(db, subtasks).await?))
+                .filename(format!("attempts_{}.xlsx", task_id.0)),
+        };
+        Ok(ExportFile::Ok(file).into())
+    }
+
+    /// Export all enabled subtasks of a task that are not locked as a printable
+    /// Markdown document.
+    ///
+    /// The document contains questions, multiple choice answers (without
+    /// marking the correct ones), matchings and coding challenge statements
+    /// with their examples, but no solutions. It is streamed while the
+    /// subtasks are loaded from the database.
+    #[oai(path = "/tasks/:task_id/export.md", method = "get")]
+    pub async fn export_markdown(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ExportMarkdown::Response<VerifiedUserAuth> {
+        let Some((task, specific)) = get_task_with_specific(&db, task_id.0)
+            .await?
+            .filter(|(task, _)| can_see_task(&auth.0, task))
+        else {
+            return ExportMarkdown::task_not_found();
+        };
+        let (title, description) = match specific {
+            Task::Challenge(challenge) => (challenge.title, Some(challenge.description)),
+            Task::CourseTask(_) => (format!("Task {}", task.id), None),
+        };
+        let subtasks = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::TaskId.eq(task.id))
+            .filter(challenges_subtasks::Column::Enabled.eq(true))
+            .filter(challenges_subtasks::Column::Locked.eq(false))
+            .order_by_asc(challenges_subtasks::Column::CreationTimestamp)
+            .all(&***db)
+            .await?;
+
+        let body = export_markdown(
+            self.state.db.clone(),
+            self.sandkasten.clone(),
+            self.judge_cache.clone(),
+            title,
+            description,
+            subtasks,
+        );
+        let file = Attachment::new(body).filename(format!("task_{}.md", task.id));
+        Ok(ExportMarkdownFile::Ok(file).into())
+    }
+}
+
+#[derive(Debug, ApiResponse)]
+pub enum ExportFile {
+    /// The exported attempts.
+    #[oai(status = 200)]
+    Ok(Attachment<Body>),
+}
+
+response!(ExportAttempts = {
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    ..ExportFile,
+});
+
+#[derive(Debug, ApiResponse)]
+pub enum ExportMarkdownFile {
+    /// The exported subtasks.
+    #[oai(status = 200)]
+    Ok(Attachment<Body>),
+}
+
+response!(ExportMarkdown = {
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    ..ExportMarkdownFile,
+});
@@ -5,7 +5,7 @@ use entity::{
     challenges_user_subtasks,
     sea_orm_active_enums::{ChallengesRating, ChallengesReportReason, ChallengesSubtaskType},
 };
-use lib::{auth::VerifiedUserAuth, config::Config, SharedState};
+use lib::{auth::VerifiedUserAuth, config::SharedConfig, SharedState};
 use poem::web::Data;
 use poem_ext::{db::DbTxn, response};
 use poem_openapi::{param::Path, payload::Json, OpenApi};
@@ -16,12 +16,15 @@ use uuid::Uuid;
 use super::{get_subtask, reports::create_report};
 use crate::{
     endpoints::Tags,
-    services::subtasks::{get_user_subtask, update_user_subtask, UserSubtaskExt},
+    services::{
+        rewards::add_coins,
+        subtasks::{can_access_subtask, get_user_subtask, update_user_subtask, UserSubtaskExt},
+    },
 };
 
 pub struct Api {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::Subtasks")]
@@ -42,7 +45,7 @@ impl Api {
         let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
             return PostFeedback::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return PostFeedback::subtask_not_found();
         }
 
@@ -65,7 +68,7 @@ impl Api {
         .await?;
 
         if data.0.rating == ChallengesRating::Positive {
-            let config = &self.config.challenges;
+            let config = &self.config.load().challenges;
             let coins = match subtask.ty {
                 ChallengesSubtaskType::CodingChallenge => config.coding_challenges.creator_coins,
                 ChallengesSubtaskType::Matching => config.matchings.creator_coins,
@@ -73,12 +76,21 @@ impl Api {
                     config.multiple_choice_questions.creator_coins
                 }
                 ChallengesSubtaskType::Question => config.questions.creator_coins,
+                ChallengesSubtaskType::TrueFalse => config.true_false.creator_coins,
+                ChallengesSubtaskType::Ordering => config.orderings.creator_coins,
+                ChallengesSubtaskType::Cloze => config.clozes.creator_coins,
+                ChallengesSubtaskType::CodeReading => config.code_readings.creator_coins,
+                ChallengesSubtaskType::SqlChallenge => config.sql_challenges.creator_coins,
             };
-            self.state
-                .services
-                .shop
-                .add_coins(subtask.creator, coins as _, "Quiz/Challenge", true)
-                .await??;
+            add_coins(
+                &self.state.services,
+                &db,
+                &config.rewards,
+                subtask.creator,
+                coins as _,
+                "Quiz/Challenge",
+            )
+            .await?;
         }
 
         if data.0.rating == ChallengesRating::Negative {
@@ -1,47 +1,92 @@
 use std::sync::Arc;
 
 use entity::{challenges_subtasks, challenges_tasks, sea_orm_active_enums::ChallengesSubtaskType};
-use lib::{auth::VerifiedUserAuth, config::Config, SharedState};
+use fnct::format::JsonFormatter;
+use lib::{auth::VerifiedUserAuth, config::SharedConfig, SharedState};
 use poem::web::Data;
 use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use schemas::challenges::subtasks::{Subtask, SubtaskStats};
+use schemas::challenges::subtasks::{NextSubtask, Subtask, SubtaskStats};
 use sea_orm::{ColumnTrait, DatabaseTransaction, EntityTrait, ModelTrait, QueryFilter};
 use uuid::Uuid;
 
 use super::Tags;
-use crate::services::subtasks::{
-    get_user_subtasks, query_subtasks_only, stat_subtasks, stat_subtasks_prepare,
-    QuerySubtasksFilter,
+use crate::services::{
+    recommendation::{recommend_subtask, Recommendation},
+    sandbox::SharedSandbox,
+    subtasks::{
+        get_user_subtasks, query_subtasks_only, stat_subtasks, stat_subtasks_prepare,
+        QuerySubtasksFilter,
+    },
+    tasks::get_task,
+    time_tracking::get_user_sessions,
 };
 
+mod analytics;
 mod bans;
+mod batch;
+mod bookmarks;
+mod bulk;
+mod clone;
 mod config;
+mod exports;
 mod feedback;
+mod moderation;
+mod notes;
+mod prerequisites;
 mod reports;
+mod reveal;
+mod sessions;
 
 #[derive(Clone)]
 pub struct Subtasks {
     pub state: Arc<SharedState>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
 }
 
 impl Subtasks {
     pub fn get_api(self) -> impl OpenApi {
         (
+            analytics::Api,
             bans::Api,
+            batch::Api {
+                state: Arc::clone(&self.state),
+                config: self.config.clone(),
+            },
+            bookmarks::Api,
+            bulk::Api,
+            clone::Api {
+                state: Arc::clone(&self.state),
+                config: self.config.clone(),
+            },
             config::Api {
-                config: Arc::clone(&self.config),
+                config: self.config.clone(),
+            },
+            exports::Api {
+                state: Arc::clone(&self.state),
+                sandkasten: self.sandkasten.clone(),
+                judge_cache: self.state.cache.with_formatter(JsonFormatter),
             },
             self.clone(),
             feedback::Api {
-                state: self.state,
-                config: Arc::clone(&self.config),
+                state: Arc::clone(&self.state),
+                config: self.config.clone(),
             },
+            moderation::Api,
+            notes::Api,
+            prerequisites::Api,
             reports::Api {
+                config: self.config.clone(),
+            },
+            reveal::Api {
+                state: self.state,
+                config: self.config.clone(),
+            },
+            sessions::Api {
                 config: self.config,
             },
         )
@@ -70,6 +115,9 @@ impl Subtasks {
         retired: Query<Option<bool>>,
         /// Filter by creator.
         creator: Query<Option<Uuid>>,
+        /// Whether to search for subtasks under a permissive license (`CC0`,
+        /// `CC_BY` or `MIT`), which can be reused by other institutions.
+        permissive: Query<Option<bool>>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> ListSubtasks::Response<VerifiedUserAuth> {
@@ -86,6 +134,7 @@ impl Subtasks {
                     retired: retired.0,
                     creator: creator.0,
                     ty: subtask_type.0,
+                    permissive: permissive.0,
                 },
             )
             .await?,
@@ -111,11 +160,41 @@ impl Subtasks {
         };
 
         let user_subtasks = get_user_subtasks(&db, auth.0.id).await?;
+        let sessions = get_user_sessions(&db, auth.0.id).await?;
         let subtasks =
             stat_subtasks_prepare(&db, &auth.0, task_id.0.map(|x| vec![x]), &filter).await?;
 
         filter.ty = None;
-        GetSubtaskStats::ok(stat_subtasks(&subtasks, &user_subtasks, filter))
+        GetSubtaskStats::ok(stat_subtasks(&subtasks, &user_subtasks, &sessions, filter))
+    }
+
+    /// Recommend the next subtask of a task the user should work on.
+    ///
+    /// Only unsolved subtasks whose prerequisites have been solved are
+    /// considered. Subtasks which are slightly harder than the user's current
+    /// success rate suggests are preferred.
+    #[oai(path = "/tasks/:task_id/next_subtask", method = "get")]
+    pub async fn get_next_subtask(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetNextSubtask::Response<VerifiedUserAuth> {
+        if get_task(&db, task_id.0).await?.is_none() {
+            return GetNextSubtask::task_not_found();
+        }
+        match recommend_subtask(&db, &auth.0, task_id.0).await? {
+            Some(Recommendation {
+                subtask,
+                difficulty,
+                score,
+            }) => GetNextSubtask::ok(NextSubtask {
+                subtask,
+                difficulty,
+                score,
+            }),
+            None => GetNextSubtask::no_subtask_available(),
+        }
     }
 
     /// Delete a subtask.
@@ -152,6 +231,14 @@ response!(GetSubtaskStats = {
     Ok(200) => SubtaskStats,
 });
 
+response!(GetNextSubtask = {
+    Ok(200) => NextSubtask,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user has solved all subtasks or the remaining subtasks are locked by prerequisites.
+    NoSubtaskAvailable(404, error),
+});
+
 response!(DeleteSubtask = {
     Ok(200),
     /// Subtask does not exist.
@@ -0,0 +1,144 @@
+use entity::{challenges_subtasks, sea_orm_active_enums::ChallengesReviewStatus};
+use lib::{auth::AdminAuth, events::Notification};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::subtasks::{RejectSubtaskRequest, Subtask};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use uuid::Uuid;
+
+use crate::{endpoints::Tags, services::notifications::notify};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Return all subtasks which have to be approved by a moderator.
+    ///
+    /// Subtasks created by shadow-banned users and, if moderation is enabled,
+    /// all subtasks created by non-admins are pending review. They are only
+    /// visible to their creator until they are approved.
+    #[oai(path = "/moderation/queue", method = "get")]
+    pub async fn list_moderation_queue(
+        &self,
+        /// Filter by creator.
+        creator: Query<Option<Uuid>>,
+        /// Filter by task.
+        task_id: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListModerationQueue::Response<AdminAuth> {
+        let mut query = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::ReviewStatus.eq(ChallengesReviewStatus::Pending));
+        if let Some(creator) = creator.0 {
+            query = query.filter(challenges_subtasks::Column::Creator.eq(creator));
+        }
+        if let Some(task_id) = task_id.0 {
+            query = query.filter(challenges_subtasks::Column::TaskId.eq(task_id));
+        }
+        ListModerationQueue::ok(
+            query
+                .order_by_asc(challenges_subtasks::Column::CreationTimestamp)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(|subtask| Subtask::from(subtask, false, false))
+                .collect(),
+        )
+    }
+
+    /// Approve and enable a subtask which is pending review.
+    #[oai(path = "/moderation/queue/:subtask_id/approve", method = "post")]
+    pub async fn approve_subtask(
+        &self,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ApproveSubtask::Response<AdminAuth> {
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(subtask_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return ApproveSubtask::subtask_not_found();
+        };
+        if subtask.review_status != ChallengesReviewStatus::Pending {
+            return ApproveSubtask::not_pending();
+        }
+
+        let subtask = challenges_subtasks::ActiveModel {
+            enabled: Set(true),
+            review_status: Set(ChallengesReviewStatus::Approved),
+            ..subtask.into()
+        }
+        .update(&***db)
+        .await?;
+        ApproveSubtask::ok(Subtask::from(subtask, false, false))
+    }
+
+    /// Reject a subtask which is pending review.
+    ///
+    /// The subtask stays disabled and its creator is notified about the
+    /// rejection.
+    #[oai(path = "/moderation/queue/:subtask_id/reject", method = "post")]
+    pub async fn reject_subtask(
+        &self,
+        subtask_id: Path<Uuid>,
+        data: Json<RejectSubtaskRequest>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> RejectSubtask::Response<AdminAuth> {
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(subtask_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return RejectSubtask::subtask_not_found();
+        };
+        if subtask.review_status != ChallengesReviewStatus::Pending {
+            return RejectSubtask::not_pending();
+        }
+
+        let subtask = challenges_subtasks::ActiveModel {
+            enabled: Set(false),
+            review_status: Set(ChallengesReviewStatus::Rejected),
+            rejection_reason: Set(Some(data.0.reason.clone())),
+            ..subtask.into()
+        }
+        .update(&***db)
+        .await?;
+        notify(
+            &***db,
+            subtask.creator,
+            Notification::SubtaskRejected {
+                task_id: subtask.task_id,
+                subtask_id: subtask.id,
+                reason: data.0.reason,
+            },
+        )
+        .await?;
+        RejectSubtask::ok(Subtask::from(subtask, false, false))
+    }
+}
+
+response!(ListModerationQueue = {
+    Ok(200) => Vec<Subtask>,
+});
+
+response!(ApproveSubtask = {
+    Ok(200) => Subtask,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The subtask is not pending review.
+    NotPending(409, error),
+});
+
+response!(RejectSubtask = {
+    Ok(200) => Subtask,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The subtask is not pending review.
+    NotPending(409, error),
+});
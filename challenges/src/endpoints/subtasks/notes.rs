@@ -0,0 +1,128 @@
+use chrono::Utc;
+use entity::challenges_user_notes;
+use lib::auth::VerifiedUserAuth;
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::subtasks::{SetSubtaskNoteRequest, SubtaskNote};
+use sea_orm::{sea_query::OnConflict, EntityTrait, ModelTrait, Set};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{endpoints::Tags, services::subtasks::can_access_subtask};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Return the private note of the authenticated user on a subtask.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/note", method = "get")]
+    pub async fn get_note(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetNote::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return GetNote::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return GetNote::subtask_not_found();
+        }
+        let Some(note) = challenges_user_notes::Entity::find_by_id((auth.0.id, subtask.id))
+            .one(&***db)
+            .await?
+        else {
+            return GetNote::note_not_found();
+        };
+        GetNote::ok(SubtaskNote::from(note, subtask.task_id))
+    }
+
+    /// Create or replace the private note of the authenticated user on a
+    /// subtask.
+    ///
+    /// Notes are only visible to the user who wrote them.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/note", method = "put")]
+    pub async fn set_note(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SetSubtaskNoteRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SetNote::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return SetNote::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SetNote::subtask_not_found();
+        }
+
+        let note = challenges_user_notes::Entity::insert(challenges_user_notes::ActiveModel {
+            user_id: Set(auth.0.id),
+            subtask_id: Set(subtask.id),
+            note: Set(data.0.note),
+            timestamp: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                challenges_user_notes::Column::UserId,
+                challenges_user_notes::Column::SubtaskId,
+            ])
+            .update_columns([
+                challenges_user_notes::Column::Note,
+                challenges_user_notes::Column::Timestamp,
+            ])
+            .to_owned(),
+        )
+        .exec_with_returning(&***db)
+        .await?;
+
+        SetNote::ok(SubtaskNote::from(note, subtask.task_id))
+    }
+
+    /// Delete the private note of the authenticated user on a subtask.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/note", method = "delete")]
+    pub async fn delete_note(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DeleteNote::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return DeleteNote::subtask_not_found();
+        };
+        let Some(note) = challenges_user_notes::Entity::find_by_id((auth.0.id, subtask.id))
+            .one(&***db)
+            .await?
+        else {
+            return DeleteNote::note_not_found();
+        };
+        note.delete(&***db).await?;
+        DeleteNote::ok()
+    }
+}
+
+response!(GetNote = {
+    Ok(200) => SubtaskNote,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user has no note on this subtask.
+    NoteNotFound(404, error),
+});
+
+response!(SetNote = {
+    Ok(200) => SubtaskNote,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(DeleteNote = {
+    Ok(200),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user has no note on this subtask.
+    NoteNotFound(404, error),
+});
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use entity::{challenges_subtask_prerequisites, challenges_subtasks};
+use lib::auth::{AdminAuth, VerifiedUserAuth};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use schemas::challenges::subtasks::SubtaskPrerequisites;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{
+    endpoints::Tags,
+    services::{
+        prerequisites::{creates_cycle, get_prerequisite_graph},
+        subtasks::check_subtask_access,
+    },
+};
+
+pub struct Api;
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Return the prerequisites of a subtask.
+    #[oai(
+        path = "/tasks/:task_id/subtasks/:subtask_id/prerequisites",
+        method = "get"
+    )]
+    pub async fn get_prerequisites(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetPrerequisites::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return GetPrerequisites::subtask_not_found();
+        };
+        // the prerequisites of a locked subtask are needed to unlock it
+        let mut access = check_subtask_access(&db, &auth.0, &subtask).await?;
+        access.missing_prerequisites.clear();
+        if !access.allowed() {
+            return GetPrerequisites::subtask_not_found();
+        }
+
+        let prerequisites = challenges_subtask_prerequisites::Entity::find()
+            .filter(challenges_subtask_prerequisites::Column::SubtaskId.eq(subtask.id))
+            .all(&***db)
+            .await?
+            .into_iter()
+            .map(|x| x.prerequisite_id)
+            .collect();
+        GetPrerequisites::ok(SubtaskPrerequisites { prerequisites })
+    }
+
+    /// Replace the prerequisites of a subtask.
+    ///
+    /// Prerequisites must be subtasks of the same task and must not form a
+    /// cycle.
+    #[oai(
+        path = "/tasks/:task_id/subtasks/:subtask_id/prerequisites",
+        method = "put"
+    )]
+    pub async fn set_prerequisites(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SubtaskPrerequisites>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> SetPrerequisites::Response<AdminAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return SetPrerequisites::subtask_not_found();
+        };
+
+        let prerequisites = data
+            .0
+            .prerequisites
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let found = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::Id.is_in(prerequisites.iter().copied()))
+            .filter(challenges_subtasks::Column::TaskId.eq(subtask.task_id))
+            .count(&***db)
+            .await?;
+        if found != prerequisites.len() as u64 {
+            return SetPrerequisites::prerequisite_not_found();
+        }
+
+        let graph = get_prerequisite_graph(&db, subtask.task_id).await?;
+        if creates_cycle(&graph, subtask.id, &prerequisites) {
+            return SetPrerequisites::cycle();
+        }
+
+        challenges_subtask_prerequisites::Entity::delete_many()
+            .filter(challenges_subtask_prerequisites::Column::SubtaskId.eq(subtask.id))
+            .exec(&***db)
+            .await?;
+        if !prerequisites.is_empty() {
+            challenges_subtask_prerequisites::Entity::insert_many(prerequisites.iter().map(
+                |&id| challenges_subtask_prerequisites::ActiveModel {
+                    subtask_id: Set(subtask.id),
+                    prerequisite_id: Set(id),
+                },
+            ))
+            .exec(&***db)
+            .await?;
+        }
+
+        SetPrerequisites::ok(SubtaskPrerequisites { prerequisites })
+    }
+}
+
+response!(GetPrerequisites = {
+    Ok(200) => SubtaskPrerequisites,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(SetPrerequisites = {
+    Ok(200) => SubtaskPrerequisites,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// One of the prerequisites does not exist or belongs to a different task.
+    PrerequisiteNotFound(404, error),
+    /// The prerequisites would form a cycle.
+    Cycle(400, error),
+});
@@ -1,13 +1,12 @@
-use std::sync::Arc;
-
 use chrono::{DateTime, Duration, Utc};
 use entity::{
     challenges_ban, challenges_subtask_reports, challenges_subtasks, challenges_user_subtasks,
-    sea_orm_active_enums::{ChallengesBanAction, ChallengesReportReason},
+    sea_orm_active_enums::{ChallengesBanAction, ChallengesReportReason, ChallengesReviewStatus},
 };
 use lib::{
     auth::{AdminAuth, VerifiedUserAuth},
-    config::Config,
+    config::SharedConfig,
+    events::Notification,
 };
 use poem::web::Data;
 use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
@@ -17,7 +16,7 @@ use poem_openapi::{
     OpenApi,
 };
 use schemas::challenges::subtasks::{
-    CreateReportRequest, Report, ResolveReportAction, ResolveReportRequest,
+    ContentViolation, CreateReportRequest, Report, ResolveReportAction, ResolveReportRequest,
 };
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityTrait, ModelTrait, PaginatorTrait,
@@ -28,13 +27,18 @@ use uuid::Uuid;
 use super::get_subtask;
 use crate::{
     endpoints::Tags,
-    services::subtasks::{
-        get_active_ban, get_user_subtask, update_user_subtask, ActiveBan, UserSubtaskExt,
+    services::{
+        content_filter::ContentChecker,
+        notifications::notify,
+        subtasks::{
+            can_access_subtask, get_active_ban, get_user_subtask, publish_ban_event,
+            update_user_subtask, ActiveBan, UserSubtaskExt,
+        },
     },
 };
 
 pub struct Api {
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
 }
 
 #[OpenApi(tag = "Tags::Subtasks")]
@@ -76,7 +80,7 @@ impl Api {
         let Some((subtask, _)) = get_subtask(&db, data.0.task_id, data.0.subtask_id).await? else {
             return CreateReport::subtask_not_found();
         };
-        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
             return CreateReport::subtask_not_found();
         }
 
@@ -91,13 +95,20 @@ impl Api {
             ActiveBan::Permanent => return CreateReport::banned(None),
         }
 
+        let config = self.config.load();
+        let mut checker = ContentChecker::new(&config.challenges.content_filter, &auth.0);
+        let comment = checker.check("comment", data.0.comment);
+        if let Err(violations) = checker.finish() {
+            return CreateReport::content_violation(violations);
+        }
+
         let (report, _) = create_report(
             &db,
             Some(auth.0.id),
             subtask,
             user_subtask.as_ref(),
             data.0.reason,
-            data.0.comment,
+            comment,
         )
         .await?;
 
@@ -132,13 +143,13 @@ impl Api {
                     &db,
                     reporter,
                     ChallengesBanAction::Report,
-                    &self.config.challenges.quizzes.ban_days,
+                    &self.config.load().challenges.quizzes.ban_days,
                     auth.0.id,
                     format!("Bad report ({}): {}", report.id, report.comment),
                 )
                 .await?;
                 challenges_subtasks::ActiveModel {
-                    enabled: Set(true),
+                    enabled: Set(subtask.review_status == ChallengesReviewStatus::Approved),
                     ..subtask.into()
                 }
                 .update(&***db)
@@ -150,7 +161,7 @@ impl Api {
                     &db,
                     subtask.creator,
                     ChallengesBanAction::Create,
-                    &self.config.challenges.quizzes.ban_days,
+                    &self.config.load().challenges.quizzes.ban_days,
                     auth.0.id,
                     format!("Bad subtask: {}", report.comment),
                 )
@@ -181,6 +192,8 @@ response!(CreateReport = {
     PermissionDenied(403, error),
     /// The user is currently banned from reporting subtasks.
     Banned(403, error) => Option<DateTime<Utc>>,
+    /// The comment contains a forbidden word or personal data.
+    ContentViolation(400, error) => Vec<ContentViolation>,
 });
 
 response!(ResolveReport = {
@@ -226,6 +239,18 @@ pub(super) async fn create_report(
     }
     .insert(db)
     .await?;
+    if user_id != Some(subtask.creator) {
+        notify(
+            db,
+            subtask.creator,
+            Notification::SubtaskReported {
+                task_id: subtask.task_id,
+                subtask_id: subtask.id,
+                reason: report.reason,
+            },
+        )
+        .await?;
+    }
 
     let subtask = challenges_subtasks::ActiveModel {
         enabled: Set(false),
@@ -257,7 +282,7 @@ async fn ban_user(
         .get(bans as usize)
         .map(|&days| Duration::days(days as _));
 
-    Ok(challenges_ban::ActiveModel {
+    let ban = challenges_ban::ActiveModel {
         id: Set(Uuid::new_v4()),
         user_id: Set(user_id),
         start: Set(now),
@@ -267,5 +292,7 @@ async fn ban_user(
         reason: Set(reason),
     }
     .insert(db)
-    .await?)
+    .await?;
+    publish_ban_event(db, &ban).await?;
+    Ok(ban)
 }
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::{
+    challenges_clozes, challenges_code_readings, challenges_coding_challenges,
+    challenges_matchings, challenges_multiple_choice_quizes, challenges_orderings,
+    challenges_questions, challenges_sql_challenges, challenges_true_false_statements,
+    challenges_user_subtasks, sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{
+    auth::VerifiedUserAuth, config::SharedConfig, services::shop::AddCoinsError, SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, OpenApi};
+use schemas::challenges::{
+    cloze::ClozeWithSolution,
+    code_readings::CodeReadingWithSolution,
+    coding_challenges::SubmissionContent,
+    matchings::MatchingWithSolution,
+    multiple_choice::{Answer, MultipleChoiceQuestion},
+    orderings::OrderingWithSolution,
+    question::QuestionWithSolution,
+    sql_challenges::SqlChallengeWithSolution,
+    subtasks::{Subtask, SubtaskSolution},
+    true_false::TrueFalseWithSolution,
+};
+use sea_orm::{DatabaseTransaction, DbErr, EntityTrait, Set};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{
+    endpoints::Tags,
+    services::{
+        exams::get_active_exam,
+        subtasks::{can_access_subtask, get_user_subtask, update_user_subtask, UserSubtaskExt},
+    },
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Reveal the solution of a subtask.
+    ///
+    /// The solution is free if the user has used all attempts of the subtask
+    /// (and the subtask reveals its solution) or has already revealed it.
+    /// Otherwise the configured number of coins is deducted. Subtasks with an
+    /// attempt limit that do not reveal their solution can never be revealed.
+    /// After the solution has been revealed, solving the subtask no longer
+    /// grants any rewards to the user.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/reveal", method = "post")]
+    pub async fn reveal_solution(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> RevealSolution::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return RevealSolution::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return RevealSolution::subtask_not_found();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+        if !(auth.0.admin || auth.0.id == subtask.creator || user_subtask.is_revealed()) {
+            if subtask.max_attempts.is_some() && !subtask.reveal_solution {
+                return RevealSolution::forbidden();
+            }
+            if get_active_exam(&db, subtask.task_id).await?.is_some() {
+                return RevealSolution::forbidden();
+            }
+
+            if !user_subtask.attempts_exhausted(&subtask) {
+                let price = self.config.load().challenges.reveal.price;
+                if price != 0 {
+                    match self
+                        .state
+                        .services
+                        .shop
+                        .add_coins(auth.0.id, -(price as i64), "Challenges / Lösungen", false)
+                        .await?
+                    {
+                        Ok(_) => {}
+                        Err(AddCoinsError::NotEnoughCoins) => {
+                            return RevealSolution::not_enough_coins()
+                        }
+                    }
+                }
+            }
+
+            update_user_subtask(
+                &db,
+                user_subtask.as_ref(),
+                challenges_user_subtasks::ActiveModel {
+                    user_id: Set(auth.0.id),
+                    subtask_id: Set(subtask.id),
+                    revealed_timestamp: Set(Some(Utc::now().naive_utc())),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        }
+
+        let subtask = Subtask::from(subtask, user_subtask.is_solved(), user_subtask.is_rated());
+        match get_solution(&db, subtask).await? {
+            Some(solution) => RevealSolution::ok(solution),
+            None => RevealSolution::subtask_not_found(),
+        }
+    }
+}
+
+response!(RevealSolution = {
+    Ok(200) => SubtaskSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The solution of this subtask cannot be revealed (e.g. because an exam is running in the parent task).
+    Forbidden(403, error),
+    /// The user does not have enough coins to reveal the solution.
+    NotEnoughCoins(403, error),
+});
+
+/// Load the type specific content of a subtask including its solution.
+async fn get_solution(
+    db: &DatabaseTransaction,
+    subtask: Subtask,
+) -> Result<Option<SubtaskSolution>, DbErr> {
+    Ok(match subtask.ty {
+        ChallengesSubtaskType::CodingChallenge => {
+            challenges_coding_challenges::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|cc| {
+                    SubtaskSolution::CodingChallenge(SubmissionContent {
+                        environment: cc.solution_environment,
+                        code: cc.solution_code,
+                        files: Vec::new(),
+                    })
+                })
+        }
+        ChallengesSubtaskType::Matching => challenges_matchings::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| SubtaskSolution::Matching(MatchingWithSolution::from(x, subtask))),
+        ChallengesSubtaskType::MultipleChoiceQuestion => {
+            challenges_multiple_choice_quizes::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| {
+                    SubtaskSolution::MultipleChoice(MultipleChoiceQuestion::<Answer>::from(
+                        x, subtask,
+                    ))
+                })
+        }
+        ChallengesSubtaskType::Question => challenges_questions::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| SubtaskSolution::Question(QuestionWithSolution::from(x, subtask))),
+        ChallengesSubtaskType::TrueFalse => {
+            challenges_true_false_statements::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| SubtaskSolution::TrueFalse(TrueFalseWithSolution::from(x, subtask)))
+        }
+        ChallengesSubtaskType::Ordering => challenges_orderings::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| SubtaskSolution::Ordering(OrderingWithSolution::from(x, subtask))),
+        ChallengesSubtaskType::Cloze => challenges_clozes::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| SubtaskSolution::Cloze(ClozeWithSolution::from(x, subtask))),
+        ChallengesSubtaskType::CodeReading => {
+            challenges_code_readings::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| SubtaskSolution::CodeReading(CodeReadingWithSolution::from(x, subtask)))
+        }
+        ChallengesSubtaskType::SqlChallenge => {
+            challenges_sql_challenges::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| SubtaskSolution::SqlChallenge(SqlChallengeWithSolution::from(x, subtask)))
+        }
+    })
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use entity::{challenges_subtask_sessions, challenges_user_subtasks};
+use lib::{auth::VerifiedUserAuth, config::SharedConfig};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, OpenApi};
+use schemas::challenges::subtasks::{SubtaskSession, TimeToSolveStats};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use super::get_subtask;
+use crate::{
+    endpoints::Tags,
+    services::{
+        subtasks::{can_access_subtask, get_user_subtask},
+        time_tracking::{start_session, time_to_solve, time_to_solve_stats},
+    },
+};
+
+pub struct Api {
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Subtasks")]
+impl Api {
+    /// Start working on a subtask.
+    ///
+    /// Clients should call this endpoint whenever the user opens a subtask.
+    /// Only the first call starts a new session, subsequent calls return the
+    /// running session. After the subtask has been solved, a new session is
+    /// only started once the subtask can be re-solved.
+    #[oai(path = "/tasks/:task_id/subtasks/:subtask_id/start", method = "post")]
+    pub async fn start_subtask(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> StartSubtask::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return StartSubtask::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return StartSubtask::subtask_not_found();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+        let start = start_session(
+            &db,
+            auth.0.id,
+            subtask.id,
+            user_subtask.as_ref(),
+            &self.config.load().challenges.re_solve,
+        )
+        .await?;
+
+        StartSubtask::ok(SubtaskSession {
+            task_id: subtask.task_id,
+            subtask_id: subtask.id,
+            start: start.and_utc(),
+        })
+    }
+
+    /// Return statistics about the time users needed to solve a subtask.
+    ///
+    /// Only the creator of the subtask and admins can see these statistics.
+    /// Solves of the creator are ignored.
+    #[oai(
+        path = "/tasks/:task_id/subtasks/:subtask_id/time_stats",
+        method = "get"
+    )]
+    pub async fn get_time_stats(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetTimeStats::Response<VerifiedUserAuth> {
+        let Some((subtask, _)) = get_subtask(&db, task_id.0, subtask_id.0).await? else {
+            return GetTimeStats::subtask_not_found();
+        };
+        if !(auth.0.admin || auth.0.id == subtask.creator) {
+            return GetTimeStats::forbidden();
+        }
+
+        let user_subtasks = challenges_user_subtasks::Entity::find()
+            .filter(challenges_user_subtasks::Column::SubtaskId.eq(subtask.id))
+            .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+            .all(&***db)
+            .await?
+            .into_iter()
+            .map(|x| (x.user_id, x))
+            .collect::<HashMap<_, _>>();
+        let durations = challenges_subtask_sessions::Entity::find()
+            .filter(challenges_subtask_sessions::Column::SubtaskId.eq(subtask.id))
+            .filter(challenges_subtask_sessions::Column::UserId.ne(subtask.creator))
+            .all(&***db)
+            .await?
+            .into_iter()
+            .filter_map(|session| time_to_solve(session.start, user_subtasks.get(&session.user_id)))
+            .collect();
+
+        GetTimeStats::ok(time_to_solve_stats(durations))
+    }
+}
+
+response!(StartSubtask = {
+    Ok(200) => SubtaskSession,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(GetTimeStats = {
+    Ok(200) => TimeToSolveStats,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not allowed to view the statistics of this subtask.
+    Forbidden(403, error),
+});
@@ -0,0 +1,508 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::Utc;
+use entity::{
+    challenges_challenges, challenges_cohort_assignments, challenges_contests,
+    challenges_course_tasks, challenges_exams, challenges_path_items, challenges_subtasks,
+    challenges_tasks,
+};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+    services::shop::AddCoinsError,
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::{
+    course_tasks::UpdateCourseTaskRequest,
+    tasks::{CreateTaskRequest, SkillTask, Task, TaskDependents, TaskKind, UnlockTaskResponse},
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait,
+    ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::{
+    course_tasks::{check_course, CourseNotFoundError},
+    Tags,
+};
+use crate::services::{
+    organizations::can_see_task,
+    subtasks::grant_subtask_access,
+    tasks::{get_skill_tasks, get_task_progress},
+    unlocks::{combined_price, get_locked_subtasks, lock_task_unlock},
+};
+
+pub struct Tasks {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::Tasks")]
+impl Tasks {
+    /// List all tasks.
+    #[oai(path = "/tasks", method = "get")]
+    async fn list_tasks(
+        &self,
+        /// Filter by creator
+        creator: Query<Option<Uuid>>,
+        /// Filter by kind
+        kind: Query<Option<TaskKind>>,
+        /// Filter by course id (implies `kind=course_task`)
+        course_id: Query<Option<String>>,
+        /// Filter by organization
+        organization_id: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListTasks::Response<AdminAuth> {
+        let mut query = challenges_tasks::Entity::find()
+            .order_by_asc(challenges_tasks::Column::CreationTimestamp);
+        if let Some(creator) = creator.0 {
+            query = query.filter(challenges_tasks::Column::Creator.eq(creator));
+        }
+        if let Some(organization_id) = organization_id.0 {
+            query = query.filter(challenges_tasks::Column::OrganizationId.eq(organization_id));
+        }
+        if let Some(course_id) = course_id.0 {
+            if kind.0 == Some(TaskKind::Challenge) {
+                return ListTasks::ok(Vec::new());
+            }
+            query = query
+                .inner_join(challenges_course_tasks::Entity)
+                .filter(challenges_course_tasks::Column::CourseId.eq(course_id));
+        } else {
+            match kind.0 {
+                Some(TaskKind::Challenge) => {
+                    query = query.inner_join(challenges_challenges::Entity)
+                }
+                Some(TaskKind::CourseTask) => {
+                    query = query.inner_join(challenges_course_tasks::Entity)
+                }
+                None => {}
+            }
+        }
+
+        let tasks = query.all(&***db).await?;
+        ListTasks::ok(load_tasks(&db, tasks).await?)
+    }
+
+    /// List all tasks that train a skill together with the progress of the
+    /// user.
+    ///
+    /// These are the challenges with this skill and the course tasks in the
+    /// courses of the skill.
+    #[oai(path = "/skills/:skill_id/tasks", method = "get")]
+    async fn list_tasks_in_skill(
+        &self,
+        skill_id: Path<String>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListTasksInSkill::Response<VerifiedUserAuth> {
+        let Some(skill) = self
+            .state
+            .services
+            .skills
+            .get_skills()
+            .await?
+            .remove(&skill_id.0)
+        else {
+            return ListTasksInSkill::not_found();
+        };
+
+        let tasks = get_skill_tasks(&db, &auth.0, &skill.id, skill.courses).await?;
+        let mut progress = get_task_progress(&db, auth.0.id, tasks.iter().map(|x| x.id)).await?;
+        ListTasksInSkill::ok(
+            load_tasks(&db, tasks)
+                .await?
+                .into_iter()
+                .map(|task| SkillTask {
+                    progress: progress.remove(&task.id).unwrap_or_default(),
+                    task,
+                })
+                .collect(),
+        )
+    }
+
+    /// Get a task by id.
+    #[oai(path = "/tasks/:task_id", method = "get")]
+    async fn get_task(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetTask::Response<AdminAuth> {
+        let Some(task) = challenges_tasks::Entity::find_by_id(task_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return GetTask::task_not_found();
+        };
+        GetTask::ok(load_task(&db, task).await?)
+    }
+
+    /// Unlock all locked subtasks of a task at once.
+    ///
+    /// Locked subtasks are subtasks with an `unlock_price` the user cannot
+    /// access yet. The combined price of these subtasks (with the configured
+    /// discount if there are at least two) is deducted in a single
+    /// transaction before the subtasks are unlocked. If the user does not
+    /// have enough coins, no subtask is unlocked.
+    #[oai(path = "/tasks/:task_id/access", method = "post")]
+    async fn unlock_task(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> UnlockTask::Response<VerifiedUserAuth> {
+        match challenges_tasks::Entity::find_by_id(task_id.0)
+            .one(&***db)
+            .await?
+        {
+            Some(task) if can_see_task(&auth.0, &task) => {}
+            _ => return UnlockTask::task_not_found(),
+        }
+
+        lock_task_unlock(&db, auth.0.id, task_id.0).await?;
+        let subtasks = get_locked_subtasks(&db, &auth.0, task_id.0).await?;
+        let prices = subtasks
+            .iter()
+            .filter_map(|x| x.unlock_price)
+            .map(|x| x as u64)
+            .collect::<Vec<_>>();
+        let price = combined_price(
+            &prices,
+            self.config.load().challenges.unlock.discount_percent,
+        );
+
+        if price != 0 {
+            match self
+                .state
+                .services
+                .shop
+                .add_coins(
+                    auth.0.id,
+                    -(price as i64),
+                    "Challenges / Freischaltungen",
+                    false,
+                )
+                .await?
+            {
+                Ok(_) => {}
+                Err(AddCoinsError::NotEnoughCoins) => return UnlockTask::not_enough_coins(),
+            }
+        }
+
+        let mut unlocked = Vec::with_capacity(subtasks.len());
+        for subtask in subtasks {
+            if let Err(err) = grant_subtask_access(&db, auth.0.id, subtask.id).await {
+                // refund the coins, as the subtasks have not been unlocked
+                if price != 0 {
+                    self.state
+                        .services
+                        .shop
+                        .add_coins(
+                            auth.0.id,
+                            price as i64,
+                            "Challenges / Freischaltungen (Erstattung)",
+                            true,
+                        )
+                        .await?
+                        .ok();
+                }
+                return Err(err.into());
+            }
+            unlocked.push(subtask.id);
+        }
+
+        UnlockTask::ok(UnlockTaskResponse { unlocked, price })
+    }
+
+    /// Create a new course task.
+    ///
+    /// Challenges are created in their category instead.
+    #[oai(path = "/tasks", method = "post")]
+    async fn create_task(
+        &self,
+        data: Json<CreateTaskRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> CreateTask::Response<AdminAuth> {
+        if data.0.lecture_id.is_some() && data.0.section_id.is_none() {
+            return CreateTask::lecture_without_section();
+        }
+        match check_course(
+            &self.state.services,
+            &data.0.course_id,
+            data.0.section_id.as_deref(),
+            data.0.lecture_id.as_deref(),
+        )
+        .await?
+        {
+            Ok(_) => {}
+            Err(CourseNotFoundError::Course) => return CreateTask::course_not_found(),
+            Err(CourseNotFoundError::Section) => return CreateTask::section_not_found(),
+            Err(CourseNotFoundError::Lecture) => return CreateTask::lecture_not_found(),
+        }
+
+        let task = challenges_tasks::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            organization_id: Set(auth.0.organization_id()),
+        }
+        .insert(&***db)
+        .await?;
+        let course_task = challenges_course_tasks::ActiveModel {
+            task_id: Set(task.id),
+            course_id: Set(data.0.course_id),
+            section_id: Set(data.0.section_id),
+            lecture_id: Set(data.0.lecture_id),
+        }
+        .insert(&***db)
+        .await?;
+
+        CreateTask::created(Task::from(task, None, Some(course_task), 0))
+    }
+
+    /// Update the course, section and lecture of a course task.
+    #[oai(path = "/tasks/:task_id", method = "patch")]
+    async fn update_task(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<UpdateCourseTaskRequest>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> UpdateTask::Response<AdminAuth> {
+        let Some((course_task, Some(task))) =
+            challenges_course_tasks::Entity::find_by_id(task_id.0)
+                .find_also_related(challenges_tasks::Entity)
+                .one(&***db)
+                .await?
+        else {
+            return match challenges_tasks::Entity::find_by_id(task_id.0)
+                .one(&***db)
+                .await?
+            {
+                Some(_) => UpdateTask::not_a_course_task(),
+                None => UpdateTask::task_not_found(),
+            };
+        };
+
+        let course_id = data.0.course_id.get_new(&course_task.course_id);
+        let section_id = data.0.section_id.get_new(&course_task.section_id);
+        let lecture_id = data.0.lecture_id.get_new(&course_task.lecture_id);
+        if lecture_id.is_some() && section_id.is_none() {
+            return UpdateTask::lecture_without_section();
+        }
+        match check_course(
+            &self.state.services,
+            course_id,
+            section_id.as_deref(),
+            lecture_id.as_deref(),
+        )
+        .await?
+        {
+            Ok(_) => {}
+            Err(CourseNotFoundError::Course) => return UpdateTask::course_not_found(),
+            Err(CourseNotFoundError::Section) => return UpdateTask::section_not_found(),
+            Err(CourseNotFoundError::Lecture) => return UpdateTask::lecture_not_found(),
+        }
+
+        let course_task = challenges_course_tasks::ActiveModel {
+            task_id: Unchanged(course_task.task_id),
+            course_id: data.0.course_id.update(course_task.course_id),
+            section_id: data.0.section_id.update(course_task.section_id),
+            lecture_id: data.0.lecture_id.update(course_task.lecture_id),
+        }
+        .update(&***db)
+        .await?;
+        let subtasks = task
+            .find_related(challenges_subtasks::Entity)
+            .count(&***db)
+            .await?;
+        UpdateTask::ok(Task::from(task, None, Some(course_task), subtasks))
+    }
+
+    /// Delete a task.
+    ///
+    /// Deleting a task also deletes its subtasks, contests, exams, learning
+    /// path items and cohort assignments. Tasks that still have any of these
+    /// are only deleted if `force` is set.
+    #[oai(path = "/tasks/:task_id", method = "delete")]
+    async fn delete_task(
+        &self,
+        task_id: Path<Uuid>,
+        /// Delete the task even if other data depends on it.
+        force: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DeleteTask::Response<AdminAuth> {
+        let Some(task) = challenges_tasks::Entity::find_by_id(task_id.0)
+            .one(&***db)
+            .await?
+        else {
+            return DeleteTask::task_not_found();
+        };
+
+        let dependents = get_dependents(&db, &task).await?;
+        if !force.0.unwrap_or(false) && !dependents.is_empty() {
+            return DeleteTask::task_in_use(dependents);
+        }
+
+        task.delete(&***db).await?;
+        DeleteTask::ok()
+    }
+}
+
+response!(ListTasks = {
+    Ok(200) => Vec<Task>,
+});
+
+response!(ListTasksInSkill = {
+    Ok(200) => Vec<SkillTask>,
+    /// Skill does not exist.
+    NotFound(404, error),
+});
+
+response!(GetTask = {
+    Ok(200) => Task,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+});
+
+response!(UnlockTask = {
+    Ok(200) => UnlockTaskResponse,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user does not have enough coins to unlock the subtasks.
+    NotEnoughCoins(403, error),
+});
+
+response!(CreateTask = {
+    Created(201) => Task,
+    /// Course does not exist.
+    CourseNotFound(404, error),
+    /// Section does not exist.
+    SectionNotFound(404, error),
+    /// Lecture does not exist.
+    LectureNotFound(404, error),
+    /// Cannot set lecture id without section id
+    LectureWithoutSection(400, error),
+});
+
+response!(UpdateTask = {
+    Ok(200) => Task,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The task is not a course task. Challenges are updated in their category instead.
+    NotACourseTask(400, error),
+    /// Course does not exist.
+    CourseNotFound(404, error),
+    /// Section does not exist.
+    SectionNotFound(404, error),
+    /// Lecture does not exist.
+    LectureNotFound(404, error),
+    /// Cannot set lecture id without section id
+    LectureWithoutSection(400, error),
+});
+
+response!(DeleteTask = {
+    Ok(200),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// Other data depends on the task. `details` contains the number of dependent objects.
+    TaskInUse(409, error) => TaskDependents,
+});
+
+async fn load_task(db: &DatabaseTransaction, task: challenges_tasks::Model) -> Result<Task, DbErr> {
+    let challenge = task
+        .find_related(challenges_challenges::Entity)
+        .one(db)
+        .await?;
+    let course_task = task
+        .find_related(challenges_course_tasks::Entity)
+        .one(db)
+        .await?;
+    let subtasks = task
+        .find_related(challenges_subtasks::Entity)
+        .count(db)
+        .await?;
+    Ok(Task::from(task, challenge, course_task, subtasks))
+}
+
+async fn load_tasks(
+    db: &DatabaseTransaction,
+    tasks: Vec<challenges_tasks::Model>,
+) -> Result<Vec<Task>, DbErr> {
+    let ids = tasks.iter().map(|x| x.id).collect::<Vec<_>>();
+    let mut challenges = challenges_challenges::Entity::find()
+        .filter(challenges_challenges::Column::TaskId.is_in(ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| (x.task_id, x))
+        .collect::<HashMap<_, _>>();
+    let mut course_tasks = challenges_course_tasks::Entity::find()
+        .filter(challenges_course_tasks::Column::TaskId.is_in(ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| (x.task_id, x))
+        .collect::<HashMap<_, _>>();
+    let subtasks = challenges_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_subtasks::Column::TaskId)
+        .column_as(Expr::col(challenges_subtasks::Column::Id).count(), "count")
+        .filter(challenges_subtasks::Column::TaskId.is_in(ids))
+        .group_by(challenges_subtasks::Column::TaskId)
+        .into_tuple::<(Uuid, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    Ok(tasks
+        .into_iter()
+        .map(|task| {
+            let challenge = challenges.remove(&task.id);
+            let course_task = course_tasks.remove(&task.id);
+            let subtasks = subtasks.get(&task.id).copied().unwrap_or_default();
+            Task::from(task, challenge, course_task, subtasks as _)
+        })
+        .collect())
+}
+
+async fn get_dependents(
+    db: &DatabaseTransaction,
+    task: &challenges_tasks::Model,
+) -> Result<TaskDependents, DbErr> {
+    Ok(TaskDependents {
+        subtasks: task
+            .find_related(challenges_subtasks::Entity)
+            .count(db)
+            .await?,
+        contests: task
+            .find_related(challenges_contests::Entity)
+            .count(db)
+            .await?,
+        exams: task
+            .find_related(challenges_exams::Entity)
+            .count(db)
+            .await?,
+        path_items: task
+            .find_related(challenges_path_items::Entity)
+            .count(db)
+            .await?,
+        cohort_assignments: task
+            .find_related(challenges_cohort_assignments::Entity)
+            .count(db)
+            .await?,
+    })
+}
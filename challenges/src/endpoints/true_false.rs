@@ -0,0 +1,507 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_true_false_attempts, challenges_true_false_statements, challenges_user_subtasks,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    config::SharedConfig,
+    events::{publish_event, Event},
+    SharedState,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::{
+    exams::ExamAnswer,
+    true_false::{
+        CreateTrueFalseRequest, SolveTrueFalseFeedback, SolveTrueFalseRequest, TrueFalse,
+        TrueFalseSummary, TrueFalseWithSolution, UpdateTrueFalseRequest,
+    },
+};
+use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    exams::{get_active_exam, record_exam_answer},
+    notifications::notify_subtask_solved,
+    revisions::{
+        get_current_revision, query_subtask_as_of, record_revision, QuerySubtaskAsOfError,
+    },
+    similarity::{find_duplicates, fingerprint, set_fingerprint},
+    subtasks::{
+        can_access_subtask, create_subtask, deduct_hearts, get_subtask, get_user_subtask,
+        query_subtask, query_subtask_admin, query_subtasks, send_task_rewards, update_subtask,
+        update_user_subtask, CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter,
+        SolveKind, UpdateSubtaskError, UserSubtaskExt,
+    },
+    task_config::get_timeout,
+};
+
+pub struct TrueFalseStatements {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+}
+
+#[OpenApi(tag = "Tags::TrueFalse")]
+impl TrueFalseStatements {
+    /// List all true/false statements in a task.
+    #[oai(path = "/tasks/:task_id/true_false", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_statements(
+        &self,
+        task_id: Path<Uuid>,
+        /// Whether to search for subtasks the user has attempted to solve.
+        attempted: Query<Option<bool>>,
+        /// Whether to search for solved subtasks.
+        solved: Query<Option<bool>>,
+        /// Whether to search for rated subtasks.
+        rated: Query<Option<bool>>,
+        /// Whether to search for enabled subtasks.
+        enabled: Query<Option<bool>>,
+        /// Whether to search for retired subtasks.
+        retired: Query<Option<bool>>,
+        /// Filter by creator.
+        creator: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListTrueFalse::Response<VerifiedUserAuth> {
+        ListTrueFalse::ok(
+            query_subtasks::<challenges_true_false_statements::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                QuerySubtasksFilter {
+                    attempted: attempted.0,
+                    solved: solved.0,
+                    rated: rated.0,
+                    enabled: enabled.0,
+                    retired: retired.0,
+                    creator: creator.0,
+                    ty: None,
+                    permissive: None,
+                },
+                TrueFalseSummary::from,
+            )
+            .await?,
+        )
+    }
+
+    /// Get a true/false statement by id.
+    #[oai(path = "/tasks/:task_id/true_false/:subtask_id", method = "get")]
+    async fn get_statement(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the statement as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        /// Include the private note of the authenticated user.
+        include_note: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetTrueFalse::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetTrueFalse::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_true_false_statements::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                TrueFalse::from,
+            )
+            .await?
+            {
+                Ok(x) => GetTrueFalse::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => GetTrueFalse::subtask_not_found(),
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => GetTrueFalse::revision_not_found(),
+            };
+        }
+
+        match query_subtask::<challenges_true_false_statements::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            TrueFalse::from,
+            include_note.0.unwrap_or(false),
+        )
+        .await?
+        {
+            Some(statement) => GetTrueFalse::ok(statement),
+            None => GetTrueFalse::subtask_not_found(),
+        }
+    }
+
+    /// Get a true/false statement and its solution by id.
+    #[oai(
+        path = "/tasks/:task_id/true_false/:subtask_id/solution",
+        method = "get"
+    )]
+    async fn get_statement_with_solution(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Return the statement as it was at this time (admins only).
+        as_of: Query<Option<DateTime<Utc>>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetTrueFalseWithSolution::Response<VerifiedUserAuth> {
+        if let Some(as_of) = as_of.0 {
+            if !auth.0.admin {
+                return GetTrueFalseWithSolution::forbidden();
+            }
+            return match query_subtask_as_of::<challenges_true_false_statements::Entity, _>(
+                &db,
+                &auth.0,
+                task_id.0,
+                subtask_id.0,
+                as_of.naive_utc(),
+                TrueFalseWithSolution::from,
+            )
+            .await?
+            {
+                Ok(x) => GetTrueFalseWithSolution::ok(x),
+                Err(QuerySubtaskAsOfError::SubtaskNotFound) => {
+                    GetTrueFalseWithSolution::subtask_not_found()
+                }
+                Err(QuerySubtaskAsOfError::RevisionNotFound) => {
+                    GetTrueFalseWithSolution::revision_not_found()
+                }
+            };
+        }
+
+        match query_subtask_admin::<challenges_true_false_statements::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            TrueFalseWithSolution::from,
+        )
+        .await?
+        {
+            Ok(statement) => GetTrueFalseWithSolution::ok(statement),
+            Err(QuerySubtaskAdminError::NotFound) => GetTrueFalseWithSolution::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => GetTrueFalseWithSolution::forbidden(),
+        }
+    }
+
+    /// Create a new true/false statement.
+    #[oai(path = "/tasks/:task_id/true_false", method = "post")]
+    async fn create_statement(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<CreateTrueFalseRequest>,
+        /// Create the subtask even if highly similar subtasks already exist in
+        /// this task.
+        allow_duplicates: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateTrueFalse::Response<VerifiedUserAuth> {
+        let subtask = match create_subtask(
+            &db,
+            &self.state.services,
+            &self.config.load(),
+            &auth.0,
+            task_id.0,
+            data.0.subtask,
+            ChallengesSubtaskType::TrueFalse,
+        )
+        .await?
+        {
+            Ok(subtask) => subtask,
+            Err(CreateSubtaskError::TaskNotFound) => return CreateTrueFalse::task_not_found(),
+            Err(CreateSubtaskError::Forbidden) => return CreateTrueFalse::forbidden(),
+            Err(CreateSubtaskError::Banned(until)) => return CreateTrueFalse::banned(until),
+            Err(CreateSubtaskError::QuotaExceeded(reset)) => {
+                return CreateTrueFalse::quota_exceeded(reset)
+            }
+            Err(CreateSubtaskError::XpLimitExceeded(x)) => {
+                return CreateTrueFalse::xp_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::CoinLimitExceeded(x)) => {
+                return CreateTrueFalse::coin_limit_exceeded(x)
+            }
+            Err(CreateSubtaskError::AttributionRequired) => {
+                return CreateTrueFalse::attribution_required()
+            }
+        };
+
+        let fingerprint = fingerprint(&data.0.statement, []);
+        if !allow_duplicates.0.unwrap_or(false) {
+            let duplicates = find_duplicates(
+                &db,
+                task_id.0,
+                ChallengesSubtaskType::TrueFalse,
+                &fingerprint,
+            )
+            .await?;
+            if !duplicates.is_empty() {
+                return CreateTrueFalse::duplicate(duplicates);
+            }
+        }
+
+        let statement = challenges_true_false_statements::ActiveModel {
+            subtask_id: Set(subtask.id),
+            statement: Set(data.0.statement),
+            answer: Set(data.0.answer),
+        }
+        .insert(&***db)
+        .await?;
+        set_fingerprint(&db, subtask.id, fingerprint).await?;
+        record_revision(&db, subtask.id, &statement).await?;
+        CreateTrueFalse::ok(TrueFalseWithSolution::from(statement, subtask))
+    }
+
+    /// Update a true/false statement.
+    #[oai(path = "/tasks/:task_id/true_false/:subtask_id", method = "patch")]
+    async fn update_statement(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<UpdateTrueFalseRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> UpdateTrueFalse::Response<AdminAuth> {
+        let (statement, subtask) = match update_subtask::<challenges_true_false_statements::Entity>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            data.0.subtask,
+        )
+        .await?
+        {
+            Ok(x) => x,
+            Err(UpdateSubtaskError::SubtaskNotFound) => {
+                return UpdateTrueFalse::subtask_not_found()
+            }
+            Err(UpdateSubtaskError::TaskNotFound) => return UpdateTrueFalse::task_not_found(),
+            Err(UpdateSubtaskError::AttributionRequired) => {
+                return UpdateTrueFalse::attribution_required()
+            }
+        };
+
+        let statement = challenges_true_false_statements::ActiveModel {
+            subtask_id: Unchanged(statement.subtask_id),
+            statement: data.0.statement.update(statement.statement),
+            answer: data.0.answer.update(statement.answer),
+        }
+        .update(&***db)
+        .await?;
+        set_fingerprint(
+            &db,
+            statement.subtask_id,
+            fingerprint(&statement.statement, []),
+        )
+        .await?;
+        record_revision(&db, statement.subtask_id, &statement).await?;
+
+        UpdateTrueFalse::ok(TrueFalseWithSolution::from(statement, subtask))
+    }
+
+    /// Attempt to solve a true/false statement.
+    #[oai(
+        path = "/tasks/:task_id/true_false/:subtask_id/attempts",
+        method = "post"
+    )]
+    async fn solve_statement(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SolveTrueFalseRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> SolveTrueFalse::Response<VerifiedUserAuth> {
+        let Some((statement, subtask)) =
+            get_subtask::<challenges_true_false_statements::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return SolveTrueFalse::subtask_not_found();
+        };
+        if !can_access_subtask(&db, &auth.0, &subtask).await? {
+            return SolveTrueFalse::subtask_not_found();
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        let solve_kind =
+            user_subtask.solve_kind(&self.config.load().challenges.re_solve, Utc::now());
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let timeout = get_timeout(
+                &db,
+                subtask.task_id,
+                self.config.load().challenges.true_false.timeout,
+            )
+            .await?;
+            let time_left = timeout as i64 - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return SolveTrueFalse::too_many_requests(time_left as u64);
+            }
+        }
+
+        if user_subtask.attempts_exhausted(&subtask) {
+            return SolveTrueFalse::attempts_exhausted();
+        }
+
+        if !deduct_hearts(&self.state.services, &self.config.load(), &auth.0, &subtask).await? {
+            return SolveTrueFalse::not_enough_hearts();
+        }
+
+        let solved = data.0.answer == statement.answer;
+
+        if let Some(exam) = get_active_exam(&db, subtask.task_id).await? {
+            let exam_answer = record_exam_answer(&db, &exam, auth.0.id, subtask.id, solved).await?;
+            return SolveTrueFalse::accepted(exam_answer.into());
+        }
+
+        if let Some(solve_kind) = solve_kind {
+            let now = Utc::now().naive_utc();
+            if solved {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(now)),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        &***db,
+                        Event::SubtaskSolved {
+                            user_id: auth.0.id,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(&***db, &subtask, auth.0.id).await?;
+                }
+
+                if auth.0.id != subtask.creator {
+                    send_task_rewards(
+                        &self.state.services,
+                        &db,
+                        &self.config.load().challenges.due_dates,
+                        &self.config.load().challenges.rewards,
+                        auth.0.id,
+                        &subtask,
+                        solve_kind,
+                        now,
+                    )
+                    .await?;
+                }
+            } else {
+                update_user_subtask(
+                    &db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(auth.0.id),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(now)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+
+            challenges_true_false_attempts::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                statement_id: Set(statement.subtask_id),
+                user_id: Set(auth.0.id),
+                timestamp: Set(now),
+                solved: Set(solved),
+                revision_id: Set(get_current_revision(&db, statement.subtask_id).await?),
+            }
+            .insert(&***db)
+            .await?;
+        }
+
+        SolveTrueFalse::ok(SolveTrueFalseFeedback { solved })
+    }
+}
+
+response!(ListTrueFalse = {
+    Ok(200) => Vec<TrueFalseSummary>,
+});
+
+response!(GetTrueFalse = {
+    Ok(200) => TrueFalse,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// Only admins can request previous revisions.
+    Forbidden(403, error),
+});
+
+response!(GetTrueFalseWithSolution = {
+    Ok(200) => TrueFalseWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// No revision of the subtask has been recorded before `as_of`.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to view the solution to this statement.
+    Forbidden(403, error),
+});
+
+response!(CreateTrueFalse = {
+    Ok(201) => TrueFalseWithSolution,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create true/false statements in this task.
+    Forbidden(403, error),
+    /// The user is currently banned from creating subtasks.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user has exceeded a quota for creating subtasks. Contains the time at which the quota is reset, if it is known.
+    QuotaExceeded(429, error) => Option<DateTime<Utc>>,
+    /// The max xp limit has been exceeded.
+    XpLimitExceeded(403, error) => u64,
+    /// The max coin limit has been exceeded.
+    CoinLimitExceeded(403, error) => u64,
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+    /// Highly similar subtasks already exist in this task. `details` contains the ids of the similar subtasks.
+    Duplicate(409, error) => Vec<Uuid>,
+});
+
+response!(UpdateTrueFalse = {
+    Ok(200) => TrueFalseWithSolution,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The license of the subtask requires an attribution.
+    AttributionRequired(400, error),
+});
+
+response!(SolveTrueFalse = {
+    Ok(201) => SolveTrueFalseFeedback,
+    /// The answer has been recorded for a running exam. Feedback is withheld
+    /// until the results of the exam are released.
+    Accepted(202) => ExamAnswer,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user has used all attempts of this subtask. If the subtask reveals its solution, it can now be requested via the solution endpoint.
+    AttemptsExhausted(403, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+});
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use chrono::{Datelike, Utc};
+use entity::challenges_notification_preferences;
+use fnct::{format::JsonFormatter, key};
+use lib::{
+    auth::{AdminAuth, VerifiedUserAuth},
+    Cache,
+};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::{
+    activity::Activity,
+    notifications::{NotificationPreferences, UpdateNotificationPreferencesRequest},
+    user_data::{DeleteUserDataResponse, UserDataExport},
+};
+use sea_orm::{sea_query::OnConflict, EntityTrait, Set};
+use uuid::Uuid;
+
+use super::Tags;
+use crate::services::{
+    activity::get_activity,
+    notifications::get_notification_preferences,
+    user_data::{delete_user_data, export_user_data},
+};
+
+pub struct Users {
+    pub cache: Cache<JsonFormatter>,
+}
+
+#[OpenApi(tag = "Tags::Users")]
+impl Users {
+    /// Export all personal data the challenges microservice has stored about
+    /// the authenticated user.
+    #[oai(path = "/users/me/data_export", method = "get")]
+    pub async fn export_data(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ExportData::Response<VerifiedUserAuth> {
+        ExportData::ok(export_user_data(&db, auth.0.id).await?)
+    }
+
+    /// Return the number of attempts and solved subtasks of the authenticated
+    /// user on each day of a year.
+    ///
+    /// Only days with at least one attempt or solve and only subtasks of tasks
+    /// visible in the selected organization are included. The data is cached
+    /// for up to one minute.
+    #[oai(path = "/users/me/activity", method = "get")]
+    pub async fn get_activity(
+        &self,
+        /// Defaults to the current year.
+        #[oai(validator(minimum(value = "1970"), maximum(value = "9999")))]
+        year: Query<Option<i32>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetActivity::Response<VerifiedUserAuth> {
+        let year = year.0.unwrap_or_else(|| Utc::now().year());
+        let activity = self
+            .cache
+            .cached_result(
+                key!(auth.0.id, auth.0.organization_id(), year),
+                &[],
+                Some(Duration::from_secs(60)),
+                || get_activity(&db, &auth.0, year),
+            )
+            .await??;
+        GetActivity::ok(activity)
+    }
+
+    /// Return the notification preferences of the authenticated user.
+    #[oai(path = "/users/me/notification_preferences", method = "get")]
+    pub async fn get_notification_preferences(
+        &self,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetNotificationPreferences::Response<VerifiedUserAuth> {
+        GetNotificationPreferences::ok(
+            get_notification_preferences(&***db, auth.0.id)
+                .await?
+                .into(),
+        )
+    }
+
+    /// Update the notification preferences of the authenticated user.
+    #[oai(path = "/users/me/notification_preferences", method = "patch")]
+    pub async fn update_notification_preferences(
+        &self,
+        data: Json<UpdateNotificationPreferencesRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> UpdateNotificationPreferences::Response<VerifiedUserAuth> {
+        let preferences = get_notification_preferences(&***db, auth.0.id).await?;
+        let preferences = challenges_notification_preferences::ActiveModel {
+            user_id: Set(preferences.user_id),
+            subtask_solved: data.0.subtask_solved.update(preferences.subtask_solved),
+            subtask_reported: data.0.subtask_reported.update(preferences.subtask_reported),
+            subtask_disabled: data.0.subtask_disabled.update(preferences.subtask_disabled),
+            submission_judged: data
+                .0
+                .submission_judged
+                .update(preferences.submission_judged),
+            contest_starting: data.0.contest_starting.update(preferences.contest_starting),
+            creator_digest: data.0.creator_digest.update(preferences.creator_digest),
+        };
+        let preferences = challenges_notification_preferences::Entity::insert(preferences)
+            .on_conflict(
+                OnConflict::column(challenges_notification_preferences::Column::UserId)
+                    .update_columns([
+                        challenges_notification_preferences::Column::SubtaskSolved,
+                        challenges_notification_preferences::Column::SubtaskReported,
+                        challenges_notification_preferences::Column::SubtaskDisabled,
+                        challenges_notification_preferences::Column::SubmissionJudged,
+                        challenges_notification_preferences::Column::ContestStarting,
+                        challenges_notification_preferences::Column::CreatorDigest,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(&***db)
+            .await?;
+        UpdateNotificationPreferences::ok(preferences.into())
+    }
+
+    /// Delete or anonymize all personal data of a user.
+    ///
+    /// Progress, attempts, submissions, sessions, exam answers, cohort
+    /// memberships, anti-cheat flags, held rewards, reviews, unlocks,
+    /// bookmarks, notes, bans and notification preferences of the user are
+    /// deleted. Content created by the user and reports are kept, but
+    /// the user id is removed.
+    #[oai(path = "/users/:user_id/data", method = "delete")]
+    pub async fn delete_data(
+        &self,
+        user_id: Path<Uuid>,
+        /// Only report the number of affected rows without modifying any data.
+        dry_run: Query<Option<bool>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> DeleteData::Response<AdminAuth> {
+        let dry_run = dry_run.0.unwrap_or(false);
+        DeleteData::ok(DeleteUserDataResponse {
+            dry_run,
+            affected: delete_user_data(&db, user_id.0, dry_run).await?,
+        })
+    }
+}
+
+response!(ExportData = {
+    Ok(200) => UserDataExport,
+});
+
+response!(GetActivity = {
+    Ok(200) => Activity,
+});
+
+response!(GetNotificationPreferences = {
+    Ok(200) => NotificationPreferences,
+});
+
+response!(UpdateNotificationPreferences = {
+    Ok(200) => NotificationPreferences,
+});
+
+response!(DeleteData = {
+    Ok(200) => DeleteUserDataResponse,
+});
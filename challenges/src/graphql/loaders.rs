@@ -0,0 +1,321 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use entity::{
+    challenges_challenges, challenges_cloze_attempts, challenges_clozes,
+    challenges_code_reading_attempts, challenges_code_readings, challenges_coding_challenge_result,
+    challenges_coding_challenge_submissions, challenges_coding_challenges, challenges_course_tasks,
+    challenges_matching_attempts, challenges_matchings, challenges_multiple_choice_attempts,
+    challenges_multiple_choice_quizes, challenges_ordering_attempts, challenges_orderings,
+    challenges_question_attempts, challenges_questions, challenges_sql_attempts,
+    challenges_sql_challenges, challenges_true_false_attempts, challenges_true_false_statements,
+    challenges_user_subtasks,
+};
+use poem_ext::db::DbTxn;
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+/// Loads at most one model per key.
+pub struct ModelLoader<E: EntityTrait> {
+    db: DbTxn,
+    column: E::Column,
+    key: fn(&E::Model) -> Uuid,
+}
+
+impl<E> Loader<Uuid> for ModelLoader<E>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    type Value = E::Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        Ok(E::find()
+            .filter(self.column.is_in(keys.iter().copied()))
+            .all(&*self.db)
+            .await?
+            .into_iter()
+            .map(|model| ((self.key)(&model), model))
+            .collect())
+    }
+}
+
+/// Loads all models of the authenticated user per key, ordered by `order`.
+pub struct UserModelsLoader<E: EntityTrait> {
+    db: DbTxn,
+    user_id: Uuid,
+    column: E::Column,
+    user_column: E::Column,
+    order: E::Column,
+    key: fn(&E::Model) -> Uuid,
+}
+
+impl<E> Loader<Uuid> for UserModelsLoader<E>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    type Value = Vec<E::Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let mut out = HashMap::<_, Vec<_>>::new();
+        for model in E::find()
+            .filter(self.column.is_in(keys.iter().copied()))
+            .filter(self.user_column.eq(self.user_id))
+            .order_by_asc(self.order)
+            .all(&*self.db)
+            .await?
+        {
+            out.entry((self.key)(&model)).or_default().push(model);
+        }
+        Ok(out)
+    }
+}
+
+pub type ChallengeLoader = DataLoader<ModelLoader<challenges_challenges::Entity>>;
+pub type CourseTaskLoader = DataLoader<ModelLoader<challenges_course_tasks::Entity>>;
+pub type MultipleChoiceLoader = DataLoader<ModelLoader<challenges_multiple_choice_quizes::Entity>>;
+pub type QuestionLoader = DataLoader<ModelLoader<challenges_questions::Entity>>;
+pub type MatchingLoader = DataLoader<ModelLoader<challenges_matchings::Entity>>;
+pub type TrueFalseLoader = DataLoader<ModelLoader<challenges_true_false_statements::Entity>>;
+pub type OrderingLoader = DataLoader<ModelLoader<challenges_orderings::Entity>>;
+pub type ClozeLoader = DataLoader<ModelLoader<challenges_clozes::Entity>>;
+pub type CodeReadingLoader = DataLoader<ModelLoader<challenges_code_readings::Entity>>;
+pub type SqlChallengeLoader = DataLoader<ModelLoader<challenges_sql_challenges::Entity>>;
+pub type CodingChallengeLoader = DataLoader<ModelLoader<challenges_coding_challenges::Entity>>;
+pub type SubmissionResultLoader =
+    DataLoader<ModelLoader<challenges_coding_challenge_result::Entity>>;
+pub type UserSubtaskLoader = DataLoader<UserModelsLoader<challenges_user_subtasks::Entity>>;
+pub type MultipleChoiceAttemptsLoader =
+    DataLoader<UserModelsLoader<challenges_multiple_choice_attempts::Entity>>;
+pub type QuestionAttemptsLoader =
+    DataLoader<UserModelsLoader<challenges_question_attempts::Entity>>;
+pub type MatchingAttemptsLoader =
+    DataLoader<UserModelsLoader<challenges_matching_attempts::Entity>>;
+pub type TrueFalseAttemptsLoader =
+    DataLoader<UserModelsLoader<challenges_true_false_attempts::Entity>>;
+pub type OrderingAttemptsLoader =
+    DataLoader<UserModelsLoader<challenges_ordering_attempts::Entity>>;
+pub type ClozeAttemptsLoader = DataLoader<UserModelsLoader<challenges_cloze_attempts::Entity>>;
+pub type CodeReadingAttemptsLoader =
+    DataLoader<UserModelsLoader<challenges_code_reading_attempts::Entity>>;
+pub type SqlAttemptsLoader = DataLoader<UserModelsLoader<challenges_sql_attempts::Entity>>;
+pub type SubmissionsLoader =
+    DataLoader<UserModelsLoader<challenges_coding_challenge_submissions::Entity>>;
+
+/// Add all dataloaders of a single request to the graphql request data.
+pub fn add_loaders(
+    request: async_graphql::Request,
+    db: DbTxn,
+    user_id: Uuid,
+) -> async_graphql::Request {
+    request
+        .data::<ChallengeLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_challenges::Column::TaskId,
+                key: |model| model.task_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<CourseTaskLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_course_tasks::Column::TaskId,
+                key: |model| model.task_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<MultipleChoiceLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_multiple_choice_quizes::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<QuestionLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_questions::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<MatchingLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_matchings::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<TrueFalseLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_true_false_statements::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<OrderingLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_orderings::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<ClozeLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_clozes::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<CodeReadingLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_code_readings::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<SqlChallengeLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_sql_challenges::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<CodingChallengeLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_coding_challenges::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<SubmissionResultLoader>(DataLoader::new(
+            ModelLoader {
+                db: Arc::clone(&db),
+                column: challenges_coding_challenge_result::Column::SubmissionId,
+                key: |model| model.submission_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<UserSubtaskLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_user_subtasks::Column::SubtaskId,
+                user_column: challenges_user_subtasks::Column::UserId,
+                order: challenges_user_subtasks::Column::SubtaskId,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<MultipleChoiceAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_multiple_choice_attempts::Column::QuestionId,
+                user_column: challenges_multiple_choice_attempts::Column::UserId,
+                order: challenges_multiple_choice_attempts::Column::Timestamp,
+                key: |model| model.question_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<QuestionAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_question_attempts::Column::QuestionId,
+                user_column: challenges_question_attempts::Column::UserId,
+                order: challenges_question_attempts::Column::Timestamp,
+                key: |model| model.question_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<MatchingAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_matching_attempts::Column::MatchingId,
+                user_column: challenges_matching_attempts::Column::UserId,
+                order: challenges_matching_attempts::Column::Timestamp,
+                key: |model| model.matching_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<TrueFalseAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_true_false_attempts::Column::StatementId,
+                user_column: challenges_true_false_attempts::Column::UserId,
+                order: challenges_true_false_attempts::Column::Timestamp,
+                key: |model| model.statement_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<OrderingAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_ordering_attempts::Column::OrderingId,
+                user_column: challenges_ordering_attempts::Column::UserId,
+                order: challenges_ordering_attempts::Column::Timestamp,
+                key: |model| model.ordering_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<ClozeAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_cloze_attempts::Column::ClozeId,
+                user_column: challenges_cloze_attempts::Column::UserId,
+                order: challenges_cloze_attempts::Column::Timestamp,
+                key: |model| model.cloze_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<CodeReadingAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_code_reading_attempts::Column::CodeReadingId,
+                user_column: challenges_code_reading_attempts::Column::UserId,
+                order: challenges_code_reading_attempts::Column::Timestamp,
+                key: |model| model.code_reading_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<SqlAttemptsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db: Arc::clone(&db),
+                user_id,
+                column: challenges_sql_attempts::Column::SqlChallengeId,
+                user_column: challenges_sql_attempts::Column::UserId,
+                order: challenges_sql_attempts::Column::Timestamp,
+                key: |model| model.sql_challenge_id,
+            },
+            tokio::spawn,
+        ))
+        .data::<SubmissionsLoader>(DataLoader::new(
+            UserModelsLoader {
+                db,
+                user_id,
+                column: challenges_coding_challenge_submissions::Column::SubtaskId,
+                user_column: challenges_coding_challenge_submissions::Column::Creator,
+                order: challenges_coding_challenge_submissions::Column::CreationTimestamp,
+                key: |model| model.subtask_id,
+            },
+            tokio::spawn,
+        ))
+}
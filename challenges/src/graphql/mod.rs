@@ -0,0 +1,121 @@
+//! Read-only GraphQL endpoint which allows clients to fetch tasks, subtasks,
+//! attempts and submissions in a single request.
+
+use std::sync::Arc;
+
+use async_graphql::{
+    http::GraphiQLSource, Context, EmptyMutation, EmptySubscription, Object, Result, Schema,
+};
+use async_graphql_poem::{GraphQLRequest, GraphQLResponse};
+use entity::{challenges_challenges, challenges_subtasks, challenges_tasks};
+use lib::{
+    auth::{authenticate, User},
+    config::Config,
+};
+use poem::{
+    handler,
+    web::{Data, Html},
+    EndpointExt, IntoResponse, Request, RouteMethod,
+};
+use poem_ext::db::DbTxn;
+use schemas::challenges::subtasks::Subtask as SubtaskSchema;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+use self::types::{Subtask, Task};
+use crate::services::{
+    organizations::{can_see_task, task_scope},
+    subtasks::{can_access_subtask, get_user_subtask, UserSubtaskExt},
+};
+
+mod loaders;
+mod types;
+
+pub type ChallengesSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// Create the graphql endpoint if it is enabled in the config.
+pub fn endpoint(config: &Config) -> Option<RouteMethod> {
+    let config = config.challenges.graphql.as_ref()?;
+    let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(config.max_depth)
+        .limit_complexity(config.max_complexity)
+        .finish();
+    let mut route = RouteMethod::new().post(graphql.data(schema));
+    if config.playground {
+        route = route.get(graphiql);
+    }
+    Some(route)
+}
+
+#[handler]
+async fn graphql(
+    schema: Data<&ChallengesSchema>,
+    db: Data<&DbTxn>,
+    req: &Request,
+    request: GraphQLRequest,
+) -> poem::Response {
+    let Some(user) = authenticate(req).await else {
+        return poem::http::StatusCode::UNAUTHORIZED.into_response();
+    };
+    let db = Arc::clone(&db);
+    let request = loaders::add_loaders(request.0, Arc::clone(&db), user.id)
+        .data(db)
+        .data(user);
+    GraphQLResponse::from(schema.execute(request).await).into_response()
+}
+
+#[handler]
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Get a task by id.
+    async fn task(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Task>> {
+        let db = ctx.data_unchecked::<DbTxn>();
+        let user = ctx.data::<User>()?;
+        Ok(challenges_tasks::Entity::find_by_id(id)
+            .one(&**db)
+            .await?
+            .filter(|task| can_see_task(user, task))
+            .map(Task))
+    }
+
+    /// List all global challenges, optionally filtered by category.
+    async fn challenges(&self, ctx: &Context<'_>, category_id: Option<Uuid>) -> Result<Vec<Task>> {
+        let db = ctx.data_unchecked::<DbTxn>();
+        let user = ctx.data::<User>()?;
+        let mut query = challenges_tasks::Entity::find()
+            .inner_join(challenges_challenges::Entity)
+            .filter(task_scope(user))
+            .order_by_asc(challenges_tasks::Column::CreationTimestamp);
+        if let Some(category_id) = category_id {
+            query = query.filter(challenges_challenges::Column::CategoryId.eq(category_id));
+        }
+        Ok(query.all(&**db).await?.into_iter().map(Task).collect())
+    }
+
+    /// Get a subtask by id.
+    async fn subtask(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Subtask>> {
+        let db = ctx.data_unchecked::<DbTxn>();
+        let user = ctx.data::<User>()?;
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(id)
+            .one(&**db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if !can_access_subtask(db, user, &subtask).await? {
+            return Ok(None);
+        }
+        let user_subtask = get_user_subtask(db, user.id, subtask.id).await?;
+        Ok(Some(Subtask(SubtaskSchema::from(
+            subtask,
+            user_subtask.is_solved(),
+            user_subtask.is_rated(),
+        ))))
+    }
+}
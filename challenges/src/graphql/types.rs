@@ -0,0 +1,633 @@
+use async_graphql::{Context, Object, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_challenges, challenges_clozes, challenges_code_readings,
+    challenges_coding_challenge_submissions, challenges_coding_challenges, challenges_course_tasks,
+    challenges_matchings, challenges_multiple_choice_quizes, challenges_orderings,
+    challenges_questions, challenges_sql_challenges, challenges_tasks,
+    challenges_true_false_statements, challenges_user_subtasks,
+    sea_orm_active_enums::{
+        ChallengesFeedbackLevel, ChallengesLicense, ChallengesRating, ChallengesSubtaskType,
+        ChallengesVerdict,
+    },
+};
+use lib::auth::User;
+use poem_ext::db::DbTxn;
+use schemas::challenges::subtasks::Subtask as SubtaskSchema;
+use uuid::Uuid;
+
+use super::loaders::{
+    ChallengeLoader, ClozeAttemptsLoader, ClozeLoader, CodeReadingAttemptsLoader,
+    CodeReadingLoader, CodingChallengeLoader, CourseTaskLoader, MatchingAttemptsLoader,
+    MatchingLoader, MultipleChoiceAttemptsLoader, MultipleChoiceLoader, OrderingAttemptsLoader,
+    OrderingLoader, QuestionAttemptsLoader, QuestionLoader, SqlAttemptsLoader, SqlChallengeLoader,
+    SubmissionResultLoader, SubmissionsLoader, TrueFalseAttemptsLoader, TrueFalseLoader,
+    UserSubtaskLoader,
+};
+use crate::services::{
+    judge::feedback_level,
+    subtasks::{query_subtasks_only, QuerySubtasksFilter},
+};
+
+pub struct Task(pub challenges_tasks::Model);
+
+#[Object]
+impl Task {
+    /// The unique identifier of the task.
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// The creator of the task.
+    async fn creator(&self) -> Uuid {
+        self.0.creator
+    }
+
+    /// The creation timestamp of the task.
+    async fn creation_timestamp(&self) -> DateTime<Utc> {
+        self.0.creation_timestamp.and_utc()
+    }
+
+    /// The challenge details if this task is a global challenge.
+    async fn challenge(&self, ctx: &Context<'_>) -> Result<Option<Challenge>> {
+        let loader = ctx.data_unchecked::<ChallengeLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The course details if this task belongs to a course.
+    async fn course_task(&self, ctx: &Context<'_>) -> Result<Option<CourseTask>> {
+        let loader = ctx.data_unchecked::<CourseTaskLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The subtasks of this task that are visible to the user.
+    async fn subtasks(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "type")] ty: Option<ChallengesSubtaskType>,
+        solved: Option<bool>,
+        attempted: Option<bool>,
+    ) -> Result<Vec<Subtask>> {
+        let db = ctx.data_unchecked::<DbTxn>();
+        let user = ctx.data::<User>()?;
+        Ok(query_subtasks_only(
+            db,
+            user,
+            Some(self.0.id),
+            QuerySubtasksFilter {
+                ty,
+                solved,
+                attempted,
+                ..Default::default()
+            },
+        )
+        .await?
+        .into_iter()
+        .map(Subtask)
+        .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Challenge {
+    /// The category of the challenge.
+    pub category_id: Uuid,
+    /// The skills of the challenge.
+    pub skill_ids: Vec<String>,
+    /// The title of the challenge.
+    pub title: String,
+    /// The description of the challenge.
+    pub description: String,
+}
+
+impl From<challenges_challenges::Model> for Challenge {
+    fn from(value: challenges_challenges::Model) -> Self {
+        Self {
+            category_id: value.category_id,
+            skill_ids: value.skill_ids,
+            title: value.title,
+            description: value.description,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CourseTask {
+    /// The course this task belongs to.
+    pub course_id: String,
+    /// The section this task belongs to.
+    pub section_id: Option<String>,
+    /// The lecture this task belongs to.
+    pub lecture_id: Option<String>,
+}
+
+impl From<challenges_course_tasks::Model> for CourseTask {
+    fn from(value: challenges_course_tasks::Model) -> Self {
+        Self {
+            course_id: value.course_id,
+            section_id: value.section_id,
+            lecture_id: value.lecture_id,
+        }
+    }
+}
+
+pub struct Subtask(pub SubtaskSchema);
+
+#[Object]
+impl Subtask {
+    /// The unique identifier of the subtask.
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// The parent task.
+    async fn task_id(&self) -> Uuid {
+        self.0.task_id
+    }
+
+    /// The type of the subtask.
+    #[graphql(name = "type")]
+    async fn ty(&self) -> ChallengesSubtaskType {
+        self.0.ty
+    }
+
+    /// The creator of the subtask.
+    async fn creator(&self) -> Uuid {
+        self.0.creator
+    }
+
+    /// The creation timestamp of the subtask.
+    async fn creation_timestamp(&self) -> DateTime<Utc> {
+        self.0.creation_timestamp
+    }
+
+    /// The number of xp a user gets for completing this subtask.
+    async fn xp(&self) -> u64 {
+        self.0.xp
+    }
+
+    /// The number of morphcoins a user gets for completing this subtask.
+    async fn coins(&self) -> u64 {
+        self.0.coins
+    }
+
+    /// Whether the user has completed this subtask.
+    async fn solved(&self) -> bool {
+        self.0.solved
+    }
+
+    /// Whether the user has submitted feedback or reported this subtask.
+    async fn rated(&self) -> bool {
+        self.0.rated
+    }
+
+    /// Whether the subtask is enabled and visible to normal users.
+    async fn enabled(&self) -> bool {
+        self.0.enabled
+    }
+
+    /// Whether the subtask is retired.
+    async fn retired(&self) -> bool {
+        self.0.retired
+    }
+
+    /// The license of the content of the subtask.
+    async fn license(&self) -> ChallengesLicense {
+        self.0.license
+    }
+
+    /// The original authors and source of the content.
+    async fn attribution(&self) -> Option<&str> {
+        self.0.attribution.as_deref()
+    }
+
+    /// The maximum number of attempts per user.
+    async fn max_attempts(&self) -> Option<u32> {
+        self.0.max_attempts
+    }
+
+    /// The progress of the user on this subtask.
+    async fn progress(&self, ctx: &Context<'_>) -> Result<Option<UserSubtask>> {
+        let loader = ctx.data_unchecked::<UserSubtaskLoader>();
+        Ok(loader
+            .load_one(self.0.id)
+            .await?
+            .and_then(|x| x.into_iter().next())
+            .map(Into::into))
+    }
+
+    /// The attempts of the user to solve this subtask (not available for
+    /// coding challenges, use `submissions` instead).
+    async fn attempts(&self, ctx: &Context<'_>) -> Result<Vec<Attempt>> {
+        macro_rules! load {
+            ($loader:ty) => {
+                ctx.data_unchecked::<$loader>()
+                    .load_one(self.0.id)
+                    .await?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| Attempt {
+                        id: x.id,
+                        timestamp: x.timestamp.and_utc(),
+                        solved: x.solved,
+                        revision_id: x.revision_id,
+                    })
+                    .collect()
+            };
+        }
+
+        Ok(match self.0.ty {
+            ChallengesSubtaskType::MultipleChoiceQuestion => load!(MultipleChoiceAttemptsLoader),
+            ChallengesSubtaskType::Question => load!(QuestionAttemptsLoader),
+            ChallengesSubtaskType::Matching => load!(MatchingAttemptsLoader),
+            ChallengesSubtaskType::TrueFalse => load!(TrueFalseAttemptsLoader),
+            ChallengesSubtaskType::Ordering => load!(OrderingAttemptsLoader),
+            ChallengesSubtaskType::Cloze => load!(ClozeAttemptsLoader),
+            ChallengesSubtaskType::CodeReading => load!(CodeReadingAttemptsLoader),
+            ChallengesSubtaskType::SqlChallenge => load!(SqlAttemptsLoader),
+            ChallengesSubtaskType::CodingChallenge => Vec::new(),
+        })
+    }
+
+    /// The submissions of the user for this coding challenge.
+    async fn submissions(&self, ctx: &Context<'_>) -> Result<Vec<Submission>> {
+        if self.0.ty != ChallengesSubtaskType::CodingChallenge {
+            return Ok(Vec::new());
+        }
+        let Some(cc) = ctx
+            .data_unchecked::<CodingChallengeLoader>()
+            .load_one(self.0.id)
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+        let feedback_level = feedback_level(ctx.data::<User>()?, self.0.creator, &cc);
+        let loader = ctx.data_unchecked::<SubmissionsLoader>();
+        Ok(loader
+            .load_one(self.0.id)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| Submission(x, feedback_level))
+            .collect())
+    }
+
+    /// The multiple choice question if this subtask is one.
+    async fn multiple_choice(&self, ctx: &Context<'_>) -> Result<Option<MultipleChoice>> {
+        if self.0.ty != ChallengesSubtaskType::MultipleChoiceQuestion {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<MultipleChoiceLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The question if this subtask is one.
+    async fn question(&self, ctx: &Context<'_>) -> Result<Option<Question>> {
+        if self.0.ty != ChallengesSubtaskType::Question {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<QuestionLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The matching if this subtask is one.
+    async fn matching(&self, ctx: &Context<'_>) -> Result<Option<Matching>> {
+        if self.0.ty != ChallengesSubtaskType::Matching {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<MatchingLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The true/false statement if this subtask is one.
+    async fn true_false(&self, ctx: &Context<'_>) -> Result<Option<TrueFalse>> {
+        if self.0.ty != ChallengesSubtaskType::TrueFalse {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<TrueFalseLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The ordering if this subtask is one.
+    async fn ordering(&self, ctx: &Context<'_>) -> Result<Option<Ordering>> {
+        if self.0.ty != ChallengesSubtaskType::Ordering {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<OrderingLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The cloze if this subtask is one.
+    async fn cloze(&self, ctx: &Context<'_>) -> Result<Option<Cloze>> {
+        if self.0.ty != ChallengesSubtaskType::Cloze {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<ClozeLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The code reading question if this subtask is one.
+    async fn code_reading(&self, ctx: &Context<'_>) -> Result<Option<CodeReading>> {
+        if self.0.ty != ChallengesSubtaskType::CodeReading {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<CodeReadingLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The sql challenge if this subtask is one.
+    async fn sql_challenge(&self, ctx: &Context<'_>) -> Result<Option<SqlChallenge>> {
+        if self.0.ty != ChallengesSubtaskType::SqlChallenge {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<SqlChallengeLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+
+    /// The coding challenge if this subtask is one.
+    async fn coding_challenge(&self, ctx: &Context<'_>) -> Result<Option<CodingChallenge>> {
+        if self.0.ty != ChallengesSubtaskType::CodingChallenge {
+            return Ok(None);
+        }
+        let loader = ctx.data_unchecked::<CodingChallengeLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(Into::into))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct UserSubtask {
+    /// The timestamp of the first successful attempt.
+    pub solved_timestamp: Option<DateTime<Utc>>,
+    /// The timestamp of the last attempt.
+    pub last_attempt_timestamp: Option<DateTime<Utc>>,
+    /// The number of attempts.
+    pub attempts: i32,
+    /// The rating the user has submitted.
+    pub rating: Option<ChallengesRating>,
+}
+
+impl From<challenges_user_subtasks::Model> for UserSubtask {
+    fn from(value: challenges_user_subtasks::Model) -> Self {
+        Self {
+            solved_timestamp: value.solved_timestamp.map(|x| x.and_utc()),
+            last_attempt_timestamp: value.last_attempt_timestamp.map(|x| x.and_utc()),
+            attempts: value.attempts,
+            rating: value.rating,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Attempt {
+    /// The unique identifier of the attempt.
+    pub id: Uuid,
+    /// The timestamp of the attempt.
+    pub timestamp: DateTime<Utc>,
+    /// Whether the attempt was successful.
+    pub solved: bool,
+    /// The revision of the subtask at the time of the attempt.
+    pub revision_id: Option<Uuid>,
+}
+
+pub struct Submission(
+    pub challenges_coding_challenge_submissions::Model,
+    pub ChallengesFeedbackLevel,
+);
+
+#[Object]
+impl Submission {
+    /// The unique identifier of the submission.
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// The creation timestamp of the submission.
+    async fn creation_timestamp(&self) -> DateTime<Utc> {
+        self.0.creation_timestamp.and_utc()
+    }
+
+    /// The environment the solution was written in.
+    async fn environment(&self) -> &str {
+        &self.0.environment
+    }
+
+    /// The submitted code.
+    async fn code(&self) -> &str {
+        &self.0.code
+    }
+
+    /// The revision of the coding challenge at the time of the submission.
+    async fn revision_id(&self) -> Option<Uuid> {
+        self.0.revision_id
+    }
+
+    /// The result of the submission. `null` if the submission is still being
+    /// judged.
+    async fn result(&self, ctx: &Context<'_>) -> Result<Option<SubmissionResult>> {
+        let loader = ctx.data_unchecked::<SubmissionResultLoader>();
+        Ok(loader.load_one(self.0.id).await?.map(|x| SubmissionResult {
+            verdict: x.verdict,
+            reason: x
+                .reason
+                .filter(|_| self.1 == ChallengesFeedbackLevel::FullDiff),
+            run_time: x.run_time,
+            run_memory: x.run_memory,
+        }))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct SubmissionResult {
+    /// The verdict of the submission.
+    pub verdict: ChallengesVerdict,
+    /// More details about the verdict, if the feedback level of the
+    /// challenge reveals them.
+    pub reason: Option<String>,
+    /// The run time of the solution in milliseconds.
+    pub run_time: Option<i32>,
+    /// The memory usage of the solution in kilobytes.
+    pub run_memory: Option<i32>,
+}
+
+#[derive(SimpleObject)]
+pub struct MultipleChoice {
+    /// The question text.
+    pub question: String,
+    /// The possible answers.
+    pub answers: Vec<String>,
+    /// Whether only one answer is correct.
+    pub single_choice: bool,
+}
+
+impl From<challenges_multiple_choice_quizes::Model> for MultipleChoice {
+    fn from(value: challenges_multiple_choice_quizes::Model) -> Self {
+        Self {
+            question: value.question,
+            answers: value.answers,
+            single_choice: value.single_choice,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Question {
+    /// The question text.
+    pub question: String,
+    /// Whether the answer is case sensitive.
+    pub case_sensitive: bool,
+    /// Whether the answer contains ascii letters.
+    pub ascii_letters: bool,
+    /// Whether the answer contains digits.
+    pub digits: bool,
+    /// Whether the answer contains punctuation.
+    pub punctuation: bool,
+    /// The blocks of the answer.
+    pub blocks: Vec<String>,
+}
+
+impl From<challenges_questions::Model> for Question {
+    fn from(value: challenges_questions::Model) -> Self {
+        Self {
+            question: value.question,
+            case_sensitive: value.case_sensitive,
+            ascii_letters: value.ascii_letters,
+            digits: value.digits,
+            punctuation: value.punctuation,
+            blocks: value.blocks,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Matching {
+    /// The left side of the matching.
+    pub left: Vec<String>,
+    /// The right side of the matching.
+    pub right: Vec<String>,
+}
+
+impl From<challenges_matchings::Model> for Matching {
+    fn from(value: challenges_matchings::Model) -> Self {
+        Self {
+            left: value.left,
+            right: value.right,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TrueFalse {
+    /// The statement that is either true or false.
+    pub statement: String,
+}
+
+impl From<challenges_true_false_statements::Model> for TrueFalse {
+    fn from(value: challenges_true_false_statements::Model) -> Self {
+        Self {
+            statement: value.statement,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Ordering {
+    /// The question text.
+    pub question: String,
+    /// The items that have to be put in the correct order.
+    pub items: Vec<String>,
+}
+
+impl From<challenges_orderings::Model> for Ordering {
+    fn from(value: challenges_orderings::Model) -> Self {
+        Self {
+            question: value.question,
+            items: value.items,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Cloze {
+    /// The text of the cloze. Each blank is marked with `{{}}`.
+    pub text: String,
+    /// The number of blanks in the text.
+    pub blanks: i32,
+}
+
+impl From<challenges_clozes::Model> for Cloze {
+    fn from(value: challenges_clozes::Model) -> Self {
+        Self {
+            text: value.text,
+            blanks: value.blanks.0.len() as _,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CodeReading {
+    /// The environment the program is written for.
+    pub environment: String,
+    /// The program whose output has to be predicted.
+    pub code: String,
+}
+
+impl From<challenges_code_readings::Model> for CodeReading {
+    fn from(value: challenges_code_readings::Model) -> Self {
+        Self {
+            environment: value.environment,
+            code: value.code,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct SqlChallenge {
+    /// The description of the sql challenge.
+    pub description: String,
+    /// The statements that create the tables of the database.
+    pub schema: String,
+    /// The statements that fill the database with data.
+    pub seed: String,
+    /// Whether the order of the rows matters.
+    pub ordered: bool,
+}
+
+impl From<challenges_sql_challenges::Model> for SqlChallenge {
+    fn from(value: challenges_sql_challenges::Model) -> Self {
+        Self {
+            description: value.description,
+            schema: value.schema,
+            seed: value.seed,
+            ordered: value.ordered,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CodingChallenge {
+    /// The problem description.
+    pub description: String,
+    /// The time limit in milliseconds.
+    pub time_limit: i64,
+    /// The memory limit in megabytes.
+    pub memory_limit: i64,
+    /// The number of static tests.
+    pub static_tests: i32,
+    /// The number of random tests.
+    pub random_tests: i32,
+    /// How much information the results of submissions reveal.
+    pub feedback_level: ChallengesFeedbackLevel,
+}
+
+impl From<challenges_coding_challenges::Model> for CodingChallenge {
+    fn from(value: challenges_coding_challenges::Model) -> Self {
+        Self {
+            description: value.description,
+            time_limit: value.time_limit,
+            memory_limit: value.memory_limit,
+            static_tests: value.static_tests,
+            random_tests: value.random_tests,
+            feedback_level: value.feedback_level,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Internal gRPC api which allows other microservices to integrate with the
+//! challenges microservice without going through the public REST api.
+
+use std::{collections::HashMap, sync::Arc};
+
+use entity::challenges_subtasks;
+use lib::{
+    auth::User,
+    jwt::{verify_jwt, InternalAuthToken, JwtSecret},
+    SharedState,
+};
+use proto::challenges::{
+    challenges_internal_server::{ChallengesInternal, ChallengesInternalServer},
+    GetUserChallengeStatsRequest, GrantSubtaskAccessRequest, GrantSubtaskAccessResponse,
+    InvalidateSubtaskRequest, InvalidateSubtaskResponse, UserChallengeStats,
+};
+use sea_orm::{ActiveModelTrait, DbErr, EntityTrait, Set, TransactionTrait, Unchanged};
+use tonic::{
+    service::{interceptor::InterceptedService, Interceptor},
+    Request, Response, Status,
+};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::services::{
+    notifications::notify_subtask_disabled,
+    subtasks::{
+        get_user_subtasks, grant_subtask_access, stat_subtasks, stat_subtasks_prepare,
+        QuerySubtasksFilter,
+    },
+};
+
+/// Audience of internal auth tokens that are accepted by this api.
+const AUDIENCE: &str = "challenges";
+
+pub fn service(
+    state: Arc<SharedState>,
+) -> InterceptedService<ChallengesInternalServer<InternalApi>, AuthInterceptor> {
+    let secret = state.jwt_secret.clone();
+    ChallengesInternalServer::with_interceptor(InternalApi { state }, AuthInterceptor { secret })
+}
+
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    secret: JwtSecret,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing internal auth token"))?;
+        match verify_jwt::<InternalAuthToken>(token, &self.secret) {
+            Ok(data) if data.aud == AUDIENCE => Ok(request),
+            _ => Err(Status::unauthenticated("invalid internal auth token")),
+        }
+    }
+}
+
+pub struct InternalApi {
+    state: Arc<SharedState>,
+}
+
+#[tonic::async_trait]
+impl ChallengesInternal for InternalApi {
+    async fn get_user_challenge_stats(
+        &self,
+        request: Request<GetUserChallengeStatsRequest>,
+    ) -> Result<Response<UserChallengeStats>, Status> {
+        let user = User {
+            id: parse_uuid(&request.get_ref().user_id, "user_id")?,
+            email_verified: true,
+            admin: false,
+            organization: None,
+        };
+        let filter = QuerySubtasksFilter {
+            enabled: Some(true),
+            ..Default::default()
+        };
+
+        let txn = self.state.db.begin().await.map_err(internal_error)?;
+        let user_subtasks = get_user_subtasks(&txn, user.id)
+            .await
+            .map_err(internal_error)?;
+        let subtasks = stat_subtasks_prepare(&txn, &user, None, &filter)
+            .await
+            .map_err(internal_error)?;
+        txn.commit().await.map_err(internal_error)?;
+
+        // the time to solve is not part of the response
+        let stats = stat_subtasks(&subtasks, &user_subtasks, &HashMap::new(), filter);
+        Ok(Response::new(UserChallengeStats {
+            total: stats.total,
+            solved: stats.solved,
+            attempted: stats.attempted,
+            unattempted: stats.unattempted,
+        }))
+    }
+
+    async fn grant_subtask_access(
+        &self,
+        request: Request<GrantSubtaskAccessRequest>,
+    ) -> Result<Response<GrantSubtaskAccessResponse>, Status> {
+        let request = request.get_ref();
+        let user_id = parse_uuid(&request.user_id, "user_id")?;
+        let subtask_id = parse_uuid(&request.subtask_id, "subtask_id")?;
+
+        let txn = self.state.db.begin().await.map_err(internal_error)?;
+        if challenges_subtasks::Entity::find_by_id(subtask_id)
+            .one(&txn)
+            .await
+            .map_err(internal_error)?
+            .is_none()
+        {
+            return Err(Status::not_found("subtask does not exist"));
+        }
+        let granted = grant_subtask_access(&txn, user_id, subtask_id)
+            .await
+            .map_err(internal_error)?;
+        txn.commit().await.map_err(internal_error)?;
+
+        Ok(Response::new(GrantSubtaskAccessResponse { granted }))
+    }
+
+    async fn invalidate_subtask(
+        &self,
+        request: Request<InvalidateSubtaskRequest>,
+    ) -> Result<Response<InvalidateSubtaskResponse>, Status> {
+        let subtask_id = parse_uuid(&request.get_ref().subtask_id, "subtask_id")?;
+
+        let txn = self.state.db.begin().await.map_err(internal_error)?;
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(subtask_id)
+            .one(&txn)
+            .await
+            .map_err(internal_error)?
+        else {
+            return Err(Status::not_found("subtask does not exist"));
+        };
+        challenges_subtasks::ActiveModel {
+            id: Unchanged(subtask_id),
+            enabled: Set(false),
+            retired: Set(true),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await
+        .map_err(internal_error)?;
+        if subtask.enabled {
+            notify_subtask_disabled(&txn, &subtask, None)
+                .await
+                .map_err(internal_error)?;
+        }
+        txn.commit().await.map_err(internal_error)?;
+
+        Ok(Response::new(InvalidateSubtaskResponse {}))
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_uuid(value: &str, field: &str) -> Result<Uuid, Status> {
+    value
+        .parse()
+        .map_err(|_| Status::invalid_argument(format!("invalid uuid in {field}")))
+}
+
+fn internal_error(err: DbErr) -> Status {
+    error!("database error in internal grpc api: {err}");
+    Status::internal("database error")
+}
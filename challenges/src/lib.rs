@@ -0,0 +1,235 @@
+#![forbid(unsafe_code)]
+#![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
+
+use std::{sync::Arc, time::Duration};
+
+use fnct::{backend::AsyncRedisBackend, format::PostcardFormatter};
+use lib::{
+    config::{self, SharedConfig},
+    deprecation::{add_deprecation_headers, Deprecation},
+    errors::add_error_codes,
+    jwt::JwtSecret,
+    openapi::{enrich_spec, spec_endpoint, ui_endpoint},
+    redis::RedisConnection,
+    request_id::add_request_id,
+    services::Services,
+    slow_log::{log_slow_queries, log_slow_requests},
+    validation::add_validation_details,
+    Cache, SharedState,
+};
+use poem::{get, middleware::Tracing, Endpoint, EndpointExt, IntoEndpoint, Route};
+use poem_ext::{db::DbTransactionMiddleware, panic_handler::PanicHandler};
+use poem_openapi::{OpenApi, OpenApiService};
+use sea_orm::{ConnectOptions, Database};
+use sentry::{integrations::tracing::EventFilter, ClientInitGuard};
+use tracing::{info, warn, Level};
+use tracing_subscriber::{prelude::*, EnvFilter};
+
+use crate::{
+    endpoints::{contests::ws::scoreboard_ws, public::sitemap, setup_api, setup_api_v2},
+    services::{
+        contests::ScoreboardHub,
+        feed::{FeedCache, FeedJob},
+        sandbox::{sandbox_from_config, SharedSandbox},
+    },
+};
+
+mod endpoints;
+mod graphql;
+pub mod grpc;
+pub mod services;
+
+/// The state shared by the binaries of the microservice.
+pub struct Setup {
+    pub config: SharedConfig,
+    pub state: Arc<SharedState>,
+    pub redis: RedisConnection,
+    pub sandkasten: SharedSandbox,
+    /// Keeps sentry alive until the process exits.
+    pub sentry_guard: Option<ClientInitGuard>,
+}
+
+/// Load the configuration, initialize logging and connect to the database,
+/// redis and Sandkasten.
+pub async fn setup() -> anyhow::Result<Setup> {
+    let shared_config = SharedConfig::new(config::load()?);
+    let config = shared_config.load();
+
+    let sentry_guard = config.challenges.sentry.as_ref().map(|sentry_config| {
+        sentry::init((
+            sentry_config.dsn.as_str(),
+            sentry::ClientOptions {
+                release: Some(env!("CARGO_PKG_VERSION").into()),
+                attach_stacktrace: true,
+                ..Default::default()
+            },
+        ))
+    });
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .with(
+            sentry::integrations::tracing::layer().event_filter(|md| match md.level() {
+                &Level::ERROR => EventFilter::Exception,
+                &Level::WARN => EventFilter::Event,
+                &Level::INFO | &Level::DEBUG => EventFilter::Breadcrumb,
+                &Level::TRACE => EventFilter::Ignore,
+            }),
+        )
+        .init();
+
+    info!("Connecting to database");
+    let mut db_options = ConnectOptions::new(config.database.url.to_string());
+    db_options.connect_timeout(Duration::from_secs(config.database.connect_timeout));
+    let mut db = Database::connect(db_options).await?;
+    log_slow_queries(&mut db, shared_config.clone());
+
+    info!("Connecting to redis");
+    let redis = RedisConnection::new(config.redis.challenges.as_str()).await?;
+    let cache = Cache::new(
+        AsyncRedisBackend::new(redis.clone(), "challenges".into()),
+        PostcardFormatter,
+        Duration::from_secs(config.cache_ttl),
+    );
+    let auth_redis = RedisConnection::new(config.redis.auth.as_str()).await?;
+
+    info!("Connecting to Sandkasten");
+    let sandkasten = sandbox_from_config(&config.challenges.coding_challenges);
+    let server_version = sandkasten.version().await?;
+    let client_version = sandkasten_client::VERSION;
+    info!("Connected to Sandkasten v{server_version}");
+    if server_version != client_version {
+        warn!(
+            "Sandkasten server version ({server_version}) and client version ({client_version}) \
+             differ!"
+        );
+    }
+
+    let jwt_secret = JwtSecret::try_from(config.jwt_secret.as_str())?;
+    let services = Services::from_config(
+        jwt_secret.clone(),
+        Duration::from_secs(config.internal_jwt_ttl),
+        &config.services,
+        cache.clone(),
+    );
+    let state = Arc::new(SharedState {
+        jwt_secret,
+        auth_redis,
+        services,
+        cache,
+        db,
+    });
+
+    Ok(Setup {
+        config: shared_config,
+        state,
+        redis,
+        sandkasten,
+        sentry_guard,
+    })
+}
+
+/// Build the http endpoint of the microservice including all middlewares.
+pub async fn app(
+    shared_state: Arc<SharedState>,
+    shared_config: SharedConfig,
+    sandkasten: SharedSandbox,
+) -> anyhow::Result<impl Endpoint> {
+    let config = shared_config.load();
+
+    let scoreboard = Arc::new(ScoreboardHub::new(shared_state.db.clone()));
+    tokio::spawn(Arc::clone(&scoreboard).forward_score_events(config.database.url.to_string()));
+    let feed = Arc::new(FeedCache::new());
+    tokio::spawn(
+        FeedJob::new(
+            shared_state.db.clone(),
+            shared_config.clone(),
+            Arc::clone(&feed),
+        )
+        .run(),
+    );
+    let title = "Bootstrap Academy Backend: Challenges Microservice";
+    let server = config.challenges.server.trim_end_matches('/');
+    let v1_service = OpenApiService::new(
+        setup_api(
+            shared_state.clone(),
+            shared_config.clone(),
+            sandkasten.clone(),
+            Arc::clone(&scoreboard),
+            Arc::clone(&feed),
+        )
+        .await?,
+        title,
+        env!("CARGO_PKG_VERSION"),
+    )
+    .external_document("/openapi.json")
+    .server(format!("{server}/v1"));
+    let v2_service = OpenApiService::new(
+        setup_api_v2(shared_state.clone(), shared_config.clone(), sandkasten).await?,
+        title,
+        env!("CARGO_PKG_VERSION"),
+    )
+    .external_document("/v2/openapi.json")
+    .server(format!("{server}/v2"));
+
+    let mut app = Route::new();
+    app = with_docs(app, "", &v1_service);
+    app = with_docs(app, "/v2", &v2_service);
+    let v1 = Arc::new(v1_service.into_endpoint());
+    let unversioned = match config.challenges.unversioned_sunset {
+        Some(sunset) => {
+            let deprecation = Arc::new(Deprecation {
+                sunset: Some(sunset),
+                successor: None,
+            });
+            Arc::clone(&v1)
+                .around(move |ep, req| add_deprecation_headers(ep, req, Arc::clone(&deprecation)))
+                .boxed()
+        }
+        None => Arc::clone(&v1).boxed(),
+    };
+    app = app
+        .at(
+            "/contests/:contest_id/scoreboard/ws",
+            get(scoreboard_ws).data(scoreboard),
+        )
+        .at(
+            "/sitemap.xml",
+            get(sitemap).data(feed).data(shared_config.clone()),
+        )
+        .nest("/v1", v1)
+        .nest("/v2", v2_service)
+        .nest("/", unversioned);
+    if let Some(graphql) = graphql::endpoint(&config) {
+        info!("GraphQL endpoint enabled");
+        app = app.at("/graphql", graphql);
+    }
+    Ok(app
+        .with(Tracing)
+        .with(PanicHandler::middleware())
+        .around(add_error_codes)
+        .around(add_validation_details)
+        .with(DbTransactionMiddleware::new(shared_state.db.clone()))
+        .around(move |ep, req| log_slow_requests(ep, req, shared_config.clone()))
+        .data(shared_state)
+        .around(add_request_id))
+}
+
+/// Serve the specification and documentation of an api version.
+fn with_docs<T: OpenApi>(route: Route, prefix: &str, service: &OpenApiService<T, ()>) -> Route {
+    let spec = service.spec();
+    let enriched_spec = enrich_spec(&spec);
+    route
+        .nest(
+            format!("{prefix}/docs"),
+            ui_endpoint(service.swagger_ui_html(), &spec, &enriched_spec),
+        )
+        .nest(
+            format!("{prefix}/redoc"),
+            ui_endpoint(service.redoc_html(), &spec, &enriched_spec),
+        )
+        .nest(
+            format!("{prefix}/openapi.json"),
+            spec_endpoint(enriched_spec),
+        )
+}
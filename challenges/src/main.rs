@@ -1,122 +1,98 @@
 #![forbid(unsafe_code)]
 #![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
 
-use std::{sync::Arc, time::Duration};
+use std::{net::ToSocketAddrs, sync::Arc};
 
-use fnct::{backend::AsyncRedisBackend, format::PostcardFormatter};
-use lib::{config, jwt::JwtSecret, redis::RedisConnection, services::Services, Cache, SharedState};
-use poem::{listener::TcpListener, middleware::Tracing, EndpointExt, Route, Server};
-use poem_ext::{db::DbTransactionMiddleware, panic_handler::PanicHandler};
-use poem_openapi::OpenApiService;
-use sandkasten_client::SandkastenClient;
-use sea_orm::{ConnectOptions, Database};
-use sentry::integrations::tracing::EventFilter;
-use tracing::{info, warn, Level};
-use tracing_subscriber::{prelude::*, EnvFilter};
+use anyhow::Context;
 
-use crate::endpoints::setup_api;
-
-mod endpoints;
-mod services;
+use challenges::{
+    app, grpc,
+    services::{
+        anti_cheat::AntiCheatJob, digest::CreatorDigestJob, notifications::ContestReminder,
+    },
+    setup, Setup,
+};
+use lib::{config::SharedConfig, events::OutboxRelay};
+use poem::{listener::TcpListener, Server};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{error, info};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let config = Arc::new(config::load()?);
+    let Setup {
+        config: shared_config,
+        state: shared_state,
+        sandkasten,
+        sentry_guard: _sentry_guard,
+        ..
+    } = setup().await?;
+    let config = shared_config.load();
+    let db = &shared_state.db;
 
-    let _sentry_guard = config.challenges.sentry.as_ref().map(|sentry_config| {
-        sentry::init((
-            sentry_config.dsn.as_str(),
-            sentry::ClientOptions {
-                release: Some(env!("CARGO_PKG_VERSION").into()),
-                attach_stacktrace: true,
-                ..Default::default()
-            },
-        ))
-    });
+    if let Some(event_bus) = &config.event_bus {
+        info!("Connecting to event bus");
+        let relay = OutboxRelay::connect(db.clone(), event_bus).await?;
+        tokio::spawn(relay.run());
+    }
 
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
-        .with(
-            sentry::integrations::tracing::layer().event_filter(|md| match md.level() {
-                &Level::ERROR => EventFilter::Exception,
-                &Level::WARN => EventFilter::Event,
-                &Level::INFO | &Level::DEBUG => EventFilter::Breadcrumb,
-                &Level::TRACE => EventFilter::Ignore,
-            }),
+    tokio::spawn(ContestReminder::new(db.clone(), shared_config.clone()).run());
+    tokio::spawn(
+        CreatorDigestJob::new(
+            db.clone(),
+            shared_state.services.clone(),
+            shared_config.clone(),
         )
-        .init();
-
-    info!("Connecting to database");
-    let mut db_options = ConnectOptions::new(config.database.url.to_string());
-    db_options.connect_timeout(Duration::from_secs(config.database.connect_timeout));
-    let db = Database::connect(db_options).await?;
-
-    info!("Connecting to redis");
-    let cache = Cache::new(
-        AsyncRedisBackend::new(
-            RedisConnection::new(config.redis.challenges.as_str()).await?,
-            "challenges".into(),
-        ),
-        PostcardFormatter,
-        Duration::from_secs(config.cache_ttl),
+        .run(),
     );
-    let auth_redis = RedisConnection::new(config.redis.auth.as_str()).await?;
+    tokio::spawn(AntiCheatJob::new(db.clone(), shared_config.clone()).run());
 
-    info!("Connecting to Sandkasten");
-    let sandkasten =
-        SandkastenClient::new(config.challenges.coding_challenges.sandkasten_url.clone());
-    let server_version = sandkasten.version().await?;
-    let client_version = sandkasten_client::VERSION;
-    info!("Connected to Sandkasten v{server_version}");
-    if server_version != client_version {
-        warn!(
-            "Sandkasten server version ({server_version}) and client version ({client_version}) \
-             differ!"
-        );
-    }
+    let app = app(Arc::clone(&shared_state), shared_config.clone(), sandkasten).await?;
 
-    let jwt_secret = JwtSecret::try_from(config.jwt_secret.as_str())?;
-    let services = Services::from_config(
-        jwt_secret.clone(),
-        Duration::from_secs(config.internal_jwt_ttl),
-        &config.services,
-        cache.clone(),
-    );
-    let shared_state = Arc::new(SharedState {
-        jwt_secret,
-        auth_redis,
-        services,
-        cache,
-        db: db.clone(),
-    });
+    tokio::spawn(reload_config_on_sighup(shared_config));
 
-    let api_service = OpenApiService::new(
-        setup_api(shared_state.clone(), Arc::clone(&config), sandkasten).await?,
-        "Bootstrap Academy Backend: Challenges Microservice",
-        env!("CARGO_PKG_VERSION"),
-    )
-    .external_document("/openapi.json")
-    .server(config.challenges.server.to_string());
-    let app = Route::new()
-        .nest("/openapi.json", api_service.spec_endpoint())
-        .nest("/docs", api_service.swagger_ui())
-        .nest("/redoc", api_service.redoc())
-        .nest("/", api_service)
-        .with(Tracing)
-        .with(PanicHandler::middleware())
-        .with(DbTransactionMiddleware::new(db))
-        .data(shared_state);
+    let grpc = async {
+        let Some(grpc_config) = &config.challenges.grpc else {
+            return Ok(());
+        };
+        let addr = (grpc_config.host.as_str(), grpc_config.port)
+            .to_socket_addrs()?
+            .next()
+            .context("failed to resolve grpc address")?;
+        info!("gRPC server listening on {addr}");
+        tonic::transport::Server::builder()
+            .add_service(grpc::service(shared_state))
+            .serve(addr)
+            .await?;
+        anyhow::Ok(())
+    };
 
     info!(
         "Listening on {}:{}",
         config.challenges.host, config.challenges.port
     );
-    Server::new(TcpListener::bind((
-        config.challenges.host.as_str(),
-        config.challenges.port,
-    )))
-    .run(app)
-    .await?;
+    let http = async {
+        Server::new(TcpListener::bind((
+            config.challenges.host.as_str(),
+            config.challenges.port,
+        )))
+        .run(app)
+        .await?;
+        anyhow::Ok(())
+    };
 
+    tokio::try_join!(http, grpc)?;
+
+    Ok(())
+}
+
+/// Reload the configuration whenever the process receives `SIGHUP`.
+async fn reload_config_on_sighup(config: SharedConfig) -> anyhow::Result<()> {
+    let mut sighup = signal(SignalKind::hangup())?;
+    while sighup.recv().await.is_some() {
+        match config.reload() {
+            Ok(_) => info!("Configuration reloaded"),
+            Err(err) => error!("Could not reload configuration: {err}"),
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,51 @@
+//! Explanations of access decisions for support requests.
+
+use chrono::Utc;
+use entity::{challenges_ban, challenges_subtasks, sea_orm_active_enums::ChallengesBanAction};
+use lib::auth::User;
+use schemas::challenges::admin::SubtaskAccessExplanation;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseTransaction, DbErr, EntityTrait, Iterable, QueryFilter,
+};
+
+use super::subtasks::check_subtask_access;
+
+/// Explain whether a user can access a subtask. The decision is made by
+/// [`check_subtask_access`], just like in the endpoints.
+pub async fn explain_subtask_access(
+    db: &DatabaseTransaction,
+    user: &User,
+    subtask: &challenges_subtasks::Model,
+) -> Result<SubtaskAccessExplanation, DbErr> {
+    let access = check_subtask_access(db, user, subtask).await?;
+
+    let now = Utc::now().naive_utc();
+    let bans = challenges_ban::Entity::find()
+        .filter(challenges_ban::Column::UserId.eq(user.id))
+        .filter(
+            Condition::any()
+                .add(challenges_ban::Column::End.is_null())
+                .add(challenges_ban::Column::End.gt(now)),
+        )
+        .all(db)
+        .await?;
+    let active_bans = ChallengesBanAction::iter()
+        .filter(|action| bans.iter().any(|ban| ban.action == *action))
+        .collect();
+
+    Ok(SubtaskAccessExplanation {
+        can_access: access.allowed(),
+        admin: access.admin,
+        creator: access.creator,
+        task_visible: access.task_visible,
+        enabled: access.enabled,
+        locked: access.locked,
+        retired: subtask.retired,
+        review_status: subtask.review_status,
+        access_granted: access.access_granted.map(|x| x.and_utc()),
+        unlock_price: subtask.unlock_price.map(|x| x as _),
+        hidden_by_pool: access.hidden_by_pool,
+        missing_prerequisites: access.missing_prerequisites,
+        active_bans,
+    })
+}
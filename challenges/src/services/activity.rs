@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use entity::{
+    challenges_cloze_attempts, challenges_code_reading_attempts,
+    challenges_coding_challenge_submissions, challenges_matching_attempts,
+    challenges_multiple_choice_attempts, challenges_ordering_attempts,
+    challenges_output_submissions, challenges_question_attempts, challenges_sql_attempts,
+    challenges_true_false_attempts, challenges_user_subtasks,
+};
+use lib::auth::User;
+use schemas::challenges::activity::{Activity, ActivityDay};
+use sea_orm::{
+    sea_query::{Alias, Expr, Func, Query, SelectStatement},
+    ColumnTrait, ConnectionTrait, DatabaseTransaction, DbErr, EntityTrait,
+};
+
+use super::organizations::subtask_id_scope;
+
+/// Return the number of attempts and solves of a user on each day of a year.
+/// Only subtasks of tasks the user can see are counted.
+pub async fn get_activity(
+    db: &DatabaseTransaction,
+    user: &User,
+    year: i32,
+) -> Result<Activity, DbErr> {
+    let start = year_start(year);
+    let end = year_start(year + 1);
+
+    let mut attempts = Vec::new();
+    for query in [
+        count_per_day(
+            challenges_multiple_choice_attempts::Entity,
+            challenges_multiple_choice_attempts::Column::UserId,
+            challenges_multiple_choice_attempts::Column::Timestamp,
+            challenges_multiple_choice_attempts::Column::QuestionId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_question_attempts::Entity,
+            challenges_question_attempts::Column::UserId,
+            challenges_question_attempts::Column::Timestamp,
+            challenges_question_attempts::Column::QuestionId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_matching_attempts::Entity,
+            challenges_matching_attempts::Column::UserId,
+            challenges_matching_attempts::Column::Timestamp,
+            challenges_matching_attempts::Column::MatchingId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_true_false_attempts::Entity,
+            challenges_true_false_attempts::Column::UserId,
+            challenges_true_false_attempts::Column::Timestamp,
+            challenges_true_false_attempts::Column::StatementId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_ordering_attempts::Entity,
+            challenges_ordering_attempts::Column::UserId,
+            challenges_ordering_attempts::Column::Timestamp,
+            challenges_ordering_attempts::Column::OrderingId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_cloze_attempts::Entity,
+            challenges_cloze_attempts::Column::UserId,
+            challenges_cloze_attempts::Column::Timestamp,
+            challenges_cloze_attempts::Column::ClozeId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_code_reading_attempts::Entity,
+            challenges_code_reading_attempts::Column::UserId,
+            challenges_code_reading_attempts::Column::Timestamp,
+            challenges_code_reading_attempts::Column::CodeReadingId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_sql_attempts::Entity,
+            challenges_sql_attempts::Column::UserId,
+            challenges_sql_attempts::Column::Timestamp,
+            challenges_sql_attempts::Column::SqlChallengeId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_output_submissions::Entity,
+            challenges_output_submissions::Column::Creator,
+            challenges_output_submissions::Column::CreationTimestamp,
+            challenges_output_submissions::Column::SubtaskId,
+            user,
+            start,
+            end,
+        ),
+        count_per_day(
+            challenges_coding_challenge_submissions::Entity,
+            challenges_coding_challenge_submissions::Column::Creator,
+            challenges_coding_challenge_submissions::Column::CreationTimestamp,
+            challenges_coding_challenge_submissions::Column::SubtaskId,
+            user,
+            start,
+            end,
+        ),
+    ] {
+        attempts.extend(query_counts(db, query).await?);
+    }
+
+    let solves = query_counts(
+        db,
+        count_per_day(
+            challenges_user_subtasks::Entity,
+            challenges_user_subtasks::Column::UserId,
+            challenges_user_subtasks::Column::SolvedTimestamp,
+            challenges_user_subtasks::Column::SubtaskId,
+            user,
+            start,
+            end,
+        ),
+    )
+    .await?;
+
+    let days = merge_activity(attempts, solves);
+    Ok(Activity {
+        year,
+        total_attempts: days.iter().map(|day| day.attempts).sum(),
+        total_solves: days.iter().map(|day| day.solves).sum(),
+        days,
+    })
+}
+
+fn year_start(year: i32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .unwrap_or(NaiveDate::MAX)
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+/// Build a query that counts the rows of a user per day in the given time
+/// range.
+fn count_per_day<E: EntityTrait>(
+    entity: E,
+    user_column: E::Column,
+    timestamp_column: E::Column,
+    subtask_column: E::Column,
+    user: &User,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> SelectStatement {
+    let day = Func::cust(Alias::new("date")).arg(Expr::col(timestamp_column));
+    Query::select()
+        .expr_as(day.clone(), Alias::new("day"))
+        .expr_as(Expr::col(timestamp_column).count(), Alias::new("count"))
+        .from(entity)
+        .and_where(user_column.eq(user.id))
+        .cond_where(subtask_id_scope(subtask_column, user))
+        .and_where(timestamp_column.gte(start))
+        .and_where(timestamp_column.lt(end))
+        .add_group_by([day.into()])
+        .to_owned()
+}
+
+async fn query_counts(
+    db: &DatabaseTransaction,
+    query: SelectStatement,
+) -> Result<Vec<(NaiveDate, i64)>, DbErr> {
+    db.query_all(db.get_database_backend().build(&query))
+        .await?
+        .into_iter()
+        .map(|row| row.try_get_many_by_index())
+        .collect()
+}
+
+/// Merge the numbers of attempts and solves per day into a list of days
+/// sorted by date. Counts of the same day are added up.
+fn merge_activity(
+    attempts: impl IntoIterator<Item = (NaiveDate, i64)>,
+    solves: impl IntoIterator<Item = (NaiveDate, i64)>,
+) -> Vec<ActivityDay> {
+    let mut days = BTreeMap::<NaiveDate, (u64, u64)>::new();
+    for (date, count) in attempts {
+        days.entry(date).or_default().0 += count as u64;
+    }
+    for (date, count) in solves {
+        days.entry(date).or_default().1 += count as u64;
+    }
+    days.into_iter()
+        .map(|(date, (attempts, solves))| ActivityDay {
+            date,
+            attempts,
+            solves,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_merge_activity() {
+        assert_eq!(
+            merge_activity(
+                [(date(3, 2), 4), (date(1, 5), 1), (date(3, 2), 2)],
+                [(date(3, 2), 1), (date(2, 1), 1)]
+            ),
+            [
+                ActivityDay {
+                    date: date(1, 5),
+                    attempts: 1,
+                    solves: 0
+                },
+                ActivityDay {
+                    date: date(2, 1),
+                    attempts: 0,
+                    solves: 1
+                },
+                ActivityDay {
+                    date: date(3, 2),
+                    attempts: 6,
+                    solves: 1
+                },
+            ]
+        );
+        assert_eq!(merge_activity([], []), []);
+    }
+}
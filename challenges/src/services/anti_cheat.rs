@@ -0,0 +1,405 @@
+//! Detection of suspicious answer patterns.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use chrono::{NaiveDateTime, Utc};
+use entity::{
+    challenges_cheat_flags, challenges_cloze_attempts, challenges_code_reading_attempts,
+    challenges_matching_attempts, challenges_multiple_choice_attempts,
+    challenges_ordering_attempts, challenges_question_attempts, challenges_sql_attempts,
+    challenges_subtask_prerequisites, challenges_true_false_attempts, challenges_user_subtasks,
+    sea_orm_active_enums::{ChallengesCheatFlagKind, ChallengesCheatFlagStatus},
+};
+use lib::config::SharedConfig;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use super::prerequisites::PrerequisiteGraph;
+
+/// A suspicious pattern found in the attempts of a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detection {
+    pub user_id: Uuid,
+    pub kind: ChallengesCheatFlagKind,
+    /// Identifies the pattern, so it is only flagged once even if it is found
+    /// again in later runs.
+    pub key: String,
+    pub details: String,
+}
+
+/// Find users who have solved at least `count` subtasks within `window`. Each
+/// user is flagged at most once per day.
+pub fn detect_rapid_solves(
+    solves: &[(Uuid, NaiveDateTime)],
+    count: usize,
+    window: chrono::Duration,
+) -> Vec<Detection> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let mut by_user = HashMap::<Uuid, Vec<NaiveDateTime>>::new();
+    for &(user_id, timestamp) in solves {
+        by_user.entry(user_id).or_default().push(timestamp);
+    }
+
+    let mut out = Vec::new();
+    for (user_id, mut timestamps) in by_user {
+        timestamps.sort_unstable();
+        let mut days = HashSet::new();
+        for burst in timestamps.windows(count) {
+            let (first, last) = (burst[0], burst[count - 1]);
+            if last - first > window || !days.insert(first.date()) {
+                continue;
+            }
+            out.push(Detection {
+                user_id,
+                kind: ChallengesCheatFlagKind::RapidSolves,
+                key: first.date().to_string(),
+                details: format!(
+                    "{count} subtasks solved within {} seconds starting at {first}",
+                    (last - first).num_seconds()
+                ),
+            });
+        }
+    }
+    out.sort_unstable_by(|a, b| (a.user_id, &a.key).cmp(&(b.user_id, &b.key)));
+    out
+}
+
+/// Find users whose wrong answers happened on the same subtasks in the same
+/// order. `attempts` contains the user, the subtask and the timestamp of each
+/// wrong answer. Only sequences of at least `min_length` wrong answers are
+/// considered.
+pub fn detect_identical_wrong_answers(
+    attempts: &[(Uuid, Uuid, NaiveDateTime)],
+    min_length: usize,
+) -> Vec<Detection> {
+    let mut by_user = HashMap::<Uuid, Vec<(NaiveDateTime, Uuid)>>::new();
+    for &(user_id, subtask_id, timestamp) in attempts {
+        by_user
+            .entry(user_id)
+            .or_default()
+            .push((timestamp, subtask_id));
+    }
+
+    let mut groups = HashMap::<Vec<Uuid>, Vec<Uuid>>::new();
+    for (user_id, mut attempts) in by_user {
+        if attempts.len() < min_length.max(1) {
+            continue;
+        }
+        attempts.sort_unstable();
+        let sequence = attempts
+            .into_iter()
+            .map(|(_, subtask_id)| subtask_id)
+            .collect();
+        groups.entry(sequence).or_default().push(user_id);
+    }
+
+    let mut out = Vec::new();
+    for (sequence, mut users) in groups {
+        if users.len() < 2 {
+            continue;
+        }
+        users.sort_unstable();
+        for &user_id in &users {
+            let others = users
+                .iter()
+                .filter(|&&x| x != user_id)
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push(Detection {
+                user_id,
+                kind: ChallengesCheatFlagKind::IdenticalWrongAnswers,
+                key: others.clone(),
+                details: format!(
+                    "same sequence of {} wrong answers as {others}",
+                    sequence.len()
+                ),
+            });
+        }
+    }
+    out.sort_unstable_by(|a, b| (a.user_id, &a.key).cmp(&(b.user_id, &b.key)));
+    out
+}
+
+/// Find subtasks which have been solved before all of their prerequisites
+/// have been solved. `solved_at` maps each (user, subtask) pair to the time
+/// the subtask has been solved.
+pub fn detect_solved_before_unlock(
+    solves: &[challenges_user_subtasks::Model],
+    prerequisites: &PrerequisiteGraph,
+    solved_at: &HashMap<(Uuid, Uuid), NaiveDateTime>,
+) -> Vec<Detection> {
+    solves
+        .iter()
+        .filter_map(|solve| {
+            let timestamp = solve.solved_timestamp?;
+            let missing = prerequisites
+                .get(&solve.subtask_id)?
+                .iter()
+                .filter(|&&id| {
+                    solved_at
+                        .get(&(solve.user_id, id))
+                        .map_or(true, |&x| x > timestamp)
+                })
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>();
+            (!missing.is_empty()).then(|| Detection {
+                user_id: solve.user_id,
+                kind: ChallengesCheatFlagKind::SolvedBeforeUnlock,
+                key: solve.subtask_id.to_string(),
+                details: format!(
+                    "subtask {} solved at {timestamp} before its prerequisites {}",
+                    solve.subtask_id,
+                    missing.join(", ")
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Return whether the rewards of the user are currently held because of an
+/// open flag.
+pub async fn has_reward_hold(db: &DatabaseTransaction, user_id: Uuid) -> Result<bool, DbErr> {
+    Ok(challenges_cheat_flags::Entity::find()
+        .filter(challenges_cheat_flags::Column::UserId.eq(user_id))
+        .filter(challenges_cheat_flags::Column::Status.eq(ChallengesCheatFlagStatus::Open))
+        .filter(challenges_cheat_flags::Column::HoldRewards.eq(true))
+        .one(db)
+        .await?
+        .is_some())
+}
+
+/// Periodically checks recent attempts for suspicious patterns and adds them
+/// to the review queue.
+pub struct AntiCheatJob {
+    db: DatabaseConnection,
+    config: SharedConfig,
+}
+
+impl AntiCheatJob {
+    pub fn new(db: DatabaseConnection, config: SharedConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Check attempts until the process exits.
+    pub async fn run(self) {
+        loop {
+            if self.config.load().challenges.anti_cheat.enabled {
+                match self.detect().await {
+                    Ok(0) => {}
+                    Ok(n) => debug!("flagged {n} suspicious pattern(s)"),
+                    Err(err) => error!("failed to detect suspicious patterns: {err}"),
+                }
+            }
+            let poll_interval = self.config.load().challenges.anti_cheat.poll_interval;
+            tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+        }
+    }
+
+    /// Check the attempts of the configured lookback period and store new
+    /// flags. Returns the number of new flags.
+    async fn detect(&self) -> Result<u64, DbErr> {
+        let config = self.config.load();
+        let config = &config.challenges.anti_cheat;
+        let now = Utc::now().naive_utc();
+        let since = now - chrono::Duration::hours(config.lookback as _);
+
+        let txn = self.db.begin().await?;
+        let solves = challenges_user_subtasks::Entity::find()
+            .filter(challenges_user_subtasks::Column::SolvedTimestamp.gte(since))
+            .all(&txn)
+            .await?;
+
+        let mut detections = detect_rapid_solves(
+            &solves
+                .iter()
+                .filter_map(|x| Some((x.user_id, x.solved_timestamp?)))
+                .collect::<Vec<_>>(),
+            config.rapid_solves_count,
+            chrono::Duration::seconds(config.rapid_solves_window as _),
+        );
+
+        let mut wrong_answers = Vec::new();
+        macro_rules! wrong_answers {
+            ($entity:ident, $subtask_id:ident) => {
+                wrong_answers.extend(
+                    $entity::Entity::find()
+                        .select_only()
+                        .column($entity::Column::UserId)
+                        .column($entity::Column::$subtask_id)
+                        .column($entity::Column::Timestamp)
+                        .filter($entity::Column::Solved.eq(false))
+                        .filter($entity::Column::Timestamp.gte(since))
+                        .into_tuple::<(Uuid, Uuid, NaiveDateTime)>()
+                        .all(&txn)
+                        .await?,
+                )
+            };
+        }
+        wrong_answers!(challenges_multiple_choice_attempts, QuestionId);
+        wrong_answers!(challenges_question_attempts, QuestionId);
+        wrong_answers!(challenges_matching_attempts, MatchingId);
+        wrong_answers!(challenges_true_false_attempts, StatementId);
+        wrong_answers!(challenges_ordering_attempts, OrderingId);
+        wrong_answers!(challenges_cloze_attempts, ClozeId);
+        wrong_answers!(challenges_code_reading_attempts, CodeReadingId);
+        wrong_answers!(challenges_sql_attempts, SqlChallengeId);
+        detections.extend(detect_identical_wrong_answers(
+            &wrong_answers,
+            config.identical_wrong_answers,
+        ));
+
+        let mut prerequisites = PrerequisiteGraph::new();
+        for edge in challenges_subtask_prerequisites::Entity::find()
+            .filter(
+                challenges_subtask_prerequisites::Column::SubtaskId
+                    .is_in(solves.iter().map(|x| x.subtask_id)),
+            )
+            .all(&txn)
+            .await?
+        {
+            prerequisites
+                .entry(edge.subtask_id)
+                .or_default()
+                .push(edge.prerequisite_id);
+        }
+        let solved_at = challenges_user_subtasks::Entity::find()
+            .filter(
+                challenges_user_subtasks::Column::UserId.is_in(solves.iter().map(|x| x.user_id)),
+            )
+            .filter(
+                challenges_user_subtasks::Column::SubtaskId
+                    .is_in(prerequisites.values().flatten().copied()),
+            )
+            .all(&txn)
+            .await?
+            .into_iter()
+            .filter_map(|x| Some(((x.user_id, x.subtask_id), x.solved_timestamp?)))
+            .collect();
+        detections.extend(detect_solved_before_unlock(
+            &solves,
+            &prerequisites,
+            &solved_at,
+        ));
+
+        if detections.is_empty() {
+            return Ok(0);
+        }
+        let flagged =
+            challenges_cheat_flags::Entity::insert_many(detections.into_iter().map(|detection| {
+                challenges_cheat_flags::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    user_id: Set(detection.user_id),
+                    kind: Set(detection.kind),
+                    key: Set(detection.key),
+                    details: Set(detection.details),
+                    timestamp: Set(now),
+                    hold_rewards: Set(config.hold_rewards),
+                    status: Set(ChallengesCheatFlagStatus::Open),
+                    reviewer: Set(None),
+                    review_timestamp: Set(None),
+                }
+            }))
+            .on_conflict(
+                OnConflict::columns([
+                    challenges_cheat_flags::Column::UserId,
+                    challenges_cheat_flags::Column::Kind,
+                    challenges_cheat_flags::Column::Key,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(flagged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn ts(seconds: i64) -> NaiveDateTime {
+        NaiveDateTime::default() + Duration::seconds(seconds)
+    }
+
+    #[test]
+    fn rapid_solves() {
+        let [u1, u2] = [1, 2].map(Uuid::from_u128);
+        let mut solves = (0..5).map(|i| (u1, ts(i * 2))).collect::<Vec<_>>();
+        solves.extend((0..5).map(|i| (u2, ts(i * 60))));
+        // a second burst on the same day is not flagged again
+        solves.extend((0..5).map(|i| (u1, ts(3600 + i))));
+
+        let detections = detect_rapid_solves(&solves, 5, Duration::seconds(10));
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].user_id, u1);
+        assert_eq!(detections[0].kind, ChallengesCheatFlagKind::RapidSolves);
+        assert_eq!(detections[0].key, "1970-01-01");
+
+        assert!(detect_rapid_solves(&solves, 0, Duration::seconds(10)).is_empty());
+    }
+
+    #[test]
+    fn identical_wrong_answers() {
+        let [u1, u2, u3] = [1, 2, 3].map(Uuid::from_u128);
+        let [s1, s2, s3] = [11, 12, 13].map(Uuid::from_u128);
+        let attempts = [
+            (u1, s1, ts(0)),
+            (u1, s2, ts(1)),
+            (u1, s3, ts(2)),
+            (u2, s1, ts(10)),
+            (u2, s2, ts(11)),
+            (u2, s3, ts(12)),
+            (u3, s2, ts(0)),
+            (u3, s1, ts(1)),
+            (u3, s3, ts(2)),
+        ];
+
+        let detections = detect_identical_wrong_answers(&attempts, 3);
+        assert_eq!(
+            detections
+                .iter()
+                .map(|x| (x.user_id, x.key.as_str()))
+                .collect::<Vec<_>>(),
+            [(u1, u2.to_string().as_str()), (u2, u1.to_string().as_str())]
+        );
+        assert!(detect_identical_wrong_answers(&attempts, 4).is_empty());
+    }
+
+    #[test]
+    fn solved_before_unlock() {
+        let user = Uuid::from_u128(1);
+        let [a, b, c] = [11, 12, 13].map(Uuid::from_u128);
+        let solve = |subtask_id, seconds| challenges_user_subtasks::Model {
+            user_id: user,
+            subtask_id,
+            solved_timestamp: Some(ts(seconds)),
+            rating: None,
+            rating_timestamp: None,
+            last_attempt_timestamp: Some(ts(seconds)),
+            attempts: 1,
+            revealed_timestamp: None,
+        };
+        let graph = PrerequisiteGraph::from([(b, vec![a]), (c, vec![a, b])]);
+        let solved_at = HashMap::from([((user, a), ts(15)), ((user, b), ts(20))]);
+
+        let detections =
+            detect_solved_before_unlock(&[solve(b, 10), solve(c, 30)], &graph, &solved_at);
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].key, b.to_string());
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::{BTreeMap, HashMap};
+
+use entity::challenges_build_configs;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+/// The build flags of a coding challenge for each environment.
+pub type BuildFlags = BTreeMap<String, Vec<String>>;
+
+/// Return the build flags of a coding challenge.
+pub async fn get_build_flags(
+    db: &impl ConnectionTrait,
+    subtask_id: Uuid,
+) -> Result<BuildFlags, DbErr> {
+    Ok(challenges_build_configs::Entity::find()
+        .filter(challenges_build_configs::Column::SubtaskId.eq(subtask_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|config| (config.environment, config.flags))
+        .collect())
+}
+
+/// Return the first flag which is not in the allowlist of the environment.
+pub fn find_disallowed_flag<'a>(
+    allowlist: &HashMap<String, Vec<String>>,
+    environment: &str,
+    flags: &'a [String],
+) -> Option<&'a str> {
+    let allowed = allowlist
+        .get(environment)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    flags
+        .iter()
+        .find(|flag| !allowed.contains(flag))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disallowed_flags() {
+        let allowlist = HashMap::from([("cpp".into(), vec!["-O2".into(), "-O3".into()])]);
+        let flags = |flags: &[&str]| flags.iter().map(|&x| x.into()).collect::<Vec<String>>();
+        assert_eq!(
+            find_disallowed_flag(&allowlist, "cpp", &flags(&["-O3"])),
+            None
+        );
+        assert_eq!(find_disallowed_flag(&allowlist, "cpp", &[]), None);
+        assert_eq!(
+            find_disallowed_flag(&allowlist, "cpp", &flags(&["-O2", "-march=native"])),
+            Some("-march=native")
+        );
+        assert_eq!(
+            find_disallowed_flag(&allowlist, "python", &flags(&["-O"])),
+            Some("-O")
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use entity::{
+    challenges_coding_challenge_submissions, challenges_cohort_assignments,
+    challenges_cohort_members, challenges_cohorts, challenges_subtasks, challenges_user_subtasks,
+};
+use lib::{auth::User, config::challenges::DueDates};
+use schemas::challenges::cohorts::{
+    CohortMemberProgress, CohortProgress, CohortProgressCell, CohortProgressSubtask,
+};
+use sea_orm::{
+    sea_query::Query, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder,
+};
+use uuid::Uuid;
+
+use super::organizations::task_id_scope;
+
+/// Characters used in invite codes. Similar looking characters are omitted.
+const INVITE_CODE_ALPHABET: &[u8; 32] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+const INVITE_CODE_LENGTH: usize = 10;
+
+/// Generate a random invite code.
+pub fn generate_invite_code() -> String {
+    // the lower 64 bits of a v4 uuid contain 62 random bits
+    let mut bits = Uuid::new_v4().as_u128() as u64;
+    (0..INVITE_CODE_LENGTH)
+        .map(|_| {
+            let c = INVITE_CODE_ALPHABET[(bits & 31) as usize] as char;
+            bits >>= 5;
+            c
+        })
+        .collect()
+}
+
+pub fn normalize_invite_code(code: &str) -> String {
+    code.trim().to_ascii_uppercase()
+}
+
+pub async fn is_cohort_member(
+    db: &DatabaseTransaction,
+    cohort_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, DbErr> {
+    Ok(
+        challenges_cohort_members::Entity::find_by_id((cohort_id, user_id))
+            .one(db)
+            .await?
+            .is_some(),
+    )
+}
+
+/// Return whether the user can manage the cohort, i.e. is its creator or an
+/// admin.
+pub fn can_manage_cohort(user: &User, cohort: &challenges_cohorts::Model) -> bool {
+    user.admin || user.id == cohort.creator
+}
+
+/// Return whether the user can see the cohort and its assignments.
+pub async fn can_view_cohort(
+    db: &DatabaseTransaction,
+    user: &User,
+    cohort: &challenges_cohorts::Model,
+) -> Result<bool, DbErr> {
+    Ok(can_manage_cohort(user, cohort) || is_cohort_member(db, cohort.id, user.id).await?)
+}
+
+/// Return the due dates of all tasks which have been assigned to the user in
+/// any of their cohorts.
+pub async fn get_due_dates(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+) -> Result<HashMap<Uuid, NaiveDateTime>, DbErr> {
+    query_due_dates(db, user_id, None).await
+}
+
+/// Return the due date of a task if it has been assigned to the user.
+pub async fn get_due_date(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> Result<Option<NaiveDateTime>, DbErr> {
+    Ok(query_due_dates(db, user_id, Some(task_id))
+        .await?
+        .remove(&task_id))
+}
+
+async fn query_due_dates(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    task_id: Option<Uuid>,
+) -> Result<HashMap<Uuid, NaiveDateTime>, DbErr> {
+    let mut query = challenges_cohort_assignments::Entity::find().filter(
+        challenges_cohort_assignments::Column::CohortId.in_subquery(
+            Query::select()
+                .column(challenges_cohort_members::Column::CohortId)
+                .from(challenges_cohort_members::Entity)
+                .and_where(challenges_cohort_members::Column::UserId.eq(user_id))
+                .to_owned(),
+        ),
+    );
+    if let Some(task_id) = task_id {
+        query = query.filter(challenges_cohort_assignments::Column::TaskId.eq(task_id));
+    }
+    Ok(due_dates(&query.all(db).await?))
+}
+
+/// Merge the due dates of assignments of the same task in different cohorts.
+/// The latest due date wins and an assignment without due date overrides all
+/// others.
+fn due_dates(assignments: &[challenges_cohort_assignments::Model]) -> HashMap<Uuid, NaiveDateTime> {
+    let mut out = HashMap::<Uuid, Option<NaiveDateTime>>::new();
+    for assignment in assignments {
+        out.entry(assignment.task_id)
+            .and_modify(|due| *due = due.zip(assignment.due).map(|(a, b)| a.max(b)))
+            .or_insert(assignment.due);
+    }
+    out.into_iter()
+        .filter_map(|(task_id, due)| Some((task_id, due?)))
+        .collect()
+}
+
+/// Return the percentage of the rewards a user gets for solving a subtask at
+/// the given time.
+pub fn reward_percent(
+    config: &DueDates,
+    due: Option<NaiveDateTime>,
+    solved_at: NaiveDateTime,
+) -> u8 {
+    match due {
+        Some(due) if solved_at > due => config.late_percent,
+        _ => 100,
+    }
+}
+
+/// Return the progress of all members of a cohort on the subtasks of all
+/// assigned tasks the user can see.
+pub async fn get_cohort_progress(
+    db: &DatabaseTransaction,
+    user: &User,
+    cohort: &challenges_cohorts::Model,
+) -> Result<CohortProgress, DbErr> {
+    let assignments = cohort
+        .find_related(challenges_cohort_assignments::Entity)
+        .filter(task_id_scope(
+            challenges_cohort_assignments::Column::TaskId,
+            user,
+        ))
+        .all(db)
+        .await?;
+    let members = cohort
+        .find_related(challenges_cohort_members::Entity)
+        .order_by_asc(challenges_cohort_members::Column::JoinTimestamp)
+        .all(db)
+        .await?;
+    let subtasks = challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::TaskId.is_in(assignments.iter().map(|x| x.task_id)))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(false))
+        .order_by_asc(challenges_subtasks::Column::CreationTimestamp)
+        .all(db)
+        .await?;
+    let user_subtasks = challenges_user_subtasks::Entity::find()
+        .filter(challenges_user_subtasks::Column::SubtaskId.is_in(subtasks.iter().map(|x| x.id)))
+        .filter(challenges_user_subtasks::Column::UserId.is_in(members.iter().map(|x| x.user_id)))
+        .all(db)
+        .await?;
+    let submissions = challenges_coding_challenge_submissions::Entity::find()
+        .filter(
+            challenges_coding_challenge_submissions::Column::SubtaskId
+                .is_in(subtasks.iter().map(|x| x.id)),
+        )
+        .filter(
+            challenges_coding_challenge_submissions::Column::Creator
+                .is_in(members.iter().map(|x| x.user_id)),
+        )
+        .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| ((x.creator, x.subtask_id), x.id))
+        .collect();
+
+    Ok(compute_cohort_progress(
+        &assignments,
+        &members,
+        &subtasks,
+        &user_subtasks,
+        &submissions,
+    ))
+}
+
+/// Build the progress matrix of a cohort. `submissions` maps each
+/// (user, subtask) pair to the latest submission.
+pub fn compute_cohort_progress(
+    assignments: &[challenges_cohort_assignments::Model],
+    members: &[challenges_cohort_members::Model],
+    subtasks: &[challenges_subtasks::Model],
+    user_subtasks: &[challenges_user_subtasks::Model],
+    submissions: &HashMap<(Uuid, Uuid), Uuid>,
+) -> CohortProgress {
+    let due = assignments
+        .iter()
+        .map(|x| (x.task_id, x.due))
+        .collect::<HashMap<_, _>>();
+    let user_subtasks = user_subtasks
+        .iter()
+        .map(|x| ((x.user_id, x.subtask_id), x))
+        .collect::<HashMap<_, _>>();
+
+    let members = members
+        .iter()
+        .map(|member| {
+            let cells = subtasks
+                .iter()
+                .map(|subtask| {
+                    let user_subtask = user_subtasks.get(&(member.user_id, subtask.id));
+                    let solved_timestamp = user_subtask.and_then(|x| x.solved_timestamp);
+                    let due = due.get(&subtask.task_id).copied().flatten();
+                    CohortProgressCell {
+                        solved: solved_timestamp.is_some(),
+                        solved_timestamp: solved_timestamp.map(|x| x.and_utc()),
+                        late: solved_timestamp.zip(due).is_some_and(|(s, d)| s > d),
+                        attempts: user_subtask.map(|x| x.attempts as _).unwrap_or(0),
+                        submission_id: submissions.get(&(member.user_id, subtask.id)).copied(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            CohortMemberProgress {
+                user_id: member.user_id,
+                solved: cells.iter().filter(|x| x.solved).count() as _,
+                subtasks: cells,
+            }
+        })
+        .collect();
+
+    CohortProgress {
+        subtasks: subtasks
+            .iter()
+            .map(|subtask| CohortProgressSubtask {
+                task_id: subtask.task_id,
+                subtask_id: subtask.id,
+                ty: subtask.ty,
+                due: due
+                    .get(&subtask.task_id)
+                    .copied()
+                    .flatten()
+                    .map(|x| x.and_utc()),
+            })
+            .collect(),
+        members,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDateTime};
+    use entity::sea_orm_active_enums::{
+        ChallengesLicense, ChallengesReviewStatus, ChallengesSubtaskType,
+    };
+
+    use super::*;
+
+    #[test]
+    fn invite_code() {
+        let code = generate_invite_code();
+        assert_eq!(code.len(), INVITE_CODE_LENGTH);
+        assert!(code.bytes().all(|c| INVITE_CODE_ALPHABET.contains(&c)));
+        assert_eq!(normalize_invite_code(&code.to_lowercase()), code);
+    }
+
+    #[test]
+    fn progress() {
+        let task = Uuid::from_u128(1);
+        let [u1, u2] = [10, 11].map(Uuid::from_u128);
+        let start = NaiveDateTime::default();
+        let subtask = challenges_subtasks::Model {
+            id: Uuid::from_u128(2),
+            task_id: task,
+            creator: Uuid::nil(),
+            creation_timestamp: start,
+            xp: 10,
+            coins: 0,
+            enabled: true,
+            ty: ChallengesSubtaskType::CodingChallenge,
+            retired: false,
+            fingerprint: None,
+            review_status: ChallengesReviewStatus::Approved,
+            rejection_reason: None,
+            license: ChallengesLicense::AllRightsReserved,
+            attribution: None,
+            max_attempts: None,
+            reveal_solution: false,
+            time_limit: None,
+            unlock_price: None,
+            locked: false,
+        };
+        let assignment = challenges_cohort_assignments::Model {
+            cohort_id: Uuid::nil(),
+            task_id: task,
+            creation_timestamp: start,
+            due: Some(start + Duration::days(1)),
+        };
+        let member = |user_id| challenges_cohort_members::Model {
+            cohort_id: Uuid::nil(),
+            user_id,
+            join_timestamp: start,
+        };
+        let user_subtask = challenges_user_subtasks::Model {
+            user_id: u1,
+            subtask_id: subtask.id,
+            solved_timestamp: Some(start + Duration::days(2)),
+            rating: None,
+            rating_timestamp: None,
+            last_attempt_timestamp: Some(start + Duration::days(2)),
+            attempts: 3,
+            revealed_timestamp: None,
+        };
+        let submission = Uuid::from_u128(3);
+
+        let progress = compute_cohort_progress(
+            &[assignment],
+            &[member(u1), member(u2)],
+            std::slice::from_ref(&subtask),
+            &[user_subtask],
+            &HashMap::from([((u1, subtask.id), submission)]),
+        );
+        assert_eq!(progress.subtasks.len(), 1);
+        assert_eq!(progress.members[0].solved, 1);
+        assert_eq!(
+            progress.members[0].subtasks[0],
+            CohortProgressCell {
+                solved: true,
+                solved_timestamp: Some((start + Duration::days(2)).and_utc()),
+                late: true,
+                attempts: 3,
+                submission_id: Some(submission),
+            }
+        );
+        assert_eq!(progress.members[1].solved, 0);
+        assert!(!progress.members[1].subtasks[0].late);
+    }
+
+    #[test]
+    fn merge_due_dates() {
+        let [t1, t2, t3] = [1, 2, 3].map(Uuid::from_u128);
+        let start = NaiveDateTime::default();
+        let assignment = |task_id, days: Option<i64>| challenges_cohort_assignments::Model {
+            cohort_id: Uuid::new_v4(),
+            task_id,
+            creation_timestamp: start,
+            due: days.map(|d| start + Duration::days(d)),
+        };
+        let due = due_dates(&[
+            assignment(t1, Some(1)),
+            assignment(t1, Some(3)),
+            assignment(t2, Some(2)),
+            assignment(t2, None),
+            assignment(t3, Some(5)),
+        ]);
+        assert_eq!(
+            due,
+            HashMap::from([
+                (t1, start + Duration::days(3)),
+                (t3, start + Duration::days(5))
+            ])
+        );
+    }
+
+    #[test]
+    fn late_rewards() {
+        let config = DueDates { late_percent: 40 };
+        let due = NaiveDateTime::default();
+        assert_eq!(reward_percent(&config, None, due), 100);
+        assert_eq!(reward_percent(&config, Some(due), due), 100);
+        assert_eq!(
+            reward_percent(&config, Some(due), due + Duration::seconds(1)),
+            40
+        );
+    }
+}
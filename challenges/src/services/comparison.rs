@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+
+use entity::sea_orm_active_enums::ChallengesComparison;
+use serde::{Deserialize, Serialize};
+
+/// The strategy used to decide whether the output of a solution matches the
+/// expected output of a test.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    /// The output is checked by the checker program of the challenge.
+    Checker,
+    /// The output is checked by the evaluator.
+    #[default]
+    Evaluator,
+    /// The output must be exactly equal to the expected output.
+    Exact,
+    /// The outputs are compared token by token. Numbers may differ by at most
+    /// `epsilon`.
+    Float,
+    /// The outputs must consist of the same whitespace separated tokens.
+    Tokens,
+    /// The outputs must consist of the same lines, ignoring trailing whitespace
+    /// in each line and empty lines at the end.
+    TrimmedLines,
+    /// The outputs must consist of the same non-empty lines in any order,
+    /// ignoring leading and trailing whitespace in each line.
+    UnorderedLines,
+}
+
+impl From<ChallengesComparison> for Comparison {
+    fn from(value: ChallengesComparison) -> Self {
+        match value {
+            ChallengesComparison::Checker => Self::Checker,
+            ChallengesComparison::Evaluator => Self::Evaluator,
+            ChallengesComparison::Exact => Self::Exact,
+            ChallengesComparison::Float => Self::Float,
+            ChallengesComparison::Tokens => Self::Tokens,
+            ChallengesComparison::TrimmedLines => Self::TrimmedLines,
+            ChallengesComparison::UnorderedLines => Self::UnorderedLines,
+        }
+    }
+}
+
+/// Compare the output of a solution with the expected output. Returns a
+/// description of the first difference if they don't match.
+///
+/// Must not be called with [`Comparison::Checker`] or
+/// [`Comparison::Evaluator`].
+pub fn compare_output(
+    comparison: Comparison,
+    epsilon: f64,
+    expected: &str,
+    output: &str,
+) -> Result<(), String> {
+    match comparison {
+        Comparison::Checker | Comparison::Evaluator => {
+            unreachable!("outputs are compared by another program")
+        }
+        Comparison::Exact => {
+            if output == expected {
+                Ok(())
+            } else {
+                Err("output does not match the expected output".into())
+            }
+        }
+        Comparison::Float => compare_sequences(
+            "token",
+            expected.split_whitespace(),
+            output.split_whitespace(),
+            |expected, output| {
+                let Ok(expected_value) = expected.parse::<f64>() else {
+                    return compare_items(expected, output);
+                };
+                let Ok(output_value) = output.parse::<f64>() else {
+                    return Err("is not a number".into());
+                };
+                let difference = (expected_value - output_value).abs();
+                if difference.is_nan() || difference > epsilon {
+                    return Err(format!(
+                        "differs by more than {epsilon}: expected {expected}"
+                    ));
+                }
+                Ok(())
+            },
+        ),
+        Comparison::Tokens => compare_sequences(
+            "token",
+            expected.split_whitespace(),
+            output.split_whitespace(),
+            compare_items,
+        ),
+        Comparison::TrimmedLines => compare_sequences(
+            "line",
+            trimmed_lines(expected),
+            trimmed_lines(output),
+            compare_items,
+        ),
+        Comparison::UnorderedLines => {
+            compare_multisets(sorted_lines(expected), sorted_lines(output))
+        }
+    }
+}
+
+/// Compare two sequences item by item. `compare` returns a description of the
+/// difference if two items don't match.
+fn compare_sequences<'a>(
+    kind: &str,
+    expected: impl IntoIterator<Item = &'a str>,
+    output: impl IntoIterator<Item = &'a str>,
+    compare: impl Fn(&str, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut expected = expected.into_iter();
+    let mut output = output.into_iter();
+    let mut i = 0;
+    loop {
+        i += 1;
+        match (expected.next(), output.next()) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => return Err(format!("output ended before {kind} {i}")),
+            (None, Some(_)) => return Err(format!("unexpected {kind} {i} at the end of output")),
+            (Some(expected), Some(output)) => {
+                compare(expected, output).map_err(|err| format!("{kind} {i} {err}"))?
+            }
+        }
+    }
+}
+
+fn compare_items(expected: &str, output: &str) -> Result<(), String> {
+    if expected == output {
+        Ok(())
+    } else {
+        Err(format!("does not match: expected {expected}"))
+    }
+}
+
+/// Compare two sorted lists of lines.
+fn compare_multisets(expected: Vec<&str>, output: Vec<&str>) -> Result<(), String> {
+    let mut expected = expected.into_iter().peekable();
+    let mut output = output.into_iter().peekable();
+    loop {
+        let ordering = match (expected.peek(), output.peek()) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(e), Some(o)) => e.cmp(o),
+        };
+        match ordering {
+            Ordering::Equal => {
+                expected.next();
+                output.next();
+            }
+            Ordering::Less => {
+                let line = expected.next().unwrap_or_default();
+                return Err(format!("line `{line}` is missing in the output"));
+            }
+            Ordering::Greater => {
+                let line = output.next().unwrap_or_default();
+                return Err(format!("unexpected line `{line}` in the output"));
+            }
+        }
+    }
+}
+
+/// Return the lines of the text without trailing whitespace and without empty
+/// lines at the end.
+fn trimmed_lines(text: &str) -> Vec<&str> {
+    let mut lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Return the sorted non-empty lines of the text without leading and trailing
+/// whitespace.
+fn sorted_lines(text: &str) -> Vec<&str> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    lines.sort_unstable();
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        assert!(compare_output(Comparison::Exact, 0.0, "42\n", "42\n").is_ok());
+        assert!(compare_output(Comparison::Exact, 0.0, "42\n", "42").is_err());
+    }
+
+    #[test]
+    fn float() {
+        let cmp = |expected, output| compare_output(Comparison::Float, 1e-6, expected, output);
+        assert!(cmp("3.1415926 yes\n", "3.1415927  yes").is_ok());
+        assert!(cmp("1 2 3", "1.0000001\n2\n3\n").is_ok());
+        assert_eq!(
+            cmp("3.14 1", "3.15 1"),
+            Err("token 1 differs by more than 0.000001: expected 3.14".into())
+        );
+        assert_eq!(cmp("1 2", "1"), Err("output ended before token 2".into()));
+        assert_eq!(
+            cmp("1", "1 2"),
+            Err("unexpected token 2 at the end of output".into())
+        );
+        assert_eq!(cmp("1", "abc"), Err("token 1 is not a number".into()));
+        assert_eq!(
+            cmp("yes", "no"),
+            Err("token 1 does not match: expected yes".into())
+        );
+        assert!(cmp("1", "NaN").is_err());
+    }
+
+    #[test]
+    fn tokens() {
+        let cmp = |expected, output| compare_output(Comparison::Tokens, 0.0, expected, output);
+        assert!(cmp("1 2\n3\n", "  1\n2 3").is_ok());
+        assert_eq!(
+            cmp("1 2", "1 2.0"),
+            Err("token 2 does not match: expected 2".into())
+        );
+        assert_eq!(cmp("1 2", "1"), Err("output ended before token 2".into()));
+    }
+
+    #[test]
+    fn trimmed() {
+        let cmp =
+            |expected, output| compare_output(Comparison::TrimmedLines, 0.0, expected, output);
+        assert!(cmp("a b\nc\n", "a b  \nc\n\n\n").is_ok());
+        assert!(cmp("a b\r\nc", "a b\nc").is_ok());
+        assert_eq!(
+            cmp("a b\nc", "a  b\nc"),
+            Err("line 1 does not match: expected a b".into())
+        );
+        assert_eq!(
+            cmp("a\nb", "a\n\nb"),
+            Err("line 2 does not match: expected b".into())
+        );
+        assert_eq!(
+            cmp("a", "a\nb"),
+            Err("unexpected line 2 at the end of output".into())
+        );
+    }
+
+    #[test]
+    fn unordered() {
+        let cmp =
+            |expected, output| compare_output(Comparison::UnorderedLines, 0.0, expected, output);
+        assert!(cmp("a\nb\nb\n", " b\n\na\nb  ").is_ok());
+        assert_eq!(
+            cmp("a\nb\nb", "b\na"),
+            Err("line `b` is missing in the output".into())
+        );
+        assert_eq!(
+            cmp("a\nc", "c\nb\na"),
+            Err("unexpected line `b` in the output".into())
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Filtering of profanity and personal data in texts created by users.
+//!
+//! Depending on the configured action, violations are either reported to the
+//! user (who has to fix them) or masked with asterisks before the text is
+//! stored. Texts created by admins are never checked.
+
+use lib::{
+    auth::User,
+    config::challenges::{ContentFilter, ContentFilterAction},
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemas::challenges::subtasks::{ContentViolation, ContentViolationKind};
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap()
+});
+static PHONE_NUMBER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:\+\d{1,3}|\(0\d{1,5}\)|\b0\d{1,5})(?:[ /-]?\d{2,}){1,4}").unwrap()
+});
+static WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\w+").unwrap());
+
+/// Checks the texts of a single request.
+pub struct ContentChecker<'a> {
+    config: &'a ContentFilter,
+    bypass: bool,
+    violations: Vec<ContentViolation>,
+}
+
+impl<'a> ContentChecker<'a> {
+    pub fn new(config: &'a ContentFilter, user: &User) -> Self {
+        Self {
+            config,
+            bypass: !config.enabled || user.admin,
+            violations: Vec::new(),
+        }
+    }
+
+    /// Check a text and return it, with all violations masked if the filter
+    /// is configured to mask them.
+    pub fn check(&mut self, field: &str, text: String) -> String {
+        if self.bypass {
+            return text;
+        }
+        let spans = find_violations(self.config, &text);
+        if spans.is_empty() {
+            return text;
+        }
+        match self.config.action {
+            ContentFilterAction::Mask => mask(&text, &spans),
+            ContentFilterAction::Reject => {
+                self.violations
+                    .extend(
+                        spans
+                            .into_iter()
+                            .map(|(start, end, kind)| ContentViolation {
+                                field: field.into(),
+                                kind,
+                                text: text[start..end].into(),
+                            }),
+                    );
+                text
+            }
+        }
+    }
+
+    /// Check a list of texts (e.g. the answers of a question).
+    pub fn check_all(&mut self, field: &str, texts: Vec<String>) -> Vec<String> {
+        texts
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| self.check(&format!("{field}.{i}"), text))
+            .collect()
+    }
+
+    /// Return the violations found in all checked texts.
+    pub fn finish(self) -> Result<(), Vec<ContentViolation>> {
+        match self.violations.is_empty() {
+            true => Ok(()),
+            false => Err(self.violations),
+        }
+    }
+}
+
+/// Return the byte ranges of all violations in the text, ordered by their
+/// position. Overlapping violations are reported only once.
+fn find_violations(
+    config: &ContentFilter,
+    text: &str,
+) -> Vec<(usize, usize, ContentViolationKind)> {
+    let mut spans = Vec::new();
+    if !config.words.is_empty() {
+        spans.extend(
+            WORD.find_iter(text)
+                .filter(|m| {
+                    config
+                        .words
+                        .iter()
+                        .any(|word| word.to_lowercase() == m.as_str().to_lowercase())
+                })
+                .map(|m| (m.start(), m.end(), ContentViolationKind::Profanity)),
+        );
+    }
+    if config.pii {
+        spans.extend(
+            EMAIL
+                .find_iter(text)
+                .map(|m| (m.start(), m.end(), ContentViolationKind::Email)),
+        );
+        spans.extend(
+            PHONE_NUMBER
+                .find_iter(text)
+                .filter(|m| {
+                    let digits = m.as_str().chars().filter(char::is_ascii_digit).count();
+                    (7..=15).contains(&digits)
+                })
+                .map(|m| (m.start(), m.end(), ContentViolationKind::PhoneNumber)),
+        );
+    }
+    spans.sort_by_key(|&(start, end, _)| (start, std::cmp::Reverse(end)));
+    let mut out: Vec<(usize, usize, ContentViolationKind)> = Vec::new();
+    for span in spans {
+        match out.last() {
+            Some(&(_, end, _)) if span.0 < end => {}
+            _ => out.push(span),
+        }
+    }
+    out
+}
+
+/// Replace every character in the given byte ranges with an asterisk.
+fn mask(text: &str, spans: &[(usize, usize, ContentViolationKind)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for &(start, end, _) in spans {
+        out.push_str(&text[pos..start]);
+        out.extend(text[start..end].chars().map(|_| '*'));
+        pos = end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(action: ContentFilterAction) -> ContentFilter {
+        ContentFilter {
+            enabled: true,
+            action,
+            words: vec!["Darn".into()],
+            pii: true,
+        }
+    }
+
+    fn user(admin: bool) -> User {
+        User {
+            id: Default::default(),
+            email_verified: true,
+            admin,
+            organization: None,
+        }
+    }
+
+    #[test]
+    fn test_find_violations() {
+        let config = config(ContentFilterAction::Reject);
+        let kinds = |text| {
+            find_violations(&config, text)
+                .into_iter()
+                .map(|(start, end, kind)| (&text[start..end], kind))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(kinds("The answer is 42."), []);
+        assert_eq!(
+            kinds("darn, DARN and darned"),
+            [
+                ("darn", ContentViolationKind::Profanity),
+                ("DARN", ContentViolationKind::Profanity)
+            ]
+        );
+        assert_eq!(
+            kinds("Write to jane.doe@example.com!"),
+            [("jane.doe@example.com", ContentViolationKind::Email)]
+        );
+        assert_eq!(
+            kinds("Call +49 170 1234567 or (030) 123456"),
+            [
+                ("+49 170 1234567", ContentViolationKind::PhoneNumber),
+                ("(030) 123456", ContentViolationKind::PhoneNumber)
+            ]
+        );
+        assert_eq!(
+            kinds("Call 0170/1234567, not 12345678"),
+            [("0170/1234567", ContentViolationKind::PhoneNumber)]
+        );
+        assert_eq!(kinds("0.5 0.25 0.125 and 0 1 1 2 3 5 8 13"), []);
+    }
+
+    #[test]
+    fn test_mask() {
+        let config = config(ContentFilterAction::Mask);
+        let mut checker = ContentChecker::new(&config, &user(false));
+        assert_eq!(
+            checker.check("question", "Darn, mail jane@example.com".into()),
+            "****, mail ****************"
+        );
+        assert_eq!(checker.finish(), Ok(()));
+    }
+
+    #[test]
+    fn test_reject() {
+        let config = config(ContentFilterAction::Reject);
+        let mut checker = ContentChecker::new(&config, &user(false));
+        checker.check("question", "What is 1 + 1?".into());
+        checker.check_all("answers", vec!["2".into(), "darn".into()]);
+        assert_eq!(
+            checker.finish(),
+            Err(vec![ContentViolation {
+                field: "answers.1".into(),
+                kind: ContentViolationKind::Profanity,
+                text: "darn".into(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_admin_bypass() {
+        let config = config(ContentFilterAction::Mask);
+        let mut checker = ContentChecker::new(&config, &user(true));
+        assert_eq!(checker.check("question", "darn".into()), "darn");
+        assert_eq!(checker.finish(), Ok(()));
+    }
+}
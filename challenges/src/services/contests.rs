@@ -0,0 +1,456 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::NaiveDateTime;
+use entity::{challenges_contests, challenges_subtasks, challenges_user_subtasks};
+use schemas::challenges::contests::{ScoreboardEntry, ScoreboardMessage};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, QueryFilter,
+    Statement,
+};
+use sqlx::postgres::PgListener;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// Number of events the hub remembers for resuming scoreboard connections.
+const HISTORY_SIZE: usize = 1024;
+
+/// The Postgres channel on which solved subtasks are announced.
+const SCOREBOARD_CHANNEL: &str = "challenges_scoreboard";
+
+/// Delay before the first attempt to reconnect to the scoreboard channel.
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay between two attempts to reconnect to the scoreboard channel.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Fans out scoreboard updates to all connected scoreboard websockets.
+///
+/// The scoreboards of contests with subscribers are cached, so each score event
+/// (e.g. a judged submission) is only evaluated and serialized once, no matter
+/// how many websockets are connected.
+pub struct ScoreboardHub {
+    db: DatabaseConnection,
+    sender: broadcast::Sender<ScoreboardUpdate>,
+    history: Mutex<History>,
+    scoreboards: tokio::sync::Mutex<HashMap<Uuid, CachedScoreboard>>,
+}
+
+#[derive(Default)]
+struct History {
+    next_seq: u64,
+    events: VecDeque<ScoreEvent>,
+}
+
+struct CachedScoreboard {
+    contest: challenges_contests::Model,
+    entries: Vec<ScoreboardEntry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreEvent {
+    pub seq: u64,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+}
+
+/// The changes of the scoreboard of a contest.
+#[derive(Debug, Clone)]
+pub struct ScoreboardUpdate {
+    pub contest_id: Uuid,
+    /// The serialized `update` message.
+    pub message: Arc<str>,
+}
+
+impl ScoreboardHub {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            sender: broadcast::channel(HISTORY_SIZE).0,
+            history: Default::default(),
+            scoreboards: Default::default(),
+        }
+    }
+
+    /// Notify all subscribers that the score of a user in a task might have
+    /// changed.
+    pub async fn publish(&self, task_id: Uuid, user_id: Uuid) {
+        let seq = {
+            let mut history = self.history.lock().unwrap();
+            let event = ScoreEvent {
+                seq: history.next_seq,
+                task_id,
+                user_id,
+            };
+            history.next_seq += 1;
+            history.events.push_back(event);
+            if history.events.len() > HISTORY_SIZE {
+                history.events.pop_front();
+            }
+            event.seq
+        };
+
+        self.update_scoreboards(Some(task_id), seq + 1).await;
+    }
+
+    /// Recompute the cached scoreboards (of the given task or all of them) and
+    /// send the changed entries to the subscribers.
+    async fn update_scoreboards(&self, task_id: Option<Uuid>, resume_seq: u64) {
+        let mut scoreboards = self.scoreboards.lock().await;
+        if self.sender.receiver_count() == 0 {
+            scoreboards.clear();
+            return;
+        }
+        for (&contest_id, cached) in scoreboards
+            .iter_mut()
+            .filter(|(_, cached)| task_id.map_or(true, |x| cached.contest.task_id == x))
+        {
+            let entries = match get_scoreboard(&self.db, &cached.contest).await {
+                Ok(entries) => entries,
+                Err(err) => {
+                    error!("failed to update scoreboard of contest {contest_id}: {err}");
+                    continue;
+                }
+            };
+            let changed = diff_scoreboard(&cached.entries, &entries);
+            cached.entries = entries;
+            if changed.is_empty() {
+                continue;
+            }
+            let message = ScoreboardMessage::Update {
+                entries: changed,
+                resume_token: resume_seq.to_string(),
+            };
+            match serde_json::to_string(&message) {
+                Ok(message) => {
+                    // sending only fails if there are no subscribers
+                    self.sender
+                        .send(ScoreboardUpdate {
+                            contest_id,
+                            message: message.into(),
+                        })
+                        .ok();
+                }
+                Err(err) => error!("failed to serialize scoreboard update: {err}"),
+            }
+        }
+    }
+
+    /// Return the current scoreboard of a contest. The scoreboard is kept up to
+    /// date as long as there are subscribers.
+    pub async fn scoreboard(
+        &self,
+        contest: &challenges_contests::Model,
+    ) -> Result<Vec<ScoreboardEntry>, DbErr> {
+        let mut scoreboards = self.scoreboards.lock().await;
+        if let Some(cached) = scoreboards.get(&contest.id) {
+            return Ok(cached.entries.clone());
+        }
+        let entries = get_scoreboard(&self.db, contest).await?;
+        scoreboards.insert(
+            contest.id,
+            CachedScoreboard {
+                contest: contest.clone(),
+                entries: entries.clone(),
+            },
+        );
+        Ok(entries)
+    }
+
+    /// Publish the score events announced by any instance of the challenges
+    /// service. If the connection is lost, the listener reconnects with
+    /// exponential backoff and afterwards refreshes all cached scoreboards, as
+    /// events might have been missed in the meantime.
+    pub async fn forward_score_events(self: Arc<Self>, database_url: String) {
+        let mut delay = MIN_RECONNECT_DELAY;
+        let mut reconnect = false;
+        loop {
+            let result: sqlx::Result<()> = async {
+                let mut listener = PgListener::connect(&database_url).await?;
+                listener.listen(SCOREBOARD_CHANNEL).await?;
+                delay = MIN_RECONNECT_DELAY;
+                if reconnect {
+                    let seq = self.history.lock().unwrap().next_seq;
+                    self.update_scoreboards(None, seq).await;
+                }
+                reconnect = true;
+                loop {
+                    let notification = listener.recv().await?;
+                    let payload = notification.payload();
+                    let Some((task_id, user_id)) = payload
+                        .split_once(':')
+                        .and_then(|(t, u)| Some((t.parse().ok()?, u.parse().ok()?)))
+                    else {
+                        warn!("invalid scoreboard event: {payload}");
+                        continue;
+                    };
+                    self.publish(task_id, user_id).await;
+                }
+            }
+            .await;
+            if let Err(err) = result {
+                error!("failed to receive scoreboard events: {err}");
+            }
+            warn!("reconnecting to scoreboard events in {delay:?}");
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ScoreboardUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// Return the resume token that refers to the current state.
+    pub fn resume_token(&self) -> String {
+        self.history.lock().unwrap().next_seq.to_string()
+    }
+
+    /// Return all events that have been published since the given resume
+    /// token was created, or `None` if the token is invalid or too old.
+    pub fn replay(&self, resume_token: &str) -> Option<Vec<ScoreEvent>> {
+        let seq = resume_token.parse::<u64>().ok()?;
+        let history = self.history.lock().unwrap();
+        if seq > history.next_seq {
+            return None;
+        }
+        let oldest = history
+            .events
+            .front()
+            .map(|x| x.seq)
+            .unwrap_or(history.next_seq);
+        if seq < oldest {
+            return None;
+        }
+        Some(
+            history
+                .events
+                .iter()
+                .filter(|x| x.seq >= seq)
+                .copied()
+                .collect(),
+        )
+    }
+}
+
+/// Announce to all instances of the challenges service that the score of a
+/// user in a task might have changed. The announcement is only delivered once
+/// the transaction has been committed.
+pub async fn announce_score_event(
+    db: &impl ConnectionTrait,
+    task_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "select pg_notify($1, $2)",
+        [
+            SCOREBOARD_CHANNEL.into(),
+            format!("{task_id}:{user_id}").into(),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+pub async fn get_scoreboard(
+    db: &impl ConnectionTrait,
+    contest: &challenges_contests::Model,
+) -> Result<Vec<ScoreboardEntry>, DbErr> {
+    let subtasks = challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::TaskId.eq(contest.task_id))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(false))
+        .all(db)
+        .await?;
+    let user_subtasks = challenges_user_subtasks::Entity::find()
+        .filter(challenges_user_subtasks::Column::SubtaskId.is_in(subtasks.iter().map(|x| x.id)))
+        .filter(
+            challenges_user_subtasks::Column::SolvedTimestamp.between(contest.start, contest.end),
+        )
+        .all(db)
+        .await?;
+    Ok(compute_scoreboard(contest.start, &subtasks, &user_subtasks))
+}
+
+/// Compute the ranked scoreboard from all solves of the contest problems.
+///
+/// Users are ranked by score (descending) and penalty (ascending).
+pub fn compute_scoreboard(
+    start: NaiveDateTime,
+    subtasks: &[challenges_subtasks::Model],
+    user_subtasks: &[challenges_user_subtasks::Model],
+) -> Vec<ScoreboardEntry> {
+    let xp = subtasks
+        .iter()
+        .map(|x| (x.id, x.xp as u64))
+        .collect::<HashMap<_, _>>();
+
+    let mut entries = HashMap::<Uuid, ScoreboardEntry>::new();
+    for user_subtask in user_subtasks {
+        let (Some(solved_timestamp), Some(&xp)) = (
+            user_subtask.solved_timestamp,
+            xp.get(&user_subtask.subtask_id),
+        ) else {
+            continue;
+        };
+        let entry = entries
+            .entry(user_subtask.user_id)
+            .or_insert_with(|| ScoreboardEntry {
+                rank: 0,
+                user_id: user_subtask.user_id,
+                solved: 0,
+                score: 0,
+                penalty: 0,
+            });
+        entry.solved += 1;
+        entry.score += xp;
+        entry.penalty += (solved_timestamp - start).num_minutes().max(0) as u64;
+    }
+
+    let mut entries = entries.into_values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.penalty.cmp(&b.penalty))
+            .then(a.user_id.cmp(&b.user_id))
+    });
+    let mut last = None;
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let key = (entry.score, entry.penalty);
+        entry.rank = match last {
+            Some((k, rank)) if k == key => rank,
+            _ => i as u64 + 1,
+        };
+        last = Some((key, entry.rank));
+    }
+    entries
+}
+
+/// Return all entries of `new` that are not contained in `old`.
+pub fn diff_scoreboard(old: &[ScoreboardEntry], new: &[ScoreboardEntry]) -> Vec<ScoreboardEntry> {
+    let old = old
+        .iter()
+        .map(|x| (x.user_id, x))
+        .collect::<HashMap<_, _>>();
+    new.iter()
+        .filter(|x| old.get(&x.user_id) != Some(x))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use entity::sea_orm_active_enums::{
+        ChallengesLicense, ChallengesReviewStatus, ChallengesSubtaskType,
+    };
+
+    use super::*;
+
+    fn subtask(xp: i64) -> challenges_subtasks::Model {
+        challenges_subtasks::Model {
+            id: Uuid::new_v4(),
+            task_id: Uuid::nil(),
+            creator: Uuid::nil(),
+            creation_timestamp: NaiveDateTime::default(),
+            xp,
+            coins: 0,
+            enabled: true,
+            ty: ChallengesSubtaskType::CodingChallenge,
+            retired: false,
+            fingerprint: None,
+            review_status: ChallengesReviewStatus::Approved,
+            rejection_reason: None,
+            license: ChallengesLicense::AllRightsReserved,
+            attribution: None,
+            max_attempts: None,
+            reveal_solution: false,
+            time_limit: None,
+            unlock_price: None,
+            locked: false,
+        }
+    }
+
+    fn solve(
+        user_id: Uuid,
+        subtask: &challenges_subtasks::Model,
+        minutes: i64,
+    ) -> challenges_user_subtasks::Model {
+        challenges_user_subtasks::Model {
+            user_id,
+            subtask_id: subtask.id,
+            solved_timestamp: Some(NaiveDateTime::default() + Duration::minutes(minutes)),
+            rating: None,
+            rating_timestamp: None,
+            last_attempt_timestamp: None,
+            attempts: 1,
+            revealed_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_compute_scoreboard() {
+        let a = subtask(10);
+        let b = subtask(20);
+        let [u1, u2, u3] = [1, 2, 3].map(Uuid::from_u128);
+        let entries = compute_scoreboard(
+            NaiveDateTime::default(),
+            &[a.clone(), b.clone()],
+            &[
+                solve(u1, &a, 5),
+                solve(u2, &b, 30),
+                solve(u3, &a, 3),
+                solve(u3, &b, 10),
+                solve(u1, &subtask(100), 1),
+            ],
+        );
+        let ranking = entries
+            .iter()
+            .map(|x| (x.rank, x.user_id, x.solved, x.score, x.penalty))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranking,
+            [(1, u3, 2, 30, 13), (2, u2, 1, 20, 30), (3, u1, 1, 10, 5)]
+        );
+    }
+
+    #[test]
+    fn test_compute_scoreboard_ties() {
+        let a = subtask(10);
+        let [u1, u2, u3] = [1, 2, 3].map(Uuid::from_u128);
+        let entries = compute_scoreboard(
+            NaiveDateTime::default(),
+            std::slice::from_ref(&a),
+            &[solve(u2, &a, 5), solve(u1, &a, 5), solve(u3, &a, 6)],
+        );
+        let ranks = entries
+            .iter()
+            .map(|x| (x.rank, x.user_id))
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, [(1, u1), (1, u2), (3, u3)]);
+    }
+
+    #[test]
+    fn test_diff_scoreboard() {
+        let entry = |rank, user| ScoreboardEntry {
+            rank,
+            user_id: Uuid::from_u128(user),
+            solved: 1,
+            score: 10,
+            penalty: 0,
+        };
+        let old = [entry(1, 1), entry(2, 2)];
+        let new = [entry(1, 3), entry(2, 1), entry(3, 2)];
+        assert_eq!(diff_scoreboard(&old, &new), new);
+        assert_eq!(diff_scoreboard(&new, &new), []);
+        assert_eq!(
+            diff_scoreboard(&old, &[entry(1, 1), entry(2, 2), entry(3, 3)]),
+            [entry(3, 3)]
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Weekly email digest for creators of subtasks.
+
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use entity::{
+    challenges_creator_digests, challenges_notification_preferences, challenges_subtask_reports,
+    challenges_subtasks, challenges_user_subtasks, sea_orm_active_enums::ChallengesRating,
+};
+use lib::{config::SharedConfig, services::Services};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, Set, TransactionTrait,
+};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+/// Statistics about the subtasks of a creator in one week.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CreatorStats {
+    /// Number of times other users have solved one of the subtasks.
+    pub solves: u64,
+    pub positive_ratings: u64,
+    pub neutral_ratings: u64,
+    pub negative_ratings: u64,
+    pub reports: u64,
+}
+
+/// Return the start of the week (monday 00:00 UTC) which contains `now`.
+pub fn week_start(now: DateTime<Utc>) -> NaiveDateTime {
+    let date = now.date_naive() - chrono::Duration::days(now.weekday().num_days_from_monday() as _);
+    date.and_hms_opt(0, 0, 0).unwrap()
+}
+
+/// Aggregate solves, ratings and reports in `[start, end)` per creator.
+/// Solves and ratings of creators on their own subtasks are ignored.
+pub fn aggregate_stats(
+    creators: &HashMap<Uuid, Uuid>,
+    user_subtasks: &[challenges_user_subtasks::Model],
+    reports: &[challenges_subtask_reports::Model],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> HashMap<Uuid, CreatorStats> {
+    let in_period = |x: Option<NaiveDateTime>| x.is_some_and(|x| x >= start && x < end);
+    let mut stats = HashMap::<Uuid, CreatorStats>::new();
+    for user_subtask in user_subtasks {
+        let Some(&creator) = creators.get(&user_subtask.subtask_id) else {
+            continue;
+        };
+        if creator == user_subtask.user_id {
+            continue;
+        }
+        if in_period(user_subtask.solved_timestamp) {
+            stats.entry(creator).or_default().solves += 1;
+        }
+        if in_period(user_subtask.rating_timestamp) {
+            let entry = stats.entry(creator).or_default();
+            match user_subtask.rating {
+                Some(ChallengesRating::Positive) => entry.positive_ratings += 1,
+                Some(ChallengesRating::Neutral) => entry.neutral_ratings += 1,
+                Some(ChallengesRating::Negative) => entry.negative_ratings += 1,
+                // reports reset the rating
+                None => {}
+            }
+        }
+    }
+    for report in reports {
+        if !in_period(Some(report.timestamp)) {
+            continue;
+        }
+        if let Some(&creator) = creators.get(&report.subtask_id) {
+            stats.entry(creator).or_default().reports += 1;
+        }
+    }
+    stats.retain(|_, x| x != &CreatorStats::default());
+    stats
+}
+
+/// Render the plain text body of a digest email.
+pub fn render_digest(stats: &CreatorStats, start: NaiveDateTime, end: NaiveDateTime) -> String {
+    let mut out = format!(
+        "Your subtasks from {} to {}:\n\n",
+        start.format("%Y-%m-%d"),
+        (end - chrono::Duration::days(1)).format("%Y-%m-%d")
+    );
+    writeln!(out, "Solves: {}", stats.solves).unwrap();
+    writeln!(
+        out,
+        "Ratings: {} positive, {} neutral, {} negative",
+        stats.positive_ratings, stats.neutral_ratings, stats.negative_ratings
+    )
+    .unwrap();
+    writeln!(out, "Reports: {}", stats.reports).unwrap();
+    if stats.reports > 0 {
+        out.push_str("\nReported subtasks are disabled until they have been revised.\n");
+    }
+    out
+}
+
+/// Sends the weekly digest to all creators whose subtasks have been solved,
+/// rated or reported in the last week.
+pub struct CreatorDigestJob {
+    db: DatabaseConnection,
+    services: Services,
+    config: SharedConfig,
+}
+
+impl CreatorDigestJob {
+    pub fn new(db: DatabaseConnection, services: Services, config: SharedConfig) -> Self {
+        Self {
+            db,
+            services,
+            config,
+        }
+    }
+
+    /// Send digests until the process exits.
+    pub async fn run(self) {
+        loop {
+            if self.config.load().challenges.creator_digest.enabled {
+                match self.send_digests().await {
+                    Ok(None) => {}
+                    Ok(Some(n)) => debug!("sent creator digest to {n} user(s)"),
+                    Err(err) => error!("failed to send creator digests: {err}"),
+                }
+            }
+            let poll_interval = self.config.load().challenges.creator_digest.poll_interval;
+            tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+        }
+    }
+
+    /// Send the digest for the last week unless it has already been sent.
+    /// Returns the number of digests sent or `None` if another run (or
+    /// another instance) has already claimed the week.
+    async fn send_digests(&self) -> Result<Option<usize>, DbErr> {
+        let end = week_start(Utc::now());
+        let start = end - chrono::Duration::days(7);
+
+        // the claim is only committed after all digests have been sent, so
+        // other instances wait here instead of sending the digest again
+        let txn = self.db.begin().await?;
+        let claimed =
+            challenges_creator_digests::Entity::insert(challenges_creator_digests::ActiveModel {
+                period_start: Set(start),
+                timestamp: Set(Utc::now().naive_utc()),
+            })
+            .on_conflict(
+                OnConflict::column(challenges_creator_digests::Column::PeriodStart)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+        if claimed == 0 {
+            return Ok(None);
+        }
+
+        let user_subtasks = challenges_user_subtasks::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(challenges_user_subtasks::Column::SolvedTimestamp.between(start, end))
+                    .add(challenges_user_subtasks::Column::RatingTimestamp.between(start, end)),
+            )
+            .all(&txn)
+            .await?;
+        let reports = challenges_subtask_reports::Entity::find()
+            .filter(challenges_subtask_reports::Column::Timestamp.between(start, end))
+            .all(&txn)
+            .await?;
+        let creators = challenges_subtasks::Entity::find()
+            .filter(
+                challenges_subtasks::Column::Id.is_in(
+                    user_subtasks
+                        .iter()
+                        .map(|x| x.subtask_id)
+                        .chain(reports.iter().map(|x| x.subtask_id)),
+                ),
+            )
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|x| (x.id, x.creator))
+            .collect();
+        let mut stats = aggregate_stats(&creators, &user_subtasks, &reports, start, end);
+
+        let opted_out = challenges_notification_preferences::Entity::find()
+            .filter(challenges_notification_preferences::Column::CreatorDigest.eq(false))
+            .filter(
+                challenges_notification_preferences::Column::UserId.is_in(stats.keys().copied()),
+            )
+            .all(&txn)
+            .await?;
+        for preferences in opted_out {
+            stats.remove(&preferences.user_id);
+        }
+
+        let mut sent = 0;
+        for (creator, stats) in &stats {
+            let body = render_digest(stats, start, end);
+            match self
+                .services
+                .email
+                .send_email(*creator, "Your weekly challenges digest", &body)
+                .await
+            {
+                Ok(()) => sent += 1,
+                Err(err) => error!("failed to send creator digest to {creator}: {err}"),
+            }
+        }
+        txn.commit().await?;
+        Ok(Some(sent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+
+    use super::*;
+
+    fn user_subtask(
+        user_id: Uuid,
+        subtask_id: Uuid,
+        timestamp: NaiveDateTime,
+        rating: Option<ChallengesRating>,
+    ) -> challenges_user_subtasks::Model {
+        challenges_user_subtasks::Model {
+            user_id,
+            subtask_id,
+            solved_timestamp: Some(timestamp),
+            rating,
+            rating_timestamp: Some(timestamp),
+            last_attempt_timestamp: Some(timestamp),
+            attempts: 1,
+            revealed_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_week_start() {
+        // 2023-11-30 is a thursday
+        let now = Utc.with_ymd_and_hms(2023, 11, 30, 15, 30, 0).unwrap();
+        assert_eq!(
+            week_start(now),
+            NaiveDate::from_ymd_opt(2023, 11, 27)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        );
+        let monday = Utc.with_ymd_and_hms(2023, 11, 27, 0, 0, 0).unwrap();
+        assert_eq!(week_start(monday), monday.naive_utc());
+    }
+
+    #[test]
+    fn test_aggregate_stats() {
+        let creator = Uuid::from_u128(1);
+        let user = Uuid::from_u128(2);
+        let subtask = Uuid::from_u128(3);
+        let start = NaiveDate::from_ymd_opt(2023, 11, 20)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let end = start + chrono::Duration::days(7);
+        let creators = HashMap::from([(subtask, creator)]);
+
+        let user_subtasks = [
+            user_subtask(user, subtask, start, Some(ChallengesRating::Positive)),
+            user_subtask(Uuid::from_u128(4), subtask, end, None),
+            user_subtask(creator, subtask, start, Some(ChallengesRating::Positive)),
+        ];
+        let reports = [challenges_subtask_reports::Model {
+            id: Uuid::nil(),
+            subtask_id: subtask,
+            user_id: Some(user),
+            timestamp: start,
+            reason: entity::sea_orm_active_enums::ChallengesReportReason::Wrong,
+            comment: String::new(),
+        }];
+
+        let stats = aggregate_stats(&creators, &user_subtasks, &reports, start, end);
+        assert_eq!(
+            stats,
+            HashMap::from([(
+                creator,
+                CreatorStats {
+                    solves: 1,
+                    positive_ratings: 1,
+                    neutral_ratings: 0,
+                    negative_ratings: 0,
+                    reports: 1,
+                }
+            )])
+        );
+        assert!(aggregate_stats(&creators, &user_subtasks, &[], end, end).is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::Utc;
+use entity::challenges_evaluator_versions;
+use schemas::challenges::coding_challenges::{DiffLine, DiffLineKind};
+use sea_orm::{ActiveModelTrait, DatabaseTransaction, DbErr, Set};
+use uuid::Uuid;
+
+/// Store an evaluator as a new version. Must be called whenever the evaluator
+/// of a coding challenge is created or changed.
+pub async fn record_evaluator_version(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+    version: i32,
+    evaluator: &str,
+    creator: Uuid,
+) -> Result<challenges_evaluator_versions::Model, DbErr> {
+    challenges_evaluator_versions::ActiveModel {
+        subtask_id: Set(subtask_id),
+        version: Set(version),
+        evaluator: Set(evaluator.into()),
+        creator: Set(creator),
+        timestamp: Set(Utc::now().naive_utc()),
+    }
+    .insert(db)
+    .await
+}
+
+/// Compute a line based diff between two versions of an evaluator.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lcs[i][j] = length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, line: &str| DiffLine {
+        kind,
+        line: line.into(),
+    };
+    let mut out = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            out.push(line(DiffLineKind::Unchanged, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(line(DiffLineKind::Removed, old[i]));
+            i += 1;
+        } else {
+            out.push(line(DiffLineKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|x| line(DiffLineKind::Removed, x)));
+    out.extend(new[j..].iter().map(|x| line(DiffLineKind::Added, x)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = |old, new| {
+            diff_lines(old, new)
+                .into_iter()
+                .map(|x| {
+                    let prefix = match x.kind {
+                        DiffLineKind::Unchanged => ' ',
+                        DiffLineKind::Removed => '-',
+                        DiffLineKind::Added => '+',
+                    };
+                    format!("{prefix}{}", x.line)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(diff("a\nb\nc", "a\nb\nc"), [" a", " b", " c"]);
+        assert_eq!(
+            diff("a\nb\nc", "a\nx\nc\nd"),
+            [" a", "-b", "+x", " c", "+d"]
+        );
+        assert_eq!(diff("", "a"), ["+a"]);
+        assert_eq!(diff("a\nb", ""), ["-a", "-b"]);
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use lib::redis::RedisConnection;
+use redis::RedisResult;
+use uuid::Uuid;
+
+/// Check the counters of all keys and increment them if none of them has
+/// reached its limit. Returns 0 if the counters have been incremented and the
+/// number of milliseconds until the earliest possible retry otherwise.
+const ACQUIRE_SCRIPT: &str = r"
+local wait = 0
+for i, key in ipairs(KEYS) do
+    if tonumber(redis.call('GET', key) or 0) >= tonumber(ARGV[i]) then
+        wait = math.max(wait, redis.call('PTTL', key))
+    end
+end
+if wait > 0 then
+    return wait
+end
+for i, key in ipairs(KEYS) do
+    if redis.call('INCR', key) == 1 then
+        redis.call('PEXPIRE', key, ARGV[#KEYS + 1])
+    end
+end
+return 0
+";
+
+/// Limits how often the examples of coding challenges are generated.
+///
+/// Each user and each challenge has a counter in redis which is reset after
+/// the configured window has elapsed.
+#[derive(Debug, Clone)]
+pub struct ExampleLimiter {
+    redis: RedisConnection,
+    key: String,
+}
+
+impl ExampleLimiter {
+    pub fn new(redis: RedisConnection) -> Self {
+        Self {
+            redis,
+            key: "challenges:example_generations".into(),
+        }
+    }
+
+    /// Count an example generation of the user for the challenge. Returns the
+    /// number of seconds until the next generation is allowed if one of the
+    /// limits has been reached.
+    pub async fn acquire(
+        &self,
+        user_id: Uuid,
+        subtask_id: Uuid,
+        user_limit: u64,
+        challenge_limit: u64,
+        window: Duration,
+    ) -> RedisResult<Option<u64>> {
+        let wait: u64 = redis::cmd("EVAL")
+            .arg(ACQUIRE_SCRIPT)
+            .arg(2)
+            .arg(format!("{}:user:{user_id}", self.key))
+            .arg(format!("{}:challenge:{subtask_id}", self.key))
+            .arg(user_limit)
+            .arg(challenge_limit)
+            .arg(window.as_millis() as u64)
+            .query_async(&mut self.redis.clone())
+            .await?;
+        Ok((wait > 0).then(|| wait.div_ceil(1000)))
+    }
+}
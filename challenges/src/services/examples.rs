@@ -0,0 +1,154 @@
+//! Examples of coding challenges.
+//!
+//! Generating the examples of a challenge runs the evaluator and the solution
+//! in the sandbox for every example, so the generated examples are cached and
+//! uncached generations are rate limited. The cache key contains the evaluator
+//! and the solution, so updating either of them invalidates the cached
+//! examples. Challenges that have used up their monthly sandbox budget are
+//! throttled as well.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use entity::challenges_coding_challenges;
+use fnct::key;
+use lib::{auth::User, config::challenges::CodingChallenges};
+use schemas::challenges::coding_challenges::Example;
+use sea_orm::{DatabaseTransaction, DbErr};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::{
+    example_limiter::ExampleLimiter,
+    judge::{self, Judge},
+    sandbox_usage::budget_exhausted,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedExamples {
+    pub examples: Vec<Example>,
+    /// The time the examples have been generated at.
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub enum GetExamplesError {
+    /// Contains the number of seconds to wait.
+    TooManyRequests(u64),
+    EvaluatorFailed,
+    ExampleGenerationFailed,
+    Judge(Box<judge::Error>),
+    Redis(redis::RedisError),
+    Database(DbErr),
+}
+
+/// Return the examples of a coding challenge, generating them if they are not
+/// cached yet. Admins and the creator of the challenge are not rate limited,
+/// but the monthly sandbox budget of the challenge applies to everyone.
+pub async fn get_examples(
+    db: &DatabaseTransaction,
+    judge: &Judge<'_>,
+    limiter: &ExampleLimiter,
+    config: &CodingChallenges,
+    user: &User,
+    creator: bool,
+    cc: &challenges_coding_challenges::Model,
+) -> Result<Result<CachedExamples, GetExamplesError>, judge::Error> {
+    Ok(judge
+        .cache
+        .cached_result(
+            key!(
+                "examples",
+                judge.evaluator,
+                judge.protocol_version,
+                judge.comparison,
+                judge.epsilon.map(|x| x.to_string()),
+                judge.reference,
+                judge.checker,
+                judge.build_flags,
+                cc.time_limit,
+                cc.memory_limit
+            ),
+            &[],
+            None,
+            || generate_examples(db, judge, limiter, config, user, creator, cc),
+        )
+        .await?)
+}
+
+async fn generate_examples(
+    db: &DatabaseTransaction,
+    judge: &Judge<'_>,
+    limiter: &ExampleLimiter,
+    config: &CodingChallenges,
+    user: &User,
+    creator: bool,
+    cc: &challenges_coding_challenges::Model,
+) -> Result<CachedExamples, GetExamplesError> {
+    if let Some(wait) = budget_exhausted(
+        db,
+        cc.subtask_id,
+        config.monthly_cpu_budget,
+        Utc::now().naive_utc(),
+    )
+    .await
+    .map_err(GetExamplesError::Database)?
+    {
+        return Err(GetExamplesError::TooManyRequests(wait));
+    }
+    if !user.admin && !creator {
+        if let Some(wait) = limiter
+            .acquire(
+                user.id,
+                cc.subtask_id,
+                config.example_generations_per_user,
+                config.example_generations_per_challenge,
+                Duration::from_secs(config.example_generation_window),
+            )
+            .await
+            .map_err(GetExamplesError::Redis)?
+        {
+            return Err(GetExamplesError::TooManyRequests(wait));
+        }
+    }
+
+    let seeds = match judge.examples().await {
+        Err(err) if err.is_evaluator_error() => {
+            error!(
+                "evaluator for {} failed to execute: {:?}",
+                cc.subtask_id, err
+            );
+            return Err(GetExamplesError::EvaluatorFailed);
+        }
+        x => x.map_err(|err| GetExamplesError::Judge(Box::new(err)))?,
+    };
+    let mut examples = Vec::with_capacity(seeds.len());
+    for seed in &seeds {
+        let example = judge
+            .get_example_checked(
+                seed,
+                &cc.solution_environment,
+                &cc.solution_code,
+                &[],
+                Some(cc.time_limit as _),
+                Some(cc.memory_limit as _),
+            )
+            .await
+            .map_err(|err| GetExamplesError::Judge(Box::new(err)))?;
+        match example {
+            Ok(checked) => examples.push(checked.example),
+            Err(err) => {
+                error!(
+                    "example generation for {} failed on example {}: {:?}",
+                    cc.subtask_id, seed, err
+                );
+                return Err(GetExamplesError::ExampleGenerationFailed);
+            }
+        }
+    }
+
+    Ok(CachedExamples {
+        examples,
+        generated_at: Utc::now(),
+    })
+}
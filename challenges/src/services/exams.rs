@@ -0,0 +1,247 @@
+//! Exams with deferred grading.
+//!
+//! While an exam is running, answers to the subtasks of its task are graded
+//! and recorded, but the user does not learn whether they were correct and the
+//! subtask is not marked as solved. Only the last answer to each subtask
+//! counts. Once the exam is over, its creator releases the results, which
+//! marks all correctly answered subtasks as solved and sends the rewards.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_exam_answers, challenges_exams, challenges_subtasks, challenges_user_subtasks,
+};
+use lib::{
+    config::challenges::{DueDates, ReSolve, Rewards},
+    events::{publish_event, Event},
+    services::Services,
+};
+use schemas::challenges::exams::{ExamAnswerResult, ExamResult};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::{
+    notifications::notify_subtask_solved,
+    reviews::schedule_review,
+    subtasks::{
+        get_user_subtask, send_task_rewards, update_user_subtask, SendTaskRewardsError, SolveKind,
+        UserSubtaskExt,
+    },
+};
+
+/// Return the exam that is currently running for the given task, if any.
+pub async fn get_active_exam(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+) -> Result<Option<challenges_exams::Model>, DbErr> {
+    let now = Utc::now().naive_utc();
+    challenges_exams::Entity::find()
+        .filter(challenges_exams::Column::TaskId.eq(task_id))
+        .filter(challenges_exams::Column::Start.lte(now))
+        .filter(challenges_exams::Column::End.gt(now))
+        .one(db)
+        .await
+}
+
+/// Record the answer of a user to a subtask of a running exam, replacing any
+/// previous answer.
+pub async fn record_exam_answer(
+    db: &DatabaseTransaction,
+    exam: &challenges_exams::Model,
+    user_id: Uuid,
+    subtask_id: Uuid,
+    correct: bool,
+) -> Result<challenges_exam_answers::Model, DbErr> {
+    challenges_exam_answers::Entity::insert(challenges_exam_answers::ActiveModel {
+        exam_id: Set(exam.id),
+        user_id: Set(user_id),
+        subtask_id: Set(subtask_id),
+        correct: Set(correct),
+        timestamp: Set(Utc::now().naive_utc()),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            challenges_exam_answers::Column::ExamId,
+            challenges_exam_answers::Column::UserId,
+            challenges_exam_answers::Column::SubtaskId,
+        ])
+        .update_columns([
+            challenges_exam_answers::Column::Correct,
+            challenges_exam_answers::Column::Timestamp,
+        ])
+        .to_owned(),
+    )
+    .exec_with_returning(db)
+    .await
+}
+
+pub async fn get_exam_answers(
+    db: &DatabaseTransaction,
+    exam_id: Uuid,
+    user_id: Option<Uuid>,
+) -> Result<Vec<challenges_exam_answers::Model>, DbErr> {
+    let mut query = challenges_exam_answers::Entity::find()
+        .filter(challenges_exam_answers::Column::ExamId.eq(exam_id));
+    if let Some(user_id) = user_id {
+        query = query.filter(challenges_exam_answers::Column::UserId.eq(user_id));
+    }
+    query
+        .order_by_asc(challenges_exam_answers::Column::Timestamp)
+        .all(db)
+        .await
+}
+
+/// Group the answers of an exam by user. Users are ordered by the number of
+/// correct answers (descending).
+pub fn compute_exam_results(answers: &[challenges_exam_answers::Model]) -> Vec<ExamResult> {
+    let mut results = BTreeMap::<Uuid, ExamResult>::new();
+    for answer in answers {
+        let result = results.entry(answer.user_id).or_insert_with(|| ExamResult {
+            user_id: answer.user_id,
+            answered: 0,
+            correct: 0,
+            answers: Vec::new(),
+        });
+        result.answered += 1;
+        result.correct += answer.correct as u64;
+        result.answers.push(ExamAnswerResult {
+            subtask_id: answer.subtask_id,
+            correct: answer.correct,
+            timestamp: answer.timestamp.and_utc(),
+        });
+    }
+    let mut results = results.into_values().collect::<Vec<_>>();
+    results.sort_by_key(|x| Reverse(x.correct));
+    results
+}
+
+/// Release the results of an exam which is over. All subtasks which have been
+/// answered correctly are marked as solved at the time of the answer.
+pub async fn release_exam_results(
+    db: &DatabaseTransaction,
+    services: &Services,
+    re_solve: &ReSolve,
+    due_dates: &DueDates,
+    rewards: &Rewards,
+    exam: &challenges_exams::Model,
+    now: DateTime<Utc>,
+) -> Result<challenges_exams::Model, SendTaskRewardsError> {
+    let answers = challenges_exam_answers::Entity::find()
+        .filter(challenges_exam_answers::Column::ExamId.eq(exam.id))
+        .filter(challenges_exam_answers::Column::Correct.eq(true))
+        .all(db)
+        .await?;
+    let subtasks = challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::Id.is_in(answers.iter().map(|x| x.subtask_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x))
+        .collect::<HashMap<_, _>>();
+
+    for answer in answers {
+        let Some(subtask) = subtasks.get(&answer.subtask_id) else {
+            continue;
+        };
+        let user_subtask = get_user_subtask(db, answer.user_id, subtask.id).await?;
+        let Some(solve_kind) = user_subtask.solve_kind(re_solve, answer.timestamp.and_utc()) else {
+            continue;
+        };
+        update_user_subtask(
+            db,
+            user_subtask.as_ref(),
+            challenges_user_subtasks::ActiveModel {
+                user_id: Set(answer.user_id),
+                subtask_id: Set(subtask.id),
+                solved_timestamp: Set(Some(answer.timestamp)),
+                last_attempt_timestamp: Set(Some(answer.timestamp)),
+                attempts: Set(user_subtask.attempts() as i32 + 1),
+                ..Default::default()
+            },
+        )
+        .await?;
+        if solve_kind == SolveKind::First {
+            publish_event(
+                db,
+                Event::SubtaskSolved {
+                    user_id: answer.user_id,
+                    task_id: subtask.task_id,
+                    subtask_id: subtask.id,
+                },
+            )
+            .await?;
+            notify_subtask_solved(db, subtask, answer.user_id).await?;
+        }
+        schedule_review(db, answer.user_id, subtask.id, answer.timestamp).await?;
+
+        if answer.user_id != subtask.creator {
+            send_task_rewards(
+                services,
+                db,
+                due_dates,
+                rewards,
+                answer.user_id,
+                subtask,
+                solve_kind,
+                answer.timestamp,
+            )
+            .await?;
+        }
+    }
+
+    Ok(challenges_exams::ActiveModel {
+        id: Unchanged(exam.id),
+        release_timestamp: Set(Some(now.naive_utc())),
+        ..Default::default()
+    }
+    .update(db)
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn answer(user: u128, subtask: u128, correct: bool) -> challenges_exam_answers::Model {
+        challenges_exam_answers::Model {
+            exam_id: Uuid::nil(),
+            user_id: Uuid::from_u128(user),
+            subtask_id: Uuid::from_u128(subtask),
+            correct,
+            timestamp: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_compute_exam_results() {
+        let results = compute_exam_results(&[
+            answer(1, 10, false),
+            answer(2, 10, true),
+            answer(1, 11, true),
+            answer(2, 11, true),
+        ]);
+        let summary = results
+            .iter()
+            .map(|x| (x.user_id.as_u128(), x.answered, x.correct))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, [(2, 2, 2), (1, 2, 1)]);
+        assert_eq!(
+            results[1]
+                .answers
+                .iter()
+                .map(|x| x.correct)
+                .collect::<Vec<_>>(),
+            [false, true]
+        );
+        assert!(compute_exam_results(&[]).is_empty());
+    }
+}
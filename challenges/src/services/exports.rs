@@ -0,0 +1,414 @@
+use std::{collections::HashMap, io};
+
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_cloze_attempts, challenges_code_reading_attempts,
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_matching_attempts, challenges_multiple_choice_attempts,
+    challenges_ordering_attempts, challenges_output_submissions, challenges_question_attempts,
+    challenges_sql_attempts, challenges_subtasks, challenges_true_false_attempts,
+    sea_orm_active_enums::{ChallengesSubtaskType, ChallengesVerdict},
+};
+use futures::{channel::mpsc, stream, SinkExt, StreamExt};
+use poem::Body;
+use rust_xlsxwriter::{Workbook, XlsxError};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Paginator, PaginatorTrait, QueryFilter,
+    QueryOrder, SelectorTrait,
+};
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+use uuid::Uuid;
+
+/// Number of attempts that are loaded from the database at once.
+const PAGE_SIZE: u64 = 1000;
+
+const HEADER: [&str; 9] = [
+    "subtask_id",
+    "subtask_type",
+    "user_id",
+    "attempt_id",
+    "timestamp",
+    "solved",
+    "verdict",
+    "environment",
+    "revision_id",
+];
+
+/// A single attempt (or coding challenge submission) of a user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttemptRow {
+    pub subtask_id: Uuid,
+    pub subtask_type: ChallengesSubtaskType,
+    pub user_id: Uuid,
+    pub attempt_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub solved: bool,
+    pub verdict: Option<ChallengesVerdict>,
+    pub environment: Option<String>,
+    pub revision_id: Option<Uuid>,
+}
+
+type Pages = mpsc::Receiver<Result<Vec<AttemptRow>, DbErr>>;
+
+/// Stream all attempts of the given subtasks as csv.
+pub fn export_csv(db: DatabaseConnection, subtasks: Vec<challenges_subtasks::Model>) -> Body {
+    let header = format!("{}\n", HEADER.join(",")).into_bytes();
+    let rows = load_attempts(db, subtasks).map(|page| {
+        let csv = match page {
+            Ok(page) => encode_csv(&page).map_err(ExportError::from),
+            Err(err) => Err(err.into()),
+        };
+        csv.map_err(|err| {
+            error!("failed to export attempts: {err}");
+            io::Error::other(err)
+        })
+    });
+    Body::from_bytes_stream(stream::once(async { Ok(header) }).chain(rows))
+}
+
+/// Create an xlsx workbook of all attempts of the given subtasks.
+///
+/// In contrast to csv exports, the workbook has to be kept in memory until it
+/// is complete, because xlsx files are zip archives.
+pub async fn export_xlsx(
+    db: DatabaseConnection,
+    subtasks: Vec<challenges_subtasks::Model>,
+) -> Result<Vec<u8>, ExportError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.write_row(0, 0, HEADER)?;
+    let mut row = 1;
+    let mut pages = load_attempts(db, subtasks);
+    while let Some(page) = pages.next().await {
+        for attempt in page? {
+            worksheet.write_row(
+                row,
+                0,
+                [
+                    attempt.subtask_id.to_string(),
+                    enum_name(attempt.subtask_type),
+                    attempt.user_id.to_string(),
+                    attempt.attempt_id.to_string(),
+                    attempt.timestamp.to_rfc3339(),
+                    attempt.solved.to_string(),
+                    attempt.verdict.map(enum_name).unwrap_or_default(),
+                    attempt.environment.unwrap_or_default(),
+                    attempt
+                        .revision_id
+                        .map(|x| x.to_string())
+                        .unwrap_or_default(),
+                ],
+            )?;
+            row += 1;
+        }
+    }
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("database error: {0}")]
+    Db(#[from] DbErr),
+    #[error("csv error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("xlsx error: {0}")]
+    Xlsx(#[from] XlsxError),
+}
+
+/// Return the name of an enum variant as it is serialized in json or csv.
+fn enum_name(value: impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|x| x.as_str().map(Into::into))
+        .unwrap_or_default()
+}
+
+fn encode_csv(rows: &[AttemptRow]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.into_inner().map_err(|err| err.into_error().into())
+}
+
+/// Load all attempts of the given subtasks page by page in a background task.
+/// Loading stops as soon as the returned receiver is dropped.
+fn load_attempts(db: DatabaseConnection, subtasks: Vec<challenges_subtasks::Model>) -> Pages {
+    let (mut tx, rx) = mpsc::channel(1);
+    tokio::spawn(async move {
+        if let Err(err) = send_attempts(&db, &subtasks, &mut tx).await {
+            tx.send(Err(err)).await.ok();
+        }
+    });
+    rx
+}
+
+async fn send_attempts(
+    db: &DatabaseConnection,
+    subtasks: &[challenges_subtasks::Model],
+    tx: &mut mpsc::Sender<Result<Vec<AttemptRow>, DbErr>>,
+) -> Result<(), DbErr> {
+    let types = subtasks
+        .iter()
+        .map(|x| (x.id, x.ty))
+        .collect::<HashMap<_, _>>();
+    let ids = || subtasks.iter().map(|x| x.id);
+
+    let attempt = |subtask_id, user_id, attempt_id, timestamp, solved, revision_id| AttemptRow {
+        subtask_id,
+        subtask_type: types[&subtask_id],
+        user_id,
+        attempt_id,
+        timestamp,
+        solved,
+        verdict: None,
+        environment: None,
+        revision_id,
+    };
+
+    let pages = challenges_multiple_choice_attempts::Entity::find()
+        .filter(challenges_multiple_choice_attempts::Column::QuestionId.is_in(ids()))
+        .order_by_asc(challenges_multiple_choice_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.question_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_question_attempts::Entity::find()
+        .filter(challenges_question_attempts::Column::QuestionId.is_in(ids()))
+        .order_by_asc(challenges_question_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.question_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_matching_attempts::Entity::find()
+        .filter(challenges_matching_attempts::Column::MatchingId.is_in(ids()))
+        .order_by_asc(challenges_matching_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.matching_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_true_false_attempts::Entity::find()
+        .filter(challenges_true_false_attempts::Column::StatementId.is_in(ids()))
+        .order_by_asc(challenges_true_false_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.statement_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_ordering_attempts::Entity::find()
+        .filter(challenges_ordering_attempts::Column::OrderingId.is_in(ids()))
+        .order_by_asc(challenges_ordering_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.ordering_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_cloze_attempts::Entity::find()
+        .filter(challenges_cloze_attempts::Column::ClozeId.is_in(ids()))
+        .order_by_asc(challenges_cloze_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.cloze_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_code_reading_attempts::Entity::find()
+        .filter(challenges_code_reading_attempts::Column::CodeReadingId.is_in(ids()))
+        .order_by_asc(challenges_code_reading_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.code_reading_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_sql_attempts::Entity::find()
+        .filter(challenges_sql_attempts::Column::SqlChallengeId.is_in(ids()))
+        .order_by_asc(challenges_sql_attempts::Column::Timestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.sql_challenge_id,
+            x.user_id,
+            x.id,
+            x.timestamp.and_utc(),
+            x.solved,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_output_submissions::Entity::find()
+        .filter(challenges_output_submissions::Column::SubtaskId.is_in(ids()))
+        .order_by_asc(challenges_output_submissions::Column::CreationTimestamp)
+        .paginate(db, PAGE_SIZE);
+    if !send_pages(pages, tx, |x| {
+        attempt(
+            x.subtask_id,
+            x.creator,
+            x.id,
+            x.creation_timestamp.and_utc(),
+            x.passed == x.total,
+            x.revision_id,
+        )
+    })
+    .await?
+    {
+        return Ok(());
+    }
+
+    let pages = challenges_coding_challenge_submissions::Entity::find()
+        .find_also_related(challenges_coding_challenge_result::Entity)
+        .filter(challenges_coding_challenge_submissions::Column::SubtaskId.is_in(ids()))
+        .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+        .paginate(db, PAGE_SIZE);
+    send_pages(pages, tx, |(submission, result)| {
+        let verdict = result.map(|x| x.verdict);
+        AttemptRow {
+            verdict,
+            environment: Some(submission.environment),
+            ..attempt(
+                submission.subtask_id,
+                submission.creator,
+                submission.id,
+                submission.creation_timestamp.and_utc(),
+                verdict == Some(ChallengesVerdict::Ok),
+                submission.revision_id,
+            )
+        }
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Send all pages to the channel. Returns `false` if the receiver has been
+/// dropped.
+async fn send_pages<'db, S: SelectorTrait + 'db>(
+    mut pages: Paginator<'db, DatabaseConnection, S>,
+    tx: &mut mpsc::Sender<Result<Vec<AttemptRow>, DbErr>>,
+    map: impl Fn(S::Item) -> AttemptRow,
+) -> Result<bool, DbErr> {
+    while let Some(page) = pages.fetch_and_next().await? {
+        if tx
+            .send(Ok(page.into_iter().map(&map).collect()))
+            .await
+            .is_err()
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_csv() {
+        let row = AttemptRow {
+            subtask_id: Uuid::from_u128(1),
+            subtask_type: ChallengesSubtaskType::CodingChallenge,
+            user_id: Uuid::from_u128(2),
+            attempt_id: Uuid::from_u128(3),
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            solved: false,
+            verdict: Some(ChallengesVerdict::WrongAnswer),
+            environment: Some("rust, nightly".into()),
+            revision_id: Some(Uuid::from_u128(4)),
+        };
+        let csv = String::from_utf8(encode_csv(&[row]).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "00000000-0000-0000-0000-000000000001,CODING_CHALLENGE,\
+             00000000-0000-0000-0000-000000000002,00000000-0000-0000-0000-000000000003,\
+             2023-11-14T22:13:20Z,false,WRONG_ANSWER,\"rust, nightly\",\
+             00000000-0000-0000-0000-000000000004\n"
+        );
+    }
+}
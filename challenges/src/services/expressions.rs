@@ -0,0 +1,378 @@
+//! A small evaluator for arithmetic expressions in question templates.
+//!
+//! Expressions support numbers, variables, the operators `+ - * / % ^`,
+//! parentheses and the functions `abs`, `min`, `max`, `round`, `floor`, `ceil`
+//! and `sqrt`. There are no loops or side effects and the nesting depth is
+//! limited, so expressions written by users can be evaluated safely.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+/// The maximum nesting depth of an expression.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ExpressionError {
+    #[error("unexpected character `{0}`")]
+    UnexpectedChar(char),
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("unexpected token `{0}`")]
+    UnexpectedToken(String),
+    #[error("unknown variable `{0}`")]
+    UnknownVariable(String),
+    #[error("unknown function `{0}`")]
+    UnknownFunction(String),
+    #[error("wrong number of arguments for `{0}`")]
+    WrongArgumentCount(String),
+    #[error("expression is nested too deeply")]
+    TooDeep,
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("result is not a finite number")]
+    NotFinite,
+    #[error("unclosed placeholder")]
+    UnclosedPlaceholder,
+}
+
+/// Evaluate an expression with the given variables.
+pub fn evaluate(expr: &str, vars: &BTreeMap<String, i64>) -> Result<f64, ExpressionError> {
+    let mut parser = Parser {
+        tokens: tokenize(expr)?,
+        pos: 0,
+        vars,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    match parser.tokens.get(parser.pos) {
+        Some(token) => Err(ExpressionError::UnexpectedToken(token.to_string())),
+        None => Ok(value),
+    }
+}
+
+/// Replace every `{{ expression }}` placeholder in the template with the value
+/// of the expression.
+pub fn render(template: &str, vars: &BTreeMap<String, i64>) -> Result<String, ExpressionError> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or(ExpressionError::UnclosedPlaceholder)?;
+        out.push_str(&format_number(evaluate(
+            &rest[start + 2..start + end],
+            vars,
+        )?));
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Format a number without trailing zeros and with at most six decimal places.
+pub fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+    let out = format!("{value:.6}");
+    let out = out.trim_end_matches('0').trim_end_matches('.');
+    match out {
+        "-0" => "0".into(),
+        _ => out.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(x) => write!(f, "{x}"),
+            Token::Ident(x) => write!(f, "{x}"),
+            Token::Op(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = &expr[start..end];
+            tokens.push(Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| ExpressionError::UnexpectedToken(number.into()))?,
+            ));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Ident(expr[start..end].into()));
+        } else if "+-*/%^(),".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(ExpressionError::UnexpectedChar(c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    vars: &'a BTreeMap<String, i64>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expect_op(&mut self, op: char) -> Result<(), ExpressionError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(x)) if *x == op => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(token) => Err(ExpressionError::UnexpectedToken(token.to_string())),
+            None => Err(ExpressionError::UnexpectedEnd),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        match self.depth > MAX_DEPTH {
+            true => Err(ExpressionError::TooDeep),
+            false => Ok(()),
+        }
+    }
+
+    /// `expr := term (('+' | '-') term)*`
+    fn expr(&mut self) -> Result<f64, ExpressionError> {
+        self.enter()?;
+        let mut value = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = match op {
+                '+' => value + rhs,
+                _ => value - rhs,
+            };
+        }
+        self.depth -= 1;
+        finite(value)
+    }
+
+    /// `term := unary (('*' | '/' | '%') unary)*`
+    fn term(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.unary()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value * rhs,
+                _ if rhs == 0.0 => return Err(ExpressionError::DivisionByZero),
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        finite(value)
+    }
+
+    /// `unary := '-' unary | power`
+    fn unary(&mut self) -> Result<f64, ExpressionError> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            self.enter()?;
+            let value = -self.unary()?;
+            self.depth -= 1;
+            return Ok(value);
+        }
+        self.power()
+    }
+
+    /// `power := atom ('^' unary)?`
+    fn power(&mut self) -> Result<f64, ExpressionError> {
+        let base = self.atom()?;
+        if self.peek_op() == Some('^') {
+            self.pos += 1;
+            self.enter()?;
+            let exp = self.unary()?;
+            self.depth -= 1;
+            return finite(base.powf(exp));
+        }
+        Ok(base)
+    }
+
+    /// `atom := number | variable | function '(' args ')' | '(' expr ')'`
+    fn atom(&mut self) -> Result<f64, ExpressionError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(ExpressionError::UnexpectedEnd)?;
+        self.pos += 1;
+        match token {
+            Token::Number(x) => Ok(x),
+            Token::Op('(') => {
+                let value = self.expr()?;
+                self.expect_op(')')?;
+                Ok(value)
+            }
+            Token::Ident(name) if self.peek_op() == Some('(') => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek_op() != Some(')') {
+                    args.push(self.expr()?);
+                    while self.peek_op() == Some(',') {
+                        self.pos += 1;
+                        args.push(self.expr()?);
+                    }
+                }
+                self.expect_op(')')?;
+                call(&name, &args)
+            }
+            Token::Ident(name) => self
+                .vars
+                .get(&name)
+                .map(|&x| x as f64)
+                .ok_or(ExpressionError::UnknownVariable(name)),
+            Token::Op(op) => Err(ExpressionError::UnexpectedToken(op.into())),
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, ExpressionError> {
+    let value = match (name, args) {
+        ("abs", &[x]) => x.abs(),
+        ("round", &[x]) => x.round(),
+        ("floor", &[x]) => x.floor(),
+        ("ceil", &[x]) => x.ceil(),
+        ("sqrt", &[x]) => x.sqrt(),
+        ("min", [_, ..]) => args.iter().copied().fold(f64::INFINITY, f64::min),
+        ("max", [_, ..]) => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        ("abs" | "round" | "floor" | "ceil" | "sqrt" | "min" | "max", _) => {
+            return Err(ExpressionError::WrongArgumentCount(name.into()))
+        }
+        _ => return Err(ExpressionError::UnknownFunction(name.into())),
+    };
+    finite(value)
+}
+
+fn finite(value: f64) -> Result<f64, ExpressionError> {
+    match value.is_finite() {
+        true => Ok(value),
+        false => Err(ExpressionError::NotFinite),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> BTreeMap<String, i64> {
+        [("a".into(), 6), ("b".into(), 4)].into()
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let eval = |expr: &str| evaluate(expr, &vars());
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("a * b - 1"), Ok(23.0));
+        assert_eq!(eval("a / b"), Ok(1.5));
+        assert_eq!(eval("a % b"), Ok(2.0));
+        assert_eq!(eval("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(eval("--a"), Ok(6.0));
+        assert_eq!(eval("max(a, b, 10) + min(a, b)"), Ok(14.0));
+        assert_eq!(eval("round(2.5) + floor(a / b) + ceil(0.1)"), Ok(5.0));
+        assert_eq!(eval("sqrt(a * a + 64)"), Ok(10.0));
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let eval = |expr: &str| evaluate(expr, &vars());
+        assert_eq!(eval("1 +"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(
+            eval("1 2"),
+            Err(ExpressionError::UnexpectedToken("2".into()))
+        );
+        assert_eq!(eval("a & b"), Err(ExpressionError::UnexpectedChar('&')));
+        assert_eq!(
+            eval("c + 1"),
+            Err(ExpressionError::UnknownVariable("c".into()))
+        );
+        assert_eq!(
+            eval("exit(1)"),
+            Err(ExpressionError::UnknownFunction("exit".into()))
+        );
+        assert_eq!(
+            eval("abs(1, 2)"),
+            Err(ExpressionError::WrongArgumentCount("abs".into()))
+        );
+        assert_eq!(eval("a / (b - 4)"), Err(ExpressionError::DivisionByZero));
+        assert_eq!(eval("10 ^ 1000"), Err(ExpressionError::NotFinite));
+        assert_eq!(eval("sqrt(-1)"), Err(ExpressionError::NotFinite));
+        assert_eq!(
+            eval(&format!("{}1{}", "(".repeat(100), ")".repeat(100))),
+            Err(ExpressionError::TooDeep)
+        );
+        assert_eq!(eval(&"-".repeat(100)), Err(ExpressionError::TooDeep));
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("What is {{a}} + {{ b }}?", &vars()).unwrap(),
+            "What is 6 + 4?"
+        );
+        assert_eq!(render("{{a / b}} m", &vars()).unwrap(), "1.5 m");
+        assert_eq!(
+            render("no placeholders", &vars()).unwrap(),
+            "no placeholders"
+        );
+        assert_eq!(
+            render("{{a", &vars()),
+            Err(ExpressionError::UnclosedPlaceholder)
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(42.0), "42");
+        assert_eq!(format_number(-3.0), "-3");
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+        assert_eq!(format_number(1.0 / 3.0), "0.333333");
+        assert_eq!(format_number(-0.0000001), "0");
+    }
+}
@@ -0,0 +1,226 @@
+//! Feed and sitemap of the recently added public content.
+//!
+//! Both are generated periodically by the [`FeedJob`] and served from the
+//! [`FeedCache`], so crawlers cannot cause expensive queries.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use entity::{challenges_challenges, challenges_subtasks, challenges_tasks};
+use lib::config::SharedConfig;
+use quick_xml::escape::escape;
+use schemas::challenges::feed::{Feed, FeedItem};
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+/// Return a condition matching the subtasks that are visible to everyone.
+pub fn public_subtasks() -> Condition {
+    Condition::all()
+        .add(challenges_subtasks::Column::Enabled.eq(true))
+        .add(challenges_subtasks::Column::Locked.eq(false))
+        .add(challenges_subtasks::Column::Retired.eq(false))
+}
+
+/// The most recently generated feed.
+#[derive(Default)]
+pub struct FeedCache {
+    feed: RwLock<Option<Arc<Feed>>>,
+}
+
+impl FeedCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached feed, if it has already been generated.
+    pub fn get(&self) -> Option<Arc<Feed>> {
+        self.feed.read().unwrap().clone()
+    }
+
+    pub fn set(&self, feed: Feed) -> Arc<Feed> {
+        let feed = Arc::new(feed);
+        *self.feed.write().unwrap() = Some(Arc::clone(&feed));
+        feed
+    }
+}
+
+/// Generate the feed of the `size` most recently added public challenges and
+/// subtasks. Challenges without public subtasks are ignored.
+pub async fn generate_feed(db: &impl ConnectionTrait, size: usize) -> Result<Feed, DbErr> {
+    let tasks = challenges_challenges::Entity::find()
+        .find_also_related(challenges_tasks::Entity)
+        .filter(challenges_tasks::Column::OrganizationId.is_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(challenge, task)| Some((challenge.task_id, (challenge.title, task?))))
+        .collect::<HashMap<_, _>>();
+
+    let subtasks = challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::TaskId.is_in(tasks.keys().copied()))
+        .filter(public_subtasks())
+        .order_by_desc(challenges_subtasks::Column::CreationTimestamp)
+        .limit(size as u64)
+        .all(db)
+        .await?;
+    let non_empty = challenges_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_subtasks::Column::TaskId)
+        .distinct()
+        .filter(challenges_subtasks::Column::TaskId.is_in(tasks.keys().copied()))
+        .filter(public_subtasks())
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await?;
+
+    let mut items = non_empty
+        .into_iter()
+        .filter_map(|task_id| {
+            let (title, task) = tasks.get(&task_id)?;
+            Some(FeedItem {
+                task_id,
+                title: title.clone(),
+                subtask_id: None,
+                subtask_type: None,
+                timestamp: task.creation_timestamp.and_utc(),
+            })
+        })
+        .chain(subtasks.into_iter().filter_map(|subtask| {
+            let (title, _) = tasks.get(&subtask.task_id)?;
+            Some(FeedItem {
+                task_id: subtask.task_id,
+                title: title.clone(),
+                subtask_id: Some(subtask.id),
+                subtask_type: Some(subtask.ty),
+                timestamp: subtask.creation_timestamp.and_utc(),
+            })
+        }))
+        .collect::<Vec<_>>();
+    items.sort_by_key(|x| Reverse(x.timestamp));
+    items.truncate(size);
+
+    Ok(Feed {
+        generated: Utc::now(),
+        items,
+    })
+}
+
+/// Render a sitemap with one entry for each task in the feed. The last
+/// modification of a task is the time its most recent item has been added.
+pub fn render_sitemap(feed: &Feed, task_url: &str) -> String {
+    let mut tasks = BTreeMap::<Uuid, DateTime<Utc>>::new();
+    for item in &feed.items {
+        let lastmod = tasks.entry(item.task_id).or_insert(item.timestamp);
+        *lastmod = (*lastmod).max(item.timestamp);
+    }
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset \
+         xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for (task_id, lastmod) in tasks {
+        let url = task_url.replace("{task_id}", &task_id.to_string());
+        writeln!(
+            out,
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>",
+            escape(&url),
+            lastmod.format("%Y-%m-%d")
+        )
+        .unwrap();
+    }
+    out.push_str("</urlset>\n");
+    out
+}
+
+/// Regenerates the feed periodically while the public api is enabled.
+pub struct FeedJob {
+    db: DatabaseConnection,
+    config: SharedConfig,
+    cache: Arc<FeedCache>,
+}
+
+impl FeedJob {
+    pub fn new(db: DatabaseConnection, config: SharedConfig, cache: Arc<FeedCache>) -> Self {
+        Self { db, config, cache }
+    }
+
+    /// Update the feed until the process exits.
+    pub async fn run(self) {
+        loop {
+            let config = self.config.load();
+            if config.challenges.public_api.enabled {
+                match generate_feed(&self.db, config.challenges.public_api.feed_size).await {
+                    Ok(feed) => {
+                        debug!("generated feed with {} item(s)", feed.items.len());
+                        self.cache.set(feed);
+                    }
+                    Err(err) => error!("failed to generate feed: {err}"),
+                }
+            }
+            let interval = config.challenges.public_api.feed_interval;
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use entity::sea_orm_active_enums::ChallengesSubtaskType;
+
+    use super::*;
+
+    fn item(task_id: Uuid, subtask_id: Option<Uuid>, day: u32) -> FeedItem {
+        FeedItem {
+            task_id,
+            title: "Title".into(),
+            subtask_id,
+            subtask_type: subtask_id.map(|_| ChallengesSubtaskType::Question),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_sitemap() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let feed = Feed {
+            generated: Utc::now(),
+            items: vec![
+                item(a, Some(Uuid::new_v4()), 5),
+                item(b, None, 4),
+                item(a, Some(Uuid::new_v4()), 3),
+                item(a, None, 1),
+            ],
+        };
+        assert_eq!(
+            render_sitemap(&feed, "https://example.com/tasks/{task_id}?a=1&b=2"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  \
+             <url><loc>https://example.com/tasks/00000000-0000-0000-0000-000000000001?a=1&amp;b=2</loc><lastmod>2024-01-05</lastmod></url>\n  \
+             <url><loc>https://example.com/tasks/00000000-0000-0000-0000-000000000002?a=1&amp;b=2</loc><lastmod>2024-01-04</lastmod></url>\n\
+             </urlset>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_empty_sitemap() {
+        let feed = Feed {
+            generated: Utc::now(),
+            items: Vec::new(),
+        };
+        assert!(render_sitemap(&feed, "{task_id}").ends_with(
+            "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n</urlset>\n"
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{NaiveDateTime, Utc};
+use entity::{
+    challenges_contests, challenges_held_rewards, challenges_subtasks,
+    sea_orm_active_enums::ChallengesHeldRewardStatus,
+};
+use lib::{config::challenges::Rewards, services::Services};
+use sea_orm::{
+    sea_query::IntoCondition, ColumnTrait, Condition, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use super::subtasks::{grant_rewards, SendTaskRewardsError};
+
+/// Return the contest which holds the rewards for solving subtasks of the
+/// given task at the given time.
+pub async fn get_contest_hold(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+    solved_at: NaiveDateTime,
+) -> Result<Option<challenges_contests::Model>, DbErr> {
+    challenges_contests::Entity::find()
+        .filter(challenges_contests::Column::TaskId.eq(task_id))
+        .filter(challenges_contests::Column::HoldRewards.eq(true))
+        .filter(challenges_contests::Column::Start.lte(solved_at))
+        .filter(challenges_contests::Column::End.gte(solved_at))
+        .one(db)
+        .await
+}
+
+/// Store rewards instead of sending them. `contest_id` is the contest whose
+/// policy holds the rewards or `None` if they are held because of an
+/// anti-cheat flag.
+pub async fn hold_rewards(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask: &challenges_subtasks::Model,
+    xp: i64,
+    coins: i64,
+    contest_id: Option<Uuid>,
+) -> Result<(), DbErr> {
+    challenges_held_rewards::Entity::insert(challenges_held_rewards::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        subtask_id: Set(subtask.id),
+        xp: Set(xp),
+        coins: Set(coins),
+        timestamp: Set(Utc::now().naive_utc()),
+        status: Set(ChallengesHeldRewardStatus::Held),
+        contest_id: Set(contest_id),
+        resolver: Set(None),
+        resolution_timestamp: Set(None),
+    })
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+/// Send all held rewards matching the condition. Returns the number of
+/// released rewards.
+pub async fn release_held_rewards(
+    services: &Services,
+    db: &DatabaseTransaction,
+    config: &Rewards,
+    condition: impl IntoCondition,
+    resolver: Uuid,
+) -> Result<u64, SendTaskRewardsError> {
+    let held = challenges_held_rewards::Entity::find()
+        .find_also_related(challenges_subtasks::Entity)
+        .filter(challenges_held_rewards::Column::Status.eq(ChallengesHeldRewardStatus::Held))
+        .filter(condition)
+        .all(db)
+        .await?;
+    for (reward, subtask) in &held {
+        if let Some(subtask) = subtask {
+            grant_rewards(
+                services,
+                db,
+                config,
+                reward.user_id,
+                subtask,
+                reward.xp,
+                reward.coins,
+            )
+            .await?;
+        }
+    }
+    Ok(resolve_held_rewards(
+        db,
+        challenges_held_rewards::Column::Id.is_in(held.iter().map(|(x, _)| x.id)),
+        ChallengesHeldRewardStatus::Released,
+        resolver,
+    )
+    .await?)
+}
+
+/// Discard all held rewards matching the condition. Returns the number of
+/// voided rewards.
+pub async fn void_held_rewards(
+    db: &DatabaseTransaction,
+    condition: impl IntoCondition,
+    resolver: Uuid,
+) -> Result<u64, DbErr> {
+    resolve_held_rewards(db, condition, ChallengesHeldRewardStatus::Voided, resolver).await
+}
+
+async fn resolve_held_rewards(
+    db: &DatabaseTransaction,
+    condition: impl IntoCondition,
+    status: ChallengesHeldRewardStatus,
+    resolver: Uuid,
+) -> Result<u64, DbErr> {
+    Ok(challenges_held_rewards::Entity::update_many()
+        .set(challenges_held_rewards::ActiveModel {
+            status: Set(status),
+            resolver: Set(Some(resolver)),
+            resolution_timestamp: Set(Some(Utc::now().naive_utc())),
+            ..Default::default()
+        })
+        .filter(challenges_held_rewards::Column::Status.eq(ChallengesHeldRewardStatus::Held))
+        .filter(condition)
+        .exec(db)
+        .await?
+        .rows_affected)
+}
+
+/// Return the condition matching the held rewards of a user which have been
+/// held because of anti-cheat flags.
+pub fn flagged_rewards(user_id: Uuid) -> Condition {
+    Condition::all()
+        .add(challenges_held_rewards::Column::UserId.eq(user_id))
+        .add(challenges_held_rewards::Column::ContestId.is_null())
+}
@@ -1,46 +1,173 @@
-use entity::sea_orm_active_enums::ChallengesVerdict;
+use std::{borrow::Cow, collections::HashSet};
+
+use entity::{
+    challenges_coding_challenges,
+    sea_orm_active_enums::{ChallengesFeedbackLevel, ChallengesVerdict},
+};
 use fnct::{format::JsonFormatter, key};
-use lib::{Cache, CacheError};
+use lib::{auth::User, Cache, CacheError};
 use sandkasten_client::{
     schemas::{
         programs::{
-            BuildRequest, BuildRunError, BuildRunRequest, BuildRunResult, File, LimitsOpt,
-            MainFile, RunRequest, RunResult,
+            BuildRequest, BuildRunError, BuildRunRequest, BuildRunResult, EnvVar, File, LimitsOpt,
+            MainFile, ResourceUsage, RunRequest, RunResult,
         },
         ErrorResponse,
     },
-    Error as SandkastenError, SandkastenClient,
+    Error as SandkastenError,
+};
+use schemas::challenges::coding_challenges::{
+    CheckResult, EvaluatorProtocolError, Example, ExecutorConfig, InteractionRound,
 };
-use schemas::challenges::coding_challenges::{CheckResult, Example, ExecutorConfig};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
+use uuid::Uuid;
+
+use super::{
+    build_configs::BuildFlags,
+    comparison::{compare_output, Comparison},
+    sandbox::Sandbox,
+};
 
 pub const EVALUATOR_TEMPLATE: &str = include_str!("../../assets/evaluator/template.py");
 pub const EVALUATOR_LIBRARY: &str = include_str!("../../assets/evaluator/lib.py");
+pub const INTERACTIVE_EVALUATOR_TEMPLATE: &str =
+    include_str!("../../assets/evaluator/interactive.py");
+
+/// The maximum number of rounds of an interactive challenge before the
+/// solution is rejected.
+pub const MAX_ROUNDS: usize = 100;
+
+/// The maximum difference between numbers for the `float` comparison if
+/// neither the evaluator nor the challenge specify an epsilon.
+pub const DEFAULT_EPSILON: f64 = 1e-6;
+
+/// The environment variable which contains the build flags of a challenge
+/// during the build of a solution.
+pub const BUILD_FLAGS_VAR: &str = "BUILD_FLAGS";
+
+/// Return the feedback level that applies to the results of a user's
+/// submissions. Admins and the creator of a challenge always get full feedback.
+pub fn feedback_level(
+    user: &User,
+    subtask_creator: Uuid,
+    challenge: &challenges_coding_challenges::Model,
+) -> ChallengesFeedbackLevel {
+    if user.admin || user.id == subtask_creator {
+        ChallengesFeedbackLevel::FullDiff
+    } else {
+        challenge.feedback_level
+    }
+}
+
+/// Return the checker program of a challenge, if it has one.
+pub fn checker_program(challenge: &challenges_coding_challenges::Model) -> Option<Program<'_>> {
+    Some(Program {
+        environment: challenge.checker_environment.as_deref()?,
+        code: challenge.checker_code.as_deref()?,
+    })
+}
+
+/// Return the number of bytes of a solution consisting of a main file and
+/// additional files.
+pub fn solution_size(code: &str, files: &[File]) -> usize {
+    code.len() + files.iter().map(|file| file.content.len()).sum::<usize>()
+}
+
+/// Whether multiple additional files of a solution have the same name.
+pub fn has_duplicate_files(files: &[File]) -> bool {
+    let mut names = HashSet::new();
+    !files.iter().all(|file| names.insert(file.name.as_str()))
+}
+
+/// An example together with the resources the solution used to solve it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckedExample {
+    pub example: Example,
+    pub resource_usage: ResourceUsage,
+}
 
 pub struct Judge<'a> {
-    pub sandkasten: &'a SandkastenClient,
+    pub sandkasten: &'a dyn Sandbox,
     pub evaluator: &'a str,
+    /// The version of the protocol the evaluator implements.
+    ///
+    /// In version `1` the outputs of the evaluator are parsed leniently and
+    /// tests don't have any metadata. Version `2` adds structured metadata to
+    /// the generated tests (see [`TestMeta`]) and reports precisely why the
+    /// output of the evaluator could not be parsed.
+    pub protocol_version: i32,
+    /// How outputs are compared unless the metadata of a test specifies a
+    /// comparison.
+    pub comparison: Comparison,
+    /// The maximum difference between numbers for the `float` comparison.
+    pub epsilon: Option<f64>,
+    /// The solution whose output is used as the expected output of tests
+    /// without an explicit expected output.
+    pub reference: Program<'a>,
+    /// The program which decides whether an output is correct if the
+    /// `checker` comparison is used.
+    pub checker: Option<Program<'a>>,
+    /// The build flags for solutions in each environment.
+    pub build_flags: &'a BuildFlags,
     pub cache: &'a Cache<JsonFormatter>,
 }
 
+/// A program provided by the creator of a challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Program<'a> {
+    pub environment: &'a str,
+    pub code: &'a str,
+}
+
+/// The configuration of a judge, used in cache keys. Serde only implements
+/// `Serialize` for tuples with up to 16 elements, so the fields are grouped
+/// here instead of being passed to [`key!`] one by one.
+#[derive(Debug, Serialize)]
+pub struct JudgeKey<'a> {
+    evaluator: &'a str,
+    protocol_version: i32,
+    comparison: Comparison,
+    epsilon: Option<String>,
+    reference: Program<'a>,
+    checker: Option<Program<'a>>,
+    build_flags: &'a BuildFlags,
+}
+
 impl Judge<'_> {
+    /// Return the parts of the judge's configuration which affect the result
+    /// of checking a solution.
+    pub fn cache_key(&self) -> JudgeKey<'_> {
+        JudgeKey {
+            evaluator: self.evaluator,
+            protocol_version: self.protocol_version,
+            comparison: self.comparison,
+            epsilon: self.epsilon.map(|x| x.to_string()),
+            reference: self.reference,
+            checker: self.checker,
+            build_flags: self.build_flags,
+        }
+    }
+
     pub async fn get_example_checked(
         &self,
         seed: &str,
         solution_environment: &str,
         solution_code: &str,
+        solution_files: &[File],
         time_limit: Option<u64>,
         memory_limit: Option<u64>,
-    ) -> Result<Result<Example, CheckResult<RunResult>>, Error> {
+    ) -> Result<Result<CheckedExample, CheckResult<RunResult>>, Error> {
         self.cache
             .cached_result(
                 key!(
-                    self.evaluator,
+                    "checked_example",
+                    self.cache_key(),
                     seed,
                     solution_environment,
                     solution_code,
+                    solution_files,
                     time_limit,
                     memory_limit
                 ),
@@ -54,6 +181,7 @@ impl Judge<'_> {
                             &input,
                             solution_environment,
                             solution_code,
+                            solution_files,
                             time_limit,
                             memory_limit,
                         )
@@ -62,12 +190,22 @@ impl Judge<'_> {
                         CheckResult {
                             verdict: ChallengesVerdict::Ok,
                             run: Some(run),
+                            transcript,
                             ..
-                        } => Ok(Example {
-                            id: seed.into(),
-                            input: input.input,
-                            output: run.stdout,
-                            explanation: (!run.stderr.is_empty()).then_some(run.stderr),
+                        } => Ok(CheckedExample {
+                            example: Example {
+                                id: seed.into(),
+                                input: transcript.into_iter().flatten().fold(
+                                    input.input,
+                                    |mut input, round| {
+                                        input.push_str(&round.judge);
+                                        input
+                                    },
+                                ),
+                                output: run.stdout,
+                                explanation: (!run.stderr.is_empty()).then_some(run.stderr),
+                            },
+                            resource_usage: run.resource_usage,
                         }),
                         _ => Err(result),
                     })
@@ -78,17 +216,82 @@ impl Judge<'_> {
 
     pub async fn examples(&self) -> Result<Vec<String>, Error> {
         self.cache
-            .cached_result(key!(self.evaluator), &[], None, || async {
-                self.run_evaluator(vec!["examples".into()], None::<()>)
-                    .await
-            })
+            .cached_result(
+                key!(self.evaluator, self.protocol_version),
+                &[],
+                None,
+                || async {
+                    self.run_evaluator(vec!["examples".into()], None::<()>)
+                        .await
+                },
+            )
             .await?
     }
 
     pub async fn generate(&self, seed: &str) -> Result<Input, Error> {
         self.cache
-            .cached_result(key!(self.evaluator, seed), &[], None, || async {
-                self.run_evaluator(vec!["generate".into(), seed.into()], None::<()>)
+            .cached_result(
+                key!(
+                    self.evaluator,
+                    self.protocol_version,
+                    self.checker.is_some(),
+                    seed
+                ),
+                &[],
+                None,
+                || async {
+                    let args = vec!["generate".into(), seed.into()];
+                    if self.protocol_version < 2 {
+                        let input: Input = self.run_evaluator(args, None::<()>).await?;
+                        return Ok(Input {
+                            test: None,
+                            ..input
+                        });
+                    }
+                    self.run_evaluator_checked(args, None::<()>, |input: &Input| {
+                        let Some(test) = &input.test else {
+                            return Ok(());
+                        };
+                        validate_test(test)?;
+                        if test.comparison == Some(Comparison::Checker) && self.checker.is_none() {
+                            return Err(
+                                "test.comparison is `checker` but the challenge has no checker"
+                                    .into(),
+                            );
+                        }
+                        Ok(())
+                    })
+                    .await
+                },
+            )
+            .await?
+    }
+
+    /// Check an output that has been uploaded for the input generated from the
+    /// given seed.
+    pub async fn check_output(
+        &self,
+        seed: &str,
+        output: &str,
+    ) -> Result<CheckResult<RunResult>, Error> {
+        let input = self.generate(seed).await?;
+        let result = self.evaluate(seed, &input, output, None).await?;
+        Ok(CheckResult {
+            verdict: result.verdict,
+            reason: result.reason,
+            score: result.score,
+            compile: None,
+            run: None,
+            transcript: None,
+        })
+    }
+
+    /// Return the version of the evaluator protocol (`1` for normal and `2` for
+    /// interactive challenges).
+    pub async fn protocol(&self) -> Result<u8, Error> {
+        self.cache
+            .cached_result(key!("protocol", self.evaluator), &[], None, || async {
+                self.run_evaluator(vec!["protocol".into()], None::<()>)
                     .await
             })
             .await?
@@ -104,11 +307,221 @@ impl Judge<'_> {
             .await
     }
 
+    /// Decide whether the output of a solution for the given input is correct,
+    /// either by comparing it with the expected output or by asking the
+    /// evaluator. `solution` is the program which produced the output, if any.
+    async fn evaluate(
+        &self,
+        seed: &str,
+        input: &Input,
+        output: &str,
+        solution: Option<Program<'_>>,
+    ) -> Result<EvaluatorCheckOutput, Error> {
+        let test = input.test.as_ref();
+        let comparison = test
+            .and_then(|test| test.comparison)
+            .unwrap_or(self.comparison);
+        if comparison == Comparison::Evaluator {
+            return self
+                .check(
+                    seed,
+                    &Output {
+                        output,
+                        data: &input.data,
+                    },
+                )
+                .await;
+        }
+
+        let expected = match test.and_then(|test| test.expected.as_deref()) {
+            Some(expected) => Cow::Borrowed(expected),
+            // the reference solution is trivially correct
+            None if solution == Some(self.reference) => {
+                return Ok(EvaluatorCheckOutput {
+                    verdict: ChallengesVerdict::Ok,
+                    reason: None,
+                    score: None,
+                })
+            }
+            None => Cow::Owned(self.reference_output(seed, input).await?),
+        };
+        if comparison == Comparison::Checker {
+            return self.run_checker(input, &expected, output).await;
+        }
+        let epsilon = test
+            .and_then(|test| test.epsilon)
+            .or(self.epsilon)
+            .unwrap_or(DEFAULT_EPSILON);
+        Ok(
+            match compare_output(comparison, epsilon, &expected, output) {
+                Ok(()) => EvaluatorCheckOutput {
+                    verdict: ChallengesVerdict::Ok,
+                    reason: None,
+                    score: None,
+                },
+                Err(reason) => EvaluatorCheckOutput {
+                    verdict: ChallengesVerdict::WrongAnswer,
+                    score: None,
+                    reason: Some(match test.and_then(|test| test.name.as_ref()) {
+                        Some(name) => format!("{name}: {reason}"),
+                        None => reason,
+                    }),
+                },
+            },
+        )
+    }
+
+    /// Ask the checker program whether the output of a solution is correct.
+    ///
+    /// The checker is part of the evaluation, so failures are reported like
+    /// failures of the evaluator.
+    async fn run_checker(
+        &self,
+        input: &Input,
+        expected: &str,
+        output: &str,
+    ) -> Result<EvaluatorCheckOutput, Error> {
+        let checker = self.checker.ok_or(Error::CheckerMissing)?;
+        let out = self
+            .sandkasten
+            .build_and_run(&BuildRunRequest {
+                build: BuildRequest {
+                    environment: checker.environment.into(),
+                    main_file: MainFile {
+                        content: checker.code.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                run: RunRequest {
+                    stdin: Some(serde_json::to_string(&CheckerRequest {
+                        input: &input.input,
+                        expected,
+                        output,
+                    })?),
+                    ..Default::default()
+                },
+            })
+            .await?;
+        if out.run.status != 0 {
+            return Err(Error::EvaluatorFailed(out));
+        }
+        let Ok(result) = serde_json::from_str::<CheckerOutput>(&out.run.stdout) else {
+            return Err(Error::InvalidOutput(out));
+        };
+        let verdict = match result.verdict {
+            CheckerVerdict::Accept => ChallengesVerdict::Ok,
+            CheckerVerdict::Reject => ChallengesVerdict::WrongAnswer,
+            CheckerVerdict::Partial => ChallengesVerdict::PartiallyCorrect,
+        };
+        let score = match (result.verdict, result.score) {
+            (CheckerVerdict::Partial, Some(score)) if (0.0..=1.0).contains(&score) => Some(score),
+            (CheckerVerdict::Partial, _) => return Err(Error::InvalidOutput(out)),
+            _ => None,
+        };
+        Ok(EvaluatorCheckOutput {
+            verdict,
+            reason: result.message,
+            score,
+        })
+    }
+
+    /// Return the output of the reference solution for the given input.
+    async fn reference_output(&self, seed: &str, input: &Input) -> Result<String, Error> {
+        self.cache
+            .cached_result(
+                key!(
+                    "reference_output",
+                    self.evaluator,
+                    self.protocol_version,
+                    seed,
+                    self.reference.environment,
+                    self.reference.code,
+                    self.build_flags.get(self.reference.environment)
+                ),
+                &[],
+                None,
+                || async {
+                    let prepare_result = self
+                        .prepare(
+                            seed,
+                            &PrepareRequest {
+                                environment: self.reference.environment,
+                                code: self.reference.code,
+                                files: &[],
+                                data: &input.data,
+                            },
+                        )
+                        .await?;
+                    let Some(code) = prepare_result.code else {
+                        return Err(Error::ReferenceFailed(Box::new(CheckResult {
+                            verdict: ChallengesVerdict::PreCheckFailed,
+                            reason: Some(prepare_result.reason),
+                            score: None,
+                            compile: None,
+                            run: None,
+                            transcript: None,
+                        })));
+                    };
+                    match run_program(
+                        self.sandkasten,
+                        self.reference.environment,
+                        code,
+                        Vec::new(),
+                        self.build_env(self.reference.environment),
+                        Some(input.input.clone()),
+                        None,
+                        None,
+                    )
+                    .await?
+                    {
+                        Ok(output) => Ok(output.run.stdout),
+                        Err(result) => Err(Error::ReferenceFailed(Box::new(result))),
+                    }
+                },
+            )
+            .await?
+    }
+
+    /// Return the environment variables for building a solution in the given
+    /// environment.
+    fn build_env(&self, environment: &str) -> Vec<EnvVar> {
+        match self.build_flags.get(environment) {
+            Some(flags) if !flags.is_empty() => vec![EnvVar {
+                name: BUILD_FLAGS_VAR.into(),
+                value: flags.join(" "),
+            }],
+            _ => Vec::new(),
+        }
+    }
+
+    async fn interact(
+        &self,
+        seed: &str,
+        interaction: &Interaction<'_>,
+    ) -> Result<InteractionStep, Error> {
+        self.run_evaluator(vec!["interact".into(), seed.into()], Some(interaction))
+            .await
+    }
+
     async fn run_evaluator<I: Serialize, O: DeserializeOwned>(
         &self,
         args: Vec<String>,
         stdin: Option<I>,
     ) -> Result<O, Error> {
+        self.run_evaluator_checked(args, stdin, |_| Ok(())).await
+    }
+
+    /// Run the evaluator and parse its output. Starting with protocol version
+    /// `2` the parsed output is also passed to `validate`, which returns a
+    /// description of the problem if the output is invalid.
+    async fn run_evaluator_checked<I: Serialize, O: DeserializeOwned>(
+        &self,
+        args: Vec<String>,
+        stdin: Option<I>,
+        validate: impl FnOnce(&O) -> Result<(), String>,
+    ) -> Result<O, Error> {
+        let command = args.first().cloned().unwrap_or_default();
         let out = self
             .sandkasten
             .build_and_run(&BuildRunRequest {
@@ -127,6 +540,10 @@ impl Judge<'_> {
                 run: RunRequest {
                     args,
                     stdin: stdin.map(|s| serde_json::to_string(&s)).transpose()?,
+                    env_vars: vec![EnvVar {
+                        name: "PROTOCOL_VERSION".into(),
+                        value: self.protocol_version.to_string(),
+                    }],
                     ..Default::default()
                 },
             })
@@ -134,24 +551,41 @@ impl Judge<'_> {
         if out.run.status != 0 {
             return Err(Error::EvaluatorFailed(out));
         }
-        serde_json::from_str(&out.run.stdout).map_err(|_| Error::InvalidOutput(out))
+        if self.protocol_version < 2 {
+            return serde_json::from_str(&out.run.stdout).map_err(|_| Error::InvalidOutput(out));
+        }
+        let result = serde_json::from_str(&out.run.stdout)
+            .map_err(|err| err.to_string())
+            .and_then(|value| validate(&value).map(|_| value));
+        result.map_err(|message| {
+            Error::ProtocolViolation(Box::new(EvaluatorProtocolError {
+                command,
+                message,
+                output: out,
+            }))
+        })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn run_solution(
         &self,
         seed: &str,
         input: &Input,
         environment: &str,
         code: &str,
+        files: &[File],
         time_limit: Option<u64>,   // ms
         memory_limit: Option<u64>, // mb
     ) -> Result<CheckResult<RunResult>, Error> {
+        // the reference solution never consists of multiple files
+        let solution = files.is_empty().then_some(Program { environment, code });
         let prepare_result = self
             .prepare(
                 seed,
                 &PrepareRequest {
                     environment,
                     code,
+                    files,
                     data: &input.data,
                 },
             )
@@ -162,92 +596,294 @@ impl Judge<'_> {
                 return Ok(CheckResult {
                     verdict: ChallengesVerdict::PreCheckFailed,
                     reason: Some(prepare_result.reason),
+                    score: None,
                     compile: None,
                     run: None,
+                    transcript: None,
                 })
             }
         };
 
-        let output = match self
-            .sandkasten
-            .build_and_run(&BuildRunRequest {
-                build: BuildRequest {
-                    environment: environment.into(),
-                    main_file: MainFile {
-                        content: code,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                run: RunRequest {
-                    stdin: Some(input.input.clone()),
-                    run_limits: LimitsOpt {
-                        time: time_limit.map(|x| x / 1000 + 1),
-                        memory: memory_limit,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-            })
-            .await
+        if self.protocol().await? >= 2 {
+            return self
+                .run_interactive(
+                    seed,
+                    input,
+                    environment,
+                    code,
+                    files,
+                    time_limit,
+                    memory_limit,
+                )
+                .await;
+        }
+
+        let output = match run_program(
+            self.sandkasten,
+            environment,
+            code,
+            files.to_vec(),
+            self.build_env(environment),
+            Some(input.input.clone()),
+            time_limit,
+            memory_limit,
+        )
+        .await?
         {
-            Err(SandkastenError::ErrorResponse(err)) => {
-                return match *err {
-                    ErrorResponse::Inner(BuildRunError::EnvironmentNotFound) => {
-                        Err(Error::EnvironmentNotFound)
-                    }
-                    ErrorResponse::Inner(BuildRunError::CompileError(result)) => Ok(CheckResult {
-                        verdict: ChallengesVerdict::CompilationError,
-                        reason: None,
-                        compile: Some(result),
-                        run: None,
-                    }),
-                    err => Err(Error::Sandkasten(SandkastenError::ErrorResponse(Box::new(
-                        err,
-                    )))),
-                }
-            }
-            x => x?,
+            Ok(output) => output,
+            Err(result) => return Ok(result),
         };
-        if let Some(verdict) = match (time_limit, memory_limit) {
-            (Some(time_limit), _) if output.run.resource_usage.time > time_limit => {
-                Some(ChallengesVerdict::TimeLimitExceeded)
-            }
-            (_, Some(memory_limit)) if output.run.resource_usage.memory / 1024 > memory_limit => {
-                Some(ChallengesVerdict::MemoryLimitExceeded)
-            }
-            _ if output.run.status != 0 => Some(ChallengesVerdict::RuntimeError),
-            _ if output.run.stdout.is_empty() => Some(ChallengesVerdict::NoOutput),
-            _ => None,
-        } {
-            return Ok(CheckResult {
-                verdict,
-                reason: None,
-                compile: output.build,
-                run: Some(output.run),
-            });
-        }
         let result = self
-            .check(
-                seed,
-                &Output {
-                    output: &output.run.stdout,
-                    data: &input.data,
-                },
-            )
+            .evaluate(seed, input, &output.run.stdout, solution)
             .await?;
         Ok(CheckResult {
             verdict: result.verdict,
             reason: result.reason,
+            score: result.score,
             compile: output.build,
             run: Some(output.run),
+            transcript: None,
         })
     }
+
+    /// Run the solution of an interactive challenge.
+    ///
+    /// Sandkasten cannot connect two running programs, so the interaction is
+    /// replayed: in every round the solution is run again with the input and
+    /// all messages of the evaluator so far on stdin, and the `n`-th line of
+    /// its output is used as the response to the `n`-th message.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_interactive(
+        &self,
+        seed: &str,
+        input: &Input,
+        environment: &str,
+        code: String,
+        files: &[File],
+        time_limit: Option<u64>,   // ms
+        memory_limit: Option<u64>, // mb
+    ) -> Result<CheckResult<RunResult>, Error> {
+        let mut stdin = input.input.clone();
+        let mut responses = Vec::new();
+        let mut transcript = Vec::new();
+        let mut compile = None;
+        let mut run = None;
+        while transcript.len() < MAX_ROUNDS {
+            let message = match self
+                .interact(
+                    seed,
+                    &Interaction {
+                        data: &input.data,
+                        responses: &responses,
+                    },
+                )
+                .await?
+            {
+                InteractionStep::Message { message } => message,
+                InteractionStep::Verdict { verdict, reason } => {
+                    return Ok(CheckResult {
+                        verdict,
+                        reason,
+                        score: None,
+                        compile,
+                        run,
+                        transcript: Some(transcript),
+                    })
+                }
+            };
+            stdin.push_str(&message);
+
+            let output = match execute_program(
+                self.sandkasten,
+                environment,
+                code.clone(),
+                files.to_vec(),
+                self.build_env(environment),
+                Some(stdin.clone()),
+                time_limit,
+                memory_limit,
+            )
+            .await?
+            {
+                Ok(output) => output,
+                Err(result) => {
+                    transcript.push(InteractionRound {
+                        judge: message,
+                        program: None,
+                    });
+                    return Ok(CheckResult {
+                        transcript: Some(transcript),
+                        ..result
+                    });
+                }
+            };
+            let response = output.run.stdout.lines().nth(responses.len());
+            transcript.push(InteractionRound {
+                judge: message,
+                program: response.map(Into::into),
+            });
+            let Some(response) = response else {
+                let verdict = if output.run.status != 0 {
+                    ChallengesVerdict::RuntimeError
+                } else {
+                    ChallengesVerdict::NoOutput
+                };
+                return Ok(CheckResult {
+                    verdict,
+                    reason: Some(format!(
+                        "the program did not respond to message {}",
+                        transcript.len()
+                    )),
+                    score: None,
+                    compile: output.build,
+                    run: Some(output.run),
+                    transcript: Some(transcript),
+                });
+            };
+            responses.push(response.into());
+            compile = output.build;
+            run = Some(output.run);
+        }
+
+        Ok(CheckResult {
+            verdict: ChallengesVerdict::WrongAnswer,
+            reason: Some(format!(
+                "the interaction did not end within {MAX_ROUNDS} rounds"
+            )),
+            score: None,
+            compile,
+            run,
+            transcript: Some(transcript),
+        })
+    }
+}
+
+/// Build and run a program. Returns the result if the program terminated
+/// successfully within the given limits and printed something.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_program(
+    sandkasten: &dyn Sandbox,
+    environment: &str,
+    code: String,
+    files: Vec<File>,
+    env_vars: Vec<EnvVar>,
+    stdin: Option<String>,
+    time_limit: Option<u64>,   // ms
+    memory_limit: Option<u64>, // mb
+) -> Result<Result<BuildRunResult, CheckResult<RunResult>>, Error> {
+    let output = match execute_program(
+        sandkasten,
+        environment,
+        code,
+        files,
+        env_vars,
+        stdin,
+        time_limit,
+        memory_limit,
+    )
+    .await?
+    {
+        Ok(output) => output,
+        Err(result) => return Ok(Err(result)),
+    };
+    let verdict = if output.run.status != 0 {
+        ChallengesVerdict::RuntimeError
+    } else if output.run.stdout.is_empty() {
+        ChallengesVerdict::NoOutput
+    } else {
+        return Ok(Ok(output));
+    };
+    Ok(Err(CheckResult {
+        verdict,
+        reason: None,
+        score: None,
+        compile: output.build,
+        run: Some(output.run),
+        transcript: None,
+    }))
+}
+
+/// Build and run a program. Returns the result if the program compiled and
+/// stayed within the given limits, regardless of its exit code.
+#[allow(clippy::too_many_arguments)]
+async fn execute_program(
+    sandkasten: &dyn Sandbox,
+    environment: &str,
+    code: String,
+    files: Vec<File>,
+    env_vars: Vec<EnvVar>,
+    stdin: Option<String>,
+    time_limit: Option<u64>,   // ms
+    memory_limit: Option<u64>, // mb
+) -> Result<Result<BuildRunResult, CheckResult<RunResult>>, Error> {
+    let output = match sandkasten
+        .build_and_run(&BuildRunRequest {
+            build: BuildRequest {
+                environment: environment.into(),
+                main_file: MainFile {
+                    content: code,
+                    ..Default::default()
+                },
+                files,
+                env_vars,
+                ..Default::default()
+            },
+            run: RunRequest {
+                stdin,
+                run_limits: LimitsOpt {
+                    time: time_limit.map(|x| x / 1000 + 1),
+                    memory: memory_limit,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        })
+        .await
+    {
+        Err(SandkastenError::ErrorResponse(err)) => {
+            return match *err {
+                ErrorResponse::Inner(BuildRunError::EnvironmentNotFound) => {
+                    Err(Error::EnvironmentNotFound)
+                }
+                ErrorResponse::Inner(BuildRunError::CompileError(result)) => Ok(Err(CheckResult {
+                    verdict: ChallengesVerdict::CompilationError,
+                    reason: None,
+                    score: None,
+                    compile: Some(result),
+                    run: None,
+                    transcript: None,
+                })),
+                err => Err(Error::Sandkasten(SandkastenError::ErrorResponse(Box::new(
+                    err,
+                )))),
+            }
+        }
+        x => x?,
+    };
+    if let Some(verdict) = match (time_limit, memory_limit) {
+        (Some(time_limit), _) if output.run.resource_usage.time > time_limit => {
+            Some(ChallengesVerdict::TimeLimitExceeded)
+        }
+        (_, Some(memory_limit)) if output.run.resource_usage.memory / 1024 > memory_limit => {
+            Some(ChallengesVerdict::MemoryLimitExceeded)
+        }
+        _ => None,
+    } {
+        return Ok(Err(CheckResult {
+            verdict,
+            reason: None,
+            score: None,
+            compile: output.build,
+            run: Some(output.run),
+            transcript: None,
+        }));
+    }
+    Ok(Ok(output))
 }
 
 pub async fn get_executor_config(
     cache: &Cache<JsonFormatter>,
-    sandkasten: &SandkastenClient,
+    sandkasten: &dyn Sandbox,
 ) -> anyhow::Result<ExecutorConfig> {
     Ok(cache
         .cached_result(key!(), &[], None, || async {
@@ -271,12 +907,82 @@ pub enum Error {
     EvaluatorFailed(BuildRunResult),
     #[error("evaluator failed to produce valid output: {0:?}")]
     InvalidOutput(BuildRunResult),
+    #[error("evaluator violated the protocol in `{}`: {}", .0.command, .0.message)]
+    ProtocolViolation(Box<EvaluatorProtocolError>),
+    #[error("reference solution failed: {0:?}")]
+    ReferenceFailed(Box<CheckResult<RunResult>>),
+    #[error("challenge has no checker")]
+    CheckerMissing,
+}
+
+impl Error {
+    /// Whether the error has been caused by the evaluator.
+    pub fn is_evaluator_error(&self) -> bool {
+        matches!(
+            self,
+            Self::EvaluatorFailed(_) | Self::InvalidOutput(_) | Self::ProtocolViolation(_)
+        )
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Input {
     pub input: String,
     pub data: Value,
+    /// Only provided by evaluators using protocol version `2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<TestMeta>,
+}
+
+/// The metadata of a generated test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestMeta {
+    /// A human-readable name of the test.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The weight of the test relative to the other tests.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// How the output of a solution is checked. Defaults to the comparison of
+    /// the challenge.
+    #[serde(default)]
+    pub comparison: Option<Comparison>,
+    /// The maximum difference between numbers for the `float` comparison.
+    #[serde(default)]
+    pub epsilon: Option<f64>,
+    /// The output of a correct solution. Defaults to the output of the
+    /// reference solution.
+    #[serde(default)]
+    pub expected: Option<String>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Return a description of the problem if the metadata of a test is invalid.
+fn validate_test(test: &TestMeta) -> Result<(), String> {
+    if !test.weight.is_finite() || test.weight <= 0.0 {
+        return Err("test.weight must be a positive number".into());
+    }
+    if test.comparison == Some(Comparison::Evaluator) && test.expected.is_some() {
+        return Err(
+            "test.expected is not allowed if the output is checked by the evaluator".into(),
+        );
+    }
+    if let Some(epsilon) = test.epsilon {
+        if test
+            .comparison
+            .is_some_and(|comparison| comparison != Comparison::Float)
+        {
+            return Err("test.epsilon is only allowed for the `float` comparison".into());
+        }
+        if !epsilon.is_finite() || epsilon < 0.0 {
+            return Err("test.epsilon must be a non-negative number".into());
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug, Serialize)]
@@ -289,6 +995,7 @@ pub struct Output<'a> {
 struct PrepareRequest<'a> {
     environment: &'a str,
     code: &'a str,
+    files: &'a [File],
     data: &'a Value,
 }
 
@@ -298,8 +1005,123 @@ struct PrepareResult {
     reason: String,
 }
 
+#[derive(Debug, Serialize)]
+struct Interaction<'a> {
+    data: &'a Value,
+    responses: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum InteractionStep {
+    Message {
+        message: String,
+    },
+    Verdict {
+        verdict: ChallengesVerdict,
+        reason: Option<String>,
+    },
+}
+
 #[derive(Debug, Deserialize)]
 struct EvaluatorCheckOutput {
     verdict: ChallengesVerdict,
     reason: Option<String>,
+    #[serde(default)]
+    score: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckerRequest<'a> {
+    input: &'a str,
+    expected: &'a str,
+    output: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckerOutput {
+    verdict: CheckerVerdict,
+    #[serde(default)]
+    score: Option<f64>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CheckerVerdict {
+    Accept,
+    Reject,
+    Partial,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse_test(value: Value) -> Result<TestMeta, String> {
+        let test = serde_json::from_value(value).map_err(|err| err.to_string())?;
+        validate_test(&test).map(|_| test)
+    }
+
+    #[test]
+    fn test_meta_defaults() {
+        let test = parse_test(json!({})).unwrap();
+        assert_eq!(test.weight, 1.0);
+        assert!(test.comparison.is_none());
+        assert!(test.name.is_none() && test.epsilon.is_none() && test.expected.is_none());
+
+        let input: Input = serde_json::from_value(json!({"input": "1", "data": 1})).unwrap();
+        assert!(input.test.is_none());
+    }
+
+    #[test]
+    fn test_meta_validation() {
+        assert!(
+            parse_test(json!({"comparison": "float", "expected": "1.5", "epsilon": 0.1})).is_ok()
+        );
+        assert_eq!(
+            parse_test(json!({"weight": 0})).unwrap_err(),
+            "test.weight must be a positive number"
+        );
+        assert!(parse_test(json!({"comparison": "unordered_lines"})).is_ok());
+        assert!(parse_test(json!({"epsilon": 0.5})).is_ok());
+        assert_eq!(
+            parse_test(json!({"comparison": "evaluator", "expected": "1"})).unwrap_err(),
+            "test.expected is not allowed if the output is checked by the evaluator"
+        );
+        assert_eq!(
+            parse_test(json!({"comparison": "exact", "expected": "1", "epsilon": 0.1}))
+                .unwrap_err(),
+            "test.epsilon is only allowed for the `float` comparison"
+        );
+        assert_eq!(
+            parse_test(json!({"comparison": "float", "expected": "1", "epsilon": -1})).unwrap_err(),
+            "test.epsilon must be a non-negative number"
+        );
+        assert!(parse_test(json!({"wieght": 2}))
+            .unwrap_err()
+            .starts_with("unknown field `wieght`"));
+    }
+
+    #[test]
+    fn solution_files() {
+        let file = |name: &str, content: &str| File {
+            name: name.into(),
+            content: content.into(),
+        };
+        assert_eq!(solution_size("main", &[]), 4);
+        assert_eq!(
+            solution_size("main", &[file("a.py", "ab"), file("b.py", "äb")]),
+            9
+        );
+        assert!(!has_duplicate_files(&[file("a.py", ""), file("b.py", "")]));
+        assert!(has_duplicate_files(&[
+            file("a.py", ""),
+            file("b.py", ""),
+            file("a.py", "x")
+        ]));
+    }
 }
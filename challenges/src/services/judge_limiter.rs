@@ -0,0 +1,228 @@
+use std::time::{Duration, Instant};
+
+use lib::redis::RedisConnection;
+use redis::RedisResult;
+use tokio::task::JoinHandle;
+use tracing::{error, trace, warn};
+use uuid::Uuid;
+
+/// Remove expired tokens and add a new token if the number of tokens is below
+/// the capacity. Returns 1 if the token has been added and 0 otherwise.
+const ACQUIRE_SCRIPT: &str = r"
+local now = redis.call('TIME')
+local now_ms = tonumber(now[1]) * 1000 + math.floor(tonumber(now[2]) / 1000)
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now_ms)
+if redis.call('ZCARD', KEYS[1]) < tonumber(ARGV[1]) then
+    redis.call('ZADD', KEYS[1], now_ms + tonumber(ARGV[2]), ARGV[3])
+    return 1
+end
+return 0
+";
+
+/// Extend the lease of an existing token. Returns 1 if the token still exists
+/// and 0 otherwise.
+const REFRESH_SCRIPT: &str = r"
+local now = redis.call('TIME')
+local now_ms = tonumber(now[1]) * 1000 + math.floor(tonumber(now[2]) / 1000)
+return redis.call('ZADD', KEYS[1], 'XX', 'CH', now_ms + tonumber(ARGV[1]), ARGV[2])
+";
+
+/// Add the wait time (in milliseconds) of a newly acquired token to the wait
+/// time statistics.
+const RECORD_WAIT_SCRIPT: &str = r"
+redis.call('HINCRBY', KEYS[1], 'acquired', 1)
+redis.call('HINCRBY', KEYS[1], 'total', ARGV[1])
+if tonumber(redis.call('HGET', KEYS[1], 'max') or 0) < tonumber(ARGV[1]) then
+    redis.call('HSET', KEYS[1], 'max', ARGV[1])
+end
+return 0
+";
+
+/// The interval between two attempts to acquire a token.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Limits the number of submissions judged at the same time across all judge
+/// workers.
+///
+/// Each running judge task holds a token in a sorted set in redis. Tokens are
+/// leased for a limited time and extended while the task is running, so tokens
+/// of crashed workers expire eventually.
+#[derive(Debug, Clone)]
+pub struct JudgeLimiter {
+    redis: RedisConnection,
+    key: String,
+    capacity: usize,
+    lease: Duration,
+}
+
+/// Statistics about the time judge workers had to wait for a token.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WaitTimes {
+    /// The number of tokens acquired.
+    pub acquired: u64,
+    /// The total time spent waiting for tokens.
+    pub total: Duration,
+    /// The longest time spent waiting for a single token.
+    pub max: Duration,
+}
+
+impl WaitTimes {
+    pub fn average(&self) -> Duration {
+        self.total
+            .checked_div(self.acquired.try_into().unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+}
+
+impl JudgeLimiter {
+    pub fn new(redis: RedisConnection, capacity: usize, lease: Duration) -> Self {
+        Self {
+            redis,
+            key: "challenges:judge_tokens".into(),
+            capacity,
+            lease,
+        }
+    }
+
+    /// The maximum number of submissions judged at the same time.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of tokens that are currently held.
+    pub async fn active(&self) -> RedisResult<usize> {
+        redis::cmd("ZCARD")
+            .arg(&self.key)
+            .query_async(&mut self.redis.clone())
+            .await
+    }
+
+    /// Return the wait time statistics of all judge workers.
+    pub async fn wait_times(&self) -> RedisResult<WaitTimes> {
+        let (acquired, total, max): (Option<u64>, Option<u64>, Option<u64>) = redis::cmd("HMGET")
+            .arg(self.wait_times_key())
+            .arg(&["acquired", "total", "max"][..])
+            .query_async(&mut self.redis.clone())
+            .await?;
+        Ok(WaitTimes {
+            acquired: acquired.unwrap_or_default(),
+            total: Duration::from_millis(total.unwrap_or_default()),
+            max: Duration::from_millis(max.unwrap_or_default()),
+        })
+    }
+
+    /// Wait until a token is available and acquire it. The token is released
+    /// when the returned permit is dropped.
+    pub async fn acquire(&self) -> JudgePermit {
+        let token = Uuid::new_v4().to_string();
+        let start = Instant::now();
+        loop {
+            match self.try_acquire(&token).await {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(err) => warn!("failed to acquire judge token: {err}"),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        let wait_time = start.elapsed();
+        trace!("acquired judge token {token} after {wait_time:?}");
+        if let Err(err) = self.record_wait_time(wait_time).await {
+            warn!("failed to record judge token wait time: {err}");
+        }
+
+        JudgePermit {
+            refresh: tokio::spawn(self.clone().refresh(token.clone())),
+            limiter: self.clone(),
+            token,
+        }
+    }
+
+    fn wait_times_key(&self) -> String {
+        format!("{}:wait_times", self.key)
+    }
+
+    async fn try_acquire(&self, token: &str) -> RedisResult<bool> {
+        redis::cmd("EVAL")
+            .arg(ACQUIRE_SCRIPT)
+            .arg(1)
+            .arg(&self.key)
+            .arg(self.capacity)
+            .arg(self.lease.as_millis() as u64)
+            .arg(token)
+            .query_async(&mut self.redis.clone())
+            .await
+    }
+
+    async fn record_wait_time(&self, wait_time: Duration) -> RedisResult<()> {
+        redis::cmd("EVAL")
+            .arg(RECORD_WAIT_SCRIPT)
+            .arg(1)
+            .arg(self.wait_times_key())
+            .arg(wait_time.as_millis() as u64)
+            .query_async(&mut self.redis.clone())
+            .await
+    }
+
+    /// Extend the lease of the token periodically until the task is aborted.
+    async fn refresh(self, token: String) {
+        loop {
+            tokio::time::sleep(self.lease / 3).await;
+            let result: RedisResult<bool> = redis::cmd("EVAL")
+                .arg(REFRESH_SCRIPT)
+                .arg(1)
+                .arg(&self.key)
+                .arg(self.lease.as_millis() as u64)
+                .arg(&token)
+                .query_async(&mut self.redis.clone())
+                .await;
+            match result {
+                Ok(true) => {}
+                Ok(false) => error!("judge token {token} expired before it was released"),
+                Err(err) => warn!("failed to refresh judge token {token}: {err}"),
+            }
+        }
+    }
+
+    async fn release(self, token: String) {
+        let result: RedisResult<()> = redis::cmd("ZREM")
+            .arg(&self.key)
+            .arg(&token)
+            .query_async(&mut self.redis.clone())
+            .await;
+        if let Err(err) = result {
+            error!("failed to release judge token {token}: {err}");
+        }
+    }
+}
+
+/// A token of the [`JudgeLimiter`]. The token is released on drop.
+pub struct JudgePermit {
+    limiter: JudgeLimiter,
+    token: String,
+    refresh: JoinHandle<()>,
+}
+
+impl Drop for JudgePermit {
+    fn drop(&mut self) {
+        self.refresh.abort();
+        tokio::spawn(
+            self.limiter
+                .clone()
+                .release(std::mem::take(&mut self.token)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_wait_time() {
+        let mut wait_times = WaitTimes::default();
+        assert_eq!(wait_times.average(), Duration::ZERO);
+        wait_times.acquired = 2;
+        wait_times.total = Duration::from_millis(400);
+        assert_eq!(wait_times.average(), Duration::from_millis(200));
+    }
+}
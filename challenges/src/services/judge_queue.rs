@@ -0,0 +1,192 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::Utc;
+use entity::challenges_judge_queue;
+use sea_orm::{
+    sea_query::{Expr, LockBehavior, LockType, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+/// Add submissions to the end of the judge queue. Submissions that are already
+/// queued keep their position.
+pub async fn enqueue_submissions(
+    db: &impl ConnectionTrait,
+    submission_ids: impl IntoIterator<Item = Uuid>,
+    rejudge: bool,
+) -> Result<(), DbErr> {
+    let now = Utc::now().naive_utc();
+    let entries = submission_ids
+        .into_iter()
+        .map(|submission_id| challenges_judge_queue::ActiveModel {
+            submission_id: Set(submission_id),
+            rejudge: Set(rejudge),
+            enqueued_timestamp: Set(now),
+            claimed_until: Set(None),
+            attempts: Set(0),
+        })
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return Ok(());
+    }
+    challenges_judge_queue::Entity::insert_many(entries)
+        .on_conflict(
+            OnConflict::column(challenges_judge_queue::Column::SubmissionId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+    Ok(())
+}
+
+/// Claim the oldest submission in the judge queue that is not claimed by
+/// another worker and count the attempt. The claim expires after `lease`
+/// unless it is extended.
+pub async fn claim_next_submission(
+    db: &DatabaseConnection,
+    lease: Duration,
+) -> Result<Option<challenges_judge_queue::Model>, DbErr> {
+    let txn = db.begin().await?;
+    let now = Utc::now().naive_utc();
+    let Some(entry) = challenges_judge_queue::Entity::find()
+        .filter(
+            Condition::any()
+                .add(challenges_judge_queue::Column::ClaimedUntil.is_null())
+                .add(challenges_judge_queue::Column::ClaimedUntil.lt(now)),
+        )
+        .order_by_asc(challenges_judge_queue::Column::EnqueuedTimestamp)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .one(&txn)
+        .await?
+    else {
+        txn.rollback().await?;
+        return Ok(None);
+    };
+    let attempts = entry.attempts + 1;
+    let mut entry = entry.into_active_model();
+    entry.claimed_until = Set(Some(now + lease));
+    entry.attempts = Set(attempts);
+    let entry = entry.update(&txn).await?;
+    txn.commit().await?;
+    Ok(Some(entry))
+}
+
+/// Extend the claim on a queued submission. Returns `false` if the submission
+/// has been removed from the queue in the meantime.
+pub async fn extend_claim(
+    db: &impl ConnectionTrait,
+    submission_id: Uuid,
+    lease: Duration,
+) -> Result<bool, DbErr> {
+    let result = challenges_judge_queue::Entity::update_many()
+        .filter(challenges_judge_queue::Column::SubmissionId.eq(submission_id))
+        .col_expr(
+            challenges_judge_queue::Column::ClaimedUntil,
+            Expr::value(Some(Utc::now().naive_utc() + lease)),
+        )
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// Release the claim on a queued submission and move it to the end of the
+/// queue, so that it does not block the other submissions.
+pub async fn release_claim(db: &impl ConnectionTrait, submission_id: Uuid) -> Result<(), DbErr> {
+    challenges_judge_queue::Entity::update_many()
+        .filter(challenges_judge_queue::Column::SubmissionId.eq(submission_id))
+        .col_expr(
+            challenges_judge_queue::Column::ClaimedUntil,
+            Expr::value(Option::<chrono::NaiveDateTime>::None),
+        )
+        .col_expr(
+            challenges_judge_queue::Column::EnqueuedTimestamp,
+            Expr::value(Utc::now().naive_utc()),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Load the current positions of all queued submissions.
+pub async fn get_queue_positions(
+    db: &impl ConnectionTrait,
+    workers: usize,
+) -> Result<QueuePositions, DbErr> {
+    let queue = challenges_judge_queue::Entity::find()
+        .select_only()
+        .column(challenges_judge_queue::Column::SubmissionId)
+        .order_by_asc(challenges_judge_queue::Column::EnqueuedTimestamp)
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await?;
+    Ok(QueuePositions::new(workers, queue))
+}
+
+/// The positions of the submissions in the judge queue. The first `workers`
+/// submissions are assumed to be judged at the moment.
+pub struct QueuePositions {
+    workers: usize,
+    len: usize,
+    ids: HashMap<Uuid, usize>,
+}
+
+impl QueuePositions {
+    pub fn new(workers: usize, queue: Vec<Uuid>) -> Self {
+        Self {
+            workers,
+            len: queue.len(),
+            ids: queue
+                .into_iter()
+                .enumerate()
+                .map(|(i, id)| (id, i))
+                .collect(),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub fn active(&self) -> usize {
+        self.workers.min(self.len)
+    }
+
+    pub fn waiting(&self) -> usize {
+        self.len.saturating_sub(self.workers)
+    }
+
+    /// Return the number of submissions that have to be picked up by a worker
+    /// before this one, or `0` if the submission is being judged.
+    pub fn position(&self, key: Uuid) -> Option<usize> {
+        let index = *self.ids.get(&key)?;
+        Some((index + 1).saturating_sub(self.workers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_positions() {
+        let key = Uuid::from_u128;
+        let qp = QueuePositions::new(3, vec![]);
+        assert_eq!(qp.workers(), 3);
+        assert_eq!((qp.active(), qp.waiting()), (0, 0));
+
+        let qp = QueuePositions::new(3, vec![key(0), key(1)]);
+        assert_eq!((qp.active(), qp.waiting()), (2, 0));
+
+        let qp = QueuePositions::new(3, (0..6).map(key).collect());
+        assert_eq!((qp.active(), qp.waiting()), (3, 3));
+        assert_eq!(qp.position(key(0)), Some(0));
+        assert_eq!(qp.position(key(1)), Some(0));
+        assert_eq!(qp.position(key(2)), Some(0));
+        assert_eq!(qp.position(key(3)), Some(1));
+        assert_eq!(qp.position(key(4)), Some(2));
+        assert_eq!(qp.position(key(5)), Some(3));
+        assert_eq!(qp.position(key(6)), None);
+    }
+}
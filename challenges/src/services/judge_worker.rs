@@ -0,0 +1,661 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use entity::{
+    challenges_coding_challenge_result::{self, Resources, TestResources, Transcript},
+    challenges_coding_challenge_submissions, challenges_coding_challenges, challenges_judge_queue,
+    challenges_submission_artifacts, challenges_submission_files, challenges_subtasks,
+    challenges_user_subtasks,
+    sea_orm_active_enums::ChallengesVerdict,
+};
+use fnct::format::JsonFormatter;
+use lib::{
+    config::{
+        challenges::{DueDates, ReSolve, Rewards},
+        SharedConfig,
+    },
+    events::{publish_event, Event, Notification},
+    Cache, SharedState,
+};
+use sandkasten_client::schemas::programs::{File, ResourceUsage};
+use sea_orm::{
+    sea_query::{Expr, LockType},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction,
+    DbBackend, DbErr, EntityTrait, ModelTrait, QueryFilter, QuerySelect, Set, Statement,
+    TransactionTrait,
+};
+use thiserror::Error;
+use tracing::{debug, error, info, trace, warn};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::coding_challenges::{
+        check_challenge, CheckChallenge, CheckError, CheckTestcaseError,
+    },
+    services::{
+        build_configs::get_build_flags,
+        judge::{self, checker_program, Judge, Program},
+        judge_limiter::JudgeLimiter,
+        judge_queue::{claim_next_submission, extend_claim, release_claim},
+        notifications::{notify, notify_subtask_solved},
+        personal_bests::update_personal_best,
+        sandbox::{MeteredSandbox, SharedSandbox},
+        sandbox_usage::record_sandbox_usage,
+        subtasks::{
+            get_user_subtask, send_task_rewards, update_user_subtask, SendTaskRewardsError,
+            SolveKind, UserSubtaskExt,
+        },
+    },
+};
+
+/// The time a worker waits before it looks for new submissions if the judge
+/// queue is empty.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Judges the submissions in the judge queue.
+pub struct JudgeWorker {
+    pub state: Arc<SharedState>,
+    pub config: SharedConfig,
+    pub sandkasten: SharedSandbox,
+    pub judge_cache: Cache<JsonFormatter>,
+    pub judge_limiter: JudgeLimiter,
+}
+
+impl JudgeWorker {
+    /// Judge submissions with `concurrency` tasks until the process is
+    /// stopped.
+    pub async fn run(self: Arc<Self>, concurrency: usize) {
+        info!("Starting {concurrency} judge task(s)");
+        let tasks = (0..concurrency)
+            .map(|_| tokio::spawn(Arc::clone(&self).run_task()))
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.ok();
+        }
+    }
+
+    async fn run_task(self: Arc<Self>) {
+        loop {
+            match self.judge_next().await {
+                Ok(true) => {}
+                Ok(false) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(err) => {
+                    error!("judge task failed: {err}");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Claim the oldest submission in the judge queue and judge it. Returns
+    /// `false` if the queue is empty.
+    async fn judge_next(&self) -> Result<bool, DbErr> {
+        let lease =
+            Duration::from_secs(self.config.load().challenges.coding_challenges.judge_lease);
+        // the claim is committed right away, so no transaction is kept open
+        // while the submission is being judged
+        let Some(entry) = claim_next_submission(&self.state.db, lease).await? else {
+            return Ok(false);
+        };
+        let submission_id = entry.submission_id;
+        let attempts = entry.attempts;
+
+        if let Err(err) = self.judge_entry(entry, lease).await {
+            error!("judge task for {submission_id} failed (attempt {attempts}): {err}");
+            let max_attempts = self
+                .config
+                .load()
+                .challenges
+                .coding_challenges
+                .judge_attempts;
+            if attempts >= max_attempts as i32 {
+                fail_submission(&self.state.db, submission_id).await?;
+            } else {
+                release_claim(&self.state.db, submission_id).await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Judge the submission of a claimed queue entry and remove the entry.
+    async fn judge_entry(
+        &self,
+        entry: challenges_judge_queue::Model,
+        lease: Duration,
+    ) -> Result<(), JudgeSubmissionError> {
+        let db = &self.state.db;
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(entry.submission_id)
+                .one(db)
+                .await?
+        else {
+            return Ok(());
+        };
+        let Some(cc) = challenges_coding_challenges::Entity::find_by_id(submission.subtask_id)
+            .one(db)
+            .await?
+        else {
+            warn!("coding challenge of submission {} not found", submission.id);
+            entry.delete(db).await?;
+            return Ok(());
+        };
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(submission.subtask_id)
+            .one(db)
+            .await?
+        else {
+            warn!("subtask of submission {} not found", submission.id);
+            entry.delete(db).await?;
+            return Ok(());
+        };
+        let build_flags = get_build_flags(db, cc.subtask_id).await?;
+        let files = submission
+            .find_related(challenges_submission_files::Entity)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|file| File {
+                name: file.name,
+                content: file.content,
+            })
+            .collect::<Vec<_>>();
+
+        let sandbox = MeteredSandbox::new(&*self.sandkasten);
+        let judge = Judge {
+            sandkasten: &sandbox,
+            evaluator: &cc.evaluator,
+            protocol_version: cc.protocol_version,
+            comparison: cc.comparison.into(),
+            epsilon: cc.epsilon,
+            reference: Program {
+                environment: &cc.solution_environment,
+                code: &cc.solution_code,
+            },
+            checker: checker_program(&cc),
+            build_flags: &build_flags,
+            cache: &self.judge_cache,
+        };
+        debug!("judging submission {}", submission.id);
+        let result = self
+            .while_claimed(submission.id, lease, async {
+                let _permit = self.judge_limiter.acquire().await;
+                check_challenge(CheckChallenge {
+                    judge,
+                    challenge_id: cc.subtask_id,
+                    solution_environment: &submission.environment,
+                    solution_code: &submission.code,
+                    solution_files: &files,
+                    time_limit: cc.time_limit as _,
+                    memory_limit: cc.memory_limit as _,
+                    static_tests: cc.static_tests as _,
+                    random_tests: cc.random_tests as _,
+                })
+                .await
+            })
+            .await;
+        record_sandbox_usage(db, cc.subtask_id, &sandbox, Utc::now().naive_utc()).await?;
+        let Some(result) = result? else {
+            info!(
+                "submission {} has been removed from the judge queue",
+                submission.id
+            );
+            return Ok(());
+        };
+        let result = result.map_err(Box::new)?;
+        trace!("judge result for {}: {result:?}", submission.id);
+
+        let txn = db.begin().await?;
+        // the submission might have been cancelled while it was being judged
+        let Some(entry) = challenges_judge_queue::Entity::find_by_id(submission.id)
+            .lock(LockType::Update)
+            .one(&txn)
+            .await?
+        else {
+            txn.rollback().await?;
+            info!(
+                "submission {} has been removed from the judge queue",
+                submission.id
+            );
+            return Ok(());
+        };
+        let config = self.config.load();
+        save_judge_result(SaveJudgeResult {
+            db: &txn,
+            subtask: &subtask,
+            challenge: &cc,
+            user_subtask: get_user_subtask(&txn, submission.creator, subtask.id).await?,
+            submission: Arc::new(submission),
+            result,
+            state: Arc::clone(&self.state),
+            rejudge: entry.rejudge,
+            max_artifact_size: config.challenges.coding_challenges.max_artifact_size,
+            re_solve: config.challenges.re_solve.clone(),
+            due_dates: config.challenges.due_dates.clone(),
+            rewards: config.challenges.rewards.clone(),
+        })
+        .await?;
+        entry.delete(&txn).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Run `future` while extending the claim on a queued submission. Returns
+    /// `None` if the submission has been removed from the queue in the
+    /// meantime.
+    async fn while_claimed<T>(
+        &self,
+        submission_id: Uuid,
+        lease: Duration,
+        future: impl Future<Output = T>,
+    ) -> Result<Option<T>, DbErr> {
+        tokio::pin!(future);
+        let mut interval = tokio::time::interval(lease / 2);
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                output = &mut future => return Ok(Some(output)),
+                _ = interval.tick() => {
+                    if !extend_claim(&self.state.db, submission_id, lease).await? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct SaveJudgeResult<'a> {
+    db: &'a DatabaseTransaction,
+    subtask: &'a challenges_subtasks::Model,
+    challenge: &'a challenges_coding_challenges::Model,
+    submission: Arc<challenges_coding_challenge_submissions::Model>,
+    result: Result<Vec<ResourceUsage>, CheckError>,
+    state: Arc<SharedState>,
+    user_subtask: Option<challenges_user_subtasks::Model>,
+    /// Whether the submission has been judged before. Attempts are only
+    /// counted the first time.
+    rejudge: bool,
+    /// The maximum number of bytes of stdout and stderr to store for a failed
+    /// submission.
+    max_artifact_size: usize,
+    re_solve: ReSolve,
+    due_dates: DueDates,
+    rewards: Rewards,
+}
+
+/// Store the result of a judged submission and update the progress of its
+/// creator.
+async fn save_judge_result(
+    SaveJudgeResult {
+        db,
+        subtask,
+        challenge,
+        submission,
+        result,
+        state,
+        user_subtask,
+        rejudge,
+        max_artifact_size,
+        re_solve,
+        due_dates,
+        rewards,
+    }: SaveJudgeResult<'_>,
+) -> Result<(), JudgeSubmissionError> {
+    let attempts = user_subtask.attempts() as i32 + if rejudge { 0 } else { 1 };
+    let judged = match result {
+        Ok(resource_usage) => {
+            let time = resource_usage
+                .iter()
+                .map(|x| x.time)
+                .max()
+                .unwrap_or_default();
+            let memory = resource_usage
+                .iter()
+                .map(|x| x.memory)
+                .max()
+                .unwrap_or_default();
+
+            // serializes the progress updates of this user and subtask across
+            // all judge workers until the transaction ends
+            lock_user_subtask(db, submission.creator, subtask.id).await?;
+
+            let new_best = update_personal_best(
+                db,
+                submission.creator,
+                subtask.id,
+                submission.id,
+                time as _,
+                memory as _,
+            )
+            .await?;
+
+            // the snapshot taken when the submission was enqueued may be stale
+            // if other submissions of the same user have been judged since then
+            let current = get_user_subtask(db, submission.creator, subtask.id).await?;
+            let solve_kind = current.solve_kind(&re_solve, submission.creation_timestamp.and_utc());
+            if let Some(solve_kind) = solve_kind {
+                update_user_subtask(
+                    db,
+                    current.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(submission.creator),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(submission.creation_timestamp)),
+                        last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
+                        attempts: Set(attempts),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if solve_kind == SolveKind::First {
+                    publish_event(
+                        db,
+                        Event::SubtaskSolved {
+                            user_id: submission.creator,
+                            task_id: subtask.task_id,
+                            subtask_id: subtask.id,
+                        },
+                    )
+                    .await?;
+                    notify_subtask_solved(db, subtask, submission.creator).await?;
+                }
+
+                if submission.creator != subtask.creator
+                    && claim_submission_rewards(&state.db, submission.id).await?
+                {
+                    send_task_rewards(
+                        &state.services,
+                        db,
+                        &due_dates,
+                        &rewards,
+                        submission.creator,
+                        subtask,
+                        solve_kind,
+                        submission.creation_timestamp,
+                    )
+                    .await?;
+                }
+            }
+            challenges_coding_challenge_result::ActiveModel {
+                submission_id: Set(submission.id),
+                verdict: Set(ChallengesVerdict::Ok),
+                reason: Set(None),
+                build_status: Set(None),
+                build_stderr: Set(None),
+                build_time: Set(None),
+                build_memory: Set(None),
+                run_status: Set(None),
+                run_stderr: Set(None),
+                run_time: Set(Some(time as _)),
+                run_memory: Set(Some(memory as _)),
+                transcript: Set(None),
+                evaluator_version: Set(Some(challenge.evaluator_version)),
+                seed: Set(None),
+                score: Set(None),
+                resources: Set(Some(Resources {
+                    time_limit: challenge.time_limit as _,
+                    memory_limit: challenge.memory_limit as _,
+                    tests: resource_usage
+                        .into_iter()
+                        .map(|x| TestResources {
+                            time: x.time,
+                            memory: x.memory,
+                        })
+                        .collect(),
+                })),
+                personal_best_time: Set(new_best.time),
+                personal_best_memory: Set(new_best.memory),
+            }
+            .insert(db)
+            .await?
+        }
+        Err(CheckError::TestcaseFailed(CheckTestcaseError { seed, result })) => {
+            // only the output of the first failed test case is stored
+            let artifacts = result.run.as_ref().or(result.compile.as_ref()).map(|x| {
+                let (stdout, stdout_truncated) = truncate_output(&x.stdout, max_artifact_size);
+                let (stderr, stderr_truncated) = truncate_output(&x.stderr, max_artifact_size);
+                challenges_submission_artifacts::ActiveModel {
+                    submission_id: Set(submission.id),
+                    stdout: Set(stdout),
+                    stderr: Set(stderr),
+                    truncated: Set(stdout_truncated || stderr_truncated),
+                }
+            });
+            let (build_status, build_stderr, build_time, build_memory) = match result.compile {
+                Some(x) => (
+                    Some(x.status),
+                    Some(x.stderr),
+                    Some(x.resource_usage.time as _),
+                    Some(x.resource_usage.memory as _),
+                ),
+                None => (None, None, None, None),
+            };
+            let (run_status, run_stderr, run_time, run_memory) = match result.run {
+                Some(x) => (
+                    Some(x.status),
+                    Some(x.stderr),
+                    Some(x.resource_usage.time as _),
+                    Some(x.resource_usage.memory as _),
+                ),
+                None => (None, None, None, None),
+            };
+            if !rejudge {
+                update_user_subtask(
+                    db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(submission.creator),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
+                        attempts: Set(attempts),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+            let judged = challenges_coding_challenge_result::ActiveModel {
+                submission_id: Set(submission.id),
+                verdict: Set(result.verdict),
+                reason: Set(result.reason),
+                build_status: Set(build_status),
+                build_stderr: Set(build_stderr),
+                build_time: Set(build_time),
+                build_memory: Set(build_memory),
+                run_status: Set(run_status),
+                run_stderr: Set(run_stderr),
+                run_time: Set(run_time),
+                run_memory: Set(run_memory),
+                transcript: Set(result
+                    .transcript
+                    .map(|x| Transcript(x.into_iter().map(Into::into).collect()))),
+                evaluator_version: Set(Some(challenge.evaluator_version)),
+                seed: Set(Some(seed)),
+                score: Set(result.score),
+                resources: Set(None),
+                personal_best_time: Set(false),
+                personal_best_memory: Set(false),
+            }
+            .insert(db)
+            .await?;
+            if let Some(artifacts) = artifacts {
+                artifacts.insert(db).await?;
+            }
+            judged
+        }
+        Err(err) => return Err(JudgeSubmissionError::Check(Box::new(err))),
+    };
+    publish_event(
+        db,
+        Event::SubmissionJudged {
+            submission_id: submission.id,
+            user_id: submission.creator,
+            subtask_id: subtask.id,
+            verdict: judged.verdict,
+        },
+    )
+    .await?;
+    notify(
+        db,
+        submission.creator,
+        Notification::SubmissionJudged {
+            submission_id: submission.id,
+            subtask_id: subtask.id,
+            verdict: judged.verdict,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Give up on a submission that could not be judged: remove it from the judge
+/// queue and store an `internal_error` result.
+async fn fail_submission(db: &DatabaseConnection, submission_id: Uuid) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    let Some(entry) = challenges_judge_queue::Entity::find_by_id(submission_id)
+        .lock(LockType::Update)
+        .one(&txn)
+        .await?
+    else {
+        txn.rollback().await?;
+        return Ok(());
+    };
+    let Some(submission) = entry
+        .find_related(challenges_coding_challenge_submissions::Entity)
+        .one(&txn)
+        .await?
+    else {
+        txn.rollback().await?;
+        return Ok(());
+    };
+    entry.delete(&txn).await?;
+
+    challenges_coding_challenge_result::Entity::delete_by_id(submission.id)
+        .exec(&txn)
+        .await?;
+    challenges_submission_artifacts::Entity::delete_by_id(submission.id)
+        .exec(&txn)
+        .await?;
+    let result = challenges_coding_challenge_result::ActiveModel {
+        submission_id: Set(submission.id),
+        verdict: Set(ChallengesVerdict::InternalError),
+        reason: Set(Some("The submission could not be judged.".into())),
+        build_status: Set(None),
+        build_stderr: Set(None),
+        build_time: Set(None),
+        build_memory: Set(None),
+        run_status: Set(None),
+        run_stderr: Set(None),
+        run_time: Set(None),
+        run_memory: Set(None),
+        transcript: Set(None),
+        evaluator_version: Set(None),
+        seed: Set(None),
+        score: Set(None),
+        resources: Set(None),
+        personal_best_time: Set(false),
+        personal_best_memory: Set(false),
+    }
+    .insert(&txn)
+    .await?;
+    publish_event(
+        &txn,
+        Event::SubmissionJudged {
+            submission_id: submission.id,
+            user_id: submission.creator,
+            subtask_id: submission.subtask_id,
+            verdict: result.verdict,
+        },
+    )
+    .await?;
+    notify(
+        &txn,
+        submission.creator,
+        Notification::SubmissionJudged {
+            submission_id: submission.id,
+            subtask_id: submission.subtask_id,
+            verdict: result.verdict,
+        },
+    )
+    .await?;
+    txn.commit().await?;
+
+    warn!("gave up judging submission {submission_id}");
+    Ok(())
+}
+
+/// Acquire a transaction level lock on the progress of a user in a subtask.
+async fn lock_user_subtask(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "select pg_advisory_xact_lock(hashtextextended($1, 0))",
+        [format!("user_subtask:{user_id}:{subtask_id}").into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Mark the rewards of a submission as granted. Returns `false` if they have
+/// already been granted before.
+///
+/// The mark is committed right away, so that rewards are never granted twice
+/// for the same submission, even if storing the judge result fails afterwards
+/// and the submission is judged again.
+async fn claim_submission_rewards(
+    db: &DatabaseConnection,
+    submission_id: Uuid,
+) -> Result<bool, DbErr> {
+    let result = challenges_coding_challenge_submissions::Entity::update_many()
+        .filter(challenges_coding_challenge_submissions::Column::Id.eq(submission_id))
+        .filter(challenges_coding_challenge_submissions::Column::Rewarded.eq(false))
+        .col_expr(
+            challenges_coding_challenge_submissions::Column::Rewarded,
+            Expr::value(true),
+        )
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+#[derive(Debug, Error)]
+enum JudgeSubmissionError {
+    #[error("failed to judge submission: {0}")]
+    Judge(#[from] Box<judge::Error>),
+    #[error("database error: {0}")]
+    Db(#[from] DbErr),
+    #[error("check error: {0:?}")]
+    Check(Box<CheckError>),
+    #[error("could not send task rewards: {0}")]
+    TaskRewards(#[from] SendTaskRewardsError),
+}
+
+/// Truncate the output of a program to at most `max_size` bytes. Return
+/// whether the output has been truncated.
+fn truncate_output(output: &str, max_size: usize) -> (String, bool) {
+    if output.len() <= max_size {
+        return (output.into(), false);
+    }
+    let end = (0..=max_size)
+        .rev()
+        .find(|&i| output.is_char_boundary(i))
+        .unwrap_or(0);
+    (output[..end].into(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(truncate_output("hello", 5), ("hello".to_owned(), false));
+        assert_eq!(truncate_output("hello", 3), ("hel".to_owned(), true));
+        assert_eq!(truncate_output("", 0), (String::new(), false));
+        assert_eq!(truncate_output("äöü", 3), ("ä".to_owned(), true));
+    }
+}
@@ -0,0 +1,176 @@
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    sea_orm_active_enums::ChallengesVerdict,
+};
+use futures::future::try_join_all;
+use lib::services::Services;
+use schemas::challenges::leaderboard::{
+    CodingChallengeLeaderboard, CodingChallengeLeaderboardUser, LeaderboardMetric, Rank,
+};
+use sea_orm::{
+    sea_query::{Alias, Expr, Query, SelectStatement},
+    ColumnTrait, ConnectionTrait, DatabaseTransaction, Order,
+};
+use uuid::Uuid;
+
+use super::resolve_user;
+
+/// Return the query selecting the best accepted submission of each user.
+fn get_base_query(
+    subtask_id: Uuid,
+    metric: LeaderboardMetric,
+    environment: Option<&str>,
+) -> SelectStatement {
+    let score = match metric {
+        LeaderboardMetric::Time => Expr::col(challenges_coding_challenge_result::Column::RunTime),
+        LeaderboardMetric::Memory => {
+            Expr::col(challenges_coding_challenge_result::Column::RunMemory)
+        }
+        LeaderboardMetric::Size => {
+            Expr::col(challenges_coding_challenge_submissions::Column::CodeLength)
+        }
+    };
+    let mut query = Query::select()
+        .distinct_on([(
+            challenges_coding_challenge_submissions::Entity,
+            challenges_coding_challenge_submissions::Column::Creator,
+        )])
+        .expr_as(
+            Expr::col(challenges_coding_challenge_submissions::Column::Creator),
+            Alias::new("user_id"),
+        )
+        .expr_as(
+            Expr::col(challenges_coding_challenge_submissions::Column::Id),
+            Alias::new("submission_id"),
+        )
+        .expr_as(
+            Expr::col(challenges_coding_challenge_submissions::Column::Environment),
+            Alias::new("environment"),
+        )
+        .expr_as(
+            score.clone().cast_as(Alias::new("int8")),
+            Alias::new("score"),
+        )
+        .expr_as(
+            Expr::col(challenges_coding_challenge_submissions::Column::CreationTimestamp),
+            Alias::new("timestamp"),
+        )
+        .from(challenges_coding_challenge_submissions::Entity)
+        .inner_join(
+            challenges_coding_challenge_result::Entity,
+            Expr::col((
+                challenges_coding_challenge_result::Entity,
+                challenges_coding_challenge_result::Column::SubmissionId,
+            ))
+            .equals((
+                challenges_coding_challenge_submissions::Entity,
+                challenges_coding_challenge_submissions::Column::Id,
+            )),
+        )
+        .and_where(challenges_coding_challenge_submissions::Column::SubtaskId.eq(subtask_id))
+        .and_where(challenges_coding_challenge_result::Column::Verdict.eq(ChallengesVerdict::Ok))
+        .and_where(score.clone().is_not_null())
+        .order_by(
+            challenges_coding_challenge_submissions::Column::Creator,
+            Order::Asc,
+        )
+        .order_by_expr(score.into(), Order::Asc)
+        .order_by(
+            challenges_coding_challenge_submissions::Column::CreationTimestamp,
+            Order::Asc,
+        )
+        .to_owned();
+    if let Some(environment) = environment {
+        query.and_where(
+            challenges_coding_challenge_submissions::Column::Environment.eq(environment),
+        );
+    }
+    query
+}
+
+pub async fn get_coding_challenge_leaderboard(
+    db: &DatabaseTransaction,
+    services: &Services,
+    subtask_id: Uuid,
+    metric: LeaderboardMetric,
+    environment: Option<&str>,
+    limit: u64,
+    offset: u64,
+) -> anyhow::Result<CodingChallengeLeaderboard> {
+    let base_query = get_base_query(subtask_id, metric, environment);
+    let rows: Vec<(Uuid, Uuid, String, i64)> = db
+        .query_all(
+            db.get_database_backend().build(
+                Query::select()
+                    .columns([
+                        Alias::new("user_id"),
+                        Alias::new("submission_id"),
+                        Alias::new("environment"),
+                        Alias::new("score"),
+                    ])
+                    .from_subquery(base_query.clone(), Alias::new("x"))
+                    .order_by(Alias::new("score"), Order::Asc)
+                    .order_by(Alias::new("timestamp"), Order::Asc)
+                    .limit(limit)
+                    .offset(offset),
+            ),
+        )
+        .await?
+        .into_iter()
+        .map(|row| row.try_get_many_by_index())
+        .collect::<Result<_, _>>()?;
+
+    let total = count(db, base_query.clone(), None).await?;
+
+    let mut rank_score = rows.first().map(|&(_, _, _, score)| score).unwrap_or(0);
+    let mut rank = count(db, base_query, Some(rank_score)).await? + 1;
+
+    let leaderboard =
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, (user_id, submission_id, environment, score))| {
+                if score > rank_score {
+                    rank = offset + i as u64 + 1;
+                    rank_score = score;
+                }
+                let rank = Rank {
+                    score: score as _,
+                    rank,
+                };
+                async move {
+                    anyhow::Ok(CodingChallengeLeaderboardUser {
+                        user: resolve_user(services, user_id, rank).await?,
+                        submission_id,
+                        environment,
+                    })
+                }
+            });
+
+    Ok(CodingChallengeLeaderboard {
+        leaderboard: try_join_all(leaderboard).await?,
+        total,
+    })
+}
+
+/// Count the users on the leaderboard, optionally only those with a score
+/// lower than `below`.
+async fn count(
+    db: &DatabaseTransaction,
+    base_query: SelectStatement,
+    below: Option<i64>,
+) -> anyhow::Result<u64> {
+    let mut query = Query::select()
+        .expr(Expr::col(Alias::new("user_id")).count())
+        .from_subquery(base_query, Alias::new("x"))
+        .to_owned();
+    if let Some(below) = below {
+        query.and_where(Expr::col(Alias::new("score")).lt(below));
+    }
+    Ok(db
+        .query_one(db.get_database_backend().build(&query))
+        .await?
+        .map(|row| row.try_get_many_by_index::<(i64,)>())
+        .transpose()?
+        .map(|(total,)| total as u64)
+        .unwrap_or(0))
+}
@@ -7,6 +7,7 @@ use sea_orm::{
 };
 use uuid::Uuid;
 
+pub mod coding_challenge;
 pub mod global;
 pub mod language;
 pub mod task;
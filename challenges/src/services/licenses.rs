@@ -0,0 +1,89 @@
+use entity::{challenges_subtasks, sea_orm_active_enums::ChallengesLicense};
+
+/// Licenses that allow reusing and adapting content for any purpose without
+/// imposing a license on the derived content.
+pub const PERMISSIVE_LICENSES: &[ChallengesLicense] = &[
+    ChallengesLicense::Cc0,
+    ChallengesLicense::CcBy,
+    ChallengesLicense::Mit,
+];
+
+/// Whether copies of content under this license must credit the original
+/// authors.
+pub fn requires_attribution(license: ChallengesLicense) -> bool {
+    !matches!(
+        license,
+        ChallengesLicense::AllRightsReserved | ChallengesLicense::Cc0
+    )
+}
+
+/// Return the attribution of a copy of a subtask. If the subtask does not
+/// have an attribution yet, its creator is credited.
+pub fn attribution_of_copy(source: &challenges_subtasks::Model) -> String {
+    source
+        .attribution
+        .clone()
+        .unwrap_or_else(|| format!("Subtask {} by user {}", source.id, source.creator))
+}
+
+/// Return the human readable name of a license.
+pub fn license_name(license: ChallengesLicense) -> &'static str {
+    match license {
+        ChallengesLicense::AllRightsReserved => "All rights reserved",
+        ChallengesLicense::Cc0 => "CC0 1.0",
+        ChallengesLicense::CcBy => "CC BY 4.0",
+        ChallengesLicense::CcByNc => "CC BY-NC 4.0",
+        ChallengesLicense::CcByNcSa => "CC BY-NC-SA 4.0",
+        ChallengesLicense::CcBySa => "CC BY-SA 4.0",
+        ChallengesLicense::Mit => "MIT",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::sea_orm_active_enums::{ChallengesReviewStatus, ChallengesSubtaskType};
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_attribution_of_copy() {
+        let id = Uuid::from_u128(1);
+        let creator = Uuid::from_u128(2);
+        let mut subtask = challenges_subtasks::Model {
+            id,
+            task_id: Uuid::nil(),
+            creator,
+            creation_timestamp: Default::default(),
+            xp: 0,
+            coins: 0,
+            enabled: true,
+            ty: ChallengesSubtaskType::Question,
+            retired: false,
+            fingerprint: None,
+            review_status: ChallengesReviewStatus::Approved,
+            rejection_reason: None,
+            license: ChallengesLicense::CcBy,
+            attribution: None,
+            max_attempts: None,
+            reveal_solution: false,
+            time_limit: None,
+            unlock_price: None,
+            locked: false,
+        };
+        assert_eq!(
+            attribution_of_copy(&subtask),
+            format!("Subtask {id} by user {creator}")
+        );
+        subtask.attribution = Some("Jane Doe, example.com".into());
+        assert_eq!(attribution_of_copy(&subtask), "Jane Doe, example.com");
+    }
+
+    #[test]
+    fn test_requires_attribution() {
+        assert!(!requires_attribution(ChallengesLicense::AllRightsReserved));
+        assert!(!requires_attribution(ChallengesLicense::Cc0));
+        assert!(requires_attribution(ChallengesLicense::CcBy));
+        assert!(requires_attribution(ChallengesLicense::Mit));
+    }
+}
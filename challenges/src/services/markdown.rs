@@ -0,0 +1,560 @@
+//! Printable Markdown exports of the content of a task.
+//!
+//! Exports only contain what normal users can see: multiple choice answers are
+//! listed without marking the correct ones, and solutions of questions,
+//! matchings, true/false statements, orderings, clozes, code reading questions,
+//! sql challenges and coding challenges are omitted. Each subtask is followed
+//! by its license and attribution.
+
+use std::{fmt::Write, io};
+
+use entity::{
+    challenges_clozes, challenges_code_readings, challenges_coding_challenges,
+    challenges_matchings, challenges_multiple_choice_quizes, challenges_orderings,
+    challenges_questions, challenges_sql_challenges, challenges_subtasks,
+    challenges_true_false_statements,
+    sea_orm_active_enums::{ChallengesLicense, ChallengesSubtaskType},
+};
+use fnct::format::JsonFormatter;
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use lib::Cache;
+use poem::Body;
+use schemas::challenges::{cloze::BLANK, coding_challenges::Example};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use tracing::error;
+
+use super::{
+    build_configs::get_build_flags,
+    judge::{checker_program, Judge, Program},
+    licenses::license_name,
+    sandbox::{Sandbox, SharedSandbox},
+};
+
+/// Stream the given subtasks as a Markdown document.
+///
+/// The subtasks are loaded and rendered one by one in a background task.
+/// Examples of coding challenges are generated by the evaluator (and usually
+/// cached already). If this fails, the examples are omitted.
+pub fn export_markdown(
+    db: DatabaseConnection,
+    sandkasten: SharedSandbox,
+    judge_cache: Cache<JsonFormatter>,
+    title: String,
+    description: Option<String>,
+    subtasks: Vec<challenges_subtasks::Model>,
+) -> Body {
+    let (mut tx, rx) = mpsc::channel::<io::Result<String>>(1);
+    tokio::spawn(async move {
+        if tx
+            .send(Ok(render_header(&title, description.as_deref())))
+            .await
+            .is_err()
+        {
+            return;
+        }
+        let mut n = 0;
+        for subtask in &subtasks {
+            let section =
+                match render_subtask(&db, &*sandkasten, &judge_cache, n + 1, subtask).await {
+                    Ok(Some(section)) => section + &render_license(subtask),
+                    Ok(None) => continue,
+                    Err(err) => {
+                        error!("failed to export subtask {}: {err}", subtask.id);
+                        tx.send(Err(io::Error::other(err))).await.ok();
+                        return;
+                    }
+                };
+            n += 1;
+            if tx.send(Ok(section)).await.is_err() {
+                return;
+            }
+        }
+    });
+    Body::from_bytes_stream(rx.map(|x| x.map(String::into_bytes)))
+}
+
+/// Load the content of a subtask and render it. Returns `None` if the
+/// content does not exist.
+async fn render_subtask(
+    db: &DatabaseConnection,
+    sandkasten: &dyn Sandbox,
+    judge_cache: &Cache<JsonFormatter>,
+    n: usize,
+    subtask: &challenges_subtasks::Model,
+) -> Result<Option<String>, DbErr> {
+    Ok(match subtask.ty {
+        ChallengesSubtaskType::Question => challenges_questions::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| render_question(n, &x)),
+        ChallengesSubtaskType::MultipleChoiceQuestion => {
+            challenges_multiple_choice_quizes::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| render_mcq(n, &x))
+        }
+        ChallengesSubtaskType::Matching => challenges_matchings::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| render_matching(n, &x)),
+        ChallengesSubtaskType::TrueFalse => {
+            challenges_true_false_statements::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| render_true_false(n, &x))
+        }
+        ChallengesSubtaskType::Ordering => challenges_orderings::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| render_ordering(n, &x)),
+        ChallengesSubtaskType::Cloze => challenges_clozes::Entity::find_by_id(subtask.id)
+            .one(db)
+            .await?
+            .map(|x| render_cloze(n, &x)),
+        ChallengesSubtaskType::CodeReading => {
+            challenges_code_readings::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| render_code_reading(n, &x))
+        }
+        ChallengesSubtaskType::SqlChallenge => {
+            challenges_sql_challenges::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+                .map(|x| render_sql_challenge(n, &x))
+        }
+        ChallengesSubtaskType::CodingChallenge => {
+            let Some(cc) = challenges_coding_challenges::Entity::find_by_id(subtask.id)
+                .one(db)
+                .await?
+            else {
+                return Ok(None);
+            };
+            let build_flags = get_build_flags(db, cc.subtask_id).await?;
+            let judge = Judge {
+                sandkasten,
+                evaluator: &cc.evaluator,
+                protocol_version: cc.protocol_version,
+                comparison: cc.comparison.into(),
+                epsilon: cc.epsilon,
+                reference: Program {
+                    environment: &cc.solution_environment,
+                    code: &cc.solution_code,
+                },
+                checker: checker_program(&cc),
+                build_flags: &build_flags,
+                cache: judge_cache,
+            };
+            let examples = load_examples(&judge, &cc).await;
+            Some(render_coding_challenge(n, &cc, examples.as_deref()))
+        }
+    })
+}
+
+async fn load_examples(
+    judge: &Judge<'_>,
+    cc: &challenges_coding_challenges::Model,
+) -> Option<Vec<Example>> {
+    let seeds = judge
+        .examples()
+        .await
+        .map_err(|err| error!("failed to list examples of {}: {err:?}", cc.subtask_id))
+        .ok()?;
+    let mut examples = Vec::with_capacity(seeds.len());
+    for seed in &seeds {
+        let example = judge
+            .get_example_checked(
+                seed,
+                &cc.solution_environment,
+                &cc.solution_code,
+                &[],
+                Some(cc.time_limit as _),
+                Some(cc.memory_limit as _),
+            )
+            .await;
+        match example {
+            Ok(Ok(checked)) => examples.push(checked.example),
+            err => {
+                error!(
+                    "example generation for {} failed on example {}: {:?}",
+                    cc.subtask_id, seed, err
+                );
+                return None;
+            }
+        }
+    }
+    Some(examples)
+}
+
+fn render_header(title: &str, description: Option<&str>) -> String {
+    let mut out = format!("# {}\n\n", single_line(title));
+    if let Some(description) = description.filter(|x| !x.trim().is_empty()) {
+        let _ = writeln!(out, "{}\n", description.trim());
+    }
+    out
+}
+
+fn render_license(subtask: &challenges_subtasks::Model) -> String {
+    match (subtask.license, &subtask.attribution) {
+        (ChallengesLicense::AllRightsReserved, None) => String::new(),
+        (license, None) => format!("_License: {}_\n\n", license_name(license)),
+        (license, Some(attribution)) => format!(
+            "_License: {}. Attribution: {}_\n\n",
+            license_name(license),
+            single_line(attribution)
+        ),
+    }
+}
+
+fn render_question(n: usize, question: &challenges_questions::Model) -> String {
+    format!(
+        "## {n}. Question\n\n{}\n\n**Answer:** ____________________\n\n",
+        question.question.trim()
+    )
+}
+
+fn render_mcq(n: usize, mcq: &challenges_multiple_choice_quizes::Model) -> String {
+    let mut out = format!(
+        "## {n}. Multiple choice question\n\n{}\n\n",
+        mcq.question.trim()
+    );
+    out.push_str(if mcq.single_choice {
+        "_Select exactly one answer._\n\n"
+    } else {
+        "_Select all correct answers._\n\n"
+    });
+    for answer in &mcq.answers {
+        let _ = writeln!(out, "- [ ] {}", single_line(answer));
+    }
+    out.push('\n');
+    out
+}
+
+fn render_matching(n: usize, matching: &challenges_matchings::Model) -> String {
+    let mut out = format!(
+        "## {n}. Matching\n\nMatch each entry on the left with an entry on the right.\n\n| | \
+         Left | | Right |\n|---|---|---|---|\n"
+    );
+    for i in 0..matching.left.len().max(matching.right.len()) {
+        let cell = |x: Option<&String>| x.map(|x| table_cell(x)).unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            i + 1,
+            cell(matching.left.get(i)),
+            letter(i),
+            cell(matching.right.get(i)),
+        );
+    }
+    out.push('\n');
+    out
+}
+
+fn render_true_false(n: usize, statement: &challenges_true_false_statements::Model) -> String {
+    format!(
+        "## {n}. True or false?\n\n{}\n\n- [ ] True\n- [ ] False\n\n",
+        statement.statement.trim()
+    )
+}
+
+fn render_ordering(n: usize, ordering: &challenges_orderings::Model) -> String {
+    let mut out = format!(
+        "## {n}. Ordering\n\n{}\n\n_Put the items in the correct order._\n\n",
+        ordering.question.trim()
+    );
+    for item in &ordering.items {
+        let _ = writeln!(out, "- ___ {}", single_line(item));
+    }
+    out.push('\n');
+    out
+}
+
+fn render_cloze(n: usize, cloze: &challenges_clozes::Model) -> String {
+    let mut parts = cloze.text.trim().split(BLANK);
+    let mut out = format!("## {n}. Fill in the blanks\n\n");
+    out.push_str(parts.next().unwrap_or_default());
+    for (i, part) in parts.enumerate() {
+        let _ = write!(out, "**({})** ________{part}", i + 1);
+    }
+    out.push_str("\n\n");
+    out
+}
+
+fn render_code_reading(n: usize, code_reading: &challenges_code_readings::Model) -> String {
+    format!(
+        "## {n}. What does this program print?\n\n_Environment: {}_\n\n{}\n**Output:**\n\n\
+         ____________________\n\n",
+        single_line(&code_reading.environment),
+        code_block(&code_reading.code)
+    )
+}
+
+fn render_sql_challenge(n: usize, sql_challenge: &challenges_sql_challenges::Model) -> String {
+    let mut out = format!(
+        "## {n}. SQL challenge\n\n{}\n\n**Schema:**\n\n{}\n",
+        sql_challenge.description.trim(),
+        code_block(&sql_challenge.schema)
+    );
+    if !sql_challenge.seed.trim().is_empty() {
+        let _ = write!(out, "**Data:**\n\n{}\n", code_block(&sql_challenge.seed));
+    }
+    out.push_str("**Query:**\n\n____________________\n\n");
+    out
+}
+
+fn render_coding_challenge(
+    n: usize,
+    cc: &challenges_coding_challenges::Model,
+    examples: Option<&[Example]>,
+) -> String {
+    let mut out = format!(
+        "## {n}. Coding challenge\n\n{}\n\n**Time limit:** {} ms, **memory limit:** {} MB\n\n",
+        cc.description.trim(),
+        cc.time_limit,
+        cc.memory_limit
+    );
+    let Some(examples) = examples else {
+        out.push_str("_The examples could not be generated._\n\n");
+        return out;
+    };
+    for (i, example) in examples.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "### Example {}\n\nInput:\n\n{}\nOutput:\n\n{}",
+            i + 1,
+            code_block(&example.input),
+            code_block(&example.output)
+        );
+        if let Some(explanation) = example
+            .explanation
+            .as_deref()
+            .filter(|x| !x.trim().is_empty())
+        {
+            let _ = writeln!(out, "{}\n", explanation.trim());
+        }
+    }
+    out
+}
+
+/// Wrap a text in a fenced code block that is longer than any sequence of
+/// backticks in the text.
+fn code_block(text: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        current = if c == '`' { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    format!("{fence}\n{text}\n{fence}\n")
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn table_cell(text: &str) -> String {
+    single_line(text).replace('|', "\\|")
+}
+
+/// Return the label of the `i`-th entry on the right side of a matching
+/// (`A`, `B`, ..., `Z`, `AA`, `AB`, ...).
+fn letter(mut i: usize) -> String {
+    let mut out = Vec::new();
+    loop {
+        out.push(b'A' + (i % 26) as u8);
+        if i < 26 {
+            break;
+        }
+        i = i / 26 - 1;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{
+        challenges_sql_challenges::SqlResult, sea_orm_active_enums::ChallengesReviewStatus,
+    };
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_render_mcq() {
+        let mcq = challenges_multiple_choice_quizes::Model {
+            subtask_id: Uuid::nil(),
+            question: "Which numbers\nare even?\n".into(),
+            answers: vec!["2".into(), "3\n4".into()],
+            correct_answers: 0b01,
+            single_choice: false,
+            shuffle_answers: false,
+        };
+        assert_eq!(
+            render_mcq(3, &mcq),
+            "## 3. Multiple choice question\n\nWhich numbers\nare even?\n\n_Select all correct \
+             answers._\n\n- [ ] 2\n- [ ] 3 4\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_matching() {
+        let matching = challenges_matchings::Model {
+            subtask_id: Uuid::nil(),
+            left: vec!["a|b".into(), "c".into()],
+            right: vec!["x".into(), "y".into()],
+            solution: vec![1, 0],
+        };
+        assert_eq!(
+            render_matching(1, &matching),
+            "## 1. Matching\n\nMatch each entry on the left with an entry on the right.\n\n| | \
+             Left | | Right |\n|---|---|---|---|\n| 1 | a\\|b | A | x |\n| 2 | c | B | y |\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_true_false() {
+        let statement = challenges_true_false_statements::Model {
+            subtask_id: Uuid::nil(),
+            statement: " Rust has a garbage collector. ".into(),
+            answer: false,
+        };
+        assert_eq!(
+            render_true_false(2, &statement),
+            "## 2. True or false?\n\nRust has a garbage collector.\n\n- [ ] True\n- [ ] \
+             False\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_ordering() {
+        let ordering = challenges_orderings::Model {
+            subtask_id: Uuid::nil(),
+            question: "Sort the steps.".into(),
+            items: vec!["cargo\nbuild".into(), "cargo new".into()],
+            solution: vec![1, 0],
+        };
+        assert_eq!(
+            render_ordering(4, &ordering),
+            "## 4. Ordering\n\nSort the steps.\n\n_Put the items in the correct order._\n\n- \
+             ___ cargo build\n- ___ cargo new\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_cloze() {
+        let cloze = challenges_clozes::Model {
+            subtask_id: Uuid::nil(),
+            text: "{{}} main() {\n    println!(\"{{}}\");\n}\n".into(),
+            blanks: challenges_clozes::ClozeBlanks(Vec::new()),
+        };
+        assert_eq!(
+            render_cloze(1, &cloze),
+            "## 1. Fill in the blanks\n\n**(1)** ________ main() {\n    println!(\"**(2)** \
+             ________\");\n}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_code_reading() {
+        let code_reading = challenges_code_readings::Model {
+            subtask_id: Uuid::nil(),
+            environment: "python".into(),
+            code: "print(6 * 7)\n".into(),
+            output: "42\n".into(),
+        };
+        assert_eq!(
+            render_code_reading(5, &code_reading),
+            "## 5. What does this program print?\n\n_Environment: python_\n\n```\nprint(6 * \
+             7)\n```\n\n**Output:**\n\n____________________\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_sql_challenge() {
+        let mut sql_challenge = challenges_sql_challenges::Model {
+            subtask_id: Uuid::nil(),
+            description: "List all users.".into(),
+            schema: "CREATE TABLE user (name TEXT);".into(),
+            seed: "INSERT INTO user VALUES ('alice');".into(),
+            solution: "SELECT name FROM user;".into(),
+            ordered: false,
+            expected: SqlResult {
+                columns: vec!["name".into()],
+                rows: vec![vec![Some("alice".into())]],
+            },
+        };
+        assert_eq!(
+            render_sql_challenge(2, &sql_challenge),
+            "## 2. SQL challenge\n\nList all users.\n\n**Schema:**\n\n```\nCREATE TABLE user \
+             (name TEXT);\n```\n\n**Data:**\n\n```\nINSERT INTO user VALUES \
+             ('alice');\n```\n\n**Query:**\n\n____________________\n\n"
+        );
+
+        sql_challenge.seed = String::new();
+        assert_eq!(
+            render_sql_challenge(2, &sql_challenge),
+            "## 2. SQL challenge\n\nList all users.\n\n**Schema:**\n\n```\nCREATE TABLE user \
+             (name TEXT);\n```\n\n**Query:**\n\n____________________\n\n"
+        );
+    }
+
+    #[test]
+    fn test_code_block() {
+        assert_eq!(code_block("1 2\n"), "```\n1 2\n```\n");
+        assert_eq!(code_block("a ```` b"), "`````\na ```` b\n`````\n");
+    }
+
+    #[test]
+    fn test_letter() {
+        assert_eq!(letter(0), "A");
+        assert_eq!(letter(25), "Z");
+        assert_eq!(letter(26), "AA");
+        assert_eq!(letter(27), "AB");
+        assert_eq!(letter(26 * 27), "AAA");
+    }
+
+    #[test]
+    fn test_render_license() {
+        let mut subtask = challenges_subtasks::Model {
+            id: Uuid::nil(),
+            task_id: Uuid::nil(),
+            creator: Uuid::nil(),
+            creation_timestamp: Default::default(),
+            xp: 0,
+            coins: 0,
+            enabled: true,
+            ty: ChallengesSubtaskType::Question,
+            retired: false,
+            fingerprint: None,
+            review_status: ChallengesReviewStatus::Approved,
+            rejection_reason: None,
+            license: ChallengesLicense::AllRightsReserved,
+            attribution: None,
+            max_attempts: None,
+            reveal_solution: false,
+            time_limit: None,
+            unlock_price: None,
+            locked: false,
+        };
+        assert_eq!(render_license(&subtask), "");
+        subtask.license = ChallengesLicense::CcBySa;
+        assert_eq!(render_license(&subtask), "_License: CC BY-SA 4.0_\n\n");
+        subtask.attribution = Some("Jane Doe,\nexample.com".into());
+        assert_eq!(
+            render_license(&subtask),
+            "_License: CC BY-SA 4.0. Attribution: Jane Doe, example.com_\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_header() {
+        assert_eq!(render_header("Rust\nBasics", None), "# Rust Basics\n\n");
+        assert_eq!(
+            render_header("Rust", Some(" Learn Rust. \n")),
+            "# Rust\n\nLearn Rust.\n\n"
+        );
+    }
+}
@@ -1,5 +1,54 @@
+pub mod access;
+pub mod activity;
+pub mod anti_cheat;
+pub mod build_configs;
+pub mod cohorts;
+pub mod comparison;
+pub mod content_filter;
+pub mod contests;
 pub mod course_tasks;
+pub mod digest;
+pub mod evaluators;
+pub mod example_limiter;
+pub mod examples;
+pub mod exams;
+pub mod exports;
+pub mod expressions;
+pub mod feed;
+pub mod held_rewards;
 pub mod judge;
+pub mod judge_limiter;
+pub mod judge_queue;
+pub mod judge_worker;
 pub mod leaderboard;
+pub mod licenses;
+pub mod markdown;
+pub mod notifications;
+pub mod numeric;
+pub mod organizations;
+pub mod paths;
+pub mod personal_bests;
+pub mod pools;
+pub mod prerequisites;
+pub mod question_import;
+pub mod question_variants;
+pub mod quotas;
+pub mod recommendation;
+pub mod reviews;
+pub mod revisions;
+pub mod rewards;
+pub mod sandbox;
+pub mod sandbox_usage;
+pub mod seed;
+pub mod shuffle;
+pub mod similarity;
+pub mod solve_tokens;
+pub mod sql;
 pub mod subtasks;
+pub mod task_completions;
+pub mod task_config;
 pub mod tasks;
+pub mod time_tracking;
+pub mod unlocks;
+pub mod user_data;
+pub mod wrong_answers;
@@ -0,0 +1,239 @@
+//! Notifications for users.
+//!
+//! Notifications are published to the event bus as
+//! [`Event::UserNotification`] and delivered by the notification microservice.
+//! Users can disable each kind of notification in their notification
+//! preferences.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use entity::{challenges_contests, challenges_notification_preferences, challenges_subtasks};
+use lib::{
+    config::SharedConfig,
+    events::{publish_event, Event, Notification},
+};
+use sea_orm::{
+    sea_query::{LockBehavior, LockType},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait, Unchanged,
+};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+/// Return the notification preferences of a user. Users who have never
+/// changed their preferences get the defaults.
+pub async fn get_notification_preferences(
+    db: &impl ConnectionTrait,
+    user_id: Uuid,
+) -> Result<challenges_notification_preferences::Model, DbErr> {
+    Ok(
+        challenges_notification_preferences::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .unwrap_or_else(|| default_preferences(user_id)),
+    )
+}
+
+pub fn default_preferences(user_id: Uuid) -> challenges_notification_preferences::Model {
+    challenges_notification_preferences::Model {
+        user_id,
+        subtask_solved: true,
+        subtask_reported: true,
+        subtask_disabled: true,
+        submission_judged: true,
+        contest_starting: false,
+        creator_digest: true,
+    }
+}
+
+/// Return whether the user wants to receive the given notification.
+pub fn is_enabled(
+    preferences: &challenges_notification_preferences::Model,
+    notification: &Notification,
+) -> bool {
+    match notification {
+        Notification::SubtaskSolved { .. } => preferences.subtask_solved,
+        Notification::SubtaskReported { .. } => preferences.subtask_reported,
+        Notification::SubtaskDisabled { .. } | Notification::SubtaskRejected { .. } => {
+            preferences.subtask_disabled
+        }
+        Notification::SubmissionJudged { .. } => preferences.submission_judged,
+        Notification::ContestStarting { .. } => preferences.contest_starting,
+    }
+}
+
+/// Send a notification to a user unless they have disabled this kind of
+/// notification. Like [`publish_event`], this should be called in the same
+/// transaction as the change the notification describes.
+pub async fn notify(
+    db: &impl ConnectionTrait,
+    user_id: Uuid,
+    notification: Notification,
+) -> Result<(), DbErr> {
+    if !is_enabled(
+        &get_notification_preferences(db, user_id).await?,
+        &notification,
+    ) {
+        return Ok(());
+    }
+    publish_event(
+        db,
+        Event::UserNotification {
+            user_id,
+            notification,
+        },
+    )
+    .await
+}
+
+/// Notify the creator of a subtask that someone else has solved it.
+pub async fn notify_subtask_solved(
+    db: &impl ConnectionTrait,
+    subtask: &challenges_subtasks::Model,
+    solver: Uuid,
+) -> Result<(), DbErr> {
+    if solver == subtask.creator {
+        return Ok(());
+    }
+    notify(
+        db,
+        subtask.creator,
+        Notification::SubtaskSolved {
+            task_id: subtask.task_id,
+            subtask_id: subtask.id,
+            solver,
+        },
+    )
+    .await
+}
+
+/// Notify the creator of a subtask that it has been disabled by someone else.
+/// `disabled_by` is `None` if the subtask has been disabled by another
+/// microservice.
+pub async fn notify_subtask_disabled(
+    db: &impl ConnectionTrait,
+    subtask: &challenges_subtasks::Model,
+    disabled_by: Option<Uuid>,
+) -> Result<(), DbErr> {
+    if disabled_by == Some(subtask.creator) {
+        return Ok(());
+    }
+    notify(
+        db,
+        subtask.creator,
+        Notification::SubtaskDisabled {
+            task_id: subtask.task_id,
+            subtask_id: subtask.id,
+        },
+    )
+    .await
+}
+
+/// Notifies all users who have enabled contest notifications shortly before a
+/// contest starts.
+pub struct ContestReminder {
+    db: DatabaseConnection,
+    config: SharedConfig,
+}
+
+impl ContestReminder {
+    pub fn new(db: DatabaseConnection, config: SharedConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Send reminders until the process exits.
+    pub async fn run(self) {
+        loop {
+            match self.send_reminders().await {
+                Ok(0) => {}
+                Ok(n) => debug!("sent reminders for {n} contest(s)"),
+                Err(err) => error!("failed to send contest reminders: {err}"),
+            }
+            let poll_interval = self.config.load().challenges.notifications.poll_interval;
+            tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+        }
+    }
+
+    /// Send reminders for all contests which start soon and mark them as
+    /// reminded. Contests are locked while the reminders are sent, so multiple
+    /// instances never remind users twice.
+    async fn send_reminders(&self) -> Result<usize, DbErr> {
+        let now = Utc::now();
+        let until = now
+            + chrono::Duration::minutes(
+                self.config.load().challenges.notifications.contest_reminder as _,
+            );
+
+        let txn = self.db.begin().await?;
+        let contests = challenges_contests::Entity::find()
+            .filter(challenges_contests::Column::ReminderSent.eq(false))
+            .filter(challenges_contests::Column::Start.gt(now.naive_utc()))
+            .filter(challenges_contests::Column::Start.lte(until.naive_utc()))
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(&txn)
+            .await?;
+        if contests.is_empty() {
+            return Ok(0);
+        }
+
+        let users = challenges_notification_preferences::Entity::find()
+            .filter(challenges_notification_preferences::Column::ContestStarting.eq(true))
+            .all(&txn)
+            .await?;
+        for contest in &contests {
+            for user in &users {
+                publish_event(
+                    &txn,
+                    Event::UserNotification {
+                        user_id: user.user_id,
+                        notification: Notification::ContestStarting {
+                            contest_id: contest.id,
+                            title: contest.title.clone(),
+                            start: contest.start.and_utc(),
+                        },
+                    },
+                )
+                .await?;
+            }
+            challenges_contests::ActiveModel {
+                id: Unchanged(contest.id),
+                reminder_sent: Set(true),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(contests.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_preferences_enabled() {
+        let preferences = default_preferences(Uuid::nil());
+        let solved = Notification::SubtaskSolved {
+            task_id: Uuid::nil(),
+            subtask_id: Uuid::nil(),
+            solver: Uuid::nil(),
+        };
+        let contest = Notification::ContestStarting {
+            contest_id: Uuid::nil(),
+            title: "Contest".into(),
+            start: Utc::now(),
+        };
+        assert!(is_enabled(&preferences, &solved));
+        assert!(!is_enabled(&preferences, &contest));
+        assert!(is_enabled(
+            &challenges_notification_preferences::Model {
+                contest_starting: true,
+                ..preferences
+            },
+            &contest
+        ));
+    }
+}
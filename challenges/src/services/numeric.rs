@@ -0,0 +1,132 @@
+//! Parsing and comparison of numeric answers.
+//!
+//! Numbers may use a point or a comma as decimal separator and may be written
+//! in scientific notation (e.g. `1,5e-3`). An optional unit may follow the
+//! number.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static NUMBER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([+\-−]?)(\d+(?:[.,]\d*)?|[.,]\d+)(?:\s*[eE]([+\-−]?\d+))?\s*(.*)$").unwrap()
+});
+
+/// Parse a number with an optional unit.
+pub fn parse_number(input: &str) -> Option<(f64, Option<&str>)> {
+    let captures = NUMBER.captures(input.trim())?;
+    let mut number = String::new();
+    if matches!(&captures[1], "-" | "−") {
+        number.push('-');
+    }
+    number.push_str(&captures[2].replace(',', "."));
+    if let Some(exp) = captures.get(3) {
+        number.push('e');
+        number.push_str(&exp.as_str().replace('−', "-"));
+    }
+    let value = number.parse::<f64>().ok().filter(|x| x.is_finite())?;
+    let unit = captures
+        .get(4)
+        .map(|x| x.as_str())
+        .filter(|x| !x.is_empty());
+    Some((value, unit))
+}
+
+/// The expected answer of a numeric question.
+pub struct NumericAnswer<'a> {
+    pub unit: Option<&'a str>,
+    pub absolute_tolerance: Option<f64>,
+    pub relative_tolerance: Option<f64>,
+}
+
+impl NumericAnswer<'_> {
+    /// Check whether the answer matches one of the expected values within the
+    /// tolerance. If the answer contains a unit, it has to match the unit of
+    /// the question (ignoring whitespace).
+    pub fn check(&self, answer: &str, expected: &[String]) -> bool {
+        let Some((value, unit)) = parse_number(answer) else {
+            return false;
+        };
+        if let Some(unit) = unit {
+            if self.unit.map(normalize_unit) != Some(normalize_unit(unit)) {
+                return false;
+            }
+        }
+        expected
+            .iter()
+            .filter_map(|x| parse_number(x))
+            .any(|(expected, _)| self.matches(value, expected))
+    }
+
+    fn matches(&self, value: f64, expected: f64) -> bool {
+        let diff = (value - expected).abs();
+        match (self.absolute_tolerance, self.relative_tolerance) {
+            (None, None) => diff <= f64::EPSILON * expected.abs(),
+            (abs, rel) => {
+                abs.is_some_and(|tol| diff <= tol)
+                    || rel.is_some_and(|tol| diff <= tol * expected.abs())
+            }
+        }
+    }
+}
+
+fn normalize_unit(unit: &str) -> String {
+    unit.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("42"), Some((42.0, None)));
+        assert_eq!(parse_number(" -1.5 "), Some((-1.5, None)));
+        assert_eq!(parse_number("1,5"), Some((1.5, None)));
+        assert_eq!(parse_number(",5"), Some((0.5, None)));
+        assert_eq!(parse_number("+3."), Some((3.0, None)));
+        assert_eq!(parse_number("1.5e3"), Some((1500.0, None)));
+        assert_eq!(parse_number("2,5E-2 m/s"), Some((0.025, Some("m/s"))));
+        assert_eq!(parse_number("−4 kg"), Some((-4.0, Some("kg"))));
+        assert_eq!(parse_number("9.81m/s^2"), Some((9.81, Some("m/s^2"))));
+        assert_eq!(parse_number("1e400"), None);
+        assert_eq!(parse_number("abc"), None);
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("."), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = ["9.81".to_owned()];
+        let exact = NumericAnswer {
+            unit: Some("m/s^2"),
+            absolute_tolerance: None,
+            relative_tolerance: None,
+        };
+        assert!(exact.check("9,81", &expected));
+        assert!(exact.check("9.81 m/s^2", &expected));
+        assert!(exact.check("9.81 m / s^2", &expected));
+        assert!(exact.check("981e-2", &expected));
+        assert!(!exact.check("9.81 km/s^2", &expected));
+        assert!(!exact.check("9.8", &expected));
+        assert!(!exact.check("nine", &expected));
+
+        let absolute = NumericAnswer {
+            unit: None,
+            absolute_tolerance: Some(0.05),
+            relative_tolerance: None,
+        };
+        assert!(absolute.check("9.8", &expected));
+        assert!(absolute.check("9.85", &expected));
+        assert!(!absolute.check("9.9", &expected));
+        assert!(!absolute.check("9.8 m/s^2", &expected));
+
+        let relative = NumericAnswer {
+            unit: None,
+            absolute_tolerance: None,
+            relative_tolerance: Some(0.01),
+        };
+        assert!(relative.check("1.009e6", &["1000000".into()]));
+        assert!(!relative.check("1.02e6", &["1000000".into()]));
+        assert!(relative.check("2", &["1000000".into(), "2.01".into()]));
+    }
+}
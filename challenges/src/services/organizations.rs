@@ -0,0 +1,90 @@
+use entity::{
+    challenges_organization_members, challenges_subtasks, challenges_tasks,
+    sea_orm_active_enums::ChallengesOrganizationRole,
+};
+use lib::auth::User;
+use sea_orm::{sea_query::Query, ColumnTrait, Condition, DatabaseTransaction, DbErr, EntityTrait};
+use uuid::Uuid;
+
+/// Return a condition matching the tasks visible to the user, i.e. tasks that
+/// do not belong to any organization and tasks of the organization the user is
+/// acting in. Platform admins can see all tasks.
+pub fn task_scope(user: &User) -> Condition {
+    if user.admin {
+        return Condition::all();
+    }
+    let mut condition = Condition::any().add(challenges_tasks::Column::OrganizationId.is_null());
+    if let Some(organization_id) = user.organization_id() {
+        condition = condition.add(challenges_tasks::Column::OrganizationId.eq(organization_id));
+    }
+    condition
+}
+
+/// Return a condition matching the rows referencing a task (via `task_id`)
+/// that is visible to the user.
+pub fn task_id_scope(task_id: impl ColumnTrait, user: &User) -> Condition {
+    if user.admin {
+        return Condition::all();
+    }
+    Condition::all().add(
+        task_id.in_subquery(
+            Query::select()
+                .column(challenges_tasks::Column::Id)
+                .from(challenges_tasks::Entity)
+                .cond_where(task_scope(user))
+                .to_owned(),
+        ),
+    )
+}
+
+/// Return a condition matching the rows referencing a subtask (via
+/// `subtask_id`) of a task that is visible to the user.
+pub fn subtask_id_scope(subtask_id: impl ColumnTrait, user: &User) -> Condition {
+    if user.admin {
+        return Condition::all();
+    }
+    Condition::all().add(
+        subtask_id.in_subquery(
+            Query::select()
+                .column(challenges_subtasks::Column::Id)
+                .from(challenges_subtasks::Entity)
+                .cond_where(task_id_scope(challenges_subtasks::Column::TaskId, user))
+                .to_owned(),
+        ),
+    )
+}
+
+/// Whether the task is visible to the user.
+pub fn can_see_task(user: &User, task: &challenges_tasks::Model) -> bool {
+    user.admin || task.organization_id.is_none() || task.organization_id == user.organization_id()
+}
+
+/// Whether the user can create content in the organization they are acting
+/// in, i.e. is a creator or admin of the organization.
+pub fn can_create_in_organization(user: &User) -> bool {
+    user.admin
+        || user.organization.is_some_and(|x| {
+            matches!(
+                x.role,
+                Some(ChallengesOrganizationRole::Creator | ChallengesOrganizationRole::Admin)
+            )
+        })
+}
+
+/// Whether the user can manage the members of an organization, i.e. is a
+/// platform admin or an admin of the organization.
+pub async fn can_manage_organization(
+    db: &DatabaseTransaction,
+    user: &User,
+    organization_id: Uuid,
+) -> Result<bool, DbErr> {
+    if user.admin {
+        return Ok(true);
+    }
+    Ok(
+        challenges_organization_members::Entity::find_by_id((organization_id, user.id))
+            .one(db)
+            .await?
+            .is_some_and(|x| x.role == ChallengesOrganizationRole::Admin),
+    )
+}
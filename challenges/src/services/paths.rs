@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
+use entity::{
+    challenges_path_items, challenges_paths, challenges_subtasks, challenges_tasks,
+    challenges_user_paths, challenges_user_subtasks,
+};
+use lib::{auth::User, config::challenges::Rewards, services::Services};
+use schemas::challenges::paths::{PathItem, PathItemProgress, PathProgress};
+use sea_orm::{
+    ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+use uuid::Uuid;
+
+use super::{
+    organizations::can_see_task,
+    pools::get_pool_sizes,
+    rewards::{add_coins, add_xp},
+    subtasks::{get_skills, SendTaskRewardsError},
+    tasks::get_task_with_specific,
+};
+
+pub async fn get_path(
+    db: &DatabaseTransaction,
+    path_id: Uuid,
+) -> Result<Option<(challenges_paths::Model, Vec<challenges_path_items::Model>)>, DbErr> {
+    let Some(path) = challenges_paths::Entity::find_by_id(path_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let items = path
+        .find_related(challenges_path_items::Entity)
+        .order_by_asc(challenges_path_items::Column::Position)
+        .all(db)
+        .await?;
+    Ok(Some((path, items)))
+}
+
+/// Whether the user can see all tasks referenced by the items of a learning
+/// path, i.e. none of them belongs to another organization.
+pub async fn can_see_path(
+    db: &DatabaseTransaction,
+    user: &User,
+    items: &[challenges_path_items::Model],
+) -> Result<bool, DbErr> {
+    if user.admin {
+        return Ok(true);
+    }
+    let mut task_ids = items
+        .iter()
+        .filter_map(|x| x.task_id)
+        .collect::<HashSet<_>>();
+    let subtask_ids = items
+        .iter()
+        .filter_map(|x| x.subtask_id)
+        .collect::<Vec<_>>();
+    if !subtask_ids.is_empty() {
+        task_ids.extend(
+            challenges_subtasks::Entity::find()
+                .filter(challenges_subtasks::Column::Id.is_in(subtask_ids))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|x| x.task_id),
+        );
+    }
+    if task_ids.is_empty() {
+        return Ok(true);
+    }
+    Ok(challenges_tasks::Entity::find()
+        .filter(challenges_tasks::Column::Id.is_in(task_ids))
+        .all(db)
+        .await?
+        .iter()
+        .all(|task| can_see_task(user, task)))
+}
+
+/// Compute the progress of a user in a learning path.
+pub async fn get_path_progress(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    path_id: Uuid,
+    items: &[challenges_path_items::Model],
+) -> Result<PathProgress, DbErr> {
+    let task_ids = items
+        .iter()
+        .filter_map(|x| x.task_id)
+        .collect::<HashSet<_>>();
+    let subtask_ids = items.iter().filter_map(|x| x.subtask_id);
+    let pools = get_pool_sizes(db, Some(task_ids.iter().copied().collect())).await?;
+
+    let mut task_subtasks = HashMap::<Uuid, Vec<Uuid>>::new();
+    for subtask in challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::TaskId.is_in(task_ids))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(false))
+        .filter(challenges_subtasks::Column::Retired.eq(false))
+        .all(db)
+        .await?
+    {
+        task_subtasks
+            .entry(subtask.task_id)
+            .or_default()
+            .push(subtask.id);
+    }
+
+    let relevant = task_subtasks.values().flatten().copied().chain(subtask_ids);
+    let solved = challenges_user_subtasks::Entity::find()
+        .filter(challenges_user_subtasks::Column::UserId.eq(user_id))
+        .filter(challenges_user_subtasks::Column::SubtaskId.is_in(relevant))
+        .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| x.subtask_id)
+        .collect::<HashSet<_>>();
+
+    let claimed = challenges_user_paths::Entity::find_by_id((user_id, path_id))
+        .one(db)
+        .await?
+        .map(|x| x.completed_timestamp.and_utc());
+
+    let items = compute_progress(items, &task_subtasks, &pools, &solved);
+    let completed_items = items.iter().filter(|x| x.completed).count() as u64;
+    Ok(PathProgress {
+        path_id,
+        completed_items,
+        total_items: items.len() as _,
+        completed: completed_items == items.len() as u64,
+        claimed,
+        items,
+    })
+}
+
+/// Compute the progress of each item of a learning path from the subtasks of
+/// the referenced tasks and the set of subtasks the user has solved. For tasks
+/// with a pool (see [`super::pools`]) only `pools[task_id]` subtasks have to be
+/// solved.
+pub fn compute_progress(
+    items: &[challenges_path_items::Model],
+    task_subtasks: &HashMap<Uuid, Vec<Uuid>>,
+    pools: &HashMap<Uuid, u64>,
+    solved: &HashSet<Uuid>,
+) -> Vec<PathItemProgress> {
+    items
+        .iter()
+        .map(|item| {
+            let (required, solved) = match (item.task_id, item.subtask_id) {
+                (Some(task_id), _) => {
+                    let subtasks = task_subtasks.get(&task_id).map_or(&[][..], |x| x);
+                    let total = pools
+                        .get(&task_id)
+                        .map_or(subtasks.len() as u64, |&x| x.min(subtasks.len() as _));
+                    (
+                        item.min_solved.map_or(total, |x| (x as u64).min(total)),
+                        subtasks.iter().filter(|x| solved.contains(x)).count() as u64,
+                    )
+                }
+                (None, Some(subtask_id)) => (1, solved.contains(&subtask_id) as u64),
+                (None, None) => (0, 0),
+            };
+            PathItemProgress {
+                item: PathItem::from(item.clone()),
+                required,
+                solved,
+                completed: solved >= required,
+            }
+        })
+        .collect()
+}
+
+/// Send the completion rewards of a learning path to a user. The xp are
+/// distributed across the skills of all tasks in the learning path.
+pub async fn send_path_rewards(
+    services: &Services,
+    db: &DatabaseTransaction,
+    config: &Rewards,
+    user_id: Uuid,
+    path: &challenges_paths::Model,
+    items: &[challenges_path_items::Model],
+) -> Result<(), SendTaskRewardsError> {
+    if path.xp != 0 {
+        let mut task_ids = items
+            .iter()
+            .filter_map(|x| x.task_id)
+            .collect::<HashSet<_>>();
+        task_ids.extend(
+            challenges_subtasks::Entity::find()
+                .filter(
+                    challenges_subtasks::Column::Id
+                        .is_in(items.iter().filter_map(|x| x.subtask_id)),
+                )
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|x| x.task_id),
+        );
+
+        let mut skills = HashSet::new();
+        for task_id in task_ids {
+            let (_, task) = get_task_with_specific(db, task_id)
+                .await?
+                .ok_or(SendTaskRewardsError::NoParentTask)?;
+            skills.extend(get_skills(services, task).await?);
+        }
+        for skill in &skills {
+            add_xp(
+                services,
+                db,
+                config,
+                user_id,
+                skill,
+                path.xp / skills.len() as i64,
+            )
+            .await?;
+        }
+    }
+    if path.coins != 0 {
+        add_coins(
+            services,
+            db,
+            config,
+            user_id,
+            path.coins,
+            "Challenges / Lernpfade",
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(
+        position: i32,
+        task_id: Option<Uuid>,
+        subtask_id: Option<Uuid>,
+        min_solved: Option<i32>,
+    ) -> challenges_path_items::Model {
+        challenges_path_items::Model {
+            path_id: Uuid::nil(),
+            position,
+            task_id,
+            subtask_id,
+            min_solved,
+        }
+    }
+
+    #[test]
+    fn test_compute_progress() {
+        let [task, a, b, c, d, pooled, e, f, g] = [1, 2, 3, 4, 5, 7, 8, 9, 10].map(Uuid::from_u128);
+        let task_subtasks = HashMap::from([(task, vec![a, b, c]), (pooled, vec![e, f, g])]);
+        let pools = HashMap::from([(pooled, 2)]);
+        let solved = HashSet::from([a, b, e, g]);
+        let progress = compute_progress(
+            &[
+                item(0, Some(task), None, None),
+                item(1, Some(task), None, Some(2)),
+                item(2, Some(task), None, Some(10)),
+                item(3, None, Some(a), None),
+                item(4, None, Some(d), None),
+                item(5, Some(Uuid::from_u128(6)), None, None),
+                item(6, Some(pooled), None, None),
+                item(7, Some(pooled), None, Some(1)),
+            ],
+            &task_subtasks,
+            &pools,
+            &solved,
+        );
+        let progress = progress
+            .iter()
+            .map(|x| (x.required, x.solved, x.completed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            progress,
+            [
+                (3, 2, false),
+                (2, 2, true),
+                (3, 2, false),
+                (1, 1, true),
+                (1, 0, false),
+                (0, 0, true),
+                (2, 2, true),
+                (1, 2, true),
+            ]
+        );
+    }
+}
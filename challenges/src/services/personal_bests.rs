@@ -0,0 +1,60 @@
+use entity::challenges_personal_bests;
+use sea_orm::{ActiveModelTrait, DatabaseTransaction, DbErr, EntityTrait, IntoActiveModel, Set};
+use uuid::Uuid;
+
+/// The personal bests an accepted submission improved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewPersonalBest {
+    pub time: bool,
+    pub memory: bool,
+}
+
+/// Update the personal bests of a user after one of their submissions has
+/// been accepted. `time` and `memory` are the maximum resources the
+/// submission used in any test.
+pub async fn update_personal_best(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+    submission_id: Uuid,
+    time: i64,
+    memory: i64,
+) -> Result<NewPersonalBest, DbErr> {
+    let Some(best) = challenges_personal_bests::Entity::find_by_id((user_id, subtask_id))
+        .one(db)
+        .await?
+    else {
+        challenges_personal_bests::ActiveModel {
+            user_id: Set(user_id),
+            subtask_id: Set(subtask_id),
+            time_submission_id: Set(submission_id),
+            time: Set(time),
+            memory_submission_id: Set(submission_id),
+            memory: Set(memory),
+        }
+        .insert(db)
+        .await?;
+        return Ok(NewPersonalBest {
+            time: true,
+            memory: true,
+        });
+    };
+
+    let new = NewPersonalBest {
+        time: time < best.time,
+        memory: memory < best.memory,
+    };
+    if new.time || new.memory {
+        let mut best = best.into_active_model();
+        if new.time {
+            best.time_submission_id = Set(submission_id);
+            best.time = Set(time);
+        }
+        if new.memory {
+            best.memory_submission_id = Set(submission_id);
+            best.memory = Set(memory);
+        }
+        best.update(db).await?;
+    }
+    Ok(new)
+}
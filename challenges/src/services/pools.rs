@@ -0,0 +1,141 @@
+//! Question pools.
+//!
+//! If a task has a `pool_size` in its config, every user only sees (and has
+//! to solve) this number of its subtasks. The subtasks are ranked by a hash of
+//! the user and subtask ids and the first ones form the pool of the user, so
+//! the selection is stable and adding a subtask to the task only changes the
+//! pools it ends up in.
+
+use std::collections::{HashMap, HashSet};
+
+use entity::{challenges_subtasks, challenges_task_config, challenges_user_subtasks};
+use lib::auth::User;
+use sea_orm::{ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Return the rank of a subtask in the pools of a user. Lower ranks are
+/// selected first.
+pub fn pool_rank(user_id: Uuid, subtask_id: Uuid) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(b"pool");
+    hasher.update(user_id.as_bytes());
+    hasher.update(subtask_id.as_bytes());
+    u64::from_be_bytes(hasher.finalize()[..8].try_into().unwrap())
+}
+
+/// Select the pool of a user from the subtasks of a task.
+pub fn select_pool(user_id: Uuid, subtasks: &[Uuid], size: usize) -> HashSet<Uuid> {
+    let mut subtasks = subtasks.to_vec();
+    subtasks.sort_by_key(|&id| (pool_rank(user_id, id), id));
+    subtasks.into_iter().take(size).collect()
+}
+
+/// Return the pool sizes of the given tasks (or of all tasks if `task_ids` is
+/// `None`). Tasks without a pool are omitted.
+pub async fn get_pool_sizes(
+    db: &DatabaseTransaction,
+    task_ids: Option<Vec<Uuid>>,
+) -> Result<HashMap<Uuid, u64>, DbErr> {
+    let mut query = challenges_task_config::Entity::find()
+        .filter(challenges_task_config::Column::PoolSize.is_not_null());
+    if let Some(task_ids) = task_ids {
+        query = query.filter(challenges_task_config::Column::TaskId.is_in(task_ids));
+    }
+    Ok(query
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|x| Some((x.task_id, x.pool_size? as _)))
+        .collect())
+}
+
+/// Return the subtasks of the given tasks (or of all tasks if `task_ids` is
+/// `None`) that are hidden from a user because they are not part of the
+/// user's pool. Subtasks the user has created or already solved are never
+/// hidden, and admins can always see all subtasks.
+pub async fn get_hidden_subtasks(
+    db: &DatabaseTransaction,
+    user: &User,
+    task_ids: Option<Vec<Uuid>>,
+) -> Result<HashSet<Uuid>, DbErr> {
+    if user.admin {
+        return Ok(HashSet::new());
+    }
+    let pools = get_pool_sizes(db, task_ids).await?;
+    if pools.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let mut task_subtasks = HashMap::<Uuid, Vec<Uuid>>::new();
+    for subtask in challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::TaskId.is_in(pools.keys().copied()))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(false))
+        .filter(challenges_subtasks::Column::Retired.eq(false))
+        .filter(challenges_subtasks::Column::Creator.ne(user.id))
+        .all(db)
+        .await?
+    {
+        task_subtasks
+            .entry(subtask.task_id)
+            .or_default()
+            .push(subtask.id);
+    }
+    let solved = challenges_user_subtasks::Entity::find()
+        .filter(challenges_user_subtasks::Column::UserId.eq(user.id))
+        .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| x.subtask_id)
+        .collect::<HashSet<_>>();
+
+    Ok(task_subtasks
+        .into_iter()
+        .flat_map(|(task_id, subtasks)| {
+            let pool = select_pool(user.id, &subtasks, pools[&task_id] as _);
+            subtasks.into_iter().filter(move |id| !pool.contains(id))
+        })
+        .filter(|id| !solved.contains(id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_pool() {
+        let user = Uuid::from_u128(1);
+        let subtasks = (0..10).map(Uuid::from_u128).collect::<Vec<_>>();
+
+        let pool = select_pool(user, &subtasks, 3);
+        assert_eq!(pool.len(), 3);
+        assert!(pool.iter().all(|x| subtasks.contains(x)));
+        assert_eq!(pool, select_pool(user, &subtasks, 3));
+
+        // adding a subtask replaces at most one subtask of the pool
+        let mut more = subtasks.clone();
+        more.push(Uuid::from_u128(10));
+        assert!(select_pool(user, &more, 3).difference(&pool).count() <= 1);
+
+        assert_eq!(select_pool(user, &subtasks, 20).len(), 10);
+        assert!(select_pool(user, &[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_select_pool_differs_between_users() {
+        let subtasks = (0..20).map(Uuid::from_u128).collect::<Vec<_>>();
+        let pools = (0..10)
+            .map(|user| {
+                let mut pool = select_pool(Uuid::from_u128(user), &subtasks, 5)
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                pool.sort();
+                pool
+            })
+            .collect::<HashSet<_>>();
+        assert!(pools.len() > 1);
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::{HashMap, HashSet};
+
+use entity::{challenges_subtask_prerequisites, challenges_subtasks};
+use sea_orm::{
+    sea_query::JoinType, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+    QuerySelect, RelationTrait,
+};
+use uuid::Uuid;
+
+/// Maps each subtask to the subtasks that have to be solved before it.
+pub type PrerequisiteGraph = HashMap<Uuid, Vec<Uuid>>;
+
+/// Return the prerequisite graph of all subtasks of a task.
+pub async fn get_prerequisite_graph(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+) -> Result<PrerequisiteGraph, DbErr> {
+    let mut graph = PrerequisiteGraph::new();
+    for edge in challenges_subtask_prerequisites::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            challenges_subtask_prerequisites::Relation::ChallengesSubtasks1.def(),
+        )
+        .filter(challenges_subtasks::Column::TaskId.eq(task_id))
+        .all(db)
+        .await?
+    {
+        graph
+            .entry(edge.subtask_id)
+            .or_default()
+            .push(edge.prerequisite_id);
+    }
+    Ok(graph)
+}
+
+/// Return whether all prerequisites of a subtask have been solved.
+pub fn prerequisites_met(
+    graph: &PrerequisiteGraph,
+    subtask_id: Uuid,
+    solved: &HashSet<Uuid>,
+) -> bool {
+    graph
+        .get(&subtask_id)
+        .map_or(true, |x| x.iter().all(|id| solved.contains(id)))
+}
+
+/// Return whether replacing the prerequisites of `subtask_id` would introduce
+/// a cycle into the graph.
+pub fn creates_cycle(graph: &PrerequisiteGraph, subtask_id: Uuid, prerequisites: &[Uuid]) -> bool {
+    let mut stack = prerequisites.to_vec();
+    let mut visited = HashSet::new();
+    while let Some(id) = stack.pop() {
+        if id == subtask_id {
+            return true;
+        }
+        if visited.insert(id) {
+            stack.extend(graph.get(&id).into_iter().flatten());
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prerequisites_met() {
+        let [a, b, c] = [1, 2, 3].map(Uuid::from_u128);
+        let graph = PrerequisiteGraph::from([(c, vec![a, b])]);
+        assert!(prerequisites_met(&graph, a, &HashSet::new()));
+        assert!(!prerequisites_met(&graph, c, &HashSet::from([a])));
+        assert!(prerequisites_met(&graph, c, &HashSet::from([a, b])));
+    }
+
+    #[test]
+    fn test_creates_cycle() {
+        let [a, b, c, d] = [1, 2, 3, 4].map(Uuid::from_u128);
+        let graph = PrerequisiteGraph::from([(b, vec![a]), (c, vec![b])]);
+        assert!(creates_cycle(&graph, a, &[c]));
+        assert!(creates_cycle(&graph, a, &[a]));
+        assert!(!creates_cycle(&graph, d, &[c]));
+        assert!(!creates_cycle(&graph, c, &[a, b]));
+    }
+}
@@ -0,0 +1,322 @@
+//! Parser for the [GIFT format](https://docs.moodle.org/en/GIFT_format).
+
+use super::{true_false, ImportItem, ImportedAnswer, ImportedQuestion, ParseError};
+
+/// Parse all questions of a GIFT file.
+pub fn parse(input: &str) -> Result<Vec<ImportItem>, ParseError> {
+    let mut items = Vec::new();
+    for (line, block) in blocks(input) {
+        if block.starts_with("$CATEGORY:") {
+            continue;
+        }
+        items.push(parse_question(&block).map_err(|message| ParseError { line, message })?);
+    }
+    Ok(items)
+}
+
+/// Split the input into blocks separated by blank lines and remove comments.
+/// Return the blocks together with their first line number.
+fn blocks(input: &str) -> Vec<(usize, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, line) in input.lines().enumerate() {
+        if line.trim_start().starts_with("//") {
+            continue;
+        }
+        if line.trim().is_empty() {
+            blocks.extend(current.take());
+            continue;
+        }
+        let (_, block) = current.get_or_insert_with(|| (i + 1, String::new()));
+        if !block.is_empty() {
+            block.push('\n');
+        }
+        block.push_str(line);
+    }
+    blocks.extend(current);
+    blocks
+}
+
+fn parse_question(block: &str) -> Result<ImportItem, String> {
+    let mut rest = block.trim();
+
+    let mut name = None;
+    if let Some(title) = rest.strip_prefix("::") {
+        let end = find_unescaped(title, "::").ok_or("unterminated title")?;
+        name = Some(unescape(title[..end].trim()));
+        rest = title[end + 2..].trim_start();
+    }
+    if rest.starts_with('[') {
+        if let Some(end) = rest.find(']') {
+            if rest[1..end].chars().all(|c| c.is_ascii_alphabetic()) {
+                rest = &rest[end + 1..];
+            }
+        }
+    }
+
+    let start = find_unescaped(rest, "{").ok_or("missing answer block")?;
+    let end = start + find_unescaped(&rest[start..], "}").ok_or("unterminated answer block")?;
+    let before = rest[..start].trim();
+    let after = rest[end + 1..].trim();
+    let question = if after.is_empty() {
+        unescape(before)
+    } else {
+        format!("{} _____ {}", unescape(before), unescape(after))
+    };
+
+    let mut answers = rest[start + 1..end].trim();
+    if let Some(feedback) = find_unescaped(answers, "####") {
+        answers = answers[..feedback].trim();
+    }
+    let unsupported = |ty: &str| {
+        Ok(ImportItem::Unsupported {
+            name: name.clone(),
+            ty: ty.into(),
+        })
+    };
+    if answers.is_empty() {
+        return unsupported("essay");
+    }
+    if answers.starts_with('#') {
+        return unsupported("numerical");
+    }
+
+    let first = strip_feedback(answers).trim().to_ascii_uppercase();
+    if let Some(correct) = match first.as_str() {
+        "T" | "TRUE" => Some(true),
+        "F" | "FALSE" => Some(false),
+        _ => None,
+    } {
+        return Ok(ImportItem::Question(ImportedQuestion {
+            name,
+            question,
+            answers: true_false(correct),
+            single_choice: true,
+        }));
+    }
+
+    let answers = split_answers(answers)?;
+    if answers.iter().all(|(marker, _)| *marker == '=') {
+        return if answers
+            .iter()
+            .any(|(_, text)| find_unescaped(text, "->").is_some())
+        {
+            unsupported("matching")
+        } else {
+            unsupported("short answer")
+        };
+    }
+
+    let mut weighted = false;
+    let answers = answers
+        .into_iter()
+        .map(|(marker, text)| {
+            let mut text = strip_feedback(text).trim();
+            let mut weight = None;
+            if let Some(weighted) = text.strip_prefix('%') {
+                let end = weighted.find('%').ok_or("unterminated answer weight")?;
+                let value = weighted[..end]
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid answer weight: {}", &weighted[..end]))?;
+                weight = Some(value);
+                text = weighted[end + 1..].trim();
+            }
+            weighted |= weight.is_some();
+            Ok(ImportedAnswer {
+                answer: unescape(text),
+                correct: marker == '=' || weight.is_some_and(|x| x > 0.0),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let single_choice = !weighted && answers.iter().filter(|x| x.correct).count() == 1;
+
+    Ok(ImportItem::Question(ImportedQuestion {
+        name,
+        question,
+        answers,
+        single_choice,
+    }))
+}
+
+/// Split an answer block into answers starting with `=` or `~`.
+fn split_answers(block: &str) -> Result<Vec<(char, &str)>, String> {
+    let mut answers = Vec::new();
+    let mut start: Option<(char, usize)> = None;
+    let mut escaped = false;
+    for (i, c) in block.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '=' | '~' => {
+                if let Some((marker, start)) = start {
+                    answers.push((marker, &block[start..i]));
+                } else if !block[..i].trim().is_empty() {
+                    return Err("answers must start with = or ~".into());
+                }
+                start = Some((c, i + 1));
+            }
+            _ => {}
+        }
+    }
+    match start {
+        Some((marker, start)) => answers.push((marker, &block[start..])),
+        None => return Err("answers must start with = or ~".into()),
+    }
+    Ok(answers)
+}
+
+/// Remove the feedback (starting with an unescaped `#`) from an answer.
+fn strip_feedback(answer: &str) -> &str {
+    match find_unescaped(answer, "#") {
+        Some(i) => &answer[..i],
+        None => answer,
+    }
+}
+
+/// Find the first occurrence of `pattern` that is not preceded by a
+/// backslash.
+fn find_unescaped(text: &str, pattern: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if text[i..].starts_with(pattern) {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(answer: &str, correct: bool) -> ImportedAnswer {
+        ImportedAnswer {
+            answer: answer.into(),
+            correct,
+        }
+    }
+
+    #[test]
+    fn test_multiple_choice() {
+        let input = "// a comment\n\
+                     ::Q1:: What is 1 + 1? {\n\
+                     =2 # correct\n\
+                     ~3\n\
+                     ~\\= 4\n\
+                     }\n\
+                     \n\
+                     $CATEGORY: foo\n\
+                     \n\
+                     Which are even? {~%50%2 ~%50%4 ~%-100%3}";
+        assert_eq!(
+            parse(input).unwrap(),
+            [
+                ImportItem::Question(ImportedQuestion {
+                    name: Some("Q1".into()),
+                    question: "What is 1 + 1?".into(),
+                    answers: vec![answer("2", true), answer("3", false), answer("= 4", false)],
+                    single_choice: true,
+                }),
+                ImportItem::Question(ImportedQuestion {
+                    name: None,
+                    question: "Which are even?".into(),
+                    answers: vec![answer("2", true), answer("4", true), answer("3", false)],
+                    single_choice: false,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_true_false() {
+        assert_eq!(
+            parse("[markdown]The sun is a star. {T#Yes!}").unwrap(),
+            [ImportItem::Question(ImportedQuestion {
+                name: None,
+                question: "The sun is a star.".into(),
+                answers: vec![answer("True", true), answer("False", false)],
+                single_choice: true,
+            })]
+        );
+        assert_eq!(
+            parse("Rust is slow. {FALSE}").unwrap()[0],
+            ImportItem::Question(ImportedQuestion {
+                name: None,
+                question: "Rust is slow.".into(),
+                answers: vec![answer("True", false), answer("False", true)],
+                single_choice: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_word() {
+        assert_eq!(
+            parse("Rust was created at {=Mozilla ~Google} Research.").unwrap()[0],
+            ImportItem::Question(ImportedQuestion {
+                name: None,
+                question: "Rust was created at _____ Research.".into(),
+                answers: vec![answer("Mozilla", true), answer("Google", false)],
+                single_choice: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let ty = |input| match parse(input).unwrap().remove(0) {
+            ImportItem::Unsupported { ty, .. } => ty,
+            item => panic!("expected unsupported question, got {item:?}"),
+        };
+        assert_eq!(ty("Write an essay. {}"), "essay");
+        assert_eq!(ty("How much is 2 + 2? {#4}"), "numerical");
+        assert_eq!(
+            ty("Who created Rust? {=Graydon =Graydon Hoare}"),
+            "short answer"
+        );
+        assert_eq!(ty("Match. {=a -> 1 =b -> 2}"), "matching");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse("\n\nNo answers here.").unwrap_err(),
+            ParseError {
+                line: 3,
+                message: "missing answer block".into()
+            }
+        );
+        assert_eq!(
+            parse("Broken {=a ~b").unwrap_err().message,
+            "unterminated answer block"
+        );
+        assert_eq!(
+            parse("Broken {a ~b}").unwrap_err().message,
+            "answers must start with = or ~"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Parsers for question banks exported from other learning platforms.
+//!
+//! Multiple choice and true/false questions are converted to multiple choice
+//! questions. Other question types are reported as unsupported, so that
+//! callers can tell the user which questions have not been imported.
+
+use thiserror::Error;
+
+pub mod gift;
+pub mod moodle_xml;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportItem {
+    Question(ImportedQuestion),
+    Unsupported {
+        name: Option<String>,
+        /// The type of the question (e.g. `essay`).
+        ty: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedQuestion {
+    pub name: Option<String>,
+    pub question: String,
+    pub answers: Vec<ImportedAnswer>,
+    pub single_choice: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedAnswer {
+    pub answer: String,
+    pub correct: bool,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Convert the answers of a true/false question to answers of a single
+/// choice question.
+fn true_false(correct: bool) -> Vec<ImportedAnswer> {
+    vec![
+        ImportedAnswer {
+            answer: "True".into(),
+            correct,
+        },
+        ImportedAnswer {
+            answer: "False".into(),
+            correct: !correct,
+        },
+    ]
+}
+
+/// Remove html tags and decode the most common entities.
+fn strip_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/');
+        if ["br", "p", "div", "li"]
+            .into_iter()
+            .any(|x| tag.split([' ', '/']).next() == Some(x))
+        {
+            out.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+
+    let out = out
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    out.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_html() {
+        assert_eq!(
+            strip_html("<p>What is <b>1 &lt; 2</b>?</p><p>Choose&nbsp;one.</p>"),
+            "What is 1 < 2?\nChoose one."
+        );
+        assert_eq!(strip_html("a<br/>b"), "a\nb");
+        assert_eq!(strip_html("plain text"), "plain text");
+    }
+}
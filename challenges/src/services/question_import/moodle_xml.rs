@@ -0,0 +1,241 @@
+//! Parser for the [Moodle XML format](https://docs.moodle.org/en/Moodle_XML_format).
+
+use quick_xml::{events::Event, Reader};
+
+use super::{strip_html, true_false, ImportItem, ImportedAnswer, ImportedQuestion, ParseError};
+
+/// Parse all questions of a Moodle XML file.
+pub fn parse(input: &str) -> Result<Vec<ImportItem>, ParseError> {
+    let mut reader = Reader::from_str(input);
+    reader.trim_text(true);
+    let error = |reader: &Reader<&[u8]>, message: String| ParseError {
+        line: line_at(input, reader.buffer_position()),
+        message,
+    };
+
+    let mut items = Vec::new();
+    let mut stack = Vec::<String>::new();
+    let mut question: Option<Question> = None;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|err| error(&reader, err.to_string()))?;
+        match event {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                let attribute = |key: &str| {
+                    start
+                        .try_get_attribute(key)
+                        .map_err(|err| err.to_string())?
+                        .map(|x| x.unescape_value().map(|x| x.into_owned()))
+                        .transpose()
+                        .map_err(|err| err.to_string())
+                };
+                match (stack.last().map(String::as_str), name.as_str()) {
+                    (Some("quiz"), "question") => {
+                        let ty = attribute("type").map_err(|err| error(&reader, err))?;
+                        question = Some(Question {
+                            ty: ty.unwrap_or_default(),
+                            ..Default::default()
+                        });
+                    }
+                    (Some("question"), "answer") => {
+                        let fraction = attribute("fraction").map_err(|err| error(&reader, err))?;
+                        let fraction = fraction
+                            .map(|x| x.parse::<f64>())
+                            .transpose()
+                            .map_err(|_| error(&reader, "invalid answer fraction".into()))?;
+                        if let Some(question) = &mut question {
+                            question.answers.push(ImportedAnswer {
+                                answer: String::new(),
+                                correct: fraction.is_some_and(|x| x > 0.0),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+                stack.push(name);
+            }
+            Event::End(_) if stack.pop().as_deref() == Some("question") && stack.len() == 1 => {
+                items.extend(question.take().and_then(Question::into_item));
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|err| error(&reader, err.to_string()))?;
+                if let Some(question) = &mut question {
+                    question.text(&stack, &text);
+                }
+            }
+            Event::CData(text) => {
+                let text = String::from_utf8_lossy(&text.into_inner()).into_owned();
+                if let Some(question) = &mut question {
+                    question.text(&stack, &text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(error(
+            &reader,
+            format!("unclosed element: {}", stack.join("/")),
+        ));
+    }
+    Ok(items)
+}
+
+#[derive(Default)]
+struct Question {
+    ty: String,
+    name: Option<String>,
+    text: String,
+    single: Option<bool>,
+    answers: Vec<ImportedAnswer>,
+}
+
+impl Question {
+    fn text(&mut self, stack: &[String], text: &str) {
+        let path = stack.iter().map(String::as_str).collect::<Vec<_>>();
+        match path.as_slice() {
+            [.., "question", "name", "text"] => self.name = Some(text.trim().into()),
+            [.., "question", "questiontext", "text"] => self.text.push_str(text),
+            [.., "question", "single"] => {
+                self.single = Some(matches!(text.trim(), "true" | "1"));
+            }
+            [.., "question", "answer", "text"] => {
+                if let Some(answer) = self.answers.last_mut() {
+                    answer.answer.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn into_item(self) -> Option<ImportItem> {
+        let question = strip_html(&self.text);
+        match self.ty.as_str() {
+            "category" => None,
+            "multichoice" => Some(ImportItem::Question(ImportedQuestion {
+                name: self.name,
+                question,
+                answers: self
+                    .answers
+                    .into_iter()
+                    .map(|answer| ImportedAnswer {
+                        answer: strip_html(&answer.answer),
+                        correct: answer.correct,
+                    })
+                    .collect(),
+                single_choice: self.single.unwrap_or(true),
+            })),
+            "truefalse" => {
+                let correct = self
+                    .answers
+                    .iter()
+                    .find(|x| x.correct)
+                    .is_some_and(|x| x.answer.trim().eq_ignore_ascii_case("true"));
+                Some(ImportItem::Question(ImportedQuestion {
+                    name: self.name,
+                    question,
+                    answers: true_false(correct),
+                    single_choice: true,
+                }))
+            }
+            _ => Some(ImportItem::Unsupported {
+                name: self.name,
+                ty: self.ty,
+            }),
+        }
+    }
+}
+
+fn line_at(input: &str, position: usize) -> usize {
+    let position = position.min(input.len());
+    input.as_bytes()[..position]
+        .iter()
+        .filter(|&&x| x == b'\n')
+        .count()
+        + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIZ: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<quiz>
+  <question type="category">
+    <category><text>$course$/Rust</text></category>
+  </question>
+  <question type="multichoice">
+    <name><text>Ownership</text></name>
+    <questiontext format="html">
+      <text><![CDATA[<p>Which types implement <code>Copy</code>?</p>]]></text>
+    </questiontext>
+    <single>false</single>
+    <answer fraction="50"><text>u32</text></answer>
+    <answer fraction="50"><text>&amp;str</text></answer>
+    <answer fraction="-100"><text>String</text><feedback><text>No</text></feedback></answer>
+  </question>
+  <question type="truefalse">
+    <name><text>Borrowing</text></name>
+    <questiontext format="html"><text>A reference can outlive its value.</text></questiontext>
+    <answer fraction="0"><text>true</text></answer>
+    <answer fraction="100"><text>false</text></answer>
+  </question>
+  <question type="essay">
+    <name><text>Essay</text></name>
+    <questiontext format="html"><text>Explain lifetimes.</text></questiontext>
+  </question>
+</quiz>"#;
+
+    fn answer(answer: &str, correct: bool) -> ImportedAnswer {
+        ImportedAnswer {
+            answer: answer.into(),
+            correct,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(QUIZ).unwrap(),
+            [
+                ImportItem::Question(ImportedQuestion {
+                    name: Some("Ownership".into()),
+                    question: "Which types implement Copy?".into(),
+                    answers: vec![
+                        answer("u32", true),
+                        answer("&str", true),
+                        answer("String", false)
+                    ],
+                    single_choice: false,
+                }),
+                ImportItem::Question(ImportedQuestion {
+                    name: Some("Borrowing".into()),
+                    question: "A reference can outlive its value.".into(),
+                    answers: vec![answer("True", false), answer("False", true)],
+                    single_choice: true,
+                }),
+                ImportItem::Unsupported {
+                    name: Some("Essay".into()),
+                    ty: "essay".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("<quiz>\n<question type=\"multichoice\">\n</quiz>").unwrap_err();
+        assert_eq!(err.line, 3);
+        let err = parse("<quiz>\n<question type=\"multichoice\">").unwrap_err();
+        assert!(err.message.starts_with("unclosed element"));
+        let err =
+            parse("<quiz><question type=\"multichoice\"><answer fraction=\"x\">").unwrap_err();
+        assert_eq!(err.message, "invalid answer fraction");
+    }
+}
@@ -0,0 +1,196 @@
+//! Templated questions with a unique variant for each user and attempt.
+//!
+//! The question text and the answers of a question with parameters may contain
+//! `{{ expression }}` placeholders. For each attempt, every parameter is
+//! replaced by a random integer derived from the seed of the attempt and the
+//! placeholders are replaced by the values of their expressions.
+
+use std::collections::BTreeMap;
+
+use entity::{
+    challenges_attempt_seeds::{self, AttemptParameters},
+    challenges_questions::{self, QuestionParameter},
+};
+use sea_orm::{DatabaseTransaction, DbErr, EntityTrait};
+use uuid::Uuid;
+
+use super::{
+    expressions::{render, ExpressionError},
+    shuffle::{attempt_seed, record_seed, splitmix64},
+};
+
+/// The number of random variants that are checked when validating a
+/// templated question (in addition to the minimum and maximum values).
+const VALIDATION_SAMPLES: i64 = 100;
+
+/// Derive the values of the parameters from the seed of an attempt.
+pub fn generate_parameters(seed: i64, parameters: &[QuestionParameter]) -> AttemptParameters {
+    let mut state = seed as u64;
+    AttemptParameters(
+        parameters
+            .iter()
+            .map(|param| {
+                let offset = match param.max.abs_diff(param.min).checked_add(1) {
+                    Some(range) => splitmix64(&mut state) % range,
+                    None => splitmix64(&mut state),
+                };
+                (param.name.clone(), param.min.wrapping_add_unsigned(offset))
+            })
+            .collect(),
+    )
+}
+
+/// Replace the placeholders in the question text and the answers.
+pub fn instantiate(
+    mut question: challenges_questions::Model,
+    values: &AttemptParameters,
+) -> Result<challenges_questions::Model, ExpressionError> {
+    if question.parameters.0.is_empty() {
+        return Ok(question);
+    }
+    question.question = render(&question.question, &values.0)?;
+    question.answers = question
+        .answers
+        .iter()
+        .map(|answer| render(answer, &values.0))
+        .collect::<Result<_, _>>()?;
+    Ok(question)
+}
+
+/// Return the variant of the question the user has to solve in the given
+/// attempt. The values of the parameters are stored on first use, so the
+/// variant does not change if the parameters of the question are modified
+/// during the attempt.
+pub async fn get_variant(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    attempt: usize,
+    question: challenges_questions::Model,
+) -> Result<Result<challenges_questions::Model, ExpressionError>, DbErr> {
+    if question.parameters.0.is_empty() {
+        return Ok(Ok(question));
+    }
+    let stored =
+        challenges_attempt_seeds::Entity::find_by_id((user_id, question.subtask_id, attempt as _))
+            .one(db)
+            .await?;
+    let values = match stored {
+        Some(challenges_attempt_seeds::Model {
+            parameters: Some(values),
+            ..
+        }) => values,
+        Some(stored) => generate_parameters(stored.seed, &question.parameters.0),
+        None => {
+            let seed = attempt_seed(user_id, question.subtask_id, attempt);
+            let values = generate_parameters(seed, &question.parameters.0);
+            record_seed(
+                db,
+                user_id,
+                question.subtask_id,
+                attempt,
+                seed,
+                Some(values.clone()),
+            )
+            .await?;
+            values
+        }
+    };
+    Ok(instantiate(question, &values))
+}
+
+/// Check that the parameters are valid and that all placeholders can be
+/// evaluated for the minimum and maximum values and a sample of random
+/// variants. Returns the answers of the variant with the minimum values.
+pub fn validate_template(
+    parameters: &[QuestionParameter],
+    question: &str,
+    answers: &[String],
+) -> Result<Vec<String>, String> {
+    for (i, param) in parameters.iter().enumerate() {
+        if param.min > param.max {
+            return Err(format!(
+                "`min` is greater than `max` for parameter `{}`",
+                param.name
+            ));
+        }
+        if parameters[..i].iter().any(|x| x.name == param.name) {
+            return Err(format!("duplicate parameter `{}`", param.name));
+        }
+    }
+    if parameters.is_empty() {
+        return Ok(answers.into());
+    }
+
+    let corner = |f: fn(&QuestionParameter) -> i64| {
+        AttemptParameters(
+            parameters
+                .iter()
+                .map(|param| (param.name.clone(), f(param)))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    };
+    let variants = [corner(|x| x.min), corner(|x| x.max)]
+        .into_iter()
+        .chain((0..VALIDATION_SAMPLES).map(|seed| generate_parameters(seed, parameters)));
+    let mut out = Vec::new();
+    for (i, values) in variants.enumerate() {
+        render(question, &values.0).map_err(|err| format!("{question}: {err}"))?;
+        for answer in answers {
+            let answer = render(answer, &values.0).map_err(|err| format!("{answer}: {err}"))?;
+            if i == 0 {
+                out.push(answer);
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, min: i64, max: i64) -> QuestionParameter {
+        QuestionParameter {
+            name: name.into(),
+            min,
+            max,
+        }
+    }
+
+    #[test]
+    fn test_generate_parameters() {
+        let parameters = [
+            param("a", 1, 10),
+            param("b", -5, -5),
+            param("c", i64::MIN, i64::MAX),
+        ];
+        for seed in 0..100 {
+            let values = generate_parameters(seed, &parameters);
+            assert_eq!(values, generate_parameters(seed, &parameters));
+            assert!((1..=10).contains(&values.0["a"]));
+            assert_eq!(values.0["b"], -5);
+        }
+        assert_ne!(
+            generate_parameters(1, &parameters),
+            generate_parameters(2, &parameters)
+        );
+    }
+
+    #[test]
+    fn test_validate_template() {
+        let answers = ["{{a * b}}".to_owned()];
+        let parameters = [param("a", 1, 10), param("b", 2, 3)];
+        assert_eq!(
+            validate_template(&parameters, "What is {{a}} * {{b}}?", &answers),
+            Ok(vec!["2".into()])
+        );
+        assert!(validate_template(&parameters, "What is {{a}} * {{c}}?", &answers).is_err());
+        assert!(validate_template(&parameters, "{{a / (b - 2)}}", &answers).is_err());
+        assert!(validate_template(&[param("a", 2, 1)], "{{a}}", &[]).is_err());
+        assert!(validate_template(&[param("a", 1, 2), param("a", 1, 2)], "{{a}}", &[]).is_err());
+        assert_eq!(
+            validate_template(&[], "{{ not a template", &answers),
+            Ok(answers.into())
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Per-user quotas for creating subtasks.
+//!
+//! In contrast to bans, quotas only limit how fast users can create subtasks
+//! and how many of their subtasks may wait for a review at the same time.
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use entity::{challenges_subtasks, sea_orm_active_enums::ChallengesReviewStatus};
+use lib::{auth::User, config::challenges::Quotas};
+use sea_orm::{
+    ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+
+pub enum Quota {
+    Available,
+    /// The quota has been exceeded. Contains the time at which the user can
+    /// create subtasks again, if it is known.
+    Exceeded(Option<DateTime<Utc>>),
+}
+
+/// Return the time at which the user can create another subtask if at least
+/// `max` of the given creation timestamps (sorted in ascending order) are
+/// within `window` before `now`. Returns `None` if the quota has not been
+/// exceeded.
+pub fn window_reset(
+    timestamps: &[NaiveDateTime],
+    now: NaiveDateTime,
+    window: Duration,
+    max: u64,
+) -> Option<NaiveDateTime> {
+    let recent = &timestamps[timestamps.partition_point(|&ts| ts <= now - window)..];
+    let excess = (recent.len() as u64).checked_sub(max)?;
+    recent.get(excess as usize).map(|&ts| ts + window)
+}
+
+/// Check whether the user is allowed to create another subtask. Admins are
+/// not subject to any quotas.
+pub async fn check_quota(
+    db: &DatabaseTransaction,
+    config: &Quotas,
+    user: &User,
+) -> Result<Quota, DbErr> {
+    if user.admin {
+        return Ok(Quota::Available);
+    }
+
+    if let Some(max) = config.max_pending {
+        let pending = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::Creator.eq(user.id))
+            .filter(challenges_subtasks::Column::ReviewStatus.eq(ChallengesReviewStatus::Pending))
+            .count(db)
+            .await?;
+        if pending >= max {
+            // the quota is reset once a moderator reviews one of the pending subtasks
+            return Ok(Quota::Exceeded(None));
+        }
+    }
+
+    if config.max_per_day.is_none() && config.max_per_week.is_none() {
+        return Ok(Quota::Available);
+    }
+    let now = Utc::now().naive_utc();
+    let timestamps = challenges_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_subtasks::Column::CreationTimestamp)
+        .filter(challenges_subtasks::Column::Creator.eq(user.id))
+        .filter(challenges_subtasks::Column::CreationTimestamp.gt(now - Duration::weeks(1)))
+        .order_by_asc(challenges_subtasks::Column::CreationTimestamp)
+        .into_tuple::<NaiveDateTime>()
+        .all(db)
+        .await?;
+    let reset = [
+        (Duration::days(1), config.max_per_day),
+        (Duration::weeks(1), config.max_per_week),
+    ]
+    .into_iter()
+    .filter_map(|(window, max)| window_reset(&timestamps, now, window, max?))
+    .max();
+
+    Ok(match reset {
+        Some(reset) => Quota::Exceeded(Some(reset.and_utc())),
+        None => Quota::Available,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_window_reset() {
+        let ts = |h| {
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                + Duration::hours(h)
+        };
+        let timestamps = [ts(0), ts(10), ts(20), ts(30)];
+        let day = Duration::days(1);
+
+        assert_eq!(window_reset(&timestamps, ts(31), day, 4), None);
+        assert_eq!(window_reset(&timestamps, ts(31), day, 3), Some(ts(34)));
+        assert_eq!(window_reset(&timestamps, ts(31), day, 2), Some(ts(44)));
+        assert_eq!(window_reset(&timestamps, ts(31), day, 1), Some(ts(54)));
+        assert_eq!(window_reset(&timestamps[..3], ts(23), day, 3), Some(ts(24)));
+        assert_eq!(window_reset(&timestamps[..3], ts(24), day, 3), None);
+        assert_eq!(window_reset(&[], ts(0), day, 1), None);
+    }
+}
@@ -0,0 +1,242 @@
+//! Recommendation of the next subtask a user should work on.
+//!
+//! The difficulty of a subtask is estimated from the aggregated solve rate of
+//! all users who attempted it. Users are pointed to unsolved subtasks whose
+//! prerequisites have been met and whose difficulty is slightly above what
+//! their own success rate suggests they can handle.
+
+use std::collections::{HashMap, HashSet};
+
+use entity::challenges_user_subtasks;
+use lib::auth::User;
+use schemas::challenges::subtasks::Subtask;
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, QuerySelect,
+};
+use uuid::Uuid;
+
+use super::{
+    prerequisites::{get_prerequisite_graph, prerequisites_met},
+    subtasks::{get_user_subtasks, query_subtasks_only, QuerySubtasksFilter},
+};
+
+/// Pseudo observations which pull success rates with little data towards 50%.
+const PRIOR_SOLVED: f64 = 1.0;
+const PRIOR_ATTEMPTED: f64 = 2.0;
+
+/// How much harder than their current level users should be challenged.
+const STRETCH: f64 = 0.1;
+
+/// Score penalty for each failed attempt of the user, so users who are stuck
+/// on a subtask are offered something else.
+const FAILED_ATTEMPT_PENALTY: f64 = 0.05;
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub id: Uuid,
+    /// Estimated difficulty between 0 (everyone solves it) and 1 (nobody
+    /// solves it).
+    pub difficulty: f64,
+    /// Number of failed attempts of the user.
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recommendation {
+    pub subtask: Subtask,
+    pub difficulty: f64,
+    pub score: f64,
+}
+
+/// Return the smoothed ratio of solved to attempted subtasks.
+pub fn success_rate(solved: u64, attempted: u64) -> f64 {
+    (solved as f64 + PRIOR_SOLVED) / (attempted as f64 + PRIOR_ATTEMPTED)
+}
+
+/// Return the difficulty that suits a user with the given success rate best.
+pub fn target_difficulty(success_rate: f64) -> f64 {
+    (success_rate + STRETCH).clamp(0.0, 1.0)
+}
+
+/// Score a candidate for a given target difficulty. Higher is better.
+pub fn score(candidate: &Candidate, target: f64) -> f64 {
+    1.0 - (candidate.difficulty - target).abs()
+        - FAILED_ATTEMPT_PENALTY * candidate.attempts.min(MAX_FAILED_ATTEMPTS) as f64
+}
+
+/// Return the best candidate for a user with the given success rate together
+/// with its score. Ties are broken by preferring easier subtasks.
+pub fn recommend(candidates: &[Candidate], success_rate: f64) -> Option<(Candidate, f64)> {
+    let target = target_difficulty(success_rate);
+    candidates
+        .iter()
+        .map(|candidate| (*candidate, score(candidate, target)))
+        .max_by(|(a, a_score), (b, b_score)| {
+            a_score
+                .total_cmp(b_score)
+                .then(b.difficulty.total_cmp(&a.difficulty))
+                .then(b.id.cmp(&a.id))
+        })
+}
+
+/// Recommend the next subtask of a task for a user.
+pub async fn recommend_subtask(
+    db: &DatabaseTransaction,
+    user: &User,
+    task_id: Uuid,
+) -> Result<Option<Recommendation>, DbErr> {
+    let subtasks = query_subtasks_only(
+        db,
+        user,
+        Some(task_id),
+        QuerySubtasksFilter {
+            retired: Some(false),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let solved = subtasks
+        .iter()
+        .filter(|x| x.solved)
+        .map(|x| x.id)
+        .collect::<HashSet<_>>();
+    let graph = get_prerequisite_graph(db, task_id).await?;
+    let mut subtasks = subtasks
+        .into_iter()
+        .filter(|x| !x.solved && prerequisites_met(&graph, x.id, &solved))
+        .map(|x| (x.id, x))
+        .collect::<HashMap<_, _>>();
+
+    let stats = get_solve_stats(db, subtasks.keys().copied()).await?;
+    let user_subtasks = get_user_subtasks(db, user.id).await?;
+    let candidates = subtasks
+        .keys()
+        .map(|&id| {
+            let (solved, attempted) = stats.get(&id).copied().unwrap_or_default();
+            Candidate {
+                id,
+                difficulty: 1.0 - success_rate(solved, attempted),
+                attempts: user_subtasks.get(&id).map_or(0, |x| x.attempts as _),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let attempted = user_subtasks.values().filter(|x| x.attempts > 0);
+    let user_success_rate = success_rate(
+        attempted
+            .clone()
+            .filter(|x| x.solved_timestamp.is_some())
+            .count() as _,
+        attempted.count() as _,
+    );
+
+    Ok(
+        recommend(&candidates, user_success_rate).and_then(|(candidate, score)| {
+            Some(Recommendation {
+                subtask: subtasks.remove(&candidate.id)?,
+                difficulty: candidate.difficulty,
+                score,
+            })
+        }),
+    )
+}
+
+/// Return the number of users who solved and attempted each of the subtasks.
+async fn get_solve_stats(
+    db: &DatabaseTransaction,
+    subtask_ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, (u64, u64)>, DbErr> {
+    Ok(challenges_user_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_user_subtasks::Column::SubtaskId)
+        .column_as(
+            Expr::col(challenges_user_subtasks::Column::SolvedTimestamp).count(),
+            "solved",
+        )
+        .column_as(
+            Expr::col(challenges_user_subtasks::Column::UserId).count(),
+            "attempted",
+        )
+        .filter(challenges_user_subtasks::Column::SubtaskId.is_in(subtask_ids))
+        .filter(challenges_user_subtasks::Column::Attempts.gt(0))
+        .group_by(challenges_user_subtasks::Column::SubtaskId)
+        .into_tuple::<(Uuid, i64, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(id, solved, attempted)| (id, (solved as _, attempted as _)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: u128, difficulty: f64, attempts: u32) -> Candidate {
+        Candidate {
+            id: Uuid::from_u128(id),
+            difficulty,
+            attempts,
+        }
+    }
+
+    #[test]
+    fn test_success_rate() {
+        assert_eq!(success_rate(0, 0), 0.5);
+        assert_eq!(success_rate(8, 8), 0.9);
+        assert_eq!(success_rate(0, 8), 0.1);
+    }
+
+    #[test]
+    fn test_target_difficulty() {
+        assert!((target_difficulty(0.5) - 0.6).abs() < 1e-9);
+        assert_eq!(target_difficulty(0.0), STRETCH);
+        assert_eq!(target_difficulty(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_recommend_slightly_harder() {
+        let candidates = [
+            candidate(1, 0.1, 0),
+            candidate(2, 0.35, 0),
+            candidate(3, 0.9, 0),
+        ];
+        assert_eq!(
+            recommend(&candidates, 0.0).unwrap().0.id,
+            Uuid::from_u128(1)
+        );
+        assert_eq!(
+            recommend(&candidates, 0.2).unwrap().0.id,
+            Uuid::from_u128(2)
+        );
+        assert_eq!(
+            recommend(&candidates, 0.8).unwrap().0.id,
+            Uuid::from_u128(3)
+        );
+    }
+
+    #[test]
+    fn test_recommend_failed_attempts() {
+        let candidates = [candidate(1, 0.5, 4), candidate(2, 0.6, 0)];
+        assert_eq!(
+            recommend(&candidates, 0.5).unwrap().0.id,
+            Uuid::from_u128(2)
+        );
+        let candidates = [candidate(1, 0.6, 4), candidate(2, 0.45, 0)];
+        assert_eq!(
+            recommend(&candidates, 0.5).unwrap().0.id,
+            Uuid::from_u128(2)
+        );
+    }
+
+    #[test]
+    fn test_recommend_ties() {
+        let candidates = [candidate(2, 0.75, 0), candidate(1, 0.25, 0)];
+        assert_eq!(
+            recommend(&candidates, 0.4).unwrap().0.id,
+            Uuid::from_u128(1)
+        );
+        assert_eq!(recommend(&[], 0.5), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Spaced repetition of solved subtasks based on the SM-2 algorithm.
+
+use chrono::{Duration, NaiveDateTime};
+use entity::{challenges_reviews, challenges_subtasks};
+use lib::{config::challenges::Rewards, services::Services};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use super::{
+    rewards::add_xp,
+    subtasks::{get_parent_task, get_skills, SendTaskRewardsError},
+};
+
+const INITIAL_EASE_FACTOR: f64 = 2.5;
+const MIN_EASE_FACTOR: f64 = 1.3;
+
+/// Answers with a quality below this threshold reset the repetitions.
+const MIN_QUALITY: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease_factor: f64,
+    /// Number of days until the next review.
+    pub interval: i32,
+    /// Number of consecutive successful reviews.
+    pub repetitions: i32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            ease_factor: INITIAL_EASE_FACTOR,
+            interval: 1,
+            repetitions: 0,
+        }
+    }
+}
+
+impl From<&challenges_reviews::Model> for Schedule {
+    fn from(review: &challenges_reviews::Model) -> Self {
+        Self {
+            ease_factor: review.ease_factor,
+            interval: review.interval,
+            repetitions: review.repetitions,
+        }
+    }
+}
+
+/// Compute the next schedule after a review with the given quality (0-5).
+pub fn next_schedule(schedule: Schedule, quality: u8) -> Schedule {
+    let quality = quality.min(5);
+    let (interval, repetitions) = if quality >= MIN_QUALITY {
+        let interval = match schedule.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (schedule.interval as f64 * schedule.ease_factor).round() as i32,
+        };
+        (interval, schedule.repetitions + 1)
+    } else {
+        (1, 0)
+    };
+    let q = (5 - quality) as f64;
+    let ease_factor = (schedule.ease_factor + 0.1 - q * (0.08 + q * 0.02)).max(MIN_EASE_FACTOR);
+    Schedule {
+        ease_factor,
+        interval,
+        repetitions,
+    }
+}
+
+/// Map the number of correct answers to the quality of a review.
+///
+/// A completely correct answer is rated 4, partially correct answers are
+/// rated proportionally below the threshold of a successful review.
+pub fn quality(correct: usize, total: usize) -> u8 {
+    if total == 0 || correct >= total {
+        4
+    } else {
+        (MIN_QUALITY as usize * correct / total) as u8
+    }
+}
+
+/// Add a subtask to the review queue of a user. Does nothing if the subtask
+/// is already in the queue.
+pub async fn schedule_review(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+    now: NaiveDateTime,
+) -> Result<(), DbErr> {
+    let schedule = Schedule::default();
+    challenges_reviews::Entity::insert(challenges_reviews::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        subtask_id: Set(subtask_id),
+        ease_factor: Set(schedule.ease_factor),
+        interval: Set(schedule.interval),
+        repetitions: Set(schedule.repetitions),
+        due: Set(now + Duration::days(schedule.interval as _)),
+        last_review: Set(None),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            challenges_reviews::Column::UserId,
+            challenges_reviews::Column::SubtaskId,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+/// Return all reviews of a user that are due, together with their subtasks.
+pub async fn get_due_reviews(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    now: NaiveDateTime,
+) -> Result<Vec<(challenges_reviews::Model, challenges_subtasks::Model)>, DbErr> {
+    Ok(challenges_reviews::Entity::find()
+        .find_also_related(challenges_subtasks::Entity)
+        .filter(challenges_reviews::Column::UserId.eq(user_id))
+        .filter(challenges_reviews::Column::Due.lte(now))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .order_by_asc(challenges_reviews::Column::Due)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(review, subtask)| Some((review, subtask?)))
+        .collect())
+}
+
+/// Distribute the xp for a completed review across the skills of the parent
+/// task of the subtask.
+pub async fn send_review_rewards(
+    services: &Services,
+    db: &DatabaseTransaction,
+    config: &Rewards,
+    user_id: Uuid,
+    subtask: &challenges_subtasks::Model,
+    xp: u64,
+) -> Result<(), SendTaskRewardsError> {
+    if subtask.retired || xp == 0 {
+        return Ok(());
+    }
+
+    let skills = get_skills(
+        services,
+        get_parent_task(db, subtask)
+            .await?
+            .ok_or(SendTaskRewardsError::NoParentTask)?
+            .1,
+    )
+    .await?;
+    for skill in &skills {
+        add_xp(
+            services,
+            db,
+            config,
+            user_id,
+            skill,
+            xp as i64 / skills.len() as i64,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_schedule_intervals() {
+        let mut schedule = Schedule::default();
+        let intervals = (0..4)
+            .map(|_| {
+                schedule = next_schedule(schedule, 4);
+                schedule.interval
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(intervals, [1, 6, 15, 38]);
+        assert_eq!(schedule.repetitions, 4);
+        assert_eq!(schedule.ease_factor, INITIAL_EASE_FACTOR);
+    }
+
+    #[test]
+    fn test_next_schedule_failure() {
+        let schedule = Schedule {
+            ease_factor: 2.5,
+            interval: 15,
+            repetitions: 3,
+        };
+        let next = next_schedule(schedule, 1);
+        assert_eq!(next.interval, 1);
+        assert_eq!(next.repetitions, 0);
+        assert!((next.ease_factor - 1.96).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_next_schedule_min_ease_factor() {
+        let mut schedule = Schedule::default();
+        for _ in 0..10 {
+            schedule = next_schedule(schedule, 0);
+        }
+        assert_eq!(schedule.ease_factor, MIN_EASE_FACTOR);
+    }
+
+    #[test]
+    fn test_quality() {
+        assert_eq!(quality(4, 4), 4);
+        assert_eq!(quality(3, 4), 2);
+        assert_eq!(quality(0, 4), 0);
+        assert_eq!(quality(0, 0), 4);
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{NaiveDateTime, Utc};
+use entity::{challenges_subtask_revisions, challenges_subtasks};
+use lib::auth::User;
+use schemas::challenges::subtasks::Subtask;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, Related, Set,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use super::subtasks::{get_subtask, get_user_subtask, UserSubtaskExt};
+
+/// Store the current state of a subtask and its type specific content as a
+/// new revision. Must be called whenever a subtask is created or updated.
+pub async fn record_revision(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+    content: &impl Serialize,
+) -> Result<challenges_subtask_revisions::Model, DbErr> {
+    let subtask = challenges_subtasks::Entity::find_by_id(subtask_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("subtask {subtask_id}")))?;
+    challenges_subtask_revisions::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        subtask_id: Set(subtask_id),
+        timestamp: Set(Utc::now().naive_utc()),
+        subtask: Set(to_json(&subtask)?),
+        content: Set(to_json(content)?),
+    }
+    .insert(db)
+    .await
+}
+
+/// Return the id of the latest revision of a subtask.
+pub async fn get_current_revision(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+) -> Result<Option<Uuid>, DbErr> {
+    Ok(challenges_subtask_revisions::Entity::find()
+        .filter(challenges_subtask_revisions::Column::SubtaskId.eq(subtask_id))
+        .order_by_desc(challenges_subtask_revisions::Column::Timestamp)
+        .one(db)
+        .await?
+        .map(|revision| revision.id))
+}
+
+/// Return a subtask in the state it had at the given time, reconstructed from
+/// the latest revision that was recorded before this time.
+pub async fn query_subtask_as_of<E, T>(
+    db: &DatabaseTransaction,
+    user: &User,
+    task_id: Uuid,
+    subtask_id: Uuid,
+    as_of: NaiveDateTime,
+    map: impl Fn(E::Model, Subtask) -> T,
+) -> Result<Result<T, QuerySubtaskAsOfError>, DbErr>
+where
+    E: EntityTrait + Related<challenges_subtasks::Entity>,
+    E::PrimaryKey: sea_orm::PrimaryKeyTrait<ValueType = Uuid>,
+    E::Model: DeserializeOwned,
+{
+    if get_subtask::<E>(db, task_id, subtask_id).await?.is_none() {
+        return Ok(Err(QuerySubtaskAsOfError::SubtaskNotFound));
+    }
+
+    let Some(revision) = challenges_subtask_revisions::Entity::find()
+        .filter(challenges_subtask_revisions::Column::SubtaskId.eq(subtask_id))
+        .filter(challenges_subtask_revisions::Column::Timestamp.lte(as_of))
+        .order_by_desc(challenges_subtask_revisions::Column::Timestamp)
+        .one(db)
+        .await?
+    else {
+        return Ok(Err(QuerySubtaskAsOfError::RevisionNotFound));
+    };
+    let subtask: challenges_subtasks::Model = from_json(revision.subtask)?;
+    let specific: E::Model = from_json(revision.content)?;
+
+    let user_subtask = get_user_subtask(db, user.id, subtask.id).await?;
+    Ok(Ok(map(
+        specific,
+        Subtask::from(subtask, user_subtask.is_solved(), user_subtask.is_rated()),
+    )))
+}
+
+pub enum QuerySubtaskAsOfError {
+    SubtaskNotFound,
+    RevisionNotFound,
+}
+
+fn to_json(value: &impl Serialize) -> Result<serde_json::Value, DbErr> {
+    serde_json::to_value(value).map_err(|err| DbErr::Json(err.to_string()))
+}
+
+fn from_json<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, DbErr> {
+    serde_json::from_value(value).map_err(|err| DbErr::Json(err.to_string()))
+}
@@ -0,0 +1,79 @@
+//! Sending xp and coins to users.
+//!
+//! All rewards are sent through this module, so they can be disabled via the
+//! `dry_run` flag (e.g. to test staging environments against production-like
+//! data). In dry runs the rewards are only logged and recorded in the reward
+//! ledger instead of being sent to the skills and shop services.
+
+use chrono::Utc;
+use entity::challenges_reward_ledger;
+use lib::{config::challenges::Rewards, services::Services};
+use sea_orm::{ActiveModelTrait, DatabaseTransaction, DbErr, Set};
+use tracing::info;
+use uuid::Uuid;
+
+use super::subtasks::SendTaskRewardsError;
+
+/// Add xp to a skill of the user.
+pub async fn add_xp(
+    services: &Services,
+    db: &DatabaseTransaction,
+    config: &Rewards,
+    user_id: Uuid,
+    skill: &str,
+    xp: i64,
+) -> Result<(), SendTaskRewardsError> {
+    if config.dry_run {
+        info!("dry run: not adding {xp} xp to skill {skill} of user {user_id}");
+        record(db, user_id, Some(skill), xp, 0, None).await?;
+        return Ok(());
+    }
+    services
+        .skills
+        .add_skill_progress(user_id, skill, xp)
+        .await??;
+    Ok(())
+}
+
+/// Add coins to the balance of the user.
+pub async fn add_coins(
+    services: &Services,
+    db: &DatabaseTransaction,
+    config: &Rewards,
+    user_id: Uuid,
+    coins: i64,
+    description: &str,
+) -> Result<(), SendTaskRewardsError> {
+    if config.dry_run {
+        info!("dry run: not adding {coins} coins ({description}) to user {user_id}");
+        record(db, user_id, None, 0, coins, Some(description)).await?;
+        return Ok(());
+    }
+    services
+        .shop
+        .add_coins(user_id, coins, description, true)
+        .await??;
+    Ok(())
+}
+
+async fn record(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    skill: Option<&str>,
+    xp: i64,
+    coins: i64,
+    description: Option<&str>,
+) -> Result<(), DbErr> {
+    challenges_reward_ledger::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        skill: Set(skill.map(Into::into)),
+        xp: Set(xp),
+        coins: Set(coins),
+        description: Set(description.map(Into::into)),
+        timestamp: Set(Utc::now().naive_utc()),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
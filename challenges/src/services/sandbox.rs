@@ -0,0 +1,463 @@
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use lib::config::challenges::{CodingChallenges, SandboxMode};
+use sandkasten_client::{
+    schemas::{
+        configuration::PublicConfig,
+        environments::Environment,
+        programs::{BuildRunError, BuildRunRequest, BuildRunResult, LimitExceeded},
+        ErrorResponse, GeneralError,
+    },
+    Error, Result, SandkastenClient,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+pub type SharedSandbox = Arc<dyn Sandbox>;
+
+/// An environment in which programs can be built and run.
+pub trait Sandbox: Send + Sync {
+    /// Return the version of the sandbox.
+    fn version(&self) -> BoxFuture<'_, Result<String>>;
+
+    /// Return the public configuration of the sandbox.
+    fn get_config(&self) -> BoxFuture<'_, Result<PublicConfig>>;
+
+    /// Return a list of all environments.
+    fn list_environments(&self) -> BoxFuture<'_, Result<HashMap<String, Environment>>>;
+
+    /// Build and immediately run a program.
+    fn build_and_run<'a>(
+        &'a self,
+        data: &'a BuildRunRequest,
+    ) -> BoxFuture<'a, Result<BuildRunResult, BuildRunError>>;
+}
+
+/// Create the sandbox selected in the config.
+pub fn sandbox_from_config(config: &CodingChallenges) -> SharedSandbox {
+    let client = SandkastenClient::new(config.sandkasten_url.clone());
+    match config.sandbox_mode {
+        SandboxMode::Live => Arc::new(client),
+        SandboxMode::Record => Arc::new(RecordingSandbox {
+            client,
+            recordings: config.recordings.clone(),
+        }),
+        SandboxMode::Replay => Arc::new(ReplaySandbox {
+            recordings: config.recordings.clone(),
+        }),
+    }
+}
+
+impl Sandbox for SandkastenClient {
+    fn version(&self) -> BoxFuture<'_, Result<String>> {
+        SandkastenClient::version(self).boxed()
+    }
+
+    fn get_config(&self) -> BoxFuture<'_, Result<PublicConfig>> {
+        SandkastenClient::get_config(self).boxed()
+    }
+
+    fn list_environments(&self) -> BoxFuture<'_, Result<HashMap<String, Environment>>> {
+        SandkastenClient::list_environments(self).boxed()
+    }
+
+    fn build_and_run<'a>(
+        &'a self,
+        data: &'a BuildRunRequest,
+    ) -> BoxFuture<'a, Result<BuildRunResult, BuildRunError>> {
+        SandkastenClient::build_and_run(self, data).boxed()
+    }
+}
+
+/// Forwards all requests to Sandkasten and stores the responses in the
+/// recordings directory.
+struct RecordingSandbox {
+    client: SandkastenClient,
+    recordings: PathBuf,
+}
+
+impl Sandbox for RecordingSandbox {
+    fn version(&self) -> BoxFuture<'_, Result<String>> {
+        async move {
+            let version = SandkastenClient::version(&self.client).await?;
+            record(
+                &self.recordings.join(VERSION_FILE),
+                json!({ "ok": version }),
+            )
+            .await;
+            Ok(version)
+        }
+        .boxed()
+    }
+
+    fn get_config(&self) -> BoxFuture<'_, Result<PublicConfig>> {
+        async move {
+            let config = SandkastenClient::get_config(&self.client).await?;
+            record(&self.recordings.join(CONFIG_FILE), json!({ "ok": config })).await;
+            Ok(config)
+        }
+        .boxed()
+    }
+
+    fn list_environments(&self) -> BoxFuture<'_, Result<HashMap<String, Environment>>> {
+        async move {
+            let environments = SandkastenClient::list_environments(&self.client).await?;
+            record(
+                &self.recordings.join(ENVIRONMENTS_FILE),
+                json!({ "ok": environments }),
+            )
+            .await;
+            Ok(environments)
+        }
+        .boxed()
+    }
+
+    fn build_and_run<'a>(
+        &'a self,
+        data: &'a BuildRunRequest,
+    ) -> BoxFuture<'a, Result<BuildRunResult, BuildRunError>> {
+        async move {
+            let result = SandkastenClient::build_and_run(&self.client, data).await;
+            let value = match &result {
+                Ok(result) => json!({ "ok": build_run_result_to_json(result) }),
+                Err(Error::ErrorResponse(err)) => json!({ "err": build_run_error_to_json(err) }),
+                // transport errors are not deterministic, so don't record them
+                Err(_) => return result,
+            };
+            record(&run_path(&self.recordings, data), value).await;
+            result
+        }
+        .boxed()
+    }
+}
+
+/// Responds to all requests with the responses previously stored by a
+/// [`RecordingSandbox`] without connecting to Sandkasten.
+struct ReplaySandbox {
+    recordings: PathBuf,
+}
+
+impl Sandbox for ReplaySandbox {
+    fn version(&self) -> BoxFuture<'_, Result<String>> {
+        async move {
+            match replay(self.recordings.join(VERSION_FILE)).await {
+                Err(Error::ErrorResponse(_)) => Ok(sandkasten_client::VERSION.into()),
+                result => result,
+            }
+        }
+        .boxed()
+    }
+
+    fn get_config(&self) -> BoxFuture<'_, Result<PublicConfig>> {
+        replay(self.recordings.join(CONFIG_FILE)).boxed()
+    }
+
+    fn list_environments(&self) -> BoxFuture<'_, Result<HashMap<String, Environment>>> {
+        replay(self.recordings.join(ENVIRONMENTS_FILE)).boxed()
+    }
+
+    fn build_and_run<'a>(
+        &'a self,
+        data: &'a BuildRunRequest,
+    ) -> BoxFuture<'a, Result<BuildRunResult, BuildRunError>> {
+        replay(run_path(&self.recordings, data)).boxed()
+    }
+}
+
+/// Forwards all requests to another sandbox and counts the cpu time used by
+/// the programs built and run in it.
+pub struct MeteredSandbox<'a> {
+    inner: &'a dyn Sandbox,
+    cpu_time: AtomicU64,
+    runs: AtomicU64,
+}
+
+impl<'a> MeteredSandbox<'a> {
+    pub fn new(inner: &'a dyn Sandbox) -> Self {
+        Self {
+            inner,
+            cpu_time: AtomicU64::new(0),
+            runs: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the cpu time (in milliseconds) used so far and the number of
+    /// programs that have been built or run.
+    pub fn usage(&self) -> (u64, u64) {
+        (
+            self.cpu_time.load(Ordering::Relaxed),
+            self.runs.load(Ordering::Relaxed),
+        )
+    }
+
+    fn add(&self, cpu_time: u64) {
+        self.cpu_time.fetch_add(cpu_time, Ordering::Relaxed);
+        self.runs.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Sandbox for MeteredSandbox<'_> {
+    fn version(&self) -> BoxFuture<'_, Result<String>> {
+        self.inner.version()
+    }
+
+    fn get_config(&self) -> BoxFuture<'_, Result<PublicConfig>> {
+        self.inner.get_config()
+    }
+
+    fn list_environments(&self) -> BoxFuture<'_, Result<HashMap<String, Environment>>> {
+        self.inner.list_environments()
+    }
+
+    fn build_and_run<'a>(
+        &'a self,
+        data: &'a BuildRunRequest,
+    ) -> BoxFuture<'a, Result<BuildRunResult, BuildRunError>> {
+        async move {
+            let result = self.inner.build_and_run(data).await;
+            match &result {
+                Ok(result) => self.add(
+                    result
+                        .build
+                        .as_ref()
+                        .filter(|_| !result.cached)
+                        .map_or(0, |build| build.resource_usage.time)
+                        + result.run.resource_usage.time,
+                ),
+                Err(Error::ErrorResponse(err)) => {
+                    if let ErrorResponse::Inner(BuildRunError::CompileError(build)) = &**err {
+                        self.add(build.resource_usage.time);
+                    }
+                }
+                Err(_) => {}
+            }
+            result
+        }
+        .boxed()
+    }
+}
+
+const VERSION_FILE: &str = "version.json";
+const CONFIG_FILE: &str = "config.json";
+const ENVIRONMENTS_FILE: &str = "environments.json";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Recording<T, E> {
+    Ok(T),
+    Err(ErrorResponse<E>),
+}
+
+async fn record(path: &Path, value: Value) {
+    if let Some(parent) = path.parent() {
+        if let Err(err) = tokio::fs::create_dir_all(parent).await {
+            warn!("Failed to create recordings directory {parent:?}: {err}");
+            return;
+        }
+    }
+    if let Err(err) = tokio::fs::write(path, value.to_string()).await {
+        warn!("Failed to record sandbox response to {path:?}: {err}");
+    }
+}
+
+async fn replay<T: DeserializeOwned, E: DeserializeOwned>(path: PathBuf) -> Result<T, E> {
+    let content = match tokio::fs::read(&path).await {
+        Ok(content) => content,
+        Err(err) => {
+            if err.kind() == ErrorKind::NotFound {
+                warn!("No recorded sandbox response found at {path:?}");
+            } else {
+                warn!("Failed to read recorded sandbox response {path:?}: {err}");
+            }
+            return Err(missing_recording());
+        }
+    };
+    match serde_json::from_slice(&content) {
+        Ok(Recording::Ok(value)) => Ok(value),
+        Ok(Recording::Err(err)) => Err(Error::ErrorResponse(Box::new(err))),
+        Err(err) => {
+            warn!("Failed to parse recorded sandbox response {path:?}: {err}");
+            Err(missing_recording())
+        }
+    }
+}
+
+fn missing_recording<E>() -> Error<E> {
+    Error::ErrorResponse(Box::new(ErrorResponse::GeneralError(
+        GeneralError::InternalServerError,
+    )))
+}
+
+fn run_path(recordings: &Path, data: &BuildRunRequest) -> PathBuf {
+    recordings
+        .join("runs")
+        .join(format!("{}.json", run_key(data)))
+}
+
+/// Return a stable key which identifies the given request.
+fn run_key(data: &BuildRunRequest) -> String {
+    let data = serde_json::to_vec(data).expect("build run requests can always be serialized");
+    Sha256::digest(data)
+        .iter()
+        .map(|x| format!("{x:02x}"))
+        .collect()
+}
+
+fn build_run_result_to_json(result: &BuildRunResult) -> Value {
+    json!({
+        "program_id": result.program_id,
+        "ttl": result.ttl,
+        "cached": result.cached,
+        "build": result.build,
+        "run": result.run,
+    })
+}
+
+fn build_run_error_to_json(err: &ErrorResponse<BuildRunError>) -> Value {
+    fn limits(limits: &[LimitExceeded]) -> Value {
+        limits
+            .iter()
+            .map(|x| json!({"name": x.name, "max_value": x.max_value}))
+            .collect()
+    }
+
+    match err {
+        ErrorResponse::GeneralError(GeneralError::UnprocessableContent(reason)) => {
+            json!({"error": "unprocessable_content", "reason": reason})
+        }
+        ErrorResponse::GeneralError(GeneralError::InternalServerError) => {
+            json!({"error": "internal_server_error"})
+        }
+        ErrorResponse::Inner(BuildRunError::EnvironmentNotFound) => {
+            json!({"error": "environment_not_found"})
+        }
+        ErrorResponse::Inner(BuildRunError::CompileError(result)) => {
+            json!({"error": "compile_error", "details": result})
+        }
+        ErrorResponse::Inner(BuildRunError::InvalidFileNames) => {
+            json!({"error": "invalid_file_names"})
+        }
+        ErrorResponse::Inner(BuildRunError::InvalidEnvVars) => {
+            json!({"error": "invalid_env_vars"})
+        }
+        ErrorResponse::Inner(BuildRunError::CompileLimitsExceeded(x)) => {
+            json!({"error": "compile_limits_exceeded", "details": limits(x)})
+        }
+        ErrorResponse::Inner(BuildRunError::RunLimitsExceeded(x)) => {
+            json!({"error": "run_limits_exceeded", "details": limits(x)})
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sandkasten_client::schemas::programs::{
+        BuildRequest, Limits, MainFile, ResourceUsage, RunRequest, RunResult,
+    };
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn request(stdin: &str) -> BuildRunRequest {
+        BuildRunRequest {
+            build: BuildRequest {
+                environment: "python".into(),
+                main_file: MainFile {
+                    name: None,
+                    content: "print(input())".into(),
+                },
+                ..Default::default()
+            },
+            run: RunRequest {
+                stdin: Some(stdin.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn run_result(status: i32) -> RunResult {
+        RunResult {
+            status,
+            stdout: "out".into(),
+            stderr: "err".into(),
+            resource_usage: ResourceUsage {
+                time: 12,
+                memory: 1024,
+            },
+            limits: Limits {
+                cpus: 1,
+                time: 5,
+                memory: 1024,
+                tmpfs: 64,
+                filesize: 16,
+                file_descriptors: 256,
+                processes: 64,
+                stdout_max_size: 65536,
+                stderr_max_size: 65536,
+                network: false,
+            },
+        }
+    }
+
+    #[test]
+    fn run_key_is_stable() {
+        let key = run_key(&request("foo"));
+        assert_eq!(key.len(), 64);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(key, run_key(&request("foo")));
+        assert_ne!(key, run_key(&request("bar")));
+    }
+
+    #[test]
+    fn recorded_result_roundtrip() {
+        let result = BuildRunResult {
+            program_id: Uuid::new_v4(),
+            ttl: 60,
+            cached: true,
+            build: None,
+            run: run_result(0),
+        };
+        let recorded = json!({ "ok": build_run_result_to_json(&result) });
+        let Recording::<BuildRunResult, BuildRunError>::Ok(replayed) =
+            serde_json::from_value(recorded).unwrap()
+        else {
+            panic!("expected result");
+        };
+        assert_eq!(replayed.program_id, result.program_id);
+        assert_eq!(replayed.run, result.run);
+        assert!(replayed.build.is_none());
+    }
+
+    #[test]
+    fn recorded_error_roundtrip() {
+        let err = ErrorResponse::Inner(BuildRunError::CompileError(run_result(1)));
+        let recorded = json!({ "err": build_run_error_to_json(&err) });
+        let Recording::<BuildRunResult, BuildRunError>::Err(ErrorResponse::Inner(
+            BuildRunError::CompileError(replayed),
+        )) = serde_json::from_value(recorded).unwrap()
+        else {
+            panic!("expected compile error");
+        };
+        assert_eq!(replayed, run_result(1));
+
+        let err = ErrorResponse::Inner(BuildRunError::EnvironmentNotFound);
+        let recorded = json!({ "err": build_run_error_to_json(&err) });
+        assert!(matches!(
+            serde_json::from_value(recorded).unwrap(),
+            Recording::<BuildRunResult, BuildRunError>::Err(ErrorResponse::Inner(
+                BuildRunError::EnvironmentNotFound
+            ))
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+//! Accounting of the cpu time coding challenges use in the sandbox.
+//!
+//! The cpu time of all programs the judge builds and runs for a challenge
+//! (evaluators, solutions and checkers) is summed up per month. If a challenge
+//! exceeds the configured monthly budget, no new examples are generated for it
+//! until the next month.
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+use entity::{challenges_sandbox_usage, challenges_subtasks};
+use schemas::challenges::admin::{ChallengeSandboxUsage, CreatorSandboxUsage, SandboxUsageStats};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, ConnectionTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use super::sandbox::MeteredSandbox;
+
+/// Return the first day of the month of `date`.
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+/// Return the number of seconds from `now` until the start of the next month.
+pub fn seconds_until_next_month(now: NaiveDateTime) -> u64 {
+    let next = month_start(now.date()) + Months::new(1);
+    (next.and_hms_opt(0, 0, 0).unwrap() - now)
+        .num_seconds()
+        .max(0) as _
+}
+
+/// Add the cpu time used in the given sandbox to the usage of a challenge.
+pub async fn record_sandbox_usage(
+    db: &impl ConnectionTrait,
+    subtask_id: Uuid,
+    sandbox: &MeteredSandbox<'_>,
+    now: NaiveDateTime,
+) -> Result<(), DbErr> {
+    let (cpu_time, runs) = sandbox.usage();
+    if runs == 0 {
+        return Ok(());
+    }
+
+    challenges_sandbox_usage::Entity::insert(challenges_sandbox_usage::ActiveModel {
+        subtask_id: Set(subtask_id),
+        month: Set(month_start(now.date())),
+        cpu_time: Set(cpu_time as _),
+        runs: Set(runs as _),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            challenges_sandbox_usage::Column::SubtaskId,
+            challenges_sandbox_usage::Column::Month,
+        ])
+        .value(
+            challenges_sandbox_usage::Column::CpuTime,
+            Expr::col((
+                challenges_sandbox_usage::Entity,
+                challenges_sandbox_usage::Column::CpuTime,
+            ))
+            .add(cpu_time as i64),
+        )
+        .value(
+            challenges_sandbox_usage::Column::Runs,
+            Expr::col((
+                challenges_sandbox_usage::Entity,
+                challenges_sandbox_usage::Column::Runs,
+            ))
+            .add(runs as i64),
+        )
+        .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Return the number of seconds until the monthly budget of a challenge is
+/// reset if the challenge has used up its budget (in seconds of cpu time).
+pub async fn budget_exhausted(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+    budget: Option<u64>,
+    now: NaiveDateTime,
+) -> Result<Option<u64>, DbErr> {
+    let Some(budget) = budget else {
+        return Ok(None);
+    };
+    let cpu_time =
+        challenges_sandbox_usage::Entity::find_by_id((subtask_id, month_start(now.date())))
+            .one(db)
+            .await?
+            .map_or(0, |x| x.cpu_time as u64);
+    Ok((cpu_time >= budget * 1000).then(|| seconds_until_next_month(now)))
+}
+
+/// Return the sandbox usage of all challenges and creators in a month.
+pub async fn get_sandbox_usage_stats(
+    db: &DatabaseTransaction,
+    month: NaiveDate,
+) -> Result<SandboxUsageStats, DbErr> {
+    let month = month_start(month);
+    let mut challenges = challenges_sandbox_usage::Entity::find()
+        .find_also_related(challenges_subtasks::Entity)
+        .filter(challenges_sandbox_usage::Column::Month.eq(month))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(usage, subtask)| {
+            let subtask = subtask?;
+            Some(ChallengeSandboxUsage {
+                subtask_id: subtask.id,
+                task_id: subtask.task_id,
+                creator: subtask.creator,
+                cpu_time: usage.cpu_time as _,
+                runs: usage.runs as _,
+            })
+        })
+        .collect::<Vec<_>>();
+    challenges.sort_by_key(|x| Reverse(x.cpu_time));
+
+    let mut creators = HashMap::<Uuid, CreatorSandboxUsage>::new();
+    for usage in &challenges {
+        let creator = creators
+            .entry(usage.creator)
+            .or_insert_with(|| CreatorSandboxUsage {
+                creator: usage.creator,
+                cpu_time: 0,
+                runs: 0,
+            });
+        creator.cpu_time += usage.cpu_time;
+        creator.runs += usage.runs;
+    }
+    let mut creators = creators.into_values().collect::<Vec<_>>();
+    creators.sort_by_key(|x| Reverse(x.cpu_time));
+
+    Ok(SandboxUsageStats {
+        month,
+        challenges,
+        creators,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_start() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(
+            month_start(date),
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()
+        );
+        assert_eq!(month_start(month_start(date)), month_start(date));
+    }
+
+    #[test]
+    fn test_seconds_until_next_month() {
+        let ts = |y, m, d, h| {
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap()
+        };
+        assert_eq!(seconds_until_next_month(ts(2024, 1, 31, 23)), 3600);
+        assert_eq!(seconds_until_next_month(ts(2023, 12, 31, 0)), 86400);
+        assert_eq!(seconds_until_next_month(ts(2024, 2, 1, 0)), 29 * 86400);
+    }
+}
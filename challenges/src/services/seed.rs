@@ -0,0 +1,489 @@
+//! Demo data for local development.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use entity::{
+    challenges_ban, challenges_challenge_categories, challenges_challenges,
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_coding_challenges, challenges_multiple_choice_attempts,
+    challenges_multiple_choice_quizes, challenges_question_attempts, challenges_questions,
+    challenges_subtasks, challenges_tasks, challenges_true_false_attempts,
+    challenges_true_false_statements, challenges_user_subtasks,
+    sea_orm_active_enums::{
+        ChallengesBanAction, ChallengesComparison, ChallengesFeedbackLevel, ChallengesLicense,
+        ChallengesQuestionAnswerType, ChallengesRating, ChallengesReviewStatus,
+        ChallengesSubtaskType, ChallengesVerdict,
+    },
+};
+use sea_orm::{ActiveModelTrait, DatabaseTransaction, DbErr, EntityTrait, PaginatorTrait, Set};
+use uuid::Uuid;
+
+use super::{
+    evaluators::record_evaluator_version, judge::EVALUATOR_TEMPLATE, revisions::record_revision,
+};
+
+/// The admin who creates the demo content.
+pub const SEED_ADMIN: Uuid = Uuid::from_u128(0x5eed_0000_0000_0000_0000_0000_0000_0001);
+/// A user who has solved some of the demo subtasks.
+pub const SEED_USER: Uuid = Uuid::from_u128(0x5eed_0000_0000_0000_0000_0000_0000_0002);
+/// A user who has been banned from creating subtasks.
+pub const SEED_BANNED_USER: Uuid = Uuid::from_u128(0x5eed_0000_0000_0000_0000_0000_0000_0003);
+
+const SUM_SOLUTION: &str = "n = int(input())\nprint(n * (n + 1) // 2)\n";
+const SUM_WRONG_SOLUTION: &str = "n = int(input())\nprint(sum(range(n)))\n";
+
+/// The number of rows created by [`seed`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeedSummary {
+    pub tasks: usize,
+    pub subtasks: usize,
+    pub attempts: usize,
+    pub bans: usize,
+}
+
+/// Whether the database already contains tasks.
+pub async fn is_seeded(db: &DatabaseTransaction) -> Result<bool, DbErr> {
+    Ok(challenges_tasks::Entity::find().count(db).await? > 0)
+}
+
+/// Populate the database with demo content: challenges with subtasks of
+/// different types (incl. a coding challenge with a working evaluator),
+/// attempts of [`SEED_USER`] and a ban of [`SEED_BANNED_USER`].
+pub async fn seed(db: &DatabaseTransaction) -> Result<SeedSummary, DbErr> {
+    let mut seeder = Seeder {
+        db,
+        now: Utc::now().naive_utc(),
+        summary: SeedSummary::default(),
+    };
+
+    let basics = seeder
+        .challenge(
+            "Programming Basics",
+            "Python Basics",
+            "Warm up with some questions about Python.",
+            &["python"],
+        )
+        .await?;
+    let question = seeder
+        .question(basics, "What is the output of `print(2 ** 3)`?", &["8"])
+        .await?;
+    let mcq = seeder
+        .multiple_choice(
+            basics,
+            "Which of the following are keywords in Python?",
+            &[
+                ("def", true),
+                ("func", false),
+                ("lambda", true),
+                ("var", false),
+            ],
+        )
+        .await?;
+    let statement = seeder
+        .true_false(basics, "Python lists are immutable.", false)
+        .await?;
+
+    let algorithms = seeder
+        .challenge(
+            "Algorithms",
+            "Sums and Series",
+            "Compute sums without iterating over all numbers.",
+            &["algorithms", "python"],
+        )
+        .await?;
+    let sum = seeder
+        .coding_challenge(
+            algorithms,
+            "Read a positive integer `n` from stdin and print the sum of all integers from `1` to `n`.",
+        )
+        .await?;
+    seeder
+        .question(
+            algorithms,
+            "What is the sum of all integers from 1 to 100?",
+            &["5050"],
+        )
+        .await?;
+
+    seeder.question_attempt(question, SEED_USER, true).await?;
+    seeder.mcq_attempt(mcq, SEED_USER, false).await?;
+    seeder.mcq_attempt(mcq, SEED_USER, true).await?;
+    seeder
+        .true_false_attempt(statement, SEED_USER, false)
+        .await?;
+    seeder
+        .submission(
+            sum,
+            SEED_USER,
+            SUM_WRONG_SOLUTION,
+            ChallengesVerdict::WrongAnswer,
+        )
+        .await?;
+    seeder
+        .submission(sum, SEED_USER, SUM_SOLUTION, ChallengesVerdict::Ok)
+        .await?;
+    seeder
+        .question_attempt(question, SEED_BANNED_USER, false)
+        .await?;
+
+    seeder
+        .ban(
+            SEED_BANNED_USER,
+            ChallengesBanAction::Create,
+            "Created spam subtasks.",
+        )
+        .await?;
+
+    Ok(seeder.summary)
+}
+
+struct Seeder<'a> {
+    db: &'a DatabaseTransaction,
+    now: NaiveDateTime,
+    summary: SeedSummary,
+}
+
+impl Seeder<'_> {
+    async fn challenge(
+        &mut self,
+        category: &str,
+        title: &str,
+        description: &str,
+        skill_ids: &[&str],
+    ) -> Result<Uuid, DbErr> {
+        let category = challenges_challenge_categories::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            title: Set(category.into()),
+            description: Set(format!("Challenges about {}.", category.to_lowercase())),
+            creation_timestamp: Set(self.now),
+        }
+        .insert(self.db)
+        .await?;
+        let task = challenges_tasks::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            creator: Set(SEED_ADMIN),
+            creation_timestamp: Set(self.now),
+            organization_id: Set(None),
+        }
+        .insert(self.db)
+        .await?;
+        challenges_challenges::ActiveModel {
+            task_id: Set(task.id),
+            category_id: Set(category.id),
+            skill_ids: Set(skill_ids.iter().map(|&x| x.into()).collect()),
+            title: Set(title.into()),
+            description: Set(description.into()),
+        }
+        .insert(self.db)
+        .await?;
+        self.summary.tasks += 1;
+        Ok(task.id)
+    }
+
+    async fn subtask(
+        &mut self,
+        task_id: Uuid,
+        ty: ChallengesSubtaskType,
+        xp: i64,
+        coins: i64,
+    ) -> Result<Uuid, DbErr> {
+        let subtask = challenges_subtasks::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            task_id: Set(task_id),
+            creator: Set(SEED_ADMIN),
+            creation_timestamp: Set(self.now),
+            xp: Set(xp),
+            coins: Set(coins),
+            enabled: Set(true),
+            ty: Set(ty),
+            retired: Set(false),
+            fingerprint: Set(None),
+            review_status: Set(ChallengesReviewStatus::Approved),
+            rejection_reason: Set(None),
+            license: Set(ChallengesLicense::AllRightsReserved),
+            attribution: Set(None),
+            max_attempts: Set(None),
+            reveal_solution: Set(false),
+            time_limit: Set(None),
+            unlock_price: Set(None),
+            locked: Set(false),
+        }
+        .insert(self.db)
+        .await?;
+        self.summary.subtasks += 1;
+        Ok(subtask.id)
+    }
+
+    async fn question(
+        &mut self,
+        task_id: Uuid,
+        question: &str,
+        answers: &[&str],
+    ) -> Result<Uuid, DbErr> {
+        let subtask_id = self
+            .subtask(task_id, ChallengesSubtaskType::Question, 10, 0)
+            .await?;
+        let question = challenges_questions::ActiveModel {
+            subtask_id: Set(subtask_id),
+            question: Set(question.into()),
+            answers: Set(answers.iter().map(|&x| x.into()).collect()),
+            case_sensitive: Set(false),
+            ascii_letters: Set(true),
+            digits: Set(true),
+            punctuation: Set(true),
+            blocks: Set(Vec::new()),
+            parameters: Set(Default::default()),
+            answer_type: Set(ChallengesQuestionAnswerType::Text),
+            unit: Set(None),
+            absolute_tolerance: Set(None),
+            relative_tolerance: Set(None),
+        }
+        .insert(self.db)
+        .await?;
+        record_revision(self.db, subtask_id, &question).await?;
+        Ok(subtask_id)
+    }
+
+    async fn multiple_choice(
+        &mut self,
+        task_id: Uuid,
+        question: &str,
+        answers: &[(&str, bool)],
+    ) -> Result<Uuid, DbErr> {
+        let subtask_id = self
+            .subtask(
+                task_id,
+                ChallengesSubtaskType::MultipleChoiceQuestion,
+                10,
+                0,
+            )
+            .await?;
+        let mcq = challenges_multiple_choice_quizes::ActiveModel {
+            subtask_id: Set(subtask_id),
+            question: Set(question.into()),
+            answers: Set(answers.iter().map(|&(x, _)| x.into()).collect()),
+            correct_answers: Set(answers
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &(_, correct))| acc | ((correct as i64) << i))),
+            single_choice: Set(false),
+            shuffle_answers: Set(false),
+        }
+        .insert(self.db)
+        .await?;
+        record_revision(self.db, subtask_id, &mcq).await?;
+        Ok(subtask_id)
+    }
+
+    async fn true_false(
+        &mut self,
+        task_id: Uuid,
+        statement: &str,
+        answer: bool,
+    ) -> Result<Uuid, DbErr> {
+        let subtask_id = self
+            .subtask(task_id, ChallengesSubtaskType::TrueFalse, 5, 0)
+            .await?;
+        let statement = challenges_true_false_statements::ActiveModel {
+            subtask_id: Set(subtask_id),
+            statement: Set(statement.into()),
+            answer: Set(answer),
+        }
+        .insert(self.db)
+        .await?;
+        record_revision(self.db, subtask_id, &statement).await?;
+        Ok(subtask_id)
+    }
+
+    async fn coding_challenge(&mut self, task_id: Uuid, description: &str) -> Result<Uuid, DbErr> {
+        let subtask_id = self
+            .subtask(task_id, ChallengesSubtaskType::CodingChallenge, 50, 5)
+            .await?;
+        let cc = challenges_coding_challenges::ActiveModel {
+            subtask_id: Set(subtask_id),
+            time_limit: Set(2000),
+            memory_limit: Set(256),
+            evaluator: Set(EVALUATOR_TEMPLATE.into()),
+            description: Set(description.into()),
+            solution_environment: Set("python".into()),
+            solution_code: Set(SUM_SOLUTION.into()),
+            static_tests: Set(10),
+            random_tests: Set(10),
+            output_only: Set(false),
+            evaluator_version: Set(1),
+            feedback_level: Set(ChallengesFeedbackLevel::FullDiff),
+            protocol_version: Set(1),
+            comparison: Set(ChallengesComparison::Evaluator),
+            epsilon: Set(None),
+            checker_environment: Set(None),
+            checker_code: Set(None),
+            golf: Set(false),
+            solution_public_after_solve: Set(false),
+        }
+        .insert(self.db)
+        .await?;
+        record_revision(self.db, subtask_id, &cc).await?;
+        record_evaluator_version(self.db, subtask_id, 1, &cc.evaluator, SEED_ADMIN).await?;
+        Ok(subtask_id)
+    }
+
+    async fn question_attempt(
+        &mut self,
+        subtask_id: Uuid,
+        user_id: Uuid,
+        solved: bool,
+    ) -> Result<(), DbErr> {
+        challenges_question_attempts::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            question_id: Set(subtask_id),
+            user_id: Set(user_id),
+            timestamp: Set(self.now),
+            solved: Set(solved),
+            revision_id: Set(None),
+        }
+        .insert(self.db)
+        .await?;
+        self.record_attempt(subtask_id, user_id, solved).await
+    }
+
+    async fn mcq_attempt(
+        &mut self,
+        subtask_id: Uuid,
+        user_id: Uuid,
+        solved: bool,
+    ) -> Result<(), DbErr> {
+        challenges_multiple_choice_attempts::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            question_id: Set(subtask_id),
+            user_id: Set(user_id),
+            timestamp: Set(self.now),
+            solved: Set(solved),
+            revision_id: Set(None),
+        }
+        .insert(self.db)
+        .await?;
+        self.record_attempt(subtask_id, user_id, solved).await
+    }
+
+    async fn true_false_attempt(
+        &mut self,
+        subtask_id: Uuid,
+        user_id: Uuid,
+        solved: bool,
+    ) -> Result<(), DbErr> {
+        challenges_true_false_attempts::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            statement_id: Set(subtask_id),
+            user_id: Set(user_id),
+            timestamp: Set(self.now),
+            solved: Set(solved),
+            revision_id: Set(None),
+        }
+        .insert(self.db)
+        .await?;
+        self.record_attempt(subtask_id, user_id, solved).await
+    }
+
+    async fn submission(
+        &mut self,
+        subtask_id: Uuid,
+        user_id: Uuid,
+        code: &str,
+        verdict: ChallengesVerdict,
+    ) -> Result<(), DbErr> {
+        let submission = challenges_coding_challenge_submissions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            subtask_id: Set(subtask_id),
+            creator: Set(user_id),
+            creation_timestamp: Set(self.now),
+            environment: Set("python".into()),
+            code: Set(code.into()),
+            attributed: Set(false),
+            revision_id: Set(None),
+            code_length: Set(code.len() as _),
+            rewarded: Set(false),
+        }
+        .insert(self.db)
+        .await?;
+        let solved = verdict == ChallengesVerdict::Ok;
+        challenges_coding_challenge_result::ActiveModel {
+            submission_id: Set(submission.id),
+            verdict: Set(verdict),
+            reason: Set((!solved).then(|| "too low".into())),
+            build_status: Set(None),
+            build_stderr: Set(None),
+            build_time: Set(None),
+            build_memory: Set(None),
+            run_status: Set(None),
+            run_stderr: Set(None),
+            run_time: Set(None),
+            run_memory: Set(None),
+            transcript: Set(None),
+            evaluator_version: Set(Some(1)),
+            seed: Set(None),
+            score: Set(None),
+            resources: Set(None),
+            personal_best_time: Set(false),
+            personal_best_memory: Set(false),
+        }
+        .insert(self.db)
+        .await?;
+        self.record_attempt(subtask_id, user_id, solved).await
+    }
+
+    /// Create or update the user subtask after an attempt.
+    async fn record_attempt(
+        &mut self,
+        subtask_id: Uuid,
+        user_id: Uuid,
+        solved: bool,
+    ) -> Result<(), DbErr> {
+        self.summary.attempts += 1;
+        let user_subtask = challenges_user_subtasks::Entity::find_by_id((user_id, subtask_id))
+            .one(self.db)
+            .await?;
+        let solved_timestamp = user_subtask
+            .as_ref()
+            .and_then(|x| x.solved_timestamp)
+            .or(solved.then_some(self.now));
+        let rating = user_subtask
+            .as_ref()
+            .and_then(|x| x.rating)
+            .or(solved.then_some(ChallengesRating::Positive));
+        let model = challenges_user_subtasks::ActiveModel {
+            user_id: Set(user_id),
+            subtask_id: Set(subtask_id),
+            solved_timestamp: Set(solved_timestamp),
+            rating: Set(rating),
+            rating_timestamp: Set(rating.map(|_| self.now)),
+            last_attempt_timestamp: Set(Some(self.now)),
+            attempts: Set(user_subtask.as_ref().map_or(0, |x| x.attempts) + 1),
+            revealed_timestamp: Set(None),
+        };
+        match user_subtask {
+            Some(_) => model.update(self.db).await?,
+            None => model.insert(self.db).await?,
+        };
+        Ok(())
+    }
+
+    async fn ban(
+        &mut self,
+        user_id: Uuid,
+        action: ChallengesBanAction,
+        reason: &str,
+    ) -> Result<(), DbErr> {
+        challenges_ban::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            start: Set(self.now),
+            end: Set(Some(self.now + Duration::days(30))),
+            action: Set(action),
+            creator: Set(SEED_ADMIN),
+            reason: Set(reason.into()),
+        }
+        .insert(self.db)
+        .await?;
+        self.summary.bans += 1;
+        Ok(())
+    }
+}
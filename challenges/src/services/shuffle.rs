@@ -0,0 +1,134 @@
+//! Reproducible shuffling of answers.
+//!
+//! The order in which answers are displayed is derived from a seed which only
+//! depends on the user, the subtask and the attempt. The seed of every attempt
+//! is stored, so admins can reconstruct what the user saw when auditing an
+//! attempt. The same seeds are used to generate the parameters of templated
+//! questions (see [`super::question_variants`]).
+
+use chrono::Utc;
+use entity::challenges_attempt_seeds::{self, AttemptParameters};
+use sea_orm::{sea_query::OnConflict, DatabaseTransaction, DbErr, EntityTrait, Set};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Return the seed for the given attempt (zero-based) of a user.
+pub fn attempt_seed(user_id: Uuid, subtask_id: Uuid, attempt: usize) -> i64 {
+    let mut hasher = Sha256::new();
+    hasher.update(user_id.as_bytes());
+    hasher.update(subtask_id.as_bytes());
+    hasher.update((attempt as u64).to_be_bytes());
+    i64::from_be_bytes(hasher.finalize()[..8].try_into().unwrap())
+}
+
+/// Return a permutation of `0..n` derived from the seed. The item at position
+/// `i` of the displayed list is the item at position `permutation[i]` of the
+/// original list.
+pub fn permutation(seed: i64, n: usize) -> Vec<usize> {
+    let mut state = seed as u64;
+    let mut out = (0..n).collect::<Vec<_>>();
+    for i in (1..n).rev() {
+        let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+        out.swap(i, j);
+    }
+    out
+}
+
+/// Reorder the items as they are displayed to the user.
+pub fn shuffle<T>(items: Vec<T>, permutation: &[usize]) -> Vec<T> {
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    permutation
+        .iter()
+        .map(|&i| items[i].take().unwrap())
+        .collect()
+}
+
+/// Restore the original order of items submitted in the displayed order.
+pub fn unshuffle<T: Clone>(items: &[T], permutation: &[usize]) -> Vec<T> {
+    let mut out = items.to_vec();
+    for (i, &j) in permutation.iter().enumerate() {
+        out[j] = items[i].clone();
+    }
+    out
+}
+
+/// Store the seed of an attempt (and the parameters derived from it) unless
+/// it has already been stored.
+pub async fn record_seed(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+    attempt: usize,
+    seed: i64,
+    parameters: Option<AttemptParameters>,
+) -> Result<(), DbErr> {
+    challenges_attempt_seeds::Entity::insert(challenges_attempt_seeds::ActiveModel {
+        user_id: Set(user_id),
+        subtask_id: Set(subtask_id),
+        attempt: Set(attempt as _),
+        seed: Set(seed),
+        timestamp: Set(Utc::now().naive_utc()),
+        parameters: Set(parameters),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            challenges_attempt_seeds::Column::UserId,
+            challenges_attempt_seeds::Column::SubtaskId,
+            challenges_attempt_seeds::Column::Attempt,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+/// Advance the state of the SplitMix64 generator and return the next number.
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attempt_seed() {
+        let user_id = Uuid::new_v4();
+        let subtask_id = Uuid::new_v4();
+        let seed = attempt_seed(user_id, subtask_id, 0);
+        assert_eq!(seed, attempt_seed(user_id, subtask_id, 0));
+        assert_ne!(seed, attempt_seed(user_id, subtask_id, 1));
+        assert_ne!(seed, attempt_seed(subtask_id, user_id, 0));
+    }
+
+    #[test]
+    fn test_permutation() {
+        for seed in [0, 1, -1, 42, i64::MAX] {
+            let mut perm = permutation(seed, 10);
+            assert_eq!(perm, permutation(seed, 10));
+            perm.sort_unstable();
+            assert_eq!(perm, (0..10).collect::<Vec<_>>());
+        }
+        assert!(permutation(7, 0).is_empty());
+        assert_eq!(permutation(7, 1), [0]);
+        assert_ne!(permutation(1, 10), permutation(2, 10));
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let perm = [2, 0, 3, 1];
+        let shuffled = shuffle(vec!["a", "b", "c", "d"], &perm);
+        assert_eq!(shuffled, ["c", "a", "d", "b"]);
+        assert_eq!(unshuffle(&shuffled, &perm), ["a", "b", "c", "d"]);
+        assert_eq!(
+            unshuffle(&[true, false, false, true], &perm),
+            [false, true, true, false]
+        );
+    }
+}
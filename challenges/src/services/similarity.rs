@@ -0,0 +1,179 @@
+//! Detection of duplicate subtasks.
+//!
+//! The content of a subtask is reduced to a normalized fingerprint, which is
+//! stored with the subtask. Two subtasks are considered duplicates if the
+//! character trigrams of their fingerprints are highly similar.
+
+use std::collections::HashSet;
+
+use entity::{challenges_subtasks, sea_orm_active_enums::ChallengesSubtaskType};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+    QuerySelect, Set, Unchanged,
+};
+use uuid::Uuid;
+
+/// Minimum similarity of two fingerprints to consider them duplicates.
+pub const DUPLICATE_THRESHOLD: f64 = 0.9;
+
+/// Compute the fingerprint of a subtask from its text and its answers. The
+/// order of the answers is ignored.
+pub fn fingerprint<'a>(text: &str, answers: impl IntoIterator<Item = &'a str>) -> String {
+    let mut answers = answers.into_iter().map(normalize).collect::<Vec<_>>();
+    answers.sort_unstable();
+    let mut out = normalize(text);
+    for answer in answers {
+        out.push('\n');
+        out.push_str(&answer);
+    }
+    out
+}
+
+/// Compute the fingerprint of a matching from its pairs of matching entries.
+pub fn matching_fingerprint(left: &[String], right: &[String], solution: &[i16]) -> String {
+    let pairs = left
+        .iter()
+        .zip(solution)
+        .filter_map(|(l, &r)| Some(format!("{l} {}", right.get(r as usize)?)))
+        .collect::<Vec<_>>();
+    fingerprint("", pairs.iter().map(String::as_str))
+}
+
+/// Compute the fingerprint of an ordering from its question and its items in
+/// the correct order.
+pub fn ordering_fingerprint(question: &str, items: &[String], solution: &[i16]) -> String {
+    let ordered = solution
+        .iter()
+        .filter_map(|&i| items.get(i as usize))
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    fingerprint(question, [ordered.as_str()])
+}
+
+/// Return the jaccard similarity of the character trigrams of two
+/// fingerprints (1 for identical and 0 for completely different
+/// fingerprints).
+pub fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let a = trigrams(a);
+    let b = trigrams(b);
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Return the ids of all subtasks of the same type in a task that are highly
+/// similar to the given fingerprint.
+pub async fn find_duplicates(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+    ty: ChallengesSubtaskType,
+    fingerprint: &str,
+) -> Result<Vec<Uuid>, DbErr> {
+    Ok(challenges_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_subtasks::Column::Id)
+        .column(challenges_subtasks::Column::Fingerprint)
+        .filter(challenges_subtasks::Column::TaskId.eq(task_id))
+        .filter(challenges_subtasks::Column::Ty.eq(ty))
+        .filter(challenges_subtasks::Column::Fingerprint.is_not_null())
+        .into_tuple::<(Uuid, String)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|(_, other)| similarity(fingerprint, other) >= DUPLICATE_THRESHOLD)
+        .map(|(id, _)| id)
+        .collect())
+}
+
+pub async fn set_fingerprint(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+    fingerprint: String,
+) -> Result<(), DbErr> {
+    challenges_subtasks::ActiveModel {
+        id: Unchanged(subtask_id),
+        fingerprint: Set(Some(fingerprint)),
+        ..Default::default()
+    }
+    .update(db)
+    .await?;
+    Ok(())
+}
+
+/// Convert to lowercase, drop punctuation and collapse whitespace.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let chars = text.chars().collect::<Vec<_>>();
+    chars.windows(3).map(|x| [x[0], x[1], x[2]]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint("  What is 2+2?\n", ["Four", "4"]),
+            "what is 2 2\n4\nfour"
+        );
+        assert_eq!(
+            fingerprint("What is 2 + 2", ["4", "four"]),
+            fingerprint("what is 2+2?", ["FOUR!", "4"])
+        );
+    }
+
+    #[test]
+    fn test_matching_fingerprint() {
+        let strings = |x: &[&str]| x.iter().map(|&x| x.into()).collect::<Vec<String>>();
+        assert_eq!(
+            matching_fingerprint(&strings(&["A", "B"]), &strings(&["X", "Y"]), &[1, 0]),
+            "\na y\nb x"
+        );
+        assert_eq!(
+            matching_fingerprint(&strings(&["A", "B"]), &strings(&["X", "Y"]), &[1, 0]),
+            matching_fingerprint(&strings(&["B", "A"]), &strings(&["Y", "X"]), &[1, 0])
+        );
+    }
+
+    #[test]
+    fn test_ordering_fingerprint() {
+        let strings = |x: &[&str]| x.iter().map(|&x| x.into()).collect::<Vec<String>>();
+        assert_eq!(
+            ordering_fingerprint("Sort!", &strings(&["B", "C", "A"]), &[2, 0, 1]),
+            "sort\na b c"
+        );
+        assert_eq!(
+            ordering_fingerprint("Sort", &strings(&["B", "C", "A"]), &[2, 0, 1]),
+            ordering_fingerprint("sort", &strings(&["A", "B", "C"]), &[0, 1, 2])
+        );
+        assert_ne!(
+            ordering_fingerprint("Sort", &strings(&["A", "B", "C"]), &[0, 1, 2]),
+            ordering_fingerprint("Sort", &strings(&["A", "B", "C"]), &[2, 1, 0])
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        let a = fingerprint("What is the capital of France?", ["Paris"]);
+        let b = fingerprint("What's the capital of France?", ["Paris"]);
+        let c = fingerprint("What is the largest planet?", ["Jupiter"]);
+        assert_eq!(similarity(&a, &a), 1.0);
+        assert!(similarity(&a, &b) >= 0.8);
+        assert!(similarity(&a, &c) < DUPLICATE_THRESHOLD);
+        assert_eq!(similarity("ab", "cd"), 0.0);
+    }
+}
@@ -0,0 +1,170 @@
+//! Signed tokens for subtasks with a time limit.
+//!
+//! When a user requests a subtask with a time limit, the response includes a
+//! token which expires after the time limit. The token has to be submitted
+//! together with the answer, so the time limit does not depend on the clock
+//! of the client.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use entity::challenges_subtasks;
+use lib::jwt::{sign_jwt, verify_jwt, JwtError, JwtSecret};
+use schemas::challenges::subtasks::{SolveToken, Subtask};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const AUDIENCE: &str = "solve";
+
+/// Number of seconds an answer is accepted after the time limit to account for
+/// network latency.
+const GRACE_PERIOD: u64 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct SolveTokenClaims {
+    aud: String,
+    uid: Uuid,
+    sid: Uuid,
+}
+
+/// Issue a token for the user to solve the subtask. Returns `None` if the
+/// subtask has no time limit.
+pub fn issue_solve_token(
+    secret: &JwtSecret,
+    user_id: Uuid,
+    subtask: &Subtask,
+) -> Result<Option<SolveToken>, JwtError> {
+    let Some(time_limit) = subtask.time_limit else {
+        return Ok(None);
+    };
+    let token = sign_jwt(
+        SolveTokenClaims {
+            aud: AUDIENCE.into(),
+            uid: user_id,
+            sid: subtask.id,
+        },
+        secret,
+        Duration::from_secs(time_limit as u64 + GRACE_PERIOD),
+    )?;
+    Ok(Some(SolveToken {
+        token,
+        expires: Utc::now() + chrono::Duration::seconds(time_limit as _),
+    }))
+}
+
+/// Check the token submitted by the user to solve the subtask.
+pub fn check_solve_token(
+    secret: &JwtSecret,
+    token: Option<&str>,
+    user_id: Uuid,
+    subtask: &challenges_subtasks::Model,
+) -> Result<(), SolveTokenError> {
+    if subtask.time_limit.is_none() {
+        return Ok(());
+    }
+    let token = token.ok_or(SolveTokenError::Invalid)?;
+    match verify_jwt::<SolveTokenClaims>(token, secret) {
+        Ok(claims)
+            if claims.aud == AUDIENCE && claims.uid == user_id && claims.sid == subtask.id =>
+        {
+            Ok(())
+        }
+        Err(JwtError::Expired(_)) => Err(SolveTokenError::Expired),
+        _ => Err(SolveTokenError::Invalid),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveTokenError {
+    /// The token is missing, invalid or belongs to another user or subtask.
+    Invalid,
+    /// The time limit has been exceeded.
+    Expired,
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::sea_orm_active_enums::{
+        ChallengesLicense, ChallengesReviewStatus, ChallengesSubtaskType,
+    };
+
+    use super::*;
+
+    fn subtask(time_limit: Option<i32>) -> challenges_subtasks::Model {
+        challenges_subtasks::Model {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            creator: Uuid::new_v4(),
+            creation_timestamp: Utc::now().naive_utc(),
+            xp: 10,
+            coins: 0,
+            enabled: true,
+            ty: ChallengesSubtaskType::Matching,
+            retired: false,
+            fingerprint: None,
+            review_status: ChallengesReviewStatus::Approved,
+            rejection_reason: None,
+            license: ChallengesLicense::AllRightsReserved,
+            attribution: None,
+            max_attempts: None,
+            reveal_solution: false,
+            time_limit,
+            unlock_price: None,
+            locked: false,
+        }
+    }
+
+    #[test]
+    fn test_solve_token() {
+        let secret = JwtSecret::try_from("secret").unwrap();
+        let user_id = Uuid::new_v4();
+        let model = subtask(Some(30));
+        let token = issue_solve_token(
+            &secret,
+            user_id,
+            &Subtask::from(model.clone(), false, false),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(token.expires > Utc::now());
+
+        assert_eq!(
+            check_solve_token(&secret, Some(&token.token), user_id, &model),
+            Ok(())
+        );
+        assert_eq!(
+            check_solve_token(&secret, None, user_id, &model),
+            Err(SolveTokenError::Invalid)
+        );
+        assert_eq!(
+            check_solve_token(&secret, Some(&token.token), Uuid::new_v4(), &model),
+            Err(SolveTokenError::Invalid)
+        );
+        assert_eq!(
+            check_solve_token(&secret, Some(&token.token), user_id, &subtask(Some(30))),
+            Err(SolveTokenError::Invalid)
+        );
+        let other_secret = JwtSecret::try_from("other").unwrap();
+        assert_eq!(
+            check_solve_token(&other_secret, Some(&token.token), user_id, &model),
+            Err(SolveTokenError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_no_time_limit() {
+        let secret = JwtSecret::try_from("secret").unwrap();
+        let model = subtask(None);
+        assert!(issue_solve_token(
+            &secret,
+            Uuid::new_v4(),
+            &Subtask::from(model.clone(), false, false)
+        )
+        .unwrap()
+        .is_none());
+        assert_eq!(
+            check_solve_token(&secret, None, Uuid::new_v4(), &model),
+            Ok(())
+        );
+    }
+}
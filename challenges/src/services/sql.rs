@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use entity::challenges_sql_challenges::SqlResult;
+use sandkasten_client::schemas::programs::{BuildRequest, BuildRunRequest, MainFile, RunRequest};
+use schemas::challenges::sql_challenges::SqlDiff;
+use serde::{Deserialize, Serialize};
+
+use super::{judge::Error, sandbox::Sandbox};
+
+pub const SQL_RUNNER: &str = include_str!("../../assets/sql/runner.py");
+
+/// The maximum number of missing and unexpected rows included in a diff.
+pub const MAX_DIFF_ROWS: usize = 10;
+
+type Row = Vec<Option<String>>;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryResult {
+    /// The query has been executed successfully.
+    Ok(SqlResult),
+    /// The schema or the seed data could not be loaded.
+    SetupError(String),
+    /// The query could not be executed.
+    Error(String),
+}
+
+#[derive(Debug, Serialize)]
+struct RunnerInput<'a> {
+    schema: &'a str,
+    seed: &'a str,
+    query: &'a str,
+    max_rows: usize,
+}
+
+/// Execute a query in a new in-memory database which is initialized with the
+/// given schema and seed data.
+pub async fn run_query(
+    sandkasten: &dyn Sandbox,
+    schema: &str,
+    seed: &str,
+    query: &str,
+    max_rows: usize,
+) -> Result<QueryResult, Error> {
+    let out = sandkasten
+        .build_and_run(&BuildRunRequest {
+            build: BuildRequest {
+                environment: "python".into(),
+                main_file: MainFile {
+                    content: SQL_RUNNER.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            run: RunRequest {
+                stdin: Some(serde_json::to_string(&RunnerInput {
+                    schema,
+                    seed,
+                    query,
+                    max_rows,
+                })?),
+                ..Default::default()
+            },
+        })
+        .await?;
+    if out.run.status != 0 {
+        return Err(Error::EvaluatorFailed(out));
+    }
+    serde_json::from_str(&out.run.stdout).map_err(|_| Error::InvalidOutput(out))
+}
+
+/// Compare the result of a query with the expected result. Rows are compared
+/// as multisets unless `ordered` is set. Column names are ignored, as they
+/// depend on the aliases chosen by the user.
+///
+/// Return `None` if both results match.
+pub fn compare_results(expected: &SqlResult, actual: &SqlResult, ordered: bool) -> Option<SqlDiff> {
+    if expected.columns.len() != actual.columns.len() {
+        return Some(SqlDiff {
+            wrong_column_count: true,
+            ..Default::default()
+        });
+    }
+
+    let missing = difference(&expected.rows, &actual.rows);
+    let unexpected = difference(&actual.rows, &expected.rows);
+    if missing.is_empty() && unexpected.is_empty() {
+        return (ordered && expected.rows != actual.rows).then(|| SqlDiff {
+            wrong_order: true,
+            ..Default::default()
+        });
+    }
+
+    Some(SqlDiff {
+        missing: missing.len(),
+        missing_rows: missing.into_iter().take(MAX_DIFF_ROWS).cloned().collect(),
+        unexpected: unexpected.len(),
+        unexpected_rows: unexpected
+            .into_iter()
+            .take(MAX_DIFF_ROWS)
+            .cloned()
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// Return all rows of `a` that are not in `b`, respecting duplicates.
+fn difference<'a>(a: &'a [Row], b: &[Row]) -> Vec<&'a Row> {
+    let mut counts = HashMap::<&Row, usize>::new();
+    for row in b {
+        *counts.entry(row).or_default() += 1;
+    }
+    a.iter()
+        .filter(|row| match counts.get_mut(row) {
+            Some(cnt) if *cnt > 0 => {
+                *cnt -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(columns: usize, rows: &[&[Option<&str>]]) -> SqlResult {
+        SqlResult {
+            columns: (0..columns).map(|i| format!("c{i}")).collect(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|x| x.map(Into::into)).collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let expected = result(2, &[&[Some("1"), Some("a")], &[Some("2"), None]]);
+
+        let reversed = result(2, &[&[Some("2"), None], &[Some("1"), Some("a")]]);
+        assert!(compare_results(&expected, &expected, true).is_none());
+        assert!(compare_results(&expected, &reversed, false).is_none());
+        let diff = compare_results(&expected, &reversed, true).unwrap();
+        assert!(diff.wrong_order);
+        assert_eq!(diff.missing + diff.unexpected, 0);
+
+        let diff = compare_results(&expected, &result(1, &[&[Some("1")]]), false).unwrap();
+        assert!(diff.wrong_column_count);
+
+        let actual = result(
+            2,
+            &[
+                &[Some("1"), Some("a")],
+                &[Some("1"), Some("a")],
+                &[Some("2"), Some("")],
+            ],
+        );
+        let diff = compare_results(&expected, &actual, false).unwrap();
+        assert!(!diff.wrong_column_count && !diff.wrong_order);
+        assert_eq!(diff.missing, 1);
+        assert_eq!(diff.missing_rows, [[Some("2".to_owned()), None]]);
+        assert_eq!(diff.unexpected, 2);
+        assert_eq!(
+            diff.unexpected_rows,
+            [
+                [Some("1".to_owned()), Some("a".to_owned())],
+                [Some("2".to_owned()), Some(String::new())]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_results_truncated() {
+        let expected = result(1, &[]);
+        let actual = SqlResult {
+            columns: vec!["x".into()],
+            rows: (0..25).map(|i| vec![Some(i.to_string())]).collect(),
+        };
+        let diff = compare_results(&expected, &actual, false).unwrap();
+        assert_eq!(diff.unexpected, 25);
+        assert_eq!(diff.unexpected_rows.len(), MAX_DIFF_ROWS);
+        assert_eq!(diff.missing, 0);
+    }
+}
@@ -1,32 +1,52 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Context;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use entity::{
-    challenges_ban, challenges_subtasks, challenges_tasks, challenges_user_subtasks,
-    sea_orm_active_enums::{ChallengesBanAction, ChallengesSubtaskType},
+    challenges_ban, challenges_subtask_access, challenges_subtask_prerequisites,
+    challenges_subtasks, challenges_tasks, challenges_user_notes, challenges_user_subtasks,
+    sea_orm_active_enums::{
+        ChallengesBanAction, ChallengesLicense, ChallengesReviewStatus, ChallengesSubtaskType,
+    },
 };
 use lib::{
     auth::User,
-    config::Config,
+    config::{
+        challenges::{DueDates, ReSolve, Rewards},
+        Config,
+    },
+    events::{publish_event, Event},
     services::{
         shop::AddCoinsError, skills::AddSkillProgressError, ServiceError, ServiceResult, Services,
     },
 };
-use poem_ext::responses::ErrorResponse;
+use poem_ext::{patch_value::PatchValue, responses::ErrorResponse};
 use schemas::challenges::subtasks::{
     CreateSubtaskRequest, Subtask, SubtaskStats, UpdateSubtaskRequest,
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseTransaction, DbErr, EntityTrait, ModelTrait,
-    QueryFilter, QueryOrder, Related, Set, Unchanged,
+    sea_query::Query, ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseTransaction,
+    DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder, Related, Set, Unchanged,
 };
 use thiserror::Error;
 use uuid::Uuid;
 
 use super::{
+    anti_cheat::has_reward_hold,
+    cohorts::{get_due_date, get_due_dates, reward_percent},
+    contests::announce_score_event,
     course_tasks::get_skills_of_course,
+    held_rewards::{get_contest_hold, hold_rewards},
+    licenses::{requires_attribution, PERMISSIVE_LICENSES},
+    notifications::notify_subtask_disabled,
+    organizations::{can_create_in_organization, can_see_task, task_id_scope},
+    pools::get_hidden_subtasks,
+    quotas::{check_quota, Quota},
+    rewards::{add_coins, add_xp},
+    task_completions::claim_completion_bonus,
+    task_config::get_task_limits,
     tasks::{get_specific_task, get_task, get_task_with_specific, Task},
+    time_tracking::{time_to_solve, time_to_solve_stats},
 };
 
 pub async fn check_hearts(
@@ -80,20 +100,82 @@ fn subtask_hearts(config: &Config, ty: ChallengesSubtaskType) -> u32 {
         ChallengesSubtaskType::Matching => config.matchings.hearts,
         ChallengesSubtaskType::MultipleChoiceQuestion => config.multiple_choice_questions.hearts,
         ChallengesSubtaskType::Question => config.questions.hearts,
+        ChallengesSubtaskType::TrueFalse => config.true_false.hearts,
+        ChallengesSubtaskType::Ordering => config.orderings.hearts,
+        ChallengesSubtaskType::Cloze => config.clozes.hearts,
+        ChallengesSubtaskType::CodeReading => config.code_readings.hearts,
+        ChallengesSubtaskType::SqlChallenge => config.sql_challenges.hearts,
     }
 }
 
+/// Send the rewards for solving a subtask to the user. If the parent task has
+/// been assigned to the user in a cohort and the subtask has been solved after
+/// the due date, only the configured percentage of the rewards is sent. The
+/// rewards of users with an open anti-cheat flag and rewards for subtasks of
+/// running contests which hold rewards are stored until an admin releases
+/// them. Users who have revealed the solution of the subtask get no rewards.
+/// If the subtask completes its parent task, the completion bonus of the task
+/// is added to the rewards.
+#[allow(clippy::too_many_arguments)]
 pub async fn send_task_rewards(
     services: &Services,
     db: &DatabaseTransaction,
+    due_dates: &DueDates,
+    rewards: &Rewards,
     user_id: Uuid,
     subtask: &challenges_subtasks::Model,
+    kind: SolveKind,
+    solved_at: NaiveDateTime,
 ) -> Result<(), SendTaskRewardsError> {
-    if subtask.retired {
+    if subtask.retired
+        || get_user_subtask(db, user_id, subtask.id)
+            .await?
+            .is_revealed()
+    {
         return Ok(());
     }
 
-    if subtask.xp != 0 {
+    let due = get_due_date(db, user_id, subtask.task_id).await?;
+    let percent = reward_percent(due_dates, due, solved_at) as i64;
+
+    let mut xp = kind.xp(subtask.xp) * percent / 100;
+    let mut coins = match kind {
+        SolveKind::First => subtask.coins * percent / 100,
+        SolveKind::ReSolve { .. } => 0,
+    };
+    if kind == SolveKind::First {
+        if let Some((bonus_xp, bonus_coins)) =
+            claim_completion_bonus(db, user_id, subtask.task_id, solved_at).await?
+        {
+            xp += bonus_xp;
+            coins += bonus_coins;
+        }
+    }
+    if xp != 0 || coins != 0 {
+        if has_reward_hold(db, user_id).await? {
+            hold_rewards(db, user_id, subtask, xp, coins, None).await?;
+            return Ok(());
+        }
+        if let Some(contest) = get_contest_hold(db, subtask.task_id, solved_at).await? {
+            hold_rewards(db, user_id, subtask, xp, coins, Some(contest.id)).await?;
+            return Ok(());
+        }
+    }
+    grant_rewards(services, db, rewards, user_id, subtask, xp, coins).await
+}
+
+/// Add xp to the skills of the parent task and coins to the balance of the
+/// user.
+pub async fn grant_rewards(
+    services: &Services,
+    db: &DatabaseTransaction,
+    config: &Rewards,
+    user_id: Uuid,
+    subtask: &challenges_subtasks::Model,
+    xp: i64,
+    coins: i64,
+) -> Result<(), SendTaskRewardsError> {
+    if xp != 0 {
         let skills = get_skills(
             services,
             get_parent_task(db, subtask)
@@ -103,17 +185,27 @@ pub async fn send_task_rewards(
         )
         .await?;
         for skill in &skills {
-            services
-                .skills
-                .add_skill_progress(user_id, skill, subtask.xp / skills.len() as i64)
-                .await??;
+            add_xp(
+                services,
+                db,
+                config,
+                user_id,
+                skill,
+                xp / skills.len() as i64,
+            )
+            .await?;
         }
     }
-    if subtask.coins != 0 {
-        services
-            .shop
-            .add_coins(user_id, subtask.coins, "Challenges / Aufgaben", true)
-            .await??;
+    if coins != 0 {
+        add_coins(
+            services,
+            db,
+            config,
+            user_id,
+            coins,
+            "Challenges / Aufgaben",
+        )
+        .await?;
     }
     Ok(())
 }
@@ -148,19 +240,170 @@ pub async fn update_user_subtask(
     user_subtask: Option<&challenges_user_subtasks::Model>,
     values: challenges_user_subtasks::ActiveModel,
 ) -> Result<challenges_user_subtasks::Model, DbErr> {
-    if let Some(user_subtask) = user_subtask {
+    let solved = matches!(values.solved_timestamp, ActiveValue::Set(Some(_)));
+    let user_subtask = if let Some(user_subtask) = user_subtask {
         challenges_user_subtasks::ActiveModel {
             user_id: Unchanged(user_subtask.user_id),
             subtask_id: Unchanged(user_subtask.subtask_id),
             ..values
         }
         .update(db)
-        .await
+        .await?
     } else {
         challenges_user_subtasks::ActiveModel { ..values }
             .insert(db)
-            .await
+            .await?
+    };
+    // solves are the only changes that affect contest scoreboards
+    if solved {
+        if let Some(subtask) = challenges_subtasks::Entity::find_by_id(user_subtask.subtask_id)
+            .one(db)
+            .await?
+        {
+            announce_score_event(db, subtask.task_id, user_subtask.user_id).await?;
+        }
+    }
+    Ok(user_subtask)
+}
+
+/// Check whether a user is allowed to see a subtask. See [`SubtaskAccess`]
+/// for the rules.
+pub async fn can_access_subtask(
+    db: &DatabaseTransaction,
+    user: &User,
+    subtask: &challenges_subtasks::Model,
+) -> Result<bool, DbErr> {
+    if user.admin {
+        return Ok(true);
+    }
+    Ok(check_subtask_access(db, user, subtask).await?.allowed())
+}
+
+/// The factors that decide whether a user can access a subtask.
+#[derive(Debug, Clone)]
+pub struct SubtaskAccess {
+    pub admin: bool,
+    pub creator: bool,
+    /// Whether the task does not belong to an organization or the user is
+    /// acting in its organization.
+    pub task_visible: bool,
+    pub enabled: bool,
+    pub locked: bool,
+    /// The time the user has been granted access to the subtask.
+    pub access_granted: Option<NaiveDateTime>,
+    /// Whether the subtask is not part of the user's pool.
+    pub hidden_by_pool: bool,
+    /// The prerequisites of the subtask the user has not solved yet.
+    pub missing_prerequisites: Vec<Uuid>,
+}
+
+impl SubtaskAccess {
+    /// Admins can access all subtasks and creators their own subtasks (if
+    /// they can see the task). Other users can only access enabled subtasks
+    /// that are not locked (or those they have been granted access to) that
+    /// are part of their pool and whose prerequisites they have solved.
+    pub fn allowed(&self) -> bool {
+        self.admin
+            || (self.task_visible
+                && (self.creator
+                    || (((self.enabled && !self.locked) || self.access_granted.is_some())
+                        && !self.hidden_by_pool
+                        && self.missing_prerequisites.is_empty())))
+    }
+}
+
+/// Collect the factors that decide whether a user can access a subtask.
+pub async fn check_subtask_access(
+    db: &DatabaseTransaction,
+    user: &User,
+    subtask: &challenges_subtasks::Model,
+) -> Result<SubtaskAccess, DbErr> {
+    let task_visible = get_task(db, subtask.task_id)
+        .await?
+        .is_some_and(|task| can_see_task(user, &task));
+    let access_granted = challenges_subtask_access::Entity::find_by_id((user.id, subtask.id))
+        .one(db)
+        .await?
+        .map(|x| x.timestamp);
+    let hidden_by_pool = get_hidden_subtasks(db, user, Some(vec![subtask.task_id]))
+        .await?
+        .contains(&subtask.id);
+
+    let prerequisites = challenges_subtask_prerequisites::Entity::find()
+        .filter(challenges_subtask_prerequisites::Column::SubtaskId.eq(subtask.id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| x.prerequisite_id)
+        .collect::<Vec<_>>();
+    let solved = if prerequisites.is_empty() {
+        HashSet::new()
+    } else {
+        challenges_user_subtasks::Entity::find()
+            .filter(challenges_user_subtasks::Column::UserId.eq(user.id))
+            .filter(challenges_user_subtasks::Column::SubtaskId.is_in(prerequisites.clone()))
+            .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| x.subtask_id)
+            .collect()
+    };
+
+    Ok(SubtaskAccess {
+        admin: user.admin,
+        creator: user.id == subtask.creator,
+        task_visible,
+        enabled: subtask.enabled,
+        locked: subtask.locked,
+        access_granted,
+        hidden_by_pool,
+        missing_prerequisites: prerequisites
+            .into_iter()
+            .filter(|id| !solved.contains(id))
+            .collect(),
+    })
+}
+
+/// Allow a user to access a subtask even if it is disabled. Returns `false`
+/// if access had already been granted before.
+pub async fn grant_subtask_access(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+) -> Result<bool, DbErr> {
+    if challenges_subtask_access::Entity::find_by_id((user_id, subtask_id))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Ok(false);
+    }
+    challenges_subtask_access::ActiveModel {
+        user_id: Set(user_id),
+        subtask_id: Set(subtask_id),
+        timestamp: Set(Utc::now().naive_utc()),
     }
+    .insert(db)
+    .await?;
+    Ok(true)
+}
+
+pub async fn publish_ban_event(
+    db: &DatabaseTransaction,
+    ban: &challenges_ban::Model,
+) -> Result<(), DbErr> {
+    publish_event(
+        db,
+        Event::UserBanned {
+            ban_id: ban.id,
+            user_id: ban.user_id,
+            action: ban.action,
+            start: ban.start.and_utc(),
+            end: ban.end.map(|ts| ts.and_utc()),
+        },
+    )
+    .await
 }
 
 pub async fn get_active_ban(
@@ -198,10 +441,19 @@ pub enum ActiveBan {
 pub async fn can_create(
     services: &Services,
     config: &Config,
-    task: &Task,
+    task: &challenges_tasks::Model,
+    specific: &Task,
     user: &User,
 ) -> Result<bool, CheckPermissionsError> {
-    Ok(match task {
+    if user.admin {
+        return Ok(true);
+    }
+    // subtasks of organization tasks are created by the creators of the
+    // organization, regardless of their skill levels
+    if task.organization_id.is_some() {
+        return Ok(can_see_task(user, task) && can_create_in_organization(user));
+    }
+    Ok(match specific {
         Task::Challenge(_) => user.admin,
         Task::CourseTask(t) => can_create_for_course(services, config, &t.course_id, user).await?,
     })
@@ -249,11 +501,41 @@ pub async fn get_skills(services: &Services, task: Task) -> ServiceResult<Vec<St
     })
 }
 
+/// How solving a subtask is rewarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveKind {
+    /// The subtask is solved for the first time.
+    First,
+    /// The subtask is solved again after the configured re-solve period. Only
+    /// the given percentage of the xp is awarded and no coins.
+    ReSolve { xp_percent: u8 },
+}
+
+impl SolveKind {
+    pub fn xp(self, xp: i64) -> i64 {
+        match self {
+            Self::First => xp,
+            Self::ReSolve { xp_percent } => xp * xp_percent as i64 / 100,
+        }
+    }
+}
+
 pub trait UserSubtaskExt {
     fn is_solved(&self) -> bool;
+    fn solved_at(&self) -> Option<DateTime<Utc>>;
     fn is_rated(&self) -> bool;
     fn last_attempt(&self) -> Option<DateTime<Utc>>;
     fn attempts(&self) -> usize;
+    fn is_revealed(&self) -> bool;
+
+    /// Whether the user has used all attempts of the subtask without solving
+    /// it.
+    fn attempts_exhausted(&self, subtask: &challenges_subtasks::Model) -> bool {
+        !self.is_solved()
+            && subtask
+                .max_attempts
+                .is_some_and(|max| self.attempts() >= max as usize)
+    }
 
     fn can_rate(&self, user: &User, subtask: &challenges_subtasks::Model) -> bool {
         user.id != subtask.creator && self.is_solved() && !self.is_rated()
@@ -262,6 +544,19 @@ pub trait UserSubtaskExt {
     fn attempted(&self) -> bool {
         self.last_attempt().is_some()
     }
+
+    /// Return how solving the subtask now would be rewarded or `None` if it
+    /// has already been solved and cannot be re-solved yet.
+    fn solve_kind(&self, config: &ReSolve, now: DateTime<Utc>) -> Option<SolveKind> {
+        let Some(solved_at) = self.solved_at() else {
+            return Some(SolveKind::First);
+        };
+        (config.enabled && now - solved_at >= Duration::days(config.period as _)).then_some(
+            SolveKind::ReSolve {
+                xp_percent: config.xp_percent,
+            },
+        )
+    }
 }
 
 impl UserSubtaskExt for challenges_user_subtasks::Model {
@@ -269,6 +564,10 @@ impl UserSubtaskExt for challenges_user_subtasks::Model {
         self.solved_timestamp.is_some()
     }
 
+    fn solved_at(&self) -> Option<DateTime<Utc>> {
+        self.solved_timestamp.map(|x| x.and_utc())
+    }
+
     fn is_rated(&self) -> bool {
         self.rating_timestamp.is_some()
     }
@@ -280,12 +579,19 @@ impl UserSubtaskExt for challenges_user_subtasks::Model {
     fn attempts(&self) -> usize {
         self.attempts as _
     }
+
+    fn is_revealed(&self) -> bool {
+        self.revealed_timestamp.is_some()
+    }
 }
 
 impl<T: UserSubtaskExt> UserSubtaskExt for &T {
     fn is_solved(&self) -> bool {
         T::is_solved(self)
     }
+    fn solved_at(&self) -> Option<DateTime<Utc>> {
+        T::solved_at(self)
+    }
     fn is_rated(&self) -> bool {
         T::is_rated(self)
     }
@@ -295,12 +601,18 @@ impl<T: UserSubtaskExt> UserSubtaskExt for &T {
     fn attempts(&self) -> usize {
         T::attempts(self)
     }
+    fn is_revealed(&self) -> bool {
+        T::is_revealed(self)
+    }
 }
 
 impl<T: UserSubtaskExt> UserSubtaskExt for Option<T> {
     fn is_solved(&self) -> bool {
         self.as_ref().is_some_and(|x| x.is_solved())
     }
+    fn solved_at(&self) -> Option<DateTime<Utc>> {
+        self.as_ref().and_then(|x| x.solved_at())
+    }
     fn is_rated(&self) -> bool {
         self.as_ref().is_some_and(|x| x.is_rated())
     }
@@ -310,6 +622,9 @@ impl<T: UserSubtaskExt> UserSubtaskExt for Option<T> {
     fn attempts(&self) -> usize {
         self.as_ref().map(|x| x.attempts()).unwrap_or(0)
     }
+    fn is_revealed(&self) -> bool {
+        self.as_ref().is_some_and(|x| x.is_revealed())
+    }
 }
 
 #[derive(Debug, Error)]
@@ -343,6 +658,8 @@ pub struct QuerySubtasksFilter {
     pub retired: Option<bool>,
     pub creator: Option<Uuid>,
     pub ty: Option<ChallengesSubtaskType>,
+    /// Whether to search for subtasks under one of the [`PERMISSIVE_LICENSES`].
+    pub permissive: Option<bool>,
 }
 
 pub async fn query_subtasks_only(
@@ -352,6 +669,8 @@ pub async fn query_subtasks_only(
     filter: QuerySubtasksFilter,
 ) -> Result<Vec<Subtask>, DbErr> {
     let user_subtasks = get_user_subtasks(db, user.id).await?;
+    let due_dates = get_due_dates(db, user.id).await?;
+    let hidden = get_hidden_subtasks(db, user, task_id.map(|x| vec![x])).await?;
     let mut query = challenges_subtasks::Entity::find();
     if let Some(task_id) = task_id {
         query = query.filter(challenges_subtasks::Column::TaskId.eq(task_id));
@@ -360,7 +679,8 @@ pub async fn query_subtasks_only(
         .all(db)
         .await?
         .into_iter()
-        .filter_map(|subtask| subtasks_filter_map(subtask, &filter, &user_subtasks))
+        .filter(|subtask| !hidden.contains(&subtask.id))
+        .filter_map(|subtask| subtasks_filter_map(subtask, &filter, &user_subtasks, &due_dates))
         .collect())
 }
 
@@ -370,21 +690,29 @@ pub async fn stat_subtasks_prepare(
     task_ids: Option<Vec<Uuid>>,
     filter: &QuerySubtasksFilter,
 ) -> Result<Vec<challenges_subtasks::Model>, DbErr> {
+    let hidden = get_hidden_subtasks(db, user, task_ids.clone()).await?;
     let mut query = challenges_subtasks::Entity::find();
     if let Some(task_ids) = task_ids {
         query = query.filter(challenges_subtasks::Column::TaskId.is_in(task_ids));
     }
-    prepare_query(query, filter, user).all(db).await
+    Ok(prepare_query(query, filter, user)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|subtask| !hidden.contains(&subtask.id))
+        .collect())
 }
 
 pub fn stat_subtasks(
     subtasks: &[challenges_subtasks::Model],
     user_subtasks: &HashMap<Uuid, challenges_user_subtasks::Model>,
+    sessions: &HashMap<Uuid, NaiveDateTime>,
     filter: QuerySubtasksFilter,
 ) -> SubtaskStats {
     let mut total = 0;
     let mut solved = 0;
     let mut attempted = 0;
+    let mut durations = Vec::new();
 
     for subtask in subtasks {
         let user_subtask = user_subtasks.get(&subtask.id);
@@ -395,6 +723,12 @@ pub fn stat_subtasks(
         total += 1;
         solved += user_subtask.is_solved() as u64;
         attempted += (!user_subtask.is_solved() && user_subtask.attempted()) as u64;
+        if let Some(duration) = sessions
+            .get(&subtask.id)
+            .and_then(|&start| time_to_solve(start, user_subtask))
+        {
+            durations.push(duration);
+        }
     }
 
     let unattempted = total - solved - attempted;
@@ -404,6 +738,7 @@ pub fn stat_subtasks(
         solved,
         attempted,
         unattempted,
+        time_to_solve: time_to_solve_stats(durations),
     }
 }
 
@@ -418,6 +753,8 @@ where
     E: EntityTrait + Related<challenges_subtasks::Entity>,
 {
     let user_subtasks = get_user_subtasks(db, user.id).await?;
+    let due_dates = get_due_dates(db, user.id).await?;
+    let hidden = get_hidden_subtasks(db, user, Some(vec![task_id])).await?;
     Ok(prepare_query(
         E::find()
             .find_also_related(challenges_subtasks::Entity)
@@ -429,7 +766,8 @@ where
     .await?
     .into_iter()
     .filter_map(|(specific, subtask)| {
-        let subtask = subtasks_filter_map(subtask?, &filter, &user_subtasks)?;
+        let subtask = subtask.filter(|x| !hidden.contains(&x.id))?;
+        let subtask = subtasks_filter_map(subtask, &filter, &user_subtasks, &due_dates)?;
         Some(map(specific, subtask))
     })
     .collect())
@@ -439,11 +777,25 @@ fn prepare_query<Q>(mut query: Q, filter: &QuerySubtasksFilter, user: &User) ->
 where
     Q: QueryFilter + QueryOrder,
 {
+    query = query.filter(task_id_scope(challenges_subtasks::Column::TaskId, user));
     if !user.admin {
         query = query.filter(
             Condition::any()
                 .add(challenges_subtasks::Column::Creator.eq(user.id))
-                .add(challenges_subtasks::Column::Enabled.eq(true)),
+                .add(
+                    Condition::all()
+                        .add(challenges_subtasks::Column::Enabled.eq(true))
+                        .add(challenges_subtasks::Column::Locked.eq(false)),
+                )
+                .add(
+                    challenges_subtasks::Column::Id.in_subquery(
+                        Query::select()
+                            .column(challenges_subtask_access::Column::SubtaskId)
+                            .from(challenges_subtask_access::Entity)
+                            .and_where(challenges_subtask_access::Column::UserId.eq(user.id))
+                            .to_owned(),
+                    ),
+                ),
         );
     }
     if let Some(enabled) = filter.enabled {
@@ -458,6 +810,14 @@ where
     if let Some(ty) = filter.ty {
         query = query.filter(challenges_subtasks::Column::Ty.eq(ty));
     }
+    if let Some(permissive) = filter.permissive {
+        let licenses = PERMISSIVE_LICENSES.iter().copied();
+        query = query.filter(if permissive {
+            challenges_subtasks::Column::License.is_in(licenses)
+        } else {
+            challenges_subtasks::Column::License.is_not_in(licenses)
+        });
+    }
     query.order_by_asc(challenges_subtasks::Column::CreationTimestamp)
 }
 
@@ -479,6 +839,7 @@ fn subtasks_filter_map(
     subtask: challenges_subtasks::Model,
     filter: &QuerySubtasksFilter,
     user_subtasks: &HashMap<Uuid, challenges_user_subtasks::Model>,
+    due_dates: &HashMap<Uuid, NaiveDateTime>,
 ) -> Option<Subtask> {
     let user_subtask = user_subtasks.get(&subtask.id);
     let attempted = user_subtask.attempted();
@@ -487,7 +848,10 @@ fn subtasks_filter_map(
     (filter.attempted.unwrap_or(attempted) == attempted
         && filter.solved.unwrap_or(solved) == solved
         && filter.rated.unwrap_or(rated) == rated)
-        .then_some(Subtask::from(subtask, solved, rated))
+        .then(|| Subtask {
+            due: due_dates.get(&subtask.task_id).map(|x| x.and_utc()),
+            ..Subtask::from(subtask, solved, rated)
+        })
 }
 
 pub async fn query_subtask<E, T>(
@@ -496,6 +860,7 @@ pub async fn query_subtask<E, T>(
     task_id: Uuid,
     subtask_id: Uuid,
     map: impl Fn(E::Model, Subtask) -> T,
+    include_note: bool,
 ) -> Result<Option<T>, DbErr>
 where
     E: EntityTrait + Related<challenges_subtasks::Entity>,
@@ -504,15 +869,29 @@ where
     let Some((specific, subtask)) = get_subtask::<E>(db, task_id, subtask_id).await? else {
         return Ok(None);
     };
-    if !user.admin && user.id != subtask.creator && !subtask.enabled {
+    if !can_access_subtask(db, user, &subtask).await? {
         return Ok(None);
     }
 
     let user_subtask = get_user_subtask(db, user.id, subtask.id).await?;
+    let note = if include_note {
+        challenges_user_notes::Entity::find_by_id((user.id, subtask.id))
+            .one(db)
+            .await?
+            .map(|note| note.note)
+    } else {
+        None
+    };
+
+    let due = get_due_date(db, user.id, subtask.task_id).await?;
 
     Ok(Some(map(
         specific,
-        Subtask::from(subtask, user_subtask.is_solved(), user_subtask.is_rated()),
+        Subtask {
+            note,
+            due: due.map(|x| x.and_utc()),
+            ..Subtask::from(subtask, user_subtask.is_solved(), user_subtask.is_rated())
+        },
     )))
 }
 
@@ -531,11 +910,15 @@ where
         return Ok(Err(QuerySubtaskAdminError::NotFound));
     };
 
-    if !(user.admin || user.id == subtask.creator) {
+    let user_subtask = get_user_subtask(db, user.id, subtask.id).await?;
+    if !(user.admin
+        || user.id == subtask.creator
+        || user_subtask.is_revealed()
+        || (subtask.reveal_solution && user_subtask.attempts_exhausted(&subtask)))
+    {
         return Ok(Err(QuerySubtaskAdminError::NoAccess));
     }
 
-    let user_subtask = get_user_subtask(db, user.id, subtask.id).await?;
     Ok(Ok(map(
         specific,
         Subtask::from(subtask, user_subtask.is_solved(), user_subtask.is_rated()),
@@ -582,30 +965,44 @@ pub async fn create_subtask(
         Some(task) => task,
         None => return Ok(Err(CreateSubtaskError::TaskNotFound)),
     };
-    if !can_create(services, config, &specific, user).await? {
+    if !can_create(services, config, &task, &specific, user).await? {
         return Ok(Err(CreateSubtaskError::Forbidden));
     }
 
-    let xp = data.xp.unwrap_or(config.challenges.quizzes.max_xp);
-    let coins = data.coins.unwrap_or(config.challenges.quizzes.max_coins);
+    let limits = get_task_limits(db, &config.challenges.quizzes, task.id).await?;
+    let xp = data.xp.unwrap_or(limits.max_xp);
+    let coins = data.coins.unwrap_or(limits.max_coins);
     if matches!(specific, Task::CourseTask(_)) && !user.admin {
-        if xp > config.challenges.quizzes.max_xp {
-            return Ok(Err(CreateSubtaskError::XpLimitExceeded(
-                config.challenges.quizzes.max_xp,
-            )));
+        if xp > limits.max_xp {
+            return Ok(Err(CreateSubtaskError::XpLimitExceeded(limits.max_xp)));
         }
-        if coins > config.challenges.quizzes.max_coins {
-            return Ok(Err(CreateSubtaskError::CoinLimitExceeded(
-                config.challenges.quizzes.max_coins,
-            )));
+        if coins > limits.max_coins {
+            return Ok(Err(CreateSubtaskError::CoinLimitExceeded(limits.max_coins)));
         }
     }
 
+    let license = data.license.unwrap_or(ChallengesLicense::AllRightsReserved);
+    if data.attribution.is_none() && requires_attribution(license) {
+        return Ok(Err(CreateSubtaskError::AttributionRequired));
+    }
+
     match get_active_ban(db, user, ChallengesBanAction::Create).await? {
         ActiveBan::NotBanned => {}
         ActiveBan::Temporary(end) => return Ok(Err(CreateSubtaskError::Banned(Some(end)))),
         ActiveBan::Permanent => return Ok(Err(CreateSubtaskError::Banned(None))),
     }
+    if let Quota::Exceeded(reset) = check_quota(db, &config.challenges.quotas, user).await? {
+        return Ok(Err(CreateSubtaskError::QuotaExceeded(reset)));
+    }
+    let shadow_banned = !matches!(
+        get_active_ban(db, user, ChallengesBanAction::ShadowCreate).await?,
+        ActiveBan::NotBanned
+    );
+    let review_status = if shadow_banned || (config.challenges.moderation.enabled && !user.admin) {
+        ChallengesReviewStatus::Pending
+    } else {
+        ChallengesReviewStatus::Approved
+    };
 
     let subtask = challenges_subtasks::ActiveModel {
         id: Set(Uuid::new_v4()),
@@ -615,11 +1012,31 @@ pub async fn create_subtask(
         creation_timestamp: Set(Utc::now().naive_utc()),
         xp: Set(xp as _),
         coins: Set(coins as _),
-        enabled: Set(true),
+        enabled: Set(review_status == ChallengesReviewStatus::Approved),
         retired: Set(false),
+        fingerprint: Set(None),
+        review_status: Set(review_status),
+        rejection_reason: Set(None),
+        license: Set(license),
+        attribution: Set(data.attribution),
+        max_attempts: Set(data.max_attempts.map(|x| x as _)),
+        reveal_solution: Set(data.reveal_solution),
+        time_limit: Set(data.time_limit.map(|x| x as _)),
+        unlock_price: Set(data.unlock_price.map(|x| x as _)),
+        locked: Set(data.locked),
     }
     .insert(db)
     .await?;
+    publish_event(
+        db,
+        Event::SubtaskCreated {
+            subtask_id: subtask.id,
+            task_id: subtask.task_id,
+            creator: subtask.creator,
+            ty: subtask.ty,
+        },
+    )
+    .await?;
 
     Ok(Ok(Subtask::from(subtask, false, false)))
 }
@@ -628,8 +1045,10 @@ pub enum CreateSubtaskError {
     TaskNotFound,
     Forbidden,
     Banned(Option<DateTime<Utc>>),
+    QuotaExceeded(Option<DateTime<Utc>>),
     XpLimitExceeded(u64),
     CoinLimitExceeded(u64),
+    AttributionRequired,
 }
 
 pub async fn update_subtask<E>(
@@ -654,6 +1073,14 @@ where
         return Ok(Err(UpdateSubtaskError::TaskNotFound));
     };
 
+    if data.attribution.get_new(&subtask.attribution).is_none()
+        && requires_attribution(*data.license.get_new(&subtask.license))
+    {
+        return Ok(Err(UpdateSubtaskError::AttributionRequired));
+    }
+
+    let was_enabled = subtask.enabled;
+    let approve = user.admin && matches!(data.enabled, PatchValue::Set(true));
     let subtask = challenges_subtasks::ActiveModel {
         id: Unchanged(subtask.id),
         task_id: data.task_id.update(subtask.task_id),
@@ -662,11 +1089,45 @@ where
         creation_timestamp: Unchanged(subtask.creation_timestamp),
         xp: data.xp.map(|x| x as _).update(subtask.xp),
         coins: data.coins.map(|x| x as _).update(subtask.coins),
-        enabled: data.enabled.update(subtask.enabled),
+        enabled: if subtask.review_status != ChallengesReviewStatus::Approved && !user.admin {
+            Unchanged(subtask.enabled)
+        } else {
+            data.enabled.update(subtask.enabled)
+        },
         retired: data.retired.update(subtask.retired),
+        fingerprint: Unchanged(subtask.fingerprint),
+        review_status: if approve {
+            Set(ChallengesReviewStatus::Approved)
+        } else {
+            Unchanged(subtask.review_status)
+        },
+        rejection_reason: if approve {
+            Set(None)
+        } else {
+            Unchanged(subtask.rejection_reason)
+        },
+        license: data.license.update(subtask.license),
+        attribution: data.attribution.update(subtask.attribution),
+        max_attempts: data
+            .max_attempts
+            .map(|x| x.map(|x| x as _))
+            .update(subtask.max_attempts),
+        reveal_solution: data.reveal_solution.update(subtask.reveal_solution),
+        time_limit: data
+            .time_limit
+            .map(|x| x.map(|x| x as _))
+            .update(subtask.time_limit),
+        unlock_price: data
+            .unlock_price
+            .map(|x| x.map(|x| x as _))
+            .update(subtask.unlock_price),
+        locked: data.locked.update(subtask.locked),
     }
     .update(db)
     .await?;
+    if was_enabled && !subtask.enabled {
+        notify_subtask_disabled(db, &subtask, Some(user.id)).await?;
+    }
 
     let user_subtask = get_user_subtask(db, user.id, subtask.id).await?;
     Ok(Ok((
@@ -678,4 +1139,70 @@ where
 pub enum UpdateSubtaskError {
     SubtaskNotFound,
     TaskNotFound,
+    AttributionRequired,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn user_subtask(solved_at: Option<DateTime<Utc>>) -> challenges_user_subtasks::Model {
+        challenges_user_subtasks::Model {
+            user_id: Uuid::nil(),
+            subtask_id: Uuid::nil(),
+            solved_timestamp: solved_at.map(|x| x.naive_utc()),
+            rating_timestamp: None,
+            last_attempt_timestamp: solved_at.map(|x| x.naive_utc()),
+            attempts: 1,
+            revealed_timestamp: None,
+            rating: None,
+        }
+    }
+
+    #[test]
+    fn solve_kind() {
+        let config = ReSolve {
+            enabled: true,
+            period: 30,
+            xp_percent: 25,
+        };
+        let now = Utc.with_ymd_and_hms(2023, 11, 30, 12, 0, 0).unwrap();
+        let re_solve = Some(SolveKind::ReSolve { xp_percent: 25 });
+
+        assert_eq!(
+            None::<challenges_user_subtasks::Model>.solve_kind(&config, now),
+            Some(SolveKind::First)
+        );
+        assert_eq!(
+            user_subtask(None).solve_kind(&config, now),
+            Some(SolveKind::First)
+        );
+        assert_eq!(
+            user_subtask(Some(now - Duration::days(29))).solve_kind(&config, now),
+            None
+        );
+        assert_eq!(
+            user_subtask(Some(now - Duration::days(30))).solve_kind(&config, now),
+            re_solve
+        );
+        assert_eq!(
+            user_subtask(Some(now - Duration::days(30))).solve_kind(
+                &ReSolve {
+                    enabled: false,
+                    ..config
+                },
+                now
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn solve_kind_xp() {
+        assert_eq!(SolveKind::First.xp(10), 10);
+        assert_eq!(SolveKind::ReSolve { xp_percent: 25 }.xp(10), 2);
+        assert_eq!(SolveKind::ReSolve { xp_percent: 100 }.xp(10), 10);
+    }
 }
@@ -0,0 +1,104 @@
+//! Bonus rewards for solving all subtasks of a task.
+//!
+//! Completions are recorded in `challenges_task_completions`, whose primary key
+//! ensures that the bonus of a task is granted at most once per user, even if
+//! the last subtasks are solved concurrently.
+
+use chrono::NaiveDateTime;
+use entity::{challenges_subtasks, challenges_task_completions, challenges_user_subtasks};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QuerySelect, Set,
+};
+use uuid::Uuid;
+
+use super::task_config::get_task_config;
+
+/// Return whether a task is completed if the user has solved `solved` of its
+/// `total` subtasks. Tasks with a pool are completed once `pool_size` subtasks
+/// have been solved.
+pub fn is_completed(total: u64, solved: u64, pool_size: Option<i32>) -> bool {
+    let required = pool_size.map_or(total, |x| (x as u64).min(total));
+    total != 0 && solved >= required
+}
+
+/// Record the completion of a task if the user has just solved all of its
+/// subtasks and the task has a completion bonus. Returns the xp and coins of
+/// the bonus, unless the user has already received it before.
+///
+/// Subtasks created by the user and subtasks whose solution the user has
+/// revealed are not taken into account.
+pub async fn claim_completion_bonus(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    task_id: Uuid,
+    now: NaiveDateTime,
+) -> Result<Option<(i64, i64)>, DbErr> {
+    let Some(config) = get_task_config(db, task_id).await? else {
+        return Ok(None);
+    };
+    let xp = config.completion_xp.unwrap_or(0);
+    let coins = config.completion_coins.unwrap_or(0);
+    if xp == 0 && coins == 0 {
+        return Ok(None);
+    }
+
+    let subtasks = challenges_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_subtasks::Column::Id)
+        .filter(challenges_subtasks::Column::TaskId.eq(task_id))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(false))
+        .filter(challenges_subtasks::Column::Retired.eq(false))
+        .filter(challenges_subtasks::Column::Creator.ne(user_id))
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await?;
+    let solved = challenges_user_subtasks::Entity::find()
+        .filter(challenges_user_subtasks::Column::UserId.eq(user_id))
+        .filter(challenges_user_subtasks::Column::SubtaskId.is_in(subtasks.iter().copied()))
+        .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+        .filter(challenges_user_subtasks::Column::RevealedTimestamp.is_null())
+        .count(db)
+        .await?;
+    if !is_completed(subtasks.len() as _, solved, config.pool_size) {
+        return Ok(None);
+    }
+
+    let inserted =
+        challenges_task_completions::Entity::insert(challenges_task_completions::ActiveModel {
+            user_id: Set(user_id),
+            task_id: Set(task_id),
+            timestamp: Set(now),
+            xp: Set(xp),
+            coins: Set(coins),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                challenges_task_completions::Column::UserId,
+                challenges_task_completions::Column::TaskId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+    Ok((inserted != 0).then_some((xp, coins)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_completed() {
+        assert!(is_completed(3, 3, None));
+        assert!(!is_completed(3, 2, None));
+        assert!(!is_completed(0, 0, None));
+        assert!(is_completed(5, 2, Some(2)));
+        assert!(!is_completed(5, 1, Some(2)));
+        assert!(is_completed(2, 2, Some(4)));
+        assert!(!is_completed(2, 1, Some(4)));
+    }
+}
@@ -0,0 +1,105 @@
+use entity::challenges_task_config;
+use lib::config::challenges::Quizzes;
+use sea_orm::{DatabaseTransaction, DbErr, EntityTrait};
+use uuid::Uuid;
+
+/// Limits for subtasks created by normal users in a specific task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskLimits {
+    pub max_xp: u64,
+    pub max_coins: u64,
+}
+
+impl TaskLimits {
+    /// Resolve the limits of a task. Task specific overrides take precedence
+    /// over the global config.
+    pub fn resolve(config: &Quizzes, overrides: Option<&challenges_task_config::Model>) -> Self {
+        Self {
+            max_xp: overrides
+                .and_then(|x| x.max_xp)
+                .map_or(config.max_xp, |x| x as _),
+            max_coins: overrides
+                .and_then(|x| x.max_coins)
+                .map_or(config.max_coins, |x| x as _),
+        }
+    }
+}
+
+pub async fn get_task_config(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+) -> Result<Option<challenges_task_config::Model>, DbErr> {
+    challenges_task_config::Entity::find_by_id(task_id)
+        .one(db)
+        .await
+}
+
+pub async fn get_task_limits(
+    db: &DatabaseTransaction,
+    config: &Quizzes,
+    task_id: Uuid,
+) -> Result<TaskLimits, DbErr> {
+    Ok(TaskLimits::resolve(
+        config,
+        get_task_config(db, task_id).await?.as_ref(),
+    ))
+}
+
+/// Return the number of seconds a user has to wait between two attempts to
+/// solve a subtask of the given task. `default` is the timeout from the global
+/// config for the type of the subtask.
+pub async fn get_timeout(
+    db: &DatabaseTransaction,
+    task_id: Uuid,
+    default: u64,
+) -> Result<u64, DbErr> {
+    Ok(get_task_config(db, task_id)
+        .await?
+        .and_then(|x| x.timeout_incr)
+        .map_or(default, |x| x as _))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_limits() {
+        let config = Quizzes {
+            min_level: 5,
+            max_xp: 5,
+            max_coins: 0,
+            ban_days: vec![],
+        };
+        let overrides = |max_xp, max_coins| challenges_task_config::Model {
+            task_id: Uuid::nil(),
+            max_xp,
+            max_coins,
+            timeout_incr: None,
+            pool_size: None,
+            completion_xp: None,
+            completion_coins: None,
+        };
+        assert_eq!(
+            TaskLimits::resolve(&config, None),
+            TaskLimits {
+                max_xp: 5,
+                max_coins: 0
+            }
+        );
+        assert_eq!(
+            TaskLimits::resolve(&config, Some(&overrides(Some(20), None))),
+            TaskLimits {
+                max_xp: 20,
+                max_coins: 0
+            }
+        );
+        assert_eq!(
+            TaskLimits::resolve(&config, Some(&overrides(None, Some(3)))),
+            TaskLimits {
+                max_xp: 5,
+                max_coins: 3
+            }
+        );
+    }
+}
@@ -1,7 +1,19 @@
-use entity::{challenges_challenges, challenges_course_tasks, challenges_tasks};
-use sea_orm::{DatabaseTransaction, DbErr, EntityTrait, ModelTrait};
+use std::collections::{HashMap, HashSet};
+
+use entity::{
+    challenges_challenges, challenges_course_tasks, challenges_subtasks, challenges_tasks,
+    challenges_user_subtasks,
+};
+use lib::auth::User;
+use schemas::challenges::tasks::TaskProgress;
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, DatabaseTransaction, DbErr, EntityTrait, JoinType,
+    ModelTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
 use uuid::Uuid;
 
+use super::organizations::task_scope;
+
 pub async fn get_task(
     db: &DatabaseTransaction,
     task_id: Uuid,
@@ -45,6 +57,78 @@ pub async fn get_specific_task(
     Ok(None)
 }
 
+/// Find all tasks visible to the user that train a skill, i.e. challenges with
+/// this skill and course tasks in one of the courses of the skill.
+pub async fn get_skill_tasks(
+    db: &DatabaseTransaction,
+    user: &User,
+    skill_id: &str,
+    courses: Vec<String>,
+) -> Result<Vec<challenges_tasks::Model>, DbErr> {
+    challenges_tasks::Entity::find()
+        .join(
+            JoinType::LeftJoin,
+            challenges_tasks::Relation::ChallengesChallenges.def(),
+        )
+        .join(
+            JoinType::LeftJoin,
+            challenges_tasks::Relation::ChallengesCourseTasks.def(),
+        )
+        .filter(
+            Condition::any()
+                .add(Expr::cust_with_values(
+                    r#"$1 = ANY("challenges_challenges"."skill_ids")"#,
+                    [skill_id],
+                ))
+                .add(challenges_course_tasks::Column::CourseId.is_in(courses)),
+        )
+        .filter(task_scope(user))
+        .order_by_asc(challenges_tasks::Column::CreationTimestamp)
+        .all(db)
+        .await
+}
+
+/// Return the number of enabled subtasks in each task and how many of them the
+/// user has solved.
+pub async fn get_task_progress(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    task_ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, TaskProgress>, DbErr> {
+    let subtasks = challenges_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_subtasks::Column::Id)
+        .column(challenges_subtasks::Column::TaskId)
+        .filter(challenges_subtasks::Column::TaskId.is_in(task_ids))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(false))
+        .filter(challenges_subtasks::Column::Retired.eq(false))
+        .into_tuple::<(Uuid, Uuid)>()
+        .all(db)
+        .await?;
+    let solved = challenges_user_subtasks::Entity::find()
+        .select_only()
+        .column(challenges_user_subtasks::Column::SubtaskId)
+        .filter(challenges_user_subtasks::Column::UserId.eq(user_id))
+        .filter(challenges_user_subtasks::Column::SubtaskId.is_in(subtasks.iter().map(|x| x.0)))
+        .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let mut progress = HashMap::<Uuid, TaskProgress>::new();
+    for (subtask_id, task_id) in subtasks {
+        let entry = progress.entry(task_id).or_default();
+        entry.subtasks += 1;
+        if solved.contains(&subtask_id) {
+            entry.solved += 1;
+        }
+    }
+    Ok(progress)
+}
+
 #[derive(Debug)]
 pub enum Task {
     Challenge(challenges_challenges::Model),
@@ -0,0 +1,204 @@
+//! Tracking of the time users need to solve subtasks.
+//!
+//! A session starts when a user opens a subtask for the first time and ends
+//! when the subtask is solved. Opening the subtask again does not restart the
+//! session, unless it has already been solved and can be re-solved now.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use entity::{challenges_subtask_sessions, challenges_user_subtasks};
+use lib::config::challenges::ReSolve;
+use schemas::challenges::subtasks::TimeToSolveStats;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::subtasks::UserSubtaskExt;
+
+/// Return the start of the current session of a user on each subtask.
+pub async fn get_user_sessions(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+) -> Result<HashMap<Uuid, NaiveDateTime>, DbErr> {
+    Ok(challenges_subtask_sessions::Entity::find()
+        .filter(challenges_subtask_sessions::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|x| (x.subtask_id, x.start))
+        .collect())
+}
+
+/// Start a session of a user on a subtask unless one is already running and
+/// return the start of the current session.
+pub async fn start_session(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+    user_subtask: Option<&challenges_user_subtasks::Model>,
+    re_solve: &ReSolve,
+) -> Result<NaiveDateTime, DbErr> {
+    let now = Utc::now();
+    let session = challenges_subtask_sessions::Entity::find_by_id((user_id, subtask_id))
+        .one(db)
+        .await?;
+    match session {
+        Some(session) if !should_restart(session.start, user_subtask, re_solve, now) => {
+            Ok(session.start)
+        }
+        Some(session) => Ok(challenges_subtask_sessions::ActiveModel {
+            user_id: Unchanged(session.user_id),
+            subtask_id: Unchanged(session.subtask_id),
+            start: Set(now.naive_utc()),
+        }
+        .update(db)
+        .await?
+        .start),
+        None => {
+            // concurrent requests must not restart the session
+            challenges_subtask_sessions::Entity::insert(challenges_subtask_sessions::ActiveModel {
+                user_id: Set(user_id),
+                subtask_id: Set(subtask_id),
+                start: Set(now.naive_utc()),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    challenges_subtask_sessions::Column::UserId,
+                    challenges_subtask_sessions::Column::SubtaskId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+            Ok(
+                challenges_subtask_sessions::Entity::find_by_id((user_id, subtask_id))
+                    .one(db)
+                    .await?
+                    .map(|x| x.start)
+                    .unwrap_or(now.naive_utc()),
+            )
+        }
+    }
+}
+
+/// Return whether opening a subtask again should start a new session. This is
+/// only the case if the current session has ended with a solve and the subtask
+/// can be re-solved now.
+pub fn should_restart(
+    start: NaiveDateTime,
+    user_subtask: Option<&challenges_user_subtasks::Model>,
+    re_solve: &ReSolve,
+    now: DateTime<Utc>,
+) -> bool {
+    user_subtask
+        .solved_at()
+        .is_some_and(|solved| solved.naive_utc() >= start)
+        && user_subtask.solve_kind(re_solve, now).is_some()
+}
+
+/// Return the number of seconds between the start of the session and the
+/// solve or `None` if the subtask has not been solved in this session.
+pub fn time_to_solve(
+    start: NaiveDateTime,
+    user_subtask: Option<&challenges_user_subtasks::Model>,
+) -> Option<u64> {
+    let solved = user_subtask.solved_at()?.naive_utc();
+    (solved >= start).then(|| (solved - start).num_seconds() as _)
+}
+
+pub fn time_to_solve_stats(mut durations: Vec<u64>) -> TimeToSolveStats {
+    if durations.is_empty() {
+        return TimeToSolveStats::default();
+    }
+    durations.sort_unstable();
+    let n = durations.len();
+    let median = if n % 2 == 0 {
+        (durations[n / 2 - 1] + durations[n / 2]) / 2
+    } else {
+        durations[n / 2]
+    };
+    TimeToSolveStats {
+        solves: n as _,
+        average: Some(durations.iter().sum::<u64>() / n as u64),
+        median: Some(median),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn user_subtask(solved_at: Option<DateTime<Utc>>) -> challenges_user_subtasks::Model {
+        challenges_user_subtasks::Model {
+            user_id: Uuid::nil(),
+            subtask_id: Uuid::nil(),
+            solved_timestamp: solved_at.map(|x| x.naive_utc()),
+            rating_timestamp: None,
+            last_attempt_timestamp: solved_at.map(|x| x.naive_utc()),
+            attempts: 1,
+            revealed_timestamp: None,
+            rating: None,
+        }
+    }
+
+    #[test]
+    fn restart() {
+        let config = ReSolve {
+            enabled: true,
+            period: 30,
+            xp_percent: 25,
+        };
+        let start = Utc.with_ymd_and_hms(2023, 11, 1, 12, 0, 0).unwrap();
+        let solved = user_subtask(Some(start + Duration::hours(1)));
+        let now = start + Duration::days(10);
+
+        assert!(!should_restart(start.naive_utc(), None, &config, now));
+        assert!(!should_restart(
+            start.naive_utc(),
+            Some(&solved),
+            &config,
+            now
+        ));
+        assert!(should_restart(
+            start.naive_utc(),
+            Some(&solved),
+            &config,
+            now + Duration::days(30)
+        ));
+        // the solve belongs to a previous session
+        assert!(!should_restart(
+            (start + Duration::days(40)).naive_utc(),
+            Some(&solved),
+            &config,
+            now + Duration::days(30)
+        ));
+    }
+
+    #[test]
+    fn durations() {
+        let start = Utc.with_ymd_and_hms(2023, 11, 1, 12, 0, 0).unwrap();
+        let solved = user_subtask(Some(start + Duration::minutes(5)));
+        assert_eq!(time_to_solve(start.naive_utc(), Some(&solved)), Some(300));
+        assert_eq!(time_to_solve(start.naive_utc(), None), None);
+        assert_eq!(
+            time_to_solve((start + Duration::hours(1)).naive_utc(), Some(&solved)),
+            None
+        );
+
+        assert_eq!(time_to_solve_stats(vec![]), TimeToSolveStats::default());
+        assert_eq!(
+            time_to_solve_stats(vec![30, 10, 20, 100]),
+            TimeToSolveStats {
+                solves: 4,
+                average: Some(40),
+                median: Some(25),
+            }
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Unlocking disabled subtasks for morphcoins.
+//!
+//! Creators can lock a subtask and set an `unlock_price` on it. While such a
+//! subtask is locked, users can buy access to it. Access is stored in
+//! `challenges_subtask_access`, just like access granted by the shop.
+
+use entity::{challenges_subtask_access, challenges_subtask_reports, challenges_subtasks};
+use lib::auth::User;
+use sea_orm::{
+    sea_query::Query, ColumnTrait, ConnectionTrait, DatabaseTransaction, DbBackend, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, Statement,
+};
+use uuid::Uuid;
+
+/// Return the combined price of unlocking the given subtasks at once. The
+/// discount is only applied if more than one subtask is unlocked.
+pub fn combined_price(prices: &[u64], discount_percent: u8) -> u64 {
+    let total = prices.iter().sum::<u64>();
+    match prices.len() {
+        0 | 1 => total,
+        _ => total - total * discount_percent.min(100) as u64 / 100,
+    }
+}
+
+/// Return the subtasks of a task the user can unlock, i.e. enabled subtasks
+/// with an unlock price which are locked and which the user cannot access
+/// yet. Reported subtasks cannot be unlocked.
+pub async fn get_locked_subtasks(
+    db: &DatabaseTransaction,
+    user: &User,
+    task_id: Uuid,
+) -> Result<Vec<challenges_subtasks::Model>, DbErr> {
+    challenges_subtasks::Entity::find()
+        .filter(challenges_subtasks::Column::TaskId.eq(task_id))
+        .filter(challenges_subtasks::Column::Enabled.eq(true))
+        .filter(challenges_subtasks::Column::Locked.eq(true))
+        .filter(challenges_subtasks::Column::Retired.eq(false))
+        .filter(challenges_subtasks::Column::UnlockPrice.is_not_null())
+        .filter(challenges_subtasks::Column::Creator.ne(user.id))
+        .filter(
+            challenges_subtasks::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(challenges_subtask_access::Column::SubtaskId)
+                    .from(challenges_subtask_access::Entity)
+                    .and_where(challenges_subtask_access::Column::UserId.eq(user.id))
+                    .to_owned(),
+            ),
+        )
+        .filter(
+            challenges_subtasks::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(challenges_subtask_reports::Column::SubtaskId)
+                    .from(challenges_subtask_reports::Entity)
+                    .to_owned(),
+            ),
+        )
+        .order_by_asc(challenges_subtasks::Column::CreationTimestamp)
+        .all(db)
+        .await
+}
+
+/// Serialize concurrent unlocks of the same task by the same user until the
+/// end of the transaction, so the locked subtasks are not paid for twice.
+pub async fn lock_task_unlock(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "select pg_advisory_xact_lock(hashtextextended($1, 0))",
+        [format!("task_unlock:{user_id}:{task_id}").into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combined_price() {
+        assert_eq!(combined_price(&[], 20), 0);
+        assert_eq!(combined_price(&[50], 20), 50);
+        assert_eq!(combined_price(&[50, 30, 20], 20), 80);
+        assert_eq!(combined_price(&[10, 5], 0), 15);
+        assert_eq!(combined_price(&[10, 5], 100), 0);
+        assert_eq!(combined_price(&[10, 5], 200), 0);
+        // the discount is rounded down
+        assert_eq!(combined_price(&[3, 4], 10), 7);
+    }
+}
@@ -0,0 +1,573 @@
+use chrono::Utc;
+use entity::{
+    challenges_ban, challenges_cheat_flags, challenges_cloze_attempts,
+    challenges_code_reading_attempts, challenges_coding_challenge_result,
+    challenges_coding_challenge_submissions, challenges_cohort_members, challenges_cohorts,
+    challenges_contests, challenges_evaluator_versions, challenges_exam_answers, challenges_exams,
+    challenges_held_rewards, challenges_matching_attempts, challenges_multiple_choice_attempts,
+    challenges_notification_preferences, challenges_ordering_attempts,
+    challenges_output_submissions, challenges_paths, challenges_question_attempts,
+    challenges_reviews, challenges_sql_attempts, challenges_subtask_access,
+    challenges_subtask_reports, challenges_subtask_revisions, challenges_subtask_sessions,
+    challenges_subtasks, challenges_task_completions, challenges_tasks,
+    challenges_true_false_attempts, challenges_user_bookmarks, challenges_user_notes,
+    challenges_user_paths, challenges_user_subtasks,
+};
+use schemas::challenges::{
+    subtasks::{Bookmark, SubtaskNote, SubtaskSession},
+    user_data::{AffectedRows, AttemptData, UserDataAction, UserDataExport},
+};
+use sea_orm::{
+    sea_query::{Expr, SimpleExpr},
+    ColumnTrait, DatabaseTransaction, DbErr, EntityName, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Value,
+};
+use uuid::Uuid;
+
+/// Collect all personal data of a user.
+pub async fn export_user_data(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+) -> Result<UserDataExport, DbErr> {
+    Ok(UserDataExport {
+        user_id,
+        timestamp: Utc::now(),
+        subtasks: challenges_user_subtasks::Entity::find()
+            .filter(challenges_user_subtasks::Column::UserId.eq(user_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        multiple_choice_attempts: challenges_multiple_choice_attempts::Entity::find()
+            .filter(challenges_multiple_choice_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_multiple_choice_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.question_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        question_attempts: challenges_question_attempts::Entity::find()
+            .filter(challenges_question_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_question_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.question_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        matching_attempts: challenges_matching_attempts::Entity::find()
+            .filter(challenges_matching_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_matching_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.matching_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        true_false_attempts: challenges_true_false_attempts::Entity::find()
+            .filter(challenges_true_false_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_true_false_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.statement_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        ordering_attempts: challenges_ordering_attempts::Entity::find()
+            .filter(challenges_ordering_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_ordering_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.ordering_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        cloze_attempts: challenges_cloze_attempts::Entity::find()
+            .filter(challenges_cloze_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_cloze_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.cloze_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        code_reading_attempts: challenges_code_reading_attempts::Entity::find()
+            .filter(challenges_code_reading_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_code_reading_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.code_reading_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        sql_attempts: challenges_sql_attempts::Entity::find()
+            .filter(challenges_sql_attempts::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_sql_attempts::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.sql_challenge_id,
+                timestamp: x.timestamp.and_utc(),
+                solved: x.solved,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        output_submissions: challenges_output_submissions::Entity::find()
+            .filter(challenges_output_submissions::Column::Creator.eq(user_id))
+            .order_by_asc(challenges_output_submissions::Column::CreationTimestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| AttemptData {
+                id: x.id,
+                subtask_id: x.subtask_id,
+                timestamp: x.creation_timestamp.and_utc(),
+                solved: x.passed == x.total,
+                revision_id: x.revision_id,
+            })
+            .collect(),
+        submissions: challenges_coding_challenge_submissions::Entity::find()
+            .find_also_related(challenges_coding_challenge_result::Entity)
+            .filter(challenges_coding_challenge_submissions::Column::Creator.eq(user_id))
+            .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        unlocks: challenges_subtask_access::Entity::find()
+            .filter(challenges_subtask_access::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_subtask_access::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        bookmarks: challenges_user_bookmarks::Entity::find()
+            .find_also_related(challenges_subtasks::Entity)
+            .filter(challenges_user_bookmarks::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_user_bookmarks::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(bookmark, subtask)| {
+                Some(Bookmark {
+                    task_id: subtask?.task_id,
+                    subtask_id: bookmark.subtask_id,
+                    timestamp: bookmark.timestamp.and_utc(),
+                })
+            })
+            .collect(),
+        notes: challenges_user_notes::Entity::find()
+            .find_also_related(challenges_subtasks::Entity)
+            .filter(challenges_user_notes::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_user_notes::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(note, subtask)| Some(SubtaskNote::from(note, subtask?.task_id)))
+            .collect(),
+        sessions: challenges_subtask_sessions::Entity::find()
+            .find_also_related(challenges_subtasks::Entity)
+            .filter(challenges_subtask_sessions::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_subtask_sessions::Column::Start)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(session, subtask)| {
+                Some(SubtaskSession {
+                    task_id: subtask?.task_id,
+                    subtask_id: session.subtask_id,
+                    start: session.start.and_utc(),
+                })
+            })
+            .collect(),
+        reports: challenges_subtask_reports::Entity::find()
+            .filter(challenges_subtask_reports::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_subtask_reports::Column::Timestamp)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        bans: challenges_ban::Entity::find()
+            .filter(challenges_ban::Column::UserId.eq(user_id))
+            .order_by_asc(challenges_ban::Column::Start)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        notification_preferences: challenges_notification_preferences::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .map(Into::into),
+    })
+}
+
+/// Remove all personal data of a user. Data that is still relevant to other
+/// users (e.g. subtasks created by the user or reports) is anonymized instead.
+/// In a dry run, only the number of affected rows is computed.
+pub async fn delete_user_data(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    dry_run: bool,
+) -> Result<Vec<AffectedRows>, DbErr> {
+    // submission results are deleted together with their submissions
+    Ok(vec![
+        delete::<challenges_user_subtasks::Entity>(
+            db,
+            challenges_user_subtasks::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_multiple_choice_attempts::Entity>(
+            db,
+            challenges_multiple_choice_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_question_attempts::Entity>(
+            db,
+            challenges_question_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_matching_attempts::Entity>(
+            db,
+            challenges_matching_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_true_false_attempts::Entity>(
+            db,
+            challenges_true_false_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_ordering_attempts::Entity>(
+            db,
+            challenges_ordering_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_cloze_attempts::Entity>(
+            db,
+            challenges_cloze_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_code_reading_attempts::Entity>(
+            db,
+            challenges_code_reading_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_sql_attempts::Entity>(
+            db,
+            challenges_sql_attempts::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_output_submissions::Entity>(
+            db,
+            challenges_output_submissions::Column::Creator.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_coding_challenge_submissions::Entity>(
+            db,
+            challenges_coding_challenge_submissions::Column::Creator.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_subtask_access::Entity>(
+            db,
+            challenges_subtask_access::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_user_bookmarks::Entity>(
+            db,
+            challenges_user_bookmarks::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_user_notes::Entity>(
+            db,
+            challenges_user_notes::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_subtask_sessions::Entity>(
+            db,
+            challenges_subtask_sessions::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_exam_answers::Entity>(
+            db,
+            challenges_exam_answers::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_cohort_members::Entity>(
+            db,
+            challenges_cohort_members::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_cheat_flags::Entity>(
+            db,
+            challenges_cheat_flags::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_held_rewards::Entity>(
+            db,
+            challenges_held_rewards::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_reviews::Entity>(
+            db,
+            challenges_reviews::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_user_paths::Entity>(
+            db,
+            challenges_user_paths::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_task_completions::Entity>(
+            db,
+            challenges_task_completions::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        delete::<challenges_ban::Entity>(db, challenges_ban::Column::UserId.eq(user_id), dry_run)
+            .await?,
+        delete::<challenges_notification_preferences::Entity>(
+            db,
+            challenges_notification_preferences::Column::UserId.eq(user_id),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_subtask_reports::Entity>(
+            db,
+            challenges_subtask_reports::Column::UserId,
+            user_id,
+            None::<Uuid>.into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_ban::Entity>(
+            db,
+            challenges_ban::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_tasks::Entity>(
+            db,
+            challenges_tasks::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_subtasks::Entity>(
+            db,
+            challenges_subtasks::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize_revisions(db, user_id, dry_run).await?,
+        anonymize::<challenges_evaluator_versions::Entity>(
+            db,
+            challenges_evaluator_versions::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_contests::Entity>(
+            db,
+            challenges_contests::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_exams::Entity>(
+            db,
+            challenges_exams::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_cohorts::Entity>(
+            db,
+            challenges_cohorts::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_cheat_flags::Entity>(
+            db,
+            challenges_cheat_flags::Column::Reviewer,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_held_rewards::Entity>(
+            db,
+            challenges_held_rewards::Column::Resolver,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+        anonymize::<challenges_paths::Entity>(
+            db,
+            challenges_paths::Column::Creator,
+            user_id,
+            Uuid::nil().into(),
+            dry_run,
+        )
+        .await?,
+    ])
+}
+
+async fn delete<E>(
+    db: &DatabaseTransaction,
+    filter: SimpleExpr,
+    dry_run: bool,
+) -> Result<AffectedRows, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let rows = if dry_run {
+        E::find().filter(filter).count(db).await?
+    } else {
+        E::delete_many()
+            .filter(filter)
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+    Ok(AffectedRows {
+        table: E::default().table_name().into(),
+        action: UserDataAction::Delete,
+        rows,
+    })
+}
+
+async fn anonymize<E>(
+    db: &DatabaseTransaction,
+    column: E::Column,
+    user_id: Uuid,
+    value: Value,
+    dry_run: bool,
+) -> Result<AffectedRows, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let rows = if dry_run {
+        E::find().filter(column.eq(user_id)).count(db).await?
+    } else {
+        E::update_many()
+            .col_expr(column, Expr::value(value))
+            .filter(column.eq(user_id))
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+    Ok(AffectedRows {
+        table: E::default().table_name().into(),
+        action: UserDataAction::Anonymize,
+        rows,
+    })
+}
+
+/// Remove the creator from the subtask snapshots in the revision history.
+async fn anonymize_revisions(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    dry_run: bool,
+) -> Result<AffectedRows, DbErr> {
+    let filter = Expr::cust_with_values("subtask->>'creator' = $1", [user_id.to_string()]);
+    let rows = if dry_run {
+        challenges_subtask_revisions::Entity::find()
+            .filter(filter)
+            .count(db)
+            .await?
+    } else {
+        challenges_subtask_revisions::Entity::update_many()
+            .col_expr(
+                challenges_subtask_revisions::Column::Subtask,
+                Expr::cust_with_values(
+                    "jsonb_set(subtask, '{creator}', to_jsonb($1::text))",
+                    [Uuid::nil().to_string()],
+                ),
+            )
+            .filter(filter)
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+    Ok(AffectedRows {
+        table: challenges_subtask_revisions::Entity.table_name().into(),
+        action: UserDataAction::Anonymize,
+        rows,
+    })
+}
@@ -0,0 +1,105 @@
+//! Aggregated statistics about the mistakes users make in multiple choice
+//! questions and matchings.
+//!
+//! Only the number of times each mistake has been made is stored, so the
+//! statistics cannot be traced back to individual users.
+
+use entity::challenges_wrong_answers;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    DatabaseTransaction, DbErr, EntityTrait, Set,
+};
+use uuid::Uuid;
+
+/// A single mistake in an attempt.
+///
+/// For multiple choice questions `item` is the index of the answer option and
+/// `choice` is `1` if the option has been selected although it is wrong and
+/// `0` if a correct option has not been selected. For matchings `item` is the
+/// index of the entry on the left and `choice` is the index of the entry on
+/// the right it has been wrongly matched with.
+pub type Mistake = (i32, i32);
+
+/// Return the mistakes in the answers to a multiple choice question.
+pub fn mcq_mistakes(answers: &[bool], correct: i64) -> Vec<Mistake> {
+    answers
+        .iter()
+        .enumerate()
+        .filter(|(i, &answer)| (correct & (1 << i) != 0) != answer)
+        .map(|(i, &answer)| (i as _, answer as _))
+        .collect()
+}
+
+/// Return the mistakes in the answer to a matching.
+pub fn matching_mistakes(answer: &[u8], solution: &[i16]) -> Vec<Mistake> {
+    answer
+        .iter()
+        .zip(solution)
+        .enumerate()
+        .filter(|(_, (&x, &y))| x as i16 != y)
+        .map(|(i, (&x, _))| (i as _, x as _))
+        .collect()
+}
+
+/// Increment the counters of the given mistakes.
+pub async fn record_mistakes(
+    db: &DatabaseTransaction,
+    subtask_id: Uuid,
+    mistakes: Vec<Mistake>,
+) -> Result<(), DbErr> {
+    if mistakes.is_empty() {
+        return Ok(());
+    }
+
+    challenges_wrong_answers::Entity::insert_many(mistakes.into_iter().map(|(item, choice)| {
+        challenges_wrong_answers::ActiveModel {
+            subtask_id: Set(subtask_id),
+            item: Set(item),
+            choice: Set(choice),
+            count: Set(1),
+        }
+    }))
+    .on_conflict(
+        OnConflict::columns([
+            challenges_wrong_answers::Column::SubtaskId,
+            challenges_wrong_answers::Column::Item,
+            challenges_wrong_answers::Column::Choice,
+        ])
+        .value(
+            challenges_wrong_answers::Column::Count,
+            Expr::col((
+                challenges_wrong_answers::Entity,
+                challenges_wrong_answers::Column::Count,
+            ))
+            .add(1),
+        )
+        .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcq_mistakes() {
+        assert_eq!(mcq_mistakes(&[true, false, true], 0b101), vec![]);
+        assert_eq!(
+            mcq_mistakes(&[false, true, true, false], 0b1001),
+            vec![(0, 0), (1, 1), (2, 1), (3, 0)]
+        );
+    }
+
+    #[test]
+    fn test_matching_mistakes() {
+        assert_eq!(matching_mistakes(&[2, 0, 1], &[2, 0, 1]), vec![]);
+        assert_eq!(
+            matching_mistakes(&[1, 0, 2], &[2, 0, 1]),
+            vec![(0, 1), (2, 2)]
+        );
+    }
+}
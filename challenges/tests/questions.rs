@@ -0,0 +1,144 @@
+use challenges::services::sandbox::sandbox_from_config;
+use poem::http::{Method, StatusCode};
+use serde_json::json;
+use testing::{
+    fixtures::{create_category, create_challenge, create_question},
+    RequestExt, TestClient, TestContext, TestUser,
+};
+
+#[tokio::test]
+async fn solve_question() {
+    let Some(ctx) = TestContext::new().await.unwrap() else {
+        return;
+    };
+    let creator = TestUser::admin();
+    let category = create_category(&ctx.db.conn).await.unwrap();
+    let challenge = create_challenge(&ctx.db.conn, category.id, creator.id, &[])
+        .await
+        .unwrap();
+    let (subtask, _) = create_question(
+        &ctx.db.conn,
+        challenge.task_id,
+        creator.id,
+        "What is the answer?",
+        &["42"],
+    )
+    .await
+    .unwrap();
+    let path = format!(
+        "/tasks/{}/questions/{}/attempts",
+        challenge.task_id, subtask.id
+    );
+    let cli = TestClient::new(
+        challenges::app(
+            ctx.state.clone(),
+            ctx.config.clone(),
+            sandbox_from_config(&ctx.config.load().challenges.coding_challenges),
+        )
+        .await
+        .unwrap(),
+    );
+
+    // wrong answer
+    let user = TestUser::new();
+    let resp = cli
+        .post(&path)
+        .auth(&user)
+        .body_json(&json!({"answer": "41"}))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::CREATED);
+    resp.assert_json(json!({"solved": false})).await;
+
+    // cooldown
+    let resp = cli
+        .post(&path)
+        .auth(&user)
+        .body_json(&json!({"answer": "42"}))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+    // correct answer
+    let other = TestUser::new();
+    let resp = cli
+        .post(&path)
+        .auth(&other)
+        .body_json(&json!({"answer": "42"}))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::CREATED);
+    resp.assert_json(json!({"solved": true})).await;
+
+    let rewards = ctx
+        .services
+        .requests_to(Method::POST, &format!("/_internal/coins/{}", other.id));
+    assert_eq!(rewards.len(), 1);
+    assert_eq!(rewards[0].body.as_ref().unwrap()["coins"], json!(5));
+    assert!(ctx
+        .services
+        .requests_to(Method::POST, &format!("/_internal/coins/{}", user.id))
+        .is_empty());
+
+    ctx.cleanup().await.unwrap();
+}
+
+#[tokio::test]
+async fn solve_question_access() {
+    let Some(ctx) = TestContext::new().await.unwrap() else {
+        return;
+    };
+    let creator = TestUser::admin();
+    let category = create_category(&ctx.db.conn).await.unwrap();
+    let challenge = create_challenge(&ctx.db.conn, category.id, creator.id, &[])
+        .await
+        .unwrap();
+    let (subtask, _) = create_question(
+        &ctx.db.conn,
+        challenge.task_id,
+        creator.id,
+        "What is the answer?",
+        &["42"],
+    )
+    .await
+    .unwrap();
+    let path = format!(
+        "/tasks/{}/questions/{}/attempts",
+        challenge.task_id, subtask.id
+    );
+    let cli = TestClient::new(
+        challenges::app(
+            ctx.state.clone(),
+            ctx.config.clone(),
+            sandbox_from_config(&ctx.config.load().challenges.coding_challenges),
+        )
+        .await
+        .unwrap(),
+    );
+    let body = json!({"answer": "42"});
+
+    let resp = cli.post(&path).body_json(&body).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+
+    let resp = cli
+        .post(&path)
+        .auth(&TestUser::unverified())
+        .body_json(&body)
+        .send()
+        .await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+
+    let resp = cli
+        .post(format!(
+            "/tasks/{}/questions/{}/attempts",
+            challenge.task_id,
+            uuid::Uuid::new_v4()
+        ))
+        .auth(&TestUser::new())
+        .body_json(&body)
+        .send()
+        .await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+
+    ctx.cleanup().await.unwrap();
+}
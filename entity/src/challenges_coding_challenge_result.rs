@@ -1,10 +1,11 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.2
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromJsonQueryResult};
+use serde::{Deserialize, Serialize};
 
 use super::sea_orm_active_enums::ChallengesVerdict;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "challenges_coding_challenge_result")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -22,6 +23,47 @@ pub struct Model {
     pub run_stderr: Option<String>,
     pub run_time: Option<i32>,
     pub run_memory: Option<i32>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub transcript: Option<Transcript>,
+    pub evaluator_version: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub seed: Option<String>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub score: Option<f64>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub resources: Option<Resources>,
+    pub personal_best_time: bool,
+    pub personal_best_memory: bool,
+}
+
+/// The rounds of an interaction between the evaluator and the solution of an
+/// interactive coding challenge.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct Transcript(pub Vec<TranscriptRound>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptRound {
+    pub judge: String,
+    pub program: Option<String>,
+}
+
+/// The resources a solution used in each test together with the limits of the
+/// challenge at the time it has been judged.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct Resources {
+    /// The time limit in milliseconds.
+    pub time_limit: u64,
+    /// The memory limit in megabytes.
+    pub memory_limit: u64,
+    pub tests: Vec<TestResources>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestResources {
+    /// The number of milliseconds the solution ran.
+    pub time: u64,
+    /// The number of kilobytes of memory the solution used.
+    pub memory: u64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -14,6 +14,10 @@ pub struct Model {
     pub environment: String,
     #[sea_orm(column_type = "Text")]
     pub code: String,
+    pub attributed: bool,
+    pub revision_id: Option<Uuid>,
+    pub code_length: i32,
+    pub rewarded: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,6 +32,20 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     ChallengesCodingChallenges,
+    #[sea_orm(has_many = "super::challenges_judge_queue::Entity")]
+    ChallengesJudgeQueue,
+    #[sea_orm(has_many = "super::challenges_submission_artifacts::Entity")]
+    ChallengesSubmissionArtifacts,
+    #[sea_orm(has_many = "super::challenges_submission_files::Entity")]
+    ChallengesSubmissionFiles,
+    #[sea_orm(
+        belongs_to = "super::challenges_subtask_revisions::Entity",
+        from = "Column::RevisionId",
+        to = "super::challenges_subtask_revisions::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    ChallengesSubtaskRevisions,
 }
 
 impl Related<super::challenges_coding_challenge_result::Entity> for Entity {
@@ -42,4 +60,28 @@ impl Related<super::challenges_coding_challenges::Entity> for Entity {
     }
 }
 
+impl Related<super::challenges_judge_queue::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesJudgeQueue.def()
+    }
+}
+
+impl Related<super::challenges_submission_artifacts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubmissionArtifacts.def()
+    }
+}
+
+impl Related<super::challenges_submission_files::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubmissionFiles.def()
+    }
+}
+
+impl Related<super::challenges_subtask_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubtaskRevisions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
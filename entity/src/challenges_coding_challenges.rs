@@ -2,7 +2,9 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+use super::sea_orm_active_enums::{ChallengesComparison, ChallengesFeedbackLevel};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, serde::Serialize, serde::Deserialize)]
 #[sea_orm(table_name = "challenges_coding_challenges")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -19,12 +21,43 @@ pub struct Model {
     pub solution_code: String,
     pub static_tests: i32,
     pub random_tests: i32,
+    #[serde(default)]
+    pub output_only: bool,
+    #[serde(default = "first_evaluator_version")]
+    pub evaluator_version: i32,
+    #[serde(default = "full_diff")]
+    pub feedback_level: ChallengesFeedbackLevel,
+    #[serde(default = "first_protocol_version")]
+    pub protocol_version: i32,
+    #[serde(default = "evaluator_comparison")]
+    pub comparison: ChallengesComparison,
+    #[sea_orm(column_type = "Double", nullable)]
+    #[serde(default)]
+    pub epsilon: Option<f64>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub checker_environment: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub checker_code: Option<String>,
+    #[serde(default)]
+    pub golf: bool,
+    #[serde(default)]
+    pub solution_public_after_solve: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::challenges_build_configs::Entity")]
+    ChallengesBuildConfigs,
     #[sea_orm(has_many = "super::challenges_coding_challenge_submissions::Entity")]
     ChallengesCodingChallengeSubmissions,
+    #[sea_orm(has_many = "super::challenges_evaluator_versions::Entity")]
+    ChallengesEvaluatorVersions,
+    #[sea_orm(has_many = "super::challenges_output_submissions::Entity")]
+    ChallengesOutputSubmissions,
+    #[sea_orm(has_many = "super::challenges_personal_bests::Entity")]
+    ChallengesPersonalBests,
     #[sea_orm(
         belongs_to = "super::challenges_subtasks::Entity",
         from = "Column::SubtaskId",
@@ -35,12 +68,36 @@ pub enum Relation {
     ChallengesSubtasks,
 }
 
+impl Related<super::challenges_build_configs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesBuildConfigs.def()
+    }
+}
+
 impl Related<super::challenges_coding_challenge_submissions::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesCodingChallengeSubmissions.def()
     }
 }
 
+impl Related<super::challenges_evaluator_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesEvaluatorVersions.def()
+    }
+}
+
+impl Related<super::challenges_output_submissions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesOutputSubmissions.def()
+    }
+}
+
+impl Related<super::challenges_personal_bests::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesPersonalBests.def()
+    }
+}
+
 impl Related<super::challenges_subtasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesSubtasks.def()
@@ -48,3 +105,19 @@ impl Related<super::challenges_subtasks::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+fn first_evaluator_version() -> i32 {
+    1
+}
+
+fn first_protocol_version() -> i32 {
+    1
+}
+
+fn evaluator_comparison() -> ChallengesComparison {
+    ChallengesComparison::Evaluator
+}
+
+fn full_diff() -> ChallengesFeedbackLevel {
+    ChallengesFeedbackLevel::FullDiff
+}
@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, serde::Serialize, serde::Deserialize)]
 #[sea_orm(table_name = "challenges_matchings")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, serde::Serialize, serde::Deserialize)]
 #[sea_orm(table_name = "challenges_multiple_choice_quizes")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -12,6 +12,8 @@ pub struct Model {
     pub answers: Vec<String>,
     pub correct_answers: i64,
     pub single_choice: bool,
+    #[serde(default)]
+    pub shuffle_answers: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -11,6 +11,7 @@ pub struct Model {
     pub user_id: Uuid,
     pub timestamp: DateTime,
     pub solved: bool,
+    pub revision_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,6 +24,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     ChallengesQuestions,
+    #[sea_orm(
+        belongs_to = "super::challenges_subtask_revisions::Entity",
+        from = "Column::RevisionId",
+        to = "super::challenges_subtask_revisions::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    ChallengesSubtaskRevisions,
 }
 
 impl Related<super::challenges_questions::Entity> for Entity {
@@ -31,4 +40,10 @@ impl Related<super::challenges_questions::Entity> for Entity {
     }
 }
 
+impl Related<super::challenges_subtask_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubtaskRevisions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
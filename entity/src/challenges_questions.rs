@@ -1,8 +1,11 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.2
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromJsonQueryResult};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+use super::sea_orm_active_enums::ChallengesQuestionAnswerType;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "challenges_questions")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -15,6 +18,33 @@ pub struct Model {
     pub digits: bool,
     pub punctuation: bool,
     pub blocks: Vec<String>,
+    #[sea_orm(column_type = "JsonBinary")]
+    #[serde(default)]
+    pub parameters: QuestionParameters,
+    #[serde(default = "text_answer_type")]
+    pub answer_type: ChallengesQuestionAnswerType,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[sea_orm(column_type = "Double", nullable)]
+    #[serde(default)]
+    pub absolute_tolerance: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    #[serde(default)]
+    pub relative_tolerance: Option<f64>,
+}
+
+/// The parameters of a templated question. Empty if the question is not
+/// templated.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct QuestionParameters(pub Vec<QuestionParameter>);
+
+/// A parameter that is replaced by a random integer in `min..=max`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestionParameter {
+    pub name: String,
+    pub min: i64,
+    pub max: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -44,3 +74,7 @@ impl Related<super::challenges_subtasks::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+fn text_answer_type() -> ChallengesQuestionAnswerType {
+    ChallengesQuestionAnswerType::Text
+}
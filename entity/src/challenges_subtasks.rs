@@ -2,9 +2,11 @@
 
 use sea_orm::entity::prelude::*;
 
-use super::sea_orm_active_enums::ChallengesSubtaskType;
+use super::sea_orm_active_enums::{
+    ChallengesLicense, ChallengesReviewStatus, ChallengesSubtaskType,
+};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, serde::Serialize, serde::Deserialize)]
 #[sea_orm(table_name = "challenges_subtasks")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -17,20 +19,59 @@ pub struct Model {
     pub enabled: bool,
     pub ty: ChallengesSubtaskType,
     pub retired: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub fingerprint: Option<String>,
+    pub review_status: ChallengesReviewStatus,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub rejection_reason: Option<String>,
+    pub license: ChallengesLicense,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub attribution: Option<String>,
+    pub max_attempts: Option<i32>,
+    pub reveal_solution: bool,
+    pub time_limit: Option<i32>,
+    pub unlock_price: Option<i64>,
+    pub locked: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::challenges_attempt_seeds::Entity")]
+    ChallengesAttemptSeeds,
+    #[sea_orm(has_many = "super::challenges_clozes::Entity")]
+    ChallengesClozes,
+    #[sea_orm(has_many = "super::challenges_code_readings::Entity")]
+    ChallengesCodeReadings,
     #[sea_orm(has_many = "super::challenges_coding_challenges::Entity")]
     ChallengesCodingChallenges,
+    #[sea_orm(has_many = "super::challenges_exam_answers::Entity")]
+    ChallengesExamAnswers,
+    #[sea_orm(has_many = "super::challenges_held_rewards::Entity")]
+    ChallengesHeldRewards,
     #[sea_orm(has_many = "super::challenges_matchings::Entity")]
     ChallengesMatchings,
     #[sea_orm(has_many = "super::challenges_multiple_choice_quizes::Entity")]
     ChallengesMultipleChoiceQuizes,
+    #[sea_orm(has_many = "super::challenges_orderings::Entity")]
+    ChallengesOrderings,
+    #[sea_orm(has_many = "super::challenges_path_items::Entity")]
+    ChallengesPathItems,
     #[sea_orm(has_many = "super::challenges_questions::Entity")]
     ChallengesQuestions,
+    #[sea_orm(has_many = "super::challenges_reviews::Entity")]
+    ChallengesReviews,
+    #[sea_orm(has_many = "super::challenges_sandbox_usage::Entity")]
+    ChallengesSandboxUsage,
+    #[sea_orm(has_many = "super::challenges_sql_challenges::Entity")]
+    ChallengesSqlChallenges,
+    #[sea_orm(has_many = "super::challenges_subtask_access::Entity")]
+    ChallengesSubtaskAccess,
     #[sea_orm(has_many = "super::challenges_subtask_reports::Entity")]
     ChallengesSubtaskReports,
+    #[sea_orm(has_many = "super::challenges_subtask_revisions::Entity")]
+    ChallengesSubtaskRevisions,
+    #[sea_orm(has_many = "super::challenges_subtask_sessions::Entity")]
+    ChallengesSubtaskSessions,
     #[sea_orm(
         belongs_to = "super::challenges_tasks::Entity",
         from = "Column::TaskId",
@@ -39,8 +80,34 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     ChallengesTasks,
+    #[sea_orm(has_many = "super::challenges_true_false_statements::Entity")]
+    ChallengesTrueFalseStatements,
+    #[sea_orm(has_many = "super::challenges_user_bookmarks::Entity")]
+    ChallengesUserBookmarks,
+    #[sea_orm(has_many = "super::challenges_user_notes::Entity")]
+    ChallengesUserNotes,
     #[sea_orm(has_many = "super::challenges_user_subtasks::Entity")]
     ChallengesUserSubtasks,
+    #[sea_orm(has_many = "super::challenges_wrong_answers::Entity")]
+    ChallengesWrongAnswers,
+}
+
+impl Related<super::challenges_attempt_seeds::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesAttemptSeeds.def()
+    }
+}
+
+impl Related<super::challenges_clozes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesClozes.def()
+    }
+}
+
+impl Related<super::challenges_code_readings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodeReadings.def()
+    }
 }
 
 impl Related<super::challenges_coding_challenges::Entity> for Entity {
@@ -49,6 +116,18 @@ impl Related<super::challenges_coding_challenges::Entity> for Entity {
     }
 }
 
+impl Related<super::challenges_exam_answers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesExamAnswers.def()
+    }
+}
+
+impl Related<super::challenges_held_rewards::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesHeldRewards.def()
+    }
+}
+
 impl Related<super::challenges_matchings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesMatchings.def()
@@ -61,28 +140,100 @@ impl Related<super::challenges_multiple_choice_quizes::Entity> for Entity {
     }
 }
 
+impl Related<super::challenges_orderings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesOrderings.def()
+    }
+}
+
+impl Related<super::challenges_path_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesPathItems.def()
+    }
+}
+
 impl Related<super::challenges_questions::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesQuestions.def()
     }
 }
 
+impl Related<super::challenges_reviews::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesReviews.def()
+    }
+}
+
+impl Related<super::challenges_sandbox_usage::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSandboxUsage.def()
+    }
+}
+
+impl Related<super::challenges_sql_challenges::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSqlChallenges.def()
+    }
+}
+
+impl Related<super::challenges_subtask_access::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubtaskAccess.def()
+    }
+}
+
 impl Related<super::challenges_subtask_reports::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesSubtaskReports.def()
     }
 }
 
+impl Related<super::challenges_subtask_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubtaskRevisions.def()
+    }
+}
+
+impl Related<super::challenges_subtask_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesSubtaskSessions.def()
+    }
+}
+
 impl Related<super::challenges_tasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesTasks.def()
     }
 }
 
+impl Related<super::challenges_true_false_statements::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesTrueFalseStatements.def()
+    }
+}
+
+impl Related<super::challenges_user_bookmarks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesUserBookmarks.def()
+    }
+}
+
+impl Related<super::challenges_user_notes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesUserNotes.def()
+    }
+}
+
 impl Related<super::challenges_user_subtasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesUserSubtasks.def()
     }
 }
 
+impl Related<super::challenges_wrong_answers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesWrongAnswers.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -9,16 +9,37 @@ pub struct Model {
     pub id: Uuid,
     pub creator: Uuid,
     pub creation_timestamp: DateTime,
+    pub organization_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::challenges_challenges::Entity")]
     ChallengesChallenges,
+    #[sea_orm(has_many = "super::challenges_cohort_assignments::Entity")]
+    ChallengesCohortAssignments,
+    #[sea_orm(has_many = "super::challenges_contests::Entity")]
+    ChallengesContests,
     #[sea_orm(has_many = "super::challenges_course_tasks::Entity")]
     ChallengesCourseTasks,
+    #[sea_orm(has_many = "super::challenges_exams::Entity")]
+    ChallengesExams,
+    #[sea_orm(
+        belongs_to = "super::challenges_organizations::Entity",
+        from = "Column::OrganizationId",
+        to = "super::challenges_organizations::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    ChallengesOrganizations,
+    #[sea_orm(has_many = "super::challenges_path_items::Entity")]
+    ChallengesPathItems,
     #[sea_orm(has_many = "super::challenges_subtasks::Entity")]
     ChallengesSubtasks,
+    #[sea_orm(has_many = "super::challenges_task_completions::Entity")]
+    ChallengesTaskCompletions,
+    #[sea_orm(has_many = "super::challenges_task_config::Entity")]
+    ChallengesTaskConfig,
 }
 
 impl Related<super::challenges_challenges::Entity> for Entity {
@@ -27,16 +48,58 @@ impl Related<super::challenges_challenges::Entity> for Entity {
     }
 }
 
+impl Related<super::challenges_cohort_assignments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCohortAssignments.def()
+    }
+}
+
+impl Related<super::challenges_contests::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesContests.def()
+    }
+}
+
 impl Related<super::challenges_course_tasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesCourseTasks.def()
     }
 }
 
+impl Related<super::challenges_exams::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesExams.def()
+    }
+}
+
+impl Related<super::challenges_organizations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesOrganizations.def()
+    }
+}
+
+impl Related<super::challenges_path_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesPathItems.def()
+    }
+}
+
 impl Related<super::challenges_subtasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesSubtasks.def()
     }
 }
 
+impl Related<super::challenges_task_completions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesTaskCompletions.def()
+    }
+}
+
+impl Related<super::challenges_task_config::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesTaskConfig.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
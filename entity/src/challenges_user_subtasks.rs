@@ -16,6 +16,7 @@ pub struct Model {
     pub rating_timestamp: Option<DateTime>,
     pub last_attempt_timestamp: Option<DateTime>,
     pub attempts: i32,
+    pub revealed_timestamp: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
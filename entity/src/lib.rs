@@ -2,21 +2,68 @@
 
 pub mod prelude;
 
+pub mod challenges_attempt_seeds;
 pub mod challenges_ban;
+pub mod challenges_build_configs;
 pub mod challenges_challenge_categories;
 pub mod challenges_challenges;
+pub mod challenges_cheat_flags;
+pub mod challenges_cloze_attempts;
+pub mod challenges_clozes;
+pub mod challenges_code_reading_attempts;
+pub mod challenges_code_readings;
 pub mod challenges_coding_challenge_result;
 pub mod challenges_coding_challenge_submissions;
 pub mod challenges_coding_challenges;
+pub mod challenges_cohort_assignments;
+pub mod challenges_cohort_members;
+pub mod challenges_cohorts;
+pub mod challenges_contests;
 pub mod challenges_course_tasks;
+pub mod challenges_creator_digests;
+pub mod challenges_disabled_environments;
+pub mod challenges_evaluator_versions;
+pub mod challenges_event_outbox;
+pub mod challenges_exam_answers;
+pub mod challenges_exams;
+pub mod challenges_held_rewards;
+pub mod challenges_judge_queue;
 pub mod challenges_matching_attempts;
 pub mod challenges_matchings;
 pub mod challenges_multiple_choice_attempts;
 pub mod challenges_multiple_choice_quizes;
+pub mod challenges_notification_preferences;
+pub mod challenges_ordering_attempts;
+pub mod challenges_orderings;
+pub mod challenges_organization_members;
+pub mod challenges_organizations;
+pub mod challenges_output_submissions;
+pub mod challenges_path_items;
+pub mod challenges_paths;
+pub mod challenges_personal_bests;
 pub mod challenges_question_attempts;
 pub mod challenges_questions;
+pub mod challenges_reviews;
+pub mod challenges_reward_ledger;
+pub mod challenges_sandbox_usage;
+pub mod challenges_sql_attempts;
+pub mod challenges_sql_challenges;
+pub mod challenges_submission_artifacts;
+pub mod challenges_submission_files;
+pub mod challenges_subtask_access;
+pub mod challenges_subtask_prerequisites;
 pub mod challenges_subtask_reports;
+pub mod challenges_subtask_revisions;
+pub mod challenges_subtask_sessions;
 pub mod challenges_subtasks;
+pub mod challenges_task_completions;
+pub mod challenges_task_config;
 pub mod challenges_tasks;
+pub mod challenges_true_false_attempts;
+pub mod challenges_true_false_statements;
+pub mod challenges_user_bookmarks;
+pub mod challenges_user_notes;
+pub mod challenges_user_paths;
 pub mod challenges_user_subtasks;
+pub mod challenges_wrong_answers;
 pub mod sea_orm_active_enums;
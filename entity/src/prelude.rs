@@ -1,20 +1,68 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.2
 
 pub use super::{
+    challenges_attempt_seeds::Entity as ChallengesAttemptSeeds,
     challenges_ban::Entity as ChallengesBan,
+    challenges_build_configs::Entity as ChallengesBuildConfigs,
     challenges_challenge_categories::Entity as ChallengesChallengeCategories,
     challenges_challenges::Entity as ChallengesChallenges,
+    challenges_cheat_flags::Entity as ChallengesCheatFlags,
+    challenges_cloze_attempts::Entity as ChallengesClozeAttempts,
+    challenges_clozes::Entity as ChallengesClozes,
+    challenges_code_reading_attempts::Entity as ChallengesCodeReadingAttempts,
+    challenges_code_readings::Entity as ChallengesCodeReadings,
     challenges_coding_challenge_result::Entity as ChallengesCodingChallengeResult,
     challenges_coding_challenge_submissions::Entity as ChallengesCodingChallengeSubmissions,
     challenges_coding_challenges::Entity as ChallengesCodingChallenges,
+    challenges_cohort_assignments::Entity as ChallengesCohortAssignments,
+    challenges_cohort_members::Entity as ChallengesCohortMembers,
+    challenges_cohorts::Entity as ChallengesCohorts,
+    challenges_contests::Entity as ChallengesContests,
     challenges_course_tasks::Entity as ChallengesCourseTasks,
+    challenges_creator_digests::Entity as ChallengesCreatorDigests,
+    challenges_disabled_environments::Entity as ChallengesDisabledEnvironments,
+    challenges_evaluator_versions::Entity as ChallengesEvaluatorVersions,
+    challenges_event_outbox::Entity as ChallengesEventOutbox,
+    challenges_exam_answers::Entity as ChallengesExamAnswers,
+    challenges_exams::Entity as ChallengesExams,
+    challenges_held_rewards::Entity as ChallengesHeldRewards,
+    challenges_judge_queue::Entity as ChallengesJudgeQueue,
     challenges_matching_attempts::Entity as ChallengesMatchingAttempts,
     challenges_matchings::Entity as ChallengesMatchings,
     challenges_multiple_choice_attempts::Entity as ChallengesMultipleChoiceAttempts,
     challenges_multiple_choice_quizes::Entity as ChallengesMultipleChoiceQuizes,
+    challenges_notification_preferences::Entity as ChallengesNotificationPreferences,
+    challenges_ordering_attempts::Entity as ChallengesOrderingAttempts,
+    challenges_orderings::Entity as ChallengesOrderings,
+    challenges_organization_members::Entity as ChallengesOrganizationMembers,
+    challenges_organizations::Entity as ChallengesOrganizations,
+    challenges_output_submissions::Entity as ChallengesOutputSubmissions,
+    challenges_path_items::Entity as ChallengesPathItems,
+    challenges_paths::Entity as ChallengesPaths,
+    challenges_personal_bests::Entity as ChallengesPersonalBests,
     challenges_question_attempts::Entity as ChallengesQuestionAttempts,
     challenges_questions::Entity as ChallengesQuestions,
+    challenges_reviews::Entity as ChallengesReviews,
+    challenges_reward_ledger::Entity as ChallengesRewardLedger,
+    challenges_sandbox_usage::Entity as ChallengesSandboxUsage,
+    challenges_sql_attempts::Entity as ChallengesSqlAttempts,
+    challenges_sql_challenges::Entity as ChallengesSqlChallenges,
+    challenges_submission_artifacts::Entity as ChallengesSubmissionArtifacts,
+    challenges_submission_files::Entity as ChallengesSubmissionFiles,
+    challenges_subtask_access::Entity as ChallengesSubtaskAccess,
+    challenges_subtask_prerequisites::Entity as ChallengesSubtaskPrerequisites,
     challenges_subtask_reports::Entity as ChallengesSubtaskReports,
-    challenges_subtasks::Entity as ChallengesSubtasks, challenges_tasks::Entity as ChallengesTasks,
+    challenges_subtask_revisions::Entity as ChallengesSubtaskRevisions,
+    challenges_subtask_sessions::Entity as ChallengesSubtaskSessions,
+    challenges_subtasks::Entity as ChallengesSubtasks,
+    challenges_task_completions::Entity as ChallengesTaskCompletions,
+    challenges_task_config::Entity as ChallengesTaskConfig,
+    challenges_tasks::Entity as ChallengesTasks,
+    challenges_true_false_attempts::Entity as ChallengesTrueFalseAttempts,
+    challenges_true_false_statements::Entity as ChallengesTrueFalseStatements,
+    challenges_user_bookmarks::Entity as ChallengesUserBookmarks,
+    challenges_user_notes::Entity as ChallengesUserNotes,
+    challenges_user_paths::Entity as ChallengesUserPaths,
     challenges_user_subtasks::Entity as ChallengesUserSubtasks,
+    challenges_wrong_answers::Entity as ChallengesWrongAnswers,
 };
@@ -11,6 +11,7 @@ use sea_orm::entity::prelude::*;
     DeriveActiveEnum,
     Copy,
     poem_openapi::Enum,
+    async_graphql::Enum,
     serde::Serialize,
     serde::Deserialize,
 )]
@@ -26,6 +27,8 @@ pub enum ChallengesBanAction {
     Create,
     #[sea_orm(string_value = "report")]
     Report,
+    #[sea_orm(string_value = "shadow_create")]
+    ShadowCreate,
 }
 #[derive(
     Debug,
@@ -36,6 +39,241 @@ pub enum ChallengesBanAction {
     DeriveActiveEnum,
     Copy,
     poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_cheat_flag_kind"
+)]
+pub enum ChallengesCheatFlagKind {
+    #[sea_orm(string_value = "identical_wrong_answers")]
+    IdenticalWrongAnswers,
+    #[sea_orm(string_value = "rapid_solves")]
+    RapidSolves,
+    #[sea_orm(string_value = "solved_before_unlock")]
+    SolvedBeforeUnlock,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_cheat_flag_status"
+)]
+pub enum ChallengesCheatFlagStatus {
+    #[sea_orm(string_value = "confirmed")]
+    Confirmed,
+    #[sea_orm(string_value = "dismissed")]
+    Dismissed,
+    #[sea_orm(string_value = "open")]
+    Open,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_comparison"
+)]
+pub enum ChallengesComparison {
+    #[sea_orm(string_value = "checker")]
+    Checker,
+    #[sea_orm(string_value = "evaluator")]
+    Evaluator,
+    #[sea_orm(string_value = "exact")]
+    Exact,
+    #[sea_orm(string_value = "float")]
+    Float,
+    #[sea_orm(string_value = "tokens")]
+    Tokens,
+    #[sea_orm(string_value = "trimmed_lines")]
+    TrimmedLines,
+    #[sea_orm(string_value = "unordered_lines")]
+    UnorderedLines,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_feedback_level"
+)]
+pub enum ChallengesFeedbackLevel {
+    #[sea_orm(string_value = "verdict_only")]
+    VerdictOnly,
+    #[sea_orm(string_value = "first_failed_seed")]
+    FirstFailedSeed,
+    #[sea_orm(string_value = "full_diff")]
+    FullDiff,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_held_reward_status"
+)]
+pub enum ChallengesHeldRewardStatus {
+    #[sea_orm(string_value = "held")]
+    Held,
+    #[sea_orm(string_value = "released")]
+    Released,
+    #[sea_orm(string_value = "voided")]
+    Voided,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "challenges_license")]
+pub enum ChallengesLicense {
+    #[sea_orm(string_value = "all_rights_reserved")]
+    AllRightsReserved,
+    #[sea_orm(string_value = "cc0")]
+    Cc0,
+    #[sea_orm(string_value = "cc_by")]
+    CcBy,
+    #[sea_orm(string_value = "cc_by_nc")]
+    CcByNc,
+    #[sea_orm(string_value = "cc_by_nc_sa")]
+    CcByNcSa,
+    #[sea_orm(string_value = "cc_by_sa")]
+    CcBySa,
+    #[sea_orm(string_value = "mit")]
+    Mit,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_organization_role"
+)]
+pub enum ChallengesOrganizationRole {
+    #[sea_orm(string_value = "admin")]
+    Admin,
+    #[sea_orm(string_value = "creator")]
+    Creator,
+    #[sea_orm(string_value = "member")]
+    Member,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_question_answer_type"
+)]
+pub enum ChallengesQuestionAnswerType {
+    #[sea_orm(string_value = "numeric")]
+    Numeric,
+    #[sea_orm(string_value = "text")]
+    Text,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
     serde::Serialize,
     serde::Deserialize,
 )]
@@ -59,6 +297,7 @@ pub enum ChallengesRating {
     DeriveActiveEnum,
     Copy,
     poem_openapi::Enum,
+    async_graphql::Enum,
     serde::Serialize,
     serde::Deserialize,
 )]
@@ -90,6 +329,35 @@ pub enum ChallengesReportReason {
     DeriveActiveEnum,
     Copy,
     poem_openapi::Enum,
+    async_graphql::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_review_status"
+)]
+pub enum ChallengesReviewStatus {
+    #[sea_orm(string_value = "approved")]
+    Approved,
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "rejected")]
+    Rejected,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    async_graphql::Enum,
     serde::Serialize,
     serde::Deserialize,
 )]
@@ -101,14 +369,24 @@ pub enum ChallengesReportReason {
     enum_name = "challenges_subtask_type"
 )]
 pub enum ChallengesSubtaskType {
+    #[sea_orm(string_value = "cloze")]
+    Cloze,
+    #[sea_orm(string_value = "code_reading")]
+    CodeReading,
     #[sea_orm(string_value = "coding_challenge")]
     CodingChallenge,
     #[sea_orm(string_value = "matching")]
     Matching,
     #[sea_orm(string_value = "multiple_choice_question")]
     MultipleChoiceQuestion,
+    #[sea_orm(string_value = "ordering")]
+    Ordering,
     #[sea_orm(string_value = "question")]
     Question,
+    #[sea_orm(string_value = "sql_challenge")]
+    SqlChallenge,
+    #[sea_orm(string_value = "true_false")]
+    TrueFalse,
 }
 #[derive(
     Debug,
@@ -119,6 +397,7 @@ pub enum ChallengesSubtaskType {
     DeriveActiveEnum,
     Copy,
     poem_openapi::Enum,
+    async_graphql::Enum,
     serde::Serialize,
     serde::Deserialize,
 )]
@@ -126,8 +405,12 @@ pub enum ChallengesSubtaskType {
 #[oai(rename_all = "SCREAMING_SNAKE_CASE")]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "challenges_verdict")]
 pub enum ChallengesVerdict {
+    #[sea_orm(string_value = "cancelled")]
+    Cancelled,
     #[sea_orm(string_value = "compilation_error")]
     CompilationError,
+    #[sea_orm(string_value = "internal_error")]
+    InternalError,
     #[sea_orm(string_value = "invalid_output_format")]
     InvalidOutputFormat,
     #[sea_orm(string_value = "memory_limit_exceeded")]
@@ -136,6 +419,8 @@ pub enum ChallengesVerdict {
     NoOutput,
     #[sea_orm(string_value = "ok")]
     Ok,
+    #[sea_orm(string_value = "partially_correct")]
+    PartiallyCorrect,
     #[sea_orm(string_value = "pre_check_failed")]
     PreCheckFailed,
     #[sea_orm(string_value = "runtime_error")]
@@ -47,9 +47,13 @@
               ./lib
               ./schemas
               ./challenges
+              ./challenges-client
+              ./proto
+              ./testing
             ];
           };
           cargoLock.lockFile = ./Cargo.lock;
+          nativeBuildInputs = [pkgs.protobuf];
           doCheck = false;
         };
     });
@@ -112,6 +116,7 @@
             ++ [
               just
               postgresql
+              protobuf
               redis
               bacon
               sea-orm-cli
@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
+use entity::{challenges_organization_members, sea_orm_active_enums::ChallengesOrganizationRole};
 use poem::Request;
 use poem_ext::{add_response_schemas, custom_auth, response};
-use poem_openapi::auth::Bearer;
+use poem_openapi::auth::{Bearer, BearerAuthorization};
+use sea_orm::EntityTrait;
 use tracing::debug;
 use uuid::Uuid;
 
@@ -11,11 +13,42 @@ use crate::{
     SharedState,
 };
 
-#[derive(Debug)]
+/// The header containing the id of the organization a request is made in.
+pub const ORGANIZATION_HEADER: &str = "x-organization-id";
+
+#[derive(Debug, Clone)]
 pub struct User {
     pub id: Uuid,
     pub email_verified: bool,
+    /// Whether the user is a platform admin. Platform admins are not
+    /// restricted to the content of their organization.
     pub admin: bool,
+    /// The organization selected via the `X-Organization-Id` header.
+    pub organization: Option<Organization>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Organization {
+    pub id: Uuid,
+    /// The role of the user in the organization. Platform admins can act in
+    /// organizations they are not a member of.
+    pub role: Option<ChallengesOrganizationRole>,
+}
+
+impl User {
+    /// Return the id of the organization the user is acting in.
+    pub fn organization_id(&self) -> Option<Uuid> {
+        self.organization.map(|x| x.id)
+    }
+
+    /// Whether the user can manage the members and content of the
+    /// organization they are acting in.
+    pub fn is_organization_admin(&self) -> bool {
+        self.admin
+            || self
+                .organization
+                .is_some_and(|x| x.role == Some(ChallengesOrganizationRole::Admin))
+    }
 }
 
 #[derive(Debug)]
@@ -49,10 +82,32 @@ async fn user_auth_check(
     {
         return Err(UserAuthError::raw::unauthorized());
     }
+
+    let organization = match req.headers().get(ORGANIZATION_HEADER) {
+        Some(value) => {
+            let id = value
+                .to_str()
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .ok_or_else(UserAuthError::raw::not_a_member)?;
+            let role = challenges_organization_members::Entity::find_by_id((id, user.uid))
+                .one(&data.db)
+                .await
+                .expect("could not load organization membership")
+                .map(|member| member.role);
+            if role.is_none() && !user.data.admin {
+                return Err(UserAuthError::raw::not_a_member());
+            }
+            Some(Organization { id, role })
+        }
+        None => None,
+    };
+
     Ok(User {
         id: user.uid,
         email_verified: user.data.email_verified,
         admin: user.data.admin,
+        organization,
     })
 }
 
@@ -78,6 +133,12 @@ async fn admin_auth_check(
     }
 }
 
+/// Authenticate a request that is not handled by the OpenAPI service.
+pub async fn authenticate(req: &Request) -> Option<User> {
+    let token = Bearer::from_request(req).ok();
+    user_auth_check(req, token).await.ok()
+}
+
 custom_auth!(PublicAuth, |req, token| async move {
     match user_auth_check(req, token).await {
         Ok(user) => Ok::<_, UserAuthError::raw::Response>(Some(user)),
@@ -98,6 +159,9 @@ add_response_schemas!(AdminAuth, AdminAuthError::raw::Response);
 response!(UserAuthError = {
     /// The user is unauthenticated.
     Unauthorized(401, error),
+    /// The user is not a member of the organization selected via the
+    /// `X-Organization-Id` header.
+    NotAMember(403, error),
 });
 
 response!(VerifiedUserAuthError = {
@@ -1,22 +1,63 @@
-use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use super::Sentry;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ChallengesConfig {
     pub host: String,
     pub port: u16,
     pub server: String,
+    /// If set, the routes without a version prefix (which are aliases of the
+    /// `/v1` routes) are marked as deprecated and announced to be removed
+    /// after this time.
+    pub unversioned_sunset: Option<DateTime<Utc>>,
     pub sentry: Option<Sentry>,
+    pub graphql: Option<GraphQl>,
+    pub grpc: Option<Grpc>,
     pub quizzes: Quizzes, // course tasks
     pub multiple_choice_questions: MultipleChoiceQuestions,
     pub questions: Questions,
     pub matchings: Matchings,
+    pub true_false: TrueFalse,
+    pub orderings: Orderings,
+    pub clozes: Clozes,
+    pub code_readings: CodeReadings,
+    pub sql_challenges: SqlChallenges,
     pub coding_challenges: CodingChallenges,
+    pub reviews: Reviews,
+    pub re_solve: ReSolve,
+    pub due_dates: DueDates,
+    pub notifications: Notifications,
+    pub creator_digest: CreatorDigest,
+    pub anti_cheat: AntiCheat,
+    pub moderation: Moderation,
+    pub quotas: Quotas,
+    pub rewards: Rewards,
+    pub content_filter: ContentFilter,
+    pub reveal: Reveal,
+    pub unlock: Unlock,
+    pub slow_log: SlowLog,
+    pub public_api: PublicApi,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GraphQl {
+    pub playground: bool,
+    pub max_depth: usize,
+    pub max_complexity: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Grpc {
+    pub host: String,
+    pub port: u16,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Quizzes {
     pub min_level: u32,
     pub max_xp: u64,
@@ -24,32 +65,237 @@ pub struct Quizzes {
     pub ban_days: Vec<u32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct MultipleChoiceQuestions {
     pub timeout: u64,
     pub hearts: u32,
     pub creator_coins: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Questions {
     pub timeout: u64,
     pub hearts: u32,
     pub creator_coins: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Matchings {
     pub timeout: u64,
     pub hearts: u32,
     pub creator_coins: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TrueFalse {
+    pub timeout: u64,
+    pub hearts: u32,
+    pub creator_coins: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Orderings {
+    pub timeout: u64,
+    pub hearts: u32,
+    pub creator_coins: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Clozes {
+    pub timeout: u64,
+    pub hearts: u32,
+    pub creator_coins: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CodeReadings {
+    pub timeout: u64,
+    pub hearts: u32,
+    pub creator_coins: u32,
+    pub time_limit: u64,
+    pub memory_limit: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SqlChallenges {
+    pub timeout: u64,
+    pub hearts: u32,
+    pub creator_coins: u32,
+    pub max_rows: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct CodingChallenges {
     pub sandkasten_url: Url,
     pub max_concurrency: usize,
+    pub judge_lease: u64,
+    /// The number of times the judge workers try to judge a submission before
+    /// it is marked as failed.
+    pub judge_attempts: u32,
     pub timeout: u64,
     pub hearts: u32,
     pub creator_coins: u32,
+    pub max_artifact_size: usize,
+    pub max_submission_size: usize,
+    pub duplicate_window: u64,
+    pub max_pending_submissions: usize,
+    /// The maximum number of times examples can be generated for a single user
+    /// and a single challenge within `example_generation_window` seconds.
+    /// Cached examples do not count towards these limits.
+    pub example_generations_per_user: u64,
+    pub example_generations_per_challenge: u64,
+    pub example_generation_window: u64,
+    /// The cpu time (in seconds) a single challenge may use in the sandbox
+    /// each month before no new examples are generated for it.
+    pub monthly_cpu_budget: Option<u64>,
+    pub sandbox_mode: SandboxMode,
+    pub recordings: PathBuf,
+    pub build_flags: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxMode {
+    /// Send all requests to Sandkasten.
+    Live,
+    /// Send all requests to Sandkasten and store the responses.
+    Record,
+    /// Only respond with previously recorded responses.
+    Replay,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Reviews {
+    pub xp: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReSolve {
+    pub enabled: bool,
+    pub period: u32,
+    pub xp_percent: u8,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DueDates {
+    /// Percentage of the xp and coins awarded for solving a subtask of an
+    /// assigned task after its due date.
+    pub late_percent: u8,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Notifications {
+    /// Number of minutes before the start of a contest to notify users.
+    pub contest_reminder: u64,
+    /// Interval (in seconds) in which upcoming contests are checked.
+    pub poll_interval: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatorDigest {
+    pub enabled: bool,
+    /// Interval (in seconds) in which the job checks whether the digest of the
+    /// last week has been sent.
+    pub poll_interval: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AntiCheat {
+    pub enabled: bool,
+    /// Interval (in seconds) in which recent attempts are checked.
+    pub poll_interval: u64,
+    /// Number of hours of attempts which are checked in each run.
+    pub lookback: u64,
+    /// Number of solves within `rapid_solves_window` seconds that is
+    /// considered suspicious.
+    pub rapid_solves_count: usize,
+    pub rapid_solves_window: u64,
+    /// Minimum length of identical sequences of wrong answers.
+    pub identical_wrong_answers: usize,
+    /// Whether rewards of flagged users are held until the flag has been
+    /// reviewed.
+    pub hold_rewards: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Moderation {
+    /// Whether subtasks created by non-admins have to be approved by a
+    /// moderator before they are enabled.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Quotas {
+    /// The maximum number of subtasks a user can create within 24 hours.
+    pub max_per_day: Option<u64>,
+    /// The maximum number of subtasks a user can create within 7 days.
+    pub max_per_week: Option<u64>,
+    /// The maximum number of subtasks of a user that can be pending review at
+    /// the same time.
+    pub max_pending: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rewards {
+    /// If set, rewards are only logged and recorded in the reward ledger
+    /// instead of being sent to the skills and shop services.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ContentFilter {
+    /// Whether texts created by non-admins (e.g. questions, report comments
+    /// and answers) are checked.
+    pub enabled: bool,
+    pub action: ContentFilterAction,
+    /// Words which are not allowed (case insensitive).
+    pub words: Vec<String>,
+    /// Whether email addresses and phone numbers are detected.
+    pub pii: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFilterAction {
+    /// Reject requests containing violations.
+    Reject,
+    /// Replace violations with asterisks.
+    Mask,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Reveal {
+    /// The number of coins a user has to pay to reveal the solution of a
+    /// subtask before they have used all attempts.
+    pub price: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Unlock {
+    /// The discount in percent on the combined price when unlocking multiple
+    /// subtasks of a task at once.
+    pub discount_percent: u8,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SlowLog {
+    /// Requests that take longer than this number of milliseconds are logged.
+    pub request_threshold: u64,
+    /// Database queries that take longer than this number of milliseconds are
+    /// logged.
+    pub query_threshold: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PublicApi {
+    /// Whether the read-only endpoints under `/public` can be used without
+    /// authentication. This also enables `/feed.json` and `/sitemap.xml`.
+    pub enabled: bool,
+    /// The maximum number of items in the feed.
+    pub feed_size: usize,
+    /// Number of seconds between two updates of the feed and the sitemap.
+    pub feed_interval: u64,
+    /// The url of a task in the sitemap. `{task_id}` is replaced with the id
+    /// of the task.
+    pub task_url: String,
 }
@@ -0,0 +1,72 @@
+//! Deprecation of routes.
+//!
+//! Responses of deprecated routes contain a `Deprecation` header and, if the
+//! route is going to be removed at a known date, a `Sunset` header (RFC 8594).
+//! Single operations can be deprecated with
+//! `#[oai(deprecated, transform = "deprecated")]`.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use poem::{http::HeaderValue, Endpoint, EndpointExt, IntoResponse, Request, Response};
+
+#[derive(Debug, Clone, Default)]
+pub struct Deprecation {
+    /// The time after which the route may be removed.
+    pub sunset: Option<DateTime<Utc>>,
+    /// The url of the route that replaces the deprecated one.
+    pub successor: Option<String>,
+}
+
+/// Mark all responses of an endpoint as deprecated.
+pub fn deprecated<E: Endpoint + 'static>(ep: E) -> impl Endpoint {
+    let deprecation = Arc::new(Deprecation::default());
+    ep.around(move |ep, req| add_deprecation_headers(ep, req, Arc::clone(&deprecation)))
+}
+
+/// Middleware that adds the `Deprecation`, `Sunset` and `Link` headers to
+/// responses.
+pub async fn add_deprecation_headers<E: Endpoint>(
+    ep: Arc<E>,
+    req: Request,
+    deprecation: Arc<Deprecation>,
+) -> poem::Result<Response> {
+    let mut resp = match ep.call(req).await {
+        Ok(resp) => resp.into_response(),
+        Err(err) => err.into_response(),
+    };
+    let headers = resp.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(value) = deprecation
+        .sunset
+        .and_then(|x| HeaderValue::from_str(&http_date(x)).ok())
+    {
+        headers.insert("sunset", value);
+    }
+    if let Some(value) = deprecation
+        .successor
+        .as_deref()
+        .and_then(|x| HeaderValue::from_str(&format!("<{x}>; rel=\"successor-version\"")).ok())
+    {
+        headers.append("link", value);
+    }
+    Ok(resp)
+}
+
+/// Format a timestamp as an http date (e.g. `Sun, 06 Nov 1994 08:49:37 GMT`).
+fn http_date(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_http_date() {
+        let timestamp = Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap();
+        assert_eq!(http_date(timestamp), "Sun, 06 Nov 1994 08:49:37 GMT");
+    }
+}
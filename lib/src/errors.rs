@@ -0,0 +1,757 @@
+//! Machine-readable error codes.
+//!
+//! Error responses generated by [`poem_ext::response!`] contain the name of
+//! the error variant in the `error` field. The [`add_error_codes`] middleware
+//! copies this name to the `error_code` field and adds a human-readable
+//! `message` if the code is listed in [`ERROR_CODES`]. Codes are never renamed
+//! or reused, so clients should match on `error_code` instead of the HTTP
+//! status or the message.
+//!
+//! Messages are available in English and German ([`ERROR_CODES_DE`]). The
+//! language is negotiated via the `Accept-Language` header of the request.
+
+use std::sync::Arc;
+
+use poem::{
+    http::{header, HeaderMap, HeaderValue},
+    Endpoint, FromRequest, IntoResponse, Request, RequestBody, Response,
+};
+use serde_json::Value;
+
+/// All error codes returned by the API together with a human-readable
+/// message, sorted by code.
+pub const ERROR_CODES: &[(&str, &str)] = &[
+    ("already_claimed", "The rewards have already been claimed."),
+    (
+        "already_released",
+        "The results have already been released.",
+    ),
+    ("already_reviewed", "The flag has already been reviewed."),
+    ("artifacts_not_found", "The submission has no artifacts."),
+    (
+        "assignment_not_found",
+        "The task has not been assigned to this cohort.",
+    ),
+    (
+        "attempts_exhausted",
+        "All attempts of the subtask have been used.",
+    ),
+    (
+        "attribution_required",
+        "The license of the subtask requires an attribution.",
+    ),
+    ("ban_not_found", "The ban does not exist."),
+    ("banned", "The user is currently banned."),
+    ("bookmark_not_found", "The subtask has not been bookmarked."),
+    (
+        "build_config_not_found",
+        "The build configuration does not exist.",
+    ),
+    ("category_not_found", "The category does not exist."),
+    ("challenge_not_found", "The challenge does not exist."),
+    ("cohort_not_found", "The cohort does not exist."),
+    (
+        "coin_limit_exceeded",
+        "The maximum number of coins has been exceeded.",
+    ),
+    (
+        "content_violation",
+        "The text contains content which is not allowed.",
+    ),
+    ("contest_not_found", "The contest does not exist."),
+    ("course_not_found", "The course does not exist."),
+    ("course_task_not_found", "The course task does not exist."),
+    ("cycle", "The prerequisites would form a cycle."),
+    ("duplicate", "A very similar subtask already exists."),
+    (
+        "duplicate_file_name",
+        "Multiple files of the submission have the same name.",
+    ),
+    (
+        "duplicate_submission",
+        "The submission is identical to the previous one.",
+    ),
+    (
+        "duplicate_subtask",
+        "A subtask occurs multiple times in the request.",
+    ),
+    ("environment_disabled", "The environment has been disabled."),
+    (
+        "environment_not_disabled",
+        "The environment is not disabled.",
+    ),
+    ("environment_not_found", "The environment does not exist."),
+    ("evaluator_failed", "The evaluator failed."),
+    ("exam_not_found", "The exam does not exist."),
+    ("exam_not_over", "The exam is not over yet."),
+    (
+        "exam_running",
+        "Coding challenges cannot be submitted during an exam.",
+    ),
+    (
+        "example_generation_failed",
+        "The examples could not be generated.",
+    ),
+    ("example_not_found", "The example does not exist."),
+    (
+        "flag_not_allowed",
+        "The build flag is not allowed for this environment.",
+    ),
+    ("flag_not_found", "The flag does not exist."),
+    (
+        "forbidden",
+        "The user is not allowed to perform this action.",
+    ),
+    ("internal_server_error", "An unexpected error occurred."),
+    ("invalid_char", "The input contains an invalid character."),
+    ("invalid_config", "The configuration could not be loaded."),
+    ("invalid_format", "The input could not be parsed."),
+    ("invalid_index", "An index is out of range."),
+    ("invalid_invite_code", "The invite code is invalid."),
+    ("invalid_item", "An item is invalid."),
+    (
+        "invalid_multiple_choice",
+        "The answers of the multiple choice question are invalid.",
+    ),
+    ("invalid_number", "The input is not a valid number."),
+    ("invalid_output", "The output is invalid."),
+    ("invalid_query", "The query could not be executed."),
+    (
+        "invalid_schema",
+        "The schema or the seed data could not be loaded.",
+    ),
+    (
+        "invalid_single_choice",
+        "A single choice question must have exactly one correct answer.",
+    ),
+    (
+        "invalid_solve_token",
+        "The solve token is missing or invalid.",
+    ),
+    ("invalid_template", "A template could not be evaluated."),
+    (
+        "items_not_ordered",
+        "One or more items are missing from the solution.",
+    ),
+    ("lecture_not_found", "The lecture does not exist."),
+    (
+        "lecture_without_section",
+        "The lecture does not belong to a section.",
+    ),
+    (
+        "left_right_different_length",
+        "The left and right entries have different lengths.",
+    ),
+    ("member_not_found", "The user is not a member."),
+    (
+        "memory_limit_exceeded",
+        "The memory limit has been exceeded.",
+    ),
+    ("negative_duration", "The end must not be before the start."),
+    ("no_checker", "The challenge has no checker."),
+    ("no_examples", "There are no examples."),
+    ("no_filter", "At least one filter has to be specified."),
+    ("no_reporter", "The report has no reporter."),
+    ("no_static_tests", "The challenge has no static tests."),
+    ("no_subtask_available", "There is no subtask available."),
+    ("not_a_course_task", "The task is not a course task."),
+    (
+        "not_a_member",
+        "The user is not a member of the selected organization.",
+    ),
+    ("not_completed", "Not all items have been completed yet."),
+    ("not_due", "The review is not due yet."),
+    ("not_enough_coins", "The user does not have enough coins."),
+    ("not_enough_hearts", "The user does not have enough hearts."),
+    ("not_found", "The resource does not exist."),
+    ("not_golf", "The coding challenge is not a golf challenge."),
+    (
+        "not_output_only",
+        "The challenge is not an output-only challenge.",
+    ),
+    ("not_pending", "The subtask is not pending review."),
+    ("not_solved", "The subtask has not been solved yet."),
+    ("note_not_found", "The user has no note on this subtask."),
+    ("organization_not_found", "The organization does not exist."),
+    (
+        "output_only",
+        "Output-only challenges do not accept source code.",
+    ),
+    ("path_not_found", "The learning path does not exist."),
+    (
+        "permission_denied",
+        "The user is not allowed to perform this action.",
+    ),
+    ("prerequisite_not_found", "A prerequisite does not exist."),
+    (
+        "program_failed",
+        "The program did not terminate successfully.",
+    ),
+    ("protocol_violation", "The evaluator violated its protocol."),
+    (
+        "quota_exceeded",
+        "The user has exceeded a quota for creating subtasks.",
+    ),
+    ("report_not_found", "The report does not exist."),
+    (
+        "results_not_released",
+        "The results have not been released yet.",
+    ),
+    ("review_not_found", "The review does not exist."),
+    (
+        "revision_not_found",
+        "No revision of the subtask exists at this time.",
+    ),
+    (
+        "right_entries_not_matched",
+        "Not all right entries are matched.",
+    ),
+    ("section_not_found", "The section does not exist."),
+    ("skills_not_found", "The skills do not exist."),
+    (
+        "solution_different_length",
+        "The solution has a different length.",
+    ),
+    ("submission_not_found", "The submission does not exist."),
+    (
+        "submission_not_pending",
+        "The submission is not waiting to be judged.",
+    ),
+    (
+        "submission_too_large",
+        "The files of the submission exceed the maximum size.",
+    ),
+    ("subtask_not_found", "The subtask does not exist."),
+    ("target_task_not_found", "The target task does not exist."),
+    ("task_in_use", "Other data depends on the task."),
+    ("task_not_found", "The task does not exist."),
+    ("testcase_failed", "A test case failed."),
+    ("time_limit_exceeded", "The time limit has been exceeded."),
+    (
+        "too_many_pending_submissions",
+        "The user has too many submissions waiting to be judged.",
+    ),
+    (
+        "too_many_requests",
+        "Too many requests. Please try again later.",
+    ),
+    ("unauthorized", "The user is not authenticated."),
+    (
+        "unprocessable_content",
+        "The request could not be processed.",
+    ),
+    (
+        "unverified",
+        "The email address of the user has not been verified.",
+    ),
+    ("user_not_found", "The user does not exist."),
+    ("version_not_found", "The version does not exist."),
+    ("wrong_answer_type", "The answer has the wrong type."),
+    (
+        "wrong_blank_count",
+        "The number of placeholders does not match the number of blanks.",
+    ),
+    ("wrong_length", "The answer has the wrong length."),
+    (
+        "xp_limit_exceeded",
+        "The maximum number of xp has been exceeded.",
+    ),
+];
+
+/// German messages of all error codes, sorted by code.
+pub const ERROR_CODES_DE: &[(&str, &str)] = &[
+    (
+        "already_claimed",
+        "Die Belohnungen wurden bereits abgeholt.",
+    ),
+    (
+        "already_released",
+        "Die Ergebnisse wurden bereits veröffentlicht.",
+    ),
+    ("already_reviewed", "Die Markierung wurde bereits geprüft."),
+    (
+        "artifacts_not_found",
+        "Die Einreichung hat keine Artefakte.",
+    ),
+    (
+        "assignment_not_found",
+        "Die Aufgabe wurde dieser Kohorte nicht zugewiesen.",
+    ),
+    (
+        "attempts_exhausted",
+        "Alle Versuche der Aufgabe wurden aufgebraucht.",
+    ),
+    (
+        "attribution_required",
+        "Die Lizenz der Aufgabe erfordert eine Namensnennung.",
+    ),
+    ("ban_not_found", "Die Sperre existiert nicht."),
+    ("banned", "Der Benutzer ist derzeit gesperrt."),
+    (
+        "bookmark_not_found",
+        "Die Aufgabe wurde nicht als Lesezeichen gespeichert.",
+    ),
+    (
+        "build_config_not_found",
+        "Die Build-Konfiguration existiert nicht.",
+    ),
+    ("category_not_found", "Die Kategorie existiert nicht."),
+    ("challenge_not_found", "Die Challenge existiert nicht."),
+    ("cohort_not_found", "Die Kohorte existiert nicht."),
+    (
+        "coin_limit_exceeded",
+        "Die maximale Anzahl an Coins wurde überschritten.",
+    ),
+    ("content_violation", "Der Text enthält unzulässige Inhalte."),
+    ("contest_not_found", "Der Wettbewerb existiert nicht."),
+    ("course_not_found", "Der Kurs existiert nicht."),
+    ("course_task_not_found", "Die Kursaufgabe existiert nicht."),
+    ("cycle", "Die Voraussetzungen würden einen Zyklus bilden."),
+    ("duplicate", "Eine sehr ähnliche Aufgabe existiert bereits."),
+    (
+        "duplicate_file_name",
+        "Mehrere Dateien der Einreichung haben denselben Namen.",
+    ),
+    (
+        "duplicate_submission",
+        "Die Einreichung ist identisch mit der vorherigen.",
+    ),
+    (
+        "duplicate_subtask",
+        "Eine Aufgabe kommt mehrfach in der Anfrage vor.",
+    ),
+    ("environment_disabled", "Die Umgebung wurde deaktiviert."),
+    (
+        "environment_not_disabled",
+        "Die Umgebung ist nicht deaktiviert.",
+    ),
+    ("environment_not_found", "Die Umgebung existiert nicht."),
+    ("evaluator_failed", "Der Evaluator ist fehlgeschlagen."),
+    ("exam_not_found", "Die Prüfung existiert nicht."),
+    ("exam_not_over", "Die Prüfung ist noch nicht vorbei."),
+    (
+        "exam_running",
+        "Während einer Prüfung können keine Coding Challenges eingereicht werden.",
+    ),
+    (
+        "example_generation_failed",
+        "Die Beispiele konnten nicht generiert werden.",
+    ),
+    ("example_not_found", "Das Beispiel existiert nicht."),
+    (
+        "flag_not_allowed",
+        "Das Build-Flag ist für diese Umgebung nicht erlaubt.",
+    ),
+    ("flag_not_found", "Die Markierung existiert nicht."),
+    (
+        "forbidden",
+        "Der Benutzer darf diese Aktion nicht ausführen.",
+    ),
+    (
+        "internal_server_error",
+        "Ein unerwarteter Fehler ist aufgetreten.",
+    ),
+    (
+        "invalid_char",
+        "Die Eingabe enthält ein ungültiges Zeichen.",
+    ),
+    (
+        "invalid_config",
+        "Die Konfiguration konnte nicht geladen werden.",
+    ),
+    (
+        "invalid_format",
+        "Die Eingabe konnte nicht verarbeitet werden.",
+    ),
+    (
+        "invalid_index",
+        "Ein Index liegt außerhalb des gültigen Bereichs.",
+    ),
+    ("invalid_invite_code", "Der Einladungscode ist ungültig."),
+    ("invalid_item", "Ein Element ist ungültig."),
+    (
+        "invalid_multiple_choice",
+        "Die Antworten der Multiple-Choice-Frage sind ungültig.",
+    ),
+    ("invalid_number", "Die Eingabe ist keine gültige Zahl."),
+    ("invalid_output", "Die Ausgabe ist ungültig."),
+    (
+        "invalid_query",
+        "Die Abfrage konnte nicht ausgeführt werden.",
+    ),
+    (
+        "invalid_schema",
+        "Das Schema oder die Startdaten konnten nicht geladen werden.",
+    ),
+    (
+        "invalid_single_choice",
+        "Eine Single-Choice-Frage muss genau eine richtige Antwort haben.",
+    ),
+    (
+        "invalid_solve_token",
+        "Das Lösungstoken fehlt oder ist ungültig.",
+    ),
+    (
+        "invalid_template",
+        "Eine Vorlage konnte nicht ausgewertet werden.",
+    ),
+    (
+        "items_not_ordered",
+        "Ein oder mehrere Elemente fehlen in der Lösung.",
+    ),
+    ("lecture_not_found", "Die Lektion existiert nicht."),
+    (
+        "lecture_without_section",
+        "Die Lektion gehört zu keinem Abschnitt.",
+    ),
+    (
+        "left_right_different_length",
+        "Die linken und rechten Einträge sind unterschiedlich lang.",
+    ),
+    ("member_not_found", "Der Benutzer ist kein Mitglied."),
+    (
+        "memory_limit_exceeded",
+        "Das Speicherlimit wurde überschritten.",
+    ),
+    (
+        "negative_duration",
+        "Das Ende darf nicht vor dem Beginn liegen.",
+    ),
+    ("no_checker", "Die Challenge hat keinen Checker."),
+    ("no_examples", "Es gibt keine Beispiele."),
+    (
+        "no_filter",
+        "Es muss mindestens ein Filter angegeben werden.",
+    ),
+    ("no_reporter", "Die Meldung hat keinen Melder."),
+    (
+        "no_static_tests",
+        "Die Challenge hat keine statischen Tests.",
+    ),
+    ("no_subtask_available", "Es ist keine Aufgabe verfügbar."),
+    ("not_a_course_task", "Die Aufgabe ist keine Kursaufgabe."),
+    (
+        "not_a_member",
+        "Der Benutzer ist kein Mitglied der ausgewählten Organisation.",
+    ),
+    (
+        "not_completed",
+        "Es wurden noch nicht alle Elemente abgeschlossen.",
+    ),
+    ("not_due", "Die Wiederholung ist noch nicht fällig."),
+    ("not_enough_coins", "Der Benutzer hat nicht genug Coins."),
+    ("not_enough_hearts", "Der Benutzer hat nicht genug Herzen."),
+    ("not_found", "Die Ressource existiert nicht."),
+    ("not_golf", "Die Coding Challenge ist keine Golf-Challenge."),
+    (
+        "not_output_only",
+        "Die Challenge ist keine Output-only-Challenge.",
+    ),
+    ("not_pending", "Die Aufgabe wartet nicht auf eine Prüfung."),
+    ("not_solved", "Die Aufgabe wurde noch nicht gelöst."),
+    (
+        "note_not_found",
+        "Der Benutzer hat keine Notiz zu dieser Aufgabe.",
+    ),
+    (
+        "organization_not_found",
+        "Die Organisation existiert nicht.",
+    ),
+    (
+        "output_only",
+        "Output-only-Challenges akzeptieren keinen Quellcode.",
+    ),
+    ("path_not_found", "Der Lernpfad existiert nicht."),
+    (
+        "permission_denied",
+        "Der Benutzer darf diese Aktion nicht ausführen.",
+    ),
+    (
+        "prerequisite_not_found",
+        "Eine Voraussetzung existiert nicht.",
+    ),
+    (
+        "program_failed",
+        "Das Programm wurde nicht erfolgreich beendet.",
+    ),
+    (
+        "protocol_violation",
+        "Der Evaluator hat sein Protokoll verletzt.",
+    ),
+    (
+        "quota_exceeded",
+        "Der Benutzer hat ein Kontingent für das Erstellen von Aufgaben überschritten.",
+    ),
+    ("report_not_found", "Die Meldung existiert nicht."),
+    (
+        "results_not_released",
+        "Die Ergebnisse wurden noch nicht veröffentlicht.",
+    ),
+    ("review_not_found", "Die Wiederholung existiert nicht."),
+    (
+        "revision_not_found",
+        "Zu diesem Zeitpunkt existiert keine Version der Aufgabe.",
+    ),
+    (
+        "right_entries_not_matched",
+        "Nicht alle rechten Einträge wurden zugeordnet.",
+    ),
+    ("section_not_found", "Der Abschnitt existiert nicht."),
+    ("skills_not_found", "Die Skills existieren nicht."),
+    (
+        "solution_different_length",
+        "Die Lösung hat eine andere Länge.",
+    ),
+    ("submission_not_found", "Die Einreichung existiert nicht."),
+    (
+        "submission_not_pending",
+        "Die Einreichung wartet nicht auf eine Bewertung.",
+    ),
+    (
+        "submission_too_large",
+        "Die Dateien der Einreichung überschreiten die maximale Größe.",
+    ),
+    ("subtask_not_found", "Die Aufgabe existiert nicht."),
+    ("target_task_not_found", "Die Zielaufgabe existiert nicht."),
+    ("task_in_use", "Andere Daten hängen von der Aufgabe ab."),
+    ("task_not_found", "Die Aufgabe existiert nicht."),
+    ("testcase_failed", "Ein Testfall ist fehlgeschlagen."),
+    ("time_limit_exceeded", "Das Zeitlimit wurde überschritten."),
+    (
+        "too_many_pending_submissions",
+        "Der Benutzer hat zu viele Einreichungen, die auf eine Bewertung warten.",
+    ),
+    (
+        "too_many_requests",
+        "Zu viele Anfragen. Bitte versuche es später erneut.",
+    ),
+    ("unauthorized", "Der Benutzer ist nicht angemeldet."),
+    (
+        "unprocessable_content",
+        "Die Anfrage konnte nicht verarbeitet werden.",
+    ),
+    (
+        "unverified",
+        "Die E-Mail-Adresse des Benutzers wurde nicht bestätigt.",
+    ),
+    ("user_not_found", "Der Benutzer existiert nicht."),
+    ("version_not_found", "Die Version existiert nicht."),
+    ("wrong_answer_type", "Die Antwort hat den falschen Typ."),
+    (
+        "wrong_blank_count",
+        "Die Anzahl der Platzhalter stimmt nicht mit der Anzahl der Lücken überein.",
+    ),
+    ("wrong_length", "Die Antwort hat die falsche Länge."),
+    (
+        "xp_limit_exceeded",
+        "Die maximale Anzahl an XP wurde überschritten.",
+    ),
+];
+
+/// The languages error messages are available in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// Choose the language preferred by the client from the value of an
+    /// `Accept-Language` header. Falls back to English if none of the
+    /// requested languages is available.
+    pub fn negotiate(accept_language: &str) -> Self {
+        let mut best = None::<(f32, Self)>;
+        for range in accept_language.split(',') {
+            let mut parts = range.split(';');
+            let Some(language) = parts.next().and_then(Self::from_tag) else {
+                continue;
+            };
+            let quality = match parts.find_map(|x| x.trim().strip_prefix("q=")) {
+                Some(quality) => quality.trim().parse::<f32>().unwrap_or(0.0),
+                None => 1.0,
+            };
+            if quality > 0.0 && !best.is_some_and(|(q, _)| q >= quality) {
+                best = Some((quality, language));
+            }
+        }
+        best.map(|(_, language)| language).unwrap_or_default()
+    }
+
+    /// Choose the language preferred by the client from the headers of a
+    /// request.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|x| x.to_str().ok())
+            .map(Self::negotiate)
+            .unwrap_or_default()
+    }
+
+    /// Return the language of a language tag (e.g. `de-DE`).
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split('-').next()?;
+        if primary.eq_ignore_ascii_case("en") {
+            Some(Self::English)
+        } else if primary.eq_ignore_ascii_case("de") {
+            Some(Self::German)
+        } else {
+            None
+        }
+    }
+
+    /// Return the ISO 639-1 code of the language.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+        }
+    }
+
+    fn error_codes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::English => ERROR_CODES,
+            Self::German => ERROR_CODES_DE,
+        }
+    }
+}
+
+impl<'a> FromRequest<'a> for Language {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+        Ok(Self::from_headers(req.headers()))
+    }
+}
+
+/// Return the message of an error code.
+pub fn get_error_message(code: &str, language: Language) -> Option<&'static str> {
+    let codes = language.error_codes();
+    codes
+        .binary_search_by(|(x, _)| (*x).cmp(code))
+        .ok()
+        .map(|i| codes[i].1)
+}
+
+/// Middleware that adds `error_code` and `message` to json error responses.
+/// The message is translated to the language requested via `Accept-Language`.
+pub async fn add_error_codes<E: Endpoint>(ep: Arc<E>, req: Request) -> poem::Result<Response> {
+    let language = Language::from_headers(req.headers());
+    let resp = ep.call(req).await?.into_response();
+    if resp.status().is_success() || !is_json(resp.headers()) {
+        return Ok(resp);
+    }
+
+    let (mut parts, body) = resp.into_parts();
+    let body = body.into_bytes().await?;
+    let body = match serde_json::from_slice::<Value>(&body) {
+        Ok(mut value) => {
+            if add_error_code(&mut value, language) {
+                parts.headers.insert(
+                    header::CONTENT_LANGUAGE,
+                    HeaderValue::from_static(language.code()),
+                );
+                serde_json::to_vec(&value).map(Into::into).unwrap_or(body)
+            } else {
+                body
+            }
+        }
+        Err(_) => body,
+    };
+    Ok(Response::from_parts(parts, body.into()))
+}
+
+pub(crate) fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|x| x.to_str().ok())
+        .is_some_and(|x| x.starts_with("application/json"))
+}
+
+/// Add `error_code` and `message` to an error response body. Return whether
+/// the body has been modified.
+pub fn add_error_code(value: &mut Value, language: Language) -> bool {
+    let Some(object) = value.as_object_mut() else {
+        return false;
+    };
+    let Some(code) = object
+        .get("error")
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+    else {
+        return false;
+    };
+    if let Some(message) = get_error_message(&code, language) {
+        object.insert("message".into(), message.into());
+    }
+    object.insert("error_code".into(), code.into());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_error_codes_sorted() {
+        assert!(ERROR_CODES.windows(2).all(|x| x[0].0 < x[1].0));
+    }
+
+    #[test]
+    fn test_error_codes_translated() {
+        let codes = |x: &[(&'static str, &str)]| x.iter().map(|x| x.0).collect::<Vec<_>>();
+        assert_eq!(codes(ERROR_CODES), codes(ERROR_CODES_DE));
+    }
+
+    #[test]
+    fn test_negotiate_language() {
+        assert_eq!(Language::negotiate(""), Language::English);
+        assert_eq!(Language::negotiate("de"), Language::German);
+        assert_eq!(Language::negotiate("de-DE,de;q=0.9"), Language::German);
+        assert_eq!(
+            Language::negotiate("fr-FR, de;q=0.5, en;q=0.3"),
+            Language::German
+        );
+        assert_eq!(Language::negotiate("de;q=0.5, EN-us"), Language::English);
+        assert_eq!(Language::negotiate("en;q=0.8, de;q=0.8"), Language::English);
+        assert_eq!(Language::negotiate("de;q=0, fr"), Language::English);
+        assert_eq!(Language::negotiate("*"), Language::English);
+        assert_eq!(Language::negotiate("de;q=abc"), Language::English);
+    }
+
+    #[test]
+    fn test_add_error_code() {
+        let mut value = json!({"error": "subtask_not_found"});
+        assert!(add_error_code(&mut value, Language::English));
+        assert_eq!(
+            value,
+            json!({
+                "error": "subtask_not_found",
+                "error_code": "subtask_not_found",
+                "message": "The subtask does not exist.",
+            })
+        );
+
+        let mut value = json!({"error": "subtask_not_found"});
+        assert!(add_error_code(&mut value, Language::German));
+        assert_eq!(
+            value,
+            json!({
+                "error": "subtask_not_found",
+                "error_code": "subtask_not_found",
+                "message": "Die Aufgabe existiert nicht.",
+            })
+        );
+
+        let mut value = json!({"error": "something_new", "details": 42});
+        assert!(add_error_code(&mut value, Language::German));
+        assert_eq!(
+            value,
+            json!({"error": "something_new", "error_code": "something_new", "details": 42})
+        );
+
+        let mut value = json!({"foo": "bar"});
+        assert!(!add_error_code(&mut value, Language::English));
+        assert_eq!(value, json!({"foo": "bar"}));
+    }
+}
@@ -0,0 +1,276 @@
+//! Domain events which are published to the event bus.
+//!
+//! Events are first written to the outbox table in the same transaction as the
+//! change they describe and are then relayed to the message broker by the
+//! [`OutboxRelay`]. Events are only removed from the outbox after they have
+//! been delivered to the broker, so every event is delivered at least once.
+
+use std::time::Duration;
+
+use async_nats::{Client, HeaderMap};
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_event_outbox,
+    sea_orm_active_enums::{
+        ChallengesBanAction, ChallengesReportReason, ChallengesSubtaskType, ChallengesVerdict,
+    },
+};
+use sea_orm::{
+    sea_query::{LockBehavior, LockType},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, trace};
+use uuid::Uuid;
+
+use crate::config::EventBus;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum Event {
+    /// A user has solved a subtask for the first time.
+    #[serde(rename = "subtask.solved")]
+    SubtaskSolved {
+        user_id: Uuid,
+        task_id: Uuid,
+        subtask_id: Uuid,
+    },
+    /// A coding challenge submission has been judged.
+    #[serde(rename = "submission.judged")]
+    SubmissionJudged {
+        submission_id: Uuid,
+        user_id: Uuid,
+        subtask_id: Uuid,
+        verdict: ChallengesVerdict,
+    },
+    /// A new subtask has been created.
+    #[serde(rename = "subtask.created")]
+    SubtaskCreated {
+        subtask_id: Uuid,
+        task_id: Uuid,
+        creator: Uuid,
+        ty: ChallengesSubtaskType,
+    },
+    /// A user has been banned from performing an action.
+    #[serde(rename = "user.banned")]
+    UserBanned {
+        ban_id: Uuid,
+        user_id: Uuid,
+        action: ChallengesBanAction,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+    },
+    /// A notification should be delivered to a user.
+    #[serde(rename = "user.notification")]
+    UserNotification {
+        user_id: Uuid,
+        notification: Notification,
+    },
+}
+
+/// Notifications for users, which are delivered by the notification
+/// microservice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Notification {
+    /// Someone else has solved a subtask created by the user for the first
+    /// time.
+    SubtaskSolved {
+        task_id: Uuid,
+        subtask_id: Uuid,
+        solver: Uuid,
+    },
+    /// A subtask created by the user has been reported.
+    SubtaskReported {
+        task_id: Uuid,
+        subtask_id: Uuid,
+        reason: ChallengesReportReason,
+    },
+    /// A subtask created by the user has been disabled.
+    SubtaskDisabled { task_id: Uuid, subtask_id: Uuid },
+    /// A subtask created by the user has been rejected by a moderator.
+    SubtaskRejected {
+        task_id: Uuid,
+        subtask_id: Uuid,
+        reason: String,
+    },
+    /// A submission of the user has been judged.
+    SubmissionJudged {
+        submission_id: Uuid,
+        subtask_id: Uuid,
+        verdict: ChallengesVerdict,
+    },
+    /// A contest starts soon.
+    ContestStarting {
+        contest_id: Uuid,
+        title: String,
+        start: DateTime<Utc>,
+    },
+}
+
+impl Event {
+    pub fn subject(&self) -> &'static str {
+        match self {
+            Self::SubtaskSolved { .. } => "subtask.solved",
+            Self::SubmissionJudged { .. } => "submission.judged",
+            Self::SubtaskCreated { .. } => "subtask.created",
+            Self::UserBanned { .. } => "user.banned",
+            Self::UserNotification { .. } => "user.notification",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    id: Uuid,
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Add an event to the outbox. This should be called in the same transaction
+/// as the change the event describes, so the event is only published if the
+/// transaction is committed.
+pub async fn publish_event(db: &impl ConnectionTrait, event: Event) -> Result<(), DbErr> {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    let payload = serde_json::to_value(Envelope {
+        id,
+        timestamp: now,
+        event: &event,
+    })
+    .map_err(|err| DbErr::Custom(format!("failed to serialize event: {err}")))?;
+    challenges_event_outbox::ActiveModel {
+        id: Set(id),
+        subject: Set(event.subject().into()),
+        payload: Set(payload),
+        timestamp: Set(now.naive_utc()),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Relays events from the outbox to the message broker.
+pub struct OutboxRelay {
+    db: DatabaseConnection,
+    client: Client,
+    subject_prefix: String,
+    poll_interval: Duration,
+    batch_size: u64,
+}
+
+impl OutboxRelay {
+    pub async fn connect(
+        db: DatabaseConnection,
+        config: &EventBus,
+    ) -> Result<Self, async_nats::ConnectError> {
+        Ok(Self {
+            db,
+            client: async_nats::connect(config.url.as_str()).await?,
+            subject_prefix: config.subject_prefix.clone(),
+            poll_interval: Duration::from_millis(config.poll_interval),
+            batch_size: config.batch_size,
+        })
+    }
+
+    /// Relay events until the process exits.
+    pub async fn run(self) {
+        loop {
+            match self.relay_batch().await {
+                Ok(n) if n as u64 >= self.batch_size => continue,
+                Ok(_) => {}
+                Err(err) => error!("failed to relay events from outbox: {err}"),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Publish the oldest events in the outbox and remove them afterwards.
+    /// Rows are locked while they are published, so multiple instances can
+    /// relay events concurrently.
+    async fn relay_batch(&self) -> Result<usize, RelayError> {
+        let txn = self.db.begin().await?;
+        let events = challenges_event_outbox::Entity::find()
+            .order_by_asc(challenges_event_outbox::Column::Timestamp)
+            .limit(self.batch_size)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(&txn)
+            .await?;
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        for event in &events {
+            trace!("publishing event {} ({})", event.id, event.subject);
+            let mut headers = HeaderMap::new();
+            headers.insert("Nats-Msg-Id", event.id.to_string().as_str());
+            self.client
+                .publish_with_headers(
+                    format!("{}.{}", self.subject_prefix, event.subject),
+                    headers,
+                    serde_json::to_vec(&event.payload)?.into(),
+                )
+                .await?;
+        }
+        self.client.flush().await?;
+
+        challenges_event_outbox::Entity::delete_many()
+            .filter(challenges_event_outbox::Column::Id.is_in(events.iter().map(|x| x.id)))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(events.len())
+    }
+}
+
+#[derive(Debug, Error)]
+enum RelayError {
+    #[error("database error: {0}")]
+    Db(#[from] DbErr),
+    #[error("failed to serialize event: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("failed to publish event: {0}")]
+    Publish(#[from] async_nats::PublishError),
+    #[error("failed to flush events: {0}")]
+    Flush(#[from] async_nats::client::FlushError),
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let event = Event::SubtaskSolved {
+            user_id: Uuid::from_u128(1),
+            task_id: Uuid::from_u128(2),
+            subtask_id: Uuid::from_u128(3),
+        };
+        let timestamp = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let payload = serde_json::to_value(Envelope {
+            id: Uuid::nil(),
+            timestamp,
+            event: &event,
+        })
+        .unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "id": Uuid::nil(),
+                "timestamp": timestamp,
+                "type": "subtask.solved",
+                "data": {
+                    "user_id": Uuid::from_u128(1),
+                    "task_id": Uuid::from_u128(2),
+                    "subtask_id": Uuid::from_u128(3),
+                },
+            })
+        );
+        assert_eq!(event.subject(), "subtask.solved");
+    }
+}
@@ -9,9 +9,16 @@ use crate::{jwt::JwtSecret, redis::RedisConnection};
 
 pub mod auth;
 pub mod config;
+pub mod deprecation;
+pub mod errors;
+pub mod events;
 pub mod jwt;
+pub mod openapi;
 pub mod redis;
+pub mod request_id;
 pub mod services;
+pub mod slow_log;
+pub mod validation;
 
 pub type Cache<S = PostcardFormatter> = AsyncCache<AsyncRedisBackend<RedisConnection>, S>;
 pub type CacheError<S = PostcardFormatter> = fnct::Error<AsyncRedisBackend<RedisConnection>, S>;
@@ -0,0 +1,229 @@
+//! Post-processing of the generated OpenAPI specification.
+//!
+//! `poem-openapi` neither generates operation ids nor knows about the fields
+//! added by the [`add_error_codes`](crate::errors::add_error_codes)
+//! middleware. [`enrich_spec`] fills these gaps so that clients can be
+//! generated from the specification.
+
+use poem::{endpoint::make_sync, web::Html, Endpoint, Response};
+use serde_json::{json, Map, Value};
+
+use crate::errors::{get_error_message, Language};
+
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Add operation ids, typed error payloads and error examples to a json
+/// specification. The specification is returned unchanged if it cannot be
+/// parsed.
+pub fn enrich_spec(spec: &str) -> String {
+    let Ok(mut value) = serde_json::from_str::<Value>(spec) else {
+        return spec.into();
+    };
+    add_operation_ids(&mut value);
+    add_error_schemas(&mut value);
+    serde_json::to_string(&value).unwrap_or_else(|_| spec.into())
+}
+
+/// Create an endpoint that serves a json specification.
+pub fn spec_endpoint(spec: String) -> impl Endpoint {
+    make_sync(move |_| {
+        Response::builder()
+            .content_type("application/json")
+            .body(spec.clone())
+    })
+}
+
+/// Create an endpoint that serves the html of a documentation ui (e.g.
+/// Swagger UI or Redoc). The original specification embedded in the html is
+/// replaced with the enriched one.
+pub fn ui_endpoint(html: String, spec: &str, enriched_spec: &str) -> impl Endpoint {
+    let html = html.replacen(spec, enriched_spec, 1);
+    make_sync(move |_| Html(html.clone()))
+}
+
+/// Derive a stable operation id from the method and path of each operation
+/// that does not have an explicit one.
+fn add_operation_ids(spec: &mut Value) {
+    let Some(paths) = spec.get_mut("paths").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for (path, item) in paths {
+        let Some(item) = item.as_object_mut() else {
+            continue;
+        };
+        for (method, operation) in item {
+            if !METHODS.contains(&method.as_str()) {
+                continue;
+            }
+            let Some(operation) = operation.as_object_mut() else {
+                continue;
+            };
+            operation
+                .entry("operationId")
+                .or_insert_with(|| operation_id(method, path).into());
+        }
+    }
+}
+
+/// Return an operation id like `get_tasks_task_id` for `GET /tasks/{task_id}`.
+fn operation_id(method: &str, path: &str) -> String {
+    path.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .fold(method.to_ascii_lowercase(), |acc, x| acc + "_" + x)
+}
+
+/// Add `error_code` and `message` to the schemas of all error responses and
+/// restrict `error` to the code of the respective error.
+fn add_error_schemas(spec: &mut Value) {
+    let Some(schemas) = spec
+        .pointer_mut("/components/schemas")
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    for schema in schemas.values_mut() {
+        add_error_schema(schema);
+    }
+}
+
+fn add_error_schema(schema: &mut Value) {
+    let Some(code) = schema
+        .pointer("/properties/error/default")
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+    else {
+        return;
+    };
+    let message = get_error_message(&code, Language::English);
+    let Some(schema) = schema.as_object_mut() else {
+        return;
+    };
+
+    let properties = schema
+        .entry("properties")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Some(error) = properties.get_mut("error").and_then(Value::as_object_mut) {
+        error.insert("enum".into(), json!([code]));
+    }
+    let Some(properties) = properties.as_object_mut() else {
+        return;
+    };
+    properties.insert(
+        "error_code".into(),
+        json!({
+            "type": "string",
+            "enum": [code],
+            "description": "The machine-readable error code.",
+        }),
+    );
+    if let Some(message) = message {
+        properties.insert(
+            "message".into(),
+            json!({
+                "type": "string",
+                "description": "A human-readable description of the error.",
+                "example": message,
+            }),
+        );
+    }
+    let has_details = properties.contains_key("details");
+
+    let required = schema.entry("required").or_insert_with(|| json!([]));
+    if let Some(required) = required.as_array_mut() {
+        for field in ["error", "error_code"]
+            .into_iter()
+            .chain(message.map(|_| "message"))
+        {
+            if !required.iter().any(|x| x == field) {
+                required.push(field.into());
+            }
+        }
+    }
+
+    if !has_details {
+        let mut example = json!({"error": code, "error_code": code});
+        if let Some(message) = message {
+            example["message"] = message.into();
+        }
+        schema.insert("example".into(), example);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operation_ids() {
+        assert_eq!(operation_id("get", "/tasks"), "get_tasks");
+        assert_eq!(
+            operation_id("delete", "/tasks/{task_id}/subtasks/{subtask_id}"),
+            "delete_tasks_task_id_subtasks_subtask_id"
+        );
+        assert_eq!(operation_id("get", "/"), "get");
+
+        let mut spec = json!({"paths": {"/tasks/{task_id}": {
+            "get": {},
+            "patch": {"operationId": "update"},
+            "parameters": [],
+        }}});
+        add_operation_ids(&mut spec);
+        assert_eq!(
+            spec,
+            json!({"paths": {"/tasks/{task_id}": {
+                "get": {"operationId": "get_tasks_task_id"},
+                "patch": {"operationId": "update"},
+                "parameters": [],
+            }}})
+        );
+    }
+
+    #[test]
+    fn error_schemas() {
+        let mut schema = json!({
+            "type": "object",
+            "required": ["error"],
+            "properties": {
+                "error": {"type": "string", "readOnly": true, "default": "task_not_found"},
+            },
+        });
+        add_error_schema(&mut schema);
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "required": ["error", "error_code", "message"],
+                "properties": {
+                    "error": {
+                        "type": "string",
+                        "readOnly": true,
+                        "default": "task_not_found",
+                        "enum": ["task_not_found"],
+                    },
+                    "error_code": {
+                        "type": "string",
+                        "enum": ["task_not_found"],
+                        "description": "The machine-readable error code.",
+                    },
+                    "message": {
+                        "type": "string",
+                        "description": "A human-readable description of the error.",
+                        "example": "The task does not exist.",
+                    },
+                },
+                "example": {
+                    "error": "task_not_found",
+                    "error_code": "task_not_found",
+                    "message": "The task does not exist.",
+                },
+            })
+        );
+
+        let mut schema = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+        let expected = schema.clone();
+        add_error_schema(&mut schema);
+        assert_eq!(schema, expected);
+    }
+}
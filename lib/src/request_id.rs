@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use poem::{http::HeaderValue, Endpoint, IntoResponse, Request, Response};
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+/// The header containing the id of a request.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The maximum length of request ids accepted from clients.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Return the id of the request that is currently being handled.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Middleware that assigns an id to each request. The id is taken from the
+/// `X-Request-Id` header if present, attached to all log lines of the request
+/// and returned in the `X-Request-Id` header of the response.
+pub async fn add_request_id<E: Endpoint>(ep: Arc<E>, req: Request) -> poem::Result<Response> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|x| x.to_str().ok())
+        .filter(|x| is_valid_request_id(x))
+        .map(Into::into)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = info_span!("request_id", request_id = %request_id);
+    let mut resp = match REQUEST_ID
+        .scope(request_id.clone(), ep.call(req).instrument(span))
+        .await
+    {
+        Ok(resp) => resp.into_response(),
+        Err(err) => err.into_response(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        resp.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    Ok(resp)
+}
+
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_request_ids() {
+        assert!(is_valid_request_id("a1b2-c3d4"));
+        assert!(is_valid_request_id(&Uuid::new_v4().to_string()));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("foo bar"));
+        assert!(!is_valid_request_id("ä"));
+        assert!(!is_valid_request_id(&"a".repeat(129)));
+    }
+}
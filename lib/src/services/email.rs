@@ -0,0 +1,31 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{Service, ServiceResult};
+
+#[derive(Debug, Clone)]
+pub struct EmailService(Service);
+
+impl EmailService {
+    pub(super) fn new(service: Service) -> Self {
+        Self(service)
+    }
+
+    /// Send a plain text email to a user. The email service resolves the
+    /// address of the user.
+    pub async fn send_email(&self, user_id: Uuid, subject: &str, body: &str) -> ServiceResult<()> {
+        self.0
+            .post(&format!("/users/{user_id}/emails"))
+            .json(&SendEmailRequest { subject, body })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SendEmailRequest<'a> {
+    subject: &'a str,
+    body: &'a str,
+}
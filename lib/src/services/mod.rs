@@ -5,13 +5,15 @@ use reqwest::{Client, Method, RequestBuilder, StatusCode};
 use thiserror::Error;
 use url::Url;
 
-use self::{auth::AuthService, shop::ShopService, skills::SkillsService};
+use self::{auth::AuthService, email::EmailService, shop::ShopService, skills::SkillsService};
 use crate::{
     jwt::{sign_jwt, InternalAuthToken, JwtSecret},
+    request_id::{current_request_id, REQUEST_ID_HEADER},
     Cache, CacheError,
 };
 
 pub mod auth;
+pub mod email;
 pub mod shop;
 pub mod skills;
 
@@ -20,6 +22,7 @@ pub struct Services {
     pub auth: AuthService,
     pub skills: SkillsService,
     pub shop: ShopService,
+    pub email: EmailService,
 }
 
 impl Services {
@@ -46,7 +49,13 @@ impl Services {
                 Arc::clone(&jwt_config),
                 cache.clone(),
             )),
-            shop: ShopService::new(Service::new("shop", conf.shop.clone(), jwt_config, cache)),
+            shop: ShopService::new(Service::new(
+                "shop",
+                conf.shop.clone(),
+                Arc::clone(&jwt_config),
+                cache.clone(),
+            )),
+            email: EmailService::new(Service::new("email", conf.email.clone(), jwt_config, cache)),
         }
     }
 }
@@ -86,14 +95,18 @@ impl Service {
             self.jwt_config.ttl,
         )
         .expect("could not sign internal auth token");
-        Client::new()
+        let request = Client::new()
             .request(
                 method,
                 self.base_url
                     .join(&format!("_internal/{}", path.trim_start_matches('/')))
                     .expect("could not build url"),
             )
-            .bearer_auth(token)
+            .bearer_auth(token);
+        match current_request_id() {
+            Some(request_id) => request.header(REQUEST_ID_HEADER, request_id),
+            None => request,
+        }
     }
 }
 
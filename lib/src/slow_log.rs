@@ -0,0 +1,114 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use poem::{http::header, Endpoint, IntoResponse, Request, Response};
+use sea_orm::DatabaseConnection;
+use tracing::warn;
+
+use crate::{
+    config::SharedConfig,
+    jwt::{verify_jwt, UserAccessToken},
+    SharedState,
+};
+
+/// The maximum length of the query summaries in the log.
+const MAX_QUERY_SUMMARY_LENGTH: usize = 256;
+
+static SLOW_REQUESTS: AtomicU64 = AtomicU64::new(0);
+static SLOW_QUERIES: AtomicU64 = AtomicU64::new(0);
+
+/// The number of slow requests and queries since the process has been started.
+#[derive(Debug, Clone, Copy)]
+pub struct SlowLogCounters {
+    pub requests: u64,
+    pub queries: u64,
+}
+
+pub fn slow_log_counters() -> SlowLogCounters {
+    SlowLogCounters {
+        requests: SLOW_REQUESTS.load(Ordering::Relaxed),
+        queries: SLOW_QUERIES.load(Ordering::Relaxed),
+    }
+}
+
+/// Log a warning for each query that takes longer than the configured
+/// threshold.
+pub fn log_slow_queries(db: &mut DatabaseConnection, config: SharedConfig) {
+    db.set_metric_callback(move |info| {
+        let threshold = Duration::from_millis(config.load().challenges.slow_log.query_threshold);
+        if info.elapsed < threshold {
+            return;
+        }
+        SLOW_QUERIES.fetch_add(1, Ordering::Relaxed);
+        warn!(
+            duration = ?info.elapsed,
+            failed = info.failed,
+            "slow query: {}",
+            summarize_query(&info.statement.sql)
+        );
+    });
+}
+
+/// Middleware that logs a warning for each request that takes longer than the
+/// configured threshold.
+pub async fn log_slow_requests<E: Endpoint>(
+    ep: Arc<E>,
+    req: Request,
+    config: SharedConfig,
+) -> poem::Result<Response> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_owned();
+    let user_id = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|x| x.to_str().ok()?.strip_prefix("Bearer "))
+        .zip(req.data::<Arc<SharedState>>())
+        .and_then(|(token, state)| verify_jwt::<UserAccessToken>(token, &state.jwt_secret).ok())
+        .map(|token| token.uid);
+
+    let start = Instant::now();
+    let resp = ep.call(req).await.map(IntoResponse::into_response);
+    let duration = start.elapsed();
+
+    let threshold = Duration::from_millis(config.load().challenges.slow_log.request_threshold);
+    if duration >= threshold {
+        SLOW_REQUESTS.fetch_add(1, Ordering::Relaxed);
+        warn!(?duration, ?user_id, "slow request: {method} {path}");
+    }
+    resp
+}
+
+/// Collapse whitespace and truncate the query for the log.
+fn summarize_query(sql: &str) -> String {
+    let mut summary = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    if summary.len() > MAX_QUERY_SUMMARY_LENGTH {
+        let end = (0..=MAX_QUERY_SUMMARY_LENGTH)
+            .rev()
+            .find(|&i| summary.is_char_boundary(i))
+            .unwrap_or(0);
+        summary.truncate(end);
+        summary.push_str("...");
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_summary() {
+        assert_eq!(
+            summarize_query("SELECT *\n  FROM  \"challenges_tasks\"\nWHERE id = $1"),
+            r#"SELECT * FROM "challenges_tasks" WHERE id = $1"#
+        );
+        let summary = summarize_query(&"a ".repeat(200));
+        assert_eq!(summary.len(), MAX_QUERY_SUMMARY_LENGTH + 3);
+        assert!(summary.ends_with("..."));
+    }
+}
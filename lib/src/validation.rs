@@ -0,0 +1,267 @@
+//! Details for requests that have been rejected by the request validation.
+//!
+//! poem-openapi only reports the first violated constraint as a plain text
+//! message, which is returned in the `reason` field of `unprocessable_content`
+//! errors. The [`add_validation_details`] middleware parses this message and
+//! adds a `details` list to the response, which contains every value in the
+//! request body that violates this constraint.
+
+use std::sync::Arc;
+
+use poem::{http::StatusCode, Endpoint, IntoResponse, Request, Response};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::is_json;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationError {
+    /// The name of the offending field or parameter.
+    pub field: String,
+    /// The location of the offending value in the request body (e.g.
+    /// `items[2].title`), if known.
+    pub path: Option<String>,
+    /// The violated constraint (e.g. `maxLength(256)`).
+    pub constraint: String,
+    /// The length of the provided string or array, if applicable.
+    pub length: Option<usize>,
+}
+
+/// Middleware that adds `details` to `unprocessable_content` errors.
+pub async fn add_validation_details<E: Endpoint>(
+    ep: Arc<E>,
+    mut req: Request,
+) -> poem::Result<Response> {
+    let body = if is_json(req.headers()) {
+        let body = req.take_body().into_bytes().await?;
+        req.set_body(body.clone());
+        Some(body)
+    } else {
+        None
+    };
+
+    let resp = ep.call(req).await?.into_response();
+    if resp.status() != StatusCode::UNPROCESSABLE_ENTITY || !is_json(resp.headers()) {
+        return Ok(resp);
+    }
+
+    let (parts, resp_body) = resp.into_parts();
+    let resp_body = resp_body.into_bytes().await?;
+    let Ok(mut value) = serde_json::from_slice::<Value>(&resp_body) else {
+        return Ok(Response::from_parts(parts, resp_body.into()));
+    };
+    let request = body
+        .and_then(|body| serde_json::from_slice::<Value>(&body).ok())
+        .unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        if let Some(reason) = object.get("reason").and_then(Value::as_str) {
+            let details = validation_errors(reason, &request);
+            object.insert(
+                "details".into(),
+                serde_json::to_value(details).unwrap_or_default(),
+            );
+        }
+    }
+    let resp_body = serde_json::to_vec(&value)
+        .map(Into::into)
+        .unwrap_or(resp_body);
+    Ok(Response::from_parts(parts, resp_body.into()))
+}
+
+/// Parse the error message of poem-openapi and find all values in the request
+/// body that violate the reported constraint.
+fn validation_errors(reason: &str, request: &Value) -> Vec<ValidationError> {
+    let Some((field, constraint)) = parse_reason(reason) else {
+        return Vec::new();
+    };
+
+    let mut values = Vec::new();
+    find_fields(request, &field, String::new(), &mut values);
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    let errors = values
+        .into_iter()
+        .filter(|(_, value)| violates(&constraint, value))
+        .map(|(path, value)| ValidationError {
+            field: field.clone(),
+            path: Some(path),
+            constraint: constraint.clone(),
+            length: length(value),
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        vec![ValidationError {
+            field,
+            path: None,
+            constraint,
+            length: None,
+        }]
+    } else {
+        errors
+    }
+}
+
+/// Extract the field name and the violated constraint from an error message.
+fn parse_reason(reason: &str) -> Option<(String, String)> {
+    if let Some(field) = between(reason, "unknown field `", "`") {
+        return Some((field.into(), "unknown".into()));
+    }
+    let field = between(reason, "field `", "`")
+        .or_else(|| between(reason, "parameter `", "`"))
+        .or_else(|| between(reason, "path `", "`"))?;
+    let constraint = reason.split_once("verification failed. ")?.1;
+    let end = if constraint.starts_with("pattern(") {
+        constraint.find("\")").map(|i| i + 2)
+    } else {
+        constraint.find(')').map(|i| i + 1)
+    };
+    Some((field.into(), constraint[..end?].into()))
+}
+
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let s = &s[s.find(start)? + start.len()..];
+    Some(&s[..s.find(end)?])
+}
+
+/// Collect all values of the given field in a json value together with their
+/// paths.
+fn find_fields<'a>(
+    value: &'a Value,
+    field: &str,
+    path: String,
+    out: &mut Vec<(String, &'a Value)>,
+) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                if key == field {
+                    out.push((path.clone(), value));
+                }
+                find_fields(value, field, path, out);
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                find_fields(value, field, format!("{path}[{i}]"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn length(value: &Value) -> Option<usize> {
+    match value {
+        Value::String(s) => Some(s.chars().count()),
+        Value::Array(values) => Some(values.len()),
+        _ => None,
+    }
+}
+
+/// Check whether a value violates a constraint. Constraints that cannot be
+/// checked here are assumed to be violated.
+fn violates(constraint: &str, value: &Value) -> bool {
+    let Some((name, args)) = constraint.strip_suffix(')').and_then(|x| x.split_once('(')) else {
+        return true;
+    };
+    let limit = args
+        .split(',')
+        .next()
+        .and_then(|x| x.trim().parse::<f64>().ok());
+    let exclusive = args.ends_with("exclusive: true");
+    match (name, limit) {
+        ("maxLength" | "maxItems", Some(limit)) => length(value).is_some_and(|x| x as f64 > limit),
+        ("minLength" | "minItems", Some(limit)) => {
+            length(value).is_some_and(|x| (x as f64) < limit)
+        }
+        ("maximum", Some(limit)) => value
+            .as_f64()
+            .is_some_and(|x| x > limit || (exclusive && x == limit)),
+        ("minimum", Some(limit)) => value
+            .as_f64()
+            .is_some_and(|x| x < limit || (exclusive && x == limit)),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_reason() {
+        assert_eq!(
+            parse_reason(
+                "parse request payload error: failed to parse \"CreatePathRequest\": field \
+                 `title` verification failed. maxLength(256)"
+            ),
+            Some(("title".into(), "maxLength(256)".into()))
+        );
+        assert_eq!(
+            parse_reason(
+                "parse request payload error: failed to parse \"PathItem\": field `min_solved` \
+                 verification failed. minimum(0, exclusive: false) (occurred while parsing \
+                 \"CreatePathRequest\")"
+            ),
+            Some(("min_solved".into(), "minimum(0, exclusive: false)".into()))
+        );
+        assert_eq!(
+            parse_reason("failed to parse \"Foo\": unknown field `bar`."),
+            Some(("bar".into(), "unknown".into()))
+        );
+        assert_eq!(
+            parse_reason(
+                "failed to parse parameter `limit`: verification failed. maximum(100, \
+                 exclusive: false)"
+            ),
+            Some(("limit".into(), "maximum(100, exclusive: false)".into()))
+        );
+        assert_eq!(parse_reason("something went wrong"), None);
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let request = json!({
+            "title": "x".repeat(300),
+            "items": [{"title": "ok"}, {"title": "y".repeat(257)}],
+        });
+        assert_eq!(
+            validation_errors(
+                "failed to parse \"Foo\": field `title` verification failed. maxLength(256)",
+                &request
+            ),
+            [
+                ValidationError {
+                    field: "title".into(),
+                    path: Some("items[1].title".into()),
+                    constraint: "maxLength(256)".into(),
+                    length: Some(257),
+                },
+                ValidationError {
+                    field: "title".into(),
+                    path: Some("title".into()),
+                    constraint: "maxLength(256)".into(),
+                    length: Some(300),
+                },
+            ]
+        );
+        assert_eq!(
+            validation_errors(
+                "failed to parse parameter `limit`: verification failed. maximum(100, exclusive: \
+                 false)",
+                &Value::Null
+            ),
+            [ValidationError {
+                field: "limit".into(),
+                path: None,
+                constraint: "maximum(100, exclusive: false)".into(),
+                length: None,
+            }]
+        );
+    }
+}
@@ -2,6 +2,10 @@
 #![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
 
 pub use sea_orm_migration::prelude::*;
+use sea_orm_migration::{
+    sea_orm::{EntityTrait, QueryOrder},
+    seaql_migrations,
+};
 
 pub struct Migrator;
 
@@ -35,6 +39,69 @@ mod m20230815_162457_remove_subtask_fee;
 mod m20230815_203544_remove_subtask_unlocked;
 mod m20230816_173651_retire_subtasks;
 mod m20231014_142202_category_creation_timestamp;
+mod m20231102_154512_contests;
+mod m20231105_093817_subtask_access;
+mod m20231107_181544_event_outbox;
+mod m20231110_120335_submission_attribution;
+mod m20231112_094127_reviews;
+mod m20231113_160512_subtask_prerequisites;
+mod m20231114_083015_subtask_fingerprints;
+mod m20231116_141903_paths;
+mod m20231117_101245_task_config;
+mod m20231118_093412_subtask_revisions;
+mod m20231119_152208_attempt_revisions;
+mod m20231120_101530_true_false_orderings;
+mod m20231121_084210_clozes;
+mod m20231122_140817_code_readings;
+mod m20231123_091254_sql_challenges;
+mod m20231124_103517_interaction_transcripts;
+mod m20231125_140302_output_only_challenges;
+mod m20231126_083145_evaluator_versions;
+mod m20231127_152204_submission_artifacts;
+mod m20231128_094517_feedback_levels;
+mod m20231129_101842_notifications;
+mod m20231130_074512_creator_digests;
+mod m20231201_083417_user_bookmarks;
+mod m20231202_091523_user_notes;
+mod m20231203_104211_subtask_sessions;
+mod m20231204_081936_exams;
+mod m20231205_093044_cohorts;
+mod m20231206_074125_cheat_flags;
+mod m20231207_102318_reward_holds;
+mod m20231208_090215_shadow_bans;
+mod m20231209_140532_moderation_queue;
+mod m20231211_081204_judge_protocol;
+mod m20231212_093518_comparison_modes;
+mod m20231213_102741_custom_checkers;
+mod m20231214_084926_submission_resources;
+mod m20231215_101533_personal_bests;
+mod m20231216_093842_code_golf;
+mod m20231217_141208_submission_files;
+mod m20231218_082317_build_configs;
+mod m20231219_090451_submission_creator_index;
+mod m20231220_103015_judge_queue;
+mod m20231221_091204_cancelled_verdict;
+mod m20231222_080934_disabled_environments;
+mod m20231223_084215_backfill_category_timestamps;
+mod m20231224_091530_organizations;
+mod m20231225_103012_subtask_licenses;
+mod m20231226_094517_subtask_attempt_limits;
+mod m20231227_081204_revealed_subtasks;
+mod m20231228_102233_subtask_time_limits;
+mod m20231229_090115_attempt_seeds;
+mod m20231230_140528_question_parameters;
+mod m20231231_101746_numeric_questions;
+mod m20240101_093517_public_solutions;
+mod m20240102_081244_wrong_answers;
+mod m20240103_154021_task_pools;
+mod m20240104_111902_task_completions;
+mod m20240105_163310_subtask_unlock_prices;
+mod m20240106_120417_sandbox_usage;
+mod m20240107_093512_reward_ledger;
+mod m20240108_101244_judge_queue_claims;
+mod m20240109_084512_judge_failures;
+mod m20240109_101530_backfill_challenge_titles;
+mod m20240109_141205_subtask_locks;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
@@ -70,6 +137,119 @@ impl MigratorTrait for Migrator {
             Box::new(m20230815_203544_remove_subtask_unlocked::Migration),
             Box::new(m20230816_173651_retire_subtasks::Migration),
             Box::new(m20231014_142202_category_creation_timestamp::Migration),
+            Box::new(m20231102_154512_contests::Migration),
+            Box::new(m20231105_093817_subtask_access::Migration),
+            Box::new(m20231107_181544_event_outbox::Migration),
+            Box::new(m20231110_120335_submission_attribution::Migration),
+            Box::new(m20231112_094127_reviews::Migration),
+            Box::new(m20231113_160512_subtask_prerequisites::Migration),
+            Box::new(m20231114_083015_subtask_fingerprints::Migration),
+            Box::new(m20231116_141903_paths::Migration),
+            Box::new(m20231117_101245_task_config::Migration),
+            Box::new(m20231118_093412_subtask_revisions::Migration),
+            Box::new(m20231119_152208_attempt_revisions::Migration),
+            Box::new(m20231120_101530_true_false_orderings::Migration),
+            Box::new(m20231121_084210_clozes::Migration),
+            Box::new(m20231122_140817_code_readings::Migration),
+            Box::new(m20231123_091254_sql_challenges::Migration),
+            Box::new(m20231124_103517_interaction_transcripts::Migration),
+            Box::new(m20231125_140302_output_only_challenges::Migration),
+            Box::new(m20231126_083145_evaluator_versions::Migration),
+            Box::new(m20231127_152204_submission_artifacts::Migration),
+            Box::new(m20231128_094517_feedback_levels::Migration),
+            Box::new(m20231129_101842_notifications::Migration),
+            Box::new(m20231130_074512_creator_digests::Migration),
+            Box::new(m20231201_083417_user_bookmarks::Migration),
+            Box::new(m20231202_091523_user_notes::Migration),
+            Box::new(m20231203_104211_subtask_sessions::Migration),
+            Box::new(m20231204_081936_exams::Migration),
+            Box::new(m20231205_093044_cohorts::Migration),
+            Box::new(m20231206_074125_cheat_flags::Migration),
+            Box::new(m20231207_102318_reward_holds::Migration),
+            Box::new(m20231208_090215_shadow_bans::Migration),
+            Box::new(m20231209_140532_moderation_queue::Migration),
+            Box::new(m20231211_081204_judge_protocol::Migration),
+            Box::new(m20231212_093518_comparison_modes::Migration),
+            Box::new(m20231213_102741_custom_checkers::Migration),
+            Box::new(m20231214_084926_submission_resources::Migration),
+            Box::new(m20231215_101533_personal_bests::Migration),
+            Box::new(m20231216_093842_code_golf::Migration),
+            Box::new(m20231217_141208_submission_files::Migration),
+            Box::new(m20231218_082317_build_configs::Migration),
+            Box::new(m20231219_090451_submission_creator_index::Migration),
+            Box::new(m20231220_103015_judge_queue::Migration),
+            Box::new(m20231221_091204_cancelled_verdict::Migration),
+            Box::new(m20231222_080934_disabled_environments::Migration),
+            Box::new(m20231223_084215_backfill_category_timestamps::Migration),
+            Box::new(m20231224_091530_organizations::Migration),
+            Box::new(m20231225_103012_subtask_licenses::Migration),
+            Box::new(m20231226_094517_subtask_attempt_limits::Migration),
+            Box::new(m20231227_081204_revealed_subtasks::Migration),
+            Box::new(m20231228_102233_subtask_time_limits::Migration),
+            Box::new(m20231229_090115_attempt_seeds::Migration),
+            Box::new(m20231230_140528_question_parameters::Migration),
+            Box::new(m20231231_101746_numeric_questions::Migration),
+            Box::new(m20240101_093517_public_solutions::Migration),
+            Box::new(m20240102_081244_wrong_answers::Migration),
+            Box::new(m20240103_154021_task_pools::Migration),
+            Box::new(m20240104_111902_task_completions::Migration),
+            Box::new(m20240105_163310_subtask_unlock_prices::Migration),
+            Box::new(m20240106_120417_sandbox_usage::Migration),
+            Box::new(m20240107_093512_reward_ledger::Migration),
+            Box::new(m20240108_101244_judge_queue_claims::Migration),
+            Box::new(m20240109_084512_judge_failures::Migration),
+            Box::new(m20240109_101530_backfill_challenge_titles::Migration),
+            Box::new(m20240109_141205_subtask_locks::Migration),
         ]
     }
 }
+
+/// The state of a migration in the database.
+#[derive(Debug, Clone)]
+pub struct MigrationState {
+    pub name: String,
+    /// The unix timestamp of the time the migration has been applied at.
+    pub applied_at: Option<i64>,
+    /// Whether the migration is part of this version of the migrator.
+    /// Unknown migrations have been applied by a newer version.
+    pub known: bool,
+}
+
+impl Migrator {
+    /// Return the state of all known and applied migrations in the order they
+    /// are applied.
+    pub async fn state(db: &impl ConnectionTrait) -> Result<Vec<MigrationState>, DbErr> {
+        let mut applied = seaql_migrations::Entity::find()
+            .order_by_asc(seaql_migrations::Column::Version)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.version, m.applied_at))
+            .collect::<Vec<_>>();
+        let mut out = Self::migrations()
+            .into_iter()
+            .map(|migration| {
+                let name = migration.name().to_owned();
+                let applied_at = applied
+                    .iter()
+                    .position(|(version, _)| *version == name)
+                    .map(|i| applied.remove(i).1);
+                MigrationState {
+                    name,
+                    applied_at,
+                    known: true,
+                }
+            })
+            .collect::<Vec<_>>();
+        out.extend(
+            applied
+                .into_iter()
+                .map(|(name, applied_at)| MigrationState {
+                    name,
+                    applied_at: Some(applied_at),
+                    known: false,
+                }),
+        );
+        Ok(out)
+    }
+}
@@ -294,6 +294,15 @@ pub enum Subtask {
     Fee,
     Enabled,
     Retired,
+    Fingerprint,
+    PendingReview,
+    ReviewStatus,
+    RejectionReason,
+    License,
+    Attribution,
+    MaxAttempts,
+    RevealSolution,
+    TimeLimit,
 }
 
 #[derive(Iden)]
@@ -305,6 +314,7 @@ pub enum MultipleChoice {
     Answers,
     CorrectAnswers,
     SingleChoice,
+    ShuffleAnswers,
 }
 
 #[derive(Iden)]
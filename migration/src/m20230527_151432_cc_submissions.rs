@@ -113,10 +113,11 @@ pub enum Submission {
     CreationTimestamp,
     Environment,
     Code,
+    Attributed,
 }
 
 #[derive(Iden)]
-enum SubmissionResult {
+pub enum SubmissionResult {
     #[iden = "challenges_coding_challenge_result"]
     Table,
     SubmissionId,
@@ -145,4 +146,5 @@ pub enum Verdict {
     CompilationError,
     RuntimeError,
     PreCheckFailed,
+    Cancelled,
 }
@@ -52,6 +52,15 @@ impl MigrationTrait for Migration {
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // tasks of whole courses or sections cannot be represented anymore
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "delete from challenges_tasks where id in (select task_id from \
+                 challenges_course_tasks where section_id is null or lecture_id is null)",
+            )
+            .await?;
+
         manager
             .alter_table(
                 Table::alter()
@@ -77,6 +86,14 @@ impl MigrationTrait for Migration {
             )
             .await?;
 
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "update challenges_tasks t set title = c.title, description = c.description \
+                 from challenges_challenges c where t.id = c.task_id",
+            )
+            .await?;
+
         manager
             .alter_table(
                 Table::alter()
@@ -99,4 +99,5 @@ pub enum UserSubtask {
     RatingTimestamp,
     LastAttemptTimestamp,
     Attempts,
+    RevealedTimestamp,
 }
@@ -89,6 +89,11 @@ pub enum Question {
     Digits,
     Punctuation,
     Blocks,
+    Parameters,
+    AnswerType,
+    Unit,
+    AbsoluteTolerance,
+    RelativeTolerance,
 }
 
 #[derive(Iden, Clone, Copy)]
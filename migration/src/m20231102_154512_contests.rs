@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Contest::Table)
+                    .col(ColumnDef::new(Contest::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Contest::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Contest::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Contest::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Contest::Title).text().not_null())
+                    .col(ColumnDef::new(Contest::Start).timestamp().not_null())
+                    .col(ColumnDef::new(Contest::End).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Contest::Table, Contest::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Contest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Contest {
+    #[iden = "challenges_contests"]
+    Table,
+    Id,
+    TaskId,
+    Creator,
+    CreationTimestamp,
+    Title,
+    Start,
+    End,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubtaskAccess::Table)
+                    .col(ColumnDef::new(SubtaskAccess::UserId).uuid().not_null())
+                    .col(ColumnDef::new(SubtaskAccess::SubtaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(SubtaskAccess::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(SubtaskAccess::UserId)
+                            .col(SubtaskAccess::SubtaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubtaskAccess::Table, SubtaskAccess::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubtaskAccess::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum SubtaskAccess {
+    #[iden = "challenges_subtask_access"]
+    Table,
+    UserId,
+    SubtaskId,
+    Timestamp,
+}
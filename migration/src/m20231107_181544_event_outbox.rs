@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EventOutbox::Table)
+                    .col(ColumnDef::new(EventOutbox::Id).uuid().primary_key())
+                    .col(ColumnDef::new(EventOutbox::Subject).text().not_null())
+                    .col(
+                        ColumnDef::new(EventOutbox::Payload)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EventOutbox::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_event_outbox_timestamp_idx")
+                    .table(EventOutbox::Table)
+                    .col(EventOutbox::Timestamp)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EventOutbox::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum EventOutbox {
+    #[iden = "challenges_event_outbox"]
+    Table,
+    Id,
+    Subject,
+    Payload,
+    Timestamp,
+}
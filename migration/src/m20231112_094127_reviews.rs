@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Review::Table)
+                    .col(ColumnDef::new(Review::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Review::UserId).uuid().not_null())
+                    .col(ColumnDef::new(Review::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(Review::EaseFactor).double().not_null())
+                    .col(ColumnDef::new(Review::Interval).integer().not_null())
+                    .col(ColumnDef::new(Review::Repetitions).integer().not_null())
+                    .col(ColumnDef::new(Review::Due).timestamp().not_null())
+                    .col(ColumnDef::new(Review::LastReview).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Review::Table, Review::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_reviews_user_id_subtask_id_idx")
+                    .table(Review::Table)
+                    .col(Review::UserId)
+                    .col(Review::SubtaskId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_reviews_user_id_due_idx")
+                    .table(Review::Table)
+                    .col(Review::UserId)
+                    .col(Review::Due)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Review::Table).to_owned())
+            .await
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Iden)]
+pub enum Review {
+    #[iden = "challenges_reviews"]
+    Table,
+    Id,
+    UserId,
+    SubtaskId,
+    EaseFactor,
+    Interval,
+    Repetitions,
+    Due,
+    LastReview,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubtaskPrerequisite::Table)
+                    .col(
+                        ColumnDef::new(SubtaskPrerequisite::SubtaskId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubtaskPrerequisite::PrerequisiteId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(SubtaskPrerequisite::SubtaskId)
+                            .col(SubtaskPrerequisite::PrerequisiteId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubtaskPrerequisite::Table, SubtaskPrerequisite::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubtaskPrerequisite::Table,
+                                SubtaskPrerequisite::PrerequisiteId,
+                            )
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubtaskPrerequisite::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum SubtaskPrerequisite {
+    #[iden = "challenges_subtask_prerequisites"]
+    Table,
+    SubtaskId,
+    PrerequisiteId,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(ColumnDef::new(Subtask::Fingerprint).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_subtasks_task_id_fingerprint_idx")
+                    .table(Subtask::Table)
+                    .col(Subtask::TaskId)
+                    .col(Subtask::Fingerprint)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::Fingerprint)
+                    .to_owned(),
+            )
+            .await
+    }
+}
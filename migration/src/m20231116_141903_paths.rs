@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::{Subtask, Task};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Path::Table)
+                    .col(ColumnDef::new(Path::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Path::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Path::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Path::Title).text().not_null())
+                    .col(ColumnDef::new(Path::Description).text().not_null())
+                    .col(ColumnDef::new(Path::Xp).big_integer().not_null())
+                    .col(ColumnDef::new(Path::Coins).big_integer().not_null())
+                    .col(ColumnDef::new(Path::Enabled).boolean().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(PathItem::Table)
+                    .col(ColumnDef::new(PathItem::PathId).uuid().not_null())
+                    .col(ColumnDef::new(PathItem::Position).integer().not_null())
+                    .col(ColumnDef::new(PathItem::TaskId).uuid().null())
+                    .col(ColumnDef::new(PathItem::SubtaskId).uuid().null())
+                    .col(ColumnDef::new(PathItem::MinSolved).integer().null())
+                    .primary_key(
+                        Index::create()
+                            .col(PathItem::PathId)
+                            .col(PathItem::Position),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PathItem::Table, PathItem::PathId)
+                            .to(Path::Table, Path::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PathItem::Table, PathItem::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PathItem::Table, PathItem::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserPath::Table)
+                    .col(ColumnDef::new(UserPath::UserId).uuid().not_null())
+                    .col(ColumnDef::new(UserPath::PathId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(UserPath::CompletedTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(Index::create().col(UserPath::UserId).col(UserPath::PathId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserPath::Table, UserPath::PathId)
+                            .to(Path::Table, Path::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserPath::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PathItem::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Path::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Path {
+    #[iden = "challenges_paths"]
+    Table,
+    Id,
+    Creator,
+    CreationTimestamp,
+    Title,
+    Description,
+    Xp,
+    Coins,
+    Enabled,
+}
+
+#[derive(Iden)]
+pub enum PathItem {
+    #[iden = "challenges_path_items"]
+    Table,
+    PathId,
+    Position,
+    TaskId,
+    SubtaskId,
+    MinSolved,
+}
+
+#[derive(Iden)]
+pub enum UserPath {
+    #[iden = "challenges_user_paths"]
+    Table,
+    UserId,
+    PathId,
+    CompletedTimestamp,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskConfig::Table)
+                    .col(ColumnDef::new(TaskConfig::TaskId).uuid().primary_key())
+                    .col(ColumnDef::new(TaskConfig::MaxXp).big_integer().null())
+                    .col(ColumnDef::new(TaskConfig::MaxCoins).big_integer().null())
+                    .col(ColumnDef::new(TaskConfig::TimeoutIncr).big_integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TaskConfig::Table, TaskConfig::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TaskConfig::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum TaskConfig {
+    #[iden = "challenges_task_config"]
+    Table,
+    TaskId,
+    MaxXp,
+    MaxCoins,
+    TimeoutIncr,
+}
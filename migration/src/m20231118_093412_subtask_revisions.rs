@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubtaskRevision::Table)
+                    .col(ColumnDef::new(SubtaskRevision::Id).uuid().primary_key())
+                    .col(ColumnDef::new(SubtaskRevision::SubtaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(SubtaskRevision::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubtaskRevision::Subtask)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubtaskRevision::Content)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubtaskRevision::Table, SubtaskRevision::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_subtask_revisions_subtask_id_timestamp_idx")
+                    .table(SubtaskRevision::Table)
+                    .col(SubtaskRevision::SubtaskId)
+                    .col(SubtaskRevision::Timestamp)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubtaskRevision::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum SubtaskRevision {
+    #[iden = "challenges_subtask_revisions"]
+    Table,
+    Id,
+    SubtaskId,
+    Timestamp,
+    Subtask,
+    Content,
+}
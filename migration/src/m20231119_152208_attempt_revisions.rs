@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230326_074819_multiple_choice_attempts::MultipleChoiceAttempt,
+    m20230527_151432_cc_submissions::Submission, m20230621_074711_questions::QuestionAttempt,
+    m20230621_141228_matchings::MatchingAttempt,
+    m20231118_093412_subtask_revisions::SubtaskRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+fn tables() -> [DynIden; 4] {
+    [
+        MultipleChoiceAttempt::Table.into_iden(),
+        QuestionAttempt::Table.into_iden(),
+        MatchingAttempt::Table.into_iden(),
+        Submission::Table.into_iden(),
+    ]
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in tables() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .add_column(ColumnDef::new(Attempt::RevisionId).uuid().null())
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(format!("{}_revision_id_fkey", table.to_string()))
+                                .from_tbl(table)
+                                .from_col(Attempt::RevisionId)
+                                .to_tbl(SubtaskRevision::Table)
+                                .to_col(SubtaskRevision::Id)
+                                .on_delete(ForeignKeyAction::SetNull),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in tables() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Attempt::RevisionId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Attempt {
+    RevisionId,
+}
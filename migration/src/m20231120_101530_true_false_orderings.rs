@@ -0,0 +1,220 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::Subtask, m20230707_200516_subtask_type::SubtaskType,
+    m20231118_093412_subtask_revisions::SubtaskRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for value in [NewSubtaskType::TrueFalse, NewSubtaskType::Ordering] {
+            let sql = Type::alter()
+                .name(SubtaskType::Type)
+                .add_value(value)
+                .to_string(PostgresQueryBuilder)
+                .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+            manager.get_connection().execute_unprepared(&sql).await?;
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TrueFalse::Table)
+                    .col(ColumnDef::new(TrueFalse::SubtaskId).uuid().primary_key())
+                    .col(ColumnDef::new(TrueFalse::Statement).text().not_null())
+                    .col(ColumnDef::new(TrueFalse::Answer).boolean().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TrueFalse::Table, TrueFalse::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TrueFalseAttempt::Table)
+                    .col(ColumnDef::new(TrueFalseAttempt::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(TrueFalseAttempt::StatementId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TrueFalseAttempt::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TrueFalseAttempt::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrueFalseAttempt::Solved)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TrueFalseAttempt::RevisionId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TrueFalseAttempt::Table, TrueFalseAttempt::StatementId)
+                            .to(TrueFalse::Table, TrueFalse::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TrueFalseAttempt::Table, TrueFalseAttempt::RevisionId)
+                            .to(SubtaskRevision::Table, SubtaskRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Ordering::Table)
+                    .col(ColumnDef::new(Ordering::SubtaskId).uuid().primary_key())
+                    .col(ColumnDef::new(Ordering::Question).text().not_null())
+                    .col(
+                        ColumnDef::new(Ordering::Items)
+                            .array(ColumnType::Text)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Ordering::Solution)
+                            .array(ColumnType::SmallInteger)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Ordering::Table, Ordering::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderingAttempt::Table)
+                    .col(ColumnDef::new(OrderingAttempt::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(OrderingAttempt::OrderingId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderingAttempt::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(OrderingAttempt::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderingAttempt::Solved).boolean().not_null())
+                    .col(ColumnDef::new(OrderingAttempt::RevisionId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OrderingAttempt::Table, OrderingAttempt::OrderingId)
+                            .to(Ordering::Table, Ordering::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OrderingAttempt::Table, OrderingAttempt::RevisionId)
+                            .to(SubtaskRevision::Table, SubtaskRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            OrderingAttempt::Table.into_iden(),
+            Ordering::Table.into_iden(),
+            TrueFalseAttempt::Table.into_iden(),
+            TrueFalse::Table.into_iden(),
+        ] {
+            manager
+                .drop_table(Table::drop().table(table).to_owned())
+                .await?;
+        }
+
+        // values cannot be removed from postgres enums, so only the subtasks
+        // of the new types are deleted
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Subtask::Table)
+                    .and_where(
+                        Expr::expr(Expr::col(Subtask::Type).cast_as(Alias::new("text"))).is_in([
+                            NewSubtaskType::TrueFalse.to_string(),
+                            NewSubtaskType::Ordering.to_string(),
+                        ]),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewSubtaskType {
+    TrueFalse,
+    Ordering,
+}
+
+#[derive(Iden)]
+pub enum TrueFalse {
+    #[iden = "challenges_true_false_statements"]
+    Table,
+    SubtaskId,
+    Statement,
+    Answer,
+}
+
+#[derive(Iden)]
+pub enum TrueFalseAttempt {
+    #[iden = "challenges_true_false_attempts"]
+    Table,
+    Id,
+    StatementId,
+    UserId,
+    Timestamp,
+    Solved,
+    RevisionId,
+}
+
+#[derive(Iden)]
+pub enum Ordering {
+    #[iden = "challenges_orderings"]
+    Table,
+    SubtaskId,
+    Question,
+    Items,
+    Solution,
+}
+
+#[derive(Iden)]
+pub enum OrderingAttempt {
+    #[iden = "challenges_ordering_attempts"]
+    Table,
+    Id,
+    OrderingId,
+    UserId,
+    Timestamp,
+    Solved,
+    RevisionId,
+}
@@ -0,0 +1,121 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::Subtask, m20230707_200516_subtask_type::SubtaskType,
+    m20231118_093412_subtask_revisions::SubtaskRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(SubtaskType::Type)
+            .add_value(NewSubtaskType::Cloze)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Cloze::Table)
+                    .col(ColumnDef::new(Cloze::SubtaskId).uuid().primary_key())
+                    .col(ColumnDef::new(Cloze::Text).text().not_null())
+                    .col(ColumnDef::new(Cloze::Blanks).json_binary().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Cloze::Table, Cloze::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClozeAttempt::Table)
+                    .col(ColumnDef::new(ClozeAttempt::Id).uuid().primary_key())
+                    .col(ColumnDef::new(ClozeAttempt::ClozeId).uuid().not_null())
+                    .col(ColumnDef::new(ClozeAttempt::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(ClozeAttempt::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ClozeAttempt::Solved).boolean().not_null())
+                    .col(ColumnDef::new(ClozeAttempt::RevisionId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClozeAttempt::Table, ClozeAttempt::ClozeId)
+                            .to(Cloze::Table, Cloze::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClozeAttempt::Table, ClozeAttempt::RevisionId)
+                            .to(SubtaskRevision::Table, SubtaskRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ClozeAttempt::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Cloze::Table).to_owned())
+            .await?;
+
+        // values cannot be removed from postgres enums, so only the subtasks
+        // of the new type are deleted
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Subtask::Table)
+                    .and_where(
+                        Expr::expr(Expr::col(Subtask::Type).cast_as(Alias::new("text")))
+                            .eq(NewSubtaskType::Cloze.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewSubtaskType {
+    Cloze,
+}
+
+#[derive(Iden)]
+pub enum Cloze {
+    #[iden = "challenges_clozes"]
+    Table,
+    SubtaskId,
+    Text,
+    Blanks,
+}
+
+#[derive(Iden)]
+pub enum ClozeAttempt {
+    #[iden = "challenges_cloze_attempts"]
+    Table,
+    Id,
+    ClozeId,
+    UserId,
+    Timestamp,
+    Solved,
+    RevisionId,
+}
@@ -0,0 +1,131 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::Subtask, m20230707_200516_subtask_type::SubtaskType,
+    m20231118_093412_subtask_revisions::SubtaskRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(SubtaskType::Type)
+            .add_value(NewSubtaskType::CodeReading)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeReading::Table)
+                    .col(ColumnDef::new(CodeReading::SubtaskId).uuid().primary_key())
+                    .col(ColumnDef::new(CodeReading::Environment).text().not_null())
+                    .col(ColumnDef::new(CodeReading::Code).text().not_null())
+                    .col(ColumnDef::new(CodeReading::Output).text().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CodeReading::Table, CodeReading::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeReadingAttempt::Table)
+                    .col(ColumnDef::new(CodeReadingAttempt::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(CodeReadingAttempt::CodeReadingId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CodeReadingAttempt::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(CodeReadingAttempt::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CodeReadingAttempt::Solved)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CodeReadingAttempt::RevisionId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CodeReadingAttempt::Table, CodeReadingAttempt::CodeReadingId)
+                            .to(CodeReading::Table, CodeReading::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CodeReadingAttempt::Table, CodeReadingAttempt::RevisionId)
+                            .to(SubtaskRevision::Table, SubtaskRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CodeReadingAttempt::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CodeReading::Table).to_owned())
+            .await?;
+
+        // values cannot be removed from postgres enums, so only the subtasks
+        // of the new type are deleted
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Subtask::Table)
+                    .and_where(
+                        Expr::expr(Expr::col(Subtask::Type).cast_as(Alias::new("text")))
+                            .eq(NewSubtaskType::CodeReading.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewSubtaskType {
+    CodeReading,
+}
+
+#[derive(Iden)]
+pub enum CodeReading {
+    #[iden = "challenges_code_readings"]
+    Table,
+    SubtaskId,
+    Environment,
+    Code,
+    Output,
+}
+
+#[derive(Iden)]
+pub enum CodeReadingAttempt {
+    #[iden = "challenges_code_reading_attempts"]
+    Table,
+    Id,
+    CodeReadingId,
+    UserId,
+    Timestamp,
+    Solved,
+    RevisionId,
+}
@@ -0,0 +1,129 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::Subtask, m20230707_200516_subtask_type::SubtaskType,
+    m20231118_093412_subtask_revisions::SubtaskRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(SubtaskType::Type)
+            .add_value(NewSubtaskType::SqlChallenge)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SqlChallenge::Table)
+                    .col(ColumnDef::new(SqlChallenge::SubtaskId).uuid().primary_key())
+                    .col(ColumnDef::new(SqlChallenge::Description).text().not_null())
+                    .col(ColumnDef::new(SqlChallenge::Schema).text().not_null())
+                    .col(ColumnDef::new(SqlChallenge::Seed).text().not_null())
+                    .col(ColumnDef::new(SqlChallenge::Solution).text().not_null())
+                    .col(ColumnDef::new(SqlChallenge::Ordered).boolean().not_null())
+                    .col(
+                        ColumnDef::new(SqlChallenge::Expected)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SqlChallenge::Table, SqlChallenge::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SqlAttempt::Table)
+                    .col(ColumnDef::new(SqlAttempt::Id).uuid().primary_key())
+                    .col(ColumnDef::new(SqlAttempt::SqlChallengeId).uuid().not_null())
+                    .col(ColumnDef::new(SqlAttempt::UserId).uuid().not_null())
+                    .col(ColumnDef::new(SqlAttempt::Timestamp).timestamp().not_null())
+                    .col(ColumnDef::new(SqlAttempt::Solved).boolean().not_null())
+                    .col(ColumnDef::new(SqlAttempt::RevisionId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SqlAttempt::Table, SqlAttempt::SqlChallengeId)
+                            .to(SqlChallenge::Table, SqlChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SqlAttempt::Table, SqlAttempt::RevisionId)
+                            .to(SubtaskRevision::Table, SubtaskRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SqlAttempt::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SqlChallenge::Table).to_owned())
+            .await?;
+
+        // values cannot be removed from postgres enums, so only the subtasks
+        // of the new type are deleted
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Subtask::Table)
+                    .and_where(
+                        Expr::expr(Expr::col(Subtask::Type).cast_as(Alias::new("text")))
+                            .eq(NewSubtaskType::SqlChallenge.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewSubtaskType {
+    SqlChallenge,
+}
+
+#[derive(Iden)]
+pub enum SqlChallenge {
+    #[iden = "challenges_sql_challenges"]
+    Table,
+    SubtaskId,
+    Description,
+    Schema,
+    Seed,
+    Solution,
+    Ordered,
+    Expected,
+}
+
+#[derive(Iden)]
+pub enum SqlAttempt {
+    #[iden = "challenges_sql_attempts"]
+    Table,
+    Id,
+    SqlChallengeId,
+    UserId,
+    Timestamp,
+    Solved,
+    RevisionId,
+}
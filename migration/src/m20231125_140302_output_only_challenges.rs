@@ -0,0 +1,109 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge,
+    m20231118_093412_subtask_revisions::SubtaskRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::OutputOnly)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutputSubmission::Table)
+                    .col(ColumnDef::new(OutputSubmission::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(OutputSubmission::SubtaskId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OutputSubmission::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(OutputSubmission::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OutputSubmission::Passed)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OutputSubmission::Total).integer().not_null())
+                    .col(
+                        ColumnDef::new(OutputSubmission::Results)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OutputSubmission::RevisionId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OutputSubmission::Table, OutputSubmission::SubtaskId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OutputSubmission::Table, OutputSubmission::RevisionId)
+                            .to(SubtaskRevision::Table, SubtaskRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutputSubmission::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(NewCodingChallenge::OutputOnly)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewCodingChallenge {
+    OutputOnly,
+}
+
+#[derive(Iden)]
+pub enum OutputSubmission {
+    #[iden = "challenges_output_submissions"]
+    Table,
+    Id,
+    SubtaskId,
+    Creator,
+    CreationTimestamp,
+    Passed,
+    Total,
+    Results,
+    RevisionId,
+}
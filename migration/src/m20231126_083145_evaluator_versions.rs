@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::CodingChallenge;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EvaluatorVersion::Table)
+                    .col(
+                        ColumnDef::new(EvaluatorVersion::SubtaskId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EvaluatorVersion::Version)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EvaluatorVersion::Evaluator)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EvaluatorVersion::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(EvaluatorVersion::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(EvaluatorVersion::SubtaskId)
+                            .col(EvaluatorVersion::Version),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(EvaluatorVersion::Table, EvaluatorVersion::SubtaskId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::EvaluatorVersion)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::EvaluatorVersion)
+                            .integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // the current evaluators of existing challenges become their first version
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "insert into challenges_evaluator_versions (subtask_id, version, evaluator, creator, \
+             timestamp) select cc.subtask_id, 1, cc.evaluator, s.creator, s.creation_timestamp \
+             from challenges_coding_challenges cc join challenges_subtasks s on s.id = \
+             cc.subtask_id",
+        )
+        .await?;
+        db.execute_unprepared(
+            "update challenges_coding_challenge_result set evaluator_version = 1",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::EvaluatorVersion)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(NewCodingChallenge::EvaluatorVersion)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(EvaluatorVersion::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewCodingChallenge {
+    EvaluatorVersion,
+}
+
+#[derive(Iden)]
+enum SubmissionResult {
+    #[iden = "challenges_coding_challenge_result"]
+    Table,
+    EvaluatorVersion,
+}
+
+#[derive(Iden)]
+pub enum EvaluatorVersion {
+    #[iden = "challenges_evaluator_versions"]
+    Table,
+    SubtaskId,
+    Version,
+    Evaluator,
+    Creator,
+    Timestamp,
+}
@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubmissionArtifacts::Table)
+                    .col(
+                        ColumnDef::new(SubmissionArtifacts::SubmissionId)
+                            .uuid()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionArtifacts::Stdout)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionArtifacts::Stderr)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionArtifacts::Truncated)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubmissionArtifacts::Table,
+                                SubmissionArtifacts::SubmissionId,
+                            )
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubmissionArtifacts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SubmissionArtifacts {
+    #[iden = "challenges_submission_artifacts"]
+    Table,
+    SubmissionId,
+    Stdout,
+    Stderr,
+    Truncated,
+}
@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::CodingChallenge;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(FeedbackLevel::Type)
+                    .values([
+                        FeedbackLevel::VerdictOnly,
+                        FeedbackLevel::FirstFailedSeed,
+                        FeedbackLevel::FullDiff,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::FeedbackLevel)
+                            .custom(FeedbackLevel::Type)
+                            .not_null()
+                            .default("full_diff"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(ColumnDef::new(SubmissionResult::Seed).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::Seed)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(NewCodingChallenge::FeedbackLevel)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(FeedbackLevel::Type).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewCodingChallenge {
+    FeedbackLevel,
+}
+
+#[derive(Iden)]
+enum SubmissionResult {
+    #[iden = "challenges_coding_challenge_result"]
+    Table,
+    Seed,
+}
+
+#[derive(Iden)]
+enum FeedbackLevel {
+    #[iden = "challenges_feedback_level"]
+    Type,
+    VerdictOnly,
+    FirstFailedSeed,
+    FullDiff,
+}
@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20231102_154512_contests::Contest;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationPreferences::Table)
+                    .col(
+                        ColumnDef::new(NotificationPreferences::UserId)
+                            .uuid()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreferences::SubtaskSolved)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreferences::SubtaskReported)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreferences::SubtaskDisabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreferences::SubmissionJudged)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreferences::ContestStarting)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column(
+                        ColumnDef::new(NewContest::ReminderSent)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .drop_column(NewContest::ReminderSent)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NotificationPreferences::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NotificationPreferences {
+    #[iden = "challenges_notification_preferences"]
+    Table,
+    UserId,
+    SubtaskSolved,
+    SubtaskReported,
+    SubtaskDisabled,
+    SubmissionJudged,
+    ContestStarting,
+}
+
+#[derive(Iden)]
+enum NewContest {
+    ReminderSent,
+}
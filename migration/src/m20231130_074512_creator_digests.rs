@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationPreferences::Table)
+                    .add_column(
+                        ColumnDef::new(NotificationPreferences::CreatorDigest)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CreatorDigest::Table)
+                    .col(
+                        ColumnDef::new(CreatorDigest::PeriodStart)
+                            .timestamp()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CreatorDigest::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CreatorDigest::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationPreferences::Table)
+                    .drop_column(NotificationPreferences::CreatorDigest)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NotificationPreferences {
+    #[iden = "challenges_notification_preferences"]
+    Table,
+    CreatorDigest,
+}
+
+#[derive(Iden)]
+enum CreatorDigest {
+    #[iden = "challenges_creator_digests"]
+    Table,
+    PeriodStart,
+    Timestamp,
+}
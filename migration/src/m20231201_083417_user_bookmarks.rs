@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserBookmark::Table)
+                    .col(ColumnDef::new(UserBookmark::UserId).uuid().not_null())
+                    .col(ColumnDef::new(UserBookmark::SubtaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(UserBookmark::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(UserBookmark::UserId)
+                            .col(UserBookmark::SubtaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserBookmark::Table, UserBookmark::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserBookmark::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserBookmark {
+    #[iden = "challenges_user_bookmarks"]
+    Table,
+    UserId,
+    SubtaskId,
+    Timestamp,
+}
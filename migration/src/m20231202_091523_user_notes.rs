@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserNote::Table)
+                    .col(ColumnDef::new(UserNote::UserId).uuid().not_null())
+                    .col(ColumnDef::new(UserNote::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(UserNote::Note).text().not_null())
+                    .col(ColumnDef::new(UserNote::Timestamp).timestamp().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(UserNote::UserId)
+                            .col(UserNote::SubtaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserNote::Table, UserNote::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserNote::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserNote {
+    #[iden = "challenges_user_notes"]
+    Table,
+    UserId,
+    SubtaskId,
+    Note,
+    Timestamp,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubtaskSession::Table)
+                    .col(ColumnDef::new(SubtaskSession::UserId).uuid().not_null())
+                    .col(ColumnDef::new(SubtaskSession::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(SubtaskSession::Start).timestamp().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(SubtaskSession::UserId)
+                            .col(SubtaskSession::SubtaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubtaskSession::Table, SubtaskSession::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubtaskSession::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SubtaskSession {
+    #[iden = "challenges_subtask_sessions"]
+    Table,
+    UserId,
+    SubtaskId,
+    Start,
+}
@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::{Subtask, Task};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Exam::Table)
+                    .col(ColumnDef::new(Exam::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Exam::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Exam::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Exam::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Exam::Title).text().not_null())
+                    .col(ColumnDef::new(Exam::Start).timestamp().not_null())
+                    .col(ColumnDef::new(Exam::End).timestamp().not_null())
+                    .col(ColumnDef::new(Exam::ReleaseTimestamp).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Exam::Table, Exam::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExamAnswer::Table)
+                    .col(ColumnDef::new(ExamAnswer::ExamId).uuid().not_null())
+                    .col(ColumnDef::new(ExamAnswer::UserId).uuid().not_null())
+                    .col(ColumnDef::new(ExamAnswer::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(ExamAnswer::Correct).boolean().not_null())
+                    .col(ColumnDef::new(ExamAnswer::Timestamp).timestamp().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ExamAnswer::ExamId)
+                            .col(ExamAnswer::UserId)
+                            .col(ExamAnswer::SubtaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ExamAnswer::Table, ExamAnswer::ExamId)
+                            .to(Exam::Table, Exam::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ExamAnswer::Table, ExamAnswer::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExamAnswer::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Exam::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Exam {
+    #[iden = "challenges_exams"]
+    Table,
+    Id,
+    TaskId,
+    Creator,
+    CreationTimestamp,
+    Title,
+    Start,
+    End,
+    ReleaseTimestamp,
+}
+
+#[derive(Iden)]
+enum ExamAnswer {
+    #[iden = "challenges_exam_answers"]
+    Table,
+    ExamId,
+    UserId,
+    SubtaskId,
+    Correct,
+    Timestamp,
+}
@@ -0,0 +1,132 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Cohort::Table)
+                    .col(ColumnDef::new(Cohort::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Cohort::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Cohort::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Cohort::Title).text().not_null())
+                    .col(
+                        ColumnDef::new(Cohort::InviteCode)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CohortMember::Table)
+                    .col(ColumnDef::new(CohortMember::CohortId).uuid().not_null())
+                    .col(ColumnDef::new(CohortMember::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(CohortMember::JoinTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(CohortMember::CohortId)
+                            .col(CohortMember::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CohortMember::Table, CohortMember::CohortId)
+                            .to(Cohort::Table, Cohort::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CohortAssignment::Table)
+                    .col(ColumnDef::new(CohortAssignment::CohortId).uuid().not_null())
+                    .col(ColumnDef::new(CohortAssignment::TaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(CohortAssignment::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CohortAssignment::Due).timestamp().null())
+                    .primary_key(
+                        Index::create()
+                            .col(CohortAssignment::CohortId)
+                            .col(CohortAssignment::TaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CohortAssignment::Table, CohortAssignment::CohortId)
+                            .to(Cohort::Table, Cohort::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CohortAssignment::Table, CohortAssignment::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CohortAssignment::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CohortMember::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Cohort::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Cohort {
+    #[iden = "challenges_cohorts"]
+    Table,
+    Id,
+    Creator,
+    CreationTimestamp,
+    Title,
+    InviteCode,
+}
+
+#[derive(Iden)]
+enum CohortMember {
+    #[iden = "challenges_cohort_members"]
+    Table,
+    CohortId,
+    UserId,
+    JoinTimestamp,
+}
+
+#[derive(Iden)]
+enum CohortAssignment {
+    #[iden = "challenges_cohort_assignments"]
+    Table,
+    CohortId,
+    TaskId,
+    CreationTimestamp,
+    Due,
+}
@@ -0,0 +1,160 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(CheatFlagKind::Type)
+                    .values([
+                        CheatFlagKind::RapidSolves,
+                        CheatFlagKind::IdenticalWrongAnswers,
+                        CheatFlagKind::SolvedBeforeUnlock,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(CheatFlagStatus::Type)
+                    .values([
+                        CheatFlagStatus::Open,
+                        CheatFlagStatus::Confirmed,
+                        CheatFlagStatus::Dismissed,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(CheatFlag::Table)
+                    .col(ColumnDef::new(CheatFlag::Id).uuid().primary_key())
+                    .col(ColumnDef::new(CheatFlag::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(CheatFlag::Kind)
+                            .custom(CheatFlagKind::Type)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CheatFlag::Key).text().not_null())
+                    .col(ColumnDef::new(CheatFlag::Details).text().not_null())
+                    .col(ColumnDef::new(CheatFlag::Timestamp).timestamp().not_null())
+                    .col(ColumnDef::new(CheatFlag::HoldRewards).boolean().not_null())
+                    .col(
+                        ColumnDef::new(CheatFlag::Status)
+                            .custom(CheatFlagStatus::Type)
+                            .not_null()
+                            .default("open"),
+                    )
+                    .col(ColumnDef::new(CheatFlag::Reviewer).uuid().null())
+                    .col(
+                        ColumnDef::new(CheatFlag::ReviewTimestamp)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_cheat_flags_user_id_kind_key_idx")
+                    .table(CheatFlag::Table)
+                    .col(CheatFlag::UserId)
+                    .col(CheatFlag::Kind)
+                    .col(CheatFlag::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(HeldReward::Table)
+                    .col(ColumnDef::new(HeldReward::Id).uuid().primary_key())
+                    .col(ColumnDef::new(HeldReward::UserId).uuid().not_null())
+                    .col(ColumnDef::new(HeldReward::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(HeldReward::Xp).big_integer().not_null())
+                    .col(ColumnDef::new(HeldReward::Coins).big_integer().not_null())
+                    .col(ColumnDef::new(HeldReward::Timestamp).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(HeldReward::Table, HeldReward::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(HeldReward::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CheatFlag::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(CheatFlagStatus::Type).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(CheatFlagKind::Type).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CheatFlag {
+    #[iden = "challenges_cheat_flags"]
+    Table,
+    Id,
+    UserId,
+    Kind,
+    Key,
+    Details,
+    Timestamp,
+    HoldRewards,
+    Status,
+    Reviewer,
+    ReviewTimestamp,
+}
+
+#[derive(Iden)]
+enum CheatFlagKind {
+    #[iden = "challenges_cheat_flag_kind"]
+    Type,
+    RapidSolves,
+    IdenticalWrongAnswers,
+    SolvedBeforeUnlock,
+}
+
+#[derive(Iden)]
+enum CheatFlagStatus {
+    #[iden = "challenges_cheat_flag_status"]
+    Type,
+    Open,
+    Confirmed,
+    Dismissed,
+}
+
+#[derive(Iden)]
+enum HeldReward {
+    #[iden = "challenges_held_rewards"]
+    Table,
+    Id,
+    UserId,
+    SubtaskId,
+    Xp,
+    Coins,
+    Timestamp,
+}
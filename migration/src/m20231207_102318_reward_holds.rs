@@ -0,0 +1,118 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(HeldRewardStatus::Type)
+                    .values([
+                        HeldRewardStatus::Held,
+                        HeldRewardStatus::Released,
+                        HeldRewardStatus::Voided,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HeldReward::Table)
+                    .add_column(
+                        ColumnDef::new(HeldReward::Status)
+                            .custom(HeldRewardStatus::Type)
+                            .not_null()
+                            .default("held"),
+                    )
+                    .add_column(ColumnDef::new(HeldReward::ContestId).uuid().null())
+                    .add_column(ColumnDef::new(HeldReward::Resolver).uuid().null())
+                    .add_column(
+                        ColumnDef::new(HeldReward::ResolutionTimestamp)
+                            .timestamp()
+                            .null(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("challenges_held_rewards_contest_id_fkey")
+                            .from_tbl(HeldReward::Table)
+                            .from_col(HeldReward::ContestId)
+                            .to_tbl(Contest::Table)
+                            .to_col(Contest::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column(
+                        ColumnDef::new(Contest::HoldRewards)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .drop_column(Contest::HoldRewards)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HeldReward::Table)
+                    .drop_column(HeldReward::Status)
+                    .drop_column(HeldReward::ContestId)
+                    .drop_column(HeldReward::Resolver)
+                    .drop_column(HeldReward::ResolutionTimestamp)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(HeldRewardStatus::Type).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum HeldReward {
+    #[iden = "challenges_held_rewards"]
+    Table,
+    Status,
+    ContestId,
+    Resolver,
+    ResolutionTimestamp,
+}
+
+#[derive(Iden)]
+enum HeldRewardStatus {
+    #[iden = "challenges_held_reward_status"]
+    Type,
+    Held,
+    Released,
+    Voided,
+}
+
+#[derive(Iden)]
+enum Contest {
+    #[iden = "challenges_contests"]
+    Table,
+    Id,
+    HoldRewards,
+}
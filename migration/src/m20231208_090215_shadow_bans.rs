@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(BanAction::Type)
+            .add_value(BanAction::ShadowCreate)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(
+                        ColumnDef::new(Subtask::PendingReview)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::PendingReview)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum BanAction {
+    #[iden = "challenges_ban_action"]
+    Type,
+    ShadowCreate,
+}
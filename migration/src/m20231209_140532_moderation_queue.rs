@@ -0,0 +1,108 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ReviewStatus::Type)
+                    .values([
+                        ReviewStatus::Approved,
+                        ReviewStatus::Pending,
+                        ReviewStatus::Rejected,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(
+                        ColumnDef::new(Subtask::ReviewStatus)
+                            .custom(ReviewStatus::Type)
+                            .not_null()
+                            .default("approved"),
+                    )
+                    .add_column(ColumnDef::new(Subtask::RejectionReason).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Subtask::Table)
+                    .value(
+                        Subtask::ReviewStatus,
+                        Expr::val("pending").as_enum(ReviewStatus::Type),
+                    )
+                    .and_where(Expr::col(Subtask::PendingReview).eq(true))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::PendingReview)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(
+                        ColumnDef::new(Subtask::PendingReview)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Subtask::Table)
+                    .value(Subtask::PendingReview, true)
+                    .and_where(
+                        Expr::col(Subtask::ReviewStatus)
+                            .eq(Expr::val("pending").as_enum(ReviewStatus::Type)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::ReviewStatus)
+                    .drop_column(Subtask::RejectionReason)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(ReviewStatus::Type).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ReviewStatus {
+    #[iden = "challenges_review_status"]
+    Type,
+    Approved,
+    Pending,
+    Rejected,
+}
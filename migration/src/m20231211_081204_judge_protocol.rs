@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::CodingChallenge;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::ProtocolVersion)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(NewCodingChallenge::ProtocolVersion)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NewCodingChallenge {
+    ProtocolVersion,
+}
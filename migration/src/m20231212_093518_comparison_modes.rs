@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::CodingChallenge;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(Comparison::Type)
+                    .values([
+                        Comparison::Evaluator,
+                        Comparison::Exact,
+                        Comparison::Float,
+                        Comparison::Tokens,
+                        Comparison::TrimmedLines,
+                        Comparison::UnorderedLines,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::Comparison)
+                            .custom(Comparison::Type)
+                            .not_null()
+                            .default("evaluator"),
+                    )
+                    .add_column(ColumnDef::new(NewCodingChallenge::Epsilon).double().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(NewCodingChallenge::Comparison)
+                    .drop_column(NewCodingChallenge::Epsilon)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(Comparison::Type).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewCodingChallenge {
+    Comparison,
+    Epsilon,
+}
+
+#[derive(Iden)]
+pub enum Comparison {
+    #[iden = "challenges_comparison"]
+    Type,
+    Evaluator,
+    Exact,
+    Float,
+    Tokens,
+    TrimmedLines,
+    UnorderedLines,
+}
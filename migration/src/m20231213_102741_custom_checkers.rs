@@ -0,0 +1,123 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge, m20230527_151432_cc_submissions::Verdict,
+    m20231212_093518_comparison_modes::Comparison,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(Comparison::Type)
+            .add_value(NewComparison::Checker)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        let sql = Type::alter()
+            .name(Verdict::Type)
+            .add_value(NewVerdict::PartiallyCorrect)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::CheckerEnvironment)
+                            .text()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(NewCodingChallenge::CheckerCode)
+                            .text()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(ColumnDef::new(SubmissionResult::Score).double().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::Score)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(NewCodingChallenge::CheckerEnvironment)
+                    .drop_column(NewCodingChallenge::CheckerCode)
+                    .to_owned(),
+            )
+            .await?;
+
+        // values cannot be removed from postgres enums, so challenges using a
+        // checker fall back to the evaluator
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(CodingChallenge::Table)
+                    .value(
+                        NewCodingChallenge::Comparison,
+                        Expr::val(Comparison::Evaluator.to_string()).as_enum(Comparison::Type),
+                    )
+                    .and_where(
+                        Expr::expr(
+                            Expr::col(NewCodingChallenge::Comparison).cast_as(Alias::new("text")),
+                        )
+                        .eq(NewComparison::Checker.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NewComparison {
+    Checker,
+}
+
+#[derive(Iden)]
+enum NewVerdict {
+    PartiallyCorrect,
+}
+
+#[derive(Iden)]
+enum NewCodingChallenge {
+    Comparison,
+    CheckerEnvironment,
+    CheckerCode,
+}
+
+#[derive(Iden)]
+enum SubmissionResult {
+    #[iden = "challenges_coding_challenge_result"]
+    Table,
+    Score,
+}
@@ -0,0 +1,121 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge, m20230527_151432_cc_submissions::Submission,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PersonalBest::Table)
+                    .col(ColumnDef::new(PersonalBest::UserId).uuid().not_null())
+                    .col(ColumnDef::new(PersonalBest::SubtaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(PersonalBest::TimeSubmissionId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PersonalBest::Time).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(PersonalBest::MemorySubmissionId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalBest::Memory)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PersonalBest::UserId)
+                            .col(PersonalBest::SubtaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PersonalBest::Table, PersonalBest::SubtaskId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PersonalBest::Table, PersonalBest::TimeSubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PersonalBest::Table, PersonalBest::MemorySubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::PersonalBestTime)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::PersonalBestMemory)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::PersonalBestTime)
+                    .drop_column(SubmissionResult::PersonalBestMemory)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PersonalBest::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum PersonalBest {
+    #[iden = "challenges_personal_bests"]
+    Table,
+    UserId,
+    SubtaskId,
+    TimeSubmissionId,
+    Time,
+    MemorySubmissionId,
+    Memory,
+}
+
+#[derive(Iden)]
+enum SubmissionResult {
+    #[iden = "challenges_coding_challenge_result"]
+    Table,
+    PersonalBestTime,
+    PersonalBestMemory,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubmissionFile::Table)
+                    .col(
+                        ColumnDef::new(SubmissionFile::SubmissionId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SubmissionFile::Name).text().not_null())
+                    .col(ColumnDef::new(SubmissionFile::Content).text().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(SubmissionFile::SubmissionId)
+                            .col(SubmissionFile::Name),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubmissionFile::Table, SubmissionFile::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubmissionFile::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SubmissionFile {
+    #[iden = "challenges_submission_files"]
+    Table,
+    SubmissionId,
+    Name,
+    Content,
+}
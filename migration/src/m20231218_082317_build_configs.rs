@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::CodingChallenge;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BuildConfig::Table)
+                    .col(ColumnDef::new(BuildConfig::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(BuildConfig::Environment).text().not_null())
+                    .col(
+                        ColumnDef::new(BuildConfig::Flags)
+                            .array(ColumnType::Text)
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(BuildConfig::SubtaskId)
+                            .col(BuildConfig::Environment),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(BuildConfig::Table, BuildConfig::SubtaskId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BuildConfig::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum BuildConfig {
+    #[iden = "challenges_build_configs"]
+    Table,
+    SubtaskId,
+    Environment,
+    Flags,
+}
@@ -0,0 +1,32 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_coding_challenge_submissions_creator_idx")
+                    .table(Submission::Table)
+                    .col(Submission::Creator)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("challenges_coding_challenge_submissions_creator_idx")
+                    .table(Submission::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
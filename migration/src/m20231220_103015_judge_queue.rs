@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JudgeQueue::Table)
+                    .col(
+                        ColumnDef::new(JudgeQueue::SubmissionId)
+                            .uuid()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(JudgeQueue::Rejudge)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(JudgeQueue::EnqueuedTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JudgeQueue::Table, JudgeQueue::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_judge_queue_enqueued_timestamp_idx")
+                    .table(JudgeQueue::Table)
+                    .col(JudgeQueue::EnqueuedTimestamp)
+                    .to_owned(),
+            )
+            .await?;
+
+        // submissions without a result have been waiting for the in-process judge
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "insert into challenges_judge_queue (submission_id, rejudge, enqueued_timestamp) \
+                 select s.id, false, s.creation_timestamp \
+                 from challenges_coding_challenge_submissions s \
+                 left join challenges_coding_challenge_result r on r.submission_id = s.id \
+                 where r.submission_id is null",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JudgeQueue::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum JudgeQueue {
+    #[iden = "challenges_judge_queue"]
+    Table,
+    SubmissionId,
+    Rejudge,
+    EnqueuedTimestamp,
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DisabledEnvironment::Table)
+                    .col(
+                        ColumnDef::new(DisabledEnvironment::Environment)
+                            .text()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(DisabledEnvironment::Reason).text())
+                    .col(
+                        ColumnDef::new(DisabledEnvironment::Creator)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DisabledEnvironment::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DisabledEnvironment::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum DisabledEnvironment {
+    #[iden = "challenges_disabled_environments"]
+    Table,
+    Environment,
+    Reason,
+    Creator,
+    Timestamp,
+}
@@ -0,0 +1,30 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // categories that existed before their creation timestamp was recorded
+        // got the time of the migration, so the creation of their first
+        // challenge is used instead
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "update challenges_challenge_categories cat set creation_timestamp = \
+                 x.creation_timestamp from (select c.category_id, min(t.creation_timestamp) as \
+                 creation_timestamp from challenges_challenges c join challenges_tasks t on t.id \
+                 = c.task_id group by c.category_id) x where x.category_id = cat.id and \
+                 x.creation_timestamp < cat.creation_timestamp",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // the backfilled timestamps are valid for the previous schema as well
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(OrganizationRole::Type)
+                    .values([
+                        OrganizationRole::Member,
+                        OrganizationRole::Creator,
+                        OrganizationRole::Admin,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Organization::Table)
+                    .col(ColumnDef::new(Organization::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Organization::Name).text().not_null())
+                    .col(ColumnDef::new(Organization::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Organization::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrganizationMember::Table)
+                    .col(
+                        ColumnDef::new(OrganizationMember::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrganizationMember::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(OrganizationMember::Role)
+                            .custom(OrganizationRole::Type)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMember::JoinTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(OrganizationMember::OrganizationId)
+                            .col(OrganizationMember::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                OrganizationMember::Table,
+                                OrganizationMember::OrganizationId,
+                            )
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_organization_members_user_id_idx")
+                    .table(OrganizationMember::Table)
+                    .col(OrganizationMember::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // existing tasks do not belong to any organization and stay visible
+        // to everyone
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(NewTask::OrganizationId).uuid().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("challenges_tasks_organization_id_fkey")
+                            .from_tbl(Task::Table)
+                            .from_col(NewTask::OrganizationId)
+                            .to_tbl(Organization::Table)
+                            .to_col(Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_tasks_organization_id_idx")
+                    .table(Task::Table)
+                    .col(NewTask::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // tasks of organizations cannot be represented anymore
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Task::Table)
+                    .and_where(Expr::col(NewTask::OrganizationId).is_not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(NewTask::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(OrganizationMember::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Organization::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(OrganizationRole::Type).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NewTask {
+    OrganizationId,
+}
+
+#[derive(Iden)]
+enum Organization {
+    #[iden = "challenges_organizations"]
+    Table,
+    Id,
+    Name,
+    Creator,
+    CreationTimestamp,
+}
+
+#[derive(Iden)]
+enum OrganizationMember {
+    #[iden = "challenges_organization_members"]
+    Table,
+    OrganizationId,
+    UserId,
+    Role,
+    JoinTimestamp,
+}
+
+#[derive(Iden)]
+enum OrganizationRole {
+    #[iden = "challenges_organization_role"]
+    Type,
+    Member,
+    Creator,
+    Admin,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(License::Type)
+                    .values([
+                        License::AllRightsReserved,
+                        License::Cc0,
+                        License::CcBy,
+                        License::CcBySa,
+                        License::CcByNc,
+                        License::CcByNcSa,
+                        License::Mit,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(
+                        ColumnDef::new(Subtask::License)
+                            .custom(License::Type)
+                            .not_null()
+                            .default("all_rights_reserved"),
+                    )
+                    .add_column(ColumnDef::new(Subtask::Attribution).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::License)
+                    .drop_column(Subtask::Attribution)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(License::Type).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum License {
+    #[iden = "challenges_license"]
+    Type,
+    AllRightsReserved,
+    Cc0,
+    CcBy,
+    CcBySa,
+    CcByNc,
+    CcByNcSa,
+    Mit,
+}
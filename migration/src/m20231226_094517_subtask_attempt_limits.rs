@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(ColumnDef::new(Subtask::MaxAttempts).integer().null())
+                    .add_column(
+                        ColumnDef::new(Subtask::RevealSolution)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::MaxAttempts)
+                    .drop_column(Subtask::RevealSolution)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::{MultipleChoice, Subtask};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttemptSeed::Table)
+                    .col(ColumnDef::new(AttemptSeed::UserId).uuid().not_null())
+                    .col(ColumnDef::new(AttemptSeed::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(AttemptSeed::Attempt).integer().not_null())
+                    .col(ColumnDef::new(AttemptSeed::Seed).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(AttemptSeed::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(AttemptSeed::UserId)
+                            .col(AttemptSeed::SubtaskId)
+                            .col(AttemptSeed::Attempt),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AttemptSeed::Table, AttemptSeed::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipleChoice::Table)
+                    .add_column(
+                        ColumnDef::new(MultipleChoice::ShuffleAnswers)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipleChoice::Table)
+                    .drop_column(MultipleChoice::ShuffleAnswers)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(AttemptSeed::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum AttemptSeed {
+    #[iden = "challenges_attempt_seeds"]
+    Table,
+    UserId,
+    SubtaskId,
+    Attempt,
+    Seed,
+    Timestamp,
+    Parameters,
+}
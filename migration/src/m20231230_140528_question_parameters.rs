@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230621_074711_questions::Question, m20231229_090115_attempt_seeds::AttemptSeed};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Question::Table)
+                    .add_column(
+                        ColumnDef::new(Question::Parameters)
+                            .json_binary()
+                            .not_null()
+                            .default(Expr::cust("'[]'::jsonb")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AttemptSeed::Table)
+                    .add_column(ColumnDef::new(AttemptSeed::Parameters).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AttemptSeed::Table)
+                    .drop_column(AttemptSeed::Parameters)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Question::Table)
+                    .drop_column(Question::Parameters)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230621_074711_questions::Question;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AnswerType::Type)
+                    .values([AnswerType::Text, AnswerType::Numeric])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Question::Table)
+                    .add_column(
+                        ColumnDef::new(Question::AnswerType)
+                            .custom(AnswerType::Type)
+                            .not_null()
+                            .default("text"),
+                    )
+                    .add_column(ColumnDef::new(Question::Unit).text().null())
+                    .add_column(ColumnDef::new(Question::AbsoluteTolerance).double().null())
+                    .add_column(ColumnDef::new(Question::RelativeTolerance).double().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Question::Table)
+                    .drop_column(Question::AnswerType)
+                    .drop_column(Question::Unit)
+                    .drop_column(Question::AbsoluteTolerance)
+                    .drop_column(Question::RelativeTolerance)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(AnswerType::Type).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AnswerType {
+    #[iden = "challenges_question_answer_type"]
+    Type,
+    Text,
+    Numeric,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WrongAnswer::Table)
+                    .col(ColumnDef::new(WrongAnswer::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(WrongAnswer::Item).integer().not_null())
+                    .col(ColumnDef::new(WrongAnswer::Choice).integer().not_null())
+                    .col(
+                        ColumnDef::new(WrongAnswer::Count)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(WrongAnswer::SubtaskId)
+                            .col(WrongAnswer::Item)
+                            .col(WrongAnswer::Choice),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WrongAnswer::Table, WrongAnswer::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WrongAnswer::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum WrongAnswer {
+    #[iden = "challenges_wrong_answers"]
+    Table,
+    SubtaskId,
+    Item,
+    Choice,
+    Count,
+}
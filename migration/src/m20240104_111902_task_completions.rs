@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskConfig::Table)
+                    .add_column(
+                        ColumnDef::new(TaskConfig::CompletionXp)
+                            .big_integer()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(TaskConfig::CompletionCoins)
+                            .big_integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskCompletion::Table)
+                    .col(ColumnDef::new(TaskCompletion::UserId).uuid().not_null())
+                    .col(ColumnDef::new(TaskCompletion::TaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TaskCompletion::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TaskCompletion::Xp).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(TaskCompletion::Coins)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TaskCompletion::UserId)
+                            .col(TaskCompletion::TaskId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TaskCompletion::Table, TaskCompletion::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TaskCompletion::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskConfig::Table)
+                    .drop_column(TaskConfig::CompletionXp)
+                    .drop_column(TaskConfig::CompletionCoins)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum TaskConfig {
+    #[iden = "challenges_task_config"]
+    Table,
+    CompletionXp,
+    CompletionCoins,
+}
+
+#[derive(Iden)]
+enum TaskCompletion {
+    #[iden = "challenges_task_completions"]
+    Table,
+    UserId,
+    TaskId,
+    Timestamp,
+    Xp,
+    Coins,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Subtask;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SandboxUsage::Table)
+                    .col(ColumnDef::new(SandboxUsage::SubtaskId).uuid().not_null())
+                    .col(ColumnDef::new(SandboxUsage::Month).date().not_null())
+                    .col(
+                        ColumnDef::new(SandboxUsage::CpuTime)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(SandboxUsage::Runs)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(SandboxUsage::SubtaskId)
+                            .col(SandboxUsage::Month),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SandboxUsage::Table, SandboxUsage::SubtaskId)
+                            .to(Subtask::Table, Subtask::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SandboxUsage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SandboxUsage {
+    #[iden = "challenges_sandbox_usage"]
+    Table,
+    SubtaskId,
+    Month,
+    CpuTime,
+    Runs,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RewardLedger::Table)
+                    .col(
+                        ColumnDef::new(RewardLedger::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RewardLedger::UserId).uuid().not_null())
+                    .col(ColumnDef::new(RewardLedger::Skill).text().null())
+                    .col(
+                        ColumnDef::new(RewardLedger::Xp)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(RewardLedger::Coins)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(RewardLedger::Description).text().null())
+                    .col(
+                        ColumnDef::new(RewardLedger::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("challenges_reward_ledger_user_id_idx")
+                    .table(RewardLedger::Table)
+                    .col(RewardLedger::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RewardLedger::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum RewardLedger {
+    #[iden = "challenges_reward_ledger"]
+    Table,
+    Id,
+    UserId,
+    Skill,
+    Xp,
+    Coins,
+    Description,
+    Timestamp,
+}
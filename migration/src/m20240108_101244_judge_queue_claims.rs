@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeQueue::Table)
+                    .add_column(ColumnDef::new(JudgeQueue::ClaimedUntil).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeQueue::Table)
+                    .drop_column(JudgeQueue::ClaimedUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum JudgeQueue {
+    #[iden = "challenges_judge_queue"]
+    Table,
+    ClaimedUntil,
+}
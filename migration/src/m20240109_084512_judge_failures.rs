@@ -0,0 +1,116 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20230527_151432_cc_submissions::{Submission, SubmissionResult};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(Verdict::Type)
+            .add_value(Verdict::InternalError)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeQueue::Table)
+                    .add_column(
+                        ColumnDef::new(JudgeQueue::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column(
+                        ColumnDef::new(SubmissionRewards::Rewarded)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // accepted submissions have already been rewarded (if they were
+        // eligible), so rejudging them must not grant rewards again
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Submission::Table)
+                    .value(SubmissionRewards::Rewarded, true)
+                    .and_where(
+                        Expr::col(Submission::Id).in_subquery(
+                            Query::select()
+                                .column(SubmissionResult::SubmissionId)
+                                .from(SubmissionResult::Table)
+                                .and_where(
+                                    Expr::expr(
+                                        Expr::col(SubmissionResult::Verdict)
+                                            .cast_as(Alias::new("text")),
+                                    )
+                                    .eq(Verdict::Ok.to_string()),
+                                )
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // values cannot be removed from postgres enums, so the internal_error
+        // verdict is kept
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(SubmissionRewards::Rewarded)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeQueue::Table)
+                    .drop_column(JudgeQueue::Attempts)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum JudgeQueue {
+    #[iden = "challenges_judge_queue"]
+    Table,
+    Attempts,
+}
+
+#[derive(Iden)]
+enum SubmissionRewards {
+    Rewarded,
+}
+
+#[derive(Iden)]
+enum Verdict {
+    #[iden = "challenges_verdict"]
+    Type,
+    Ok,
+    InternalError,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // m20230612_182959_new_course_tasks drops the titles and descriptions
+        // of tasks without moving them to their challenges, so they can only
+        // be copied in databases in which the columns still exist
+        if !manager.has_column("challenges_tasks", "title").await?
+            || !manager
+                .has_column("challenges_tasks", "description")
+                .await?
+        {
+            return Ok(());
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "update challenges_challenges c set title = t.title, description = \
+                 t.description from challenges_tasks t where t.id = c.task_id and c.title = ''",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::Title)
+                    .drop_column(Task::Description)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // the copied titles and descriptions are valid for the previous schema
+        // as well
+        Ok(())
+    }
+}
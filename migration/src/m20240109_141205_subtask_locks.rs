@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230620_093716_reports::Report;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .add_column(
+                        ColumnDef::new(Subtask::Locked)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // subtasks that could be unlocked before were disabled subtasks with
+        // an unlock price
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Subtask::Table)
+                    .value(Subtask::Locked, true)
+                    .value(Subtask::Enabled, true)
+                    .and_where(Expr::col(Subtask::Enabled).eq(false))
+                    .and_where(Expr::col(Subtask::Retired).eq(false))
+                    .and_where(
+                        Expr::expr(Expr::col(Subtask::ReviewStatus).cast_as(Alias::new("text")))
+                            .eq("approved"),
+                    )
+                    .and_where(Expr::col(Subtask::UnlockPrice).is_not_null())
+                    .and_where(
+                        Expr::col(Subtask::Id).not_in_subquery(
+                            Query::select()
+                                .column(Report::SubtaskId)
+                                .from(Report::Table)
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Subtask::Table)
+                    .value(Subtask::Enabled, false)
+                    .and_where(Expr::col(Subtask::Locked).eq(true))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subtask::Table)
+                    .drop_column(Subtask::Locked)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Subtask {
+    #[iden = "challenges_subtasks"]
+    Table,
+    Id,
+    Enabled,
+    Retired,
+    ReviewStatus,
+    UnlockPrice,
+    Locked,
+}
@@ -0,0 +1,11 @@
+#![forbid(unsafe_code)]
+#![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
+
+//! Protocol buffer definitions of the internal gRPC api of the challenges
+//! microservice, shared with other microservices.
+
+pub mod challenges {
+    #![allow(clippy::all)]
+
+    tonic::include_proto!("challenges");
+}
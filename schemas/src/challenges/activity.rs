@@ -0,0 +1,27 @@
+use chrono::NaiveDate;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Object, Serialize, Deserialize)]
+pub struct Activity {
+    /// The year of the activity.
+    pub year: i32,
+    /// The activity on each day with at least one attempt or solve, in
+    /// ascending order.
+    pub days: Vec<ActivityDay>,
+    /// The total number of attempts in this year.
+    pub total_attempts: u64,
+    /// The total number of solved subtasks in this year.
+    pub total_solves: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object, Serialize, Deserialize)]
+pub struct ActivityDay {
+    /// The day (UTC).
+    pub date: NaiveDate,
+    /// The number of attempts to solve subtasks (including coding challenge
+    /// submissions) on this day.
+    pub attempts: u64,
+    /// The number of subtasks solved on this day.
+    pub solves: u64,
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use entity::sea_orm_active_enums::{ChallengesBanAction, ChallengesReviewStatus};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Metrics {
+    /// The number of requests that took longer than the configured threshold
+    /// since the microservice has been started.
+    pub slow_requests: u64,
+    /// The number of database queries that took longer than the configured
+    /// threshold since the microservice has been started.
+    pub slow_queries: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct MigrationStatus {
+    /// The name of the database migration.
+    pub name: String,
+    /// Whether the migration has been applied.
+    pub applied: bool,
+    /// The time the migration has been applied at.
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Whether the migration has been applied by a newer version of the
+    /// microservice and is unknown to this version.
+    pub unknown: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubtaskAccessExplanation {
+    /// Whether the user can access the subtask.
+    pub can_access: bool,
+    /// Whether the user is a platform admin. Admins can access all subtasks.
+    pub admin: bool,
+    /// Whether the user has created the subtask.
+    pub creator: bool,
+    /// Whether the task is visible to the user, i.e. it does not belong to an
+    /// organization or the user is acting in its organization.
+    pub task_visible: bool,
+    /// Whether the subtask is enabled. Disabled subtasks can only be accessed
+    /// by their creator and users that have been granted access.
+    pub enabled: bool,
+    /// Whether the subtask is locked. Locked subtasks can only be accessed by
+    /// their creator and users that have been granted access.
+    pub locked: bool,
+    /// Whether the subtask has been retired.
+    pub retired: bool,
+    /// The review status of the subtask.
+    pub review_status: ChallengesReviewStatus,
+    /// The time the user has been granted access to the subtask (e.g. by
+    /// buying or unlocking it).
+    pub access_granted: Option<DateTime<Utc>>,
+    /// The price for unlocking the subtask while it is locked.
+    pub unlock_price: Option<u64>,
+    /// Whether the subtask is not part of the user's pool and therefore
+    /// cannot be accessed by them.
+    pub hidden_by_pool: bool,
+    /// The prerequisites of the subtask the user has not solved yet.
+    pub missing_prerequisites: Vec<Uuid>,
+    /// The actions the user is currently banned from.
+    pub active_bans: Vec<ChallengesBanAction>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SandboxUsageStats {
+    /// The first day of the month.
+    pub month: NaiveDate,
+    /// The usage of each coding challenge, sorted by cpu time.
+    pub challenges: Vec<ChallengeSandboxUsage>,
+    /// The usage of the challenges of each creator, sorted by cpu time.
+    pub creators: Vec<CreatorSandboxUsage>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ChallengeSandboxUsage {
+    pub subtask_id: Uuid,
+    pub task_id: Uuid,
+    pub creator: Uuid,
+    /// The cpu time (in milliseconds) used in the sandbox.
+    pub cpu_time: u64,
+    /// The number of programs built or run in the sandbox.
+    pub runs: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreatorSandboxUsage {
+    pub creator: Uuid,
+    /// The cpu time (in milliseconds) used in the sandbox.
+    pub cpu_time: u64,
+    /// The number of programs built or run in the sandbox.
+    pub runs: u64,
+}
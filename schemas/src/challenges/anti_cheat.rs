@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_cheat_flags,
+    sea_orm_active_enums::{ChallengesCheatFlagKind, ChallengesCheatFlagStatus},
+};
+use poem_openapi::{Enum, Object};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct CheatFlag {
+    /// The unique identifier of the flag.
+    pub id: Uuid,
+    /// The flagged user.
+    pub user_id: Uuid,
+    /// The kind of suspicious pattern.
+    pub kind: ChallengesCheatFlagKind,
+    /// A description of the suspicious pattern.
+    pub details: String,
+    /// The time the pattern has been detected.
+    pub timestamp: DateTime<Utc>,
+    /// Whether the rewards of the user are held until the flag has been
+    /// reviewed.
+    pub hold_rewards: bool,
+    /// The review status of the flag.
+    pub status: ChallengesCheatFlagStatus,
+    /// The admin who has reviewed the flag.
+    pub reviewer: Option<Uuid>,
+    /// The time the flag has been reviewed.
+    pub review_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ReviewCheatFlagRequest {
+    pub verdict: CheatFlagVerdict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CheatFlagVerdict {
+    /// The user has cheated. Held rewards are voided.
+    Confirmed,
+    /// The pattern is harmless. Held rewards are sent once all flags of the
+    /// user have been reviewed.
+    Dismissed,
+}
+
+impl From<challenges_cheat_flags::Model> for CheatFlag {
+    fn from(value: challenges_cheat_flags::Model) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            kind: value.kind,
+            details: value.details,
+            timestamp: value.timestamp.and_utc(),
+            hold_rewards: value.hold_rewards,
+            status: value.status,
+            reviewer: value.reviewer,
+            review_timestamp: value.review_timestamp.map(|x| x.and_utc()),
+        }
+    }
+}
+
+impl From<CheatFlagVerdict> for ChallengesCheatFlagStatus {
+    fn from(value: CheatFlagVerdict) -> Self {
+        match value {
+            CheatFlagVerdict::Confirmed => Self::Confirmed,
+            CheatFlagVerdict::Dismissed => Self::Dismissed,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use entity::challenges_clozes;
+use poem_ext::patch_value::PatchValue;
+use poem_openapi::Object;
+
+use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
+
+/// The placeholder that marks a blank in the text of a cloze.
+pub const BLANK: &str = "{{}}";
+
+#[derive(Debug, Clone, Object)]
+pub struct ClozeSummary {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The text of the cloze. Each blank is marked with `{{}}`.
+    pub text: String,
+    /// The blanks in the order of their placeholders in the text.
+    pub blanks: Vec<ClozeBlank>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct Cloze {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The text of the cloze. Each blank is marked with `{{}}`.
+    pub text: String,
+    /// The blanks in the order of their placeholders in the text.
+    pub blanks: Vec<ClozeBlank>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ClozeWithSolution {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The text of the cloze. Each blank is marked with `{{}}`.
+    pub text: String,
+    /// The blanks in the order of their placeholders in the text.
+    pub blanks: Vec<ClozeBlankWithSolution>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ClozeBlank {
+    /// Whether the answer is case sensitive.
+    pub case_sensitive: bool,
+    /// Whether the answer can contain letters.
+    pub ascii_letters: bool,
+    /// Whether the answer can contain digits.
+    pub digits: bool,
+    /// Whether the answer can contain puncutation characters
+    pub punctuation: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ClozeBlankWithSolution {
+    /// The possible answers for this blank.
+    #[oai(validator(min_items = 1, max_items = 32, max_length = 256))]
+    pub answers: Vec<String>,
+    /// Whether the answer is case sensitive.
+    pub case_sensitive: bool,
+    /// Whether the answer can contain letters.
+    pub ascii_letters: bool,
+    /// Whether the answer can contain digits.
+    pub digits: bool,
+    /// Whether the answer can contain puncutation characters
+    pub punctuation: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateClozeRequest {
+    #[oai(flatten)]
+    pub subtask: CreateSubtaskRequest,
+    /// The text of the cloze. Each blank is marked with `{{}}`.
+    #[oai(validator(max_length = 4096))]
+    pub text: String,
+    /// The blanks in the order of their placeholders in the text.
+    #[oai(validator(min_items = 1, max_items = 32))]
+    pub blanks: Vec<ClozeBlankWithSolution>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UpdateClozeRequest {
+    #[oai(flatten)]
+    pub subtask: UpdateSubtaskRequest,
+    /// The text of the cloze. Each blank is marked with `{{}}`.
+    #[oai(validator(max_length = 4096))]
+    pub text: PatchValue<String>,
+    /// The blanks in the order of their placeholders in the text.
+    #[oai(validator(min_items = 1, max_items = 32))]
+    pub blanks: PatchValue<Vec<ClozeBlankWithSolution>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveClozeRequest {
+    /// One answer per blank in the order of their placeholders in the text.
+    #[oai(validator(max_items = 32, max_length = 256))]
+    pub answers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveClozeFeedback {
+    /// Whether the user has successfully solved the cloze.
+    pub solved: bool,
+    /// The indices of the blanks that have been filled in incorrectly.
+    pub wrong: Vec<u8>,
+}
+
+impl ClozeSummary {
+    pub fn from(cloze: challenges_clozes::Model, subtask: Subtask) -> Self {
+        Self {
+            text: cloze.text,
+            blanks: cloze.blanks.0.into_iter().map(Into::into).collect(),
+            subtask,
+        }
+    }
+}
+
+impl Cloze {
+    pub fn from(cloze: challenges_clozes::Model, subtask: Subtask) -> Self {
+        Self {
+            text: cloze.text,
+            blanks: cloze.blanks.0.into_iter().map(Into::into).collect(),
+            subtask,
+        }
+    }
+}
+
+impl ClozeWithSolution {
+    pub fn from(cloze: challenges_clozes::Model, subtask: Subtask) -> Self {
+        Self {
+            text: cloze.text,
+            blanks: cloze.blanks.0.into_iter().map(Into::into).collect(),
+            subtask,
+        }
+    }
+}
+
+impl From<challenges_clozes::ClozeBlank> for ClozeBlank {
+    fn from(value: challenges_clozes::ClozeBlank) -> Self {
+        Self {
+            case_sensitive: value.case_sensitive,
+            ascii_letters: value.ascii_letters,
+            digits: value.digits,
+            punctuation: value.punctuation,
+        }
+    }
+}
+
+impl From<challenges_clozes::ClozeBlank> for ClozeBlankWithSolution {
+    fn from(value: challenges_clozes::ClozeBlank) -> Self {
+        Self {
+            answers: value.answers,
+            case_sensitive: value.case_sensitive,
+            ascii_letters: value.ascii_letters,
+            digits: value.digits,
+            punctuation: value.punctuation,
+        }
+    }
+}
+
+impl From<ClozeBlankWithSolution> for challenges_clozes::ClozeBlank {
+    fn from(value: ClozeBlankWithSolution) -> Self {
+        Self {
+            answers: value.answers,
+            case_sensitive: value.case_sensitive,
+            ascii_letters: value.ascii_letters,
+            digits: value.digits,
+            punctuation: value.punctuation,
+        }
+    }
+}
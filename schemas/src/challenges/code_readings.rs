@@ -0,0 +1,103 @@
+use entity::challenges_code_readings;
+use poem_ext::patch_value::PatchValue;
+use poem_openapi::Object;
+
+use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
+
+#[derive(Debug, Clone, Object)]
+pub struct CodeReadingSummary {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The environment the program is written for.
+    pub environment: String,
+    /// The program whose output has to be predicted.
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CodeReading {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The environment the program is written for.
+    pub environment: String,
+    /// The program whose output has to be predicted.
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CodeReadingWithSolution {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The environment the program is written for.
+    pub environment: String,
+    /// The program whose output has to be predicted.
+    pub code: String,
+    /// The output of the program, as determined by running it.
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateCodeReadingRequest {
+    #[oai(flatten)]
+    pub subtask: CreateSubtaskRequest,
+    /// The environment the program is written for.
+    pub environment: String,
+    /// The program whose output has to be predicted.
+    #[oai(validator(max_length = 65536))]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UpdateCodeReadingRequest {
+    #[oai(flatten)]
+    pub subtask: UpdateSubtaskRequest,
+    /// The environment the program is written for.
+    pub environment: PatchValue<String>,
+    /// The program whose output has to be predicted.
+    #[oai(validator(max_length = 65536))]
+    pub code: PatchValue<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveCodeReadingRequest {
+    /// The output the user expects the program to print.
+    #[oai(validator(max_length = 65536))]
+    pub answer: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveCodeReadingFeedback {
+    /// Whether the user has successfully solved the question.
+    pub solved: bool,
+}
+
+impl CodeReadingSummary {
+    pub fn from(code_reading: challenges_code_readings::Model, subtask: Subtask) -> Self {
+        Self {
+            environment: code_reading.environment,
+            code: code_reading.code,
+            subtask,
+        }
+    }
+}
+
+impl CodeReading {
+    pub fn from(code_reading: challenges_code_readings::Model, subtask: Subtask) -> Self {
+        Self {
+            environment: code_reading.environment,
+            code: code_reading.code,
+            subtask,
+        }
+    }
+}
+
+impl CodeReadingWithSolution {
+    pub fn from(code_reading: challenges_code_readings::Model, subtask: Subtask) -> Self {
+        Self {
+            environment: code_reading.environment,
+            code: code_reading.code,
+            output: code_reading.output,
+            subtask,
+        }
+    }
+}
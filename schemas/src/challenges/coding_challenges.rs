@@ -1,16 +1,20 @@
 use chrono::{DateTime, Utc};
 use entity::{
-    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
-    challenges_coding_challenges, sea_orm_active_enums::ChallengesVerdict,
+    challenges_build_configs,
+    challenges_coding_challenge_result::{self, Resources, TranscriptRound},
+    challenges_coding_challenge_submissions, challenges_coding_challenges,
+    challenges_disabled_environments, challenges_output_submissions, challenges_personal_bests,
+    challenges_submission_artifacts,
+    sea_orm_active_enums::{ChallengesComparison, ChallengesFeedbackLevel, ChallengesVerdict},
 };
 use poem_ext::patch_value::PatchValue;
 use poem_openapi::{
     types::{ParseFromJSON, ToJSON, Type},
-    Object,
+    Enum, Object,
 };
 use sandkasten_client::schemas::{
     configuration::PublicConfig,
-    programs::{ResourceUsage, RunResult},
+    programs::{BuildRunResult, File, ResourceUsage, RunResult},
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -25,6 +29,33 @@ pub struct QueueStatus {
     pub active: usize,
     /// The number of submissions that are waiting to be picked up by a worker.
     pub waiting: usize,
+    /// The number of judge tokens this replica has acquired since it started.
+    pub acquired_tokens: u64,
+    /// The average time (in milliseconds) this replica had to wait for a judge
+    /// token.
+    pub average_wait_time: u64,
+    /// The longest time (in milliseconds) this replica had to wait for a judge
+    /// token.
+    pub max_wait_time: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PendingSubmissions {
+    /// The maximum number of submissions a user may have pending at the same
+    /// time.
+    pub limit: usize,
+    /// The submissions of the user that have not been judged yet.
+    pub submissions: Vec<PendingSubmission>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PendingSubmission {
+    /// The unique identifier of the submission.
+    pub id: Uuid,
+    /// The challenge of the submission.
+    pub subtask_id: Uuid,
+    /// The number of submissions in the judge's queue before this one.
+    pub queue_position: Option<usize>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -41,6 +72,9 @@ pub struct CodingChallengeSummary {
     pub static_tests: u8,
     /// The number of random tests to run for submission evaluation.
     pub random_tests: u8,
+    /// Whether users upload the outputs for the published inputs instead of
+    /// submitting code.
+    pub output_only: bool,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -57,6 +91,41 @@ pub struct CodingChallenge {
     pub static_tests: u8,
     /// The number of random tests to run for submission evaluation.
     pub random_tests: u8,
+    /// Whether users upload the outputs for the published inputs instead of
+    /// submitting code.
+    pub output_only: bool,
+    /// The current version of the evaluator.
+    pub evaluator_version: i32,
+    /// How much information the results of submissions reveal.
+    pub feedback_level: ChallengesFeedbackLevel,
+    /// The version of the protocol the evaluator implements.
+    pub protocol_version: i32,
+    /// How the output of a solution is compared with the expected output
+    /// unless the evaluator specifies a comparison for a test.
+    pub comparison: ChallengesComparison,
+    /// The maximum difference between numbers for the `FLOAT` comparison.
+    pub epsilon: Option<f64>,
+    /// Whether the challenge has a checker program.
+    pub has_checker: bool,
+    /// Whether accepted submissions are ranked by the length of their code.
+    pub golf: bool,
+    /// Whether users who have solved the challenge can see the solution.
+    pub solution_public_after_solve: bool,
+    /// The best accepted submissions of the authenticated user.
+    pub personal_best: Option<PersonalBest>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PersonalBest {
+    /// The accepted submission with the lowest runtime.
+    pub time_submission_id: Uuid,
+    /// The number of milliseconds this submission ran in its slowest test.
+    pub time: u64,
+    /// The accepted submission with the lowest memory usage.
+    pub memory_submission_id: Uuid,
+    /// The number of kilobytes of memory this submission used in its most
+    /// memory intensive test.
+    pub memory: u64,
 }
 
 #[derive(Debug, Clone, Object, Serialize, Deserialize)]
@@ -71,6 +140,14 @@ pub struct Example {
     pub explanation: Option<String>,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct CodingChallengeExamples {
+    pub examples: Vec<Example>,
+    /// The time the examples have been generated at. Examples are cached
+    /// until the evaluator or the solution of the challenge is updated.
+    pub generated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Object)]
 pub struct CreateCodingChallengeRequest {
     #[oai(flatten)]
@@ -101,10 +178,50 @@ pub struct CreateCodingChallengeRequest {
     /// The solution code
     #[oai(validator(max_length = 65536))]
     pub solution_code: String,
+    /// Whether users upload the outputs for the published inputs instead of
+    /// submitting code. The static tests are published as inputs in this case.
+    #[oai(default)]
+    pub output_only: bool,
+    /// How much information the results of submissions reveal.
+    #[oai(default = "feedback_level_default")]
+    pub feedback_level: ChallengesFeedbackLevel,
+    /// The version of the protocol the evaluator implements. Evaluators using
+    /// version `2` describe each test with structured metadata.
+    #[oai(
+        default = "protocol_version_default",
+        validator(minimum(value = "1"), maximum(value = "2"))
+    )]
+    pub protocol_version: i32,
+    /// How the output of a solution is compared with the expected output
+    /// unless the evaluator specifies a comparison for a test. The expected
+    /// output is the output of the solution unless the evaluator provides it.
+    #[oai(default = "comparison_default")]
+    pub comparison: ChallengesComparison,
+    /// The maximum difference between numbers for the `FLOAT` comparison.
+    #[oai(validator(minimum(value = "0")))]
+    pub epsilon: Option<f64>,
+    /// The program which decides whether the output of a solution is correct
+    /// if the `CHECKER` comparison is used.
+    pub checker: Option<Checker>,
+    /// Whether accepted submissions are ranked by the length of their code.
+    #[oai(default)]
+    pub golf: bool,
+    /// Whether users who have solved the challenge can see the solution.
+    #[oai(default)]
+    pub solution_public_after_solve: bool,
 }
 fn tests_default() -> u8 {
     10
 }
+fn feedback_level_default() -> ChallengesFeedbackLevel {
+    ChallengesFeedbackLevel::FullDiff
+}
+fn protocol_version_default() -> i32 {
+    1
+}
+fn comparison_default() -> ChallengesComparison {
+    ChallengesComparison::Evaluator
+}
 
 #[derive(Debug, Clone, Object)]
 pub struct UpdateCodingChallengeRequest {
@@ -133,6 +250,92 @@ pub struct UpdateCodingChallengeRequest {
     /// The solution code
     #[oai(validator(max_length = 65536))]
     pub solution_code: PatchValue<String>,
+    /// Whether users upload the outputs for the published inputs instead of
+    /// submitting code. The static tests are published as inputs in this case.
+    pub output_only: PatchValue<bool>,
+    /// How much information the results of submissions reveal.
+    pub feedback_level: PatchValue<ChallengesFeedbackLevel>,
+    /// The version of the protocol the evaluator implements.
+    #[oai(validator(minimum(value = "1"), maximum(value = "2")))]
+    pub protocol_version: PatchValue<i32>,
+    /// How the output of a solution is compared with the expected output
+    /// unless the evaluator specifies a comparison for a test.
+    pub comparison: PatchValue<ChallengesComparison>,
+    /// The maximum difference between numbers for the `FLOAT` comparison.
+    #[oai(validator(minimum(value = "0")))]
+    pub epsilon: PatchValue<Option<f64>>,
+    /// The program which decides whether the output of a solution is correct
+    /// if the `CHECKER` comparison is used.
+    pub checker: PatchValue<Option<Checker>>,
+    /// Whether accepted submissions are ranked by the length of their code.
+    pub golf: PatchValue<bool>,
+    /// Whether users who have solved the challenge can see the solution.
+    pub solution_public_after_solve: PatchValue<bool>,
+}
+
+/// A program which decides whether the output of a solution is correct.
+///
+/// The checker receives a JSON object with the `input`, the `expected` output
+/// and the `output` of the solution on stdin and prints a JSON object with a
+/// `verdict` (`"accept"`, `"reject"` or `"partial"`), an optional `message`
+/// and a `score` between 0 and 1 for partial verdicts.
+#[derive(Debug, Clone, Object)]
+pub struct Checker {
+    /// The environment to run the checker in.
+    pub environment: String,
+    /// The code of the checker.
+    #[oai(validator(max_length = 65536))]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BuildConfig {
+    /// The environment the build flags apply to.
+    pub environment: String,
+    /// The flags which are passed to the build of solutions in this
+    /// environment.
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SetBuildConfigRequest {
+    /// The flags which are passed to the build of solutions in this
+    /// environment. Every flag must be allowed for the environment.
+    #[oai(validator(max_items = 16))]
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CodingEnvironment {
+    /// The id of the environment, which is used in submissions.
+    pub id: String,
+    /// The display name of the environment (e.g. `Rust` or `C++`).
+    pub name: String,
+    /// The version of the environment.
+    pub version: String,
+    /// Whether new submissions in this environment are rejected.
+    pub disabled: bool,
+    /// Why the environment has been disabled.
+    pub disabled_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DisabledEnvironment {
+    /// The id of the environment.
+    pub environment: String,
+    /// Why the environment has been disabled.
+    pub reason: Option<String>,
+    /// The admin who disabled the environment.
+    pub creator: Uuid,
+    /// When the environment has been disabled.
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DisableEnvironmentRequest {
+    /// Why the environment is disabled. The reason is shown to users.
+    #[oai(validator(max_length = 1024))]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -147,10 +350,62 @@ pub struct Submission {
     pub creation_timestamp: DateTime<Utc>,
     /// The environment of the submission.
     pub environment: String,
+    /// The number of bytes of the submitted code.
+    pub code_length: u32,
+    /// Whether the creator is shown to other users browsing the accepted
+    /// solutions of this challenge.
+    pub attributed: bool,
+    /// The revision of the coding challenge at the time of the submission.
+    pub revision_id: Option<Uuid>,
     /// The evaluation result of the submission.
     pub result: Option<CheckResult<RunSummary>>,
+    /// The version of the evaluator the submission has been judged against.
+    pub evaluator_version: Option<i32>,
+    /// The seed of the first failed test case, if the feedback level of the
+    /// challenge reveals it.
+    pub failed_seed: Option<String>,
     /// The number of submissions in the judge's queue before this one.
     pub queue_position: Option<usize>,
+    /// The resources the solution used, if it passed all tests.
+    pub resources: Option<SubmissionResources>,
+    /// The personal bests of the creator this submission improved, if it
+    /// passed all tests.
+    pub new_personal_best: Option<NewPersonalBest>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct NewPersonalBest {
+    /// Whether the submission has the lowest runtime of all accepted
+    /// submissions of the creator at the time it was judged.
+    pub time: bool,
+    /// Whether the submission has the lowest memory usage of all accepted
+    /// submissions of the creator at the time it was judged.
+    pub memory: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmissionResources {
+    /// The time limit of the challenge when the submission was judged.
+    pub time_limit: u64,
+    /// The memory limit of the challenge when the submission was judged.
+    pub memory_limit: u64,
+    /// The maximum resources the solution used in any test.
+    pub max: TestResourceUsage,
+    /// The resources the solution used in each test, in the order the tests
+    /// were run.
+    pub tests: Vec<TestResourceUsage>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct TestResourceUsage {
+    /// The number of milliseconds the solution ran.
+    pub time: u64,
+    /// The number of kilobytes of memory the solution used.
+    pub memory: u64,
+    /// The percentage of the time limit the solution used.
+    pub time_percentage: f64,
+    /// The percentage of the memory limit the solution used.
+    pub memory_percentage: f64,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -160,6 +415,183 @@ pub struct SubmissionContent {
     /// The solution code.
     #[oai(validator(max_length = 65536))]
     pub code: String,
+    /// Additional files which are put next to the main file.
+    #[oai(default, validator(max_items = 10))]
+    pub files: Vec<File>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmissionArtifacts {
+    /// The stdout output of the solution on the first failed test case.
+    pub stdout: String,
+    /// The stderr output of the solution on the first failed test case.
+    pub stderr: String,
+    /// Whether stdout or stderr have been truncated.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateSubmissionRequest {
+    /// The environment to run the solution in.
+    pub environment: String,
+    /// The solution code.
+    #[oai(validator(max_length = 65536))]
+    pub code: String,
+    /// Additional files which are put next to the main file.
+    #[oai(default, validator(max_items = 10))]
+    pub files: Vec<File>,
+    /// Whether to show the creator to other users browsing the accepted
+    /// solutions of this challenge. Solutions are anonymized by default.
+    #[oai(default)]
+    pub attributed: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PublishedInput {
+    /// The unique identifier of the input.
+    pub id: String,
+    /// The input for which the output has to be uploaded.
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmitOutputsRequest {
+    /// The uploaded outputs. Inputs without an output are rejected with the
+    /// `NO_OUTPUT` verdict.
+    #[oai(validator(max_items = 20))]
+    pub outputs: Vec<UploadedOutput>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UploadedOutput {
+    /// The unique identifier of the input.
+    pub input_id: String,
+    /// The output for this input.
+    #[oai(validator(max_length = 1048576))]
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct OutputSubmission {
+    /// The unique identifier of the submission.
+    pub id: Uuid,
+    /// The challenge of the submission.
+    pub subtask_id: Uuid,
+    /// The creator of the submission.
+    pub creator: Uuid,
+    /// The creation timestamp of the submission.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The revision of the coding challenge at the time of the submission.
+    pub revision_id: Option<Uuid>,
+    /// The number of accepted outputs.
+    pub passed: u32,
+    /// The number of published inputs.
+    pub total: u32,
+    /// The verdicts of the uploaded outputs in the order of the published
+    /// inputs.
+    pub results: Vec<OutputResult>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmitOutputsFeedback {
+    #[oai(flatten)]
+    pub submission: OutputSubmission,
+    /// The number of published inputs for which the user has uploaded an
+    /// accepted output in this or a previous submission.
+    pub credited: u32,
+    /// Whether all published inputs have been credited.
+    pub solved: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct OutputResult {
+    /// The unique identifier of the input.
+    pub input_id: String,
+    pub verdict: ChallengesVerdict,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PublicSolutions {
+    /// The accepted solutions of the current page.
+    pub solutions: Vec<PublicSolution>,
+    /// The total number of accepted solutions.
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PublicSolution {
+    /// The unique identifier of the submission.
+    pub id: Uuid,
+    /// The creator of the submission (`null` if the creator has not opted in
+    /// to attribution).
+    pub creator: Option<Uuid>,
+    /// The creation timestamp of the submission.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The environment of the submission.
+    pub environment: String,
+    /// The solution code.
+    pub code: String,
+    /// The maximum number of milliseconds the solution ran on a single test.
+    pub time: Option<u64>,
+    /// The maximum amount of memory (in KB) the solution used on a single
+    /// test.
+    pub memory: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    /// The submission is waiting in the judge queue or is being judged.
+    Pending,
+    /// The submission has been cancelled by an admin.
+    Cancelled,
+    /// The submission could not be judged because of an internal error.
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum SolutionsSort {
+    Runtime,
+    Memory,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct EvaluatorVersion {
+    /// The version number, starting at 1.
+    pub version: i32,
+    /// The user who created this version.
+    pub creator: Uuid,
+    /// The creation timestamp of this version.
+    pub timestamp: DateTime<Utc>,
+    /// Whether this is the version new submissions are judged against.
+    pub current: bool,
+    /// The number of submissions that have been judged against this version.
+    pub submissions: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    /// The line exists in both versions.
+    Unchanged,
+    /// The line has been removed in the newer version.
+    Removed,
+    /// The line has been added in the newer version.
+    Added,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct Rejudge {
+    /// The number of submissions that have been enqueued to be judged again.
+    pub submissions: u64,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -180,12 +612,68 @@ pub struct RunSummary {
     pub resource_usage: ResourceUsage,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct CheckReport {
+    /// Whether the solution passed all tests.
+    pub passed: bool,
+    pub tests: Vec<CheckReportTest>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CheckReportTest {
+    pub seed: String,
+    pub kind: CheckTestKind,
+    pub passed: bool,
+    pub verdict: ChallengesVerdict,
+    pub reason: Option<String>,
+    /// The number of milliseconds the solution ran, if it has been run.
+    pub time: Option<u64>,
+    /// The amount of memory (in KB) the solution used, if it has been run.
+    pub memory: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum CheckTestKind {
+    /// One of the examples provided by the evaluator.
+    Example,
+    /// A static test, whose input is the same every time.
+    Static,
+    /// A test with a random seed.
+    Random,
+}
+
 #[derive(Debug, Clone, Object, Serialize, Deserialize)]
 pub struct CheckResult<T: Send + Sync + Type + ParseFromJSON + ToJSON> {
     pub verdict: ChallengesVerdict,
     pub reason: Option<String>,
+    /// The fraction of the test the solution solved, if the checker of the
+    /// challenge reported a `PARTIALLY_CORRECT` verdict.
+    #[serde(default)]
+    pub score: Option<f64>,
     pub compile: Option<T>,
     pub run: Option<T>,
+    /// The messages exchanged between the evaluator and the solution, if the
+    /// challenge is interactive. `run` contains the result of the last round.
+    pub transcript: Option<Vec<InteractionRound>>,
+}
+
+#[derive(Debug, Clone, Object, Serialize, Deserialize)]
+pub struct InteractionRound {
+    /// The message the evaluator sent to the solution.
+    pub judge: String,
+    /// The line the solution printed in response, if any.
+    pub program: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct EvaluatorProtocolError {
+    /// The evaluator command that produced the invalid output.
+    pub command: String,
+    /// A description of what is wrong with the output.
+    pub message: String,
+    /// The result of running the evaluator.
+    pub output: BuildRunResult,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -204,6 +692,7 @@ impl CodingChallengeSummary {
             memory_limit: cc.memory_limit as _,
             static_tests: cc.static_tests as _,
             random_tests: cc.random_tests as _,
+            output_only: cc.output_only,
             subtask,
         }
     }
@@ -217,11 +706,50 @@ impl CodingChallenge {
             memory_limit: cc.memory_limit as _,
             static_tests: cc.static_tests as _,
             random_tests: cc.random_tests as _,
+            output_only: cc.output_only,
+            evaluator_version: cc.evaluator_version,
+            feedback_level: cc.feedback_level,
+            protocol_version: cc.protocol_version,
+            comparison: cc.comparison,
+            epsilon: cc.epsilon,
+            has_checker: cc.checker_code.is_some(),
+            golf: cc.golf,
+            solution_public_after_solve: cc.solution_public_after_solve,
+            personal_best: None,
             subtask,
         }
     }
 }
 
+impl<T: Send + Sync + Type + ParseFromJSON + ToJSON> CheckResult<T> {
+    /// Remove all details that are not revealed by the given feedback level.
+    ///
+    /// - `VERDICT_ONLY`: only the verdict.
+    /// - `FIRST_FAILED_SEED`: the verdict and the compiler output. The seed of
+    ///   the failed test case is part of the submission.
+    /// - `FULL_DIFF`: everything, incl. the reason provided by the evaluator
+    ///   and the output of the solution.
+    pub fn restrict(self, feedback_level: ChallengesFeedbackLevel) -> Self {
+        match feedback_level {
+            ChallengesFeedbackLevel::VerdictOnly => Self {
+                verdict: self.verdict,
+                reason: None,
+                score: self.score,
+                compile: None,
+                run: None,
+                transcript: None,
+            },
+            ChallengesFeedbackLevel::FirstFailedSeed => Self {
+                reason: None,
+                run: None,
+                transcript: None,
+                ..self
+            },
+            ChallengesFeedbackLevel::FullDiff => self,
+        }
+    }
+}
+
 impl From<RunResult> for RunSummary {
     fn from(value: RunResult) -> Self {
         Self {
@@ -237,8 +765,10 @@ impl From<CheckResult<RunResult>> for CheckResult<RunSummary> {
         Self {
             verdict: value.verdict,
             reason: value.reason,
+            score: value.score,
             compile: value.compile.map(Into::into),
             run: value.run.map(Into::into),
+            transcript: value.transcript,
         }
     }
 }
@@ -246,8 +776,9 @@ impl From<CheckResult<RunResult>> for CheckResult<RunSummary> {
 impl Submission {
     pub fn from(
         submission: &challenges_coding_challenge_submissions::Model,
-        result: Option<CheckResult<RunSummary>>,
+        result: Option<challenges_coding_challenge_result::Model>,
         queue_position: Option<usize>,
+        feedback_level: ChallengesFeedbackLevel,
     ) -> Self {
         Self {
             id: submission.id,
@@ -255,12 +786,134 @@ impl Submission {
             creator: submission.creator,
             creation_timestamp: submission.creation_timestamp.and_utc(),
             environment: submission.environment.clone(),
-            result,
+            code_length: submission.code_length as _,
+            attributed: submission.attributed,
+            revision_id: submission.revision_id,
+            evaluator_version: result.as_ref().and_then(|x| x.evaluator_version),
+            failed_seed: match feedback_level {
+                ChallengesFeedbackLevel::VerdictOnly => None,
+                ChallengesFeedbackLevel::FirstFailedSeed | ChallengesFeedbackLevel::FullDiff => {
+                    result.as_ref().and_then(|x| x.seed.clone())
+                }
+            },
+            resources: result
+                .as_ref()
+                .and_then(|x| x.resources.clone())
+                .map(Into::into),
+            new_personal_best: result
+                .as_ref()
+                .filter(|x| x.verdict == ChallengesVerdict::Ok)
+                .map(|x| NewPersonalBest {
+                    time: x.personal_best_time,
+                    memory: x.personal_best_memory,
+                }),
+            result: result.map(|x| CheckResult::from(x).restrict(feedback_level)),
             queue_position,
         }
     }
 }
 
+impl From<challenges_disabled_environments::Model> for DisabledEnvironment {
+    fn from(value: challenges_disabled_environments::Model) -> Self {
+        Self {
+            environment: value.environment,
+            reason: value.reason,
+            creator: value.creator,
+            timestamp: value.timestamp.and_utc(),
+        }
+    }
+}
+
+impl From<challenges_build_configs::Model> for BuildConfig {
+    fn from(value: challenges_build_configs::Model) -> Self {
+        Self {
+            environment: value.environment,
+            flags: value.flags,
+        }
+    }
+}
+
+impl From<challenges_personal_bests::Model> for PersonalBest {
+    fn from(value: challenges_personal_bests::Model) -> Self {
+        Self {
+            time_submission_id: value.time_submission_id,
+            time: value.time as _,
+            memory_submission_id: value.memory_submission_id,
+            memory: value.memory as _,
+        }
+    }
+}
+
+impl From<Resources> for SubmissionResources {
+    fn from(value: Resources) -> Self {
+        let usage = |time: u64, memory: u64| TestResourceUsage {
+            time,
+            memory,
+            time_percentage: percentage(time, value.time_limit),
+            memory_percentage: percentage(memory, value.memory_limit * 1024),
+        };
+        Self {
+            max: usage(
+                value.tests.iter().map(|x| x.time).max().unwrap_or_default(),
+                value
+                    .tests
+                    .iter()
+                    .map(|x| x.memory)
+                    .max()
+                    .unwrap_or_default(),
+            ),
+            tests: value
+                .tests
+                .iter()
+                .map(|x| usage(x.time, x.memory))
+                .collect(),
+            time_limit: value.time_limit,
+            memory_limit: value.memory_limit,
+        }
+    }
+}
+
+fn percentage(value: u64, limit: u64) -> f64 {
+    if limit == 0 {
+        return 0.0;
+    }
+    value as f64 / limit as f64 * 100.0
+}
+
+impl From<challenges_submission_artifacts::Model> for SubmissionArtifacts {
+    fn from(value: challenges_submission_artifacts::Model) -> Self {
+        Self {
+            stdout: value.stdout,
+            stderr: value.stderr,
+            truncated: value.truncated,
+        }
+    }
+}
+
+impl From<challenges_output_submissions::Model> for OutputSubmission {
+    fn from(value: challenges_output_submissions::Model) -> Self {
+        Self {
+            id: value.id,
+            subtask_id: value.subtask_id,
+            creator: value.creator,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+            revision_id: value.revision_id,
+            passed: value.passed as _,
+            total: value.total as _,
+            results: value
+                .results
+                .0
+                .into_iter()
+                .map(|x| OutputResult {
+                    input_id: x.input_id,
+                    verdict: x.verdict,
+                    reason: x.reason,
+                })
+                .collect(),
+        }
+    }
+}
+
 impl From<challenges_coding_challenge_result::Model> for CheckResult<RunSummary> {
     fn from(value: challenges_coding_challenge_result::Model) -> Self {
         let summary = |status, stderr, time, memory| {
@@ -276,6 +929,7 @@ impl From<challenges_coding_challenge_result::Model> for CheckResult<RunSummary>
         Self {
             verdict: value.verdict,
             reason: value.reason,
+            score: value.score,
             compile: summary(
                 value.build_status,
                 value.build_stderr,
@@ -288,6 +942,27 @@ impl From<challenges_coding_challenge_result::Model> for CheckResult<RunSummary>
                 value.run_time,
                 value.run_memory,
             ),
+            transcript: value
+                .transcript
+                .map(|x| x.0.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl From<TranscriptRound> for InteractionRound {
+    fn from(value: TranscriptRound) -> Self {
+        Self {
+            judge: value.judge,
+            program: value.program,
+        }
+    }
+}
+
+impl From<InteractionRound> for TranscriptRound {
+    fn from(value: InteractionRound) -> Self {
+        Self {
+            judge: value.judge,
+            program: value.program,
         }
     }
 }
@@ -300,3 +975,27 @@ impl From<PublicConfig> for ExecutorConfig {
         }
     }
 }
+
+impl
+    From<(
+        challenges_coding_challenge_submissions::Model,
+        challenges_coding_challenge_result::Model,
+    )> for PublicSolution
+{
+    fn from(
+        (submission, result): (
+            challenges_coding_challenge_submissions::Model,
+            challenges_coding_challenge_result::Model,
+        ),
+    ) -> Self {
+        Self {
+            id: submission.id,
+            creator: submission.attributed.then_some(submission.creator),
+            creation_timestamp: submission.creation_timestamp.and_utc(),
+            environment: submission.environment,
+            code: submission.code,
+            time: result.run_time.map(|x| x as _),
+            memory: result.run_memory.map(|x| x as _),
+        }
+    }
+}
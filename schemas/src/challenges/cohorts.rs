@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_cohort_assignments, challenges_cohort_members, challenges_cohorts,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Cohort {
+    /// The unique identifier of the cohort.
+    pub id: Uuid,
+    /// The instructor who has created the cohort.
+    pub creator: Uuid,
+    /// The creation timestamp of the cohort.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The title of the cohort.
+    pub title: String,
+    /// The code users need to join the cohort. Only visible to the creator of
+    /// the cohort and admins.
+    pub invite_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateCohortRequest {
+    /// The title of the cohort.
+    #[oai(validator(max_length = 256))]
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct JoinCohortRequest {
+    /// The invite code of the cohort.
+    #[oai(validator(max_length = 64))]
+    pub invite_code: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CohortMember {
+    /// The member of the cohort.
+    pub user_id: Uuid,
+    /// The time the user has joined the cohort.
+    pub join_timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CohortAssignment {
+    /// The assigned task.
+    pub task_id: Uuid,
+    /// The time the task has been assigned.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The time until which all subtasks of the task should be solved.
+    pub due: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SetCohortAssignmentRequest {
+    /// The time until which all subtasks of the task should be solved.
+    pub due: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct CohortProgress {
+    /// The subtasks of all assigned tasks (the columns of the matrix).
+    pub subtasks: Vec<CohortProgressSubtask>,
+    /// The progress of each member (the rows of the matrix).
+    pub members: Vec<CohortMemberProgress>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct CohortProgressSubtask {
+    pub task_id: Uuid,
+    pub subtask_id: Uuid,
+    #[oai(rename = "type")]
+    pub ty: ChallengesSubtaskType,
+    /// The due date of the assignment the subtask belongs to.
+    pub due: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct CohortMemberProgress {
+    pub user_id: Uuid,
+    /// The number of subtasks the member has solved.
+    pub solved: u64,
+    /// The progress of the member on each subtask, in the same order as
+    /// `subtasks`.
+    pub subtasks: Vec<CohortProgressCell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct CohortProgressCell {
+    /// Whether the member has solved the subtask.
+    pub solved: bool,
+    /// The time the member has solved the subtask.
+    pub solved_timestamp: Option<DateTime<Utc>>,
+    /// Whether the subtask has been solved after the due date.
+    pub late: bool,
+    /// The number of attempts of the member.
+    pub attempts: u64,
+    /// The latest submission of the member (coding challenges only).
+    pub submission_id: Option<Uuid>,
+}
+
+impl Cohort {
+    pub fn from(cohort: challenges_cohorts::Model, show_invite_code: bool) -> Self {
+        Self {
+            id: cohort.id,
+            creator: cohort.creator,
+            creation_timestamp: cohort.creation_timestamp.and_utc(),
+            title: cohort.title,
+            invite_code: show_invite_code.then_some(cohort.invite_code),
+        }
+    }
+}
+
+impl From<challenges_cohort_members::Model> for CohortMember {
+    fn from(value: challenges_cohort_members::Model) -> Self {
+        Self {
+            user_id: value.user_id,
+            join_timestamp: value.join_timestamp.and_utc(),
+        }
+    }
+}
+
+impl From<challenges_cohort_assignments::Model> for CohortAssignment {
+    fn from(value: challenges_cohort_assignments::Model) -> Self {
+        Self {
+            task_id: value.task_id,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+            due: value.due.map(|x| x.and_utc()),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use entity::challenges_contests;
+use poem_openapi::Object;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Contest {
+    /// The unique identifier of the contest.
+    pub id: Uuid,
+    /// The task whose subtasks are the problems of this contest.
+    pub task_id: Uuid,
+    /// The creator of the contest.
+    pub creator: Uuid,
+    /// The creation timestamp of the contest.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The title of the contest.
+    pub title: String,
+    /// The start timestamp of the contest.
+    pub start: DateTime<Utc>,
+    /// The end timestamp of the contest.
+    pub end: DateTime<Utc>,
+    /// Whether rewards for subtasks solved during the contest are held until
+    /// an admin releases them.
+    pub hold_rewards: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateContestRequest {
+    /// The task whose subtasks are the problems of this contest.
+    pub task_id: Uuid,
+    /// The title of the contest.
+    #[oai(validator(max_length = 256))]
+    pub title: String,
+    /// The start timestamp of the contest.
+    pub start: DateTime<Utc>,
+    /// The end timestamp of the contest.
+    pub end: DateTime<Utc>,
+    /// Whether rewards for subtasks solved during the contest are held until
+    /// an admin releases them.
+    #[oai(default)]
+    pub hold_rewards: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object, Serialize)]
+pub struct ScoreboardEntry {
+    /// The rank of the user (1-based). Users with the same score and penalty
+    /// share the same rank.
+    pub rank: u64,
+    /// The unique identifier of the user.
+    pub user_id: Uuid,
+    /// The number of problems the user has solved during the contest.
+    pub solved: u64,
+    /// The total xp of all problems the user has solved during the contest.
+    pub score: u64,
+    /// The sum of the minutes between the start of the contest and each
+    /// solve.
+    pub penalty: u64,
+}
+
+#[derive(Debug, Clone, Object, Serialize)]
+pub struct Scoreboard {
+    /// The entries of the scoreboard, ordered by rank.
+    pub entries: Vec<ScoreboardEntry>,
+    /// Token that can be passed to the scoreboard websocket to resume
+    /// receiving updates from this point.
+    pub resume_token: String,
+}
+
+/// A message sent via the scoreboard websocket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScoreboardMessage {
+    /// The complete scoreboard.
+    Snapshot(Scoreboard),
+    /// The updated entries of a single user. The ranks of other users may
+    /// have changed as well and are included in `entries`.
+    Update {
+        entries: Vec<ScoreboardEntry>,
+        resume_token: String,
+    },
+}
+
+impl From<challenges_contests::Model> for Contest {
+    fn from(value: challenges_contests::Model) -> Self {
+        Self {
+            id: value.id,
+            task_id: value.task_id,
+            creator: value.creator,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+            title: value.title,
+            start: value.start.and_utc(),
+            end: value.end.and_utc(),
+            hold_rewards: value.hold_rewards,
+        }
+    }
+}
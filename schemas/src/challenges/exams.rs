@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use entity::{challenges_exam_answers, challenges_exams};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Exam {
+    /// The unique identifier of the exam.
+    pub id: Uuid,
+    /// The task whose subtasks are the questions of this exam.
+    pub task_id: Uuid,
+    /// The creator of the exam.
+    pub creator: Uuid,
+    /// The creation timestamp of the exam.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The title of the exam.
+    pub title: String,
+    /// The start timestamp of the exam.
+    pub start: DateTime<Utc>,
+    /// The end timestamp of the exam.
+    pub end: DateTime<Utc>,
+    /// The time the results of the exam have been released, if any.
+    pub release_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateExamRequest {
+    /// The task whose subtasks are the questions of this exam.
+    pub task_id: Uuid,
+    /// The title of the exam.
+    #[oai(validator(max_length = 256))]
+    pub title: String,
+    /// The start timestamp of the exam.
+    pub start: DateTime<Utc>,
+    /// The end timestamp of the exam.
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ExamAnswer {
+    /// The exam the answer has been recorded for.
+    pub exam_id: Uuid,
+    /// The answered subtask.
+    pub subtask_id: Uuid,
+    /// The time the answer has been recorded.
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct ExamResult {
+    /// The user who took the exam.
+    pub user_id: Uuid,
+    /// The number of subtasks the user has answered.
+    pub answered: u64,
+    /// The number of subtasks the user has answered correctly.
+    pub correct: u64,
+    /// The last answer of the user to each subtask.
+    pub answers: Vec<ExamAnswerResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct ExamAnswerResult {
+    /// The answered subtask.
+    pub subtask_id: Uuid,
+    /// Whether the last answer to this subtask was correct.
+    pub correct: bool,
+    /// The time of the last answer to this subtask.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<challenges_exams::Model> for Exam {
+    fn from(value: challenges_exams::Model) -> Self {
+        Self {
+            id: value.id,
+            task_id: value.task_id,
+            creator: value.creator,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+            title: value.title,
+            start: value.start.and_utc(),
+            end: value.end.and_utc(),
+            release_timestamp: value.release_timestamp.map(|x| x.and_utc()),
+        }
+    }
+}
+
+impl From<challenges_exam_answers::Model> for ExamAnswer {
+    fn from(value: challenges_exam_answers::Model) -> Self {
+        Self {
+            exam_id: value.exam_id,
+            subtask_id: value.subtask_id,
+            timestamp: value.timestamp.and_utc(),
+        }
+    }
+}
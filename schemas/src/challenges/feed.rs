@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use entity::sea_orm_active_enums::ChallengesSubtaskType;
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Feed {
+    /// The time the feed has been generated.
+    pub generated: DateTime<Utc>,
+    /// The most recently added public tasks and subtasks (newest first).
+    pub items: Vec<FeedItem>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct FeedItem {
+    /// The task that has been added or the parent task of the subtask.
+    pub task_id: Uuid,
+    /// The title of the task.
+    pub title: String,
+    /// The subtask that has been added (if this item is a subtask).
+    pub subtask_id: Option<Uuid>,
+    /// The type of the subtask (if this item is a subtask).
+    pub subtask_type: Option<ChallengesSubtaskType>,
+    /// The time the task or subtask has been added.
+    pub timestamp: DateTime<Utc>,
+}
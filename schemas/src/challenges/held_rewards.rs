@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use entity::{challenges_held_rewards, sea_orm_active_enums::ChallengesHeldRewardStatus};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct HeldReward {
+    /// The unique identifier of the held reward.
+    pub id: Uuid,
+    /// The user who has earned the reward.
+    pub user_id: Uuid,
+    /// The solved subtask.
+    pub subtask_id: Uuid,
+    /// The number of xp.
+    pub xp: i64,
+    /// The number of morphcoins.
+    pub coins: i64,
+    /// The time the reward has been held.
+    pub timestamp: DateTime<Utc>,
+    /// Whether the reward is still held, has been released or has been
+    /// voided.
+    pub status: ChallengesHeldRewardStatus,
+    /// The contest whose policy holds the reward. `null` if the reward is held
+    /// because of an anti-cheat flag.
+    pub contest_id: Option<Uuid>,
+    /// The admin who has released or voided the reward.
+    pub resolver: Option<Uuid>,
+    /// The time the reward has been released or voided.
+    pub resolution_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ResolveHeldRewardsRequest {
+    /// Only resolve the rewards with these ids.
+    pub ids: Option<Vec<Uuid>>,
+    /// Only resolve the rewards of this user.
+    pub user_id: Option<Uuid>,
+    /// Only resolve the rewards held by this contest.
+    pub contest_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ResolveHeldRewardsResult {
+    /// The number of released or voided rewards.
+    pub rewards: u64,
+}
+
+impl From<challenges_held_rewards::Model> for HeldReward {
+    fn from(value: challenges_held_rewards::Model) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            subtask_id: value.subtask_id,
+            xp: value.xp,
+            coins: value.coins,
+            timestamp: value.timestamp.and_utc(),
+            status: value.status,
+            contest_id: value.contest_id,
+            resolver: value.resolver,
+            resolution_timestamp: value.resolution_timestamp.map(|x| x.and_utc()),
+        }
+    }
+}
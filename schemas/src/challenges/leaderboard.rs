@@ -1,6 +1,6 @@
 use chrono::{DateTime, TimeZone, Utc};
 use lib::services;
-use poem_openapi::Object;
+use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -19,6 +19,40 @@ pub struct LeaderboardUser {
     pub rank: Rank,
 }
 
+#[derive(Debug, Clone, Object, Serialize, Deserialize)]
+pub struct CodingChallengeLeaderboard {
+    /// The best accepted submission of each user in ascending order of the
+    /// metric.
+    pub leaderboard: Vec<CodingChallengeLeaderboardUser>,
+    /// The total number of users on the leaderboard.
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Object, Serialize, Deserialize)]
+pub struct CodingChallengeLeaderboardUser {
+    #[oai(flatten)]
+    pub user: LeaderboardUser,
+    /// The best accepted submission of the user.
+    pub submission_id: Uuid,
+    /// The environment of the submission.
+    pub environment: String,
+}
+
+/// The metric by which the submissions of a coding challenge are ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+#[oai(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardMetric {
+    /// The number of milliseconds the submission ran in its slowest test.
+    Time,
+    /// The number of kilobytes of memory the submission used in its most
+    /// memory intensive test.
+    Memory,
+    /// The number of bytes of the submitted code. Only available for golf
+    /// challenges.
+    Size,
+}
+
 #[derive(Debug, Clone, Object, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
@@ -73,6 +73,9 @@ pub struct SolveMatchingRequest {
     /// For each entry on the left the index of its match on the right.
     /// E.g. left=[A, B, C], right=[X, Y, Z], answer=[2, 0, 1] -> AZ, BX, CY
     pub answer: Vec<u8>,
+    /// The solve token of the matching if it has a time limit.
+    #[oai(default)]
+    pub token: Option<String>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -1,9 +1,28 @@
+pub mod activity;
+pub mod admin;
+pub mod anti_cheat;
 #[allow(clippy::module_inception)]
 pub mod challenges;
+pub mod cloze;
+pub mod code_readings;
 pub mod coding_challenges;
+pub mod cohorts;
+pub mod contests;
 pub mod course_tasks;
+pub mod exams;
+pub mod feed;
+pub mod held_rewards;
 pub mod leaderboard;
 pub mod matchings;
 pub mod multiple_choice;
+pub mod notifications;
+pub mod orderings;
+pub mod organizations;
+pub mod paths;
 pub mod question;
+pub mod reviews;
+pub mod sql_challenges;
 pub mod subtasks;
+pub mod tasks;
+pub mod true_false;
+pub mod user_data;
@@ -1,9 +1,11 @@
+use chrono::{DateTime, Utc};
 use entity::challenges_multiple_choice_quizes;
 use poem_ext::patch_value::PatchValue;
 use poem_openapi::{
     types::{ParseFromJSON, ToJSON, Type},
-    Object,
+    Enum, Object,
 };
+use uuid::Uuid;
 
 use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
 
@@ -32,6 +34,9 @@ where
     /// Whether this question is a single choice question (exactly one answer is
     /// correct).
     pub single_choice: bool,
+    /// Whether the answers are displayed in a different order for each user
+    /// and attempt. Answers must be submitted in the displayed order.
+    pub shuffle_answers: bool,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -47,6 +52,10 @@ pub struct CreateMultipleChoiceQuestionRequest {
     /// Whether this question is a single choice question (exactly one answer is
     /// correct).
     pub single_choice: bool,
+    /// Whether the answers are displayed in a different order for each user
+    /// and attempt.
+    #[oai(default)]
+    pub shuffle_answers: bool,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -62,6 +71,9 @@ pub struct UpdateMultipleChoiceQuestionRequest {
     /// Whether this question is a single choice question (exactly one answer is
     /// correct).
     pub single_choice: PatchValue<bool>,
+    /// Whether the answers are displayed in a different order for each user
+    /// and attempt.
+    pub shuffle_answers: PatchValue<bool>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -73,11 +85,154 @@ pub struct Answer {
     pub correct: bool,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct ValidateMCQsRequest {
+    /// The draft questions to validate.
+    #[oai(validator(max_items = 100))]
+    pub questions: Vec<MCQDraft>,
+}
+
+/// A draft of a multiple choice question. In contrast to
+/// `CreateMultipleChoiceQuestionRequest`, length limits are not enforced while
+/// parsing the request, but reported in the diagnostics.
+#[derive(Debug, Clone, Object)]
+pub struct MCQDraft {
+    /// The number of xp a user gets for completing this subtask. Omit to use
+    /// the configured default value.
+    #[oai(default)]
+    pub xp: Option<u64>,
+    /// The number of morphcoins a user gets for completing this subtask. Omit
+    /// to use the configured default value.
+    #[oai(default)]
+    pub coins: Option<u64>,
+    /// The question text.
+    pub question: String,
+    /// The possible answers to the question.
+    pub answers: Vec<MCQDraftAnswer>,
+    /// Whether this question is a single choice question (exactly one answer is
+    /// correct).
+    pub single_choice: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct MCQDraftAnswer {
+    /// The answer.
+    pub answer: String,
+    /// Whether this answer is correct.
+    pub correct: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct MCQDiagnostics {
+    /// The index of the draft in the request.
+    pub index: usize,
+    /// Whether the draft can be created without changes.
+    pub valid: bool,
+    /// The problems found in the draft.
+    pub issues: Vec<MCQIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct MCQIssue {
+    /// The kind of the problem.
+    pub kind: MCQIssueKind,
+    /// The index of the affected answer, if the problem concerns a single
+    /// answer.
+    pub answer: Option<usize>,
+    /// The ids of highly similar existing subtasks (only for `duplicate`).
+    pub subtasks: Vec<Uuid>,
+    /// The index of a highly similar draft in the same request (only for
+    /// `duplicate_draft`).
+    pub draft: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum MCQIssueKind {
+    /// The question text is longer than 4096 characters.
+    QuestionTooLong,
+    /// The question has no answers.
+    TooFewAnswers,
+    /// The question has more than 32 answers.
+    TooManyAnswers,
+    /// An answer is longer than 256 characters.
+    AnswerTooLong,
+    /// `single_choice` is set to `true`, but there is not exactly one correct
+    /// answer.
+    InvalidSingleChoice,
+    /// There is no correct answer.
+    InvalidMultipleChoice,
+    /// The max xp limit of the task has been exceeded.
+    XpLimitExceeded,
+    /// The max coin limit of the task has been exceeded.
+    CoinLimitExceeded,
+    /// Highly similar subtasks already exist in this task.
+    Duplicate,
+    /// A previous draft in the same request is highly similar.
+    DuplicateDraft,
+    /// The question or an answer contains a forbidden word or personal data.
+    ContentViolation,
+}
+
+impl MCQIssue {
+    pub fn new(kind: MCQIssueKind) -> Self {
+        Self {
+            kind,
+            answer: None,
+            subtasks: Vec::new(),
+            draft: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ImportMCQsRequest {
+    #[oai(flatten)]
+    pub subtask: CreateSubtaskRequest,
+    /// The format of the question bank.
+    pub format: MCQImportFormat,
+    /// The content of the exported question bank.
+    #[oai(validator(max_length = 1048576))]
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum MCQImportFormat {
+    /// The Moodle GIFT format.
+    Gift,
+    /// The Moodle XML format.
+    MoodleXml,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ImportMCQsResponse {
+    /// The questions that have been created.
+    pub created: Vec<MultipleChoiceQuestion<Answer>>,
+    /// The questions that have not been imported.
+    pub rejected: Vec<RejectedMCQ>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct RejectedMCQ {
+    /// The index of the question in the question bank.
+    pub index: usize,
+    /// The name of the question in the question bank.
+    pub name: Option<String>,
+    /// The type of the question, if questions of this type cannot be imported.
+    pub unsupported_type: Option<String>,
+    /// The problems found in the question.
+    pub issues: Vec<MCQIssue>,
+}
+
 #[derive(Debug, Clone, Object)]
 pub struct SolveMCQRequest {
     /// For each possible answer exactly one boolean (`true` for "answer is
     /// correct" or `false` for "answer is incorrect").
     pub answers: Vec<bool>,
+    /// The solve token of the question if it has a time limit.
+    #[oai(default)]
+    pub token: Option<String>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -88,6 +243,18 @@ pub struct SolveMCQFeedback {
     pub correct: usize,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct AttemptSeed {
+    /// The attempt (zero-based) in which the question has been displayed.
+    pub attempt: u32,
+    /// The seed from which the order of the answers has been derived.
+    pub seed: i64,
+    /// The time the question has first been displayed in this attempt.
+    pub timestamp: DateTime<Utc>,
+    /// For each displayed answer the index of the answer in the question.
+    pub order: Vec<usize>,
+}
+
 impl MultipleChoiceQuestionSummary {
     pub fn from(mcq: challenges_multiple_choice_quizes::Model, subtask: Subtask) -> Self {
         Self {
@@ -104,6 +271,7 @@ impl MultipleChoiceQuestion<Answer> {
             question: mcq.question,
             answers: combine_answers(mcq.answers, mcq.correct_answers),
             single_choice: mcq.single_choice,
+            shuffle_answers: mcq.shuffle_answers,
             subtask,
         }
     }
@@ -115,6 +283,7 @@ impl MultipleChoiceQuestion<String> {
             question: mcq.question,
             answers: mcq.answers,
             single_choice: mcq.single_choice,
+            shuffle_answers: mcq.shuffle_answers,
             subtask,
         }
     }
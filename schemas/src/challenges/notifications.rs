@@ -0,0 +1,53 @@
+use entity::challenges_notification_preferences;
+use poem_ext::patch_value::PatchValue;
+use poem_openapi::Object;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Object)]
+pub struct NotificationPreferences {
+    /// Notify the user when someone else solves a subtask they created.
+    pub subtask_solved: bool,
+    /// Notify the user when a subtask they created has been reported.
+    pub subtask_reported: bool,
+    /// Notify the user when a subtask they created has been disabled or
+    /// rejected by a moderator.
+    pub subtask_disabled: bool,
+    /// Notify the user when one of their submissions has been judged.
+    pub submission_judged: bool,
+    /// Notify the user shortly before a contest starts.
+    pub contest_starting: bool,
+    /// Send the user a weekly email with statistics about the subtasks they
+    /// created.
+    pub creator_digest: bool,
+}
+
+#[derive(Debug, Clone, Object, Serialize)]
+pub struct UpdateNotificationPreferencesRequest {
+    /// Notify the user when someone else solves a subtask they created.
+    pub subtask_solved: PatchValue<bool>,
+    /// Notify the user when a subtask they created has been reported.
+    pub subtask_reported: PatchValue<bool>,
+    /// Notify the user when a subtask they created has been disabled or
+    /// rejected by a moderator.
+    pub subtask_disabled: PatchValue<bool>,
+    /// Notify the user when one of their submissions has been judged.
+    pub submission_judged: PatchValue<bool>,
+    /// Notify the user shortly before a contest starts.
+    pub contest_starting: PatchValue<bool>,
+    /// Send the user a weekly email with statistics about the subtasks they
+    /// created.
+    pub creator_digest: PatchValue<bool>,
+}
+
+impl From<challenges_notification_preferences::Model> for NotificationPreferences {
+    fn from(value: challenges_notification_preferences::Model) -> Self {
+        Self {
+            subtask_solved: value.subtask_solved,
+            subtask_reported: value.subtask_reported,
+            subtask_disabled: value.subtask_disabled,
+            submission_judged: value.submission_judged,
+            contest_starting: value.contest_starting,
+            creator_digest: value.creator_digest,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use entity::challenges_orderings;
+use poem_ext::patch_value::PatchValue;
+use poem_openapi::Object;
+
+use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
+
+#[derive(Debug, Clone, Object)]
+pub struct OrderingSummary {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The question text.
+    pub question: String,
+    /// The items that have to be put in the correct order.
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct Ordering {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The question text.
+    pub question: String,
+    /// The items that have to be put in the correct order.
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct OrderingWithSolution {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The question text.
+    pub question: String,
+    /// The items that have to be put in the correct order.
+    pub items: Vec<String>,
+    /// The indices of the items in the correct order.
+    pub solution: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateOrderingRequest {
+    #[oai(flatten)]
+    pub subtask: CreateSubtaskRequest,
+    /// The question text.
+    #[oai(validator(max_length = 4096))]
+    pub question: String,
+    /// The items that have to be put in the correct order.
+    #[oai(validator(min_items = 2, max_items = 32, max_length = 256))]
+    pub items: Vec<String>,
+    /// The indices of the items in the correct order.
+    /// E.g. items=[B, C, A], solution=[2, 0, 1] -> A, B, C
+    #[oai(validator(min_items = 2, max_items = 32, maximum(value = "31")))]
+    pub solution: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UpdateOrderingRequest {
+    #[oai(flatten)]
+    pub subtask: UpdateSubtaskRequest,
+    /// The question text.
+    #[oai(validator(max_length = 4096))]
+    pub question: PatchValue<String>,
+    /// The items that have to be put in the correct order.
+    #[oai(validator(min_items = 2, max_items = 32, max_length = 256))]
+    pub items: PatchValue<Vec<String>>,
+    /// The indices of the items in the correct order.
+    /// E.g. items=[B, C, A], solution=[2, 0, 1] -> A, B, C
+    #[oai(validator(min_items = 2, max_items = 32, maximum(value = "31")))]
+    pub solution: PatchValue<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveOrderingRequest {
+    /// The indices of the items in the order the user thinks is correct.
+    /// E.g. items=[B, C, A], answer=[2, 0, 1] -> A, B, C
+    pub answer: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveOrderingFeedback {
+    /// Whether the user has successfully solved the question.
+    pub solved: bool,
+    /// The number of items at the correct position.
+    pub correct: usize,
+}
+
+impl OrderingSummary {
+    pub fn from(ordering: challenges_orderings::Model, subtask: Subtask) -> Self {
+        Self {
+            question: ordering.question,
+            items: ordering.items,
+            subtask,
+        }
+    }
+}
+
+impl Ordering {
+    pub fn from(ordering: challenges_orderings::Model, subtask: Subtask) -> Self {
+        Self {
+            question: ordering.question,
+            items: ordering.items,
+            subtask,
+        }
+    }
+}
+
+impl OrderingWithSolution {
+    pub fn from(ordering: challenges_orderings::Model, subtask: Subtask) -> Self {
+        Self {
+            question: ordering.question,
+            items: ordering.items,
+            solution: ordering.solution.into_iter().map(|x| x as _).collect(),
+            subtask,
+        }
+    }
+}
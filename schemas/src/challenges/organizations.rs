@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_organization_members, challenges_organizations,
+    sea_orm_active_enums::ChallengesOrganizationRole,
+};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Organization {
+    /// The unique identifier of the organization.
+    pub id: Uuid,
+    /// The name of the organization.
+    pub name: String,
+    /// The admin who has created the organization.
+    pub creator: Uuid,
+    /// The creation timestamp of the organization.
+    pub creation_timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateOrganizationRequest {
+    /// The name of the organization.
+    #[oai(validator(max_length = 256))]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct OrganizationMember {
+    /// The member of the organization.
+    pub user_id: Uuid,
+    /// The role of the member in the organization.
+    pub role: ChallengesOrganizationRole,
+    /// The time the user has been added to the organization.
+    pub join_timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SetOrganizationMemberRequest {
+    /// The role of the member in the organization.
+    pub role: ChallengesOrganizationRole,
+}
+
+impl From<challenges_organizations::Model> for Organization {
+    fn from(value: challenges_organizations::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            creator: value.creator,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+        }
+    }
+}
+
+impl From<challenges_organization_members::Model> for OrganizationMember {
+    fn from(value: challenges_organization_members::Model) -> Self {
+        Self {
+            user_id: value.user_id,
+            role: value.role,
+            join_timestamp: value.join_timestamp.and_utc(),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use entity::{challenges_path_items, challenges_paths};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Path {
+    /// The unique identifier of the learning path.
+    pub id: Uuid,
+    /// The creator of the learning path.
+    pub creator: Uuid,
+    /// The creation timestamp of the learning path.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The title of the learning path.
+    pub title: String,
+    /// The description of the learning path.
+    pub description: String,
+    /// The number of xp a user gets for completing this learning path.
+    pub xp: u64,
+    /// The number of morphcoins a user gets for completing this learning path.
+    pub coins: u64,
+    /// Whether the learning path is enabled and visible to normal users.
+    pub enabled: bool,
+    /// The ordered items of the learning path.
+    pub items: Vec<PathItem>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PathItem {
+    /// The task to work on. Exactly one of `task_id` and `subtask_id` is set.
+    pub task_id: Option<Uuid>,
+    /// The subtask to solve. Exactly one of `task_id` and `subtask_id` is set.
+    pub subtask_id: Option<Uuid>,
+    /// The number of subtasks of the task that have to be solved to complete
+    /// this item. If omitted, all subtasks have to be solved. Ignored for
+    /// subtask items.
+    pub min_solved: Option<u32>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreatePathRequest {
+    /// The title of the learning path.
+    #[oai(validator(max_length = 256))]
+    pub title: String,
+    /// The description of the learning path.
+    #[oai(validator(max_length = 4096))]
+    pub description: String,
+    /// The number of xp a user gets for completing this learning path.
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub xp: u64,
+    /// The number of morphcoins a user gets for completing this learning path.
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub coins: u64,
+    /// Whether the learning path is enabled and visible to normal users.
+    pub enabled: bool,
+    /// The ordered items of the learning path.
+    #[oai(validator(min_items = 1, max_items = 256))]
+    pub items: Vec<PathItem>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PathProgress {
+    /// The learning path.
+    pub path_id: Uuid,
+    /// The progress of the user in each item of the learning path.
+    pub items: Vec<PathItemProgress>,
+    /// The number of items the user has completed.
+    pub completed_items: u64,
+    /// The total number of items.
+    pub total_items: u64,
+    /// Whether the user has completed all items.
+    pub completed: bool,
+    /// The timestamp the completion rewards have been claimed.
+    pub claimed: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct PathItemProgress {
+    #[oai(flatten)]
+    pub item: PathItem,
+    /// The number of subtasks that have to be solved to complete this item.
+    pub required: u64,
+    /// The number of relevant subtasks the user has solved.
+    pub solved: u64,
+    /// Whether the user has completed this item.
+    pub completed: bool,
+}
+
+impl Path {
+    pub fn from(path: challenges_paths::Model, items: Vec<challenges_path_items::Model>) -> Self {
+        Self {
+            id: path.id,
+            creator: path.creator,
+            creation_timestamp: path.creation_timestamp.and_utc(),
+            title: path.title,
+            description: path.description,
+            xp: path.xp as _,
+            coins: path.coins as _,
+            enabled: path.enabled,
+            items: items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<challenges_path_items::Model> for PathItem {
+    fn from(value: challenges_path_items::Model) -> Self {
+        Self {
+            task_id: value.task_id,
+            subtask_id: value.subtask_id,
+            min_solved: value.min_solved.map(|x| x as _),
+        }
+    }
+}
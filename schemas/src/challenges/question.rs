@@ -1,4 +1,4 @@
-use entity::challenges_questions;
+use entity::{challenges_questions, sea_orm_active_enums::ChallengesQuestionAnswerType};
 use poem_ext::patch_value::PatchValue;
 use poem_openapi::Object;
 
@@ -21,6 +21,10 @@ pub struct QuestionSummary {
     // The list of \"building blocks\" that can be used to compose the answer.
     // Empty if the answer has to be typed.
     pub blocks: Vec<String>,
+    /// Whether the answer is a text or a number.
+    pub answer_type: ChallengesQuestionAnswerType,
+    /// The unit of a numeric answer.
+    pub unit: Option<String>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -40,6 +44,10 @@ pub struct Question {
     // The list of \"building blocks\" that can be used to compose the answer.
     // Empty if the answer has to be typed.
     pub blocks: Vec<String>,
+    /// Whether the answer is a text or a number.
+    pub answer_type: ChallengesQuestionAnswerType,
+    /// The unit of a numeric answer.
+    pub unit: Option<String>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -61,6 +69,19 @@ pub struct QuestionWithSolution {
     // The list of \"building blocks\" that can be used to compose the answer.
     // Empty if the answer has to be typed.
     pub blocks: Vec<String>,
+    /// The parameters of the question. If not empty, the question text and
+    /// the answers may contain `{{ expression }}` placeholders.
+    pub parameters: Vec<QuestionParameter>,
+    /// Whether the answer is a text or a number.
+    pub answer_type: ChallengesQuestionAnswerType,
+    /// The unit of a numeric answer.
+    pub unit: Option<String>,
+    /// The maximum absolute difference between a numeric answer and the
+    /// expected value.
+    pub absolute_tolerance: Option<f64>,
+    /// The maximum difference between a numeric answer and the expected value
+    /// relative to the expected value.
+    pub relative_tolerance: Option<f64>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -85,6 +106,26 @@ pub struct CreateQuestionRequest {
     /// Empty if the answer has to be typed.
     #[oai(validator(max_items = 32, max_length = 256))]
     pub blocks: Vec<String>,
+    /// The parameters of the question. If not empty, the question text and
+    /// the answers may contain `{{ expression }}` placeholders, which are
+    /// evaluated with a random value for each parameter.
+    #[oai(default, validator(max_items = 16))]
+    pub parameters: Vec<QuestionParameter>,
+    /// Whether the answer is a text or a number. Defaults to `TEXT`. The
+    /// answers of numeric questions must be numbers without a unit.
+    #[oai(default)]
+    pub answer_type: Option<ChallengesQuestionAnswerType>,
+    /// The unit of a numeric answer. Users may omit the unit in their answer.
+    #[oai(default, validator(max_length = 32))]
+    pub unit: Option<String>,
+    /// The maximum absolute difference between a numeric answer and the
+    /// expected value.
+    #[oai(default, validator(minimum(value = "0")))]
+    pub absolute_tolerance: Option<f64>,
+    /// The maximum difference between a numeric answer and the expected value
+    /// relative to the expected value (e.g. `0.01` for 1%).
+    #[oai(default, validator(minimum(value = "0")))]
+    pub relative_tolerance: Option<f64>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -109,6 +150,38 @@ pub struct UpdateQuestionRequest {
     /// Empty if the answer has to be typed.
     #[oai(validator(max_items = 32, max_length = 256))]
     pub blocks: PatchValue<Vec<String>>,
+    /// The parameters of the question. If not empty, the question text and
+    /// the answers may contain `{{ expression }}` placeholders, which are
+    /// evaluated with a random value for each parameter.
+    #[oai(validator(max_items = 16))]
+    pub parameters: PatchValue<Vec<QuestionParameter>>,
+    /// Whether the answer is a text or a number. The answers of numeric
+    /// questions must be numbers without a unit.
+    pub answer_type: PatchValue<ChallengesQuestionAnswerType>,
+    /// The unit of a numeric answer. Users may omit the unit in their answer.
+    #[oai(validator(max_length = 32))]
+    pub unit: PatchValue<Option<String>>,
+    /// The maximum absolute difference between a numeric answer and the
+    /// expected value.
+    #[oai(validator(minimum(value = "0")))]
+    pub absolute_tolerance: PatchValue<Option<f64>>,
+    /// The maximum difference between a numeric answer and the expected value
+    /// relative to the expected value (e.g. `0.01` for 1%).
+    #[oai(validator(minimum(value = "0")))]
+    pub relative_tolerance: PatchValue<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct QuestionParameter {
+    /// The name of the parameter as used in expressions.
+    #[oai(validator(pattern = "^[a-zA-Z_][a-zA-Z0-9_]*$", max_length = 32))]
+    pub name: String,
+    /// The smallest possible value.
+    #[oai(validator(minimum(value = "-1000000000"), maximum(value = "1000000000")))]
+    pub min: i64,
+    /// The largest possible value.
+    #[oai(validator(minimum(value = "-1000000000"), maximum(value = "1000000000")))]
+    pub max: i64,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -131,6 +204,8 @@ impl QuestionSummary {
             digits: question.digits,
             punctuation: question.punctuation,
             blocks: question.blocks,
+            answer_type: question.answer_type,
+            unit: question.unit,
             subtask,
         }
     }
@@ -145,6 +220,8 @@ impl Question {
             digits: question.digits,
             punctuation: question.punctuation,
             blocks: question.blocks,
+            answer_type: question.answer_type,
+            unit: question.unit,
             subtask,
         }
     }
@@ -160,7 +237,32 @@ impl QuestionWithSolution {
             digits: question.digits,
             punctuation: question.punctuation,
             blocks: question.blocks,
+            parameters: question.parameters.0.into_iter().map(Into::into).collect(),
+            answer_type: question.answer_type,
+            unit: question.unit,
+            absolute_tolerance: question.absolute_tolerance,
+            relative_tolerance: question.relative_tolerance,
             subtask,
         }
     }
 }
+
+impl From<challenges_questions::QuestionParameter> for QuestionParameter {
+    fn from(value: challenges_questions::QuestionParameter) -> Self {
+        Self {
+            name: value.name,
+            min: value.min,
+            max: value.max,
+        }
+    }
+}
+
+impl From<QuestionParameter> for challenges_questions::QuestionParameter {
+    fn from(value: QuestionParameter) -> Self {
+        Self {
+            name: value.name,
+            min: value.min,
+            max: value.max,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use entity::{challenges_reviews, sea_orm_active_enums::ChallengesSubtaskType};
+use poem_openapi::{Object, Union};
+use uuid::Uuid;
+
+use super::{multiple_choice::SolveMCQRequest, question::SolveQuestionRequest};
+
+#[derive(Debug, Clone, Object)]
+pub struct Review {
+    /// The unique identifier of the review.
+    pub id: Uuid,
+    /// The parent task of the subtask to review.
+    pub task_id: Uuid,
+    /// The subtask to review.
+    pub subtask_id: Uuid,
+    /// The type of the subtask.
+    pub subtask_type: ChallengesSubtaskType,
+    /// The time the review is due.
+    pub due: DateTime<Utc>,
+    /// The number of consecutive successful reviews.
+    pub repetitions: i32,
+    /// The current review interval in days.
+    pub interval: i32,
+}
+
+#[derive(Debug, Clone, Union)]
+#[oai(discriminator_name = "type", one_of)]
+pub enum ReviewAnswer {
+    #[oai(mapping = "multiple_choice")]
+    MultipleChoice(SolveMCQRequest),
+    #[oai(mapping = "question")]
+    Question(SolveQuestionRequest),
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ReviewFeedback {
+    /// Whether the answer was correct.
+    pub correct: bool,
+    /// The time the next review is due.
+    pub next_due: DateTime<Utc>,
+    /// The new review interval in days.
+    pub interval: i32,
+    /// The xp the user has received for this review.
+    pub xp: u64,
+}
+
+impl Review {
+    pub fn from(
+        review: challenges_reviews::Model,
+        task_id: Uuid,
+        subtask_type: ChallengesSubtaskType,
+    ) -> Self {
+        Self {
+            id: review.id,
+            task_id,
+            subtask_id: review.subtask_id,
+            subtask_type,
+            due: review.due.and_utc(),
+            repetitions: review.repetitions,
+            interval: review.interval,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use entity::challenges_sql_challenges::{self, SqlResult};
+use poem_ext::patch_value::PatchValue;
+use poem_openapi::Object;
+
+use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
+
+#[derive(Debug, Clone, Object)]
+pub struct SqlChallengeSummary {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The description of the sql challenge.
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SqlChallenge {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The description of the sql challenge.
+    pub description: String,
+    /// The statements that create the tables of the database.
+    pub schema: String,
+    /// The statements that fill the database with data.
+    pub seed: String,
+    /// Whether the order of the rows matters.
+    pub ordered: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SqlChallengeWithSolution {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The description of the sql challenge.
+    pub description: String,
+    /// The statements that create the tables of the database.
+    pub schema: String,
+    /// The statements that fill the database with data.
+    pub seed: String,
+    /// Whether the order of the rows matters.
+    pub ordered: bool,
+    /// The reference query.
+    pub solution: String,
+    /// The result of the reference query, as determined by running it.
+    pub expected: SqlTable,
+}
+
+/// The result of a query. All values are converted to strings.
+#[derive(Debug, Clone, Object)]
+pub struct SqlTable {
+    /// The names of the columns.
+    pub columns: Vec<String>,
+    /// The rows of the result. SQL `NULL` values are represented by `null`.
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateSqlChallengeRequest {
+    #[oai(flatten)]
+    pub subtask: CreateSubtaskRequest,
+    /// The description of the sql challenge.
+    #[oai(validator(max_length = 4096))]
+    pub description: String,
+    /// The statements that create the tables of the database.
+    #[oai(validator(max_length = 65536))]
+    pub schema: String,
+    /// The statements that fill the database with data.
+    #[oai(validator(max_length = 65536))]
+    pub seed: String,
+    /// The reference query.
+    #[oai(validator(max_length = 65536))]
+    pub solution: String,
+    /// Whether the order of the rows matters.
+    #[oai(default)]
+    pub ordered: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UpdateSqlChallengeRequest {
+    #[oai(flatten)]
+    pub subtask: UpdateSubtaskRequest,
+    /// The description of the sql challenge.
+    #[oai(validator(max_length = 4096))]
+    pub description: PatchValue<String>,
+    /// The statements that create the tables of the database.
+    #[oai(validator(max_length = 65536))]
+    pub schema: PatchValue<String>,
+    /// The statements that fill the database with data.
+    #[oai(validator(max_length = 65536))]
+    pub seed: PatchValue<String>,
+    /// The reference query.
+    #[oai(validator(max_length = 65536))]
+    pub solution: PatchValue<String>,
+    /// Whether the order of the rows matters.
+    pub ordered: PatchValue<bool>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveSqlChallengeRequest {
+    /// The query submitted by the user.
+    #[oai(validator(max_length = 65536))]
+    pub query: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveSqlChallengeFeedback {
+    /// Whether the user has successfully solved the challenge.
+    pub solved: bool,
+    /// The error message of the database, if the query could not be executed.
+    pub error: Option<String>,
+    /// The differences between the result of the query and the expected
+    /// result, if the query could be executed but returned a wrong result.
+    pub diff: Option<SqlDiff>,
+}
+
+#[derive(Debug, Clone, Default, Object)]
+pub struct SqlDiff {
+    /// Whether the result has a different number of columns than expected.
+    /// The rows are not compared in this case.
+    pub wrong_column_count: bool,
+    /// The number of expected rows that are missing from the result.
+    pub missing: usize,
+    /// Some of the missing rows.
+    pub missing_rows: Vec<Vec<Option<String>>>,
+    /// The number of rows in the result that were not expected.
+    pub unexpected: usize,
+    /// Some of the unexpected rows.
+    pub unexpected_rows: Vec<Vec<Option<String>>>,
+    /// Whether the result contains the expected rows, but in the wrong order.
+    pub wrong_order: bool,
+}
+
+impl SqlChallengeSummary {
+    pub fn from(sql_challenge: challenges_sql_challenges::Model, subtask: Subtask) -> Self {
+        Self {
+            description: sql_challenge.description,
+            subtask,
+        }
+    }
+}
+
+impl SqlChallenge {
+    pub fn from(sql_challenge: challenges_sql_challenges::Model, subtask: Subtask) -> Self {
+        Self {
+            description: sql_challenge.description,
+            schema: sql_challenge.schema,
+            seed: sql_challenge.seed,
+            ordered: sql_challenge.ordered,
+            subtask,
+        }
+    }
+}
+
+impl SqlChallengeWithSolution {
+    pub fn from(sql_challenge: challenges_sql_challenges::Model, subtask: Subtask) -> Self {
+        Self {
+            description: sql_challenge.description,
+            schema: sql_challenge.schema,
+            seed: sql_challenge.seed,
+            ordered: sql_challenge.ordered,
+            solution: sql_challenge.solution,
+            expected: sql_challenge.expected.into(),
+            subtask,
+        }
+    }
+}
+
+impl From<SqlResult> for SqlTable {
+    fn from(value: SqlResult) -> Self {
+        Self {
+            columns: value.columns,
+            rows: value.rows,
+        }
+    }
+}
@@ -1,15 +1,30 @@
 use chrono::{DateTime, Utc};
 use entity::{
-    challenges_ban, challenges_subtask_reports, challenges_subtasks,
+    challenges_ban, challenges_subtask_reports, challenges_subtasks, challenges_task_config,
+    challenges_user_notes,
     sea_orm_active_enums::{
-        ChallengesBanAction, ChallengesRating, ChallengesReportReason, ChallengesSubtaskType,
+        ChallengesBanAction, ChallengesLicense, ChallengesRating, ChallengesReportReason,
+        ChallengesReviewStatus, ChallengesSubtaskType,
     },
 };
 use poem_ext::patch_value::PatchValue;
-use poem_openapi::{Enum, Object};
+use poem_openapi::{Enum, Object, Union};
 use serde::Deserialize;
+use serde_json::Value;
 use uuid::Uuid;
 
+use super::{
+    cloze::{Cloze, ClozeWithSolution},
+    code_readings::{CodeReading, CodeReadingWithSolution},
+    coding_challenges::{CodingChallenge, SubmissionContent},
+    matchings::{Matching, MatchingWithSolution, SolveMatchingRequest},
+    multiple_choice::{Answer, MultipleChoiceQuestion, SolveMCQRequest},
+    orderings::{Ordering, OrderingWithSolution},
+    question::{Question, QuestionWithSolution, SolveQuestionRequest},
+    sql_challenges::{SqlChallenge, SqlChallengeWithSolution},
+    true_false::{TrueFalse, TrueFalseWithSolution},
+};
+
 #[derive(Debug, Clone, Object)]
 pub struct Subtask {
     /// The unique identifier of the subtask.
@@ -35,6 +50,37 @@ pub struct Subtask {
     pub enabled: bool,
     /// Whether the subtask is retired.
     pub retired: bool,
+    /// Whether the subtask has been approved by a moderator. Subtasks can
+    /// only be enabled after they have been approved.
+    pub review_status: ChallengesReviewStatus,
+    /// The reason why a moderator has rejected the subtask.
+    pub rejection_reason: Option<String>,
+    /// The license of the content of the subtask.
+    pub license: ChallengesLicense,
+    /// The original authors and source of the content.
+    pub attribution: Option<String>,
+    /// The maximum number of attempts per user.
+    pub max_attempts: Option<u32>,
+    /// Whether users can see the solution after they have used all attempts.
+    pub reveal_solution: bool,
+    /// The number of seconds a user has to answer after requesting the
+    /// subtask (only supported by matchings and multiple choice questions).
+    pub time_limit: Option<u32>,
+    /// The number of morphcoins a user has to pay to unlock this subtask while
+    /// it is locked.
+    pub unlock_price: Option<u64>,
+    /// Whether users have to unlock the subtask before they can access it.
+    pub locked: bool,
+    /// The token which has to be submitted with the answer if the subtask has
+    /// a time limit. Only included when requesting a single subtask.
+    pub solve_token: Option<SolveToken>,
+    /// The private note of the user on this subtask. Only included if it has
+    /// been explicitly requested.
+    pub note: Option<String>,
+    /// The due date of the parent task if it has been assigned to the user in
+    /// a cohort. Solving the subtask after this date is only rewarded with a
+    /// percentage of the xp and coins.
+    pub due: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -47,6 +93,33 @@ pub struct CreateSubtaskRequest {
     /// to use the configured default value.
     #[oai(validator(maximum(value = "9223372036854775807")), default)]
     pub coins: Option<u64>,
+    /// The license of the content of the subtask. Defaults to
+    /// `ALL_RIGHTS_RESERVED`.
+    #[oai(default)]
+    pub license: Option<ChallengesLicense>,
+    /// The original authors and source of the content. Required if the
+    /// license requires attribution.
+    #[oai(validator(max_length = 1024), default)]
+    pub attribution: Option<String>,
+    /// The maximum number of attempts per user. Omit to allow unlimited
+    /// attempts.
+    #[oai(validator(minimum(value = "1")), default)]
+    pub max_attempts: Option<u32>,
+    /// Whether users can see the solution after they have used all attempts.
+    #[oai(default)]
+    pub reveal_solution: bool,
+    /// The number of seconds a user has to answer after requesting the
+    /// subtask (only supported by matchings and multiple choice questions).
+    /// Omit to allow unlimited time.
+    #[oai(validator(minimum(value = "1")), default)]
+    pub time_limit: Option<u32>,
+    /// The number of morphcoins a user has to pay to unlock this subtask while
+    /// it is locked. Omit if the subtask cannot be unlocked.
+    #[oai(validator(maximum(value = "9223372036854775807")), default)]
+    pub unlock_price: Option<u64>,
+    /// Whether users have to unlock the subtask before they can access it.
+    #[oai(default)]
+    pub locked: bool,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -59,10 +132,61 @@ pub struct UpdateSubtaskRequest {
     /// The number of morphcoins a user gets for completing this subtask.
     #[oai(validator(maximum(value = "9223372036854775807")), default)]
     pub coins: PatchValue<u64>,
-    /// Whether the subtask is enabled and visible to normal users.
+    /// Whether the subtask is enabled and visible to normal users. Subtasks
+    /// which have not been approved can only be enabled by admins.
     pub enabled: PatchValue<bool>,
     /// Whether the subtask is retired.
     pub retired: PatchValue<bool>,
+    /// The license of the content of the subtask.
+    pub license: PatchValue<ChallengesLicense>,
+    /// The original authors and source of the content. Required if the
+    /// (new) license requires attribution.
+    #[oai(validator(max_length = 1024))]
+    pub attribution: PatchValue<Option<String>>,
+    /// The maximum number of attempts per user.
+    #[oai(validator(minimum(value = "1")))]
+    pub max_attempts: PatchValue<Option<u32>>,
+    /// Whether users can see the solution after they have used all attempts.
+    pub reveal_solution: PatchValue<bool>,
+    /// The number of seconds a user has to answer after requesting the
+    /// subtask.
+    #[oai(validator(minimum(value = "1")))]
+    pub time_limit: PatchValue<Option<u32>>,
+    /// The number of morphcoins a user has to pay to unlock this subtask while
+    /// it is locked.
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub unlock_price: PatchValue<Option<u64>>,
+    /// Whether users have to unlock the subtask before they can access it.
+    pub locked: PatchValue<bool>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BulkUpdateSubtasksRequest {
+    /// The subtasks to update. Omit to update all subtasks of the task.
+    pub subtask_ids: Option<Vec<Uuid>>,
+    /// Enable or disable the subtasks.
+    pub enabled: Option<bool>,
+    /// Move the subtasks to another task.
+    pub move_to: Option<Uuid>,
+    /// Adjust the xp of the subtasks by this percentage (e.g. `10` to increase
+    /// by 10% or `-50` to halve).
+    #[oai(validator(minimum(value = "-100")))]
+    pub xp_percent: Option<i32>,
+    /// Adjust the coins of the subtasks by this percentage.
+    #[oai(validator(minimum(value = "-100")))]
+    pub coins_percent: Option<i32>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BulkUpdateSubtasksSummary {
+    /// The number of subtasks the operations have been applied to.
+    pub matched: u64,
+    /// The number of subtasks that have been enabled or disabled.
+    pub enabled_changed: u64,
+    /// The number of subtasks that have been moved to another task.
+    pub moved: u64,
+    /// The number of subtasks whose xp or coins have changed.
+    pub rewards_changed: u64,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -78,6 +202,108 @@ pub struct SubtaskStats {
     pub attempted: u64,
     /// Number of subtasks the user has not yet tried to solve.
     pub unattempted: u64,
+    /// Time the user needed to solve the subtasks.
+    pub time_to_solve: TimeToSolveStats,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Object)]
+pub struct TimeToSolveStats {
+    /// Number of solves for which the time to solve is known, i.e. the subtask
+    /// has been started before it was solved.
+    pub solves: u64,
+    /// The average time to solve in seconds.
+    pub average: Option<u64>,
+    /// The median time to solve in seconds.
+    pub median: Option<u64>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct WrongAnswerStats {
+    /// The subtask type. Mistakes are only recorded for multiple choice
+    /// questions and matchings.
+    pub subtask_type: ChallengesSubtaskType,
+    /// The mistakes users have made, ordered by how often they have been made.
+    pub mistakes: Vec<WrongAnswerCount>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct WrongAnswerCount {
+    /// For multiple choice questions the index of the answer option. For
+    /// matchings the index of the entry on the left.
+    pub item: u32,
+    /// For multiple choice questions `1` if the option has been selected
+    /// although it is wrong and `0` if a correct option has not been selected.
+    /// For matchings the index of the entry on the right the left entry has
+    /// been wrongly matched with.
+    pub choice: u32,
+    /// The number of failed attempts that contained this mistake.
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubtaskSession {
+    /// The parent task of the subtask.
+    pub task_id: Uuid,
+    /// The started subtask.
+    pub subtask_id: Uuid,
+    /// The time the user has started working on the subtask.
+    pub start: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct NextSubtask {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The estimated difficulty of the subtask between 0 (solved by everyone)
+    /// and 1 (solved by nobody), based on the solve rate of all users.
+    pub difficulty: f64,
+    /// How well the subtask matches the current level of the user. Higher is
+    /// better.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubtaskPrerequisites {
+    /// The subtasks that have to be solved before this subtask is
+    /// recommended.
+    pub prerequisites: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct Bookmark {
+    /// The parent task of the bookmarked subtask.
+    pub task_id: Uuid,
+    /// The bookmarked subtask.
+    pub subtask_id: Uuid,
+    /// The time the subtask has been bookmarked.
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BookmarkedSubtask {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The time the subtask has been bookmarked.
+    pub bookmark_timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubtaskNote {
+    /// The parent task of the subtask.
+    pub task_id: Uuid,
+    /// The subtask the note belongs to.
+    pub subtask_id: Uuid,
+    /// The content of the note (markdown).
+    pub note: String,
+    /// The time the note has last been changed.
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SetSubtaskNoteRequest {
+    /// The content of the note (markdown).
+    #[oai(validator(max_length = 16384))]
+    pub note: String,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -119,6 +345,132 @@ pub enum ResolveReportAction {
     BlockCreator,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct SolveToken {
+    /// The signed token.
+    pub token: String,
+    /// The time until which the answer has to be submitted.
+    pub expires: DateTime<Utc>,
+}
+
+/// The solution of a subtask of any type. The `type` of the subtask is already
+/// used by the subtask itself, so the variants are distinguished by
+/// `solution_type`.
+#[derive(Debug, Clone, Union)]
+#[oai(discriminator_name = "solution_type", one_of)]
+pub enum SubtaskSolution {
+    #[oai(mapping = "coding_challenge")]
+    CodingChallenge(SubmissionContent),
+    #[oai(mapping = "matching")]
+    Matching(MatchingWithSolution),
+    #[oai(mapping = "multiple_choice")]
+    MultipleChoice(MultipleChoiceQuestion<Answer>),
+    #[oai(mapping = "question")]
+    Question(QuestionWithSolution),
+    #[oai(mapping = "true_false")]
+    TrueFalse(TrueFalseWithSolution),
+    #[oai(mapping = "ordering")]
+    Ordering(OrderingWithSolution),
+    #[oai(mapping = "cloze")]
+    Cloze(ClozeWithSolution),
+    #[oai(mapping = "code_reading")]
+    CodeReading(CodeReadingWithSolution),
+    #[oai(mapping = "sql_challenge")]
+    SqlChallenge(SqlChallengeWithSolution),
+}
+
+/// The content of a subtask of any type without its solution. The variants are
+/// distinguished by `content_type`.
+#[derive(Debug, Clone, Union)]
+#[oai(discriminator_name = "content_type", one_of)]
+pub enum SubtaskContent {
+    #[oai(mapping = "coding_challenge")]
+    CodingChallenge(CodingChallenge),
+    #[oai(mapping = "matching")]
+    Matching(Matching),
+    #[oai(mapping = "multiple_choice")]
+    MultipleChoice(MultipleChoiceQuestion<String>),
+    #[oai(mapping = "question")]
+    Question(Question),
+    #[oai(mapping = "true_false")]
+    TrueFalse(TrueFalse),
+    #[oai(mapping = "ordering")]
+    Ordering(Ordering),
+    #[oai(mapping = "cloze")]
+    Cloze(Cloze),
+    #[oai(mapping = "code_reading")]
+    CodeReading(CodeReading),
+    #[oai(mapping = "sql_challenge")]
+    SqlChallenge(SqlChallenge),
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BatchSolveRequest {
+    /// The answers to submit. Each subtask may occur at most once.
+    #[oai(validator(min_items = 1, max_items = 50))]
+    pub attempts: Vec<BatchAttempt>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BatchAttempt {
+    /// The subtask to solve.
+    pub subtask_id: Uuid,
+    /// The answer to submit. The `type` has to match the type of the subtask.
+    pub answer: BatchAnswer,
+}
+
+#[derive(Debug, Clone, Union)]
+#[oai(discriminator_name = "type", one_of)]
+pub enum BatchAnswer {
+    #[oai(mapping = "multiple_choice")]
+    MultipleChoice(SolveMCQRequest),
+    #[oai(mapping = "matching")]
+    Matching(SolveMatchingRequest),
+    #[oai(mapping = "question")]
+    Question(SolveQuestionRequest),
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BatchSolveResponse {
+    /// The results of the attempts in the order of the request.
+    pub results: Vec<BatchAttemptResult>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct BatchAttemptResult {
+    /// The subtask of the attempt.
+    pub subtask_id: Uuid,
+    /// The status code the corresponding solve endpoint would have returned.
+    pub status: u16,
+    /// The response body the corresponding solve endpoint would have returned.
+    pub body: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Object)]
+pub struct ContentViolation {
+    /// The field containing the violation (e.g. `answers.1`).
+    pub field: String,
+    pub kind: ContentViolationKind,
+    /// The offending part of the text.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContentViolationKind {
+    /// A word on the configured wordlist.
+    Profanity,
+    Email,
+    PhoneNumber,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
 #[derive(Debug, Clone, Object, Deserialize)]
 pub struct SubtasksUserConfig {
     /// The minimum level a normal user needs to have in each skill related to a
@@ -130,6 +482,34 @@ pub struct SubtasksUserConfig {
     pub max_coins: u64,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct TaskConfig {
+    /// Overrides the maximum `xp` value for subtasks created by normal users
+    /// in this task.
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub max_xp: Option<u64>,
+    /// Overrides the maximum `coins` value for subtasks created by normal
+    /// users in this task.
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub max_coins: Option<u64>,
+    /// Overrides the number of seconds a user has to wait after an attempt
+    /// before trying to solve a subtask of this task again.
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub timeout_incr: Option<u64>,
+    /// If set, every user only has to solve this number of randomly selected
+    /// subtasks of this task. The selection is stable for each user.
+    #[oai(validator(minimum(value = "1"), maximum(value = "2147483647")))]
+    pub pool_size: Option<u64>,
+    /// The number of xp a user gets once for solving all subtasks of this
+    /// task (or all subtasks in the pool of the user).
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub completion_xp: Option<u64>,
+    /// The number of morphcoins a user gets once for solving all subtasks of
+    /// this task (or all subtasks in the pool of the user).
+    #[oai(validator(maximum(value = "9223372036854775807")))]
+    pub completion_coins: Option<u64>,
+}
+
 #[derive(Debug, Clone, Object)]
 pub struct Ban {
     /// The unique identifier of the ban.
@@ -150,6 +530,14 @@ pub struct Ban {
     pub reason: String,
 }
 
+#[derive(Debug, Clone, Object)]
+pub struct RejectSubtaskRequest {
+    /// The reason why the subtask has been rejected. This is sent to the
+    /// creator of the subtask.
+    #[oai(validator(max_length = 4096))]
+    pub reason: String,
+}
+
 #[derive(Debug, Clone, Object)]
 pub struct CreateBanRequest {
     /// The unique identifier of the user who is banned.
@@ -159,6 +547,8 @@ pub struct CreateBanRequest {
     /// The end timestamp of the ban. Null if this is a permanent ban.
     pub end: Option<DateTime<Utc>>,
     /// The action the user is not allowed to perform due to this ban.
+    /// `SHADOW_CREATE` does not block the creation of subtasks, but keeps new
+    /// subtasks of the user disabled until a moderator approves them.
     pub action: ChallengesBanAction,
     /// The reason why the user has been banned.
     #[oai(validator(max_length = 4096))]
@@ -199,6 +589,17 @@ impl Report {
     }
 }
 
+impl SubtaskNote {
+    pub fn from(note: challenges_user_notes::Model, task_id: Uuid) -> Self {
+        Self {
+            task_id,
+            subtask_id: note.subtask_id,
+            note: note.note,
+            timestamp: note.timestamp.and_utc(),
+        }
+    }
+}
+
 impl Subtask {
     pub fn from(subtask: challenges_subtasks::Model, solved: bool, rated: bool) -> Self {
         Self {
@@ -213,6 +614,18 @@ impl Subtask {
             rated,
             enabled: subtask.enabled,
             retired: subtask.retired,
+            review_status: subtask.review_status,
+            rejection_reason: subtask.rejection_reason,
+            license: subtask.license,
+            attribution: subtask.attribution,
+            max_attempts: subtask.max_attempts.map(|x| x as _),
+            reveal_solution: subtask.reveal_solution,
+            time_limit: subtask.time_limit.map(|x| x as _),
+            unlock_price: subtask.unlock_price.map(|x| x as _),
+            locked: subtask.locked,
+            solve_token: None,
+            note: None,
+            due: None,
         }
     }
 }
@@ -233,3 +646,16 @@ impl From<challenges_ban::Model> for Ban {
         }
     }
 }
+
+impl From<challenges_task_config::Model> for TaskConfig {
+    fn from(value: challenges_task_config::Model) -> Self {
+        Self {
+            max_xp: value.max_xp.map(|x| x as _),
+            max_coins: value.max_coins.map(|x| x as _),
+            timeout_incr: value.timeout_incr.map(|x| x as _),
+            pool_size: value.pool_size.map(|x| x as _),
+            completion_xp: value.completion_xp.map(|x| x as _),
+            completion_coins: value.completion_coins.map(|x| x as _),
+        }
+    }
+}
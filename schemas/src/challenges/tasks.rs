@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use entity::{challenges_challenges, challenges_course_tasks, challenges_tasks};
+use poem_openapi::{Enum, Object};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Object)]
+pub struct Task {
+    /// The unique identifier of the task
+    pub id: Uuid,
+    /// The creator of the task
+    pub creator: Uuid,
+    /// The creation timestamp of the task
+    pub creation_timestamp: DateTime<Utc>,
+    /// The organization this task belongs to (if any)
+    pub organization_id: Option<Uuid>,
+    /// The kind of the task
+    pub kind: Option<TaskKind>,
+    /// The course this task is associated with (only for course tasks)
+    pub course_id: Option<String>,
+    /// The section this task is associated with (only for course tasks)
+    pub section_id: Option<String>,
+    /// The lecture this task is associated with (only for course tasks)
+    pub lecture_id: Option<String>,
+    /// The category of the challenge (only for challenges)
+    pub category_id: Option<Uuid>,
+    /// The title of the challenge (only for challenges)
+    pub title: Option<String>,
+    /// The skills of the challenge (only for challenges)
+    pub skills: Vec<String>,
+    /// The number of subtasks in this task
+    pub subtasks: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SkillTask {
+    #[oai(flatten)]
+    pub task: Task,
+    /// The progress of the user in this task
+    pub progress: TaskProgress,
+}
+
+#[derive(Debug, Clone, Copy, Default, Object)]
+pub struct TaskProgress {
+    /// The number of enabled subtasks in the task
+    pub subtasks: u64,
+    /// The number of enabled subtasks the user has solved
+    pub solved: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// A global challenge in a category
+    Challenge,
+    /// A task that exists within a course
+    CourseTask,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateTaskRequest {
+    /// The course this task is associated with
+    pub course_id: String,
+    /// The section this task is associated with
+    pub section_id: Option<String>,
+    /// The lecture this task is associated with
+    pub lecture_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UnlockTaskResponse {
+    /// The subtasks that have been unlocked.
+    pub unlocked: Vec<Uuid>,
+    /// The number of morphcoins the user has paid.
+    pub price: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct TaskDependents {
+    /// The number of subtasks in the task
+    pub subtasks: u64,
+    /// The number of contests of the task
+    pub contests: u64,
+    /// The number of exams of the task
+    pub exams: u64,
+    /// The number of learning path items referencing the task
+    pub path_items: u64,
+    /// The number of cohorts the task is assigned to
+    pub cohort_assignments: u64,
+}
+
+impl TaskDependents {
+    pub fn is_empty(&self) -> bool {
+        self.subtasks == 0
+            && self.contests == 0
+            && self.exams == 0
+            && self.path_items == 0
+            && self.cohort_assignments == 0
+    }
+}
+
+impl Task {
+    pub fn from(
+        task: challenges_tasks::Model,
+        challenge: Option<challenges_challenges::Model>,
+        course_task: Option<challenges_course_tasks::Model>,
+        subtasks: u64,
+    ) -> Self {
+        let kind = match (&challenge, &course_task) {
+            (Some(_), _) => Some(TaskKind::Challenge),
+            (None, Some(_)) => Some(TaskKind::CourseTask),
+            (None, None) => None,
+        };
+        let (course_id, section_id, lecture_id) = match course_task {
+            Some(x) => (Some(x.course_id), x.section_id, x.lecture_id),
+            None => (None, None, None),
+        };
+        let (category_id, title, skills) = match challenge {
+            Some(x) => (Some(x.category_id), Some(x.title), x.skill_ids),
+            None => (None, None, Vec::new()),
+        };
+        Self {
+            id: task.id,
+            creator: task.creator,
+            creation_timestamp: task.creation_timestamp.and_utc(),
+            organization_id: task.organization_id,
+            kind,
+            course_id,
+            section_id,
+            lecture_id,
+            category_id,
+            title,
+            skills,
+            subtasks,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use entity::challenges_true_false_statements;
+use poem_ext::patch_value::PatchValue;
+use poem_openapi::Object;
+
+use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
+
+#[derive(Debug, Clone, Object)]
+pub struct TrueFalseSummary {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The statement.
+    pub statement: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct TrueFalse {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The statement.
+    pub statement: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct TrueFalseWithSolution {
+    #[oai(flatten)]
+    pub subtask: Subtask,
+    /// The statement.
+    pub statement: String,
+    /// Whether the statement is true.
+    pub answer: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateTrueFalseRequest {
+    #[oai(flatten)]
+    pub subtask: CreateSubtaskRequest,
+    /// The statement.
+    #[oai(validator(max_length = 4096))]
+    pub statement: String,
+    /// Whether the statement is true.
+    pub answer: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UpdateTrueFalseRequest {
+    #[oai(flatten)]
+    pub subtask: UpdateSubtaskRequest,
+    /// The statement.
+    #[oai(validator(max_length = 4096))]
+    pub statement: PatchValue<String>,
+    /// Whether the statement is true.
+    pub answer: PatchValue<bool>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveTrueFalseRequest {
+    /// Whether the user thinks that the statement is true.
+    pub answer: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SolveTrueFalseFeedback {
+    /// Whether the user has successfully solved the statement.
+    pub solved: bool,
+}
+
+impl TrueFalseSummary {
+    pub fn from(statement: challenges_true_false_statements::Model, subtask: Subtask) -> Self {
+        Self {
+            statement: statement.statement,
+            subtask,
+        }
+    }
+}
+
+impl TrueFalse {
+    pub fn from(statement: challenges_true_false_statements::Model, subtask: Subtask) -> Self {
+        Self {
+            statement: statement.statement,
+            subtask,
+        }
+    }
+}
+
+impl TrueFalseWithSolution {
+    pub fn from(statement: challenges_true_false_statements::Model, subtask: Subtask) -> Self {
+        Self {
+            statement: statement.statement,
+            answer: statement.answer,
+            subtask,
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_subtask_access, challenges_subtask_reports, challenges_user_subtasks,
+    sea_orm_active_enums::{ChallengesRating, ChallengesReportReason, ChallengesVerdict},
+};
+use poem_openapi::{Enum, Object};
+use uuid::Uuid;
+
+use super::{
+    notifications::NotificationPreferences,
+    subtasks::{Ban, Bookmark, SubtaskNote, SubtaskSession},
+};
+
+#[derive(Debug, Clone, Object)]
+pub struct UserDataExport {
+    /// The unique identifier of the user.
+    pub user_id: Uuid,
+    /// The timestamp of the export.
+    pub timestamp: DateTime<Utc>,
+    /// The progress of the user in all subtasks they have interacted with
+    /// (incl. ratings).
+    pub subtasks: Vec<UserSubtaskData>,
+    /// All attempts to solve multiple choice questions.
+    pub multiple_choice_attempts: Vec<AttemptData>,
+    /// All attempts to solve questions.
+    pub question_attempts: Vec<AttemptData>,
+    /// All attempts to solve matchings.
+    pub matching_attempts: Vec<AttemptData>,
+    /// All attempts to solve true/false statements.
+    pub true_false_attempts: Vec<AttemptData>,
+    /// All attempts to solve orderings.
+    pub ordering_attempts: Vec<AttemptData>,
+    /// All attempts to solve clozes.
+    pub cloze_attempts: Vec<AttemptData>,
+    /// All attempts to solve code reading questions.
+    pub code_reading_attempts: Vec<AttemptData>,
+    /// All attempts to solve sql challenges.
+    pub sql_attempts: Vec<AttemptData>,
+    /// All outputs uploaded for output-only coding challenges. An upload is
+    /// considered solved if all outputs have been accepted.
+    pub output_submissions: Vec<AttemptData>,
+    /// All coding challenge submissions (incl. code).
+    pub submissions: Vec<SubmissionData>,
+    /// All subtasks the user has been granted access to.
+    pub unlocks: Vec<UnlockData>,
+    /// All subtasks the user has bookmarked.
+    pub bookmarks: Vec<Bookmark>,
+    /// All private notes of the user on subtasks.
+    pub notes: Vec<SubtaskNote>,
+    /// The times the user has started working on subtasks.
+    pub sessions: Vec<SubtaskSession>,
+    /// All reports the user has created (incl. comments).
+    pub reports: Vec<ReportData>,
+    /// All bans of the user.
+    pub bans: Vec<Ban>,
+    /// The notification preferences of the user (`null` if the user has never
+    /// changed them).
+    pub notification_preferences: Option<NotificationPreferences>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UserSubtaskData {
+    pub subtask_id: Uuid,
+    pub solved_timestamp: Option<DateTime<Utc>>,
+    pub rating: Option<ChallengesRating>,
+    pub rating_timestamp: Option<DateTime<Utc>>,
+    pub last_attempt_timestamp: Option<DateTime<Utc>>,
+    pub attempts: i32,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct AttemptData {
+    pub id: Uuid,
+    pub subtask_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub solved: bool,
+    /// The revision of the subtask at the time of the attempt.
+    pub revision_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmissionData {
+    pub id: Uuid,
+    pub subtask_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub environment: String,
+    pub code: String,
+    /// The verdict of the judge (`null` if the submission has not been judged
+    /// yet).
+    pub verdict: Option<ChallengesVerdict>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UnlockData {
+    pub subtask_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ReportData {
+    pub id: Uuid,
+    pub subtask_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub reason: ChallengesReportReason,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DeleteUserDataResponse {
+    /// Whether this was a dry run. If `true`, no data has been modified.
+    pub dry_run: bool,
+    /// The number of affected rows per table.
+    pub affected: Vec<AffectedRows>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct AffectedRows {
+    /// The name of the database table.
+    pub table: String,
+    /// Whether the rows are removed or anonymized.
+    pub action: UserDataAction,
+    /// The number of affected rows.
+    pub rows: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UserDataAction {
+    Delete,
+    Anonymize,
+}
+
+impl From<challenges_user_subtasks::Model> for UserSubtaskData {
+    fn from(value: challenges_user_subtasks::Model) -> Self {
+        Self {
+            subtask_id: value.subtask_id,
+            solved_timestamp: value.solved_timestamp.map(|ts| ts.and_utc()),
+            rating: value.rating,
+            rating_timestamp: value.rating_timestamp.map(|ts| ts.and_utc()),
+            last_attempt_timestamp: value.last_attempt_timestamp.map(|ts| ts.and_utc()),
+            attempts: value.attempts,
+        }
+    }
+}
+
+impl
+    From<(
+        challenges_coding_challenge_submissions::Model,
+        Option<challenges_coding_challenge_result::Model>,
+    )> for SubmissionData
+{
+    fn from(
+        (submission, result): (
+            challenges_coding_challenge_submissions::Model,
+            Option<challenges_coding_challenge_result::Model>,
+        ),
+    ) -> Self {
+        Self {
+            id: submission.id,
+            subtask_id: submission.subtask_id,
+            timestamp: submission.creation_timestamp.and_utc(),
+            environment: submission.environment,
+            code: submission.code,
+            verdict: result.map(|x| x.verdict),
+        }
+    }
+}
+
+impl From<challenges_subtask_access::Model> for UnlockData {
+    fn from(value: challenges_subtask_access::Model) -> Self {
+        Self {
+            subtask_id: value.subtask_id,
+            timestamp: value.timestamp.and_utc(),
+        }
+    }
+}
+
+impl From<challenges_subtask_reports::Model> for ReportData {
+    fn from(value: challenges_subtask_reports::Model) -> Self {
+        Self {
+            id: value.id,
+            subtask_id: value.subtask_id,
+            timestamp: value.timestamp.and_utc(),
+            reason: value.reason,
+            comment: value.comment,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use lib::jwt::{sign_jwt, JwtSecret, UserAccessToken, UserAccessTokenData};
+use uuid::Uuid;
+
+/// The jwt secret used by the [`TestContext`](crate::TestContext).
+pub const JWT_SECRET: &str = "test-secret";
+
+/// A fake user for whom access tokens can be minted without the auth
+/// microservice.
+#[derive(Debug, Clone, Copy)]
+pub struct TestUser {
+    pub id: Uuid,
+    pub email_verified: bool,
+    pub admin: bool,
+}
+
+impl TestUser {
+    /// Return a new normal user with a verified email address.
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            email_verified: true,
+            admin: false,
+        }
+    }
+
+    /// Return a new admin.
+    pub fn admin() -> Self {
+        Self {
+            admin: true,
+            ..Self::new()
+        }
+    }
+
+    /// Return a new user who has not verified their email address yet.
+    pub fn unverified() -> Self {
+        Self {
+            email_verified: false,
+            ..Self::new()
+        }
+    }
+
+    /// Return a valid access token of this user.
+    pub fn token(&self) -> String {
+        let secret = JwtSecret::try_from(JWT_SECRET).expect("invalid jwt secret");
+        sign_jwt(
+            UserAccessToken {
+                uid: self.id,
+                rt: Uuid::new_v4().to_string(),
+                data: UserAccessTokenData {
+                    email_verified: self.email_verified,
+                    admin: self.admin,
+                },
+            },
+            &secret,
+            Duration::from_secs(3600),
+        )
+        .expect("could not sign access token")
+    }
+}
+
+impl Default for TestUser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
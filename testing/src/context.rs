@@ -0,0 +1,115 @@
+use std::{env, sync::Arc, time::Duration};
+
+use fnct::{backend::AsyncRedisBackend, format::PostcardFormatter};
+use lib::{
+    config::{Config, Redis, Services as ServicesConfig, SharedConfig},
+    jwt::JwtSecret,
+    redis::RedisConnection,
+    services::Services,
+    Cache, SharedState,
+};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{auth::JWT_SECRET, MockServer, TestDatabase};
+
+const CONFIG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../config.toml");
+
+/// Everything needed to run the microservice in an integration test: a fresh
+/// database, the configuration and mocks of all external services.
+pub struct TestContext {
+    pub db: TestDatabase,
+    pub config: SharedConfig,
+    pub state: Arc<SharedState>,
+    /// Mock of the skills, shop and email microservices.
+    pub services: MockServer,
+    pub sandkasten: MockServer,
+}
+
+impl TestContext {
+    /// Set up a new context with the default configuration. Returns `None` if
+    /// `TEST_DATABASE_URL` or `TEST_REDIS_URL` is not set.
+    pub async fn new() -> anyhow::Result<Option<Self>> {
+        Self::with_config(|_| {}).await
+    }
+
+    /// Set up a new context and adjust the default configuration before it is
+    /// used. Returns `None` if `TEST_DATABASE_URL` or `TEST_REDIS_URL` is not
+    /// set.
+    pub async fn with_config(f: impl FnOnce(&mut Config)) -> anyhow::Result<Option<Self>> {
+        let (Ok(database_url), Ok(redis_url)) =
+            (env::var("TEST_DATABASE_URL"), env::var("TEST_REDIS_URL"))
+        else {
+            return Ok(None);
+        };
+        let database_url: Url = database_url.parse()?;
+        let redis_url: Url = redis_url.parse()?;
+
+        let db = TestDatabase::create(&database_url).await?;
+        let services = MockServer::services().await?;
+        let sandkasten = MockServer::sandkasten().await?;
+
+        let mut config: Config = config::Config::builder()
+            .add_source(config::File::with_name(CONFIG_PATH))
+            .build()?
+            .try_deserialize()?;
+        config.jwt_secret = JWT_SECRET.into();
+        config.database.url = db.url().clone();
+        config.redis = Redis {
+            auth: redis_url.clone(),
+            skills: redis_url.clone(),
+            shop: redis_url.clone(),
+            jobs: redis_url.clone(),
+            events: redis_url.clone(),
+            challenges: redis_url.clone(),
+        };
+        config.services = ServicesConfig {
+            auth: services.url().clone(),
+            skills: services.url().clone(),
+            shop: services.url().clone(),
+            jobs: services.url().clone(),
+            events: services.url().clone(),
+            challenges: services.url().clone(),
+            email: services.url().clone(),
+        };
+        config.event_bus = None;
+        config.challenges.coding_challenges.sandkasten_url = sandkasten.url().clone();
+        f(&mut config);
+
+        // use a separate namespace for each context, as tests run concurrently
+        let cache = Cache::new(
+            AsyncRedisBackend::new(
+                RedisConnection::new(redis_url.as_str()).await?,
+                format!("challenges_test_{}", Uuid::new_v4().simple()),
+            ),
+            PostcardFormatter,
+            Duration::from_secs(config.cache_ttl),
+        );
+        let jwt_secret = JwtSecret::try_from(config.jwt_secret.as_str())?;
+        let state = Arc::new(SharedState {
+            services: Services::from_config(
+                jwt_secret.clone(),
+                Duration::from_secs(config.internal_jwt_ttl),
+                &config.services,
+                cache.clone(),
+            ),
+            jwt_secret,
+            auth_redis: RedisConnection::new(redis_url.as_str()).await?,
+            cache,
+            db: db.conn.clone(),
+        });
+
+        Ok(Some(Self {
+            db,
+            config: SharedConfig::new(config),
+            state,
+            services,
+            sandkasten,
+        }))
+    }
+
+    /// Drop the database of this context.
+    pub async fn cleanup(self) -> anyhow::Result<()> {
+        Ok(self.db.drop().await?)
+    }
+}
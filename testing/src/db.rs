@@ -0,0 +1,50 @@
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbErr};
+use url::Url;
+use uuid::Uuid;
+
+/// A temporary database with all migrations applied.
+pub struct TestDatabase {
+    pub conn: DatabaseConnection,
+    url: Url,
+    name: String,
+    server: DatabaseConnection,
+}
+
+impl TestDatabase {
+    /// Create a new database on the PostgreSQL server at `server_url` and
+    /// apply all migrations.
+    pub async fn create(server_url: &Url) -> Result<Self, DbErr> {
+        let server = Database::connect(server_url.as_str()).await?;
+        let name = format!("challenges_test_{}", Uuid::new_v4().simple());
+        server
+            .execute_unprepared(&format!("CREATE DATABASE \"{name}\""))
+            .await?;
+
+        let mut url = server_url.clone();
+        url.set_path(&name);
+        let conn = Database::connect(url.as_str()).await?;
+        Migrator::up(&conn, None).await?;
+
+        Ok(Self {
+            conn,
+            url,
+            name,
+            server,
+        })
+    }
+
+    /// Return the url of the database.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Close all connections and drop the database.
+    pub async fn drop(self) -> Result<(), DbErr> {
+        self.conn.close().await?;
+        self.server
+            .execute_unprepared(&format!("DROP DATABASE \"{}\" WITH (FORCE)", self.name))
+            .await?;
+        self.server.close().await
+    }
+}
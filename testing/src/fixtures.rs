@@ -0,0 +1,121 @@
+//! Functions inserting test data directly into the database.
+//!
+//! All functions accept any connection, so fixtures can also be created in a
+//! transaction which is rolled back at the end of a test.
+
+use chrono::Utc;
+use entity::{
+    challenges_challenge_categories, challenges_challenges, challenges_questions,
+    challenges_subtasks, challenges_tasks,
+    sea_orm_active_enums::{
+        ChallengesLicense, ChallengesQuestionAnswerType, ChallengesReviewStatus,
+        ChallengesSubtaskType,
+    },
+};
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use uuid::Uuid;
+
+/// Create a new challenge category.
+pub async fn create_category(
+    db: &impl ConnectionTrait,
+) -> Result<challenges_challenge_categories::Model, DbErr> {
+    challenges_challenge_categories::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        title: Set("Test Category".into()),
+        description: Set("A category for tests".into()),
+        creation_timestamp: Set(Utc::now().naive_utc()),
+    }
+    .insert(db)
+    .await
+}
+
+/// Create a new challenge (task) in a category.
+pub async fn create_challenge(
+    db: &impl ConnectionTrait,
+    category_id: Uuid,
+    creator: Uuid,
+    skill_ids: &[&str],
+) -> Result<challenges_challenges::Model, DbErr> {
+    let task = challenges_tasks::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        creator: Set(creator),
+        creation_timestamp: Set(Utc::now().naive_utc()),
+        organization_id: Set(None),
+    }
+    .insert(db)
+    .await?;
+    challenges_challenges::ActiveModel {
+        task_id: Set(task.id),
+        category_id: Set(category_id),
+        skill_ids: Set(skill_ids.iter().map(|&x| x.into()).collect()),
+        title: Set("Test Challenge".into()),
+        description: Set("A challenge for tests".into()),
+    }
+    .insert(db)
+    .await
+}
+
+/// Create a new enabled and approved subtask without any content. Use the
+/// other functions of this module to create subtasks of a specific type.
+pub async fn create_subtask(
+    db: &impl ConnectionTrait,
+    task_id: Uuid,
+    creator: Uuid,
+    ty: ChallengesSubtaskType,
+    xp: i64,
+    coins: i64,
+) -> Result<challenges_subtasks::Model, DbErr> {
+    challenges_subtasks::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        task_id: Set(task_id),
+        creator: Set(creator),
+        creation_timestamp: Set(Utc::now().naive_utc()),
+        xp: Set(xp),
+        coins: Set(coins),
+        enabled: Set(true),
+        ty: Set(ty),
+        retired: Set(false),
+        fingerprint: Set(None),
+        review_status: Set(ChallengesReviewStatus::Approved),
+        rejection_reason: Set(None),
+        license: Set(ChallengesLicense::AllRightsReserved),
+        attribution: Set(None),
+        max_attempts: Set(None),
+        reveal_solution: Set(false),
+        time_limit: Set(None),
+        unlock_price: Set(None),
+        locked: Set(false),
+    }
+    .insert(db)
+    .await
+}
+
+/// Create a new question with typed answers.
+pub async fn create_question(
+    db: &impl ConnectionTrait,
+    task_id: Uuid,
+    creator: Uuid,
+    question: &str,
+    answers: &[&str],
+) -> Result<(challenges_subtasks::Model, challenges_questions::Model), DbErr> {
+    let subtask =
+        create_subtask(db, task_id, creator, ChallengesSubtaskType::Question, 10, 5).await?;
+    let question = challenges_questions::ActiveModel {
+        subtask_id: Set(subtask.id),
+        question: Set(question.into()),
+        answers: Set(answers.iter().map(|&x| x.into()).collect()),
+        case_sensitive: Set(false),
+        ascii_letters: Set(true),
+        digits: Set(true),
+        punctuation: Set(true),
+        blocks: Set(Vec::new()),
+        parameters: Set(Default::default()),
+        answer_type: Set(ChallengesQuestionAnswerType::Text),
+        unit: Set(None),
+        absolute_tolerance: Set(None),
+        relative_tolerance: Set(None),
+    }
+    .insert(db)
+    .await?;
+    Ok((subtask, question))
+}
@@ -0,0 +1,29 @@
+//! Utilities for integration tests of the microservice.
+//!
+//! Integration tests need a PostgreSQL and a redis server, which are
+//! configured via the `TEST_DATABASE_URL` and `TEST_REDIS_URL` environment
+//! variables. [`TestContext::new`] returns `None` if these variables are not
+//! set, so tests can be skipped in environments without these servers.
+//!
+//! The other microservices and Sandkasten are replaced by [`MockServer`]s,
+//! which record all requests they receive.
+
+#![forbid(unsafe_code)]
+#![warn(clippy::dbg_macro, clippy::use_debug, clippy::todo)]
+
+pub use poem::test::TestClient;
+
+pub use self::{
+    auth::TestUser,
+    context::TestContext,
+    db::TestDatabase,
+    mock::{MockServer, RecordedRequest},
+    request::RequestExt,
+};
+
+pub mod auth;
+mod context;
+mod db;
+pub mod fixtures;
+pub mod mock;
+mod request;
@@ -0,0 +1,232 @@
+use std::sync::{Arc, Mutex};
+
+use poem::{
+    endpoint::make,
+    http::{Method, StatusCode},
+    listener::{Acceptor, Listener, TcpListener},
+    Body, Request, Response, Server,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use url::Url;
+
+/// A http server which responds to requests with previously registered
+/// responses and records all requests it receives.
+pub struct MockServer {
+    url: Url,
+    state: Arc<Mutex<MockState>>,
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+#[derive(Default)]
+struct MockState {
+    routes: Vec<MockRoute>,
+    requests: Vec<RecordedRequest>,
+}
+
+struct MockRoute {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    body: Value,
+}
+
+impl MockServer {
+    /// Start a new server on a random port.
+    pub async fn start() -> std::io::Result<Self> {
+        let acceptor = TcpListener::bind("127.0.0.1:0").into_acceptor().await?;
+        let addr = *acceptor.local_addr()[0]
+            .as_socket_addr()
+            .expect("tcp listener has no socket address");
+        let url = format!("http://{addr}/").parse().expect("invalid url");
+
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let endpoint = make({
+            let state = Arc::clone(&state);
+            move |req| handle(Arc::clone(&state), req)
+        });
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(endpoint));
+
+        Ok(Self { url, state })
+    }
+
+    /// Start a new server which responds like the skills, shop and email
+    /// microservices of the backend.
+    ///
+    /// Users have no premium, but enough hearts, and all skill progress and
+    /// coin updates succeed.
+    pub async fn services() -> std::io::Result<Self> {
+        let server = Self::start().await?;
+        server.mock(Method::GET, "/_internal/premium/*", StatusCode::OK, false);
+        server.mock(
+            Method::GET,
+            "/_internal/hearts/*",
+            StatusCode::OK,
+            json!({"hearts": 100}),
+        );
+        server.mock(Method::POST, "/_internal/hearts/*", StatusCode::OK, true);
+        server.mock(
+            Method::POST,
+            "/_internal/coins/*",
+            StatusCode::OK,
+            json!({"coins": 0, "withheld_coins": 0}),
+        );
+        server.mock(Method::GET, "/_internal/skills", StatusCode::OK, json!([]));
+        server.mock(
+            Method::GET,
+            "/_internal/skills/*",
+            StatusCode::OK,
+            json!({}),
+        );
+        server.mock(
+            Method::POST,
+            "/_internal/skills/*/*",
+            StatusCode::OK,
+            json!({}),
+        );
+        server.mock(Method::GET, "/_internal/courses", StatusCode::OK, json!({}));
+        server.mock(
+            Method::POST,
+            "/_internal/users/*/emails",
+            StatusCode::OK,
+            json!({}),
+        );
+        Ok(server)
+    }
+
+    /// Start a new server which responds like a Sandkasten server without any
+    /// environments. Use [`MockServer::mock`] with [`sandkasten_run`] to mock
+    /// the execution of programs.
+    pub async fn sandkasten() -> std::io::Result<Self> {
+        let server = Self::start().await?;
+        server.mock(
+            Method::GET,
+            "/openapi.json",
+            StatusCode::OK,
+            json!({"info": {"version": sandkasten_client::VERSION}}),
+        );
+        server.mock(Method::GET, "/environments", StatusCode::OK, json!({}));
+        Ok(server)
+    }
+
+    /// Return the base url of the server.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Respond to requests matching `method` and `path` with the given status
+    /// code and json body. A `*` in `path` matches a single path segment.
+    /// Routes registered later take precedence.
+    pub fn mock(&self, method: Method, path: &str, status: StatusCode, body: impl Serialize) {
+        self.state.lock().unwrap().routes.push(MockRoute {
+            method,
+            path: path.into(),
+            status,
+            body: serde_json::to_value(body).expect("could not serialize mock response"),
+        });
+    }
+
+    /// Return all requests the server has received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Return all requests the server has received so far which match
+    /// `method` and `path`.
+    pub fn requests_to(&self, method: Method, path: &str) -> Vec<RecordedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|req| req.method == method && path_matches(path, &req.path))
+            .collect()
+    }
+}
+
+/// Return the response of Sandkasten to a program that has been built and run
+/// successfully.
+pub fn sandkasten_run(status: i32, stdout: &str, stderr: &str) -> Value {
+    let result = json!({
+        "status": status,
+        "stdout": stdout,
+        "stderr": stderr,
+        "resource_usage": {"time": 1, "memory": 1024},
+        "limits": {
+            "cpus": 1,
+            "time": 5,
+            "memory": 1024,
+            "tmpfs": 256,
+            "filesize": 16,
+            "file_descriptors": 256,
+            "processes": 64,
+            "stdout_max_size": 65536,
+            "stderr_max_size": 65536,
+            "network": false,
+        },
+    });
+    json!({
+        "program_id": uuid::Uuid::nil(),
+        "ttl": 300,
+        "cached": false,
+        "build": null,
+        "run": result,
+    })
+}
+
+async fn handle(state: Arc<Mutex<MockState>>, mut req: Request) -> Response {
+    let body = req.take_body().into_bytes().await.unwrap_or_default();
+    let request = RecordedRequest {
+        method: req.method().clone(),
+        path: req.uri().path().into(),
+        body: serde_json::from_slice(&body).ok(),
+    };
+
+    let mut state = state.lock().unwrap();
+    let response = state
+        .routes
+        .iter()
+        .rev()
+        .find(|route| route.method == request.method && path_matches(&route.path, &request.path))
+        .map(|route| {
+            Response::builder()
+                .status(route.status)
+                .content_type("application/json")
+                .body(Body::from_json(&route.body).unwrap_or_else(|_| Body::empty()))
+        })
+        .unwrap_or_else(|| StatusCode::NOT_FOUND.into());
+    state.requests.push(request);
+    response
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_matches('/').split('/');
+    let path = path.trim_matches('/').split('/');
+    pattern.clone().count() == path.clone().count()
+        && pattern.zip(path).all(|(p, s)| p == "*" || p == s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_paths() {
+        assert!(path_matches("/_internal/skills", "/_internal/skills"));
+        assert!(path_matches("/_internal/skills/*", "/_internal/skills/abc"));
+        assert!(path_matches(
+            "/_internal/skills/*/*",
+            "/_internal/skills/a/b"
+        ));
+        assert!(!path_matches("/_internal/skills/*", "/_internal/skills"));
+        assert!(!path_matches(
+            "/_internal/skills/*",
+            "/_internal/skills/a/b"
+        ));
+        assert!(!path_matches("/_internal/coins/*", "/_internal/hearts/a"));
+    }
+}
@@ -0,0 +1,15 @@
+use poem::{http::header::AUTHORIZATION, test::TestRequestBuilder, Endpoint};
+
+use crate::TestUser;
+
+/// Helpers for requests sent via [`poem::test::TestClient`].
+pub trait RequestExt {
+    /// Authenticate the request as the given user.
+    fn auth(self, user: &TestUser) -> Self;
+}
+
+impl<E: Endpoint> RequestExt for TestRequestBuilder<'_, E> {
+    fn auth(self, user: &TestUser) -> Self {
+        self.header(AUTHORIZATION, format!("Bearer {}", user.token()))
+    }
+}